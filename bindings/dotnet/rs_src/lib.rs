@@ -127,6 +127,19 @@ pub unsafe extern "C" fn db_close(db_ptr: *mut Database) {
     let _ = unsafe { Box::from_raw(db_ptr) };
 }
 
+/// Interrupts the statements currently running on the database connection.
+/// Interrupted statements fail with an "Interrupted" error on their next step.
+///
+/// # Safety
+///
+/// - `db_ptr` must be a pointer returned by `db_open`.
+/// - Unlike other functions, this one may be called from another thread while a statement is stepping.
+#[no_mangle]
+pub unsafe extern "C" fn db_interrupt(db_ptr: *const Database) {
+    let db = unsafe { &*db_ptr };
+    db.connection.interrupt();
+}
+
 /// Frees a null-terminated string previously allocated by this library.
 ///
 /// # Safety
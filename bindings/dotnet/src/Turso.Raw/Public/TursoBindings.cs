@@ -19,6 +19,12 @@ public static class TursoBindings
         return TursoDatabaseHandle.FromPtr(dbPtr);
     }
 
+    public static void Interrupt(TursoDatabaseHandle db)
+    {
+        db.ThrowIfInvalid();
+        TursoInterop.InterruptDatabase(db);
+    }
+
     public static TursoStatementHandle PrepareStatement(TursoDatabaseHandle db, string sql)
     {
         db.ThrowIfInvalid();
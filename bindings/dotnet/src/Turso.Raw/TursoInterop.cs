@@ -14,6 +14,9 @@ internal static class TursoInterop
     [DllImport(DllName, EntryPoint = "db_close", CallingConvention = CallingConvention.Cdecl)]
     public static extern void CloseDatabase(IntPtr db);
 
+    [DllImport(DllName, EntryPoint = "db_interrupt", CallingConvention = CallingConvention.Cdecl)]
+    public static extern void InterruptDatabase(TursoDatabaseHandle db);
+
     [DllImport(DllName, EntryPoint = "free_string", CallingConvention = CallingConvention.Cdecl)]
     public static extern void FreeString(IntPtr stringPtr);
     
@@ -257,4 +257,40 @@ public class TursoTests
         results[1].Should().Be((2, "bob", 40));
         results[2].Should().Be((3, "charlie", 50));
     }
-}
\ No newline at end of file
+
+    [Test]
+    public async Task TestAsyncExecutionAndNulls()
+    {
+        using var connection = new TursoConnection();
+        connection.Open();
+
+        using var create = new TursoCommand(connection, "CREATE TABLE t(id INTEGER, name TEXT)");
+        (await create.ExecuteNonQueryAsync()).Should().Be(0);
+
+        using var insert = new TursoCommand(connection, "INSERT INTO t VALUES (1, 'alice'), (2, NULL)");
+        (await insert.ExecuteNonQueryAsync()).Should().Be(2);
+
+        using var select = new TursoCommand(connection, "SELECT name FROM t ORDER BY id");
+        await using var reader = await select.ExecuteReaderAsync();
+        (await reader.ReadAsync()).Should().BeTrue();
+        reader.IsDBNull(0).Should().BeFalse();
+        (await reader.ReadAsync()).Should().BeTrue();
+        reader.IsDBNull(0).Should().BeTrue();
+        (await reader.ReadAsync()).Should().BeFalse();
+    }
+
+    [Test]
+    public async Task TestAsyncExecutionCancellation()
+    {
+        using var connection = new TursoConnection();
+        connection.Open();
+
+        using var cmd = new TursoCommand(connection,
+            "SELECT count(*) FROM generate_series(1,1000) a, generate_series(1,1000) b, generate_series(1,1000) c");
+        using var cts = new CancellationTokenSource(TimeSpan.FromMilliseconds(50));
+        await cmd.Invoking(x => x.ExecuteScalarAsync(cts.Token)).Should().ThrowAsync<OperationCanceledException>();
+
+        using var next = new TursoCommand(connection, "SELECT 1");
+        (await next.ExecuteScalarAsync()).Should().Be(1L);
+    }
+}
@@ -64,6 +64,7 @@ public class TursoCommand : DbCommand
 
     public override void Cancel()
     {
+        TursoBindings.Interrupt(_connection.Turso);
     }
 
     public override int ExecuteNonQuery()
@@ -101,6 +102,39 @@ public class TursoCommand : DbCommand
         }
     }
 
+    public override Task<int> ExecuteNonQueryAsync(CancellationToken cancellationToken)
+    {
+        return Task.FromResult(WithCancellation(ExecuteNonQuery, cancellationToken));
+    }
+
+    public override Task<object?> ExecuteScalarAsync(CancellationToken cancellationToken)
+    {
+        return Task.FromResult(WithCancellation(ExecuteScalar, cancellationToken));
+    }
+
+    protected override Task<DbDataReader> ExecuteDbDataReaderAsync(CommandBehavior behavior,
+        CancellationToken cancellationToken)
+    {
+        return Task.FromResult(WithCancellation(() => Execute(behavior), cancellationToken));
+    }
+
+    /// <summary>
+    /// Runs <paramref name="action"/> and interrupts the statement if <paramref name="cancellationToken"/> is cancelled meanwhile.
+    /// </summary>
+    internal T WithCancellation<T>(Func<T> action, CancellationToken cancellationToken)
+    {
+        cancellationToken.ThrowIfCancellationRequested();
+        using var registration = cancellationToken.Register(Cancel);
+        try
+        {
+            return action();
+        }
+        catch (TursoException) when (cancellationToken.IsCancellationRequested)
+        {
+            throw new OperationCanceledException(cancellationToken);
+        }
+    }
+
     protected override DbParameter CreateDbParameter()
     {
         return new TursoParameter();
@@ -167,7 +167,8 @@ public class TursoDataReader : DbDataReader
 
     public override bool IsDBNull(int ordinal)
     {
-        throw new NotImplementedException();
+        var valueType = TursoBindings.GetValue(_statement, ordinal).ValueType;
+        return valueType is TursoValueType.Null or TursoValueType.Empty;
     }
 
     public override int FieldCount => TursoBindings.GetFieldCount(_statement);
@@ -204,6 +205,16 @@ public class TursoDataReader : DbDataReader
         return TursoBindings.Read(_statement);
     }
 
+    public override Task<bool> ReadAsync(CancellationToken cancellationToken)
+    {
+        return Task.FromResult(_command.WithCancellation(Read, cancellationToken));
+    }
+
+    public override Task<bool> NextResultAsync(CancellationToken cancellationToken)
+    {
+        return Task.FromResult(_command.WithCancellation(NextResult, cancellationToken));
+    }
+
     public override int Depth => 0;
 
     public override IEnumerator GetEnumerator()
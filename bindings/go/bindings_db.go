@@ -149,6 +149,7 @@ var (
 	c_turso_database_open                    func(database TursoDatabase, error_opt_out **byte) turso_status_code_t
 	c_turso_database_connect                 func(self TursoDatabase, connection **turso_connection_t, error_opt_out **byte) turso_status_code_t
 	c_turso_connection_get_autocommit        func(self TursoConnection) bool
+	c_turso_connection_interrupt             func(self TursoConnection)
 	c_turso_connection_set_busy_timeout_ms   func(self TursoConnection, timeout_ms int64)
 	c_turso_connection_last_insert_rowid     func(self TursoConnection) int64
 	c_turso_connection_prepare_single        func(self TursoConnection, sql string, statement **turso_statement_t, error_opt_out **byte) turso_status_code_t
@@ -188,6 +189,7 @@ func registerTursoDb(handle uintptr) error {
 	purego.RegisterLibFunc(&c_turso_database_open, handle, "turso_database_open")
 	purego.RegisterLibFunc(&c_turso_database_connect, handle, "turso_database_connect")
 	purego.RegisterLibFunc(&c_turso_connection_get_autocommit, handle, "turso_connection_get_autocommit")
+	purego.RegisterLibFunc(&c_turso_connection_interrupt, handle, "turso_connection_interrupt")
 	purego.RegisterLibFunc(&c_turso_connection_set_busy_timeout_ms, handle, "turso_connection_set_busy_timeout_ms")
 	purego.RegisterLibFunc(&c_turso_connection_last_insert_rowid, handle, "turso_connection_last_insert_rowid")
 	purego.RegisterLibFunc(&c_turso_connection_prepare_single, handle, "turso_connection_prepare_single")
@@ -417,6 +419,12 @@ func turso_connection_get_autocommit(self TursoConnection) bool {
 	return c_turso_connection_get_autocommit(self)
 }
 
+// turso_connection_interrupt interrupts statements currently executing on the connection.
+// Safe to call from another goroutine; interrupted statements fail with ErrTursoInterrupt.
+func turso_connection_interrupt(self TursoConnection) {
+	c_turso_connection_interrupt(self)
+}
+
 // turso_connection_set_busy_timeout_ms sets busy timeout for the connection
 func turso_connection_set_busy_timeout_ms(self TursoConnection, timeoutMs int64) {
 	c_turso_connection_set_busy_timeout_ms(self, timeoutMs)
@@ -50,6 +50,9 @@ type tursoDbRows struct {
 	conn    *tursoDbConnection
 	stmt    TursoStatement
 	columns []string
+	// ctx is the query context; its cancellation interrupts the statement
+	ctx       context.Context
+	stopWatch func() bool
 
 	closed bool
 	err    error
@@ -211,6 +214,9 @@ func (c *tursoDbConnection) ExecContext(ctx context.Context, query string, args
 	c.mu.Lock()
 	defer c.mu.Unlock()
 
+	stopWatch := c.interruptOnCancel(ctx)
+	defer stopWatch()
+
 	offset := 0
 	first := true
 	var lastInsert int64 = 0
@@ -243,7 +249,7 @@ func (c *tursoDbConnection) ExecContext(ctx context.Context, query string, args
 		_ = turso_statement_finalize(stmt)
 		turso_statement_deinit(stmt)
 		if err != nil {
-			return nil, err
+			return nil, contextError(ctx, err)
 		}
 		// rows affected is capped at MaxInt64
 		if affected > uint64(math.MaxInt64-totalAffected) {
@@ -284,11 +290,30 @@ func (c *tursoDbConnection) QueryContext(ctx context.Context, query string, args
 	}
 	// Return rows wrapper; do not step yet, leave cursor before first row
 	return &tursoDbRows{
-		conn: c,
-		stmt: stmt,
+		conn:      c,
+		stmt:      stmt,
+		ctx:       ctx,
+		stopWatch: c.interruptOnCancel(ctx),
 	}, nil
 }
 
+// interruptOnCancel interrupts the statements running on the connection once ctx is cancelled.
+// The returned function stops watching the context.
+func (c *tursoDbConnection) interruptOnCancel(ctx context.Context) func() bool {
+	conn := c.conn
+	return context.AfterFunc(ctx, func() {
+		turso_connection_interrupt(conn)
+	})
+}
+
+// contextError reports the context error instead of ErrTursoInterrupt when the interrupt was caused by ctx cancellation.
+func contextError(ctx context.Context, err error) error {
+	if errors.Is(err, ErrTursoInterrupt) && ctx.Err() != nil {
+		return ctx.Err()
+	}
+	return err
+}
+
 func (c *tursoDbConnection) checkOpen() error {
 	c.mu.Lock()
 	defer c.mu.Unlock()
@@ -369,6 +394,9 @@ func (r *tursoDbRows) Close() error {
 		return nil
 	}
 	r.closed = true
+	if r.stopWatch != nil {
+		r.stopWatch()
+	}
 	_ = turso_statement_finalize(r.stmt)
 	turso_statement_deinit(r.stmt)
 	return nil
@@ -381,6 +409,9 @@ func (r *tursoDbRows) Next(dest []driver.Value) error {
 	for {
 		status, err := turso_statement_step(r.stmt)
 		if err != nil {
+			if r.ctx != nil {
+				err = contextError(r.ctx, err)
+			}
 			r.err = err
 			return err
 		}
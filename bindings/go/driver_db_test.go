@@ -2,6 +2,7 @@ package turso
 
 import (
 	"bytes"
+	"context"
 	"database/sql"
 	"fmt"
 	"log"
@@ -1279,3 +1280,25 @@ func TestMultiStatementExecution(t *testing.T) {
 		}
 	})
 }
+
+func TestContextCancelInterruptsQuery(t *testing.T) {
+	db := openMem(t)
+	db.SetMaxOpenConns(1)
+	_, err := db.Exec("CREATE TABLE t(x); INSERT INTO t SELECT value FROM generate_series(1, 1000)")
+	require.Nil(t, err)
+
+	ctx, cancel := context.WithTimeout(context.Background(), 50*time.Millisecond)
+	defer cancel()
+	var count int64
+	err = db.QueryRowContext(ctx, "SELECT count(*) FROM t a, t b, t c").Scan(&count)
+	require.ErrorIs(t, err, context.DeadlineExceeded)
+
+	ctx, cancel = context.WithTimeout(context.Background(), 50*time.Millisecond)
+	defer cancel()
+	_, err = db.ExecContext(ctx, "INSERT INTO t SELECT a.x FROM t a, t b, t c")
+	require.ErrorIs(t, err, context.DeadlineExceeded)
+
+	// the connection stays usable and the interrupted insert left no rows behind
+	require.Nil(t, db.QueryRow("SELECT count(*) FROM t").Scan(&count))
+	require.Equal(t, int64(1000), count)
+}
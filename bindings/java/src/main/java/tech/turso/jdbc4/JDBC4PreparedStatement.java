@@ -22,11 +22,12 @@ import tech.turso.core.TursoResultSet;
 public final class JDBC4PreparedStatement extends JDBC4Statement implements PreparedStatement {
 
   private final String sql;
-  private final JDBC4ResultSet resultSet;
+  private JDBC4ResultSet resultSet;
 
   private final int paramCount;
   private Object[] currentBatchParams;
   private final ArrayList<Object[]> batchQueryParams = new ArrayList<>();
+  private boolean executed = false;
 
   /**
    * Creates a new JDBC4PreparedStatement.
@@ -47,6 +48,7 @@ public final class JDBC4PreparedStatement extends JDBC4Statement implements Prep
   @Override
   public ResultSet executeQuery() throws SQLException {
     // TODO: check bindings etc
+    resetIfExecuted();
     bindParams(currentBatchParams);
     return this.resultSet;
   }
@@ -54,6 +56,7 @@ public final class JDBC4PreparedStatement extends JDBC4Statement implements Prep
   @Override
   public int executeUpdate() throws SQLException {
     requireNonNull(this.statement);
+    resetIfExecuted();
     bindParams(currentBatchParams);
     final TursoResultSet resultSet = statement.getResultSet();
     resultSet.consumeAll();
@@ -268,7 +271,8 @@ public final class JDBC4PreparedStatement extends JDBC4Statement implements Prep
 
   @Override
   public void setObject(int parameterIndex, Object x, int targetSqlType) throws SQLException {
-    // TODO
+    // Values are stored with their dynamic type, so the target type is only a hint
+    setObject(parameterIndex, x);
   }
 
   @Override
@@ -318,9 +322,23 @@ public final class JDBC4PreparedStatement extends JDBC4Statement implements Prep
 
   @Override
   public boolean execute() throws SQLException {
+    resetIfExecuted();
     return execute(currentBatchParams);
   }
 
+  /**
+   * Resets the underlying statement if it already ran, so the prepared statement can be executed
+   * again with new parameters.
+   */
+  private void resetIfExecuted() throws SQLException {
+    requireNonNull(statement);
+    if (executed) {
+      statement.reset();
+      resultSet = new JDBC4ResultSet(statement.getResultSet(), this);
+    }
+    executed = true;
+  }
+
   /** This helper method runs the statement using the provided parameter values. */
   private boolean execute(Object[] params) throws SQLException {
     // TODO: check whether this is sufficient
@@ -358,6 +376,7 @@ public final class JDBC4PreparedStatement extends JDBC4Statement implements Prep
     for (int i = 0; i < batchQueryParams.size(); i++) {
       try {
         statement.reset();
+        executed = true;
         execute(batchQueryParams.get(i));
         updateCounts[i] = getUpdateCount();
       } catch (SQLException e) {
@@ -468,7 +487,7 @@ public final class JDBC4PreparedStatement extends JDBC4Statement implements Prep
 
   @Override
   public void setNull(int parameterIndex, int sqlType, String typeName) throws SQLException {
-    // TODO
+    setNull(parameterIndex, sqlType);
   }
 
   @Override
@@ -490,7 +509,7 @@ public final class JDBC4PreparedStatement extends JDBC4Statement implements Prep
 
   @Override
   public void setNString(int parameterIndex, String value) throws SQLException {
-    // TODO
+    setString(parameterIndex, value);
   }
 
   @Override
@@ -528,7 +547,7 @@ public final class JDBC4PreparedStatement extends JDBC4Statement implements Prep
   @Override
   public void setObject(int parameterIndex, Object x, int targetSqlType, int scaleOrLength)
       throws SQLException {
-    // TODO
+    setObject(parameterIndex, x);
   }
 
   @Override
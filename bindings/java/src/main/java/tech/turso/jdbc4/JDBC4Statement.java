@@ -42,6 +42,9 @@ public class JDBC4Statement implements Statement {
   /** The number of rows affected by the last update operation. */
   protected long updateCount;
 
+  /** The statement backing the result set returned by {@link #getGeneratedKeys()}. */
+  @Nullable private TursoStatement generatedKeysStatement = null;
+
   // Because JDBC4Statement has different life cycle in compared to tursoStatement, let's use this
   // field to manage JDBC4Statement lifecycle
   private boolean closed;
@@ -121,6 +124,9 @@ public class JDBC4Statement implements Statement {
     if (this.statement != null) {
       this.statement.close();
     }
+    if (this.generatedKeysStatement != null) {
+      this.generatedKeysStatement.close();
+    }
 
     closed = true;
   }
@@ -362,11 +368,18 @@ public class JDBC4Statement implements Statement {
     return false;
   }
 
+  /**
+   * Returns the rowid of the last row inserted on this connection as a single row result set with a
+   * {@code last_insert_rowid()} column, which is how sqlite-jdbc exposes generated keys.
+   */
   @Override
-  @SkipNullableCheck
   public ResultSet getGeneratedKeys() throws SQLException {
-    // TODO
-    return null;
+    ensureOpen();
+    if (generatedKeysStatement != null) {
+      generatedKeysStatement.close();
+    }
+    generatedKeysStatement = connection.prepare("SELECT last_insert_rowid()");
+    return new JDBC4ResultSet(generatedKeysStatement.getResultSet(), this);
   }
 
   @Override
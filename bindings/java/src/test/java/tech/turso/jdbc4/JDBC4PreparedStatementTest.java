@@ -1202,4 +1202,36 @@ class JDBC4PreparedStatementTest {
     preparedStatement.addBatch();
     assertThrows(BatchUpdateException.class, preparedStatement::executeBatch);
   }
+
+  @Test
+  void testGeneratedKeysAndTypedSetters() throws Exception {
+    connection
+        .prepareStatement("CREATE TABLE test (id INTEGER PRIMARY KEY, name TEXT, note TEXT)")
+        .execute();
+    PreparedStatement insert =
+        connection.prepareStatement(
+            "INSERT INTO test (name, note) VALUES (?, ?)", Statement.RETURN_GENERATED_KEYS);
+    insert.setNString(1, "turso");
+    insert.setNull(2, Types.VARCHAR, "TEXT");
+    assertEquals(1, insert.executeUpdate());
+    ResultSet keys = insert.getGeneratedKeys();
+    assertTrue(keys.next());
+    assertEquals(1L, keys.getLong(1));
+
+    insert.setObject(1, "limbo", Types.VARCHAR);
+    insert.setObject(2, "note", Types.VARCHAR, 0);
+    assertEquals(1, insert.executeUpdate());
+    keys = insert.getGeneratedKeys();
+    assertTrue(keys.next());
+    assertEquals(2L, keys.getLong(1));
+
+    ResultSet rs = connection.prepareStatement("SELECT name, note FROM test").executeQuery();
+    assertTrue(rs.next());
+    assertEquals("turso", rs.getString(1));
+    assertNull(rs.getString(2));
+    assertTrue(rs.next());
+    assertEquals("limbo", rs.getString(1));
+    assertEquals("note", rs.getString(2));
+    assertFalse(rs.next());
+  }
 }
@@ -1,6 +1,6 @@
 import { bindParams } from "./bind.js";
 import { SqliteError } from "./sqlite-error.js";
-import { NativeDatabase, NativeStatement, STEP_IO, STEP_ROW, STEP_DONE } from "./types.js";
+import { NativeDatabase, NativeStatement, RunResult, STEP_IO, STEP_ROW, STEP_DONE } from "./types.js";
 
 const convertibleErrorTypes = { TypeError };
 const CONVERTIBLE_ERROR_PREFIX = "[TURSO_CONVERT_TYPE]";
@@ -36,7 +36,6 @@ class Database {
   inTransaction: boolean;
 
   private db: NativeDatabase;
-  private _inTransaction: boolean = false;
 
   /**
    * Creates a new database connection. If the database file pointed to by `path` does not exists, it will be created.
@@ -57,7 +56,7 @@ class Database {
       readonly: { get: () => this.db.readonly },
       open: { get: () => this.db.open },
       memory: { get: () => this.db.memory },
-      inTransaction: { get: () => this._inTransaction },
+      inTransaction: { get: () => this.db.inTransaction },
     });
   }
 
@@ -90,16 +89,17 @@ class Database {
     const db = this;
     const wrapTxn = (mode) => {
       return (...bindParameters) => {
+        // SAVEPOINT is not supported yet, so nested transaction functions join the enclosing transaction
+        if (db.inTransaction) {
+          return fn(...bindParameters);
+        }
         db.exec("BEGIN " + mode);
-        db._inTransaction = true;
         try {
           const result = fn(...bindParameters);
           db.exec("COMMIT");
-          db._inTransaction = false;
           return result;
         } catch (err) {
           db.exec("ROLLBACK");
-          db._inTransaction = false;
           throw err;
         }
       };
@@ -131,6 +131,9 @@ class Database {
 
     const stmt = this.prepare(pragma);
     try {
+      if (options.simple) {
+        return stmt.pluck().get();
+      }
       const results = stmt.all();
       return results;
     } finally {
@@ -284,7 +287,7 @@ class Statement {
   /**
    * Executes the SQL statement and returns an info object.
    */
-  run(...bindParameters) {
+  run(...bindParameters): RunResult {
     const totalChangesBefore = this.db.totalChanges();
 
     this.stmt.reset();
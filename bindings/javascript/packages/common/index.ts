@@ -1,11 +1,11 @@
-import { NativeDatabase, NativeStatement, DatabaseOpts } from "./types.js";
+import { NativeDatabase, NativeStatement, DatabaseOpts, SqliteValue, RunResult } from "./types.js";
 import { Database as DatabaseCompat, Statement as StatementCompat } from "./compat.js";
 import { Database as DatabasePromise, Statement as StatementPromise } from "./promise.js";
 import { SqliteError } from "./sqlite-error.js";
 import { AsyncLock } from "./async-lock.js";
 
 export {
-    DatabaseOpts,
+    DatabaseOpts, SqliteValue, RunResult,
     DatabaseCompat, StatementCompat,
     DatabasePromise, StatementPromise,
     NativeDatabase, NativeStatement,
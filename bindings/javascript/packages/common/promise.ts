@@ -1,7 +1,7 @@
 import { AsyncLock } from "./async-lock.js";
 import { bindParams } from "./bind.js";
 import { SqliteError } from "./sqlite-error.js";
-import { NativeDatabase, NativeStatement, STEP_IO, STEP_ROW, STEP_DONE, DatabaseOpts } from "./types.js";
+import { NativeDatabase, NativeStatement, STEP_IO, STEP_ROW, STEP_DONE, DatabaseOpts, RunResult } from "./types.js";
 
 const convertibleErrorTypes = { TypeError };
 const CONVERTIBLE_ERROR_PREFIX = "[TURSO_CONVERT_TYPE]";
@@ -39,7 +39,6 @@ class Database {
   private db: NativeDatabase;
   private ioStep: () => Promise<void>;
   private execLock: AsyncLock;
-  private _inTransaction: boolean = false;
   protected connected: boolean = false;
 
   constructor(db: NativeDatabase, ioStep?: () => Promise<void>) {
@@ -51,7 +50,7 @@ class Database {
       readonly: { get: () => this.db.readonly },
       open: { get: () => this.db.open },
       memory: { get: () => this.db.memory },
-      inTransaction: { get: () => this._inTransaction },
+      inTransaction: { get: () => this.db.inTransaction },
     });
   }
 
@@ -97,16 +96,17 @@ class Database {
     const db = this;
     const wrapTxn = (mode) => {
       return async (...bindParameters) => {
+        // SAVEPOINT is not supported yet, so nested transaction functions join the enclosing transaction
+        if (db.inTransaction) {
+          return await fn(...bindParameters);
+        }
         await db.exec("BEGIN " + mode);
-        db._inTransaction = true;
         try {
           const result = await fn(...bindParameters);
           await db.exec("COMMIT");
-          db._inTransaction = false;
           return result;
         } catch (err) {
           await db.exec("ROLLBACK");
-          db._inTransaction = false;
           throw err;
         }
       };
@@ -138,6 +138,9 @@ class Database {
 
     const stmt = this.prepare(pragma);
     try {
+      if (options.simple) {
+        return await stmt.pluck().get();
+      }
       const results = await stmt.all();
       return results;
     } finally {
@@ -346,7 +349,7 @@ class Statement {
   /**
    * Executes the SQL statement and returns an info object.
    */
-  async run(...bindParameters) {
+  async run(...bindParameters): Promise<RunResult> {
     let stmt = await this.stmt.resolve();
 
     bindParams(stmt, bindParameters);
@@ -5,11 +5,24 @@ export interface DatabaseOpts {
     tracing?: 'info' | 'debug' | 'trace'
 }
 
+/**
+ * A value which can be bound to a statement parameter or read from a row column.
+ * Integers outside of the safe range are returned as `bigint` when safe integers mode is enabled.
+ */
+export type SqliteValue = null | number | bigint | string | Uint8Array;
+
+/** Result of the `Statement.run` method. */
+export interface RunResult {
+    changes: number,
+    lastInsertRowid: number
+}
+
 export interface NativeDatabase {
     memory: boolean,
     path: string,
     readonly: boolean;
     open: boolean;
+    inTransaction: boolean;
     new(path: string): NativeDatabase;
 
     connectSync();
@@ -109,4 +109,32 @@ test('blobs', () => {
     const db = new Database(":memory:");
     const rows = db.prepare("SELECT x'1020' as x").all();
     expect(rows).toEqual([{ x: Buffer.from([16, 32]) }])
-})
\ No newline at end of file
+})
+test('transactions', () => {
+    const db = new Database(":memory:");
+    db.exec("CREATE TABLE t(x)");
+    const insert = db.prepare("INSERT INTO t VALUES (?)");
+    const insertMany = db.transaction((values) => {
+        expect(db.inTransaction).toBe(true);
+        for (const value of values) insert.run(value);
+    });
+    const insertTwice = db.transaction((values) => {
+        insertMany(values);
+        insertMany(values);
+    });
+    insertTwice.immediate([1, 2]);
+    expect(db.inTransaction).toBe(false);
+    const failing = db.transaction(() => {
+        insert.run(3);
+        throw new Error("boom");
+    });
+    expect(() => failing()).toThrow("boom");
+    expect(db.inTransaction).toBe(false);
+    expect(db.pragma("page_count", { simple: true })).toBeGreaterThan(0);
+    expect(db.prepare("SELECT x FROM t").pluck().all()).toEqual([1, 2, 1, 2]);
+
+    db.exec("BEGIN");
+    expect(db.inTransaction).toBe(true);
+    db.exec("ROLLBACK");
+    expect(db.inTransaction).toBe(false);
+})
@@ -32,6 +32,8 @@ export declare class Database {
   get path(): string
   /** Returns whether the database connection is open. */
   get open(): boolean
+  /** Returns whether the connection has an open transaction. */
+  get inTransaction(): boolean
   /**
    * Prepares a statement for execution.
    *
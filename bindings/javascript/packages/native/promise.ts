@@ -21,3 +21,4 @@ async function connect(path: string, opts: DatabaseOpts = {}): Promise<Database>
 }
 
 export { connect, Database, SqliteError }
+export type { SqliteValue, RunResult } from "@tursodatabase/database-common"
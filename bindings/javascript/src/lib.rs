@@ -314,6 +314,15 @@ impl Database {
         Ok(self.inner()?.connect.get().is_some())
     }
 
+    /// Returns whether the connection has an open transaction.
+    #[napi(getter)]
+    pub fn in_transaction(&self) -> napi::Result<bool> {
+        if !self.open()? {
+            return Ok(false);
+        }
+        Ok(!self.conn()?.get_auto_commit())
+    }
+
     /// Prepares a statement for execution.
     ///
     /// # Arguments
@@ -1,6 +1,6 @@
 use pyo3::{
     prelude::*,
-    types::{PyBytes, PyTuple},
+    types::{PyBytes, PyDict, PyTuple},
 };
 use std::sync::Arc;
 use turso_sdk_kit::rsapi::{self, EncryptionOpts, TursoError, TursoStatusCode, Value, ValueRef};
@@ -272,6 +272,29 @@ impl PyTursoStatement {
         Ok(())
     }
 
+    /// binds named parameters (`:name`, `@name` or `$name`) to the statement
+    /// Keys may be given with or without the control character; keys which do not match any parameter are ignored
+    pub fn bind_named(&mut self, parameters: Bound<PyDict>) -> PyResult<()> {
+        for (key, value) in parameters.iter() {
+            let key = key.extract::<String>()?;
+            let candidates = if key.starts_with([':', '@', '$']) {
+                vec![key]
+            } else {
+                vec![format!(":{key}"), format!("@{key}"), format!("${key}")]
+            };
+            let Some(index) = candidates
+                .iter()
+                .find_map(|name| self.statement.named_position(name).ok())
+            else {
+                continue;
+            };
+            self.statement
+                .bind_positional(index, py_to_db_value(value)?)
+                .map_err(turso_error_to_py_err)?;
+        }
+        Ok(())
+    }
+
     /// step one iteration of the statement execution
     /// Returns [PyTursoStatusCode::Done] when execution is finished
     /// Returns [PyTursoStatusCode::Row] when execution generated a row which can be consumed with [Self::row] method
@@ -437,6 +437,24 @@ def test_executemany_with_parameters(provider):
     conn.close()
 
 
+@pytest.mark.parametrize("provider", ["sqlite3", "turso"])
+def test_named_parameters(provider):
+    conn = connect(provider, ":memory:")
+    cursor = conn.cursor()
+    cursor.execute("CREATE TABLE test (id INTEGER, name TEXT)")
+
+    cursor.execute("INSERT INTO test VALUES (:id, :name)", {"id": 1, "name": "alice"})
+    cursor.executemany(
+        "INSERT INTO test VALUES (:id, :name)",
+        [{"name": "bob", "id": 2}, {"name": "charlie", "id": 3}],
+    )
+
+    cursor.execute("SELECT name FROM test WHERE id >= :min AND id <= :max ORDER BY id", {"min": 2, "max": 3})
+    assert cursor.fetchall() == [("bob",), ("charlie",)]
+
+    conn.close()
+
+
 # SQL tests
 
 
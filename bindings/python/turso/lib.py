@@ -35,7 +35,7 @@ from ._turso import (
 # DB-API 2.0 module attributes
 apilevel = "2.0"
 threadsafety = 1  # 1 means: Threads may share the module, but not connections.
-paramstyle = "qmark"  # Only positional parameters are supported.
+paramstyle = "qmark"  # Named parameters (:name, @name, $name) are accepted as a mapping.
 
 
 # Exception hierarchy following DB-API 2.0
@@ -522,16 +522,18 @@ class Cursor:
         # Do not reset lastrowid here; sqlite3 preserves lastrowid until next insert.
 
     @staticmethod
-    def _to_positional_params(parameters: Sequence[Any] | Mapping[str, Any]) -> tuple[Any, ...]:
-        if isinstance(parameters, Mapping):
-            # Named placeholders are not supported
-            raise ProgrammingError("Named parameters are not supported; use positional parameters with '?'")
+    def _bind_params(stmt: PyTursoStatement, parameters: Sequence[Any] | Mapping[str, Any] | None) -> None:
         if parameters is None:
-            return ()
-        if isinstance(parameters, tuple):
-            return parameters
+            return
+        if isinstance(parameters, Mapping):
+            # Named placeholders (:name, @name, $name) are bound by key, like sqlite3
+            if parameters:
+                stmt.bind_named(dict(parameters))
+            return
         # Convert arbitrary sequences to tuple efficiently
-        return tuple(parameters)
+        params = parameters if isinstance(parameters, tuple) else tuple(parameters)
+        if params:
+            stmt.bind(params)
 
     def _maybe_implicit_begin(self, sql: str) -> None:
         self._connection._maybe_implicit_begin(sql)
@@ -554,10 +556,8 @@ class Cursor:
 
         stmt = prepared.stmt
         try:
-            # Bind positional parameters
-            params = self._to_positional_params(parameters)
-            if params:
-                stmt.bind(params)
+            # Bind positional or named parameters
+            self._bind_params(stmt, parameters)
 
             if prepared.has_columns:
                 # Stepped statement (e.g., SELECT or DML with RETURNING)
@@ -637,9 +637,7 @@ class Cursor:
             for parameters in seq_of_parameters:
                 # Reset previous bindings and program memory before reusing
                 stmt.reset()
-                params = self._to_positional_params(parameters)
-                if params:
-                    stmt.bind(params)
+                self._bind_params(stmt, parameters)
                 result = _run_execute_with_io(stmt, self._connection.extra_io)
                 # rowcount is "the number of modified rows" for the LAST executed statement only
                 self._rowcount = int(result.rows_changed) + (self._rowcount if self._rowcount != -1 else 0)
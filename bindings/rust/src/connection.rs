@@ -118,6 +118,26 @@ impl Connection {
         stmt.execute(params).await
     }
 
+    /// Execute a query that returns a single row and map it with `f`.
+    ///
+    /// # Errors
+    ///
+    /// - Returns `QueryReturnedNoRows` if no rows were returned.
+    pub async fn query_row<T, F>(
+        &self,
+        sql: impl AsRef<str>,
+        params: impl IntoParams,
+        f: F,
+    ) -> Result<T>
+    where
+        F: FnOnce(&Row) -> Result<T>,
+    {
+        self.maybe_handle_dangling_tx().await?;
+        let mut stmt = self.prepare(sql).await?;
+        let row = stmt.query_row(params).await?;
+        f(&row)
+    }
+
     /// get the inner connection
     fn get_inner_connection(&self) -> Result<Arc<turso_sdk_kit::rsapi::TursoConnection>> {
         match &self.inner {
@@ -37,7 +37,10 @@
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
 pub mod connection;
+#[cfg(feature = "serde")]
+mod de;
 pub mod params;
+pub mod pool;
 mod rows;
 pub mod transaction;
 pub mod value;
@@ -46,7 +49,8 @@ pub mod value;
 pub mod sync;
 
 pub use connection::Connection;
-pub use value::Value;
+pub use pool::{ConnectionPool, PooledConnection};
+pub use value::{CArray, Value};
 
 pub use params::params_from_iter;
 pub use params::IntoParams;
@@ -336,7 +340,10 @@ impl Statement {
                 .into_owned();
             cols.push(Column {
                 name,
-                decl_type: None, // TODO
+                decl_type: stmt
+                    .column_decltype(i)
+                    .expect("column index must be within valid range")
+                    .map(|decl_type| decl_type.into_owned()),
             });
         }
 
@@ -364,6 +371,21 @@ impl Statement {
         while rows.next().await?.is_some() {}
         Ok(first_row)
     }
+
+    /// Execute a query and map every returned [`Row`] with `f`, collecting the results.
+    ///
+    /// This mirrors `rusqlite::Statement::query_map`, except that rows are collected eagerly.
+    pub async fn query_map<T, F>(&mut self, params: impl IntoParams, mut f: F) -> Result<Vec<T>>
+    where
+        F: FnMut(&Row) -> Result<T>,
+    {
+        let mut rows = self.query(params).await?;
+        let mut mapped = Vec::new();
+        while let Some(row) = rows.next().await? {
+            mapped.push(f(&row)?);
+        }
+        Ok(mapped)
+    }
 }
 
 /// Column information.
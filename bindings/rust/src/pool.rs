@@ -0,0 +1,110 @@
+//! A pool of reusable connections for multi-threaded hosts.
+//!
+//! [`Connection`] is `Send + Sync`, so a single connection can already be shared between
+//! threads, but every statement on it is serialized. A [`ConnectionPool`] hands out a separate
+//! connection to each caller and keeps connections around after they are released so that
+//! request handlers do not pay the cost of opening a new connection every time.
+//!
+//! ```rust,no_run
+//! # async fn run() {
+//! use turso::{Builder, ConnectionPool};
+//!
+//! let db = Builder::new_local("app.db").build().await.unwrap();
+//! let pool = ConnectionPool::new(db, 8);
+//! let conn = pool.get().unwrap();
+//! conn.execute("INSERT INTO users (email) VALUES ('alice@example.org')", ()).await.unwrap();
+//! // `conn` goes back to the pool when dropped.
+//! # }
+//! ```
+
+use crate::{assert_send_sync, Connection, Database, Result};
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+
+/// A pool of connections to a single [`Database`].
+#[derive(Clone)]
+pub struct ConnectionPool {
+    inner: Arc<PoolInner>,
+}
+
+struct PoolInner {
+    db: Database,
+    idle: Mutex<Vec<Connection>>,
+    max_idle: usize,
+}
+
+assert_send_sync!(ConnectionPool);
+
+impl ConnectionPool {
+    /// Create a pool that keeps at most `max_idle` released connections open for reuse.
+    pub fn new(db: Database, max_idle: usize) -> Self {
+        Self {
+            inner: Arc::new(PoolInner {
+                db,
+                idle: Mutex::new(Vec::with_capacity(max_idle)),
+                max_idle,
+            }),
+        }
+    }
+
+    /// Take an idle connection from the pool, or open a new one if none is available.
+    pub fn get(&self) -> Result<PooledConnection> {
+        let conn = self.inner.idle.lock().unwrap().pop();
+        let conn = match conn {
+            Some(conn) => conn,
+            None => self.inner.db.connect()?,
+        };
+        Ok(PooledConnection {
+            conn: Some(conn),
+            pool: self.inner.clone(),
+        })
+    }
+
+    /// Number of connections currently waiting in the pool.
+    pub fn idle_connections(&self) -> usize {
+        self.inner.idle.lock().unwrap().len()
+    }
+}
+
+impl std::fmt::Debug for ConnectionPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConnectionPool")
+            .field("max_idle", &self.inner.max_idle)
+            .finish()
+    }
+}
+
+/// A [`Connection`] borrowed from a [`ConnectionPool`].
+///
+/// The connection is returned to the pool when this value is dropped. Connections that are
+/// still inside a transaction are closed instead, so that the next user starts from a clean
+/// state.
+pub struct PooledConnection {
+    conn: Option<Connection>,
+    pool: Arc<PoolInner>,
+}
+
+assert_send_sync!(PooledConnection);
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("connection already released")
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        let Some(conn) = self.conn.take() else {
+            return;
+        };
+        if !matches!(conn.is_autocommit(), Ok(true)) {
+            return;
+        }
+        let mut idle = self.pool.idle.lock().unwrap();
+        if idle.len() < self.pool.max_idle {
+            idle.push(conn);
+        }
+    }
+}
@@ -66,6 +66,32 @@ impl Row {
         T::from_sql(val.clone()).map_err(|err| Error::ConversionFailure(err.to_string()))
     }
 
+    /// Deserializes the row into `T`, matching the columns with the fields of `T` by position.
+    ///
+    /// ```rust,no_run
+    /// # async fn run() {
+    /// # use turso::Builder;
+    /// # let db = Builder::new_local(":memory:").build().await.unwrap();
+    /// # let conn = db.connect().unwrap();
+    /// #[derive(serde::Deserialize)]
+    /// struct User {
+    ///     id: i64,
+    ///     email: Option<String>,
+    /// }
+    ///
+    /// let mut rows = conn.query("SELECT id, email FROM users", ()).await.unwrap();
+    /// let row = rows.next().await.unwrap().unwrap();
+    /// let user: User = row.deserialize().unwrap();
+    /// # }
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn deserialize<T>(&self) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        T::deserialize(crate::de::RowDeserializer::new(&self.values))
+    }
+
     pub fn column_count(&self) -> usize {
         self.values.len()
     }
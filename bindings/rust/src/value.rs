@@ -2,6 +2,19 @@ use std::str::FromStr;
 
 use crate::{Error, Result};
 
+/// An array bound as a single parameter, whose elements are read back as rows by the
+/// `carray` table-valued function:
+///
+/// ```rust,no_run
+/// # async fn run(conn: turso::Connection) {
+/// use turso::CArray;
+///
+/// let ids = CArray::from(vec![1, 2, 3]);
+/// let mut rows = conn.query("SELECT * FROM users WHERE id IN carray(?1)", [ids]).await.unwrap();
+/// # }
+/// ```
+pub use turso_sdk_kit::rsapi::CArray;
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Value {
     Null,
@@ -134,6 +147,12 @@ impl From<Value> for turso_sdk_kit::rsapi::Value {
     }
 }
 
+impl From<CArray> for Value {
+    fn from(array: CArray) -> Value {
+        Value::Blob(array.to_blob())
+    }
+}
+
 impl From<i8> for Value {
     fn from(value: i8) -> Value {
         Value::Integer(value as i64)
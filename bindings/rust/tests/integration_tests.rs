@@ -1,5 +1,5 @@
 use tokio::fs;
-use turso::{Builder, EncryptionOpts, Error, Value};
+use turso::{Builder, CArray, ConnectionPool, EncryptionOpts, Error, Value};
 
 #[tokio::test]
 async fn test_rows_next() {
@@ -217,6 +217,40 @@ pub async fn test_execute_batch() {
     }
 }
 
+#[tokio::test]
+async fn test_carray_parameter() {
+    let db = Builder::new_local(":memory:").build().await.unwrap();
+    let conn = db.connect().unwrap();
+    conn.execute_batch(
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);
+         INSERT INTO users VALUES (1, 'Frodo'), (2, 'Sam'), (3, 'Merry'), (4, 'Pippin');",
+    )
+    .await
+    .unwrap();
+
+    let mut rows = conn
+        .query(
+            "SELECT name FROM users WHERE id IN carray(?1) ORDER BY id",
+            [CArray::from(vec![4, 2, 42])],
+        )
+        .await
+        .unwrap();
+    let mut names = Vec::new();
+    while let Some(row) = rows.next().await.unwrap() {
+        names.push(row.get::<String>(0).unwrap());
+    }
+    assert_eq!(names, ["Sam", "Pippin"]);
+
+    let row = conn
+        .prepare("SELECT count(*) FROM users WHERE name NOT IN carray(?1)")
+        .await
+        .unwrap()
+        .query_row([CArray::from(vec!["Frodo", "Sam"])])
+        .await
+        .unwrap();
+    assert_eq!(row.get::<i64>(0).unwrap(), 2);
+}
+
 #[tokio::test]
 async fn test_query_row_returns_first_row() {
     let db = Builder::new_local(":memory:").build().await.unwrap();
@@ -242,6 +276,49 @@ async fn test_query_row_returns_first_row() {
     assert_eq!(id, 1);
 }
 
+#[tokio::test]
+async fn test_query_map_and_connection_query_row() {
+    let db = Builder::new_local(":memory:").build().await.unwrap();
+    let conn = db.connect().unwrap();
+
+    conn.execute("CREATE TABLE users (id INTEGER, name TEXT)", ())
+        .await
+        .unwrap();
+    conn.execute(
+        "INSERT INTO users VALUES (1, 'Frodo'), (2, 'Sam'), (3, 'Pippin')",
+        (),
+    )
+    .await
+    .unwrap();
+
+    let names = conn
+        .prepare("SELECT id, name FROM users WHERE id >= ? ORDER BY id")
+        .await
+        .unwrap()
+        .query_map(turso::params![2], |row| {
+            Ok((row.get::<i64>(0)?, row.get::<String>(1)?))
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        names,
+        vec![(2, "Sam".to_string()), (3, "Pippin".to_string())]
+    );
+
+    let count: i64 = conn
+        .query_row("SELECT count(*) FROM users", (), |row| row.get(0))
+        .await
+        .unwrap();
+    assert_eq!(count, 3);
+
+    let result = conn
+        .query_row("SELECT id FROM users WHERE name = ?", ["Ghost"], |row| {
+            row.get::<i64>(0)
+        })
+        .await;
+    assert!(matches!(result, Err(Error::QueryReturnedNoRows)));
+}
+
 #[tokio::test]
 async fn test_query_row_returns_no_rows_error() {
     let db = Builder::new_local(":memory:").build().await.unwrap();
@@ -303,6 +380,116 @@ async fn test_row_get_conversion_error() {
     assert!(matches!(result, Err(Error::ConversionFailure(_))));
 }
 
+#[tokio::test]
+async fn test_row_get_type_mismatch() {
+    let db = Builder::new_local(":memory:").build().await.unwrap();
+    let conn = db.connect().unwrap();
+
+    let mut rows = conn
+        .query("SELECT 'text', 3000000000, 2, x'0102', NULL", ())
+        .await
+        .unwrap();
+    let row = rows.next().await.unwrap().unwrap();
+
+    assert!(matches!(
+        row.get::<i64>(0),
+        Err(Error::ConversionFailure(_))
+    ));
+    assert!(matches!(
+        row.get::<Vec<u8>>(0),
+        Err(Error::ConversionFailure(_))
+    ));
+    assert!(matches!(
+        row.get::<i32>(1),
+        Err(Error::ConversionFailure(_))
+    ));
+    assert_eq!(row.get::<i64>(1).unwrap(), 3_000_000_000);
+    assert_eq!(row.get::<f64>(2).unwrap(), 2.0);
+    assert!(matches!(
+        row.get::<String>(2),
+        Err(Error::ConversionFailure(_))
+    ));
+    assert_eq!(row.get::<Vec<u8>>(3).unwrap(), vec![1, 2]);
+    assert_eq!(row.get::<Option<String>>(4).unwrap(), None);
+    assert_eq!(
+        row.get::<Option<String>>(0).unwrap(),
+        Some("text".to_string())
+    );
+}
+
+#[cfg(feature = "serde")]
+#[tokio::test]
+async fn test_row_deserialize() {
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Item {
+        id: i64,
+        name: String,
+        price: f64,
+        data: Vec<u8>,
+        note: Option<String>,
+        active: bool,
+    }
+
+    let db = Builder::new_local(":memory:").build().await.unwrap();
+    let conn = db.connect().unwrap();
+    conn.execute(
+        "CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT, price REAL, data BLOB, note TEXT, active INTEGER)",
+        (),
+    )
+    .await
+    .unwrap();
+    conn.execute(
+        "INSERT INTO items VALUES (1, 'apple', 1.5, x'ff00', NULL, 1), (2, 'pear', 2, x'', 'ripe', 0)",
+        (),
+    )
+    .await
+    .unwrap();
+
+    let mut rows = conn
+        .query("SELECT * FROM items ORDER BY id", ())
+        .await
+        .unwrap();
+    let mut items = Vec::new();
+    while let Some(row) = rows.next().await.unwrap() {
+        items.push(row.deserialize::<Item>().unwrap());
+    }
+    assert_eq!(
+        items,
+        vec![
+            Item {
+                id: 1,
+                name: "apple".to_string(),
+                price: 1.5,
+                data: vec![0xff, 0x00],
+                note: None,
+                active: true,
+            },
+            Item {
+                id: 2,
+                name: "pear".to_string(),
+                price: 2.0,
+                data: vec![],
+                note: Some("ripe".to_string()),
+                active: false,
+            },
+        ]
+    );
+
+    let mut rows = conn.query("SELECT id, name FROM items", ()).await.unwrap();
+    let row = rows.next().await.unwrap().unwrap();
+    let tuple: (i64, String) = row.deserialize().unwrap();
+    assert_eq!(tuple, (1, "apple".to_string()));
+    // Too few columns for the struct and mismatched types are conversion failures.
+    assert!(matches!(
+        row.deserialize::<Item>(),
+        Err(Error::ConversionFailure(_))
+    ));
+    assert!(matches!(
+        row.deserialize::<(String, String)>(),
+        Err(Error::ConversionFailure(_))
+    ));
+}
+
 #[tokio::test]
 async fn test_index() {
     let db = Builder::new_local(":memory:").build().await.unwrap();
@@ -747,3 +934,45 @@ async fn test_transaction_with_insert_returning_then_commit() {
     let count: i64 = count_rows.next().await.unwrap().unwrap().get(0).unwrap();
     assert_eq!(count, 3, "All rows should be committed");
 }
+
+#[tokio::test]
+async fn test_connection_pool_reuses_connections() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let db_path = temp_dir.path().join("pool.db");
+    let db = Builder::new_local(db_path.to_str().unwrap())
+        .build()
+        .await
+        .unwrap();
+    let pool = ConnectionPool::new(db, 1);
+
+    {
+        let conn = pool.get().unwrap();
+        conn.execute("CREATE TABLE t (x INTEGER)", ())
+            .await
+            .unwrap();
+        conn.execute("INSERT INTO t VALUES (1)", ()).await.unwrap();
+    }
+    assert_eq!(pool.idle_connections(), 1);
+
+    let handles = (0..4)
+        .map(|_| {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                let conn = pool.get().unwrap();
+                conn.query_row("SELECT count(*) FROM t", (), |row| row.get::<i64>(0))
+                    .await
+                    .unwrap()
+            })
+        })
+        .collect::<Vec<_>>();
+    for handle in handles {
+        assert_eq!(handle.await.unwrap(), 1);
+    }
+    assert_eq!(pool.idle_connections(), 1);
+
+    {
+        let conn = pool.get().unwrap();
+        conn.execute("BEGIN", ()).await.unwrap();
+    }
+    assert_eq!(pool.idle_connections(), 0);
+}
@@ -0,0 +1,44 @@
+use sqlx_core::arguments::Arguments;
+use sqlx_core::encode::{Encode, IsNull};
+use sqlx_core::error::BoxDynError;
+use sqlx_core::types::Type;
+use turso_core::Value;
+
+use crate::Turso;
+
+/// Arguments bound to the positional parameters of a query.
+#[derive(Debug, Default, Clone)]
+pub struct TursoArguments {
+    pub(crate) values: Vec<Value>,
+}
+
+impl<'q> Arguments<'q> for TursoArguments {
+    type Database = Turso;
+
+    fn reserve(&mut self, additional: usize, _size: usize) {
+        self.values.reserve(additional);
+    }
+
+    fn add<T>(&mut self, value: T) -> Result<(), BoxDynError>
+    where
+        T: Encode<'q, Turso> + Type<Turso>,
+    {
+        let len = self.values.len();
+        match value.encode(&mut self.values) {
+            Ok(IsNull::Yes) => {
+                self.values.truncate(len);
+                self.values.push(Value::Null);
+            }
+            Ok(IsNull::No) => {}
+            Err(err) => {
+                self.values.truncate(len);
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+}
@@ -0,0 +1,28 @@
+use sqlx_core::column::Column;
+use sqlx_core::ext::ustr::UStr;
+
+use crate::{Turso, TursoTypeInfo};
+
+/// A result column of a Turso statement.
+#[derive(Debug, Clone)]
+pub struct TursoColumn {
+    pub(crate) name: UStr,
+    pub(crate) ordinal: usize,
+    pub(crate) type_info: TursoTypeInfo,
+}
+
+impl Column for TursoColumn {
+    type Database = Turso;
+
+    fn ordinal(&self) -> usize {
+        self.ordinal
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn type_info(&self) -> &TursoTypeInfo {
+        &self.type_info
+    }
+}
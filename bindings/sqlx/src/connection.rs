@@ -0,0 +1,285 @@
+use std::borrow::Cow;
+use std::fmt::{self, Debug, Formatter};
+use std::num::NonZero;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures_core::future::BoxFuture;
+use futures_core::stream::{BoxStream, Stream};
+use futures_util::{future, stream, TryStreamExt};
+use sqlx_core::connection::{Connection, LogSettings};
+use sqlx_core::describe::Describe;
+use sqlx_core::error::Error;
+use sqlx_core::executor::{Execute, Executor};
+use sqlx_core::logger::QueryLogger;
+use sqlx_core::transaction::Transaction;
+use sqlx_core::Either;
+use turso_core::{LimboError, QueryRunner, Statement, StepResult, Value};
+
+use crate::error::db_error;
+use crate::{
+    Turso, TursoColumn, TursoConnectOptions, TursoQueryResult, TursoRow, TursoStatement,
+    TursoTypeInfo,
+};
+
+/// A connection to a Turso database.
+pub struct TursoConnection {
+    pub(crate) conn: Arc<turso_core::Connection>,
+    log_settings: LogSettings,
+}
+
+impl TursoConnection {
+    pub(crate) fn new(conn: Arc<turso_core::Connection>, log_settings: LogSettings) -> Self {
+        Self { conn, log_settings }
+    }
+
+    /// The underlying `turso_core` connection.
+    pub fn as_core(&self) -> &Arc<turso_core::Connection> {
+        &self.conn
+    }
+
+    pub(crate) fn in_transaction(&self) -> bool {
+        !self.conn.get_auto_commit()
+    }
+
+    /// Runs a statement that returns no rows, such as `BEGIN` or `COMMIT`.
+    pub(crate) fn execute_raw(&self, sql: &str) -> Result<(), Error> {
+        self.conn.execute(sql).map_err(db_error)
+    }
+}
+
+impl Debug for TursoConnection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TursoConnection").finish_non_exhaustive()
+    }
+}
+
+impl Connection for TursoConnection {
+    type Database = Turso;
+
+    type Options = TursoConnectOptions;
+
+    fn close(self) -> BoxFuture<'static, Result<(), Error>> {
+        Box::pin(async move { self.conn.close().map_err(db_error) })
+    }
+
+    fn close_hard(self) -> BoxFuture<'static, Result<(), Error>> {
+        self.close()
+    }
+
+    fn ping(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+        Box::pin(future::ok(()))
+    }
+
+    fn begin(&mut self) -> BoxFuture<'_, Result<Transaction<'_, Turso>, Error>> {
+        Transaction::begin(self, None)
+    }
+
+    fn shrink_buffers(&mut self) {}
+
+    fn flush(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+        Box::pin(future::ok(()))
+    }
+
+    fn should_flush(&self) -> bool {
+        false
+    }
+}
+
+/// The result columns of a prepared statement.
+fn columns(stmt: &Statement) -> Arc<[TursoColumn]> {
+    (0..stmt.num_columns())
+        .map(|ordinal| TursoColumn {
+            name: stmt.get_column_name(ordinal).into_owned().into(),
+            ordinal,
+            type_info: stmt
+                .get_column_decl_type(ordinal)
+                .map(|decl| TursoTypeInfo::from_decl_type(&decl))
+                .unwrap_or(TursoTypeInfo(crate::type_info::DataType::Null)),
+        })
+        .collect()
+}
+
+/// Runs each statement of a query in turn, yielding its rows followed by its result.
+///
+/// Arguments are bound in order: each statement takes as many as it has parameters. When a
+/// statement waits for IO, the stream runs one round of IO and returns `Pending`, so the
+/// executor's thread is never blocked for the whole query.
+struct QueryIter<'q> {
+    conn: &'q Arc<turso_core::Connection>,
+    runner: QueryRunner<'q>,
+    current: Option<(Statement, Arc<[TursoColumn]>)>,
+    arguments: std::vec::IntoIter<Value>,
+    logger: QueryLogger<'q>,
+    done: bool,
+}
+
+impl QueryIter<'_> {
+    fn prepare_next(&mut self) -> Result<bool, Error> {
+        let mut stmt = loop {
+            match self.runner.next() {
+                Some(Ok(Some(stmt))) => break stmt,
+                Some(Ok(None)) => continue,
+                Some(Err(err)) => return Err(db_error(err)),
+                None if self.arguments.len() > 0 => {
+                    return Err(Error::Protocol(format!(
+                        "{} arguments were not bound to any parameter",
+                        self.arguments.len()
+                    )))
+                }
+                None => return Ok(false),
+            }
+        };
+        for index in 1..=stmt.parameters_count() {
+            let value = self.arguments.next().ok_or_else(|| {
+                Error::Protocol(format!("missing an argument for parameter {index}"))
+            })?;
+            stmt.bind_at(NonZero::new(index).unwrap(), value);
+        }
+        let columns = columns(&stmt);
+        self.current = Some((stmt, columns));
+        Ok(true)
+    }
+
+    fn poll_step(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<Either<TursoQueryResult, TursoRow>>, Error>> {
+        if self.current.is_none() && !self.prepare_next()? {
+            return Poll::Ready(Ok(None));
+        }
+        let (stmt, columns) = self.current.as_mut().unwrap();
+        match stmt.step_with_waker(cx.waker()).map_err(db_error)? {
+            StepResult::IO => {
+                stmt._io().step().map_err(db_error)?;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            StepResult::Row => {
+                let row = stmt.row().expect("row should be present");
+                self.logger.increment_rows_returned();
+                Poll::Ready(Ok(Some(Either::Right(TursoRow {
+                    values: row.get_values().cloned().collect(),
+                    columns: Arc::clone(columns),
+                }))))
+            }
+            StepResult::Done => {
+                let changes = stmt.n_change() as u64;
+                self.logger.increase_rows_affected(changes);
+                self.current = None;
+                Poll::Ready(Ok(Some(Either::Left(TursoQueryResult {
+                    changes,
+                    last_insert_rowid: self.conn.last_insert_rowid(),
+                }))))
+            }
+            StepResult::Interrupt => Poll::Ready(Err(db_error(LimboError::Interrupt))),
+            StepResult::Busy => Poll::Ready(Err(db_error(LimboError::Busy))),
+        }
+    }
+}
+
+impl Stream for QueryIter<'_> {
+    type Item = Result<Either<TursoQueryResult, TursoRow>, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+        let Poll::Ready(result) = this.poll_step(cx) else {
+            return Poll::Pending;
+        };
+        let result = result.transpose();
+        if !matches!(result, Some(Ok(_))) {
+            this.done = true;
+            this.current = None;
+            this.logger.finish();
+        }
+        Poll::Ready(result)
+    }
+}
+
+impl<'c> Executor<'c> for &'c mut TursoConnection {
+    type Database = Turso;
+
+    fn fetch_many<'e, 'q, E>(
+        self,
+        mut query: E,
+    ) -> BoxStream<'e, Result<Either<TursoQueryResult, TursoRow>, Error>>
+    where
+        'c: 'e,
+        E: 'q + Execute<'q, Turso>,
+        'q: 'e,
+    {
+        let sql = query.sql();
+        let arguments = match query.take_arguments().map_err(Error::Encode) {
+            Ok(arguments) => arguments
+                .map(|arguments| arguments.values)
+                .unwrap_or_default(),
+            Err(err) => return Box::pin(stream::once(future::err(err))),
+        };
+        let this: &'e TursoConnection = self;
+        Box::pin(QueryIter {
+            conn: &this.conn,
+            runner: this.conn.query_runner(sql.as_bytes()),
+            current: None,
+            arguments: arguments.into_iter(),
+            logger: QueryLogger::new(sql, this.log_settings.clone()),
+            done: false,
+        })
+    }
+
+    fn fetch_optional<'e, 'q, E>(self, query: E) -> BoxFuture<'e, Result<Option<TursoRow>, Error>>
+    where
+        'c: 'e,
+        E: 'q + Execute<'q, Turso>,
+        'q: 'e,
+    {
+        // Run the query to completion so that every statement takes effect, as with `execute`.
+        let mut results = self.fetch_many(query);
+        Box::pin(async move {
+            let mut first = None;
+            while let Some(result) = results.try_next().await? {
+                if let Either::Right(row) = result {
+                    first.get_or_insert(row);
+                }
+            }
+            Ok(first)
+        })
+    }
+
+    fn prepare_with<'e, 'q>(
+        self,
+        sql: &'q str,
+        _parameters: &'e [TursoTypeInfo],
+    ) -> BoxFuture<'e, Result<TursoStatement<'q>, Error>>
+    where
+        'c: 'e,
+        'q: 'e,
+    {
+        Box::pin(async move {
+            let stmt = self.conn.prepare(sql).map_err(db_error)?;
+            Ok(TursoStatement {
+                sql: Cow::Borrowed(sql),
+                parameters: stmt.parameters_count(),
+                columns: columns(&stmt),
+            })
+        })
+    }
+
+    fn describe<'e, 'q>(self, sql: &'q str) -> BoxFuture<'e, Result<Describe<Turso>, Error>>
+    where
+        'c: 'e,
+        'q: 'e,
+    {
+        Box::pin(async move {
+            let stmt = self.prepare_with(sql, &[]).await?;
+            Ok(Describe {
+                nullable: vec![None; stmt.columns.len()],
+                columns: stmt.columns.to_vec(),
+                parameters: Some(Either::Right(stmt.parameters)),
+            })
+        })
+    }
+}
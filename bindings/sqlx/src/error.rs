@@ -0,0 +1,91 @@
+use std::borrow::Cow;
+use std::error::Error as StdError;
+use std::fmt::{self, Display, Formatter};
+
+use sqlx_core::error::{DatabaseError, ErrorKind};
+use turso_core::LimboError;
+
+// SQLite extended result codes for constraint violations.
+const SQLITE_CONSTRAINT_CHECK: usize = 275;
+const SQLITE_CONSTRAINT_FOREIGNKEY: usize = 787;
+const SQLITE_CONSTRAINT_NOTNULL: usize = 1299;
+const SQLITE_CONSTRAINT_PRIMARYKEY: usize = 1555;
+const SQLITE_CONSTRAINT_UNIQUE: usize = 2067;
+
+/// An error returned by the Turso database engine.
+#[derive(Debug)]
+pub struct TursoError {
+    code: usize,
+    message: String,
+    source: LimboError,
+}
+
+impl TursoError {
+    /// The SQLite extended result code of the error.
+    pub fn extended_code(&self) -> usize {
+        self.code
+    }
+
+    /// The underlying `turso_core` error.
+    pub fn inner(&self) -> &LimboError {
+        &self.source
+    }
+}
+
+impl From<LimboError> for TursoError {
+    fn from(source: LimboError) -> Self {
+        Self {
+            code: source.extended_result_code(),
+            message: source.to_string(),
+            source,
+        }
+    }
+}
+
+impl Display for TursoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl StdError for TursoError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl DatabaseError for TursoError {
+    fn message(&self) -> &str {
+        &self.message
+    }
+
+    fn code(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Owned(self.code.to_string()))
+    }
+
+    fn as_error(&self) -> &(dyn StdError + Send + Sync + 'static) {
+        self
+    }
+
+    fn as_error_mut(&mut self) -> &mut (dyn StdError + Send + Sync + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn StdError + Send + Sync + 'static> {
+        self
+    }
+
+    fn kind(&self) -> ErrorKind {
+        match self.code {
+            SQLITE_CONSTRAINT_UNIQUE | SQLITE_CONSTRAINT_PRIMARYKEY => ErrorKind::UniqueViolation,
+            SQLITE_CONSTRAINT_FOREIGNKEY => ErrorKind::ForeignKeyViolation,
+            SQLITE_CONSTRAINT_NOTNULL => ErrorKind::NotNullViolation,
+            SQLITE_CONSTRAINT_CHECK => ErrorKind::CheckViolation,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+pub(crate) fn db_error(err: LimboError) -> sqlx_core::Error {
+    sqlx_core::Error::Database(Box::new(TursoError::from(err)))
+}
@@ -0,0 +1,95 @@
+//! A runtime [sqlx](https://docs.rs/sqlx) driver for Turso.
+//!
+//! The driver implements the `sqlx-core` traits on top of `turso_core`, so `sqlx::query`,
+//! `query_as`, transactions and `FromRow` work against a Turso database:
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), sqlx_core::Error> {
+//! use sqlx_core::connection::ConnectOptions;
+//! use sqlx_core::query::query;
+//! use turso_sqlx::TursoConnectOptions;
+//!
+//! let mut conn = TursoConnectOptions::new().filename("app.db").connect().await?;
+//! query("CREATE TABLE IF NOT EXISTS t (x INTEGER)").execute(&mut conn).await?;
+//! query("INSERT INTO t VALUES (?)").bind(42).execute(&mut conn).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Statements run on the calling task; `turso_core` drives its own IO. Pools need sqlx to be
+//! built with one of its runtime features, e.g. `sqlx = { features = ["runtime-tokio"] }`.
+//!
+//! The compile-time checked `query!` macros only know about the drivers built into sqlx and
+//! cannot be used with this crate.
+
+mod arguments;
+mod column;
+mod connection;
+mod error;
+mod options;
+mod query_result;
+mod row;
+mod statement;
+mod transaction;
+mod type_info;
+mod types;
+mod value;
+
+pub use arguments::TursoArguments;
+pub use column::TursoColumn;
+pub use connection::TursoConnection;
+pub use error::TursoError;
+pub use options::TursoConnectOptions;
+pub use query_result::TursoQueryResult;
+pub use row::TursoRow;
+pub use statement::TursoStatement;
+pub use transaction::TursoTransactionManager;
+pub use type_info::TursoTypeInfo;
+pub use value::{TursoValue, TursoValueRef};
+
+use sqlx_core::database::Database;
+
+/// Turso database driver.
+#[derive(Debug)]
+pub struct Turso;
+
+impl Database for Turso {
+    type Connection = TursoConnection;
+
+    type TransactionManager = TursoTransactionManager;
+
+    type Row = TursoRow;
+
+    type QueryResult = TursoQueryResult;
+
+    type Column = TursoColumn;
+
+    type TypeInfo = TursoTypeInfo;
+
+    type Value = TursoValue;
+    type ValueRef<'r> = TursoValueRef<'r>;
+
+    type Arguments<'q> = TursoArguments;
+    type ArgumentBuffer<'q> = Vec<turso_core::Value>;
+
+    type Statement<'q> = TursoStatement<'q>;
+
+    const NAME: &'static str = "Turso";
+
+    const URL_SCHEMES: &'static [&'static str] = &["turso"];
+}
+
+/// An alias for [`Pool`][sqlx_core::pool::Pool], specialized for Turso.
+pub type TursoPool = sqlx_core::pool::Pool<Turso>;
+
+/// An alias for [`PoolOptions`][sqlx_core::pool::PoolOptions], specialized for Turso.
+pub type TursoPoolOptions = sqlx_core::pool::PoolOptions<Turso>;
+
+/// An alias for [`Transaction`][sqlx_core::transaction::Transaction], specialized for Turso.
+pub type TursoTransaction<'c> = sqlx_core::transaction::Transaction<'c, Turso>;
+
+sqlx_core::impl_into_arguments_for_arguments!(TursoArguments);
+sqlx_core::impl_column_index_for_row!(TursoRow);
+sqlx_core::impl_column_index_for_statement!(TursoStatement);
+sqlx_core::impl_acquire!(Turso, TursoConnection);
+sqlx_core::impl_encode_for_option!(Turso);
@@ -0,0 +1,162 @@
+use std::fmt::{self, Debug, Formatter};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_core::future::BoxFuture;
+use log::LevelFilter;
+use sqlx_core::connection::{ConnectOptions, LogSettings};
+use sqlx_core::error::Error;
+use sqlx_core::Url;
+use turso_core::{Database, DatabaseOpts, OpenFlags};
+
+use crate::error::db_error;
+use crate::TursoConnection;
+
+/// Options for opening a Turso database.
+///
+/// A URL has the form `turso:<path>` or `turso://<path>`, optionally followed by `?mode=ro`,
+/// `?mode=rw` or `?mode=rwc` (the default). `turso::memory:` opens an in-memory database.
+///
+/// The database is opened once, by the first connection, and shared by every connection made
+/// from clones of the same options, so all connections of a pool see the same database.
+#[derive(Clone)]
+pub struct TursoConnectOptions {
+    pub(crate) path: String,
+    pub(crate) read_only: bool,
+    pub(crate) create_if_missing: bool,
+    pub(crate) log_settings: LogSettings,
+    database: Arc<Mutex<Option<Arc<Database>>>>,
+}
+
+impl Default for TursoConnectOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TursoConnectOptions {
+    /// Options for a private in-memory database.
+    pub fn new() -> Self {
+        Self {
+            path: ":memory:".to_string(),
+            read_only: false,
+            create_if_missing: true,
+            log_settings: LogSettings::default(),
+            database: Arc::default(),
+        }
+    }
+
+    /// Sets the path of the database file.
+    pub fn filename(mut self, path: impl AsRef<std::path::Path>) -> Self {
+        self.path = path.as_ref().to_string_lossy().into_owned();
+        self.database = Arc::default();
+        self
+    }
+
+    /// Opens the database in read-only mode.
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self.database = Arc::default();
+        self
+    }
+
+    /// Creates the database file if it doesn't exist. Defaults to `true`.
+    pub fn create_if_missing(mut self, create: bool) -> Self {
+        self.create_if_missing = create;
+        self.database = Arc::default();
+        self
+    }
+
+    pub(crate) fn database(&self) -> Result<Arc<Database>, Error> {
+        let mut database = self.database.lock().unwrap();
+        if let Some(db) = database.as_ref() {
+            return Ok(db.clone());
+        }
+        let flags = if self.read_only {
+            OpenFlags::ReadOnly
+        } else if self.create_if_missing {
+            OpenFlags::Create
+        } else {
+            OpenFlags::None
+        };
+        let (_, db) =
+            Database::open_new(&self.path, None::<&str>, flags, DatabaseOpts::new(), None)
+                .map_err(db_error)?;
+        *database = Some(db.clone());
+        Ok(db)
+    }
+}
+
+impl Debug for TursoConnectOptions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TursoConnectOptions")
+            .field("path", &self.path)
+            .field("read_only", &self.read_only)
+            .field("create_if_missing", &self.create_if_missing)
+            .finish()
+    }
+}
+
+impl FromStr for TursoConnectOptions {
+    type Err = Error;
+
+    fn from_str(url: &str) -> Result<Self, Error> {
+        let rest = url
+            .strip_prefix("turso://")
+            .or_else(|| url.strip_prefix("turso:"))
+            .ok_or_else(|| Error::Configuration(format!("not a turso URL: {url}").into()))?;
+        let (path, params) = rest.split_once('?').unwrap_or((rest, ""));
+        let mut options = Self::new().filename(if path.is_empty() { ":memory:" } else { path });
+        for param in params.split('&').filter(|param| !param.is_empty()) {
+            match param.split_once('=') {
+                Some(("mode", "ro")) => options = options.read_only(true),
+                Some(("mode", "rw")) => options = options.create_if_missing(false),
+                Some(("mode", "rwc")) => options = options.create_if_missing(true),
+                Some(("mode", "memory")) => options = options.filename(":memory:"),
+                _ => {
+                    return Err(Error::Configuration(
+                        format!("unknown turso URL parameter: {param}").into(),
+                    ))
+                }
+            }
+        }
+        Ok(options)
+    }
+}
+
+impl ConnectOptions for TursoConnectOptions {
+    type Connection = TursoConnection;
+
+    fn from_url(url: &Url) -> Result<Self, Error> {
+        Self::from_str(url.as_str())
+    }
+
+    fn to_url_lossy(&self) -> Url {
+        let mode = if self.read_only {
+            "ro"
+        } else if self.create_if_missing {
+            "rwc"
+        } else {
+            "rw"
+        };
+        Url::parse(&format!("turso:{}?mode={mode}", self.path)).expect("valid turso URL")
+    }
+
+    fn connect(&self) -> BoxFuture<'_, Result<TursoConnection, Error>> {
+        Box::pin(async move {
+            let conn = self.database()?.connect().map_err(db_error)?;
+            Ok(TursoConnection::new(conn, self.log_settings.clone()))
+        })
+    }
+
+    fn log_statements(mut self, level: LevelFilter) -> Self {
+        self.log_settings.log_statements(level);
+        self
+    }
+
+    fn log_slow_statements(mut self, level: LevelFilter, duration: Duration) -> Self {
+        self.log_settings.log_slow_statements(level, duration);
+        self
+    }
+}
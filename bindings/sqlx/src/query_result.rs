@@ -0,0 +1,27 @@
+/// The result of executing a statement that returns no rows.
+#[derive(Debug, Default)]
+pub struct TursoQueryResult {
+    pub(crate) changes: u64,
+    pub(crate) last_insert_rowid: i64,
+}
+
+impl TursoQueryResult {
+    /// Number of rows inserted, updated or deleted.
+    pub fn rows_affected(&self) -> u64 {
+        self.changes
+    }
+
+    /// Rowid of the most recent successful INSERT on the connection.
+    pub fn last_insert_rowid(&self) -> i64 {
+        self.last_insert_rowid
+    }
+}
+
+impl Extend<TursoQueryResult> for TursoQueryResult {
+    fn extend<T: IntoIterator<Item = TursoQueryResult>>(&mut self, iter: T) {
+        for elem in iter {
+            self.changes += elem.changes;
+            self.last_insert_rowid = elem.last_insert_rowid;
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use sqlx_core::column::{Column, ColumnIndex};
+use sqlx_core::error::Error;
+use sqlx_core::row::Row;
+
+use crate::{Turso, TursoColumn, TursoValueRef};
+
+/// A row returned by a Turso query.
+pub struct TursoRow {
+    pub(crate) values: Box<[turso_core::Value]>,
+    pub(crate) columns: Arc<[TursoColumn]>,
+}
+
+impl Row for TursoRow {
+    type Database = Turso;
+
+    fn columns(&self) -> &[TursoColumn] {
+        &self.columns
+    }
+
+    fn try_get_raw<I>(&self, index: I) -> Result<TursoValueRef<'_>, Error>
+    where
+        I: ColumnIndex<Self>,
+    {
+        let index = index.index(self)?;
+        Ok(TursoValueRef(&self.values[index]))
+    }
+}
+
+impl ColumnIndex<TursoRow> for &'_ str {
+    fn index(&self, row: &TursoRow) -> Result<usize, Error> {
+        column_index(&row.columns, self)
+    }
+}
+
+pub(crate) fn column_index(columns: &[TursoColumn], name: &str) -> Result<usize, Error> {
+    columns
+        .iter()
+        .position(|column| column.name().eq_ignore_ascii_case(name))
+        .ok_or_else(|| Error::ColumnNotFound(name.into()))
+}
+
+impl std::fmt::Debug for TursoRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut map = f.debug_map();
+        for (column, value) in self.columns.iter().zip(self.values.iter()) {
+            map.entry(&column.name(), value);
+        }
+        map.finish()
+    }
+}
@@ -0,0 +1,50 @@
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use sqlx_core::column::ColumnIndex;
+use sqlx_core::error::Error;
+use sqlx_core::statement::Statement;
+use sqlx_core::Either;
+
+use crate::row::column_index;
+use crate::{Turso, TursoArguments, TursoColumn, TursoTypeInfo};
+
+/// A prepared Turso statement.
+#[derive(Debug, Clone)]
+pub struct TursoStatement<'q> {
+    pub(crate) sql: Cow<'q, str>,
+    pub(crate) parameters: usize,
+    pub(crate) columns: Arc<[TursoColumn]>,
+}
+
+impl<'q> Statement<'q> for TursoStatement<'q> {
+    type Database = Turso;
+
+    fn to_owned(&self) -> TursoStatement<'static> {
+        TursoStatement {
+            sql: Cow::Owned(self.sql.clone().into_owned()),
+            parameters: self.parameters,
+            columns: Arc::clone(&self.columns),
+        }
+    }
+
+    fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    fn parameters(&self) -> Option<Either<&[TursoTypeInfo], usize>> {
+        Some(Either::Right(self.parameters))
+    }
+
+    fn columns(&self) -> &[TursoColumn] {
+        &self.columns
+    }
+
+    sqlx_core::impl_statement_query!(TursoArguments);
+}
+
+impl ColumnIndex<TursoStatement<'_>> for &'_ str {
+    fn index(&self, statement: &TursoStatement<'_>) -> Result<usize, Error> {
+        column_index(&statement.columns, self)
+    }
+}
@@ -0,0 +1,67 @@
+use std::borrow::Cow;
+
+use futures_core::future::BoxFuture;
+use sqlx_core::error::Error;
+use sqlx_core::transaction::TransactionManager;
+
+use crate::{Turso, TursoConnection};
+
+/// Implements transactions with `BEGIN`, `COMMIT` and `ROLLBACK`.
+///
+/// Turso doesn't support `SAVEPOINT` yet, so transactions cannot be nested.
+pub struct TursoTransactionManager;
+
+impl TransactionManager for TursoTransactionManager {
+    type Database = Turso;
+
+    fn begin<'conn>(
+        conn: &'conn mut TursoConnection,
+        statement: Option<Cow<'static, str>>,
+    ) -> BoxFuture<'conn, Result<(), Error>> {
+        Box::pin(async move {
+            if conn.in_transaction() {
+                return Err(match statement {
+                    Some(_) => Error::InvalidSavePointStatement,
+                    None => Error::Protocol("nested transactions are not supported".into()),
+                });
+            }
+            conn.execute_raw(statement.as_deref().unwrap_or("BEGIN"))?;
+            if !conn.in_transaction() {
+                return Err(Error::BeginFailed);
+            }
+            Ok(())
+        })
+    }
+
+    fn commit(conn: &mut TursoConnection) -> BoxFuture<'_, Result<(), Error>> {
+        Box::pin(async move {
+            if conn.in_transaction() {
+                conn.execute_raw("COMMIT")?;
+            }
+            Ok(())
+        })
+    }
+
+    fn rollback(conn: &mut TursoConnection) -> BoxFuture<'_, Result<(), Error>> {
+        Box::pin(async move {
+            if conn.in_transaction() {
+                conn.execute_raw("ROLLBACK")?;
+            }
+            Ok(())
+        })
+    }
+
+    fn start_rollback(conn: &mut TursoConnection) {
+        // Statements run synchronously, so the rollback can finish before the transaction
+        // guard is dropped.
+        if conn.in_transaction() {
+            if let Err(err) = conn.execute_raw("ROLLBACK") {
+                log::warn!("failed to roll back transaction: {err}");
+            }
+        }
+    }
+
+    fn get_transaction_depth(conn: &TursoConnection) -> usize {
+        usize::from(conn.in_transaction())
+    }
+}
@@ -0,0 +1,85 @@
+use std::fmt::{self, Display, Formatter};
+
+use sqlx_core::type_info::TypeInfo;
+use turso_core::Value;
+
+/// The storage class of a value, or the affinity of a declared column type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum DataType {
+    Null,
+    Integer,
+    Real,
+    Text,
+    Blob,
+    Numeric,
+    Bool,
+}
+
+/// Type information for a Turso value or column.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TursoTypeInfo(pub(crate) DataType);
+
+impl TursoTypeInfo {
+    pub(crate) fn of_value(value: &Value) -> Self {
+        Self(match value {
+            Value::Null => DataType::Null,
+            Value::Integer(_) => DataType::Integer,
+            Value::Float(_) => DataType::Real,
+            Value::Text(_) => DataType::Text,
+            Value::Blob(_) => DataType::Blob,
+        })
+    }
+
+    /// Maps a declared column type to a type using SQLite's affinity rules.
+    pub(crate) fn from_decl_type(decl: &str) -> Self {
+        let decl = decl.to_ascii_uppercase();
+        Self(if decl == "BOOLEAN" || decl == "BOOL" {
+            DataType::Bool
+        } else if decl.contains("INT") {
+            DataType::Integer
+        } else if decl.contains("CHAR") || decl.contains("CLOB") || decl.contains("TEXT") {
+            DataType::Text
+        } else if decl.contains("BLOB") || decl.is_empty() {
+            DataType::Blob
+        } else if decl.contains("REAL") || decl.contains("FLOA") || decl.contains("DOUB") {
+            DataType::Real
+        } else {
+            DataType::Numeric
+        })
+    }
+}
+
+impl Display for TursoTypeInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+impl TypeInfo for TursoTypeInfo {
+    fn is_null(&self) -> bool {
+        matches!(self.0, DataType::Null)
+    }
+
+    fn name(&self) -> &str {
+        match self.0 {
+            DataType::Null => "NULL",
+            DataType::Integer => "INTEGER",
+            DataType::Real => "REAL",
+            DataType::Text => "TEXT",
+            DataType::Blob => "BLOB",
+            DataType::Numeric => "NUMERIC",
+            DataType::Bool => "BOOLEAN",
+        }
+    }
+
+    fn type_compatible(&self, other: &Self) -> bool {
+        // Values carry their storage class while columns carry an affinity, so anything that
+        // the affinity would coerce into the other type is accepted.
+        match (self.0, other.0) {
+            (DataType::Numeric, DataType::Integer | DataType::Real | DataType::Bool)
+            | (DataType::Integer | DataType::Real | DataType::Bool, DataType::Numeric) => true,
+            (DataType::Bool, DataType::Integer) | (DataType::Integer, DataType::Bool) => true,
+            (a, b) => a == b,
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! Conversions between Rust types and Turso values.
+
+use sqlx_core::decode::Decode;
+use sqlx_core::encode::{Encode, IsNull};
+use sqlx_core::error::BoxDynError;
+use sqlx_core::types::Type;
+use turso_core::Value;
+
+use crate::type_info::DataType;
+use crate::{Turso, TursoTypeInfo, TursoValueRef};
+
+fn unexpected(expected: &str, value: &Value) -> BoxDynError {
+    format!(
+        "expected {expected}, got {}",
+        TursoTypeInfo::of_value(value)
+    )
+    .into()
+}
+
+impl Type<Turso> for bool {
+    fn type_info() -> TursoTypeInfo {
+        TursoTypeInfo(DataType::Bool)
+    }
+
+    fn compatible(ty: &TursoTypeInfo) -> bool {
+        matches!(ty.0, DataType::Bool | DataType::Integer | DataType::Numeric)
+    }
+}
+
+impl Encode<'_, Turso> for bool {
+    fn encode_by_ref(&self, buf: &mut Vec<Value>) -> Result<IsNull, BoxDynError> {
+        buf.push(Value::Integer(i64::from(*self)));
+        Ok(IsNull::No)
+    }
+}
+
+impl Decode<'_, Turso> for bool {
+    fn decode(value: TursoValueRef<'_>) -> Result<Self, BoxDynError> {
+        Ok(i64::decode(value)? != 0)
+    }
+}
+
+macro_rules! impl_integer {
+    ($($ty:ty),*) => {
+        $(
+            impl Type<Turso> for $ty {
+                fn type_info() -> TursoTypeInfo {
+                    TursoTypeInfo(DataType::Integer)
+                }
+
+                fn compatible(ty: &TursoTypeInfo) -> bool {
+                    matches!(
+                        ty.0,
+                        DataType::Integer | DataType::Bool | DataType::Numeric
+                    )
+                }
+            }
+
+            impl Encode<'_, Turso> for $ty {
+                fn encode_by_ref(&self, buf: &mut Vec<Value>) -> Result<IsNull, BoxDynError> {
+                    buf.push(Value::Integer(i64::try_from(*self)?));
+                    Ok(IsNull::No)
+                }
+            }
+
+            impl Decode<'_, Turso> for $ty {
+                fn decode(value: TursoValueRef<'_>) -> Result<Self, BoxDynError> {
+                    match value.value() {
+                        Value::Integer(i) => Ok(<$ty>::try_from(*i)?),
+                        other => Err(unexpected("INTEGER", other)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(i8, i16, i32, i64, u8, u16, u32);
+
+macro_rules! impl_float {
+    ($($ty:ty),*) => {
+        $(
+            impl Type<Turso> for $ty {
+                fn type_info() -> TursoTypeInfo {
+                    TursoTypeInfo(DataType::Real)
+                }
+
+                fn compatible(ty: &TursoTypeInfo) -> bool {
+                    matches!(
+                        ty.0,
+                        DataType::Real | DataType::Integer | DataType::Numeric
+                    )
+                }
+            }
+
+            impl Encode<'_, Turso> for $ty {
+                fn encode_by_ref(&self, buf: &mut Vec<Value>) -> Result<IsNull, BoxDynError> {
+                    buf.push(Value::Float(f64::from(*self)));
+                    Ok(IsNull::No)
+                }
+            }
+
+            impl Decode<'_, Turso> for $ty {
+                #[allow(clippy::cast_possible_truncation)]
+                fn decode(value: TursoValueRef<'_>) -> Result<Self, BoxDynError> {
+                    match value.value() {
+                        Value::Float(f) => Ok(*f as $ty),
+                        Value::Integer(i) => Ok(*i as $ty),
+                        other => Err(unexpected("REAL", other)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_float!(f32, f64);
+
+impl Type<Turso> for str {
+    fn type_info() -> TursoTypeInfo {
+        TursoTypeInfo(DataType::Text)
+    }
+}
+
+impl Encode<'_, Turso> for &'_ str {
+    fn encode_by_ref(&self, buf: &mut Vec<Value>) -> Result<IsNull, BoxDynError> {
+        buf.push(Value::build_text(self.to_string()));
+        Ok(IsNull::No)
+    }
+}
+
+impl<'r> Decode<'r, Turso> for &'r str {
+    fn decode(value: TursoValueRef<'r>) -> Result<Self, BoxDynError> {
+        match value.0 {
+            Value::Text(text) => Ok(text.as_str()),
+            other => Err(unexpected("TEXT", other)),
+        }
+    }
+}
+
+impl Type<Turso> for String {
+    fn type_info() -> TursoTypeInfo {
+        <str as Type<Turso>>::type_info()
+    }
+}
+
+impl Encode<'_, Turso> for String {
+    fn encode(self, buf: &mut Vec<Value>) -> Result<IsNull, BoxDynError> {
+        buf.push(Value::build_text(self));
+        Ok(IsNull::No)
+    }
+
+    fn encode_by_ref(&self, buf: &mut Vec<Value>) -> Result<IsNull, BoxDynError> {
+        buf.push(Value::build_text(self.clone()));
+        Ok(IsNull::No)
+    }
+}
+
+impl Decode<'_, Turso> for String {
+    fn decode(value: TursoValueRef<'_>) -> Result<Self, BoxDynError> {
+        <&str as Decode<Turso>>::decode(value).map(ToOwned::to_owned)
+    }
+}
+
+impl Type<Turso> for [u8] {
+    fn type_info() -> TursoTypeInfo {
+        TursoTypeInfo(DataType::Blob)
+    }
+
+    fn compatible(ty: &TursoTypeInfo) -> bool {
+        matches!(ty.0, DataType::Blob | DataType::Text)
+    }
+}
+
+impl Encode<'_, Turso> for &'_ [u8] {
+    fn encode_by_ref(&self, buf: &mut Vec<Value>) -> Result<IsNull, BoxDynError> {
+        buf.push(Value::from_blob(self.to_vec()));
+        Ok(IsNull::No)
+    }
+}
+
+impl<'r> Decode<'r, Turso> for &'r [u8] {
+    fn decode(value: TursoValueRef<'r>) -> Result<Self, BoxDynError> {
+        match value.0 {
+            Value::Blob(blob) => Ok(blob),
+            Value::Text(text) => Ok(text.as_str().as_bytes()),
+            other => Err(unexpected("BLOB", other)),
+        }
+    }
+}
+
+impl Type<Turso> for Vec<u8> {
+    fn type_info() -> TursoTypeInfo {
+        <[u8] as Type<Turso>>::type_info()
+    }
+
+    fn compatible(ty: &TursoTypeInfo) -> bool {
+        <[u8] as Type<Turso>>::compatible(ty)
+    }
+}
+
+impl Encode<'_, Turso> for Vec<u8> {
+    fn encode(self, buf: &mut Vec<Value>) -> Result<IsNull, BoxDynError> {
+        buf.push(Value::from_blob(self));
+        Ok(IsNull::No)
+    }
+
+    fn encode_by_ref(&self, buf: &mut Vec<Value>) -> Result<IsNull, BoxDynError> {
+        buf.push(Value::from_blob(self.clone()));
+        Ok(IsNull::No)
+    }
+}
+
+impl Decode<'_, Turso> for Vec<u8> {
+    fn decode(value: TursoValueRef<'_>) -> Result<Self, BoxDynError> {
+        <&[u8] as Decode<Turso>>::decode(value).map(ToOwned::to_owned)
+    }
+}
@@ -0,0 +1,51 @@
+use std::borrow::Cow;
+
+use sqlx_core::value::{Value, ValueRef};
+
+use crate::{Turso, TursoTypeInfo};
+
+/// An owned value read from a Turso row.
+#[derive(Debug, Clone)]
+pub struct TursoValue(pub(crate) turso_core::Value);
+
+/// A borrowed value read from a Turso row.
+#[derive(Debug, Clone, Copy)]
+pub struct TursoValueRef<'r>(pub(crate) &'r turso_core::Value);
+
+impl TursoValueRef<'_> {
+    pub(crate) fn value(&self) -> &turso_core::Value {
+        self.0
+    }
+}
+
+impl Value for TursoValue {
+    type Database = Turso;
+
+    fn as_ref(&self) -> TursoValueRef<'_> {
+        TursoValueRef(&self.0)
+    }
+
+    fn type_info(&self) -> Cow<'_, TursoTypeInfo> {
+        Cow::Owned(TursoTypeInfo::of_value(&self.0))
+    }
+
+    fn is_null(&self) -> bool {
+        matches!(self.0, turso_core::Value::Null)
+    }
+}
+
+impl<'r> ValueRef<'r> for TursoValueRef<'r> {
+    type Database = Turso;
+
+    fn to_owned(&self) -> TursoValue {
+        TursoValue(self.0.clone())
+    }
+
+    fn type_info(&self) -> Cow<'_, TursoTypeInfo> {
+        Cow::Owned(TursoTypeInfo::of_value(self.0))
+    }
+
+    fn is_null(&self) -> bool {
+        matches!(self.0, turso_core::Value::Null)
+    }
+}
@@ -0,0 +1,249 @@
+use futures_util::TryStreamExt;
+use sqlx_core::column::Column;
+use sqlx_core::connection::{ConnectOptions, Connection};
+use sqlx_core::error::{DatabaseError, ErrorKind};
+use sqlx_core::executor::Executor;
+use sqlx_core::from_row::FromRow;
+use sqlx_core::query::query;
+use sqlx_core::query_as::query_as;
+use sqlx_core::query_scalar::query_scalar;
+use sqlx_core::row::Row;
+use sqlx_core::statement::Statement;
+use sqlx_core::type_info::TypeInfo;
+use turso_sqlx::{TursoConnectOptions, TursoConnection, TursoRow};
+
+async fn connect() -> TursoConnection {
+    TursoConnectOptions::new().connect().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_bind_and_fetch() {
+    let mut conn = connect().await;
+    query("CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT, score REAL, data BLOB)")
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let result = query("INSERT INTO t (name, score, data) VALUES (?, ?, ?)")
+        .bind("alice")
+        .bind(1.5)
+        .bind(vec![1u8, 2, 3])
+        .execute(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(result.rows_affected(), 1);
+    assert_eq!(result.last_insert_rowid(), 1);
+    query("INSERT INTO t (name) VALUES (?)")
+        .bind(None::<String>)
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let rows = query("SELECT id, name, score, data FROM t ORDER BY id")
+        .fetch_all(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].get::<i64, _>("id"), 1);
+    assert_eq!(rows[0].get::<String, _>("name"), "alice");
+    assert_eq!(rows[0].get::<f64, _>(2), 1.5);
+    assert_eq!(rows[0].get::<Vec<u8>, _>("data"), vec![1, 2, 3]);
+    assert_eq!(rows[1].get::<Option<String>, _>("name"), None);
+    assert!(rows[1].try_get::<String, _>("name").is_err());
+}
+
+#[derive(Debug, PartialEq)]
+struct User {
+    id: i64,
+    name: String,
+}
+
+impl FromRow<'_, TursoRow> for User {
+    fn from_row(row: &TursoRow) -> Result<Self, sqlx_core::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_query_as_and_scalar() {
+    let mut conn = connect().await;
+    conn.execute("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT); INSERT INTO users VALUES (1, 'a'), (2, 'b');")
+        .await
+        .unwrap();
+
+    let users: Vec<User> = query_as("SELECT id, name FROM users WHERE id >= ? ORDER BY id")
+        .bind(1)
+        .fetch_all(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(
+        users,
+        vec![
+            User {
+                id: 1,
+                name: "a".into()
+            },
+            User {
+                id: 2,
+                name: "b".into()
+            },
+        ]
+    );
+
+    let count: i64 = query_scalar("SELECT count(*) FROM users")
+        .fetch_one(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(count, 2);
+
+    let missing = query("SELECT id FROM users WHERE id = ?")
+        .bind(3)
+        .fetch_optional(&mut conn)
+        .await
+        .unwrap();
+    assert!(missing.is_none());
+}
+
+#[tokio::test]
+async fn test_fetch_stream() {
+    let mut conn = connect().await;
+    conn.execute("CREATE TABLE t (x INTEGER); INSERT INTO t VALUES (1), (2), (3);")
+        .await
+        .unwrap();
+    let mut rows = query("SELECT x FROM t ORDER BY x").fetch(&mut conn);
+    let mut seen = Vec::new();
+    while let Some(row) = rows.try_next().await.unwrap() {
+        seen.push(row.get::<i32, _>(0));
+    }
+    assert_eq!(seen, vec![1, 2, 3]);
+}
+
+#[tokio::test]
+async fn test_transaction_commit_and_rollback() {
+    let mut conn = connect().await;
+    conn.execute("CREATE TABLE t (x INTEGER)").await.unwrap();
+
+    let mut tx = conn.begin().await.unwrap();
+    query("INSERT INTO t VALUES (1)")
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+    assert!(tx.begin().await.is_err());
+    tx.commit().await.unwrap();
+
+    let mut tx = conn.begin().await.unwrap();
+    query("INSERT INTO t VALUES (2)")
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+    tx.rollback().await.unwrap();
+
+    {
+        let mut tx = conn.begin().await.unwrap();
+        query("INSERT INTO t VALUES (3)")
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        // Dropped without committing.
+    }
+
+    let values: Vec<i64> = query_scalar("SELECT x FROM t")
+        .fetch_all(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(values, vec![1]);
+}
+
+#[tokio::test]
+async fn test_constraint_error_kind() {
+    let mut conn = connect().await;
+    conn.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE)")
+        .await
+        .unwrap();
+    query("INSERT INTO t (name) VALUES ('a')")
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+    let err = query("INSERT INTO t (name) VALUES ('a')")
+        .execute(&mut conn)
+        .await
+        .unwrap_err();
+    let db_err = err.as_database_error().unwrap();
+    assert_eq!(db_err.kind(), ErrorKind::UniqueViolation);
+
+    let err = query("INSERT INTO t (name) VALUES (NULL)")
+        .execute(&mut conn)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.as_database_error().map(DatabaseError::kind),
+        Some(ErrorKind::NotNullViolation)
+    );
+}
+
+#[tokio::test]
+async fn test_connections_share_database() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = format!("turso:{}", dir.path().join("test.db").display());
+    let options: TursoConnectOptions = url.parse().unwrap();
+
+    let mut writer = options.connect().await.unwrap();
+    writer
+        .execute("CREATE TABLE t (x INTEGER); INSERT INTO t VALUES (42);")
+        .await
+        .unwrap();
+
+    let mut reader = options.connect().await.unwrap();
+    let x: i64 = query_scalar("SELECT x FROM t")
+        .fetch_one(&mut reader)
+        .await
+        .unwrap();
+    assert_eq!(x, 42);
+    writer.close().await.unwrap();
+    reader.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_prepare_describes_columns() {
+    let mut conn = connect().await;
+    conn.execute("CREATE TABLE t (id INTEGER, name TEXT)")
+        .await
+        .unwrap();
+    let stmt = conn
+        .prepare("SELECT id, name FROM t WHERE id = ?")
+        .await
+        .unwrap();
+    let columns: Vec<_> = stmt
+        .columns()
+        .iter()
+        .map(|column| {
+            (
+                column.name().to_string(),
+                column.type_info().name().to_string(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        columns,
+        vec![
+            ("id".to_string(), "INTEGER".to_string()),
+            ("name".to_string(), "TEXT".to_string()),
+        ]
+    );
+    assert_eq!(stmt.parameters().unwrap().right(), Some(1));
+}
+
+#[tokio::test]
+async fn test_missing_arguments_error() {
+    let mut conn = connect().await;
+    let err = query("SELECT ?, ?")
+        .bind(1)
+        .fetch_all(&mut conn)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, sqlx_core::Error::Protocol(_)), "{err}");
+}
@@ -1,6 +1,8 @@
 use crate::{
     commands::{
+        archive::Archive,
         args::{EchoMode, HeadersMode, TimerMode},
+        export::{json_value, ExportFile},
         import::ImportFile,
         Command, CommandParser,
     },
@@ -20,6 +22,7 @@ use clap::Parser;
 use comfy_table::{Attribute, Cell, CellAlignment, ContentArrangement, Row, Table};
 use rustyline::{error::ReadlineError, history::DefaultHistory, Editor};
 use std::{
+    borrow::Cow,
     io::{self, BufRead, IsTerminal, Write},
     mem::{forget, ManuallyDrop},
     path::PathBuf,
@@ -99,6 +102,8 @@ pub struct Limbo {
     input_buff: ManuallyDrop<String>,
     pub(crate) opts: Settings,
     read_state: ReadState,
+    /// Number of statements that failed so far, used by `.read` to stop at the first error
+    error_count: usize,
     pub rl: Option<Editor<LimboHelper, DefaultHistory>>,
     config: Option<Config>,
 }
@@ -228,6 +233,14 @@ impl Limbo {
                     "Failed to register completion extension".to_string()
                 ));
             }
+            #[cfg(feature = "parquet")]
+            if !limbo_parquet::register_extension_static(&mut ext_api).is_ok() {
+                return Err(anyhow!("Failed to register parquet extension".to_string()));
+            }
+            #[cfg(feature = "icu")]
+            if !limbo_icu::register_extension_static(&mut ext_api).is_ok() {
+                return Err(anyhow!("Failed to register icu extension".to_string()));
+            }
             conn._free_extension_ctx(ext_api);
         }
         let interrupt_count = Arc::new(AtomicUsize::new(0));
@@ -251,6 +264,7 @@ impl Limbo {
             interrupt_count,
             input_buff: ManuallyDrop::new(sql.unwrap_or_default()),
             read_state: ReadState::default(),
+            error_count: 0,
             opts: Settings::from(opts),
             rl: None,
             config: Some(config),
@@ -435,7 +449,9 @@ impl Limbo {
             Ok(file) => {
                 self.writer = Some(Box::new(file));
                 self.opts.is_stdout = false;
-                self.opts.output_mode = OutputMode::List;
+                if self.opts.output_mode == OutputMode::Pretty {
+                    self.opts.output_mode = OutputMode::List;
+                }
                 self.opts.output_filename = path.to_string();
                 Ok(())
             }
@@ -693,11 +709,19 @@ impl Limbo {
                 Command::NullValue(args) => {
                     self.opts.null_value = args.value;
                 }
-                Command::OutputMode(args) => {
-                    if let Err(e) = self.set_mode(args.mode) {
-                        let _ = self.writeln_fmt(format_args!("Error: {e}"));
+                Command::OutputMode(args) => match args.mode {
+                    Some(mode) => {
+                        if let Err(e) = self.set_mode(mode) {
+                            let _ = self.writeln_fmt(format_args!("Error: {e}"));
+                        } else if mode == OutputMode::Insert {
+                            self.opts.insert_table = args.table.unwrap_or("table".to_string());
+                        }
                     }
-                }
+                    None => {
+                        let mode = self.opts.output_mode;
+                        let _ = self.writeln_fmt(format_args!("current output mode: {mode}"));
+                    }
+                },
                 Command::SetOutput(args) => {
                     if let Some(path) = args.path {
                         if let Err(e) = self.set_output_file(&path) {
@@ -726,6 +750,15 @@ impl Limbo {
                     let mut import_file = ImportFile::new(self.conn.clone(), w);
                     import_file.import(args)
                 }
+                Command::Export(args) => {
+                    let w = self.writer.as_mut().unwrap();
+                    let mut export_file = ExportFile::new(self.conn.clone(), w);
+                    export_file.export(args)
+                }
+                Command::Archive(args) => {
+                    let w = self.writer.as_mut().unwrap();
+                    Archive::new(self.conn.clone(), w).run(args)
+                }
                 Command::LoadExtension(args) => {
                     #[cfg(not(target_family = "wasm"))]
                     if let Err(e) = self.handle_load_extension(&args.path) {
@@ -737,6 +770,11 @@ impl Limbo {
                         let _ = self.writeln_fmt(format_args!("/****** ERROR: {e} ******/"));
                     }
                 }
+                Command::Read(args) => {
+                    if let Err(e) = self.read_file(&args.path) {
+                        let _ = self.writeln_fmt(format_args!("Error: {e}"));
+                    }
+                }
                 Command::DbConfig(_args) => {
                     let _ = self.writeln("dbconfig currently ignored");
                 }
@@ -805,11 +843,24 @@ impl Limbo {
                     (OutputMode::Line, _) => {
                         self.print_line_mode(rows, statistics)?;
                     }
+                    (OutputMode::Table | OutputMode::Markdown, _) => {
+                        self.print_aligned_mode(rows, statistics)?;
+                    }
+                    (OutputMode::Csv, _) => {
+                        self.print_csv_mode(rows, statistics)?;
+                    }
+                    (OutputMode::Json | OutputMode::Ndjson, _) => {
+                        self.print_json_mode(rows, statistics)?;
+                    }
+                    (OutputMode::Insert, _) => {
+                        self.print_insert_mode(rows, statistics)?;
+                    }
                 }
             }
             Ok(None) => {}
 
             Err(err) => {
+                self.error_count += 1;
                 match err {
                     LimboError::Busy => {}
                     LimboError::Interrupt => {}
@@ -1153,7 +1204,215 @@ impl Limbo {
         Ok(())
     }
 
+    /// Table and markdown modes have to see every row before printing, so that each column can
+    /// be padded to its widest value.
+    fn print_aligned_mode(
+        &mut self,
+        rows: &mut Statement,
+        statistics: Option<&mut QueryStatistics>,
+    ) -> turso_core::Result<()> {
+        let column_names: Vec<String> = (0..rows.num_columns())
+            .map(|i| rows.get_column_name(i).to_string())
+            .collect();
+        let null_value = self.opts.null_value.clone();
+        let markdown = self.opts.output_mode == OutputMode::Markdown;
+
+        let mut table_rows: Vec<Vec<String>> = Vec::new();
+        let mut stepper = RowStepper::new(rows, statistics);
+        loop {
+            match stepper.next_row() {
+                Ok(Some(row)) => {
+                    table_rows.push(
+                        row.get_values()
+                            .map(|value| match value {
+                                Value::Null => null_value.clone(),
+                                _ => format!("{value}"),
+                            })
+                            .collect(),
+                    );
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    self.handle_step_error(e);
+                    break;
+                }
+            }
+        }
+        if table_rows.is_empty() {
+            return Ok(());
+        }
+
+        let widths: Vec<usize> = column_names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                table_rows
+                    .iter()
+                    .map(|row| row[i].chars().count())
+                    .chain(std::iter::once(name.chars().count()))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let separator = |joint: &str| {
+            let dashes = widths.iter().map(|w| "-".repeat(w + 2));
+            format!("{joint}{}{joint}", dashes.collect::<Vec<_>>().join(joint))
+        };
+        let line = |cells: &mut dyn Iterator<Item = String>| {
+            format!("| {} |", cells.collect::<Vec<_>>().join(" | "))
+        };
+
+        let header = line(&mut column_names.iter().zip(&widths).map(|(name, &width)| {
+            let padding = width - name.chars().count();
+            let left = padding / 2;
+            format!("{}{name}{}", " ".repeat(left), " ".repeat(padding - left))
+        }));
+        if markdown {
+            self.writeln(header)?;
+            self.writeln(separator("|"))?;
+        } else {
+            self.writeln(separator("+"))?;
+            self.writeln(header)?;
+            self.writeln(separator("+"))?;
+        }
+        for row in &table_rows {
+            let row_line = line(&mut row.iter().zip(&widths).map(|(cell, &width)| {
+                format!("{cell}{}", " ".repeat(width - cell.chars().count()))
+            }));
+            self.writeln(row_line)?;
+        }
+        if !markdown {
+            self.writeln(separator("+"))?;
+        }
+        Ok(())
+    }
+
+    fn print_csv_mode(
+        &mut self,
+        rows: &mut Statement,
+        statistics: Option<&mut QueryStatistics>,
+    ) -> turso_core::Result<()> {
+        fn csv_field(field: &str) -> Cow<'_, str> {
+            if field.contains([',', '"', '\n', '\r']) {
+                Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+            } else {
+                Cow::Borrowed(field)
+            }
+        }
+
+        let column_names: Vec<String> = (0..rows.num_columns())
+            .map(|i| rows.get_column_name(i).to_string())
+            .collect();
+        let null_value = self.opts.null_value.clone();
+
+        if self.opts.headers {
+            let header = column_names
+                .iter()
+                .map(|name| csv_field(name))
+                .collect::<Vec<_>>()
+                .join(",");
+            self.writeln(header)?;
+        }
+        let mut stepper = RowStepper::new(rows, statistics);
+        loop {
+            match stepper.next_row() {
+                Ok(Some(row)) => {
+                    let line = row
+                        .get_values()
+                        .map(|value| match value {
+                            Value::Null => csv_field(&null_value).into_owned(),
+                            _ => csv_field(&value.to_string()).into_owned(),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(",");
+                    self.writeln(line)?;
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    self.handle_step_error(e);
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Json mode prints a single array like sqlite3 does, ndjson one object per line.
+    fn print_json_mode(
+        &mut self,
+        rows: &mut Statement,
+        statistics: Option<&mut QueryStatistics>,
+    ) -> turso_core::Result<()> {
+        let keys: Vec<String> = (0..rows.num_columns())
+            .map(|i| serde_json::Value::from(rows.get_column_name(i)).to_string())
+            .collect();
+        let ndjson = self.opts.output_mode == OutputMode::Ndjson;
+
+        let mut first = true;
+        let mut stepper = RowStepper::new(rows, statistics);
+        loop {
+            match stepper.next_row() {
+                Ok(Some(row)) => {
+                    let object = keys
+                        .iter()
+                        .zip(row.get_values())
+                        .map(|(key, value)| format!("{key}:{}", json_value(value)))
+                        .collect::<Vec<_>>()
+                        .join(",");
+                    if ndjson {
+                        self.writeln_fmt(format_args!("{{{object}}}"))?;
+                    } else {
+                        let prefix = if first { "[" } else { ",\n" };
+                        self.write_fmt(format_args!("{prefix}{{{object}}}"))?;
+                    }
+                    first = false;
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    self.handle_step_error(e);
+                    break;
+                }
+            }
+        }
+        if !ndjson && !first {
+            self.writeln("]")?;
+        }
+        Ok(())
+    }
+
+    fn print_insert_mode(
+        &mut self,
+        rows: &mut Statement,
+        statistics: Option<&mut QueryStatistics>,
+    ) -> turso_core::Result<()> {
+        let table = quote_ident(&self.opts.insert_table);
+
+        let mut stepper = RowStepper::new(rows, statistics);
+        loop {
+            match stepper.next_row() {
+                Ok(Some(row)) => {
+                    let mut statement = format!("INSERT INTO {table} VALUES(").into_bytes();
+                    for (i, value) in row.get_values().enumerate() {
+                        if i > 0 {
+                            statement.push(b',');
+                        }
+                        Self::write_sql_value_from_value(&mut statement, value)?;
+                    }
+                    statement.extend_from_slice(b");");
+                    self.writeln(statement)?;
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    self.handle_step_error(e);
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn handle_step_error(&mut self, err: LimboError) {
+        self.error_count += 1;
         match err {
             LimboError::Interrupt => {
                 let _ = self.writeln(LimboError::Interrupt.to_string());
@@ -1788,6 +2047,49 @@ impl Limbo {
         res
     }
 
+    /// Replays a script of SQL statements and dot-commands. Execution stops at the first failing
+    /// statement and any transaction the script left open is rolled back, so replaying a `.dump`
+    /// either applies completely or not at all.
+    fn read_file(&mut self, path: &str) -> anyhow::Result<()> {
+        let file = std::fs::File::open(path).map_err(|e| anyhow!("cannot open \"{path}\": {e}"))?;
+        let mut sql = String::new();
+        let mut state = ReadState::default();
+        let mut failed = false;
+        for line in io::BufReader::new(file).lines() {
+            let line = line?;
+            if sql.trim().is_empty() && line.trim_start().starts_with('.') {
+                self.handle_dot_command(line.trim().strip_prefix('.').unwrap());
+                continue;
+            }
+            state.process(&line);
+            state.process("\n");
+            sql.push_str(&line);
+            sql.push('\n');
+            if state.is_complete() {
+                failed = !self.read_statement(&sql);
+                sql.clear();
+                state = ReadState::default();
+                if failed {
+                    break;
+                }
+            }
+        }
+        if !failed && !sql.trim().is_empty() {
+            failed = !self.read_statement(&sql);
+        }
+        if failed && !self.conn.get_auto_commit() {
+            Self::exec_all_conn(&self.conn, "ROLLBACK")?;
+        }
+        Ok(())
+    }
+
+    /// Runs one statement read from a script, returning whether it succeeded
+    fn read_statement(&mut self, sql: &str) -> bool {
+        let errors = self.error_count;
+        self.run_query(sql.trim());
+        self.error_count == errors
+    }
+
     fn clone_database(&mut self, output_file: &str) -> anyhow::Result<()> {
         use std::path::Path;
         if Path::new(output_file).exists() {
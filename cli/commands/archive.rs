@@ -0,0 +1,398 @@
+//! `.archive`: using the database as a file archive.
+//!
+//! Files are stored in the `sqlar` table in the same format as the `.archive` command of the
+//! sqlite3 shell (see <https://sqlite.org/sqlar.html>), so archives created by one shell can be
+//! listed and extracted by the other. File contents are zlib compressed unless that doesn't make
+//! them smaller, in which case they are stored as is; `sz` always holds the original size.
+use anyhow::{bail, Context};
+use clap::Args;
+use clap_complete::{ArgValueCompleter, PathCompleter};
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+use std::{
+    fs,
+    io::{Read, Write},
+    num::NonZero,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+    time::{Duration, UNIX_EPOCH},
+};
+use turso_core::{Connection, LimboError, Value};
+
+/// Schema of the archive table, identical to the one created by sqlite3.
+pub const SQLAR_SCHEMA: &str = "CREATE TABLE IF NOT EXISTS sqlar(
+  name TEXT PRIMARY KEY,  -- name of the file
+  mode INT,               -- access permissions
+  mtime INT,              -- last modification time
+  sz INT,                 -- original file size
+  data BLOB               -- compressed content
+)";
+
+const S_IFMT: u32 = 0o170000;
+const S_IFDIR: u32 = 0o040000;
+const S_IFREG: u32 = 0o100000;
+
+#[derive(Debug, Clone, Args)]
+pub struct ArchiveArgs {
+    #[command(flatten)]
+    operation: ArchiveOperation,
+    /// Read or write files relative to DIR instead of the current directory
+    #[arg(short = 'C', long = "directory", value_name = "DIR", add = ArgValueCompleter::new(PathCompleter::dir()))]
+    directory: Option<PathBuf>,
+    /// Print the name of each file as it is processed, or its details when listing
+    #[arg(short, long)]
+    verbose: bool,
+    /// Files to archive, or the files to extract or list (all of them if none are given)
+    #[arg(add = ArgValueCompleter::new(PathCompleter::any()))]
+    files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Args)]
+#[group(required = true, multiple = false)]
+struct ArchiveOperation {
+    /// Create a new archive, replacing the current one
+    #[arg(short, long)]
+    create: bool,
+    /// Extract files from the archive
+    #[arg(short = 'x', long)]
+    extract: bool,
+    /// List the files in the archive
+    #[arg(short = 't', long)]
+    list: bool,
+}
+
+/// An entry of the `sqlar` table.
+struct SqlarEntry {
+    name: String,
+    mode: u32,
+    mtime: i64,
+    data: Option<Vec<u8>>,
+}
+
+/// Returns the `sz` and `data` columns for `content`.
+pub fn sqlar_compress(content: &[u8]) -> std::io::Result<(i64, Vec<u8>)> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(content)?;
+    let compressed = encoder.finish()?;
+    if compressed.len() < content.len() {
+        Ok((content.len() as i64, compressed))
+    } else {
+        Ok((content.len() as i64, content.to_vec()))
+    }
+}
+
+/// Returns the file content stored in the `sz` and `data` columns.
+pub fn sqlar_uncompress(sz: i64, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    if sz == data.len() as i64 {
+        return Ok(data.to_vec());
+    }
+    let mut content = Vec::with_capacity(sz.max(0) as usize);
+    ZlibDecoder::new(data)
+        .read_to_end(&mut content)
+        .context("corrupt archive data")?;
+    if content.len() as i64 != sz {
+        bail!(
+            "corrupt archive data: expected {sz} bytes, got {}",
+            content.len()
+        );
+    }
+    Ok(content)
+}
+
+pub struct Archive<'a> {
+    conn: Arc<Connection>,
+    writer: &'a mut dyn Write,
+}
+
+impl<'a> Archive<'a> {
+    pub fn new(conn: Arc<Connection>, writer: &'a mut dyn Write) -> Self {
+        Self { conn, writer }
+    }
+
+    pub fn run(&mut self, args: ArchiveArgs) {
+        let result = if args.operation.create {
+            self.create(&args)
+        } else if args.operation.extract {
+            self.extract(&args)
+        } else {
+            self.list(&args)
+        };
+        if let Err(e) = result {
+            let _ = writeln!(self.writer, "Error: {e}");
+        }
+    }
+
+    fn create(&mut self, args: &ArchiveArgs) -> anyhow::Result<()> {
+        if args.files.is_empty() {
+            bail!("no files to archive");
+        }
+        let base = args.directory.clone().unwrap_or_else(|| PathBuf::from("."));
+        let mut entries = Vec::new();
+        for file in &args.files {
+            let name = file.trim_end_matches('/');
+            collect_entries(&base, name, &mut entries)
+                .with_context(|| format!("cannot archive {name}"))?;
+        }
+
+        self.conn.execute("BEGIN")?;
+        let result = self.insert_entries(&entries, args.verbose);
+        match result {
+            Ok(()) => self.conn.execute("COMMIT")?,
+            Err(_) => self.conn.execute("ROLLBACK")?,
+        }
+        result
+    }
+
+    fn insert_entries(&mut self, entries: &[SqlarEntry], verbose: bool) -> anyhow::Result<()> {
+        self.conn.execute("DROP TABLE IF EXISTS sqlar")?;
+        self.conn.execute(SQLAR_SCHEMA)?;
+        let mut stmt = self
+            .conn
+            .prepare("INSERT INTO sqlar(name, mode, mtime, sz, data) VALUES (?, ?, ?, ?, ?)")?;
+        for entry in entries {
+            let (sz, data) = match &entry.data {
+                Some(content) => {
+                    let (sz, data) = sqlar_compress(content)?;
+                    (sz, Value::from_blob(data))
+                }
+                None => (0, Value::Null),
+            };
+            stmt.reset();
+            stmt.bind_at(
+                NonZero::new(1).unwrap(),
+                Value::build_text(entry.name.clone()),
+            );
+            stmt.bind_at(NonZero::new(2).unwrap(), Value::Integer(entry.mode as i64));
+            stmt.bind_at(NonZero::new(3).unwrap(), Value::Integer(entry.mtime));
+            stmt.bind_at(NonZero::new(4).unwrap(), Value::Integer(sz));
+            stmt.bind_at(NonZero::new(5).unwrap(), data);
+            stmt.run_ignore_rows()?;
+            if verbose {
+                writeln!(self.writer, "{}", entry.name)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn extract(&mut self, args: &ArchiveArgs) -> anyhow::Result<()> {
+        let base = args.directory.clone().unwrap_or_else(|| PathBuf::from("."));
+        let mut stmt = self
+            .conn
+            .prepare("SELECT name, mode, mtime, sz, data FROM sqlar ORDER BY name")?;
+        let mut matched = vec![false; args.files.len()];
+        let mut extracted = Vec::new();
+        stmt.run_with_row_callback(|row| {
+            let name = row.get::<String>(0)?;
+            if !select(&name, &args.files, &mut matched) {
+                return Ok(());
+            }
+            let entry = SqlarEntry {
+                mode: row.get::<i64>(1)? as u32,
+                mtime: row.get::<i64>(2)?,
+                data: match row.get_value(4) {
+                    Value::Blob(data) => Some(
+                        sqlar_uncompress(row.get::<i64>(3)?, data)
+                            .map_err(|e| LimboError::InternalError(format!("{name}: {e}")))?,
+                    ),
+                    Value::Text(text) => Some(text.as_str().as_bytes().to_vec()),
+                    _ => None,
+                },
+                name,
+            };
+            extract_entry(&base, &entry)
+                .map_err(|e| LimboError::InternalError(format!("{}: {e}", entry.name)))?;
+            extracted.push(entry.name);
+            Ok(())
+        })?;
+        drop(stmt);
+        check_all_matched(&args.files, &matched)?;
+        if args.verbose {
+            for name in extracted {
+                writeln!(self.writer, "{name}")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn list(&mut self, args: &ArchiveArgs) -> anyhow::Result<()> {
+        let mut stmt = self.conn.prepare(
+            "SELECT name, mode, sz, datetime(mtime / 86400.0 + 2440587.5) FROM sqlar ORDER BY name",
+        )?;
+        let mut matched = vec![false; args.files.len()];
+        let mut lines = Vec::new();
+        stmt.run_with_row_callback(|row| {
+            let name = row.get::<String>(0)?;
+            if !select(&name, &args.files, &mut matched) {
+                return Ok(());
+            }
+            if args.verbose {
+                lines.push(format!(
+                    "{} {:>10} {} {name}",
+                    mode_string(row.get::<i64>(1)? as u32),
+                    row.get::<i64>(2)?,
+                    row.get::<String>(3)?,
+                ));
+            } else {
+                lines.push(name);
+            }
+            Ok(())
+        })?;
+        drop(stmt);
+        check_all_matched(&args.files, &matched)?;
+        for line in lines {
+            writeln!(self.writer, "{line}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Adds `name`, and everything below it if it is a directory, to `entries`.
+fn collect_entries(base: &Path, name: &str, entries: &mut Vec<SqlarEntry>) -> anyhow::Result<()> {
+    let path = base.join(name);
+    let metadata = fs::metadata(&path)?;
+    let mtime = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64);
+    if metadata.is_dir() {
+        entries.push(SqlarEntry {
+            name: name.to_string(),
+            mode: file_mode(&metadata),
+            mtime,
+            data: None,
+        });
+        let mut children = fs::read_dir(&path)?
+            .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        children.sort();
+        for child in children {
+            collect_entries(base, &format!("{name}/{child}"), entries)?;
+        }
+    } else {
+        entries.push(SqlarEntry {
+            name: name.to_string(),
+            mode: file_mode(&metadata),
+            mtime,
+            data: Some(fs::read(&path)?),
+        });
+    }
+    Ok(())
+}
+
+fn extract_entry(base: &Path, entry: &SqlarEntry) -> anyhow::Result<()> {
+    let relative = Path::new(&entry.name);
+    if !relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        bail!("refusing to extract a file outside of the target directory");
+    }
+    let path = base.join(relative);
+    if entry.mode & S_IFMT == S_IFDIR {
+        fs::create_dir_all(&path)?;
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = fs::File::create(&path)?;
+    file.write_all(entry.data.as_deref().unwrap_or_default())?;
+    file.set_modified(UNIX_EPOCH + Duration::from_secs(entry.mtime.max(0) as u64))?;
+    set_permissions(&path, entry.mode)?;
+    Ok(())
+}
+
+/// Whether `name` is one of `files` or inside one of them, or `files` is empty.
+fn select(name: &str, files: &[String], matched: &mut [bool]) -> bool {
+    let mut selected = files.is_empty();
+    for (file, matched) in files.iter().zip(matched.iter_mut()) {
+        let file = file.trim_end_matches('/');
+        if name == file
+            || name
+                .strip_prefix(file)
+                .is_some_and(|rest| rest.starts_with('/'))
+        {
+            *matched = true;
+            selected = true;
+        }
+    }
+    selected
+}
+
+fn check_all_matched(files: &[String], matched: &[bool]) -> anyhow::Result<()> {
+    match files.iter().zip(matched).find(|(_, matched)| !**matched) {
+        Some((file, _)) => bail!("not found in archive: {file}"),
+        None => Ok(()),
+    }
+}
+
+#[cfg(unix)]
+fn file_mode(metadata: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.mode()
+}
+
+#[cfg(not(unix))]
+fn file_mode(metadata: &fs::Metadata) -> u32 {
+    match (metadata.is_dir(), metadata.permissions().readonly()) {
+        (true, _) => S_IFDIR | 0o755,
+        (false, true) => S_IFREG | 0o444,
+        (false, false) => S_IFREG | 0o644,
+    }
+}
+
+#[cfg(unix)]
+fn set_permissions(path: &Path, mode: u32) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o777))
+}
+
+#[cfg(not(unix))]
+fn set_permissions(path: &Path, mode: u32) -> std::io::Result<()> {
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_readonly(mode & 0o222 == 0);
+    fs::set_permissions(path, permissions)
+}
+
+/// Formats `mode` like `ls -l` does.
+fn mode_string(mode: u32) -> String {
+    let kind = match mode & S_IFMT {
+        S_IFDIR => 'd',
+        S_IFREG => '-',
+        0o120000 => 'l',
+        _ => '?',
+    };
+    let mut s = String::with_capacity(10);
+    s.push(kind);
+    for shift in [6, 3, 0] {
+        let bits = (mode >> shift) & 0o7;
+        s.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        s.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        s.push(if bits & 0o1 != 0 { 'x' } else { '-' });
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sqlar_compress_round_trip() {
+        let text = b"hello hello hello hello hello hello hello hello".repeat(10);
+        let (sz, data) = sqlar_compress(&text).unwrap();
+        assert_eq!(sz, text.len() as i64);
+        assert!(data.len() < text.len());
+        assert_eq!(sqlar_uncompress(sz, &data).unwrap(), text);
+
+        // Content that doesn't get smaller is stored as is.
+        let (sz, data) = sqlar_compress(b"ab").unwrap();
+        assert_eq!((sz, data.as_slice()), (2, b"ab".as_slice()));
+        assert_eq!(sqlar_uncompress(sz, &data).unwrap(), b"ab");
+    }
+
+    #[test]
+    fn test_mode_string() {
+        assert_eq!(mode_string(S_IFREG | 0o644), "-rw-r--r--");
+        assert_eq!(mode_string(S_IFDIR | 0o755), "drwxr-xr-x");
+    }
+}
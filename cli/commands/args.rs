@@ -43,8 +43,11 @@ pub struct SetOutputArgs {
 
 #[derive(Debug, Clone, Args)]
 pub struct OutputModeArgs {
+    /// Output mode to switch to. Prints the current mode when omitted
     #[arg(value_enum)]
-    pub mode: OutputMode,
+    pub mode: Option<OutputMode>,
+    /// Table name used in the statements of insert mode
+    pub table: Option<String>,
 }
 
 fn opcodes_completer(current: &std::ffi::OsStr) -> Vec<CompletionCandidate> {
@@ -124,6 +127,13 @@ pub struct TablesArgs {
     pub pattern: Option<String>,
 }
 
+#[derive(Debug, Clone, Args)]
+pub struct ReadArgs {
+    /// Path to the SQL script to execute
+    #[arg(add = ArgValueCompleter::new(PathCompleter::file()))]
+    pub path: String,
+}
+
 #[derive(Debug, Clone, Args)]
 pub struct LoadExtensionArgs {
     /// Path to extension file
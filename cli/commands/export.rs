@@ -0,0 +1,152 @@
+use clap::Args;
+use clap_complete::{ArgValueCompleter, PathCompleter};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    sync::Arc,
+};
+use turso_core::{Connection, LimboError, Value};
+
+#[derive(Debug, Clone, Args)]
+pub struct ExportArgs {
+    /// Write rows as comma separated values with a header line (default)
+    #[arg(long, conflicts_with_all = ["tsv", "json"])]
+    csv: bool,
+    /// Write rows as tab separated values with a header line
+    #[arg(long, conflicts_with = "json")]
+    tsv: bool,
+    /// Write rows as newline delimited JSON objects
+    #[arg(long)]
+    json: bool,
+    /// File to write the query results to
+    #[arg(add = ArgValueCompleter::new(PathCompleter::file()))]
+    file: PathBuf,
+    /// Query whose results are exported
+    #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+    query: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Tsv,
+    Json,
+}
+
+impl ExportArgs {
+    fn format(&self) -> ExportFormat {
+        if self.json {
+            ExportFormat::Json
+        } else if self.tsv {
+            ExportFormat::Tsv
+        } else {
+            ExportFormat::Csv
+        }
+    }
+}
+
+pub struct ExportFile<'a> {
+    conn: Arc<Connection>,
+    writer: &'a mut dyn Write,
+}
+
+impl<'a> ExportFile<'a> {
+    pub fn new(conn: Arc<Connection>, writer: &'a mut dyn Write) -> Self {
+        Self { conn, writer }
+    }
+
+    pub fn export(&mut self, args: ExportArgs) {
+        let query = args.query.join(" ");
+        let file = match File::create(&args.file) {
+            Ok(file) => file,
+            Err(e) => {
+                let _ = writeln!(self.writer, "Error: cannot open {:?}: {e}", args.file);
+                return;
+            }
+        };
+        let mut out = BufWriter::new(file);
+        match export_query(&self.conn, &query, args.format(), &mut out).and_then(|rows| {
+            out.flush()?;
+            Ok(rows)
+        }) {
+            Ok(rows) => {
+                let _ = writeln!(self.writer, "Exported {rows} rows to {:?}", args.file);
+            }
+            Err(e) => {
+                let _ = writeln!(self.writer, "Error: {e}");
+            }
+        }
+    }
+}
+
+/// Streams the rows of `query` into `out` one at a time, returning the number of rows written.
+pub fn export_query(
+    conn: &Arc<Connection>,
+    query: &str,
+    format: ExportFormat,
+    out: &mut dyn Write,
+) -> Result<u64, LimboError> {
+    let Some(mut rows) = conn.query(query)? else {
+        return Ok(0);
+    };
+    let columns = (0..rows.num_columns())
+        .map(|i| rows.get_column_name(i).into_owned())
+        .collect::<Vec<_>>();
+    let mut count = 0;
+    match format {
+        ExportFormat::Csv | ExportFormat::Tsv => {
+            let delimiter = if format == ExportFormat::Tsv {
+                b'\t'
+            } else {
+                b','
+            };
+            let mut csv = csv::WriterBuilder::new()
+                .delimiter(delimiter)
+                .from_writer(out);
+            csv.write_record(&columns).map_err(csv_error)?;
+            rows.run_with_row_callback(|row| {
+                csv.write_record(row.get_values().map(|v| v.to_string()))
+                    .map_err(csv_error)?;
+                count += 1;
+                Ok(())
+            })?;
+            csv.flush()?;
+        }
+        ExportFormat::Json => {
+            rows.run_with_row_callback(|row| {
+                let object = columns
+                    .iter()
+                    .zip(row.get_values())
+                    .map(|(name, value)| (name.clone(), json_value(value)))
+                    .collect::<serde_json::Map<_, _>>();
+                serde_json::to_writer(&mut *out, &object)
+                    .map_err(|e| LimboError::InternalError(e.to_string()))?;
+                out.write_all(b"\n")?;
+                count += 1;
+                Ok(())
+            })?;
+        }
+    }
+    Ok(count)
+}
+
+fn csv_error(e: csv::Error) -> LimboError {
+    LimboError::InternalError(e.to_string())
+}
+
+/// Blobs have no JSON representation and are written as hex strings.
+pub(crate) fn json_value(value: &Value) -> serde_json::Value {
+    match value {
+        Value::Null => serde_json::Value::Null,
+        Value::Integer(i) => (*i).into(),
+        Value::Float(f) => serde_json::Number::from_f64(*f)
+            .map_or(serde_json::Value::Null, serde_json::Value::Number),
+        Value::Text(t) => t.as_str().into(),
+        Value::Blob(b) => b
+            .iter()
+            .map(|byte| format!("{byte:02X}"))
+            .collect::<String>()
+            .into(),
+    }
+}
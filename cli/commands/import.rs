@@ -1,6 +1,11 @@
 use clap::Args;
 use clap_complete::{ArgValueCompleter, PathCompleter};
-use std::{fs::File, io::Write, path::PathBuf, sync::Arc};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Write},
+    path::PathBuf,
+    sync::Arc,
+};
 use turso_core::{Connection, LimboError};
 
 #[derive(Debug, Clone, Args)]
@@ -8,6 +13,12 @@ pub struct ImportArgs {
     /// Use , and \n as column and row separators
     #[arg(long, default_value = "true")]
     csv: bool,
+    /// Use \t and \n as column and row separators
+    #[arg(long, conflicts_with = "json")]
+    tsv: bool,
+    /// Read one JSON object per line, mapping keys to columns
+    #[arg(long)]
+    json: bool,
     /// "Verbose" - increase auxiliary output
     #[arg(short, default_value = "false")]
     verbose: bool,
@@ -19,25 +30,50 @@ pub struct ImportArgs {
     table: String,
 }
 
+/// TODO: should this be in a single transaction (i.e. all or nothing)?
+const INSERT_BATCH_SIZE: usize = 1000;
+
 pub struct ImportFile<'a> {
     conn: Arc<Connection>,
     writer: &'a mut dyn Write,
+    success_rows: u64,
+    failed_rows: u64,
 }
 
 impl<'a> ImportFile<'a> {
     pub fn new(conn: Arc<Connection>, writer: &'a mut dyn Write) -> Self {
-        Self { conn, writer }
+        Self {
+            conn,
+            writer,
+            success_rows: 0,
+            failed_rows: 0,
+        }
     }
 
     pub fn import(&mut self, args: ImportArgs) {
-        self.import_csv(args);
+        if args.json {
+            self.import_json(&args);
+        } else {
+            self.import_csv(&args);
+        }
+
+        if args.verbose {
+            let _ = self.writer.write_all(
+                format!(
+                    "Added {} rows with {} errors using {} lines of input",
+                    self.success_rows,
+                    self.failed_rows,
+                    self.success_rows + self.failed_rows,
+                )
+                .as_bytes(),
+            );
+        }
     }
 
-    pub fn import_csv(&mut self, args: ImportArgs) {
-        // Check if the target table exists
+    fn table_exists(&mut self, table: &str) -> Option<bool> {
         let table_check_query = format!(
             "SELECT name FROM sqlite_master WHERE type='table' AND name='{}';",
-            args.table
+            table.replace("'", "''")
         );
 
         let mut table_exists = false;
@@ -53,7 +89,7 @@ impl<'a> ImportFile<'a> {
                         let _ = self.writer.write_all(
                             format!("Error checking table existence: {e:?}\n").as_bytes(),
                         );
-                        return;
+                        return None;
                     }
                 }
             }
@@ -61,11 +97,106 @@ impl<'a> ImportFile<'a> {
                 let _ = self
                     .writer
                     .write_all(format!("Error checking table existence: {e:?}\n").as_bytes());
-                return;
+                return None;
+            }
+        }
+        Some(table_exists)
+    }
+
+    fn create_table<'c>(&mut self, table: &str, columns: impl Iterator<Item = &'c str>) -> bool {
+        let columns = columns.map(normalize_ident).collect::<Vec<_>>().join(", ");
+        let create_table = format!("CREATE TABLE {table} ({columns});");
+
+        let rows = match self.conn.query(create_table) {
+            Ok(rows) => rows,
+            Err(e) => {
+                let _ = self
+                    .writer
+                    .write_all(format!("Error creating table: {e:?}\n").as_bytes());
+                return false;
+            }
+        };
+        let Some(mut rows) = rows else {
+            let _ = self.writer.write_all(b"Error creating table\n");
+            return false;
+        };
+
+        let res = rows.run_with_row_callback(|_| {
+            // Not expected for CREATE TABLE
+            panic!("Unexpected row for CREATE TABLE");
+        });
+        match res {
+            Ok(_) => true,
+            Err(LimboError::Busy | LimboError::Interrupt) => {
+                let _ = self
+                    .writer
+                    .write_all("Error creating table: interrupted / busy\n".as_bytes());
+                false
+            }
+            Err(e) => {
+                let _ = self
+                    .writer
+                    .write_all(format!("Error checking table existence: {e:?}\n").as_bytes());
+                false
+            }
+        }
+    }
+
+    /// Inserts the batch of `VALUES` tuples and clears it, updating the row counters.
+    fn insert_batch(&mut self, table: &str, columns: &str, batch: &mut Vec<String>) {
+        if batch.is_empty() {
+            return;
+        }
+        let insert_string = format!(
+            "INSERT INTO {table}{columns} VALUES ({});",
+            batch.join("),(")
+        );
+        let rows = batch.len() as u64;
+        batch.clear();
+
+        match self.conn.query(insert_string) {
+            Ok(Some(mut stmt)) => {
+                let res = stmt.run_with_row_callback(|_| {
+                    panic!("Unexpected row for INSERT");
+                });
+                match res {
+                    Ok(_) => {
+                        self.success_rows += rows;
+                    }
+                    Err(LimboError::Interrupt) => {
+                        let _ = self.writer.write_all(b"interrupt\n");
+                        self.failed_rows += rows;
+                    }
+                    Err(LimboError::Busy) => {
+                        let _ = self.writer.write_all(b"database is busy\n");
+                        self.failed_rows += rows;
+                    }
+                    Err(e) => {
+                        let _ = self
+                            .writer
+                            .write_all(format!("Error executing query: {e:?}\n").as_bytes());
+                        self.failed_rows += rows;
+                    }
+                }
+            }
+            Ok(None) => {
+                self.success_rows += rows;
+            }
+            Err(e) => {
+                let _ = self
+                    .writer
+                    .write_all(format!("Error executing query: {e:?}\n").as_bytes());
+                self.failed_rows += rows;
             }
         }
+    }
+
+    pub fn import_csv(&mut self, args: &ImportArgs) {
+        let Some(table_exists) = self.table_exists(&args.table) else {
+            return;
+        };
 
-        let file = match File::open(args.file) {
+        let file = match File::open(&args.file) {
             Ok(file) => file,
             Err(e) => {
                 let _ = self.writer.write_all(format!("{e:?}\n").as_bytes());
@@ -75,55 +206,16 @@ impl<'a> ImportFile<'a> {
 
         let mut rdr = csv::ReaderBuilder::new()
             .has_headers(false)
+            .delimiter(if args.tsv { b'\t' } else { b',' })
             .from_reader(file);
 
-        let mut success_rows = 0u64;
-        let mut failed_rows = 0u64;
-
         let mut records = rdr.records().skip(args.skip as usize).peekable();
 
         // If table doesn't exist, use first row as header to create table
         if !table_exists {
             if let Some(Ok(header)) = records.next() {
-                let columns = header
-                    .iter()
-                    .map(normalize_ident)
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                let create_table = format!("CREATE TABLE {} ({});", args.table, columns);
-
-                let rows = match self.conn.query(create_table) {
-                    Ok(rows) => rows,
-                    Err(e) => {
-                        let _ = self
-                            .writer
-                            .write_all(format!("Error creating table: {e:?}\n").as_bytes());
-                        return;
-                    }
-                };
-                let Some(mut rows) = rows else {
-                    let _ = self.writer.write_all(b"Error creating table\n");
+                if !self.create_table(&args.table, header.iter()) {
                     return;
-                };
-
-                let res = rows.run_with_row_callback(|_| {
-                    // Not expected for CREATE TABLE
-                    panic!("Unexpected row for CREATE TABLE");
-                });
-                match res {
-                    Ok(_) => {}
-                    Err(LimboError::Busy | LimboError::Interrupt) => {
-                        let _ = self
-                            .writer
-                            .write_all("Error creating table: interrupted / busy\n".as_bytes());
-                        return;
-                    }
-                    Err(e) => {
-                        let _ = self.writer.write_all(
-                            format!("Error checking table existence: {e:?}\n").as_bytes(),
-                        );
-                        return;
-                    }
                 }
             } else {
                 let _ = self.writer.write_all(b"Error: Empty input file\n");
@@ -131,14 +223,12 @@ impl<'a> ImportFile<'a> {
             }
         }
 
-        /// TODO: should this be in a single transaction (i.e. all or nothing)?
-        const CSV_INSERT_BATCH_SIZE: usize = 1000;
-        let mut batch = Vec::with_capacity(CSV_INSERT_BATCH_SIZE);
+        let mut batch = Vec::with_capacity(INSERT_BATCH_SIZE);
         for result in records {
             let record = match result {
                 Ok(r) => r,
                 Err(e) => {
-                    failed_rows += 1;
+                    self.failed_rows += 1;
                     let _ = self
                         .writer
                         .write_all(format!("Error reading row: {e:?}\n").as_bytes());
@@ -147,119 +237,109 @@ impl<'a> ImportFile<'a> {
             };
 
             if !record.is_empty() {
-                let values: Vec<String> = record
-                    .iter()
-                    .map(|r| format!("'{}'", r.replace("'", "''")))
-                    .collect();
+                let values: Vec<String> = record.iter().map(quote_text).collect();
                 batch.push(values.join(","));
 
-                if batch.len() >= CSV_INSERT_BATCH_SIZE {
-                    println!("Inserting batch of {} rows", batch.len());
-                    let insert_string =
-                        format!("INSERT INTO {} VALUES ({});", args.table, batch.join("),("));
-
-                    match self.conn.query(insert_string) {
-                        Ok(rows) => {
-                            if let Some(mut rows) = rows {
-                                let res = rows.run_with_row_callback(|_| {
-                                    panic!("Unexpected row for INSERT");
-                                });
-                                match res {
-                                    Ok(_) => {
-                                        success_rows += batch.len() as u64;
-                                    }
-                                    Err(LimboError::Interrupt) => {
-                                        let _ = self.writer.write_all(b"interrupt\n");
-
-                                        failed_rows += batch.len() as u64;
-                                    }
-                                    Err(LimboError::Busy) => {
-                                        let _ = self.writer.write_all(b"database is busy\n");
-
-                                        failed_rows += batch.len() as u64;
-                                    }
-                                    Err(e) => {
-                                        let _ = self.writer.write_all(
-                                            format!("Error executing query: {e:?}\n").as_bytes(),
-                                        );
-                                        failed_rows += batch.len() as u64;
-                                    }
-                                }
-                            } else {
-                                success_rows += batch.len() as u64;
-                            }
-                        }
-                        Err(e) => {
-                            let _ = self
-                                .writer
-                                .write_all(format!("Error executing query: {e:?}\n").as_bytes());
-                            failed_rows += batch.len() as u64;
-                        }
-                    }
-                    batch.clear();
+                if batch.len() >= INSERT_BATCH_SIZE {
+                    self.insert_batch(&args.table, "", &mut batch);
                 }
             }
         }
 
         // Insert remaining records
-        if !batch.is_empty() {
-            let insert_string =
-                format!("INSERT INTO {} VALUES ({});", args.table, batch.join("),("));
-
-            match self.conn.query(insert_string) {
-                Ok(rows) => {
-                    if let Some(mut rows) = rows {
-                        let res = rows.run_with_row_callback(|_| {
-                            panic!("Unexpected row for INSERT");
-                        });
-                        match res {
-                            Ok(_) => {
-                                success_rows += batch.len() as u64;
-                            }
-                            Err(LimboError::Interrupt) => {
-                                let _ = self.writer.write_all(b"interrupt\n");
-
-                                failed_rows += batch.len() as u64;
-                            }
-                            Err(LimboError::Busy) => {
-                                let _ = self.writer.write_all(b"database is busy\n");
-
-                                failed_rows += batch.len() as u64;
-                            }
-                            Err(e) => {
-                                let _ = self.writer.write_all(
-                                    format!("Error executing query: {e:?}\n").as_bytes(),
-                                );
-                                failed_rows += batch.len() as u64;
-                            }
-                        }
-                    } else {
-                        success_rows += batch.len() as u64;
-                    }
+        self.insert_batch(&args.table, "", &mut batch);
+    }
+
+    /// Imports newline delimited JSON objects. The keys of the first object pick the columns
+    /// (and name them when the table is created); later objects missing a key insert NULL.
+    pub fn import_json(&mut self, args: &ImportArgs) {
+        let Some(table_exists) = self.table_exists(&args.table) else {
+            return;
+        };
+
+        let file = match File::open(&args.file) {
+            Ok(file) => file,
+            Err(e) => {
+                let _ = self.writer.write_all(format!("{e:?}\n").as_bytes());
+                return;
+            }
+        };
+
+        let mut columns: Option<Vec<String>> = None;
+        let mut column_list = String::new();
+        let mut batch = Vec::with_capacity(INSERT_BATCH_SIZE);
+        let lines = BufReader::new(file)
+            .lines()
+            .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+            .skip(args.skip as usize);
+        for line in lines {
+            let object = match line
+                .map_err(|e| e.to_string())
+                .and_then(|line| serde_json::from_str(&line).map_err(|e| e.to_string()))
+            {
+                Ok(serde_json::Value::Object(object)) => object,
+                Ok(_) => {
+                    self.failed_rows += 1;
+                    let _ = self
+                        .writer
+                        .write_all(b"Error reading row: not a JSON object\n");
+                    continue;
                 }
                 Err(e) => {
+                    self.failed_rows += 1;
                     let _ = self
                         .writer
-                        .write_all(format!("Error executing query: {e:?}\n").as_bytes());
-                    failed_rows += batch.len() as u64;
+                        .write_all(format!("Error reading row: {e}\n").as_bytes());
+                    continue;
+                }
+            };
+
+            if columns.is_none() {
+                let keys = object.keys().cloned().collect::<Vec<_>>();
+                if !table_exists && !self.create_table(&args.table, keys.iter().map(|k| k.as_str()))
+                {
+                    return;
                 }
+                column_list = format!(
+                    " ({})",
+                    keys.iter()
+                        .map(|k| format!("\"{}\"", k.replace('"', "\"\"")))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+                columns = Some(keys);
+            }
+
+            let values: Vec<String> = columns
+                .iter()
+                .flatten()
+                .map(|column| match object.get(column) {
+                    None | Some(serde_json::Value::Null) => "NULL".to_string(),
+                    Some(serde_json::Value::Bool(b)) => (*b as i64).to_string(),
+                    Some(serde_json::Value::Number(n)) => n.to_string(),
+                    Some(serde_json::Value::String(s)) => quote_text(s),
+                    Some(nested) => quote_text(&nested.to_string()),
+                })
+                .collect();
+            batch.push(values.join(","));
+
+            if batch.len() >= INSERT_BATCH_SIZE {
+                self.insert_batch(&args.table, &column_list, &mut batch);
             }
         }
 
-        if args.verbose {
-            let _ = self.writer.write_all(
-                format!(
-                    "Added {} rows with {} errors using {} lines of input",
-                    success_rows,
-                    failed_rows,
-                    success_rows + failed_rows,
-                )
-                .as_bytes(),
-            );
+        if columns.is_none() {
+            let _ = self.writer.write_all(b"Error: Empty input file\n");
+            return;
         }
+        self.insert_batch(&args.table, &column_list, &mut batch);
     }
 }
 
+fn quote_text(s: &str) -> String {
+    format!("'{}'", s.replace("'", "''"))
+}
+
 // https://sqlite.org/lang_keywords.html
 const QUOTE_PAIRS: &[(char, char)] = &[('"', '"'), ('[', ']'), ('`', '`')];
 
@@ -1,12 +1,16 @@
+pub mod archive;
 pub mod args;
+pub mod export;
 pub mod import;
 
+use archive::ArchiveArgs;
 use args::{
     CwdArgs, DbConfigArgs, EchoArgs, ExitArgs, HeadersArgs, IndexesArgs, LoadExtensionArgs,
-    ManualArgs, NullValueArgs, OpcodesArgs, OpenArgs, OutputModeArgs, SchemaArgs, SetOutputArgs,
-    StatsArgs, TablesArgs, TimerArgs,
+    ManualArgs, NullValueArgs, OpcodesArgs, OpenArgs, OutputModeArgs, ReadArgs, SchemaArgs,
+    SetOutputArgs, StatsArgs, TablesArgs, TimerArgs,
 };
 use clap::Parser;
+use export::ExportArgs;
 use import::ImportArgs;
 
 use crate::{
@@ -64,17 +68,29 @@ pub enum Command {
     #[command(display_name = ".echo")]
     Echo(EchoArgs),
     /// Display tables
+    #[command(name = "tables", display_name = ".tables")]
     Tables(TablesArgs),
     /// Display attached databases
+    #[command(name = "databases", display_name = ".databases")]
     Databases,
     /// Import data from FILE into TABLE
     #[command(name = "import", display_name = ".import")]
     Import(ImportArgs),
+    /// Export the results of QUERY to FILE as CSV, TSV or JSON
+    #[command(name = "export", display_name = ".export")]
+    Export(ExportArgs),
+    /// Create, extract or list a file archive stored in the sqlar table
+    #[command(name = "archive", display_name = ".archive", alias = "ar")]
+    Archive(ArchiveArgs),
     /// Loads an extension library
     #[command(name = "load", display_name = ".load")]
     LoadExtension(LoadExtensionArgs),
     /// Dump the current database as a list of SQL statements
+    #[command(name = "dump", display_name = ".dump")]
     Dump,
+    /// Execute SQL statements and dot-commands from FILE
+    #[command(name = "read", display_name = ".read")]
+    Read(ReadArgs),
     /// Print or set the current configuration for the database. Currently ignored.
     #[command(name = "dbconfig", display_name = ".dbconfig")]
     DbConfig(DbConfigArgs),
@@ -64,6 +64,16 @@ pub enum OutputMode {
     List,
     Pretty,
     Line,
+    /// ASCII table with columns sized to fit their widest value
+    Table,
+    Markdown,
+    Csv,
+    /// JSON array of objects, one per row
+    Json,
+    /// Newline delimited JSON, one object per row
+    Ndjson,
+    /// INSERT statements for the table set with `.mode insert TABLE`
+    Insert,
 }
 
 impl std::fmt::Display for OutputMode {
@@ -80,6 +90,8 @@ pub struct Settings {
     pub db_file: String,
     pub null_value: String,
     pub output_mode: OutputMode,
+    /// Table name used by [OutputMode::Insert]
+    pub insert_table: String,
     pub echo: bool,
     pub is_stdout: bool,
     pub io: Io,
@@ -95,6 +107,7 @@ impl From<Opts> for Settings {
         Self {
             null_value: String::new(),
             output_mode: opts.output_mode,
+            insert_table: "table".to_string(),
             echo: false,
             is_stdout: opts.output.is_empty(),
             output_filename: opts.output,
@@ -19,6 +19,8 @@ use turso_sync_engine::server_proto::{
     StmtResult, StreamRequest, StreamResponse, StreamResult, Value,
 };
 
+use crate::commands::export::json_value;
+
 const WAL_FRAME_HEADER_SIZE: usize = 24;
 const PAGE_SIZE: usize = 4096;
 
@@ -126,6 +128,10 @@ impl TursoSyncServer {
                 debug!("Handling /pull-updates request");
                 self.handle_pull_updates(&body)
             }
+            ("POST", "/query") => {
+                debug!("Handling /query request");
+                self.handle_query(&body)
+            }
             _ => {
                 info!("Unknown endpoint: {} {}", method, path);
                 Ok(HttpResponse {
@@ -194,6 +200,31 @@ impl TursoSyncServer {
         })
     }
 
+    /// Runs a single statement sent as `{"sql": "...", "params": [...]}` and responds with
+    /// `{"columns": [...], "rows": [[...]], "rows_affected": n, "last_insert_rowid": n}`,
+    /// or with status 400 and `{"error": "..."}` if the statement fails.
+    fn handle_query(&self, body: &[u8]) -> Result<HttpResponse> {
+        let req: QueryRequest = serde_json::from_slice(body)
+            .map_err(|e| anyhow!("Failed to parse query request: {}", e))?;
+
+        debug!("Query request: {:?}", req);
+
+        let conn = self.conn.lock().unwrap();
+        let (status, response) = match run_query(&conn, &req) {
+            Ok(response) => (200, response),
+            Err(e) => {
+                error!("Failed to execute query: {}", e);
+                (400, serde_json::json!({ "error": e.to_string() }))
+            }
+        };
+
+        Ok(HttpResponse {
+            status,
+            content_type: "application/json".to_string(),
+            body: serde_json::to_vec(&response)?,
+        })
+    }
+
     fn execute_statement(&self, conn: &Arc<Connection>, req: &ExecuteStreamReq) -> StreamResult {
         let sql = match &req.stmt.sql {
             Some(s) => s.clone(),
@@ -557,6 +588,37 @@ impl TursoSyncServer {
     }
 }
 
+#[derive(Debug, serde::Deserialize)]
+struct QueryRequest {
+    sql: String,
+    #[serde(default)]
+    params: Vec<serde_json::Value>,
+}
+
+fn run_query(conn: &Arc<Connection>, req: &QueryRequest) -> Result<serde_json::Value> {
+    let mut stmt = conn.prepare(&req.sql)?;
+    for (i, param) in req.params.iter().enumerate() {
+        stmt.bind_at(
+            std::num::NonZero::new(i + 1).unwrap(),
+            convert_json_to_core(param)?,
+        );
+    }
+    let columns: Vec<String> = (0..stmt.num_columns())
+        .map(|i| stmt.get_column_name(i).to_string())
+        .collect();
+    let rows: Vec<Vec<serde_json::Value>> = stmt
+        .run_collect_rows()?
+        .iter()
+        .map(|row| row.iter().map(json_value).collect())
+        .collect();
+    Ok(serde_json::json!({
+        "columns": columns,
+        "rows": rows,
+        "rows_affected": stmt.n_change(),
+        "last_insert_rowid": conn.last_insert_rowid(),
+    }))
+}
+
 struct HttpResponse {
     status: u16,
     content_type: String,
@@ -602,6 +664,7 @@ fn parse_http_request(data: &[u8]) -> Result<(String, String, Vec<u8>)> {
 fn format_http_response(resp: &HttpResponse) -> Vec<u8> {
     let status_text = match resp.status {
         200 => "OK",
+        400 => "Bad Request",
         404 => "Not Found",
         500 => "Internal Server Error",
         _ => "Unknown",
@@ -636,13 +699,26 @@ fn convert_value_to_core(value: &Value) -> CoreValue {
         Value::Integer { value } => CoreValue::Integer(*value),
         Value::Float { value } => CoreValue::Float(*value),
         Value::Text { value } => CoreValue::Text(turso_core::types::Text {
-            value: std::borrow::Cow::Owned(value.clone()),
+            value: value.clone().into(),
             subtype: turso_core::types::TextSubtype::Text,
         }),
         Value::Blob { value } => CoreValue::Blob(value.to_vec()),
     }
 }
 
+fn convert_json_to_core(value: &serde_json::Value) -> Result<CoreValue> {
+    match value {
+        serde_json::Value::Null => Ok(CoreValue::Null),
+        serde_json::Value::Bool(b) => Ok(CoreValue::Integer(*b as i64)),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Ok(CoreValue::Integer(i)),
+            None => Ok(CoreValue::Float(n.as_f64().unwrap_or(f64::NAN))),
+        },
+        serde_json::Value::String(s) => Ok(CoreValue::build_text(s.clone())),
+        _ => Err(anyhow!("Unsupported parameter value: {}", value)),
+    }
+}
+
 fn convert_core_to_value(value: CoreValue) -> Value {
     match value {
         CoreValue::Null => Value::Null,
@@ -540,6 +540,145 @@ fn bench_execute_select_count(criterion: &mut Criterion) {
     group.finish();
 }
 
+fn bench_execute_select_text(criterion: &mut Criterion) {
+    // https://github.com/tursodatabase/turso/issues/174
+    // The rusqlite benchmark crashes on Mac M1 when using the flamegraph features
+    let enable_rusqlite = std::env::var("DISABLE_RUSQLITE_BENCHMARK").is_err();
+
+    #[allow(clippy::arc_with_non_send_sync)]
+    let io = Arc::new(PlatformIO::new().unwrap());
+    let db = Database::open_file(io.clone(), "../testing/testing.db").unwrap();
+    let limbo_conn = db.connect().unwrap();
+
+    let mut group = criterion.benchmark_group("Execute queries over TEXT columns");
+
+    // Text values that are copied between registers, sorted and grouped.
+    let queries = [
+        ("scan", "SELECT first_name, last_name, email FROM users"),
+        (
+            "order_by",
+            "SELECT first_name, city FROM users ORDER BY city",
+        ),
+        (
+            "group_by",
+            "SELECT state, max(last_name), group_concat(first_name) FROM users GROUP BY state",
+        ),
+    ];
+    for (name, query) in queries {
+        group.bench_with_input(
+            BenchmarkId::new("limbo_execute_select_text", name),
+            &query,
+            |b, query| {
+                let mut stmt = limbo_conn.prepare(query).unwrap();
+                b.iter(|| {
+                    loop {
+                        match stmt.step().unwrap() {
+                            turso_core::StepResult::Row => {
+                                black_box(stmt.row());
+                            }
+                            turso_core::StepResult::IO => {
+                                db.io.step().unwrap();
+                            }
+                            turso_core::StepResult::Done => {
+                                break;
+                            }
+                            turso_core::StepResult::Interrupt | turso_core::StepResult::Busy => {
+                                unreachable!();
+                            }
+                        }
+                    }
+                    stmt.reset();
+                });
+            },
+        );
+
+        if enable_rusqlite {
+            let sqlite_conn = rusqlite_open();
+
+            group.bench_with_input(
+                BenchmarkId::new("sqlite_execute_select_text", name),
+                &query,
+                |b, query| {
+                    let mut stmt = sqlite_conn.prepare(query).unwrap();
+                    b.iter(|| {
+                        let mut rows = stmt.raw_query();
+                        while let Some(row) = rows.next().unwrap() {
+                            black_box(row);
+                        }
+                    });
+                },
+            );
+        }
+    }
+
+    group.finish();
+}
+
+fn bench_execute_point_lookup(criterion: &mut Criterion) {
+    // https://github.com/tursodatabase/turso/issues/174
+    // The rusqlite benchmark crashes on Mac M1 when using the flamegraph features
+    let enable_rusqlite = std::env::var("DISABLE_RUSQLITE_BENCHMARK").is_err();
+
+    #[allow(clippy::arc_with_non_send_sync)]
+    let io = Arc::new(PlatformIO::new().unwrap());
+    let db = Database::open_file(io.clone(), "../testing/testing.db").unwrap();
+    let limbo_conn = db.connect().unwrap();
+
+    let mut group = criterion.benchmark_group("Execute `SELECT * FROM users WHERE id = ?`");
+
+    group.bench_function("limbo_execute_point_lookup", |b| {
+        let mut stmt = limbo_conn
+            .prepare("SELECT * FROM users WHERE id = ?")
+            .unwrap();
+        let mut id = 0;
+        b.iter(|| {
+            id = id % 10000 + 1;
+            stmt.bind_at(
+                std::num::NonZero::new(1).unwrap(),
+                turso_core::Value::Integer(id),
+            );
+            loop {
+                match stmt.step().unwrap() {
+                    turso_core::StepResult::Row => {
+                        black_box(stmt.row());
+                    }
+                    turso_core::StepResult::IO => {
+                        db.io.step().unwrap();
+                    }
+                    turso_core::StepResult::Done => {
+                        break;
+                    }
+                    turso_core::StepResult::Interrupt | turso_core::StepResult::Busy => {
+                        unreachable!();
+                    }
+                }
+            }
+            stmt.reset();
+        });
+    });
+
+    if enable_rusqlite {
+        let sqlite_conn = rusqlite_open();
+
+        group.bench_function("sqlite_execute_point_lookup", |b| {
+            let mut stmt = sqlite_conn
+                .prepare("SELECT * FROM users WHERE id = ?")
+                .unwrap();
+            let mut id = 0;
+            b.iter(|| {
+                id = id % 10000 + 1;
+                stmt.raw_bind_parameter(1, id).unwrap();
+                let mut rows = stmt.raw_query();
+                while let Some(row) = rows.next().unwrap() {
+                    black_box(row);
+                }
+            });
+        });
+    }
+
+    group.finish();
+}
+
 fn bench_insert_rows(criterion: &mut Criterion) {
     // The rusqlite benchmark crashes on Mac M1 when using the flamegraph features
     let enable_rusqlite = std::env::var("DISABLE_RUSQLITE_BENCHMARK").is_err();
@@ -1019,6 +1158,6 @@ fn bench_insert_randomblob(criterion: &mut Criterion) {
 criterion_group! {
     name = benches;
     config = Criterion::default().with_profiler(PProfProfiler::new(100, Output::Flamegraph(None)));
-    targets = bench_open, bench_alter, bench_prepare_query, bench_execute_select_1, bench_execute_select_rows, bench_execute_select_count, bench_insert_rows, bench_concurrent_writes, bench_insert_randomblob
+    targets = bench_open, bench_alter, bench_prepare_query, bench_execute_select_1, bench_execute_select_rows, bench_execute_select_count, bench_execute_select_text, bench_execute_point_lookup, bench_insert_rows, bench_concurrent_writes, bench_insert_randomblob
 }
 criterion_main!(benches);
@@ -0,0 +1,396 @@
+use std::sync::Arc;
+
+use turso_ext::{
+    Connection, ConstraintInfo, ConstraintOp, ConstraintUsage, ExtensionApi, IndexInfo,
+    OrderByInfo, ResultCode, VTabCursor, VTabKind, VTabModule, VTabModuleDerive, VTable, Value,
+};
+
+pub fn register_extension(ext_api: &mut ExtensionApi) {
+    unsafe {
+        CArrayVTabModule::register_CArrayVTabModule(ext_api);
+    }
+}
+
+/// Prefix of the blobs produced by [CArray::to_blob], so that `carray` can tell them apart
+/// from arbitrary blobs.
+const MAGIC: &[u8] = b"carray";
+
+const TYPE_INTEGER: u8 = b'i';
+const TYPE_FLOAT: u8 = b'f';
+const TYPE_TEXT: u8 = b't';
+const TYPE_BLOB: u8 = b'b';
+
+/// An array of values that can be bound as a single parameter and read back as rows by the
+/// `carray` table-valued function, e.g. `SELECT * FROM users WHERE id IN carray(?1)`.
+///
+/// Unlike SQLite's carray, which binds a pointer, the array is bound as a blob holding a copy
+/// of the values, so the caller doesn't need to keep it alive while the statement runs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CArray {
+    Integers(Vec<i64>),
+    Floats(Vec<f64>),
+    Texts(Vec<String>),
+    Blobs(Vec<Vec<u8>>),
+}
+
+impl CArray {
+    /// Encodes the array as the blob accepted by `carray`.
+    ///
+    /// The blob is [MAGIC], a type byte, and then the elements: 8 little-endian bytes for
+    /// integers and floats, or a 4-byte little-endian length followed by the bytes for texts
+    /// and blobs.
+    pub fn to_blob(&self) -> Vec<u8> {
+        let mut blob = MAGIC.to_vec();
+        match self {
+            CArray::Integers(values) => {
+                blob.push(TYPE_INTEGER);
+                for value in values {
+                    blob.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+            CArray::Floats(values) => {
+                blob.push(TYPE_FLOAT);
+                for value in values {
+                    blob.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+            CArray::Texts(values) => {
+                blob.push(TYPE_TEXT);
+                for value in values {
+                    push_with_length(&mut blob, value.as_bytes());
+                }
+            }
+            CArray::Blobs(values) => {
+                blob.push(TYPE_BLOB);
+                for value in values {
+                    push_with_length(&mut blob, value);
+                }
+            }
+        }
+        blob
+    }
+
+    /// Decodes a blob produced by [CArray::to_blob], returning `None` if it isn't one.
+    pub fn from_blob(blob: &[u8]) -> Option<Self> {
+        let rest = blob.strip_prefix(MAGIC)?;
+        let (&kind, mut data) = rest.split_first()?;
+        match kind {
+            TYPE_INTEGER | TYPE_FLOAT => {
+                if data.len() % 8 != 0 {
+                    return None;
+                }
+                let words = data
+                    .chunks_exact(8)
+                    .map(|chunk| chunk.try_into().expect("chunks are 8 bytes"));
+                Some(if kind == TYPE_INTEGER {
+                    CArray::Integers(words.map(i64::from_le_bytes).collect())
+                } else {
+                    CArray::Floats(words.map(f64::from_le_bytes).collect())
+                })
+            }
+            TYPE_TEXT | TYPE_BLOB => {
+                let mut values = Vec::new();
+                while !data.is_empty() {
+                    let (len, rest) = data.split_first_chunk::<4>()?;
+                    let len = u32::from_le_bytes(*len) as usize;
+                    if rest.len() < len {
+                        return None;
+                    }
+                    let (value, rest) = rest.split_at(len);
+                    values.push(value.to_vec());
+                    data = rest;
+                }
+                if kind == TYPE_BLOB {
+                    return Some(CArray::Blobs(values));
+                }
+                let texts = values
+                    .into_iter()
+                    .map(String::from_utf8)
+                    .collect::<Result<_, _>>()
+                    .ok()?;
+                Some(CArray::Texts(texts))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            CArray::Integers(values) => values.len(),
+            CArray::Floats(values) => values.len(),
+            CArray::Texts(values) => values.len(),
+            CArray::Blobs(values) => values.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn value(&self, idx: usize) -> Value {
+        match self {
+            CArray::Integers(values) => Value::from_integer(values[idx]),
+            CArray::Floats(values) => Value::from_float(values[idx]),
+            CArray::Texts(values) => Value::from_text(values[idx].clone()),
+            CArray::Blobs(values) => Value::from_blob(values[idx].clone()),
+        }
+    }
+}
+
+fn push_with_length(blob: &mut Vec<u8>, bytes: &[u8]) {
+    blob.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    blob.extend_from_slice(bytes);
+}
+
+impl From<Vec<i64>> for CArray {
+    fn from(values: Vec<i64>) -> Self {
+        CArray::Integers(values)
+    }
+}
+
+impl From<&[i64]> for CArray {
+    fn from(values: &[i64]) -> Self {
+        CArray::Integers(values.to_vec())
+    }
+}
+
+impl From<Vec<f64>> for CArray {
+    fn from(values: Vec<f64>) -> Self {
+        CArray::Floats(values)
+    }
+}
+
+impl From<&[f64]> for CArray {
+    fn from(values: &[f64]) -> Self {
+        CArray::Floats(values.to_vec())
+    }
+}
+
+impl From<Vec<String>> for CArray {
+    fn from(values: Vec<String>) -> Self {
+        CArray::Texts(values)
+    }
+}
+
+impl From<&[String]> for CArray {
+    fn from(values: &[String]) -> Self {
+        CArray::Texts(values.to_vec())
+    }
+}
+
+impl From<Vec<&str>> for CArray {
+    fn from(values: Vec<&str>) -> Self {
+        CArray::Texts(values.into_iter().map(String::from).collect())
+    }
+}
+
+impl From<&[&str]> for CArray {
+    fn from(values: &[&str]) -> Self {
+        CArray::Texts(values.iter().map(|&value| value.to_string()).collect())
+    }
+}
+
+impl From<Vec<Vec<u8>>> for CArray {
+    fn from(values: Vec<Vec<u8>>) -> Self {
+        CArray::Blobs(values)
+    }
+}
+
+/// A table-valued function returning the elements of an array bound with [CArray::to_blob]
+#[derive(Debug, VTabModuleDerive, Default)]
+struct CArrayVTabModule;
+
+impl VTabModule for CArrayVTabModule {
+    type Table = CArrayTable;
+    const NAME: &'static str = "carray";
+    const VTAB_KIND: VTabKind = VTabKind::TableValuedFunction;
+
+    fn create(_args: &[Value]) -> Result<(String, Self::Table), ResultCode> {
+        let schema = "CREATE TABLE carray (
+            value,
+            pointer HIDDEN
+        )"
+        .into();
+        Ok((schema, CArrayTable {}))
+    }
+}
+
+struct CArrayTable {}
+
+impl VTable for CArrayTable {
+    type Cursor = CArrayCursor;
+    type Error = ResultCode;
+
+    fn open(&self, _conn: Option<Arc<Connection>>) -> Result<Self::Cursor, Self::Error> {
+        Ok(CArrayCursor {
+            array: CArray::Integers(Vec::new()),
+            current: 0,
+        })
+    }
+
+    fn best_index(
+        constraints: &[ConstraintInfo],
+        _order_by: &[OrderByInfo],
+    ) -> Result<IndexInfo, ResultCode> {
+        const POINTER_COLUMN_INDEX: u32 = 1;
+
+        let pointer = constraints
+            .iter()
+            .position(|c| c.column_index == POINTER_COLUMN_INDEX && c.op == ConstraintOp::Eq)
+            .ok_or(ResultCode::InvalidArgs)?;
+        if !constraints[pointer].usable {
+            return Err(ResultCode::ConstraintViolation);
+        }
+
+        let constraint_usages = (0..constraints.len())
+            .map(|idx| {
+                let argv_index = (idx == pointer).then_some(1);
+                ConstraintUsage {
+                    argv_index,
+                    omit: argv_index.is_some(),
+                }
+            })
+            .collect();
+
+        Ok(IndexInfo {
+            idx_num: 1,
+            idx_str: Some("1".to_string()),
+            constraint_usages,
+            ..Default::default()
+        })
+    }
+}
+
+/// The cursor for iterating over the elements of the array
+#[derive(Debug)]
+struct CArrayCursor {
+    array: CArray,
+    current: usize,
+}
+
+impl VTabCursor for CArrayCursor {
+    type Error = ResultCode;
+
+    fn filter(&mut self, args: &[Value], _idx_info: Option<(&str, i32)>) -> ResultCode {
+        let Some(array) = args
+            .first()
+            .and_then(|arg| arg.to_blob())
+            .and_then(|blob| CArray::from_blob(&blob))
+        else {
+            return ResultCode::InvalidArgs;
+        };
+        self.array = array;
+        self.current = 0;
+        if self.eof() {
+            return ResultCode::EOF;
+        }
+        ResultCode::OK
+    }
+
+    fn next(&mut self) -> ResultCode {
+        if self.eof() {
+            return ResultCode::EOF;
+        }
+        self.current += 1;
+        if self.eof() {
+            return ResultCode::EOF;
+        }
+        ResultCode::OK
+    }
+
+    fn eof(&self) -> bool {
+        self.current >= self.array.len()
+    }
+
+    fn column(&self, idx: u32) -> Result<Value, Self::Error> {
+        Ok(match idx {
+            0 if !self.eof() => self.array.value(self.current),
+            _ => Value::null(),
+        })
+    }
+
+    fn rowid(&self) -> i64 {
+        self.current as i64 + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blob_round_trip() {
+        let arrays = [
+            CArray::from(vec![1, -2, i64::MAX]),
+            CArray::from(vec![0.5, -1e300]),
+            CArray::from(vec!["a", "", "héllo"]),
+            CArray::from(vec![vec![0u8, 1], vec![]]),
+            CArray::Integers(vec![]),
+        ];
+        for array in arrays {
+            assert_eq!(CArray::from_blob(&array.to_blob()), Some(array));
+        }
+    }
+
+    #[test]
+    fn test_invalid_blobs() {
+        let mut truncated = CArray::from(vec!["abc"]).to_blob();
+        truncated.pop();
+        let mut misaligned = CArray::from(vec![1i64]).to_blob();
+        misaligned.push(0);
+        for blob in [
+            b"".as_slice(),
+            b"carray",
+            b"carrayx",
+            b"not an array",
+            &truncated,
+            &misaligned,
+        ] {
+            assert_eq!(CArray::from_blob(blob), None);
+        }
+    }
+
+    #[test]
+    fn test_cursor() {
+        let mut cursor = CArrayTable {}.open(None).unwrap();
+        let arg = Value::from_blob(CArray::from(vec![10i64, 20]).to_blob());
+        assert_eq!(cursor.filter(&[arg], Some(("1", 1))), ResultCode::OK);
+        assert_eq!(cursor.column(0).unwrap().to_integer(), Some(10));
+        assert_eq!(cursor.rowid(), 1);
+        assert_eq!(cursor.next(), ResultCode::OK);
+        assert_eq!(cursor.column(0).unwrap().to_integer(), Some(20));
+        assert_eq!(cursor.next(), ResultCode::EOF);
+        assert!(cursor.eof());
+
+        let empty = Value::from_blob(CArray::Texts(vec![]).to_blob());
+        assert_eq!(cursor.filter(&[empty], Some(("1", 1))), ResultCode::EOF);
+
+        let invalid = Value::from_integer(1);
+        assert_eq!(
+            cursor.filter(&[invalid], Some(("1", 1))),
+            ResultCode::InvalidArgs
+        );
+    }
+
+    #[test]
+    fn test_best_index_requires_pointer() {
+        let constraint = |column_index, usable| ConstraintInfo {
+            column_index,
+            op: ConstraintOp::Eq,
+            usable,
+            index: 0,
+        };
+
+        let index_info =
+            CArrayTable::best_index(&[constraint(0, true), constraint(1, true)], &[]).unwrap();
+        assert_eq!(index_info.constraint_usages[0].argv_index, None);
+        assert_eq!(index_info.constraint_usages[1].argv_index, Some(1));
+        assert!(index_info.constraint_usages[1].omit);
+
+        let result = CArrayTable::best_index(&[constraint(0, true)], &[]);
+        assert!(matches!(result, Err(ResultCode::InvalidArgs)));
+
+        let result = CArrayTable::best_index(&[constraint(1, false)], &[]);
+        assert!(matches!(result, Err(ResultCode::ConstraintViolation)));
+    }
+}
@@ -0,0 +1,387 @@
+//! `dbstat` eponymous virtual table: https://www.sqlite.org/dbstat.html
+//!
+//! Walks every btree of the database and reports page-level space usage, one row per page
+//! (or one row per btree when the hidden `aggregate` column is constrained to true).
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+use turso_ext::{
+    ConstraintInfo, ConstraintOp, ConstraintUsage, IndexInfo, OrderByInfo, ResultCode,
+};
+
+use crate::{
+    schema::Table,
+    storage::{
+        pager::{PageRef, Pager},
+        sqlite3_ondisk::{BTreeCell, PageType},
+    },
+    vtab::{InternalVirtualTable, InternalVirtualTableCursor},
+    Connection, LimboError, Value,
+};
+
+const COL_NAME: usize = 0;
+const COL_PATH: usize = 1;
+const COL_PAGENO: usize = 2;
+const COL_PAGETYPE: usize = 3;
+const COL_NCELL: usize = 4;
+const COL_PAYLOAD: usize = 5;
+const COL_UNUSED: usize = 6;
+const COL_MX_PAYLOAD: usize = 7;
+const COL_PGOFFSET: usize = 8;
+const COL_PGSIZE: usize = 9;
+const COL_SCHEMA: usize = 10;
+const COL_AGGREGATE: usize = 11;
+
+/// idx_num bit set when the `schema` hidden column is passed to filter
+const IDX_SCHEMA: i32 = 1;
+/// idx_num bit set when the `aggregate` hidden column is passed to filter
+const IDX_AGGREGATE: i32 = 2;
+
+#[derive(Debug)]
+pub struct DbStatVirtualTable;
+
+impl InternalVirtualTable for DbStatVirtualTable {
+    fn name(&self) -> String {
+        "dbstat".to_owned()
+    }
+
+    fn open(
+        &self,
+        conn: Arc<Connection>,
+    ) -> crate::Result<Arc<RwLock<dyn InternalVirtualTableCursor>>> {
+        Ok(Arc::new(RwLock::new(DbStatCursor {
+            conn,
+            rows: Vec::new(),
+            idx: 0,
+            aggregate: false,
+        })))
+    }
+
+    fn best_index(
+        &self,
+        constraints: &[ConstraintInfo],
+        _order_by: &[OrderByInfo],
+    ) -> Result<IndexInfo, ResultCode> {
+        let mut usages = vec![
+            ConstraintUsage {
+                argv_index: None,
+                omit: false
+            };
+            constraints.len()
+        ];
+        let find = |column: usize| {
+            constraints.iter().position(|c| {
+                c.usable && c.op == ConstraintOp::Eq && c.column_index as usize == column
+            })
+        };
+        let mut idx_num = 0;
+        let mut argc = 0;
+        for (column, bit) in [(COL_SCHEMA, IDX_SCHEMA), (COL_AGGREGATE, IDX_AGGREGATE)] {
+            if let Some(i) = find(column) {
+                argc += 1;
+                idx_num |= bit;
+                usages[i] = ConstraintUsage {
+                    argv_index: Some(argc),
+                    omit: true,
+                };
+            }
+        }
+        Ok(IndexInfo {
+            idx_num,
+            idx_str: None,
+            order_by_consumed: false,
+            estimated_cost: 1_000_000.0,
+            estimated_rows: 1_000,
+            constraint_usages: usages,
+        })
+    }
+
+    fn sql(&self) -> String {
+        "CREATE TABLE x(
+            name TEXT,               -- name of table or index
+            path TEXT,               -- path to page from root
+            pageno INTEGER,          -- page number, or page count in aggregate mode
+            pagetype TEXT,           -- 'internal', 'leaf' or 'overflow'
+            ncell INTEGER,           -- cells on page (0 for overflow pages)
+            payload INTEGER,         -- bytes of payload on this page
+            unused INTEGER,          -- bytes of unused space on this page
+            mx_payload INTEGER,      -- largest payload size of all cells on this page
+            pgoffset INTEGER,        -- offset of page in file
+            pgsize INTEGER,          -- size of the page, or total size in aggregate mode
+            schema TEXT HIDDEN,      -- 1st input parameter: database schema being analyzed
+            aggregate BOOLEAN HIDDEN -- 2nd input parameter: one row per btree if true
+        );"
+        .to_owned()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct DbStatRow {
+    name: String,
+    /// None in aggregate mode
+    path: Option<String>,
+    pageno: i64,
+    /// None in aggregate mode
+    pagetype: Option<&'static str>,
+    ncell: i64,
+    payload: i64,
+    unused: i64,
+    mx_payload: i64,
+    /// None in aggregate mode
+    pgoffset: Option<i64>,
+    pgsize: i64,
+}
+
+pub struct DbStatCursor {
+    conn: Arc<Connection>,
+    rows: Vec<DbStatRow>,
+    idx: usize,
+    aggregate: bool,
+}
+
+impl DbStatCursor {
+    fn collect_rows(&self) -> crate::Result<Vec<DbStatRow>> {
+        let pager = self.conn.pager.load().clone();
+        let mut btrees = Vec::new();
+        {
+            let schema = self.conn.schema.read();
+            for table in schema.tables.values() {
+                let Table::BTree(table) = table.as_ref() else {
+                    continue;
+                };
+                if table.root_page > 0 {
+                    btrees.push((table.name.clone(), table.root_page));
+                }
+                for index in schema.get_indices(&table.name) {
+                    if index.root_page > 0 {
+                        btrees.push((index.name.clone(), index.root_page));
+                    }
+                }
+            }
+        }
+        btrees.sort();
+
+        let walker = BTreeWalker {
+            pager: &pager,
+            usable_space: pager.usable_space(),
+            page_size: pager.get_page_size_unchecked().get() as i64,
+        };
+        let mut rows = Vec::new();
+        for (name, root_page) in btrees {
+            let start = rows.len();
+            walker.walk(&name, root_page, "/".to_owned(), &mut rows)?;
+            if self.aggregate {
+                let pages = rows.split_off(start);
+                rows.push(aggregate(name, &pages));
+            }
+        }
+        Ok(rows)
+    }
+}
+
+fn aggregate(name: String, pages: &[DbStatRow]) -> DbStatRow {
+    DbStatRow {
+        name,
+        path: None,
+        pageno: pages.len() as i64,
+        pagetype: None,
+        ncell: pages.iter().map(|p| p.ncell).sum(),
+        payload: pages.iter().map(|p| p.payload).sum(),
+        unused: pages.iter().map(|p| p.unused).sum(),
+        mx_payload: pages.iter().map(|p| p.mx_payload).max().unwrap_or(0),
+        pgoffset: None,
+        pgsize: pages.iter().map(|p| p.pgsize).sum(),
+    }
+}
+
+struct BTreeWalker<'a> {
+    pager: &'a Arc<Pager>,
+    usable_space: usize,
+    page_size: i64,
+}
+
+impl BTreeWalker<'_> {
+    fn read_page(&self, page_idx: i64) -> crate::Result<PageRef> {
+        let (page, c) = self.pager.read_page(page_idx)?;
+        if let Some(c) = c {
+            self.pager.io.wait_for_completion(c)?;
+        }
+        Ok(page)
+    }
+
+    fn row(&self, name: &str, path: String, pageno: i64, pagetype: &'static str) -> DbStatRow {
+        DbStatRow {
+            name: name.to_owned(),
+            path: Some(path),
+            pageno,
+            pagetype: Some(pagetype),
+            ncell: 0,
+            payload: 0,
+            unused: 0,
+            mx_payload: 0,
+            pgoffset: Some((pageno - 1) * self.page_size),
+            pgsize: self.page_size,
+        }
+    }
+
+    /// Appends rows for the page, the overflow pages of its cells and then, recursively, its children
+    fn walk(
+        &self,
+        name: &str,
+        page_idx: i64,
+        path: String,
+        rows: &mut Vec<DbStatRow>,
+    ) -> crate::Result<()> {
+        let page = self.read_page(page_idx)?;
+        let contents = page.get_contents();
+        let Some(page_type) = contents.maybe_page_type() else {
+            return Err(LimboError::Corrupt(format!(
+                "dbstat: page {page_idx} of {name} is not a btree page"
+            )));
+        };
+        let is_leaf = matches!(page_type, PageType::TableLeaf | PageType::IndexLeaf);
+        let ncell = contents.cell_count();
+
+        // unallocated region between cell pointers and cell content, fragments and freeblocks
+        let header_end = contents.offset() + contents.header_size() + 2 * ncell;
+        let mut unused = (contents.cell_content_area() as usize).saturating_sub(header_end)
+            + contents.num_frag_free_bytes() as usize;
+        let mut freeblock = contents.first_freeblock();
+        while freeblock != 0 {
+            let offset = freeblock as usize;
+            unused += contents.read_u16_no_offset(offset + 2) as usize;
+            freeblock = contents.read_u16_no_offset(offset);
+        }
+
+        let mut row = self.row(
+            name,
+            path.clone(),
+            page_idx,
+            if is_leaf { "leaf" } else { "internal" },
+        );
+        row.ncell = ncell as i64;
+        row.unused = unused as i64;
+
+        let mut children = Vec::new();
+        let mut overflows = Vec::new();
+        for cell_idx in 0..ncell {
+            let (payload, payload_size, first_overflow_page) =
+                match contents.cell_get(cell_idx, self.usable_space)? {
+                    BTreeCell::TableInteriorCell(cell) => {
+                        children.push(cell.left_child_page);
+                        continue;
+                    }
+                    BTreeCell::IndexInteriorCell(cell) => {
+                        children.push(cell.left_child_page);
+                        (cell.payload, cell.payload_size, cell.first_overflow_page)
+                    }
+                    BTreeCell::TableLeafCell(cell) => {
+                        (cell.payload, cell.payload_size, cell.first_overflow_page)
+                    }
+                    BTreeCell::IndexLeafCell(cell) => {
+                        (cell.payload, cell.payload_size, cell.first_overflow_page)
+                    }
+                };
+            row.payload += payload.len() as i64;
+            row.mx_payload = row.mx_payload.max(payload_size as i64);
+            if let Some(first_overflow_page) = first_overflow_page {
+                let remaining = payload_size as usize - payload.len();
+                overflows.push((cell_idx, first_overflow_page, remaining));
+            }
+        }
+        if let Some(rightmost) = contents.rightmost_pointer() {
+            children.push(rightmost);
+        }
+        rows.push(row);
+
+        for (cell_idx, first_overflow_page, mut remaining) in overflows {
+            let mut overflow_page = first_overflow_page;
+            let mut overflow_idx = 0;
+            while overflow_page != 0 && remaining > 0 {
+                let page = self.read_page(overflow_page as i64)?;
+                let next = page.get_contents().read_u32_no_offset(0);
+                let local = remaining.min(self.usable_space - 4);
+                let mut row = self.row(
+                    name,
+                    format!("{path}{cell_idx:03x}+{overflow_idx:06x}"),
+                    overflow_page as i64,
+                    "overflow",
+                );
+                row.payload = local as i64;
+                row.unused = (self.usable_space - 4 - local) as i64;
+                rows.push(row);
+                remaining -= local;
+                overflow_page = next;
+                overflow_idx += 1;
+            }
+        }
+
+        for (child_idx, child) in children.into_iter().enumerate() {
+            self.walk(name, child as i64, format!("{path}{child_idx:03x}/"), rows)?;
+        }
+        Ok(())
+    }
+}
+
+impl InternalVirtualTableCursor for DbStatCursor {
+    fn next(&mut self) -> Result<bool, LimboError> {
+        self.idx += 1;
+        Ok(self.idx < self.rows.len())
+    }
+
+    fn rowid(&self) -> i64 {
+        self.idx as i64 + 1
+    }
+
+    fn column(&self, column: usize) -> Result<Value, LimboError> {
+        let Some(row) = self.rows.get(self.idx) else {
+            return Ok(Value::Null);
+        };
+        let optional_integer = |v: Option<i64>| v.map_or(Value::Null, Value::Integer);
+        Ok(match column {
+            COL_NAME => Value::build_text(row.name.clone()),
+            COL_PATH => row.path.clone().map_or(Value::Null, Value::build_text),
+            COL_PAGENO => Value::Integer(row.pageno),
+            COL_PAGETYPE => row.pagetype.map_or(Value::Null, Value::build_text),
+            COL_NCELL => Value::Integer(row.ncell),
+            COL_PAYLOAD => Value::Integer(row.payload),
+            COL_UNUSED => Value::Integer(row.unused),
+            COL_MX_PAYLOAD => Value::Integer(row.mx_payload),
+            COL_PGOFFSET => optional_integer(row.pgoffset),
+            COL_PGSIZE => Value::Integer(row.pgsize),
+            COL_SCHEMA => Value::build_text("main"),
+            COL_AGGREGATE => Value::Integer(self.aggregate as i64),
+            _ => Value::Null,
+        })
+    }
+
+    fn filter(
+        &mut self,
+        args: &[Value],
+        _idx_str: Option<String>,
+        idx_num: i32,
+    ) -> Result<bool, LimboError> {
+        let mut args = args.iter();
+        self.rows.clear();
+        self.idx = 0;
+        if idx_num & IDX_SCHEMA != 0 {
+            match args.next() {
+                Some(Value::Null) | None => {}
+                Some(Value::Text(schema)) if schema.as_str().eq_ignore_ascii_case("main") => {}
+                // like SQLite, an unknown schema yields no rows rather than an error
+                Some(_) => return Ok(false),
+            }
+        }
+        self.aggregate = false;
+        if idx_num & IDX_AGGREGATE != 0 {
+            self.aggregate = match args.next() {
+                Some(Value::Integer(i)) => *i != 0,
+                Some(Value::Float(f)) => *f != 0.0,
+                _ => false,
+            };
+        }
+        self.rows = self.collect_rows()?;
+        self.idx = 0;
+        Ok(!self.rows.is_empty())
+    }
+}
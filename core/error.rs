@@ -1,6 +1,7 @@
 use thiserror::Error;
 
 use crate::storage::page_cache::CacheError;
+use crate::types::ValueType;
 
 #[derive(Debug, Clone, Error, miette::Diagnostic)]
 pub enum LimboError {
@@ -43,8 +44,11 @@ pub enum LimboError {
     InvalidArgument(String),
     #[error("Invalid formatter supplied: {0}")]
     InvalidFormatter(String),
-    #[error("Runtime error: {0}")]
-    Constraint(String),
+    /// A constraint was violated. `code` is the SQLite extended result code, e.g.
+    /// [SQLITE_CONSTRAINT_UNIQUE], or the primary code [SQLITE_CONSTRAINT] if no
+    /// extended code applies.
+    #[error("Runtime error: {message}")]
+    Constraint { code: usize, message: String },
     #[error("Extension error: {0}")]
     ExtensionError(String),
     #[error("Runtime error: integer overflow")]
@@ -79,12 +83,50 @@ pub enum LimboError {
     InvalidColumnType,
     #[error("Invalid blob size, expected {0}")]
     InvalidBlobSize(usize),
+    #[error("Cannot convert {0} value to {1}")]
+    InvalidConversion(ValueType, &'static str),
     #[error("Planning error: {0}")]
     PlanningError(String),
     #[error("Checkpoint failed: {0}")]
     CheckpointFailed(String),
 }
 
+impl LimboError {
+    /// The SQLite extended result code that corresponds to this error, e.g.
+    /// [SQLITE_CONSTRAINT_UNIQUE] for a duplicate key. Errors without a more specific
+    /// equivalent map to [SQLITE_ERROR].
+    pub fn extended_result_code(&self) -> usize {
+        match self {
+            LimboError::Constraint { code, .. } => *code,
+            LimboError::Corrupt(_) => SQLITE_CORRUPT,
+            LimboError::NotADB => SQLITE_NOTADB,
+            LimboError::DatabaseFull(_) => SQLITE_FULL,
+            LimboError::InternalError(_) | LimboError::Page1NotAlloc => SQLITE_INTERNAL,
+            LimboError::CompletionError(_) => SQLITE_IOERR,
+            LimboError::TableLocked => SQLITE_LOCKED,
+            LimboError::ReadOnly => SQLITE_READONLY,
+            LimboError::Busy => SQLITE_BUSY,
+            LimboError::BusySnapshot => SQLITE_BUSY_SNAPSHOT,
+            LimboError::Interrupt => SQLITE_INTERRUPT,
+            LimboError::SchemaUpdated => SQLITE_SCHEMA,
+            LimboError::InvalidColumnType | LimboError::InvalidConversion(..) => SQLITE_MISMATCH,
+            _ => SQLITE_ERROR,
+        }
+    }
+
+    /// The SQLite primary result code that corresponds to this error, i.e. the low byte of
+    /// [LimboError::extended_result_code].
+    pub fn result_code(&self) -> usize {
+        self.extended_result_code() & 0xff
+    }
+
+    /// Converts an error from parsing `sql` into a [LimboError::ParseError] whose message gives
+    /// the line and column of the offending token and shows it underlined in `sql`.
+    pub fn from_parse_error(err: turso_parser::error::Error, sql: &str) -> Self {
+        Self::ParseError(err.display_with_source(sql))
+    }
+}
+
 // We only propagate the error kind so we can avoid string allocation in hot path and copying/cloning enums is cheaper
 impl From<std::io::Error> for LimboError {
     fn from(value: std::io::Error) -> Self {
@@ -163,7 +205,10 @@ macro_rules! bail_corrupt_error {
 #[macro_export]
 macro_rules! bail_constraint_error {
     ($($arg:tt)*) => {
-        return $crate::error::cold_return(Err($crate::error::LimboError::Constraint(format!($($arg)*))))
+        return $crate::error::cold_return(Err($crate::error::LimboError::Constraint {
+            code: $crate::error::SQLITE_CONSTRAINT,
+            message: format!($($arg)*),
+        }))
     };
 }
 
@@ -173,10 +218,21 @@ impl From<turso_ext::ResultCode> for LimboError {
     }
 }
 
+pub const SQLITE_ERROR: usize = 1;
+pub const SQLITE_INTERNAL: usize = 2;
+pub const SQLITE_BUSY: usize = 5;
+pub const SQLITE_LOCKED: usize = 6;
+pub const SQLITE_READONLY: usize = 8;
+pub const SQLITE_INTERRUPT: usize = 9;
+pub const SQLITE_IOERR: usize = 10;
+pub const SQLITE_CORRUPT: usize = 11;
+pub const SQLITE_FULL: usize = 13; // we want this in autoincrement - incase if user inserts max allowed int
+pub const SQLITE_SCHEMA: usize = 17;
 pub const SQLITE_CONSTRAINT: usize = 19;
+pub const SQLITE_MISMATCH: usize = 20;
+pub const SQLITE_NOTADB: usize = 26;
+pub const SQLITE_BUSY_SNAPSHOT: usize = SQLITE_BUSY | (2 << 8);
 pub const SQLITE_CONSTRAINT_PRIMARYKEY: usize = SQLITE_CONSTRAINT | (6 << 8);
-#[allow(dead_code)]
 pub const SQLITE_CONSTRAINT_FOREIGNKEY: usize = SQLITE_CONSTRAINT | (7 << 8);
 pub const SQLITE_CONSTRAINT_NOTNULL: usize = SQLITE_CONSTRAINT | (5 << 8);
-pub const SQLITE_FULL: usize = 13; // we want this in autoincrement - incase if user inserts max allowed int
-pub const SQLITE_CONSTRAINT_UNIQUE: usize = 2067;
+pub const SQLITE_CONSTRAINT_UNIQUE: usize = SQLITE_CONSTRAINT | (8 << 8);
@@ -2,11 +2,14 @@
 mod dynamic;
 mod vtab_xconnect;
 use crate::index_method::backing_btree::BackingBtreeIndexMethod;
+use crate::index_method::toy_vector_dense_lsh::VectorDenseLshIndexMethod;
 use crate::index_method::toy_vector_sparse_ivf::VectorSparseInvertedIndexMethod;
 use crate::index_method::{
-    BACKING_BTREE_INDEX_METHOD_NAME, TOY_VECTOR_SPARSE_IVF_INDEX_METHOD_NAME,
+    BACKING_BTREE_INDEX_METHOD_NAME, TOY_VECTOR_DENSE_LSH_INDEX_METHOD_NAME,
+    TOY_VECTOR_SPARSE_IVF_INDEX_METHOD_NAME,
 };
 use crate::schema::{Schema, Table};
+use crate::translate::collate::CollationSeq;
 #[cfg(all(target_os = "linux", feature = "io_uring", not(miri)))]
 use crate::UringIO;
 use crate::{function::ExternalFunc, Connection, Database};
@@ -21,11 +24,17 @@ use std::{
     sync::Arc,
 };
 use turso_ext::{
-    ExtensionApi, InitAggFunction, ResultCode, ScalarFunction, VTabKind, VTabModuleImpl,
+    CollationFunction, ExtensionApi, InitAggFunction, ResultCode, ScalarFunction, VTabKind,
+    VTabModuleImpl,
 };
 pub use turso_ext::{FinalizeFunction, StepFunction, Value as ExtValue, ValueType as ExtValueType};
 pub use vtab_xconnect::{execute, prepare_stmt};
 
+/// The registration function of a statically linked extension, generated by the
+/// `register_extension!` macro as `register_extension_static` when the extension is built
+/// with its `static` feature.
+pub type StaticExtension = unsafe extern "C" fn(api: &mut ExtensionApi) -> ResultCode;
+
 /// The context passed to extensions to register with Core
 /// along with the function pointers
 #[repr(C)]
@@ -83,6 +92,15 @@ pub struct VTabImpl {
     pub implementation: Arc<VTabModuleImpl>,
 }
 
+impl VTabImpl {
+    /// Whether `CREATE VIRTUAL TABLE` can use the module. Besides virtual table modules, this
+    /// includes table-valued functions that can be written to, which like SQLite's `zipfile`
+    /// are read as functions and written through tables created from their arguments.
+    pub fn can_create_table(&self) -> bool {
+        self.module_kind == VTabKind::VirtualTable || !self.implementation.readonly
+    }
+}
+
 pub(crate) unsafe extern "C" fn register_scalar_function(
     ctx: *mut c_void,
     name: *const c_char,
@@ -106,6 +124,28 @@ pub(crate) unsafe extern "C" fn register_scalar_function(
     ResultCode::OK
 }
 
+pub(crate) unsafe extern "C" fn register_collation(
+    ctx: *mut c_void,
+    name: *const c_char,
+    func: CollationFunction,
+) -> ResultCode {
+    let c_str = unsafe { CStr::from_ptr(name) };
+    let name_str = match c_str.to_str() {
+        Ok(s) => s.to_string(),
+        Err(_) => return ResultCode::InvalidArgs,
+    };
+    if ctx.is_null() {
+        return ResultCode::Error;
+    }
+    let compare = move |lhs: &str, rhs: &str| {
+        unsafe { func(lhs.as_ptr(), lhs.len(), rhs.as_ptr(), rhs.len()) }.cmp(&0)
+    };
+    match CollationSeq::register(&name_str, Arc::new(compare)) {
+        Ok(_) => ResultCode::OK,
+        Err(_) => ResultCode::Error,
+    }
+}
+
 pub(crate) unsafe extern "C" fn register_aggregate_function(
     ctx: *mut c_void,
     name: *const c_char,
@@ -172,11 +212,64 @@ impl Database {
                 TOY_VECTOR_SPARSE_IVF_INDEX_METHOD_NAME.to_string(),
                 Arc::new(VectorSparseInvertedIndexMethod),
             );
+            syms.index_methods.insert(
+                TOY_VECTOR_DENSE_LSH_INDEX_METHOD_NAME.to_string(),
+                Arc::new(VectorDenseLshIndexMethod),
+            );
             syms.index_methods.insert(
                 BACKING_BTREE_INDEX_METHOD_NAME.to_string(),
                 Arc::new(BackingBtreeIndexMethod),
             );
         }
+        #[allow(unused)]
+        let mut ext_api = self.build_builtin_ext_api();
+        let ctx = ext_api.ctx as *mut ExtensionCtx;
+
+        #[cfg(feature = "uuid")]
+        crate::uuid::register_extension(&mut ext_api);
+        #[cfg(feature = "series")]
+        crate::series::register_extension(&mut ext_api);
+        #[cfg(feature = "carray")]
+        crate::carray::register_extension(&mut ext_api);
+        #[cfg(feature = "time")]
+        crate::time::register_extension(&mut ext_api);
+        #[cfg(feature = "fs")]
+        {
+            let vfslist = add_builtin_vfs_extensions(Some(ext_api)).map_err(|e| e.to_string())?;
+            for (name, vfs) in vfslist {
+                add_vfs_module(name, vfs);
+            }
+        }
+        let _ = unsafe { Box::from_raw(ctx) };
+        Ok(())
+    }
+
+    /// Register a statically linked extension, such as one built with the `static` feature of
+    /// an extension crate, so that its functions and virtual table modules are available to
+    /// every connection opened afterwards.
+    ///
+    /// usage:
+    /// ```ignore
+    /// db.register_extension(my_extension::register_extension_static)?;
+    /// let conn = db.connect()?;
+    /// ```
+    pub fn register_extension(&self, register: StaticExtension) -> crate::Result<()> {
+        let mut ext_api = self.build_builtin_ext_api();
+        let ctx = ext_api.ctx as *mut ExtensionCtx;
+        let result_code = unsafe { register(&mut ext_api) };
+        let _ = unsafe { Box::from_raw(ctx) };
+        if result_code.is_ok() {
+            Ok(())
+        } else {
+            Err(crate::LimboError::ExtensionError(
+                "Extension registration failed".to_string(),
+            ))
+        }
+    }
+
+    /// Build the extension api context that registers symbols in the database's built-in symbol
+    /// table. The caller is responsible for freeing `ctx` after registering the extensions.
+    fn build_builtin_ext_api(&self) -> ExtensionApi {
         let syms = self.builtin_syms.data_ptr();
         // Pass the mutex pointer and the appropriate handler
         let schema_mutex_ptr = &self.schema as *const Mutex<Arc<Schema>> as *mut Mutex<Arc<Schema>>;
@@ -184,35 +277,19 @@ impl Database {
             syms,
             schema: schema_mutex_ptr as *mut c_void,
         }));
-        #[allow(unused)]
-        let mut ext_api = ExtensionApi {
+        ExtensionApi {
             ctx: ctx as *mut c_void,
             register_scalar_function,
             register_aggregate_function,
             register_vtab_module,
+            register_collation,
             #[cfg(feature = "fs")]
             vfs_interface: turso_ext::VfsInterface {
                 register_vfs: dynamic::register_vfs,
                 builtin_vfs: std::ptr::null_mut(),
                 builtin_vfs_count: 0,
             },
-        };
-
-        #[cfg(feature = "uuid")]
-        crate::uuid::register_extension(&mut ext_api);
-        #[cfg(feature = "series")]
-        crate::series::register_extension(&mut ext_api);
-        #[cfg(feature = "time")]
-        crate::time::register_extension(&mut ext_api);
-        #[cfg(feature = "fs")]
-        {
-            let vfslist = add_builtin_vfs_extensions(Some(ext_api)).map_err(|e| e.to_string())?;
-            for (name, vfs) in vfslist {
-                add_vfs_module(name, vfs);
-            }
         }
-        let _ = unsafe { Box::from_raw(ctx) };
-        Ok(())
     }
 }
 
@@ -247,6 +324,7 @@ impl Connection {
             register_scalar_function,
             register_aggregate_function,
             register_vtab_module,
+            register_collation,
             #[cfg(feature = "fs")]
             vfs_interface: turso_ext::VfsInterface {
                 register_vfs: dynamic::register_vfs,
@@ -324,6 +324,18 @@ pub enum ScalarFunc {
     ZeroBlob,
     LastInsertRowid,
     Replace,
+    #[cfg(feature = "regexp")]
+    Regexp,
+    #[cfg(feature = "uuid")]
+    Uuid4,
+    #[cfg(feature = "uuid")]
+    Uuid4Str,
+    #[cfg(feature = "uuid")]
+    Ulid,
+    #[cfg(feature = "uuid")]
+    Uuid7,
+    #[cfg(feature = "uuid")]
+    Uuid7Str,
     #[cfg(feature = "fs")]
     #[cfg(not(target_family = "wasm"))]
     LoadExtension,
@@ -391,6 +403,14 @@ impl ScalarFunc {
             ScalarFunc::ZeroBlob => true,
             ScalarFunc::LastInsertRowid => false,
             ScalarFunc::Replace => true,
+            #[cfg(feature = "regexp")]
+            ScalarFunc::Regexp => true,
+            #[cfg(feature = "uuid")]
+            ScalarFunc::Uuid4
+            | ScalarFunc::Uuid4Str
+            | ScalarFunc::Ulid
+            | ScalarFunc::Uuid7
+            | ScalarFunc::Uuid7Str => false,
             #[cfg(feature = "fs")]
             #[cfg(not(target_family = "wasm"))]
             ScalarFunc::LoadExtension => true,
@@ -459,6 +479,18 @@ impl Display for ScalarFunc {
             Self::ZeroBlob => "zeroblob".to_string(),
             Self::LastInsertRowid => "last_insert_rowid".to_string(),
             Self::Replace => "replace".to_string(),
+            #[cfg(feature = "regexp")]
+            Self::Regexp => "regexp".to_string(),
+            #[cfg(feature = "uuid")]
+            Self::Uuid4 => "uuid4".to_string(),
+            #[cfg(feature = "uuid")]
+            Self::Uuid4Str => "uuid4_str".to_string(),
+            #[cfg(feature = "uuid")]
+            Self::Ulid => "ulid".to_string(),
+            #[cfg(feature = "uuid")]
+            Self::Uuid7 => "uuid7".to_string(),
+            #[cfg(feature = "uuid")]
+            Self::Uuid7Str => "uuid7_str".to_string(),
             Self::DateTime => "datetime".to_string(),
             #[cfg(feature = "fs")]
             #[cfg(not(target_family = "wasm"))]
@@ -766,6 +798,18 @@ impl Func {
             "if" | "iif" => Ok(Self::Scalar(ScalarFunc::Iif)),
             "instr" => Ok(Self::Scalar(ScalarFunc::Instr)),
             "like" => Ok(Self::Scalar(ScalarFunc::Like)),
+            #[cfg(feature = "regexp")]
+            "regexp" => Ok(Self::Scalar(ScalarFunc::Regexp)),
+            #[cfg(feature = "uuid")]
+            "uuid4" => Ok(Self::Scalar(ScalarFunc::Uuid4)),
+            #[cfg(feature = "uuid")]
+            "uuid4_str" | "gen_random_uuid" => Ok(Self::Scalar(ScalarFunc::Uuid4Str)),
+            #[cfg(feature = "uuid")]
+            "ulid" => Ok(Self::Scalar(ScalarFunc::Ulid)),
+            #[cfg(feature = "uuid")]
+            "uuid7" => Ok(Self::Scalar(ScalarFunc::Uuid7)),
+            #[cfg(feature = "uuid")]
+            "uuid7_str" => Ok(Self::Scalar(ScalarFunc::Uuid7Str)),
             "abs" => Ok(Self::Scalar(ScalarFunc::Abs)),
             "upper" => Ok(Self::Scalar(ScalarFunc::Upper)),
             "lower" => Ok(Self::Scalar(ScalarFunc::Lower)),
@@ -250,9 +250,13 @@ fn apply_modifier(dt: &mut NaiveDateTime, modifier: &str, n_floor: &mut i64) ->
             let days_to_add = (target_day + 7 - current_day) % 7;
             *dt += TimeDelta::days(days_to_add as i64);
         }
-        Modifier::Auto => todo!(), // Will require storing info about the original arg passed when
-        Modifier::UnixEpoch => todo!(), // applying modifiers. All numbers passed to date/time/dt are
-        Modifier::JulianDay => todo!(), // assumed to be julianday, so adding these now is redundant
+        // Will require storing info about the original arg passed when applying modifiers.
+        // All numbers passed to date/time/dt are assumed to be julianday for now.
+        Modifier::Auto | Modifier::UnixEpoch | Modifier::JulianDay => {
+            return Err(InvalidModifier(format!(
+                "modifier '{modifier}' is not supported yet"
+            )));
+        }
         Modifier::Localtime => {
             let utc_dt = DateTime::<Utc>::from_naive_utc_and_offset(*dt, Utc);
             *dt = utc_dt.with_timezone(&chrono::Local).naive_local();
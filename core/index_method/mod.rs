@@ -11,10 +11,12 @@ use crate::{
 };
 
 pub mod backing_btree;
+pub mod toy_vector_dense_lsh;
 pub mod toy_vector_sparse_ivf;
 
 pub const BACKING_BTREE_INDEX_METHOD_NAME: &str = "backing_btree";
 pub const TOY_VECTOR_SPARSE_IVF_INDEX_METHOD_NAME: &str = "toy_vector_sparse_ivf";
+pub const TOY_VECTOR_DENSE_LSH_INDEX_METHOD_NAME: &str = "toy_vector_dense_lsh";
 
 /// index method "entry point" which can create attachment of the method to the table with given configuration
 /// (this trait acts like a "factory")
@@ -0,0 +1,790 @@
+use std::{
+    collections::{BTreeSet, VecDeque},
+    sync::Arc,
+};
+
+use turso_parser::ast::{self, SortOrder};
+
+use crate::{
+    index_method::{
+        open_index_cursor, open_table_cursor, parse_patterns, IndexMethod, IndexMethodAttachment,
+        IndexMethodConfiguration, IndexMethodCursor, IndexMethodDefinition,
+        BACKING_BTREE_INDEX_METHOD_NAME, TOY_VECTOR_DENSE_LSH_INDEX_METHOD_NAME,
+    },
+    return_if_io,
+    storage::btree::{BTreeCursor, BTreeKey, CursorTrait},
+    translate::collate::CollationSeq,
+    types::{IOResult, ImmutableRecord, KeyInfo, SeekKey, SeekOp, SeekResult},
+    vdbe::Register,
+    vector::{
+        operations,
+        vector_types::{Vector, VectorType},
+    },
+    Connection, LimboError, Result, Value, ValueRef,
+};
+
+const DEFAULT_BITS: u32 = 8;
+const MAX_BITS: u32 = 16;
+const DEFAULT_PROBE_DISTANCE: u32 = 1;
+const DEFAULT_SEED: u64 = 0x5eed_1a5e_d00d_cafe;
+
+/// Approximate nearest neighbor index for dense vectors based on random hyperplane LSH
+/// > CREATE INDEX t_idx ON t USING toy_vector_dense_lsh (embedding) WITH (bits = 8, probe_distance = 1)
+///
+/// Every vector is assigned to the bucket formed by the signs of its projections onto `bits` random hyperplanes,
+/// so vectors with small angle between them tend to share a bucket. Query probes all buckets within
+/// `probe_distance` (hamming distance) from the bucket of the query vector and rescores candidates exactly.
+///
+/// It accept single column which must contain vector encoded in dense format (e.g. vector32(...) or vector64(...))
+/// It can handle cosine and l2 distance queries like the following:
+/// > SELECT vector_distance_cos(embedding, ?) as d FROM t ORDER BY d LIMIT ?
+/// > SELECT vector_distance_l2(embedding, ?) as d FROM t ORDER BY d LIMIT ?
+#[derive(Debug)]
+pub struct VectorDenseLshIndexMethod;
+
+#[derive(Debug)]
+pub struct VectorDenseLshIndexMethodAttachment {
+    configuration: IndexMethodConfiguration,
+    patterns: Vec<ast::Select>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DistanceKind {
+    Cos,
+    L2,
+}
+
+#[derive(Debug)]
+enum VectorDenseLshInsertState {
+    Init,
+    Seek { key: Option<ImmutableRecord> },
+    Insert { key: Option<ImmutableRecord> },
+}
+
+#[derive(Debug)]
+enum VectorDenseLshDeleteState {
+    Init,
+    Seek { key: Option<ImmutableRecord> },
+    Next,
+    Delete,
+}
+
+#[derive(Debug, PartialEq)]
+struct FloatOrd(f64);
+
+impl Eq for FloatOrd {}
+impl PartialOrd for FloatOrd {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for FloatOrd {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+#[derive(Debug)]
+enum VectorDenseLshSearchState {
+    Init,
+    Seek {
+        buckets: Option<VecDeque<i64>>,
+        distances: Option<BTreeSet<(FloatOrd, i64)>>,
+        limit: i64,
+        key: Option<ImmutableRecord>,
+        bucket: Option<i64>,
+    },
+    Read {
+        buckets: Option<VecDeque<i64>>,
+        distances: Option<BTreeSet<(FloatOrd, i64)>>,
+        limit: i64,
+        bucket: i64,
+        current: Option<Vec<i64>>,
+    },
+    Next {
+        buckets: Option<VecDeque<i64>>,
+        distances: Option<BTreeSet<(FloatOrd, i64)>>,
+        limit: i64,
+        bucket: i64,
+        current: Option<Vec<i64>>,
+    },
+    EvaluateSeek {
+        buckets: Option<VecDeque<i64>>,
+        distances: Option<BTreeSet<(FloatOrd, i64)>>,
+        limit: i64,
+        current: Option<VecDeque<i64>>,
+        rowid: Option<i64>,
+    },
+    EvaluateRead {
+        buckets: Option<VecDeque<i64>>,
+        distances: Option<BTreeSet<(FloatOrd, i64)>>,
+        limit: i64,
+        current: Option<VecDeque<i64>>,
+        rowid: i64,
+    },
+}
+
+pub struct VectorDenseLshIndexMethodCursor {
+    configuration: IndexMethodConfiguration,
+    bits: u32,
+    probe_distance: u32,
+    seed: u64,
+    buckets_btree: String,
+    buckets_cursor: Option<BTreeCursor>,
+    main_btree: Option<BTreeCursor>,
+    query: Option<(DistanceKind, Vector<'static>)>,
+    insert_state: VectorDenseLshInsertState,
+    delete_state: VectorDenseLshDeleteState,
+    search_state: VectorDenseLshSearchState,
+    search_result: VecDeque<(i64, f64)>,
+}
+
+impl IndexMethod for VectorDenseLshIndexMethod {
+    fn attach(
+        &self,
+        configuration: &IndexMethodConfiguration,
+    ) -> Result<Arc<dyn IndexMethodAttachment>> {
+        let column = &configuration.columns[0].name;
+        let table = &configuration.table_name;
+        // pattern index is used in query_start(...) to pick the distance: cos for 0..2, l2 for 2..4
+        let patterns = [
+            format!("SELECT vector_distance_cos({column}, ?) as distance FROM {table} ORDER BY distance LIMIT ?"),
+            format!("SELECT vector_distance_cos(?, {column}) as distance FROM {table} ORDER BY distance LIMIT ?"),
+            format!("SELECT vector_distance_l2({column}, ?) as distance FROM {table} ORDER BY distance LIMIT ?"),
+            format!("SELECT vector_distance_l2(?, {column}) as distance FROM {table} ORDER BY distance LIMIT ?"),
+        ];
+        let patterns = patterns.iter().map(|p| p.as_str()).collect::<Vec<_>>();
+        Ok(Arc::new(VectorDenseLshIndexMethodAttachment {
+            configuration: configuration.clone(),
+            patterns: parse_patterns(&patterns)?,
+        }))
+    }
+}
+
+impl IndexMethodAttachment for VectorDenseLshIndexMethodAttachment {
+    fn definition<'a>(&'a self) -> IndexMethodDefinition<'a> {
+        IndexMethodDefinition {
+            method_name: TOY_VECTOR_DENSE_LSH_INDEX_METHOD_NAME,
+            index_name: &self.configuration.index_name,
+            patterns: self.patterns.as_slice(),
+            backing_btree: false,
+        }
+    }
+    fn init(&self) -> Result<Box<dyn IndexMethodCursor>> {
+        Ok(Box::new(VectorDenseLshIndexMethodCursor::new(
+            self.configuration.clone(),
+        )?))
+    }
+}
+
+impl VectorDenseLshIndexMethodCursor {
+    pub fn new(configuration: IndexMethodConfiguration) -> Result<Self> {
+        let buckets_btree = format!("{}_buckets", configuration.index_name);
+        let bits = match configuration.parameters.get("bits") {
+            Some(&Value::Integer(bits)) if (1..=MAX_BITS as i64).contains(&bits) => bits as u32,
+            Some(_) => {
+                return Err(LimboError::InvalidArgument(format!(
+                    "toy_vector_dense_lsh: bits must be an integer in range 1..={MAX_BITS}"
+                )))
+            }
+            None => DEFAULT_BITS,
+        };
+        let probe_distance = match configuration.parameters.get("probe_distance") {
+            Some(&Value::Integer(distance)) if distance >= 0 => distance.min(bits as i64) as u32,
+            Some(_) => {
+                return Err(LimboError::InvalidArgument(
+                    "toy_vector_dense_lsh: probe_distance must be a non-negative integer"
+                        .to_string(),
+                ))
+            }
+            None => DEFAULT_PROBE_DISTANCE.min(bits),
+        };
+        let seed = match configuration.parameters.get("seed") {
+            Some(&Value::Integer(seed)) => seed as u64,
+            _ => DEFAULT_SEED,
+        };
+        Ok(Self {
+            configuration,
+            bits,
+            probe_distance,
+            seed,
+            buckets_btree,
+            buckets_cursor: None,
+            main_btree: None,
+            query: None,
+            insert_state: VectorDenseLshInsertState::Init,
+            delete_state: VectorDenseLshDeleteState::Init,
+            search_state: VectorDenseLshSearchState::Init,
+            search_result: VecDeque::new(),
+        })
+    }
+
+    /// computes bucket of the vector: i-th bit is set if the vector lies on the positive side of the i-th hyperplane
+    fn bucket(&self, vector: &Vector) -> i64 {
+        let mut projections = vec![0.0f64; self.bits as usize];
+        let mut project = |dim: usize, value: f64| {
+            for (plane, projection) in projections.iter_mut().enumerate() {
+                *projection += value * hyperplane_component(self.seed, plane, dim);
+            }
+        };
+        match vector.vector_type {
+            VectorType::Float32Dense => {
+                for (dim, value) in vector.as_f32_slice().iter().enumerate() {
+                    project(dim, *value as f64);
+                }
+            }
+            VectorType::Float64Dense => {
+                for (dim, value) in vector.as_f64_slice().iter().enumerate() {
+                    project(dim, *value);
+                }
+            }
+            VectorType::Float32Sparse => {
+                unreachable!("sparse vectors are rejected by parse_vector")
+            }
+        }
+        projections
+            .iter()
+            .enumerate()
+            .filter(|(_, projection)| **projection >= 0.0)
+            .fold(0i64, |bucket, (plane, _)| bucket | (1 << plane))
+    }
+
+    /// returns all buckets within probe_distance from the given bucket ordered by hamming distance
+    fn probe_buckets(&self, bucket: i64) -> VecDeque<i64> {
+        let mut buckets = (0..(1i64 << self.bits))
+            .map(|b| ((b ^ bucket).count_ones(), b))
+            .filter(|(distance, _)| *distance <= self.probe_distance)
+            .collect::<Vec<_>>();
+        buckets.sort();
+        buckets.into_iter().map(|(_, b)| b).collect()
+    }
+
+    fn bucket_key(&self, values: &[Register]) -> Result<ImmutableRecord> {
+        let vector = parse_vector(&values[0], "first value must be dense vector")?;
+        let Some(rowid) = values[1].get_value().as_int() else {
+            return Err(LimboError::InternalError(
+                "second value must be i64 rowid".to_string(),
+            ));
+        };
+        let bucket = self.bucket(&vector);
+        Ok(ImmutableRecord::from_values(
+            &[Value::Integer(bucket), Value::Integer(rowid)],
+            2,
+        ))
+    }
+}
+
+/// deterministic pseudo-random component of the hyperplane normal in range [-1, 1)
+fn hyperplane_component(seed: u64, plane: usize, dim: usize) -> f64 {
+    // splitmix64 finalizer over (seed, plane, dim)
+    let mut z = seed
+        ^ (((plane as u64) << 32) | dim as u64)
+            .wrapping_add(1)
+            .wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0
+}
+
+fn parse_vector(register: &Register, error: &str) -> Result<Vector<'static>> {
+    let Some(vector) = register.get_value().to_blob() else {
+        return Err(LimboError::InternalError(error.to_string()));
+    };
+    let vector = Vector::from_vec(vector.to_vec())?;
+    if !matches!(
+        vector.vector_type,
+        VectorType::Float32Dense | VectorType::Float64Dense
+    ) {
+        return Err(LimboError::InternalError(error.to_string()));
+    }
+    Ok(vector)
+}
+
+fn parse_bucket_row(record: Option<&ImmutableRecord>) -> Result<(i64, i64)> {
+    let Some(record) = record else {
+        return Err(LimboError::Corrupt(
+            "buckets index corrupted: expected row".to_string(),
+        ));
+    };
+    let ValueRef::Integer(bucket) = record.get_value(0)? else {
+        return Err(LimboError::Corrupt(
+            "buckets index corrupted: expected integer".to_string(),
+        ));
+    };
+    let ValueRef::Integer(rowid) = record.get_value(1)? else {
+        return Err(LimboError::Corrupt(
+            "buckets index corrupted: expected integer".to_string(),
+        ));
+    };
+    Ok((bucket, rowid))
+}
+
+fn key_info() -> KeyInfo {
+    KeyInfo {
+        collation: CollationSeq::Binary,
+        sort_order: SortOrder::Asc,
+    }
+}
+
+impl IndexMethodCursor for VectorDenseLshIndexMethodCursor {
+    fn create(&mut self, connection: &Arc<Connection>) -> Result<IOResult<()>> {
+        let columns = &self.configuration.columns;
+        let columns = columns.iter().map(|x| x.name.as_str()).collect::<Vec<_>>();
+        let sql = format!(
+            "CREATE INDEX {} ON {} USING {} ({})",
+            self.buckets_btree,
+            self.configuration.table_name,
+            BACKING_BTREE_INDEX_METHOD_NAME,
+            columns.join(", ")
+        );
+        let mut stmt = connection.prepare(&sql)?;
+        // see toy_vector_sparse_ivf: nested statement runs inside of the parent statement subjournal
+        stmt.program.needs_stmt_subtransactions = false;
+        connection.start_nested();
+        let result = stmt.run_ignore_rows();
+        connection.end_nested();
+        result?;
+
+        Ok(IOResult::Done(()))
+    }
+
+    fn destroy(&mut self, connection: &Arc<Connection>) -> Result<IOResult<()>> {
+        let sql = format!("DROP INDEX {}", self.buckets_btree);
+        let mut stmt = connection.prepare(&sql)?;
+        connection.start_nested();
+        let result = stmt.run_ignore_rows();
+        connection.end_nested();
+        result?;
+
+        Ok(IOResult::Done(()))
+    }
+
+    fn open_read(&mut self, connection: &Arc<Connection>) -> Result<IOResult<()>> {
+        self.buckets_cursor = Some(open_index_cursor(
+            connection,
+            &self.configuration.table_name,
+            &self.buckets_btree,
+            // bucket, rowid
+            vec![key_info(), key_info()],
+        )?);
+        self.main_btree = Some(open_table_cursor(
+            connection,
+            &self.configuration.table_name,
+        )?);
+        Ok(IOResult::Done(()))
+    }
+
+    fn open_write(&mut self, connection: &Arc<Connection>) -> Result<IOResult<()>> {
+        self.buckets_cursor = Some(open_index_cursor(
+            connection,
+            &self.configuration.table_name,
+            &self.buckets_btree,
+            // bucket, rowid
+            vec![key_info(), key_info()],
+        )?);
+        Ok(IOResult::Done(()))
+    }
+
+    fn insert(&mut self, values: &[Register]) -> Result<IOResult<()>> {
+        loop {
+            tracing::debug!("insert_state: {:?}", self.insert_state);
+            match &mut self.insert_state {
+                VectorDenseLshInsertState::Init => {
+                    let key = self.bucket_key(values)?;
+                    self.insert_state = VectorDenseLshInsertState::Seek { key: Some(key) };
+                }
+                VectorDenseLshInsertState::Seek { key } => {
+                    let Some(cursor) = &mut self.buckets_cursor else {
+                        return Err(LimboError::InternalError(
+                            "buckets cursor must be opened".to_string(),
+                        ));
+                    };
+                    let Some(k) = key.as_ref() else {
+                        return Err(LimboError::InternalError(
+                            "key must be present in Seek state".to_string(),
+                        ));
+                    };
+                    return_if_io!(cursor.seek(SeekKey::IndexKey(k), SeekOp::GE { eq_only: true }));
+                    self.insert_state = VectorDenseLshInsertState::Insert { key: key.take() };
+                }
+                VectorDenseLshInsertState::Insert { key } => {
+                    let Some(cursor) = &mut self.buckets_cursor else {
+                        return Err(LimboError::InternalError(
+                            "buckets cursor must be opened".to_string(),
+                        ));
+                    };
+                    let Some(k) = key.as_ref() else {
+                        return Err(LimboError::InternalError(
+                            "key must be present in Insert state".to_string(),
+                        ));
+                    };
+                    return_if_io!(cursor.insert(&BTreeKey::IndexKey(k)));
+                    self.insert_state = VectorDenseLshInsertState::Init;
+                    return Ok(IOResult::Done(()));
+                }
+            }
+        }
+    }
+
+    fn delete(&mut self, values: &[Register]) -> Result<IOResult<()>> {
+        loop {
+            tracing::debug!("delete_state: {:?}", self.delete_state);
+            match &mut self.delete_state {
+                VectorDenseLshDeleteState::Init => {
+                    let key = self.bucket_key(values)?;
+                    self.delete_state = VectorDenseLshDeleteState::Seek { key: Some(key) };
+                }
+                VectorDenseLshDeleteState::Seek { key } => {
+                    let Some(cursor) = &mut self.buckets_cursor else {
+                        return Err(LimboError::InternalError(
+                            "buckets cursor must be opened".to_string(),
+                        ));
+                    };
+                    let Some(k) = key.as_ref() else {
+                        return Err(LimboError::InternalError(
+                            "key must be present in Seek state".to_string(),
+                        ));
+                    };
+                    let result = return_if_io!(
+                        cursor.seek(SeekKey::IndexKey(k), SeekOp::GE { eq_only: true })
+                    );
+                    match result {
+                        SeekResult::Found => {
+                            self.delete_state = VectorDenseLshDeleteState::Delete;
+                        }
+                        SeekResult::TryAdvance => {
+                            self.delete_state = VectorDenseLshDeleteState::Next;
+                        }
+                        SeekResult::NotFound => {
+                            return Err(LimboError::Corrupt("buckets index corrupted".to_string()))
+                        }
+                    }
+                }
+                VectorDenseLshDeleteState::Next => {
+                    let Some(cursor) = &mut self.buckets_cursor else {
+                        return Err(LimboError::InternalError(
+                            "buckets cursor must be opened".to_string(),
+                        ));
+                    };
+                    if !return_if_io!(cursor.next()) {
+                        return Err(LimboError::Corrupt("buckets index corrupted".to_string()));
+                    }
+                    self.delete_state = VectorDenseLshDeleteState::Delete;
+                }
+                VectorDenseLshDeleteState::Delete => {
+                    let Some(cursor) = &mut self.buckets_cursor else {
+                        return Err(LimboError::InternalError(
+                            "buckets cursor must be opened".to_string(),
+                        ));
+                    };
+                    return_if_io!(cursor.delete());
+                    self.delete_state = VectorDenseLshDeleteState::Init;
+                    return Ok(IOResult::Done(()));
+                }
+            }
+        }
+    }
+
+    fn query_start(&mut self, values: &[Register]) -> Result<IOResult<bool>> {
+        loop {
+            tracing::debug!("query_state: {:?}", self.search_state);
+            match &mut self.search_state {
+                VectorDenseLshSearchState::Init => {
+                    let Some(pattern) = values[0].get_value().as_int() else {
+                        return Err(LimboError::InternalError(
+                            "pattern index must be i64".to_string(),
+                        ));
+                    };
+                    let kind = match pattern {
+                        0 | 1 => DistanceKind::Cos,
+                        2 | 3 => DistanceKind::L2,
+                        _ => {
+                            return Err(LimboError::InternalError(format!(
+                                "unexpected pattern index: {pattern}"
+                            )))
+                        }
+                    };
+                    let vector = parse_vector(&values[1], "first value must be dense vector")?;
+                    let Some(limit) = values[2].get_value().as_int() else {
+                        return Err(LimboError::InternalError(
+                            "second value must be i64 limit parameter".to_string(),
+                        ));
+                    };
+                    let buckets = self.probe_buckets(self.bucket(&vector));
+                    tracing::debug!(
+                        "query_start: kind={:?}, bits={}, probe_distance={}, buckets={}",
+                        kind,
+                        self.bits,
+                        self.probe_distance,
+                        buckets.len()
+                    );
+                    self.query = Some((kind, vector));
+                    self.search_state = VectorDenseLshSearchState::Seek {
+                        buckets: Some(buckets),
+                        distances: Some(BTreeSet::new()),
+                        limit,
+                        key: None,
+                        bucket: None,
+                    };
+                }
+                VectorDenseLshSearchState::Seek {
+                    buckets,
+                    distances,
+                    limit,
+                    key,
+                    bucket,
+                } => {
+                    if key.is_none() {
+                        let next = buckets.as_mut().and_then(|b| b.pop_front());
+                        let Some(next) = next else {
+                            let Some(distances) = distances.take() else {
+                                return Err(LimboError::InternalError(
+                                    "distances must be present in Seek state".to_string(),
+                                ));
+                            };
+                            self.search_result = distances.iter().map(|(d, i)| (*i, d.0)).collect();
+                            self.search_state = VectorDenseLshSearchState::Init;
+                            return Ok(IOResult::Done(!self.search_result.is_empty()));
+                        };
+                        *key = Some(ImmutableRecord::from_values(&[Value::Integer(next)], 1));
+                        *bucket = Some(next);
+                    }
+                    let Some(cursor) = &mut self.buckets_cursor else {
+                        return Err(LimboError::InternalError(
+                            "cursor must be opened".to_string(),
+                        ));
+                    };
+                    let Some(k) = key.as_ref() else {
+                        return Err(LimboError::InternalError(
+                            "key must be present in Seek state".to_string(),
+                        ));
+                    };
+                    let result = return_if_io!(
+                        cursor.seek(SeekKey::IndexKey(k), SeekOp::GE { eq_only: false })
+                    );
+                    let Some(b) = bucket.take() else {
+                        return Err(LimboError::InternalError(
+                            "bucket must be present in Seek state".to_string(),
+                        ));
+                    };
+                    match result {
+                        SeekResult::Found => {
+                            self.search_state = VectorDenseLshSearchState::Read {
+                                buckets: buckets.take(),
+                                distances: distances.take(),
+                                limit: *limit,
+                                bucket: b,
+                                current: Some(Vec::new()),
+                            };
+                        }
+                        SeekResult::TryAdvance | SeekResult::NotFound => {
+                            self.search_state = VectorDenseLshSearchState::Next {
+                                buckets: buckets.take(),
+                                distances: distances.take(),
+                                limit: *limit,
+                                bucket: b,
+                                current: Some(Vec::new()),
+                            };
+                        }
+                    }
+                }
+                VectorDenseLshSearchState::Read {
+                    buckets,
+                    distances,
+                    limit,
+                    bucket,
+                    current,
+                } => {
+                    let Some(cursor) = &mut self.buckets_cursor else {
+                        return Err(LimboError::InternalError(
+                            "cursor must be opened".to_string(),
+                        ));
+                    };
+                    let record = return_if_io!(cursor.record());
+                    let (row_bucket, rowid) = parse_bucket_row(record)?;
+                    let Some(curr) = current.as_mut() else {
+                        return Err(LimboError::InternalError(
+                            "current must be present in Read state".to_string(),
+                        ));
+                    };
+                    if row_bucket != *bucket {
+                        self.search_state = VectorDenseLshSearchState::EvaluateSeek {
+                            buckets: buckets.take(),
+                            distances: distances.take(),
+                            limit: *limit,
+                            current: current.take().map(VecDeque::from),
+                            rowid: None,
+                        };
+                        continue;
+                    }
+                    curr.push(rowid);
+                    self.search_state = VectorDenseLshSearchState::Next {
+                        buckets: buckets.take(),
+                        distances: distances.take(),
+                        limit: *limit,
+                        bucket: *bucket,
+                        current: current.take(),
+                    };
+                }
+                VectorDenseLshSearchState::Next {
+                    buckets,
+                    distances,
+                    limit,
+                    bucket,
+                    current,
+                } => {
+                    let Some(cursor) = &mut self.buckets_cursor else {
+                        return Err(LimboError::InternalError(
+                            "cursor must be opened".to_string(),
+                        ));
+                    };
+                    if return_if_io!(cursor.next()) {
+                        self.search_state = VectorDenseLshSearchState::Read {
+                            buckets: buckets.take(),
+                            distances: distances.take(),
+                            limit: *limit,
+                            bucket: *bucket,
+                            current: current.take(),
+                        };
+                    } else {
+                        self.search_state = VectorDenseLshSearchState::EvaluateSeek {
+                            buckets: buckets.take(),
+                            distances: distances.take(),
+                            limit: *limit,
+                            current: current.take().map(VecDeque::from),
+                            rowid: None,
+                        };
+                    }
+                }
+                VectorDenseLshSearchState::EvaluateSeek {
+                    buckets,
+                    distances,
+                    limit,
+                    current,
+                    rowid,
+                } => {
+                    if rowid.is_none() {
+                        let Some(curr) = current.as_mut() else {
+                            return Err(LimboError::InternalError(
+                                "current must be present in EvaluateSeek state".to_string(),
+                            ));
+                        };
+                        let Some(next) = curr.pop_front() else {
+                            self.search_state = VectorDenseLshSearchState::Seek {
+                                buckets: buckets.take(),
+                                distances: distances.take(),
+                                limit: *limit,
+                                key: None,
+                                bucket: None,
+                            };
+                            continue;
+                        };
+                        *rowid = Some(next);
+                    }
+                    let Some(main) = &mut self.main_btree else {
+                        return Err(LimboError::InternalError(
+                            "cursor must be opened".to_string(),
+                        ));
+                    };
+                    let Some(rid) = *rowid else {
+                        return Err(LimboError::InternalError(
+                            "rowid must be present in EvaluateSeek state".to_string(),
+                        ));
+                    };
+                    let result = return_if_io!(
+                        main.seek(SeekKey::TableRowId(rid), SeekOp::GE { eq_only: true })
+                    );
+                    if !matches!(result, SeekResult::Found) {
+                        return Err(LimboError::Corrupt(
+                            "vector_dense_lsh corrupted: unable to find rowid in main table"
+                                .to_string(),
+                        ));
+                    }
+                    self.search_state = VectorDenseLshSearchState::EvaluateRead {
+                        buckets: buckets.take(),
+                        distances: distances.take(),
+                        limit: *limit,
+                        current: current.take(),
+                        rowid: rid,
+                    };
+                }
+                VectorDenseLshSearchState::EvaluateRead {
+                    buckets,
+                    distances,
+                    limit,
+                    current,
+                    rowid,
+                } => {
+                    let Some(main) = &mut self.main_btree else {
+                        return Err(LimboError::InternalError(
+                            "cursor must be opened".to_string(),
+                        ));
+                    };
+                    let record = return_if_io!(main.record());
+                    if let Some(record) = record {
+                        let column_idx = self.configuration.columns[0].pos_in_table;
+                        let ValueRef::Blob(data) = record.get_value(column_idx)? else {
+                            return Err(LimboError::InternalError(
+                                "table column value must be dense vector".to_string(),
+                            ));
+                        };
+                        let data = Vector::from_slice(data)?;
+                        let Some((kind, query)) = self.query.as_ref() else {
+                            return Err(LimboError::InternalError(
+                                "query vector must be present in EvaluateRead state".to_string(),
+                            ));
+                        };
+                        let distance = match kind {
+                            DistanceKind::Cos => {
+                                operations::distance_cos::vector_distance_cos(&data, query)?
+                            }
+                            DistanceKind::L2 => {
+                                operations::distance_l2::vector_distance_l2(&data, query)?
+                            }
+                        };
+                        let Some(dists) = distances.as_mut() else {
+                            return Err(LimboError::InternalError(
+                                "distances must be present in EvaluateRead state".to_string(),
+                            ));
+                        };
+                        dists.insert((FloatOrd(distance), *rowid));
+                        if dists.len() > *limit as usize {
+                            let _ = dists.pop_last();
+                        }
+                    }
+                    self.search_state = VectorDenseLshSearchState::EvaluateSeek {
+                        buckets: buckets.take(),
+                        distances: distances.take(),
+                        limit: *limit,
+                        current: current.take(),
+                        rowid: None,
+                    };
+                }
+            }
+        }
+    }
+
+    fn query_rowid(&mut self) -> Result<IOResult<Option<i64>>> {
+        let Some(result) = self.search_result.front() else {
+            return Err(LimboError::InternalError(
+                "search_result must not be empty when query_rowid is called".to_string(),
+            ));
+        };
+        Ok(IOResult::Done(Some(result.0)))
+    }
+
+    fn query_column(&mut self, _: usize) -> Result<IOResult<Value>> {
+        let Some(result) = self.search_result.front() else {
+            return Err(LimboError::InternalError(
+                "search_result must not be empty when query_column is called".to_string(),
+            ));
+        };
+        Ok(IOResult::Done(Value::Float(result.1)))
+    }
+
+    fn query_next(&mut self) -> Result<IOResult<bool>> {
+        let _ = self.search_result.pop_front();
+        Ok(IOResult::Done(!self.search_result.is_empty()))
+    }
+}
@@ -0,0 +1,236 @@
+//! Databases stored inside a larger file.
+//!
+//! Self-contained executables and archives sometimes carry a database as a byte range of
+//! another file, e.g. appended to the end of a binary. A path registered with
+//! [EmbeddedIO::register] is opened as the byte range starting at `offset` of the file at that
+//! path, so page 1 of the database is read from `offset`. Files that were not registered,
+//! including the WAL of an embedded database (`<path>-wal`), are regular files of the inner IO.
+//!
+//! Without a length, the database extends to the end of the file and can grow. With a length,
+//! the database has to fit in `offset..offset + length`, and writes past the end of that range
+//! fail with [LimboError::DatabaseFull].
+use super::{Buffer, Clock, Completion, File, OpenFlags, IO};
+use crate::io::clock::Instant;
+use crate::{LimboError, Result};
+use parking_lot::Mutex;
+use std::{collections::HashMap, sync::Arc};
+use tracing::debug;
+
+#[derive(Clone, Copy, Debug)]
+struct Region {
+    offset: u64,
+    length: Option<u64>,
+}
+
+pub struct EmbeddedIO {
+    inner: Arc<dyn IO>,
+    regions: Mutex<HashMap<String, Region>>,
+}
+
+impl EmbeddedIO {
+    pub fn new(inner: Arc<dyn IO>) -> Self {
+        debug!("Using IO backend 'embedded'");
+        Self {
+            inner,
+            regions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Opens the file at `path` as the `length` bytes starting at `offset`, or as everything
+    /// from `offset` to the end of the file if `length` is `None`.
+    pub fn register(&self, path: &str, offset: u64, length: Option<u64>) {
+        self.regions
+            .lock()
+            .insert(path.to_string(), Region { offset, length });
+    }
+}
+
+impl Clock for EmbeddedIO {
+    fn now(&self) -> Instant {
+        self.inner.now()
+    }
+}
+
+impl IO for EmbeddedIO {
+    fn open_file(&self, path: &str, flags: OpenFlags, direct: bool) -> Result<Arc<dyn File>> {
+        let file = self.inner.open_file(path, flags, direct)?;
+        let Some(region) = self.regions.lock().get(path).copied() else {
+            return Ok(file);
+        };
+        let size = match region.length {
+            Some(length) => Some(Mutex::new(
+                length.min(file.size()?.saturating_sub(region.offset)),
+            )),
+            None => None,
+        };
+        Ok(Arc::new(EmbeddedFile {
+            inner: file,
+            offset: region.offset,
+            length: region.length,
+            size,
+        }))
+    }
+
+    fn remove_file(&self, path: &str) -> Result<()> {
+        if self.regions.lock().contains_key(path) {
+            return Err(LimboError::InvalidArgument(format!(
+                "cannot remove embedded database {path}"
+            )));
+        }
+        self.inner.remove_file(path)
+    }
+
+    fn step(&self) -> Result<()> {
+        self.inner.step()
+    }
+}
+
+struct EmbeddedFile {
+    inner: Arc<dyn File>,
+    offset: u64,
+    length: Option<u64>,
+    /// Size of a database with a fixed length. Truncating it only changes this size, since the
+    /// bytes after the database belong to the enclosing file.
+    size: Option<Mutex<u64>>,
+}
+
+impl EmbeddedFile {
+    fn check_fits(&self, end: u64) -> Result<()> {
+        match self.length {
+            Some(length) if end > length => Err(LimboError::DatabaseFull(format!(
+                "embedded database cannot grow past {length} bytes"
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl File for EmbeddedFile {
+    fn lock_file(&self, exclusive: bool) -> Result<()> {
+        self.inner.lock_file(exclusive)
+    }
+
+    fn unlock_file(&self) -> Result<()> {
+        self.inner.unlock_file()
+    }
+
+    fn pread(&self, pos: u64, c: Completion) -> Result<Completion> {
+        self.inner.pread(self.offset + pos, c)
+    }
+
+    fn pwrite(&self, pos: u64, buffer: Arc<Buffer>, c: Completion) -> Result<Completion> {
+        let end = pos + buffer.len() as u64;
+        self.check_fits(end)?;
+        if let Some(size) = &self.size {
+            let mut size = size.lock();
+            *size = (*size).max(end);
+        }
+        self.inner.pwrite(self.offset + pos, buffer, c)
+    }
+
+    fn pwritev(&self, pos: u64, buffers: Vec<Arc<Buffer>>, c: Completion) -> Result<Completion> {
+        let end = pos + buffers.iter().map(|b| b.len() as u64).sum::<u64>();
+        self.check_fits(end)?;
+        if let Some(size) = &self.size {
+            let mut size = size.lock();
+            *size = (*size).max(end);
+        }
+        self.inner.pwritev(self.offset + pos, buffers, c)
+    }
+
+    fn sync(&self, c: Completion) -> Result<Completion> {
+        self.inner.sync(c)
+    }
+
+    fn size(&self) -> Result<u64> {
+        match &self.size {
+            Some(size) => Ok(*size.lock()),
+            None => Ok(self.inner.size()?.saturating_sub(self.offset)),
+        }
+    }
+
+    fn truncate(&self, len: u64, c: Completion) -> Result<Completion> {
+        match &self.size {
+            Some(size) => {
+                self.check_fits(len)?;
+                *size.lock() = len;
+                c.complete(0);
+                Ok(c)
+            }
+            None => self.inner.truncate(self.offset + len, c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Database, DatabaseOpts, PlatformIO};
+
+    fn count_rows(io: Arc<dyn IO>, path: &str) -> i64 {
+        let db = Database::open_file_with_flags(
+            io,
+            path,
+            OpenFlags::default(),
+            DatabaseOpts::new(),
+            None,
+        )
+        .unwrap();
+        let conn = db.connect().unwrap();
+        let mut stmt = conn.prepare("SELECT count(*) FROM t").unwrap();
+        let mut count = 0;
+        stmt.run_with_row_callback(|row| {
+            count = row.get::<i64>(0)?;
+            Ok(())
+        })
+        .unwrap();
+        count
+    }
+
+    #[test]
+    fn test_database_appended_to_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("plain.db");
+        let conn = rusqlite::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE t(x);
+             WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 100)
+             INSERT INTO t SELECT i FROM n;",
+        )
+        .unwrap();
+        drop(conn);
+
+        let prefix = vec![0xAB; 12345];
+        let bundle_path = dir.path().join("bundle.bin");
+        let mut bundle = prefix.clone();
+        bundle.extend(std::fs::read(&db_path).unwrap());
+        std::fs::write(&bundle_path, bundle).unwrap();
+        let bundle_path = bundle_path.to_str().unwrap();
+
+        let io = Arc::new(EmbeddedIO::new(Arc::new(PlatformIO::new().unwrap())));
+        io.register(bundle_path, prefix.len() as u64, None);
+        assert_eq!(count_rows(io.clone(), bundle_path), 100);
+
+        let db = Database::open_file_with_flags(
+            io.clone(),
+            bundle_path,
+            OpenFlags::default(),
+            DatabaseOpts::new(),
+            None,
+        )
+        .unwrap();
+        let conn = db.connect().unwrap();
+        conn.execute("INSERT INTO t SELECT value FROM generate_series(1, 1000)")
+            .unwrap();
+        conn.execute("PRAGMA wal_checkpoint(TRUNCATE)").unwrap();
+        conn.close().unwrap();
+        drop(db);
+
+        // The bytes before the database are left alone.
+        assert_eq!(
+            std::fs::read(bundle_path).unwrap()[..prefix.len()],
+            prefix[..]
+        );
+        assert_eq!(count_rows(io, bundle_path), 1100);
+    }
+}
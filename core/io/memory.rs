@@ -71,6 +71,10 @@ impl IO for MemoryIO {
         files.remove(path);
         Ok(())
     }
+
+    fn is_memory(&self) -> bool {
+        true
+    }
 }
 
 pub struct MemoryFile {
@@ -3,10 +3,12 @@ use crate::storage::sqlite3_ondisk::WAL_FRAME_HEADER_SIZE;
 use crate::{turso_assert, BufferPool, Result};
 use bitflags::bitflags;
 use cfg_block::cfg_block;
+use parking_lot::Mutex;
 use rand::{Rng, RngCore};
 use std::cell::RefCell;
 use std::fmt;
 use std::ptr::NonNull;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::{fmt::Debug, pin::Pin};
 
@@ -32,10 +34,20 @@ cfg_block! {
     }
 }
 
+mod embedded;
 mod memory;
+#[cfg(feature = "object_store")]
+mod object_store;
+#[cfg(feature = "object_store")]
+mod tiered;
 #[cfg(feature = "fs")]
 mod vfs;
+pub use embedded::EmbeddedIO;
 pub use memory::MemoryIO;
+#[cfg(feature = "object_store")]
+pub use object_store::{HttpRangeReader, ObjectStoreIO, RangeReader};
+#[cfg(feature = "object_store")]
+pub use tiered::{RemoteObject, TieredIO};
 pub mod clock;
 mod common;
 mod completions;
@@ -110,42 +122,92 @@ pub trait File: Send + Sync {
     }
 }
 
+/// The directory a connection keeps its temporary files in: the temp database, sorter and hash
+/// table spill files, and ephemeral tables. It is created on first use and removed once the
+/// connection and every file created in it are gone.
+#[derive(Default)]
+pub struct TempDirectory {
+    dir: Mutex<Option<Arc<tempfile::TempDir>>>,
+    next_file_id: AtomicU64,
+}
+
+impl TempDirectory {
+    /// The path of a new file in the directory, along with the directory, which must be kept
+    /// alive for as long as the file is used.
+    pub(crate) fn new_file_path(&self, prefix: &str) -> Result<(Arc<tempfile::TempDir>, String)> {
+        let dir = {
+            let mut dir = self.dir.lock();
+            match &*dir {
+                Some(dir) => dir.clone(),
+                None => dir.insert(Arc::new(tempfile::tempdir()?)).clone(),
+            }
+        };
+        let file_id = self.next_file_id.fetch_add(1, Ordering::Relaxed);
+        let path = dir.path().join(format!("{prefix}-{file_id}"));
+        let path = path
+            .to_str()
+            .ok_or_else(|| {
+                crate::LimboError::InternalError("temp file path is not valid UTF-8".to_string())
+            })?
+            .to_string();
+        Ok((dir, path))
+    }
+}
+
 pub struct TempFile {
-    /// When temp_dir is dropped the folder is deleted
-    /// set to None if tempfile allocated in memory (for example, in case of WASM target)
-    _temp_dir: Option<tempfile::TempDir>,
+    /// Keeps the directory of the file alive, so that it is only deleted after its last file.
+    /// Set to None if tempfile allocated in memory (for example, in case of WASM target)
+    _temp_dir: Option<Arc<tempfile::TempDir>>,
+    io: Arc<dyn IO>,
+    path: String,
     pub(crate) file: Arc<dyn File>,
 }
 
 impl TempFile {
-    pub fn new(io: &Arc<dyn IO>) -> Result<Self> {
+    pub fn new(io: &Arc<dyn IO>, temp_dir: &TempDirectory) -> Result<Self> {
         #[cfg(not(target_family = "wasm"))]
         {
-            let temp_dir = tempfile::tempdir()?;
-            let chunk_file_path = temp_dir.as_ref().join("tursodb_temp_file");
-            let chunk_file_path_str = chunk_file_path.to_str().ok_or_else(|| {
-                crate::LimboError::InternalError("temp file path is not valid UTF-8".to_string())
-            })?;
-            let chunk_file = io.open_file(chunk_file_path_str, OpenFlags::Create, false)?;
+            if io.is_memory() {
+                return Self::new_in_memory();
+            }
+            let (temp_dir, path) = temp_dir.new_file_path("tursodb_temp_file")?;
+            let file = io.open_file(&path, OpenFlags::Create, false)?;
             Ok(TempFile {
                 _temp_dir: Some(temp_dir),
-                file: chunk_file.clone(),
+                io: io.clone(),
+                path,
+                file,
             })
         }
         // on WASM in browser we do not support temp files (as we pre-register db files in advance and can't easily create a new one)
         // so, for now, we use in-memory IO for tempfiles in WASM
         #[cfg(target_family = "wasm")]
         {
-            use crate::MemoryIO;
-
-            let memory_io = Arc::new(MemoryIO::new());
-            let memory_file = memory_io.open_file("tursodb_temp_file", OpenFlags::Create, false)?;
-            Ok(TempFile {
-                _temp_dir: None,
-                file: memory_file,
-            })
+            let _ = (io, temp_dir);
+            Self::new_in_memory()
         }
     }
+
+    /// A temp file in a private in-memory IO, without any directory on disk.
+    fn new_in_memory() -> Result<Self> {
+        let memory_io: Arc<dyn IO> = Arc::new(MemoryIO::new());
+        let path = "tursodb_temp_file".to_string();
+        let memory_file = memory_io.open_file(&path, OpenFlags::Create, false)?;
+        Ok(TempFile {
+            _temp_dir: None,
+            io: memory_io,
+            path,
+            file: memory_file,
+        })
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        // The directory outlives the file when it is shared by the whole connection, so the file
+        // is removed on its own. A failure leaves it for the directory cleanup.
+        let _ = self.io.remove_file(&self.path);
+    }
 }
 
 impl core::ops::Deref for TempFile {
@@ -221,6 +283,12 @@ pub trait IO: Clock + Send + Sync {
         Arc::new(MemoryIO::new())
     }
 
+    /// Whether the files of this IO only live in memory, in which case temporary files are kept
+    /// in memory too instead of in a directory on disk.
+    fn is_memory(&self) -> bool {
+        false
+    }
+
     fn register_fixed_buffer(&self, _ptr: NonNull<u8>, _len: usize) -> Result<u32> {
         Err(crate::LimboError::InternalError(
             "unsupported operation".to_string(),
@@ -461,3 +529,81 @@ impl TempBufferCache {
         }
     }
 }
+
+#[cfg(all(test, not(target_family = "wasm")))]
+mod tests {
+    use super::*;
+    use crate::{Database, PlatformIO};
+
+    #[test]
+    fn temp_files_share_a_directory_that_outlives_them() {
+        let io: Arc<dyn IO> = Arc::new(PlatformIO::new().unwrap());
+        let temp_directory = TempDirectory::default();
+        let first = TempFile::new(&io, &temp_directory).unwrap();
+        let second = TempFile::new(&io, &temp_directory).unwrap();
+        let first_path = std::path::PathBuf::from(&first.path);
+        let dir = first_path.parent().unwrap().to_path_buf();
+        assert_eq!(
+            std::path::Path::new(&second.path).parent(),
+            Some(dir.as_path())
+        );
+        assert_ne!(first.path, second.path);
+
+        drop(first);
+        assert!(!first_path.exists());
+        assert!(dir.exists());
+
+        drop(temp_directory);
+        assert!(dir.exists());
+        drop(second);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn temp_database_is_removed_with_the_connection() {
+        let main_dir = tempfile::tempdir().unwrap();
+        let main_path = main_dir.path().join("main.db");
+        let io: Arc<dyn IO> = Arc::new(PlatformIO::new().unwrap());
+        let db = Database::open_file(io, main_path.to_str().unwrap()).unwrap();
+        let conn = db.connect().unwrap();
+        conn.execute("CREATE TEMP TABLE t(x)").unwrap();
+        conn.execute("INSERT INTO t VALUES (1)").unwrap();
+
+        let dir = conn
+            .temp_directory()
+            .new_file_path("probe")
+            .unwrap()
+            .0
+            .path()
+            .to_path_buf();
+        let files: Vec<String> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert!(
+            files
+                .iter()
+                .any(|name| name.starts_with("tursodb_temp_database")),
+            "{files:?}"
+        );
+
+        conn.close().unwrap();
+        drop(conn);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn memory_io_temp_files_stay_in_memory() {
+        let io: Arc<dyn IO> = Arc::new(MemoryIO::new());
+        let temp_directory = TempDirectory::default();
+        let temp_file = TempFile::new(&io, &temp_directory).unwrap();
+        assert!(temp_file._temp_dir.is_none());
+        assert!(temp_directory.dir.lock().is_none());
+
+        let db = Database::open_file(io, ":memory:").unwrap();
+        let conn = db.connect().unwrap();
+        conn.execute("CREATE TEMP TABLE t(x)").unwrap();
+        conn.execute("INSERT INTO t VALUES (1)").unwrap();
+        assert!(conn.temp_directory().dir.lock().is_none());
+    }
+}
@@ -0,0 +1,470 @@
+//! Read-only access to database files kept in an object store such as S3 or GCS.
+//!
+//! Database files are read with ranged `GET` requests issued by a [RangeReader].
+//! [HttpRangeReader] reads objects that are reachable by URL, e.g. public or presigned S3 and
+//! GCS URLs; embedders that sign requests themselves can implement [RangeReader] on top of
+//! their own HTTP client. Reads are done in fixed size blocks
+//! that are kept in an LRU cache, so scanning a table costs one request per block instead of
+//! one per page, and pages that are read again are not fetched again.
+//!
+//! Files that were not registered with [ObjectStoreIO::register], like temporary files used
+//! for sorting, are kept in memory.
+use super::{Buffer, Clock, Completion, File, MemoryIO, OpenFlags, IO};
+use crate::io::clock::{DefaultClock, Instant};
+use crate::{LimboError, Result};
+use parking_lot::Mutex;
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Read,
+    sync::Arc,
+};
+use tracing::debug;
+
+/// Reads byte ranges of a single object, e.g. with `GET` requests carrying a
+/// `Range: bytes=<offset>-<offset + len - 1>` header.
+pub trait RangeReader: Send + Sync {
+    /// Size of the object in bytes.
+    fn size(&self) -> Result<u64>;
+    /// Fills `buf` with the bytes of the object starting at `offset`. The range never extends
+    /// past the end of the object.
+    fn read_range(&self, offset: u64, buf: &mut [u8]) -> Result<()>;
+}
+
+/// Reads an object over HTTP(S) with `Range` requests.
+pub struct HttpRangeReader {
+    agent: ureq::Agent,
+    url: String,
+    headers: Vec<(String, String)>,
+}
+
+impl HttpRangeReader {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            agent: ureq::Agent::new_with_defaults(),
+            url: url.into(),
+            headers: Vec::new(),
+        }
+    }
+
+    /// Sends the header `name: value` with every request, e.g. an `Authorization` header.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Requests `len` bytes starting at `offset` and returns the response once the server
+    /// confirmed that it only sends that range.
+    fn get_range(&self, offset: u64, len: u64) -> Result<ureq::http::Response<ureq::Body>> {
+        let mut request = self
+            .agent
+            .get(&self.url)
+            .header("Range", format!("bytes={offset}-{}", offset + len - 1));
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        let response = request.call().map_err(|e| self.error(e))?;
+        if response.status() != ureq::http::StatusCode::PARTIAL_CONTENT {
+            return Err(LimboError::InternalError(format!(
+                "GET {}: expected a partial response, got {}",
+                self.url,
+                response.status()
+            )));
+        }
+        Ok(response)
+    }
+
+    fn error(&self, e: impl std::fmt::Display) -> LimboError {
+        LimboError::InternalError(format!("GET {}: {e}", self.url))
+    }
+}
+
+impl RangeReader for HttpRangeReader {
+    fn size(&self) -> Result<u64> {
+        // `HEAD` is not allowed by URLs presigned for `GET`, so ask for the first byte instead
+        // and take the size from `Content-Range: bytes 0-0/<size>`.
+        let response = self.get_range(0, 1)?;
+        response
+            .headers()
+            .get("Content-Range")
+            .and_then(|range| range.to_str().ok())
+            .and_then(|range| range.rsplit_once('/'))
+            .and_then(|(_, size)| size.parse().ok())
+            .ok_or_else(|| self.error("missing object size in Content-Range"))
+    }
+
+    fn read_range(&self, offset: u64, buf: &mut [u8]) -> Result<()> {
+        if buf.is_empty() {
+            return Ok(());
+        }
+        let response = self.get_range(offset, buf.len() as u64)?;
+        response
+            .into_body()
+            .into_reader()
+            .read_exact(buf)
+            .map_err(|e| self.error(e))
+    }
+}
+
+/// Default number of bytes fetched by a single request.
+pub const DEFAULT_BLOCK_SIZE: usize = 256 * 1024;
+/// Default number of blocks cached per file.
+pub const DEFAULT_CACHED_BLOCKS: usize = 256;
+
+pub struct ObjectStoreIO {
+    objects: Mutex<HashMap<String, Arc<dyn RangeReader>>>,
+    memory: MemoryIO,
+    block_size: usize,
+    cached_blocks: usize,
+}
+
+impl ObjectStoreIO {
+    pub fn new() -> Self {
+        Self::with_cache(DEFAULT_BLOCK_SIZE, DEFAULT_CACHED_BLOCKS)
+    }
+
+    /// Creates an IO that fetches `block_size` bytes per request and keeps up to
+    /// `cached_blocks` blocks of every open file in memory.
+    pub fn with_cache(block_size: usize, cached_blocks: usize) -> Self {
+        debug!("Using IO backend 'object_store'");
+        Self {
+            objects: Mutex::new(HashMap::new()),
+            memory: MemoryIO::new(),
+            block_size: block_size.max(1),
+            cached_blocks,
+        }
+    }
+
+    /// Serves the file at `path` from `reader`. Such files can only be opened for reading, so
+    /// the database has to be opened with [OpenFlags::ReadOnly].
+    pub fn register(&self, path: &str, reader: Arc<dyn RangeReader>) {
+        self.objects.lock().insert(path.to_string(), reader);
+    }
+}
+
+impl Default for ObjectStoreIO {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ObjectStoreIO {
+    fn now(&self) -> Instant {
+        DefaultClock.now()
+    }
+}
+
+impl IO for ObjectStoreIO {
+    fn open_file(&self, path: &str, flags: OpenFlags, direct: bool) -> Result<Arc<dyn File>> {
+        let Some(reader) = self.objects.lock().get(path).cloned() else {
+            return self.memory.open_file(path, flags, direct);
+        };
+        let size = reader.size()?;
+        Ok(Arc::new(ObjectStoreFile {
+            path: path.to_string(),
+            reader,
+            size,
+            block_size: self.block_size as u64,
+            cache: Mutex::new(BlockCache::new(self.cached_blocks)),
+        }))
+    }
+
+    fn remove_file(&self, path: &str) -> Result<()> {
+        if self.objects.lock().contains_key(path) {
+            return Err(LimboError::ReadOnly);
+        }
+        self.memory.remove_file(path)
+    }
+}
+
+struct ObjectStoreFile {
+    path: String,
+    reader: Arc<dyn RangeReader>,
+    size: u64,
+    block_size: u64,
+    cache: Mutex<BlockCache>,
+}
+
+impl ObjectStoreFile {
+    /// Returns the blocks `first..=last`, fetching the ones that are not cached. Adjacent
+    /// missing blocks are fetched with a single request.
+    fn blocks(&self, first: u64, last: u64) -> Result<Vec<Arc<[u8]>>> {
+        let mut blocks = {
+            let mut cache = self.cache.lock();
+            (first..=last)
+                .map(|block| cache.get(block))
+                .collect::<Vec<_>>()
+        };
+        let mut i = 0;
+        while i < blocks.len() {
+            if blocks[i].is_some() {
+                i += 1;
+                continue;
+            }
+            let run_start = i;
+            while i < blocks.len() && blocks[i].is_none() {
+                i += 1;
+            }
+            let start = (first + run_start as u64) * self.block_size;
+            let end = ((first + i as u64) * self.block_size).min(self.size);
+            let mut data = vec![0; (end - start) as usize];
+            self.reader.read_range(start, &mut data)?;
+            let mut cache = self.cache.lock();
+            for (j, chunk) in data.chunks(self.block_size as usize).enumerate() {
+                let block: Arc<[u8]> = chunk.into();
+                cache.insert(first + (run_start + j) as u64, block.clone());
+                blocks[run_start + j] = Some(block);
+            }
+        }
+        Ok(blocks.into_iter().flatten().collect())
+    }
+}
+
+impl File for ObjectStoreFile {
+    fn lock_file(&self, _exclusive: bool) -> Result<()> {
+        Ok(())
+    }
+
+    fn unlock_file(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn pread(&self, pos: u64, c: Completion) -> Result<Completion> {
+        tracing::debug!("pread(path={}): pos={}", self.path, pos);
+        let r = c.as_read();
+        let end = (pos + r.buf().len() as u64).min(self.size);
+        if pos >= end {
+            c.complete(0);
+            return Ok(c);
+        }
+        let first = pos / self.block_size;
+        let blocks = self.blocks(first, (end - 1) / self.block_size)?;
+        let out = r.buf().as_mut_slice();
+        let mut copied = 0;
+        let mut offset = (pos - first * self.block_size) as usize;
+        for block in blocks {
+            let n = (block.len() - offset).min((end - pos) as usize - copied);
+            out[copied..copied + n].copy_from_slice(&block[offset..offset + n]);
+            copied += n;
+            offset = 0;
+        }
+        c.complete(copied as i32);
+        Ok(c)
+    }
+
+    fn pwrite(&self, _pos: u64, _buffer: Arc<Buffer>, _c: Completion) -> Result<Completion> {
+        Err(LimboError::ReadOnly)
+    }
+
+    fn sync(&self, c: Completion) -> Result<Completion> {
+        c.complete(0);
+        Ok(c)
+    }
+
+    fn size(&self) -> Result<u64> {
+        Ok(self.size)
+    }
+
+    fn truncate(&self, _len: u64, _c: Completion) -> Result<Completion> {
+        Err(LimboError::ReadOnly)
+    }
+}
+
+/// Least recently used blocks of a file.
+struct BlockCache {
+    capacity: usize,
+    tick: u64,
+    blocks: HashMap<u64, (u64, Arc<[u8]>)>,
+    /// Block numbers by the tick at which they were last used.
+    lru: BTreeMap<u64, u64>,
+}
+
+impl BlockCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tick: 0,
+            blocks: HashMap::new(),
+            lru: BTreeMap::new(),
+        }
+    }
+
+    fn get(&mut self, block: u64) -> Option<Arc<[u8]>> {
+        let (used, data) = self.blocks.get_mut(&block)?;
+        self.lru.remove(used);
+        self.tick += 1;
+        *used = self.tick;
+        self.lru.insert(self.tick, block);
+        Some(data.clone())
+    }
+
+    fn insert(&mut self, block: u64, data: Arc<[u8]>) {
+        if self.capacity == 0 {
+            return;
+        }
+        while self.blocks.len() >= self.capacity {
+            let Some((_, evicted)) = self.lru.pop_first() else {
+                break;
+            };
+            self.blocks.remove(&evicted);
+        }
+        self.tick += 1;
+        self.lru.insert(self.tick, block);
+        if let Some((used, _)) = self.blocks.insert(block, (self.tick, data)) {
+            self.lru.remove(&used);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Database, DatabaseOpts};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct VecReader {
+        data: Vec<u8>,
+        requests: AtomicUsize,
+    }
+
+    impl RangeReader for VecReader {
+        fn size(&self) -> Result<u64> {
+            Ok(self.data.len() as u64)
+        }
+
+        fn read_range(&self, offset: u64, buf: &mut [u8]) -> Result<()> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            let offset = offset as usize;
+            buf.copy_from_slice(&self.data[offset..offset + buf.len()]);
+            Ok(())
+        }
+    }
+
+    fn remote_db(rows: usize) -> Arc<VecReader> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("remote.db");
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute_batch(&format!(
+            "CREATE TABLE t(x INTEGER PRIMARY KEY, y TEXT);
+             WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < {rows})
+             INSERT INTO t SELECT i, hex(randomblob(100)) FROM n;"
+        ))
+        .unwrap();
+        drop(conn);
+        Arc::new(VecReader {
+            data: std::fs::read(&path).unwrap(),
+            requests: AtomicUsize::new(0),
+        })
+    }
+
+    #[test]
+    fn test_query_database_in_object_store() {
+        let reader = remote_db(1000);
+        let io = Arc::new(ObjectStoreIO::with_cache(16 * 1024, 4));
+        io.register("s3://bucket/remote.db", reader.clone());
+        let db = Database::open_file_with_flags(
+            io,
+            "s3://bucket/remote.db",
+            OpenFlags::ReadOnly,
+            DatabaseOpts::new(),
+            None,
+        )
+        .unwrap();
+        let conn = db.connect().unwrap();
+        let mut stmt = conn.prepare("SELECT count(*), sum(x) FROM t").unwrap();
+        let mut rows = Vec::new();
+        stmt.run_with_row_callback(|row| {
+            rows.push((row.get::<i64>(0)?, row.get::<i64>(1)?));
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(rows, vec![(1000, 500500)]);
+        assert!(conn.execute("INSERT INTO t VALUES (1001, 'x')").is_err());
+    }
+
+    /// Serves `data` on a local port, answering every request with the requested range.
+    fn serve(data: Vec<u8>) -> String {
+        use std::io::{BufRead, BufReader, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/remote.db", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut range = None;
+                for line in BufReader::new(stream.try_clone().unwrap()).lines() {
+                    let line = line.unwrap().to_ascii_lowercase();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some(bytes) = line.strip_prefix("range: bytes=") {
+                        let (start, end) = bytes.split_once('-').unwrap();
+                        range = Some((start.parse::<usize>().unwrap(), end.parse().unwrap()));
+                    }
+                }
+                let (start, end) = range.expect("request without a range");
+                let end = usize::min(end, data.len() - 1);
+                write!(
+                    stream,
+                    "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {start}-{end}/{}\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n",
+                    data.len(),
+                    end + 1 - start
+                )
+                .unwrap();
+                stream.write_all(&data[start..=end]).unwrap();
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn test_query_database_over_http() {
+        let url = serve(remote_db(1000).data.clone());
+        let io = Arc::new(ObjectStoreIO::with_cache(16 * 1024, 4));
+        io.register("remote.db", Arc::new(HttpRangeReader::new(url)));
+        let db = Database::open_file_with_flags(
+            io,
+            "remote.db",
+            OpenFlags::ReadOnly,
+            DatabaseOpts::new(),
+            None,
+        )
+        .unwrap();
+        let conn = db.connect().unwrap();
+        let mut stmt = conn.prepare("SELECT count(*), max(x) FROM t").unwrap();
+        let mut rows = Vec::new();
+        stmt.run_with_row_callback(|row| {
+            rows.push((row.get::<i64>(0)?, row.get::<i64>(1)?));
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(rows, vec![(1000, 1000)]);
+    }
+
+    #[test]
+    fn test_blocks_are_fetched_once() {
+        let reader = remote_db(100);
+        let io = ObjectStoreIO::with_cache(4096, 2);
+        io.register("remote.db", reader.clone());
+        let file = io
+            .open_file("remote.db", OpenFlags::ReadOnly, false)
+            .unwrap();
+        let read = |pos: u64, len: usize| {
+            let buf = Arc::new(Buffer::new_temporary(len));
+            let c = file
+                .pread(pos, Completion::new_read(buf.clone(), |_| {}))
+                .unwrap();
+            assert!(c.succeeded());
+            buf.as_slice().to_vec()
+        };
+
+        // Spans two blocks, fetched with one request.
+        assert_eq!(read(4000, 200), reader.data[4000..4200]);
+        assert_eq!(reader.requests.load(Ordering::SeqCst), 1);
+        assert_eq!(read(4096, 100), reader.data[4096..4196]);
+        assert_eq!(reader.requests.load(Ordering::SeqCst), 1);
+        // Evicts the first block, which is then fetched again.
+        read(8192, 10);
+        read(0, 10);
+        assert_eq!(reader.requests.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,443 @@
+//! Database files kept in an object store with a local cache file in front of them.
+//!
+//! A file registered with [TieredIO::register] lives in a local cache file at the same path,
+//! which is filled block by block from the [RemoteObject] as pages are read. Writes only go to
+//! the cache file; the blocks they touched are uploaded when the file is synced. SQLite only
+//! syncs the database file at the end of a checkpoint (commits sync the WAL, which stays
+//! local), so the remote object is updated once per checkpoint with the pages it backfilled.
+//! `TRUNCATE` checkpoints, which run when the last connection is closed, don't sync the database
+//! file, so the WAL of a registered file uploads the dirty blocks before it is truncated.
+//!
+//! Which blocks the cache file holds is tracked in memory, so the cache starts out empty every
+//! time the file is opened.
+use super::{object_store::RangeReader, Buffer, Clock, Completion, File, OpenFlags, IO};
+use crate::io::clock::Instant;
+use crate::Result;
+use parking_lot::Mutex;
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    sync::{Arc, Weak},
+};
+use tracing::debug;
+
+/// An object that can be read with range requests and updated in place.
+pub trait RemoteObject: RangeReader {
+    /// Writes `ranges`, given as offset and bytes, and sets the size of the object to `size`.
+    /// Object stores can't update part of an object, so implementations usually store the
+    /// object as a set of block-sized objects, or rewrite it with a multipart upload that
+    /// copies the unchanged ranges from the current version.
+    fn write_ranges(&self, ranges: &[(u64, &[u8])], size: u64) -> Result<()>;
+}
+
+pub struct TieredIO {
+    local: Arc<dyn IO>,
+    remotes: Mutex<HashMap<String, Arc<dyn RemoteObject>>>,
+    /// Cache files that are open, by path, for the WAL files next to them.
+    open_files: Arc<Mutex<HashMap<String, Weak<TieredFile>>>>,
+    block_size: usize,
+}
+
+impl TieredIO {
+    /// Creates an IO that keeps cache files, and all files that are not registered, in `local`
+    /// and moves `block_size` bytes per remote request.
+    pub fn new(local: Arc<dyn IO>, block_size: usize) -> Self {
+        debug!("Using IO backend 'tiered'");
+        Self {
+            local,
+            remotes: Mutex::new(HashMap::new()),
+            open_files: Arc::new(Mutex::new(HashMap::new())),
+            block_size: block_size.max(1),
+        }
+    }
+
+    /// Backs the file at `path` with `remote`. `path` is where the local cache file is kept,
+    /// and the WAL of a database opened at `path` is kept next to it.
+    pub fn register(&self, path: &str, remote: Arc<dyn RemoteObject>) {
+        self.remotes.lock().insert(path.to_string(), remote);
+    }
+}
+
+impl Clock for TieredIO {
+    fn now(&self) -> Instant {
+        self.local.now()
+    }
+}
+
+impl IO for TieredIO {
+    fn open_file(&self, path: &str, flags: OpenFlags, direct: bool) -> Result<Arc<dyn File>> {
+        let Some(remote) = self.remotes.lock().get(path).cloned() else {
+            let local = self.local.open_file(path, flags, direct)?;
+            return match path.strip_suffix("-wal") {
+                Some(db_path) if self.remotes.lock().contains_key(db_path) => {
+                    Ok(Arc::new(TieredWalFile {
+                        db_path: db_path.to_string(),
+                        open_files: self.open_files.clone(),
+                        local,
+                    }))
+                }
+                _ => Ok(local),
+            };
+        };
+        let local = self.local.open_file(path, OpenFlags::Create, direct)?;
+        // Whatever the cache file held before is not known to match the remote object.
+        self.local
+            .wait_for_completion(local.truncate(0, Completion::new_trunc(|_| {}))?)?;
+        let size = remote.size()?;
+        let file = Arc::new(TieredFile {
+            path: path.to_string(),
+            io: self.local.clone(),
+            local,
+            remote,
+            block_size: self.block_size as u64,
+            state: Mutex::new(TierState {
+                size,
+                remote_size: size,
+                cached: HashSet::new(),
+                dirty: BTreeSet::new(),
+                resized: false,
+            }),
+        });
+        self.open_files
+            .lock()
+            .insert(path.to_string(), Arc::downgrade(&file));
+        Ok(file)
+    }
+
+    fn remove_file(&self, path: &str) -> Result<()> {
+        self.local.remove_file(path)
+    }
+
+    fn step(&self) -> Result<()> {
+        self.local.step()
+    }
+}
+
+struct TieredFile {
+    path: String,
+    io: Arc<dyn IO>,
+    local: Arc<dyn File>,
+    remote: Arc<dyn RemoteObject>,
+    block_size: u64,
+    state: Mutex<TierState>,
+}
+
+struct TierState {
+    size: u64,
+    /// Size of the remote object when the file was opened, or the size the file was truncated
+    /// to if that is smaller. Bytes past it are created locally and never need to be fetched.
+    remote_size: u64,
+    /// Remote blocks that have been copied into the cache file.
+    cached: HashSet<u64>,
+    /// Blocks written since the last upload.
+    dirty: BTreeSet<u64>,
+    /// Whether the file was truncated since the last upload.
+    resized: bool,
+}
+
+impl TieredFile {
+    fn blocks_in(&self, pos: u64, len: u64) -> std::ops::Range<u64> {
+        if len == 0 {
+            return 0..0;
+        }
+        pos / self.block_size..(pos + len - 1) / self.block_size + 1
+    }
+
+    /// Copies the blocks in `blocks` that are not in the cache file yet from the remote object.
+    /// Adjacent missing blocks are fetched with a single request.
+    fn fetch_blocks(&self, state: &mut TierState, blocks: std::ops::Range<u64>) -> Result<()> {
+        let end = blocks.end.min(state.remote_size.div_ceil(self.block_size));
+        let mut block = blocks.start;
+        while block < end {
+            if state.cached.contains(&block) {
+                block += 1;
+                continue;
+            }
+            let run_start = block;
+            while block < end && !state.cached.contains(&block) {
+                block += 1;
+            }
+            let start = run_start * self.block_size;
+            let len = (block * self.block_size).min(state.remote_size) - start;
+            let mut data = vec![0; len as usize];
+            self.remote.read_range(start, &mut data)?;
+            let c = self.local.pwrite(
+                start,
+                Arc::new(Buffer::new(data)),
+                Completion::new_write(|_| {}),
+            )?;
+            self.io.wait_for_completion(c)?;
+            state.cached.extend(run_start..block);
+        }
+        Ok(())
+    }
+
+    /// Uploads the dirty blocks, reading them back from the cache file.
+    fn upload(&self, state: &mut TierState) -> Result<()> {
+        if state.dirty.is_empty() && !state.resized {
+            return Ok(());
+        }
+        let mut blocks = Vec::with_capacity(state.dirty.len());
+        for &block in &state.dirty {
+            let start = block * self.block_size;
+            if start >= state.size {
+                continue;
+            }
+            let len = self.block_size.min(state.size - start) as usize;
+            let buf = Arc::new(Buffer::new_temporary(len));
+            let c = self
+                .local
+                .pread(start, Completion::new_read(buf.clone(), |_| {}))?;
+            self.io.wait_for_completion(c)?;
+            blocks.push((start, buf));
+        }
+        let ranges = blocks
+            .iter()
+            .map(|(start, buf)| (*start, buf.as_slice()))
+            .collect::<Vec<_>>();
+        self.remote.write_ranges(&ranges, state.size)?;
+        state.dirty.clear();
+        state.resized = false;
+        Ok(())
+    }
+}
+
+impl File for TieredFile {
+    fn lock_file(&self, exclusive: bool) -> Result<()> {
+        self.local.lock_file(exclusive)
+    }
+
+    fn unlock_file(&self) -> Result<()> {
+        self.local.unlock_file()
+    }
+
+    fn pread(&self, pos: u64, c: Completion) -> Result<Completion> {
+        tracing::debug!("pread(path={}): pos={}", self.path, pos);
+        {
+            let mut state = self.state.lock();
+            let len = (c.as_read().buf().len() as u64).min(state.size.saturating_sub(pos));
+            let blocks = self.blocks_in(pos, len);
+            self.fetch_blocks(&mut state, blocks)?;
+        }
+        self.local.pread(pos, c)
+    }
+
+    fn pwrite(&self, pos: u64, buffer: Arc<Buffer>, c: Completion) -> Result<Completion> {
+        tracing::debug!(
+            "pwrite(path={}): pos={}, size={}",
+            self.path,
+            pos,
+            buffer.len()
+        );
+        {
+            let mut state = self.state.lock();
+            let blocks = self.blocks_in(pos, buffer.len() as u64);
+            // Blocks that are only partially overwritten need the rest of their bytes.
+            self.fetch_blocks(&mut state, blocks.clone())?;
+            state.dirty.extend(blocks);
+            state.size = state.size.max(pos + buffer.len() as u64);
+        }
+        self.local.pwrite(pos, buffer, c)
+    }
+
+    fn sync(&self, c: Completion) -> Result<Completion> {
+        tracing::debug!("sync(path={})", self.path);
+        self.upload(&mut self.state.lock())?;
+        self.local.sync(c)
+    }
+
+    fn size(&self) -> Result<u64> {
+        Ok(self.state.lock().size)
+    }
+
+    fn truncate(&self, len: u64, c: Completion) -> Result<Completion> {
+        tracing::debug!("truncate(path={}): len={}", self.path, len);
+        {
+            let mut state = self.state.lock();
+            let kept_blocks = len.div_ceil(self.block_size);
+            state.remote_size = state.remote_size.min(len);
+            state.cached.retain(|&block| block < kept_blocks);
+            state.dirty.retain(|&block| block < kept_blocks);
+            state.size = len;
+            state.resized = true;
+        }
+        self.local.truncate(len, c)
+    }
+}
+
+/// The local WAL of a registered file. Truncating it drops the frames that a `TRUNCATE`
+/// checkpoint copied into the cache file, so the cache file is uploaded first.
+struct TieredWalFile {
+    db_path: String,
+    open_files: Arc<Mutex<HashMap<String, Weak<TieredFile>>>>,
+    local: Arc<dyn File>,
+}
+
+impl File for TieredWalFile {
+    fn lock_file(&self, exclusive: bool) -> Result<()> {
+        self.local.lock_file(exclusive)
+    }
+
+    fn unlock_file(&self) -> Result<()> {
+        self.local.unlock_file()
+    }
+
+    fn pread(&self, pos: u64, c: Completion) -> Result<Completion> {
+        self.local.pread(pos, c)
+    }
+
+    fn pwrite(&self, pos: u64, buffer: Arc<Buffer>, c: Completion) -> Result<Completion> {
+        self.local.pwrite(pos, buffer, c)
+    }
+
+    fn pwritev(&self, pos: u64, buffers: Vec<Arc<Buffer>>, c: Completion) -> Result<Completion> {
+        self.local.pwritev(pos, buffers, c)
+    }
+
+    fn sync(&self, c: Completion) -> Result<Completion> {
+        self.local.sync(c)
+    }
+
+    fn size(&self) -> Result<u64> {
+        self.local.size()
+    }
+
+    fn truncate(&self, len: u64, c: Completion) -> Result<Completion> {
+        let db_file = self
+            .open_files
+            .lock()
+            .get(&self.db_path)
+            .and_then(Weak::upgrade);
+        if let Some(db_file) = db_file {
+            debug!("uploading {} before truncating its WAL", self.db_path);
+            db_file.upload(&mut db_file.state.lock())?;
+        }
+        self.local.truncate(len, c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Database, DatabaseOpts, MemoryIO};
+
+    #[derive(Default)]
+    struct MemoryObject {
+        data: Mutex<Vec<u8>>,
+    }
+
+    impl RangeReader for MemoryObject {
+        fn size(&self) -> Result<u64> {
+            Ok(self.data.lock().len() as u64)
+        }
+
+        fn read_range(&self, offset: u64, buf: &mut [u8]) -> Result<()> {
+            let offset = offset as usize;
+            buf.copy_from_slice(&self.data.lock()[offset..offset + buf.len()]);
+            Ok(())
+        }
+    }
+
+    impl RemoteObject for MemoryObject {
+        fn write_ranges(&self, ranges: &[(u64, &[u8])], size: u64) -> Result<()> {
+            let mut data = self.data.lock();
+            data.resize(size as usize, 0);
+            for (offset, bytes) in ranges {
+                let offset = *offset as usize;
+                data[offset..offset + bytes.len()].copy_from_slice(bytes);
+            }
+            Ok(())
+        }
+    }
+
+    fn count_rows(remote: Arc<MemoryObject>) -> i64 {
+        let io = Arc::new(TieredIO::new(Arc::new(MemoryIO::new()), 8192));
+        io.register("replica.db", remote);
+        let db = Database::open_file_with_flags(
+            io,
+            "replica.db",
+            OpenFlags::ReadOnly,
+            DatabaseOpts::new(),
+            None,
+        )
+        .unwrap();
+        let conn = db.connect().unwrap();
+        let mut stmt = conn.prepare("SELECT count(*) FROM t").unwrap();
+        let mut count = 0;
+        stmt.run_with_row_callback(|row| {
+            count = row.get::<i64>(0)?;
+            Ok(())
+        })
+        .unwrap();
+        count
+    }
+
+    #[test]
+    fn test_checkpoint_uploads_dirty_blocks() {
+        let remote = Arc::new(MemoryObject::default());
+        let io = Arc::new(TieredIO::new(Arc::new(MemoryIO::new()), 8192));
+        io.register("edge.db", remote.clone());
+        let db = Database::open_file_with_flags(
+            io,
+            "edge.db",
+            OpenFlags::default(),
+            DatabaseOpts::new(),
+            None,
+        )
+        .unwrap();
+        let conn = db.connect().unwrap();
+        conn.wal_auto_checkpoint_disable();
+        conn.execute("CREATE TABLE t(x INTEGER PRIMARY KEY, y)")
+            .unwrap();
+        conn.execute("INSERT INTO t SELECT value, randomblob(200) FROM generate_series(1, 500)")
+            .unwrap();
+        // Committed transactions are only in the local WAL.
+        assert_eq!(remote.size().unwrap(), 0);
+
+        conn.execute("PRAGMA wal_checkpoint(FULL)").unwrap();
+        assert_eq!(count_rows(remote.clone()), 500);
+
+        conn.execute("DELETE FROM t WHERE x > 100").unwrap();
+        conn.execute("PRAGMA wal_checkpoint(FULL)").unwrap();
+        assert_eq!(count_rows(remote), 100);
+    }
+
+    #[test]
+    fn test_reopen_after_close_keeps_data() {
+        let remote = Arc::new(MemoryObject::default());
+        let io = Arc::new(TieredIO::new(Arc::new(MemoryIO::new()), 8192));
+        io.register("edge.db", remote.clone());
+        let open = || {
+            Database::open_file_with_flags(
+                io.clone(),
+                "edge.db",
+                OpenFlags::default(),
+                DatabaseOpts::new(),
+                None,
+            )
+            .unwrap()
+        };
+
+        let db = open();
+        let conn = db.connect().unwrap();
+        conn.execute("CREATE TABLE t(x INTEGER PRIMARY KEY, y)")
+            .unwrap();
+        conn.execute("INSERT INTO t SELECT value, randomblob(200) FROM generate_series(1, 500)")
+            .unwrap();
+        // Closing the last connection checkpoints the WAL and truncates it.
+        conn.close().unwrap();
+        drop(conn);
+        drop(db);
+        assert_eq!(count_rows(remote.clone()), 500);
+
+        let db = open();
+        let conn = db.connect().unwrap();
+        let mut stmt = conn.prepare("SELECT count(*) FROM t").unwrap();
+        let mut count = 0;
+        stmt.run_with_row_callback(|row| {
+            count = row.get::<i64>(0)?;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(count, 500);
+    }
+}
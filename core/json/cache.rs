@@ -422,7 +422,10 @@ mod tests {
         // Test error handling
         let error_result = cache_cell.get_or_insert_with(&key, |_| {
             // Return an error
-            Err(crate::LimboError::Constraint("Test error".to_string()))
+            Err(crate::LimboError::Constraint {
+                code: crate::error::SQLITE_CONSTRAINT,
+                message: "Test error".to_string(),
+            })
         });
 
         // Should propagate the error
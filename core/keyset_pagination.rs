@@ -0,0 +1,311 @@
+use std::sync::Arc;
+
+use crate::{Connection, LimboError, Result, Value};
+
+/// Pages through the rows of a table in the order of a key, as a replacement for paging with
+/// `LIMIT ... OFFSET ...`, which reads and discards all the rows of the previous pages.
+///
+/// Each page continues after the key of the last row of the previous page, with a comparison of
+/// row values such as `(created, id) > (?1, ?2)`. The rowid is appended to the key, unless it is
+/// already part of it, so that rows with equal keys are neither skipped nor repeated. When an index
+/// starts with the key columns and the table has an `INTEGER PRIMARY KEY`, a page seeks the index
+/// to its first row and reads the following entries in index order without sorting them, so each
+/// page costs the same wherever it is in the table. Rows with NULL in a column of the key are
+/// never returned.
+///
+/// ```ignore
+/// let events = KeysetPagination::new("events")
+///     .with_columns(&["id", "body"])
+///     .with_key(&["created"]);
+/// let mut page = events.paginate(&conn, None, 100)?;
+/// while let Some(key) = page.next_key {
+///     page = events.paginate(&conn, Some(&key), 100)?;
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct KeysetPagination {
+    table: String,
+    columns: Vec<String>,
+    key: Vec<String>,
+    filter: Option<String>,
+    descending: bool,
+}
+
+/// A page of rows returned by [KeysetPagination::paginate].
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeysetPage {
+    /// The rows of the page, with the columns given to [KeysetPagination::with_columns].
+    pub rows: Vec<Vec<Value>>,
+    /// The key of the last row of the page, which the next page continues after,
+    /// or None if there are no more rows.
+    pub next_key: Option<Vec<Value>>,
+}
+
+impl KeysetPagination {
+    /// Creates a pagination over all the columns of `table`, in rowid order.
+    pub fn new(table: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            columns: Vec::new(),
+            key: Vec::new(),
+            filter: None,
+            descending: false,
+        }
+    }
+
+    /// Only returns the given columns, in this order.
+    pub fn with_columns(mut self, columns: &[&str]) -> Self {
+        self.columns = columns.iter().map(|c| c.to_string()).collect();
+        self
+    }
+
+    /// Orders the rows by the given columns, followed by the rowid.
+    pub fn with_key(mut self, key: &[&str]) -> Self {
+        self.key = key.iter().map(|c| c.to_string()).collect();
+        self
+    }
+
+    /// Only returns the rows for which the SQL expression `filter` is true. The expression can
+    /// reference the columns of the table, but not parameters.
+    pub fn with_filter(mut self, filter: impl Into<String>) -> Self {
+        self.filter = Some(filter.into());
+        self
+    }
+
+    /// Returns the rows in descending order of the key.
+    pub fn descending(mut self) -> Self {
+        self.descending = true;
+        self
+    }
+
+    /// Fetches at most `n` rows that come after `after_key` in the order of the key, or the
+    /// first `n` rows if `after_key` is None. `after_key` is the [KeysetPage::next_key] of the
+    /// previous page.
+    pub fn paginate(
+        &self,
+        conn: &Arc<Connection>,
+        after_key: Option<&[Value]>,
+        n: usize,
+    ) -> Result<KeysetPage> {
+        let (sql, key_len) = self.page_sql(conn, after_key.is_some(), n)?;
+        let mut stmt = conn.prepare(sql)?;
+        if let Some(after_key) = after_key {
+            if after_key.len() != key_len {
+                return Err(LimboError::InvalidArgument(format!(
+                    "expected a key of {key_len} values, got {}",
+                    after_key.len()
+                )));
+            }
+            if after_key.iter().any(|value| matches!(value, Value::Null)) {
+                return Err(LimboError::InvalidArgument(
+                    "a key cannot contain NULL".to_string(),
+                ));
+            }
+            for (i, value) in after_key.iter().enumerate() {
+                stmt.bind_at((i + 1).try_into().unwrap(), value.clone());
+            }
+        }
+        let mut rows = stmt.run_collect_rows()?;
+        let mut next_key = None;
+        for row in rows.iter_mut() {
+            // The key columns are selected after the requested ones.
+            next_key = Some(row.split_off(row.len() - key_len));
+        }
+        if rows.len() < n {
+            next_key = None;
+        }
+        Ok(KeysetPage { rows, next_key })
+    }
+
+    /// The query that fetches a page, with the values of the key to continue after as parameters,
+    /// and the number of columns in the key.
+    fn page_sql(
+        &self,
+        conn: &Arc<Connection>,
+        has_after_key: bool,
+        n: usize,
+    ) -> Result<(String, usize)> {
+        let schema = conn.schema.read().clone();
+        let Some(table) = schema.get_btree_table(&self.table) else {
+            return Err(LimboError::ParseError(format!(
+                "no such table: {}",
+                self.table
+            )));
+        };
+        let column_name = |name: &String| match table.get_column(name) {
+            Some((_, column)) => Ok(quote_ident(column.name.as_deref().unwrap_or(name))),
+            None => Err(LimboError::ParseError(format!("no such column: {name}"))),
+        };
+        let columns = if self.columns.is_empty() {
+            "*".to_string()
+        } else {
+            self.columns
+                .iter()
+                .map(column_name)
+                .collect::<Result<Vec<_>>>()?
+                .join(", ")
+        };
+        let mut key = self
+            .key
+            .iter()
+            .map(column_name)
+            .collect::<Result<Vec<_>>>()?;
+        // Rows with NULL in the key can't be compared with the key of the previous page.
+        let mut conditions: Vec<String> = key.iter().map(|k| format!("{k} IS NOT NULL")).collect();
+        if table.has_rowid {
+            // The rowid alias is used instead of `rowid` so that indexes can be seeked with it.
+            let rowid = table
+                .columns
+                .iter()
+                .find(|c| c.is_rowid_alias())
+                .and_then(|c| c.name.as_deref())
+                .map_or("rowid".to_string(), quote_ident);
+            if !key.contains(&rowid) {
+                key.push(rowid);
+            }
+        }
+        if key.is_empty() {
+            return Err(LimboError::InvalidArgument(format!(
+                "a key is required to paginate WITHOUT ROWID table {}",
+                table.name
+            )));
+        }
+        let (cmp, order) = if self.descending {
+            ("<", " DESC")
+        } else {
+            (">", "")
+        };
+        if has_after_key {
+            let params: Vec<String> = (1..=key.len()).map(|i| format!("?{i}")).collect();
+            conditions.push(format!(
+                "({}) {cmp} ({})",
+                key.join(", "),
+                params.join(", ")
+            ));
+        }
+        if let Some(filter) = &self.filter {
+            conditions.push(format!("({filter})"));
+        }
+        let mut sql = format!(
+            "SELECT {columns}, {} FROM {}",
+            key.join(", "),
+            quote_ident(&table.name)
+        );
+        if !conditions.is_empty() {
+            sql.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+        }
+        let order_by: Vec<String> = key.iter().map(|k| format!("{k}{order}")).collect();
+        sql.push_str(&format!(" ORDER BY {} LIMIT {n}", order_by.join(", ")));
+        Ok((sql, key.len()))
+    }
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Database, MemoryIO};
+
+    fn paginate_all(
+        conn: &Arc<Connection>,
+        pagination: &KeysetPagination,
+        n: usize,
+    ) -> Vec<Vec<Value>> {
+        let mut rows = Vec::new();
+        let mut after_key = None;
+        loop {
+            let page = pagination.paginate(conn, after_key.as_deref(), n).unwrap();
+            assert!(page.rows.len() <= n);
+            rows.extend(page.rows);
+            match page.next_key {
+                Some(key) => after_key = Some(key),
+                None => return rows,
+            }
+        }
+    }
+
+    #[test]
+    fn test_keyset_pagination() {
+        let db = Database::open_file(Arc::new(MemoryIO::new()), ":memory:").unwrap();
+        let conn = db.connect().unwrap();
+        conn.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, created, \"the body\")")
+            .unwrap();
+        conn.execute("CREATE INDEX t_created ON t (created)")
+            .unwrap();
+        conn.execute("BEGIN").unwrap();
+        for i in 1..=100 {
+            let created = if i % 10 == 0 {
+                "NULL".to_string()
+            } else {
+                (i % 7).to_string()
+            };
+            conn.execute(format!("INSERT INTO t VALUES ({i}, {created}, 'b{i}')"))
+                .unwrap();
+        }
+        conn.execute("COMMIT").unwrap();
+
+        let expected = |sql: &str| conn.prepare(sql).unwrap().run_collect_rows().unwrap();
+        let by_created = KeysetPagination::new("T")
+            .with_columns(&["id", "The Body"])
+            .with_key(&["created"]);
+        for n in [1, 7, 30, 90, 200] {
+            assert_eq!(
+                paginate_all(&conn, &by_created, n),
+                expected(
+                    "SELECT id, \"the body\" FROM t WHERE created IS NOT NULL ORDER BY created, id"
+                )
+            );
+        }
+        assert_eq!(
+            paginate_all(
+                &conn,
+                &by_created.clone().with_filter("id % 3 = 0").descending(),
+                4
+            ),
+            expected(
+                "SELECT id, \"the body\" FROM t WHERE created IS NOT NULL AND id % 3 = 0 ORDER BY created DESC, id DESC"
+            )
+        );
+        assert_eq!(
+            paginate_all(&conn, &KeysetPagination::new("t"), 15),
+            expected("SELECT * FROM t")
+        );
+
+        let page = by_created
+            .paginate(&conn, Some(&[Value::Integer(5), Value::Integer(90)]), 3)
+            .unwrap();
+        assert_eq!(
+            page.rows
+                .iter()
+                .map(|row| row[0].clone())
+                .collect::<Vec<_>>(),
+            vec![Value::Integer(96), Value::Integer(6), Value::Integer(13)]
+        );
+        assert_eq!(
+            page.next_key,
+            Some(vec![Value::Integer(6), Value::Integer(13)])
+        );
+
+        for (pagination, after_key) in [
+            (by_created.clone(), vec![Value::Integer(6)]),
+            (by_created.clone(), vec![Value::Null, Value::Integer(1)]),
+        ] {
+            assert!(matches!(
+                pagination.paginate(&conn, Some(&after_key), 3),
+                Err(LimboError::InvalidArgument(_))
+            ));
+        }
+        for pagination in [
+            KeysetPagination::new("missing"),
+            KeysetPagination::new("t").with_key(&["missing"]),
+        ] {
+            assert!(matches!(
+                pagination.paginate(&conn, None, 3),
+                Err(LimboError::ParseError(_))
+            ));
+        }
+    }
+}
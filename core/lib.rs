@@ -3,6 +3,9 @@ extern crate core;
 
 mod assert;
 pub mod busy;
+#[cfg(feature = "carray")]
+mod carray;
+mod dbstat;
 mod error;
 mod ext;
 mod fast_lock;
@@ -14,13 +17,18 @@ mod info;
 pub mod io;
 #[cfg(feature = "json")]
 mod json;
+mod keyset_pagination;
 pub mod mvcc;
+#[cfg(not(target_family = "wasm"))]
+mod parallel_scan;
 mod parameters;
 mod pragma;
 mod pseudo;
+pub mod record;
 pub mod schema;
 #[cfg(feature = "series")]
 mod series;
+mod snapshot;
 pub mod state_machine;
 mod statement;
 mod stats;
@@ -61,20 +69,30 @@ use crate::vdbe::metrics::ConnectionMetrics;
 use crate::vtab::VirtualTable;
 use crate::{incremental::view::AllViewsTxState, translate::emitter::TransactionMode};
 use arc_swap::{ArcSwap, ArcSwapOption};
+#[cfg(feature = "carray")]
+pub use carray::CArray;
 use core::str;
 pub use error::{CompletionError, LimboError};
+pub use ext::StaticExtension;
 pub use io::clock::{Clock, Instant};
+use io::TempDirectory;
 #[cfg(all(feature = "fs", target_family = "unix", not(miri)))]
 pub use io::UnixIO;
 #[cfg(all(feature = "fs", target_os = "linux", feature = "io_uring", not(miri)))]
 pub use io::UringIO;
 pub use io::{
-    Buffer, Completion, CompletionType, File, GroupCompletion, MemoryIO, OpenFlags, PlatformIO,
-    SyscallIO, WriteCompletion, IO,
+    Buffer, Completion, CompletionType, EmbeddedIO, File, GroupCompletion, MemoryIO, OpenFlags,
+    PlatformIO, SyscallIO, WriteCompletion, IO,
 };
+#[cfg(feature = "object_store")]
+pub use io::{HttpRangeReader, ObjectStoreIO, RangeReader, RemoteObject, TieredIO};
+pub use keyset_pagination::{KeysetPage, KeysetPagination};
+#[cfg(not(target_family = "wasm"))]
+pub use parallel_scan::{ParallelScan, ParallelScanRows};
 use parking_lot::{Mutex, RwLock};
 use rustc_hash::FxHashMap;
 use schema::Schema;
+pub use snapshot::Snapshot;
 pub use statement::Statement;
 use std::collections::HashSet;
 use std::time::Duration;
@@ -83,7 +101,10 @@ use std::{
     fmt::{self, Display},
     ops::Deref,
     sync::{
-        atomic::{AtomicBool, AtomicI32, AtomicI64, AtomicIsize, AtomicU16, AtomicUsize, Ordering},
+        atomic::{
+            AtomicBool, AtomicI32, AtomicI64, AtomicIsize, AtomicU16, AtomicU32, AtomicUsize,
+            Ordering,
+        },
         Arc, LazyLock, Weak,
     },
 };
@@ -97,8 +118,11 @@ pub use storage::{
     buffer_pool::BufferPool,
     database::DatabaseStorage,
     pager::PageRef,
-    pager::{Page, Pager},
-    wal::{CheckpointMode, CheckpointResult, Wal, WalFile, WalFileShared},
+    pager::{GroupCommit, Page, Pager},
+    wal::{
+        CheckpointMode, CheckpointResult, Wal, WalFile, WalFileShared, WalHookCallback,
+        DEFAULT_WAL_AUTOCHECKPOINT,
+    },
 };
 use tracing::{instrument, Level};
 use turso_macros::{match_ignore_ascii_case, AtomicEnum};
@@ -110,7 +134,7 @@ use util::parse_schema_rows;
 pub use util::IOExt;
 pub use vdbe::{
     builder::QueryMode, explain::EXPLAIN_COLUMNS, explain::EXPLAIN_QUERY_PLAN_COLUMNS,
-    FromValueRow, Register,
+    metrics::StatementMetrics, FromValueRow, Register,
 };
 
 /// Configuration for database features
@@ -122,7 +146,11 @@ pub struct DatabaseOpts {
     pub enable_index_method: bool,
     pub enable_autovacuum: bool,
     pub enable_triggers: bool,
+    /// Page size of the database when it is created, see [DatabaseOpts::with_page_size].
+    pub page_size: Option<u32>,
     enable_load_extension: bool,
+    #[cfg(feature = "background_checkpoint")]
+    pub enable_background_checkpoint: bool,
 }
 
 impl DatabaseOpts {
@@ -156,6 +184,16 @@ impl DatabaseOpts {
         self
     }
 
+    /// Creates the database with pages of `page_size` bytes, a power of two between 512 and
+    /// 65536, instead of the default of 4096. Databases that already exist keep their page size.
+    pub fn with_page_size(mut self, page_size: u32) -> Self {
+        self.page_size = Some(page_size);
+        self
+    }
+
+    /// Enables the experimental support for databases with `auto_vacuum`. Without it, such
+    /// databases can be opened and read, but not written, because the pointer map pages that
+    /// track the parent of every page would not be updated.
     pub fn with_autovacuum(mut self, enable: bool) -> Self {
         self.enable_autovacuum = enable;
         self
@@ -165,6 +203,13 @@ impl DatabaseOpts {
         self.enable_triggers = enable;
         self
     }
+
+    /// Run automatic checkpoints on a dedicated thread instead of on the committing connection.
+    #[cfg(feature = "background_checkpoint")]
+    pub fn with_background_checkpoint(mut self, enable: bool) -> Self {
+        self.enable_background_checkpoint = enable;
+        self
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -204,6 +249,8 @@ impl TransactionState {
 #[derive(Debug, AtomicEnum, Clone, Copy, PartialEq, Eq)]
 pub enum SyncMode {
     Off = 0,
+    /// In WAL mode, commits do not sync the WAL; it is synced by checkpoints instead.
+    Normal = 1,
     Full = 2,
 }
 
@@ -223,6 +270,8 @@ static DATABASE_MANAGER: LazyLock<Mutex<HashMap<String, Weak<Database>>>> =
 pub struct Database {
     mv_store: ArcSwapOption<MvStore>,
     schema: Mutex<Arc<Schema>>,
+    /// Committed schemas of the namespaces loaded by the connections of this database.
+    namespace_schemas: Mutex<FxHashMap<String, Arc<Schema>>>,
     pub db_file: Arc<dyn DatabaseStorage>,
     pub path: String,
     wal_path: String,
@@ -244,6 +293,12 @@ pub struct Database {
     // Encryption
     encryption_key: RwLock<Option<EncryptionKey>>,
     encryption_cipher_mode: AtomicCipherMode,
+
+    /// Group commit window, shared by the pagers of all connections.
+    group_commit: Arc<GroupCommit>,
+
+    #[cfg(feature = "background_checkpoint")]
+    background_checkpointer: std::sync::OnceLock<storage::checkpointer::BackgroundCheckpointer>,
 }
 
 // SAFETY: This needs to be audited for thread safety.
@@ -347,6 +402,7 @@ impl Database {
             path: path.into(),
             wal_path: wal_path.into(),
             schema: Mutex::new(Arc::new(Schema::new())),
+            namespace_schemas: Mutex::new(FxHashMap::default()),
             _shared_page_cache: shared_page_cache.clone(),
             shared_wal,
             db_file,
@@ -364,6 +420,9 @@ impl Database {
             encryption_cipher_mode: AtomicCipherMode::new(
                 encryption_cipher_mode.unwrap_or(CipherMode::None),
             ),
+            group_commit: Arc::new(GroupCommit::default()),
+            #[cfg(feature = "background_checkpoint")]
+            background_checkpointer: std::sync::OnceLock::new(),
         };
 
         db.register_global_builtin_extensions()
@@ -540,6 +599,13 @@ impl Database {
             mv_store.bootstrap(mvcc_bootstrap_conn)?;
         }
 
+        #[cfg(feature = "background_checkpoint")]
+        if db.opts.enable_background_checkpoint && !db.is_readonly() {
+            let checkpointer =
+                storage::checkpointer::BackgroundCheckpointer::spawn(Arc::downgrade(&db))?;
+            let _ = db.background_checkpointer.set(checkpointer);
+        }
+
         Ok(db)
     }
 
@@ -562,19 +628,16 @@ impl Database {
             AutoVacuumMode::None
         };
 
-        // Force autovacuum to None if the experimental flag is not enabled
+        // Force autovacuum to None if the experimental flag is not enabled. Such databases can
+        // be read, but writes are refused since they would not maintain the pointer map.
         let final_mode = if !self.opts.enable_autovacuum {
-            if mode != AutoVacuumMode::None {
-                tracing::warn!(
-                        "Database has autovacuum enabled but --experimental-autovacuum flag is not set. Forcing autovacuum to None."
-                    );
-            }
             AutoVacuumMode::None
         } else {
             mode
         };
 
         pager.set_auto_vacuum_mode(final_mode);
+        pager.set_ptrmap_unmaintained(mode != final_mode);
 
         tracing::debug!(
                 "Opened existing database. Detected auto_vacuum_mode from header: {:?}, final mode: {:?}",
@@ -591,13 +654,23 @@ impl Database {
     fn header_validation(&mut self) -> Result<Arc<Pager>> {
         let wal_exists = journal_mode::wal_exists(std::path::Path::new(&self.wal_path));
         let log_exists = journal_mode::logical_log_exists(std::path::Path::new(&self.path));
-        let is_readonly = self.open_flags.contains(OpenFlags::ReadOnly);
 
         let mut pager = self._init()?;
         assert!(pager.wal.is_none(), "Pager should have no WAL yet");
 
         let header: HeaderRefMut = self.io.block(|| HeaderRefMut::from_pager(&pager))?;
         let header_mut = header.borrow_mut();
+
+        let is_readonly = self.open_flags.contains(OpenFlags::ReadOnly);
+        // Pointer map pages are only maintained when autovacuum is enabled. Writing to such a
+        // file otherwise would allocate pages over the pointer map and leave stale entries
+        // behind, so write transactions on it are refused, see `Pager::check_ptrmap_maintained`.
+        if header_mut.vacuum_mode_largest_root_page.get() > 0 && !self.opts.enable_autovacuum {
+            tracing::warn!(
+                "Database {} has autovacuum enabled but --experimental-autovacuum flag is not set. It can only be read.",
+                self.path
+            );
+        }
         let (read_version, write_version) = { (header_mut.read_version, header_mut.write_version) };
         // TODO: right now we don't support READ ONLY and no READ or WRITE in the Version header
         // https://www.sqlite.org/fileformat.html#file_format_version_numbers
@@ -710,6 +783,12 @@ impl Database {
         self._connect(false, None)
     }
 
+    /// Opens a read-only [Snapshot] of the database at its last commit. Queries on the snapshot
+    /// don't see the changes committed after it was opened.
+    pub fn snapshot(self: &Arc<Database>) -> Result<Snapshot> {
+        Snapshot::open(self)
+    }
+
     #[instrument(skip_all, level = Level::INFO)]
     fn _connect(
         self: &Arc<Database>,
@@ -747,9 +826,13 @@ impl Database {
             cache_size: AtomicI32::new(default_cache_size),
             page_size: AtomicU16::new(page_size.get_raw()),
             wal_auto_checkpoint_disabled: AtomicBool::new(false),
+            wal_autocheckpoint: AtomicU32::new(DEFAULT_WAL_AUTOCHECKPOINT),
+            wal_hook: RwLock::new(None),
             capture_data_changes: RwLock::new(CaptureDataChangesMode::Off),
             closed: AtomicBool::new(false),
+            interrupted: AtomicBool::new(false),
             attached_databases: RwLock::new(DatabaseCatalog::new()),
+            attached_transactions: RwLock::new(FxHashMap::default()),
             query_only: AtomicBool::new(false),
             mv_tx: RwLock::new(None),
             view_transaction_states: AllViewsTxState::new(),
@@ -765,7 +848,12 @@ impl Database {
             is_mvcc_bootstrap_connection: AtomicBool::new(is_mvcc_bootstrap_connection),
             fk_pragma: AtomicBool::new(false),
             fk_deferred_violations: AtomicIsize::new(0),
+            case_sensitive_like: AtomicBool::new(false),
+            automatic_index: AtomicBool::new(true),
+            load_extension_enabled: AtomicBool::new(false),
+            vdbe_profile: AtomicBool::new(false),
             vtab_txn_states: RwLock::new(HashSet::new()),
+            temp_directory: Arc::new(TempDirectory::default()),
         });
         self.n_connections
             .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
@@ -841,7 +929,9 @@ impl Database {
         if self.initialized() {
             Ok(self.read_page_size_from_db_header()?)
         } else {
-            let Some(size) = requested_page_size else {
+            let Some(size) =
+                requested_page_size.or_else(|| self.opts.page_size.map(|size| size as usize))
+            else {
                 return Ok(PageSize::default());
             };
             let Some(page_size) = PageSize::new(size as u32) else {
@@ -900,7 +990,7 @@ impl Database {
             None
         };
 
-        let pager = Pager::new(
+        let mut pager = Pager::new(
             self.db_file.clone(),
             pager_wal,
             self.io.clone(),
@@ -909,6 +999,7 @@ impl Database {
             self.init_lock.clone(),
             self.init_page_1.clone(),
         )?;
+        pager.group_commit = self.group_commit.clone();
         pager.set_page_size(page_size);
         if let Some(reserved_bytes) = reserved_bytes {
             pager.set_reserved_space_bytes(reserved_bytes);
@@ -1001,6 +1092,23 @@ impl Database {
     }
 
     pub(crate) fn update_schema_if_newer(&self, another: Arc<Schema>) {
+        if let Some(namespace) = &another.namespace {
+            // The schema cookie is shared by all the namespaces of the file, so connections using
+            // other namespaces must notice the change as well.
+            let mut schema = self.schema.lock();
+            if schema.schema_version < another.schema_version {
+                Arc::make_mut(&mut *schema).schema_version = another.schema_version;
+            }
+            drop(schema);
+            let mut namespace_schemas = self.namespace_schemas.lock();
+            let is_newer = namespace_schemas
+                .get(namespace)
+                .is_none_or(|schema| schema.schema_version < another.schema_version);
+            if is_newer {
+                namespace_schemas.insert(namespace.clone(), another);
+            }
+            return;
+        }
         let mut schema = self.schema.lock();
         if schema.schema_version < another.schema_version {
             tracing::debug!(
@@ -1008,6 +1116,12 @@ impl Database {
                 schema.schema_version,
                 another.schema_version
             );
+            // Forget the namespaces that were dropped.
+            self.namespace_schemas.lock().retain(|namespace, _| {
+                another
+                    .get_btree_table(&schema::namespace_table_name(namespace))
+                    .is_some()
+            });
             *schema = another;
         } else {
             tracing::debug!(
@@ -1018,6 +1132,26 @@ impl Database {
         }
     }
 
+    /// Returns the committed schema of a namespace if a connection loaded it and the namespace was
+    /// not dropped since.
+    pub(crate) fn namespace_schema(&self, namespace: &str) -> Option<Arc<Schema>> {
+        let default_schema = self.clone_schema();
+        let root_page = default_schema
+            .get_btree_table(&schema::namespace_table_name(namespace))?
+            .root_page;
+        let mut namespace_schemas = self.namespace_schemas.lock();
+        let schema = namespace_schemas.get_mut(namespace)?;
+        if schema.schema_table_root_page() != root_page {
+            // The namespace was dropped and created again.
+            namespace_schemas.remove(namespace);
+            return None;
+        }
+        if schema.schema_version < default_schema.schema_version {
+            Arc::make_mut(schema).schema_version = default_schema.schema_version;
+        }
+        Some(schema.clone())
+    }
+
     pub fn get_mv_store(&self) -> impl Deref<Target = Option<Arc<MvStore>>> {
         self.mv_store.load()
     }
@@ -1121,6 +1255,9 @@ struct DatabaseCatalog {
     index_to_data: HashMap<usize, (Arc<Database>, Arc<Pager>)>,
 }
 
+/// Index of the temp schema in the [DatabaseCatalog], the main database is always 0.
+pub(crate) const TEMP_DB_INDEX: usize = 1;
+
 #[allow(unused)]
 impl DatabaseCatalog {
     fn new() -> Self {
@@ -1243,10 +1380,19 @@ pub struct Connection {
     /// Disable automatic checkpoint behaviour when DB is shutted down or WAL reach certain size
     /// Client still can manually execute PRAGMA wal_checkpoint(...) commands
     wal_auto_checkpoint_disabled: AtomicBool,
+    /// Number of frames past the last backfilled frame after which a commit runs a passive
+    /// checkpoint, 0 to never checkpoint on commit. Set with `PRAGMA wal_autocheckpoint`.
+    wal_autocheckpoint: AtomicU32,
+    wal_hook: RwLock<Option<WalHookCallback>>,
     capture_data_changes: RwLock<CaptureDataChangesMode>,
     closed: AtomicBool,
+    /// Set by [Connection::interrupt] to abort the statements currently running on this connection
+    interrupted: AtomicBool,
     /// Attached databases
     attached_databases: RwLock<DatabaseCatalog>,
+    /// Transactions open on attached and temp databases, by database index. The transaction of
+    /// the main database is tracked by `transaction_state`.
+    attached_transactions: RwLock<FxHashMap<usize, TransactionState>>,
     query_only: AtomicBool,
     pub(crate) mv_tx: RwLock<Option<(crate::mvcc::database::TxID, TransactionMode)>>,
 
@@ -1279,8 +1425,18 @@ pub struct Connection {
     /// Whether pragma foreign_keys=ON for this connection
     fk_pragma: AtomicBool,
     fk_deferred_violations: AtomicIsize,
+    /// Whether pragma case_sensitive_like=ON for this connection
+    case_sensitive_like: AtomicBool,
+    /// Whether pragma automatic_index=ON for this connection
+    automatic_index: AtomicBool,
+    /// Whether the load_extension() SQL function may be used on this connection
+    load_extension_enabled: AtomicBool,
+    /// Whether pragma vdbe_profile=ON for this connection
+    vdbe_profile: AtomicBool,
     /// Track when each virtual table instance is currently in transaction.
     vtab_txn_states: RwLock<HashSet<u64>>,
+    /// Holds the temp database, the spill files of sorters and hash tables, and ephemeral tables.
+    temp_directory: Arc<TempDirectory>,
 }
 
 // SAFETY: This needs to be audited for thread safety.
@@ -1361,8 +1517,10 @@ impl Connection {
         self._prepare(sql)
     }
 
-    #[instrument(skip_all, level = Level::INFO)]
+    #[instrument(skip_all, fields(sql = tracing::field::Empty), level = Level::INFO)]
     pub fn _prepare(self: &Arc<Connection>, sql: impl AsRef<str>) -> Result<Statement> {
+        #[cfg(feature = "query_tracing")]
+        tracing::Span::current().record("sql", sql.as_ref());
         if self.is_closed() {
             return Err(LimboError::InternalError("Connection closed".to_string()));
         }
@@ -1374,8 +1532,11 @@ impl Connection {
 
         let sql = sql.as_ref();
         tracing::debug!("Preparing: {}", sql);
+        let prepare_start = self.pager.load().io.now();
         let mut parser = Parser::new(sql.as_bytes());
-        let cmd = parser.next_cmd()?;
+        let cmd = parser
+            .next_cmd()
+            .map_err(|e| LimboError::from_parse_error(e, sql))?;
         let syms = self.syms.read();
         let cmd = cmd.expect("Successful parse on nonempty input string should produce a command");
         let byte_offset_end = parser.offset();
@@ -1395,7 +1556,9 @@ impl Connection {
             mode,
             input,
         )?;
-        Ok(Statement::new(program, pager, mode))
+        let mut stmt = Statement::new(program, pager.clone(), mode);
+        stmt.set_prepare_time(elapsed_since(&pager, prepare_start));
+        Ok(stmt)
     }
 
     /// Whether this is an internal connection used for MVCC bootstrap
@@ -1498,6 +1661,7 @@ impl Connection {
         // create fresh schema as some objects can be deleted
         let mut fresh = Schema::new();
         fresh.schema_version = cookie;
+        let namespace = self.schema.read().namespace.clone();
 
         // Preserve existing views to avoid expensive repopulation.
         // TODO: We may not need to do this if we materialize our views.
@@ -1537,12 +1701,86 @@ impl Connection {
             fresh.schema_version,
             fresh.tables.keys()
         );
-        self.with_schema_mut(|schema| {
-            *schema = fresh;
-        });
+        match namespace {
+            None => self.with_schema_mut(|schema| {
+                *schema = fresh;
+            }),
+            Some(namespace) => {
+                // The connection keeps using its namespace, whose schema table is parsed again too.
+                let namespace_schema = self.parse_namespace_schema(&namespace, &fresh)?;
+                self.db.update_schema_if_newer(Arc::new(fresh));
+                *self.schema.write() = Arc::new(namespace_schema);
+            }
+        }
         Result::Ok(())
     }
 
+    /// Parses the schema table of a namespace registered in `default_schema`.
+    ///
+    /// Must be called within a transaction, the schema table is read with a statement prepared
+    /// on this connection.
+    pub(crate) fn parse_namespace_schema(
+        self: &Arc<Connection>,
+        namespace: &str,
+        default_schema: &Schema,
+    ) -> Result<Schema> {
+        let Some(table) = default_schema.get_btree_table(&schema::namespace_table_name(namespace))
+        else {
+            return Err(LimboError::ParseError(format!(
+                "no such namespace: {namespace}"
+            )));
+        };
+        let mut fresh = Schema::new_namespace(namespace, table.root_page);
+        fresh.schema_version = default_schema.schema_version;
+
+        // Same trick as in reparse_schema: the statement reading the schema table of the
+        // namespace is prepared against an empty schema with the current cookie.
+        let previous = std::mem::replace(&mut *self.schema.write(), Arc::new(fresh.clone()));
+        let result = self
+            .prepare("SELECT * FROM sqlite_schema")
+            .and_then(|stmt| {
+                parse_schema_rows(
+                    stmt,
+                    &mut fresh,
+                    &self.syms.read(),
+                    self.get_mv_tx(),
+                    std::collections::HashMap::new(),
+                    self.experimental_triggers_enabled(),
+                )
+            });
+        *self.schema.write() = previous;
+        result?;
+        Ok(fresh)
+    }
+
+    /// Returns the namespace this connection uses as its main database, `None` for the default
+    /// namespace.
+    pub fn current_namespace(&self) -> Option<String> {
+        self.schema.read().namespace.clone()
+    }
+
+    /// Returns the last committed schema of the namespace this connection uses.
+    pub(crate) fn committed_schema(&self) -> Result<Arc<Schema>> {
+        match self.current_namespace() {
+            None => Ok(self.db.clone_schema()),
+            Some(namespace) => self
+                .db
+                .namespace_schema(&namespace)
+                .ok_or_else(|| LimboError::ParseError(format!("no such namespace: {namespace}"))),
+        }
+    }
+
+    /// Returns the schema of the default namespace as seen by this connection, including the
+    /// uncommitted changes of its transaction when the connection uses the default namespace.
+    pub(crate) fn default_namespace_schema(&self) -> Arc<Schema> {
+        let schema = self.schema.read();
+        if schema.namespace.is_none() {
+            schema.clone()
+        } else {
+            self.db.clone_schema()
+        }
+    }
+
     #[instrument(skip_all, level = Level::INFO)]
     pub fn prepare_execute_batch(self: &Arc<Connection>, sql: impl AsRef<str>) -> Result<()> {
         if self.is_closed() {
@@ -1557,7 +1795,10 @@ impl Connection {
         let sql = sql.as_ref();
         tracing::trace!("Preparing and executing batch: {}", sql);
         let mut parser = Parser::new(sql.as_bytes());
-        while let Some(cmd) = parser.next_cmd()? {
+        while let Some(cmd) = parser
+            .next_cmd()
+            .map_err(|e| LimboError::from_parse_error(e, sql))?
+        {
             let syms = self.syms.read();
             let pager = self.pager.load().clone();
             let byte_offset_end = parser.offset();
@@ -1589,7 +1830,9 @@ impl Connection {
         self.maybe_update_schema();
         tracing::trace!("Querying: {}", sql);
         let mut parser = Parser::new(sql.as_bytes());
-        let cmd = parser.next_cmd()?;
+        let cmd = parser
+            .next_cmd()
+            .map_err(|e| LimboError::from_parse_error(e, sql))?;
         let byte_offset_end = parser.offset();
         let input = str::from_utf8(&sql.as_bytes()[..byte_offset_end])
             .unwrap()
@@ -1640,7 +1883,10 @@ impl Connection {
         let sql = sql.as_ref();
         self.maybe_update_schema();
         let mut parser = Parser::new(sql.as_bytes());
-        while let Some(cmd) = parser.next_cmd()? {
+        while let Some(cmd) = parser
+            .next_cmd()
+            .map_err(|e| LimboError::from_parse_error(e, sql))?
+        {
             let syms = self.syms.read();
             let pager = self.pager.load().clone();
             let byte_offset_end = parser.offset();
@@ -1669,7 +1915,10 @@ impl Connection {
         sql: impl AsRef<str>,
     ) -> Result<Option<(Statement, usize)>> {
         let mut parser = Parser::new(sql.as_ref().as_bytes());
-        let Some(cmd) = parser.next_cmd()? else {
+        let Some(cmd) = parser
+            .next_cmd()
+            .map_err(|e| LimboError::from_parse_error(e, sql.as_ref()))?
+        else {
             return Ok(None);
         };
         let syms = self.syms.read();
@@ -1718,13 +1967,30 @@ impl Connection {
             }
             (None, None) => None,
         };
-        let (io, db) = Database::open_new(
-            &opts.path,
-            opts.vfs.as_ref(),
-            flags,
-            db_opts,
-            encryption_opts.clone(),
-        )?;
+        let (io, db) = if opts.offset.is_some() || opts.length.is_some() {
+            let inner = match opts.vfs.as_ref() {
+                Some(vfs) => Database::io_for_vfs(vfs)?,
+                None => Database::io_for_path(&opts.path)?,
+            };
+            let io = Arc::new(EmbeddedIO::new(inner));
+            io.register(&opts.path, opts.offset.unwrap_or(0), opts.length);
+            let db = Database::open_file_with_flags(
+                io.clone(),
+                &opts.path,
+                flags,
+                db_opts,
+                encryption_opts.clone(),
+            )?;
+            (io as Arc<dyn IO>, db)
+        } else {
+            Database::open_new(
+                &opts.path,
+                opts.vfs.as_ref(),
+                flags,
+                db_opts,
+                encryption_opts.clone(),
+            )?
+        };
         if let Some(modeof) = opts.modeof {
             let perms = std::fs::metadata(modeof)?;
             std::fs::set_permissions(&opts.path, perms.permissions())?;
@@ -1767,6 +2033,47 @@ impl Connection {
     pub fn foreign_keys_enabled(&self) -> bool {
         self.fk_pragma.load(Ordering::Acquire)
     }
+
+    pub fn set_case_sensitive_like(&self, enable: bool) {
+        self.case_sensitive_like.store(enable, Ordering::Release);
+    }
+
+    pub fn case_sensitive_like(&self) -> bool {
+        self.case_sensitive_like.load(Ordering::Acquire)
+    }
+
+    pub fn set_automatic_index_enabled(&self, enable: bool) {
+        self.automatic_index.store(enable, Ordering::Release);
+    }
+
+    /// Whether the planner may build a transient index on the inner table of a join
+    /// when no persistent index can be used for the join key.
+    pub fn automatic_index_enabled(&self) -> bool {
+        self.automatic_index.load(Ordering::Acquire)
+    }
+
+    /// Enables or disables per-instruction profiling. The per-opcode totals collected so far
+    /// are discarded.
+    pub fn set_vdbe_profile_enabled(&self, enable: bool) {
+        self.vdbe_profile.store(enable, Ordering::Release);
+        self.metrics.write().opcode_profile.clear();
+    }
+
+    pub fn vdbe_profile_enabled(&self) -> bool {
+        self.vdbe_profile.load(Ordering::Acquire)
+    }
+
+    /// Enables or disables the load_extension() SQL function for this connection, like
+    /// sqlite3_enable_load_extension(). [Connection::load_extension] is not affected.
+    pub fn enable_load_extension(&self, enable: bool) {
+        self.load_extension_enabled.store(enable, Ordering::Release);
+    }
+
+    /// Returns whether the load_extension() SQL function may be used on this connection,
+    /// either because it was enabled on the connection or for the whole database.
+    pub fn load_extension_enabled(&self) -> bool {
+        self.load_extension_enabled.load(Ordering::Acquire) || self.db.can_load_extensions()
+    }
     pub(crate) fn clear_deferred_foreign_key_violations(&self) -> isize {
         self.fk_deferred_violations.swap(0, Ordering::Release)
     }
@@ -1777,12 +2084,17 @@ impl Connection {
 
     pub fn maybe_update_schema(&self) {
         let current_schema_version = self.schema.read().schema_version;
-        let schema = self.db.schema.lock();
+        let schema_version = self.db.schema.lock().schema_version;
         if matches!(self.get_tx_state(), TransactionState::None)
-            && current_schema_version != schema.schema_version
+            && current_schema_version != schema_version
         {
-            *self.schema.write() = schema.clone();
+            // If the namespace was dropped, the outdated schema is kept and statements fail
+            // with "no such namespace" once they notice the schema change.
+            if let Ok(schema) = self.committed_schema() {
+                *self.schema.write() = schema;
+            }
         }
+        self.reload_database_schemas();
     }
 
     /// Read schema version at current transaction
@@ -1941,27 +2253,22 @@ impl Connection {
                 ));
             };
 
-            let commit_err = if force_commit {
-                pager
-                    .io
-                    .block(|| {
-                        pager.commit_dirty_pages(
-                            true,
-                            self.get_sync_mode(),
-                            self.get_data_sync_retry(),
-                        )
-                    })
-                    .err()
+            if force_commit {
+                // Commit through the regular path, so that with group commit the session also
+                // waits for the shared sync covering its frames.
+                let commit_result = pager.io.block(|| pager.commit_tx_then(self, None, || {}));
+                self.auto_commit.store(true, Ordering::SeqCst);
+                self.set_tx_state(TransactionState::None);
+                if let Err(err) = commit_result {
+                    pager.rollback_tx_with(self, true, false);
+                    return Err(err);
+                }
             } else {
-                None
-            };
-
-            self.auto_commit.store(true, Ordering::SeqCst);
-            self.set_tx_state(TransactionState::None);
-            wal.end_write_tx();
-            wal.end_read_tx();
+                self.auto_commit.store(true, Ordering::SeqCst);
+                self.set_tx_state(TransactionState::None);
+                wal.end_write_tx();
+                wal.end_read_tx();
 
-            if !force_commit {
                 // remove all non-commited changes in case if WAL session left some suffix without commit frame
                 if let Some(mv_store) = self.mv_store().as_ref() {
                     if let Some(tx_id) = self.get_mv_tx_id() {
@@ -1970,9 +2277,6 @@ impl Connection {
                 }
                 pager.rollback(false, self, true);
             }
-            if let Some(err) = commit_err {
-                return Err(err);
-            }
         }
 
         // let's re-parse schema from scratch if schema cookie changed compared to the our in-memory view of schema
@@ -1989,6 +2293,10 @@ impl Connection {
         pager.io.block(|| pager.cacheflush())
     }
 
+    #[cfg_attr(
+        feature = "query_tracing",
+        instrument(skip_all, fields(mode = ?mode), level = Level::INFO)
+    )]
     pub fn checkpoint(self: &Arc<Self>, mode: CheckpointMode) -> Result<CheckpointResult> {
         use crate::mvcc::database::CheckpointStateMachine;
         use crate::state_machine::StateMachine;
@@ -2006,9 +2314,11 @@ impl Connection {
             ));
             io.as_ref().block(|| ckpt_sm.step(&()))
         } else {
-            self.pager
-                .load()
-                .blocking_checkpoint(mode, self.get_sync_mode())
+            self.pager.load().blocking_checkpoint(
+                mode,
+                self.get_sync_mode(),
+                self.get_data_sync_retry(),
+            )
         }
     }
 
@@ -2031,11 +2341,25 @@ impl Connection {
                 self.set_tx_state(TransactionState::None);
             }
         }
+        self.rollback_attached_transactions();
+        // Temp objects belong to this connection alone and go away with it. The files are
+        // removed explicitly as well, in case they live in memory.
+        let temp_database = self
+            .attached_databases
+            .write()
+            .index_to_data
+            .remove(&TEMP_DB_INDEX);
+        if let Some((temp_db, _)) = temp_database {
+            let _ = temp_db.io.remove_file(&temp_db.path);
+            let _ = temp_db.io.remove_file(&temp_db.wal_path);
+        }
+        self.database_schemas.write().remove(&TEMP_DB_INDEX);
 
         if self.db.n_connections.fetch_sub(1, Ordering::SeqCst).eq(&1) && !self.db.is_readonly() {
             self.pager.load().checkpoint_shutdown(
                 self.is_wal_auto_checkpoint_disabled(),
                 self.get_sync_mode(),
+                self.get_data_sync_retry(),
             )?;
         };
         Ok(())
@@ -2050,6 +2374,58 @@ impl Connection {
         self.wal_auto_checkpoint_disabled.load(Ordering::SeqCst) || self.db.get_mv_store().is_some()
     }
 
+    pub fn set_wal_autocheckpoint(&self, frames: u32) {
+        self.wal_autocheckpoint.store(frames, Ordering::SeqCst);
+    }
+
+    pub fn wal_autocheckpoint(&self) -> u32 {
+        self.wal_autocheckpoint.load(Ordering::SeqCst)
+    }
+
+    /// The WAL size, in frames past the last backfilled frame, at which a commit runs a passive
+    /// checkpoint, or None if commits never checkpoint.
+    pub(crate) fn wal_auto_checkpoint_threshold(&self) -> Option<u64> {
+        #[cfg(feature = "background_checkpoint")]
+        if self.db.background_checkpointer.get().is_some() {
+            return None;
+        }
+        self.wal_checkpoint_threshold()
+    }
+
+    fn wal_checkpoint_threshold(&self) -> Option<u64> {
+        match self.wal_autocheckpoint() {
+            _ if self.is_wal_auto_checkpoint_disabled() => None,
+            0 => None,
+            frames => Some(frames as u64),
+        }
+    }
+
+    /// Sets a callback that is invoked after every write transaction committed on this
+    /// connection, with the database name and the number of frames in its WAL. Passing None
+    /// removes the callback.
+    pub fn set_wal_hook(&self, hook: Option<WalHookCallback>) {
+        *self.wal_hook.write() = hook;
+    }
+
+    /// Runs after a write transaction was committed to the WAL of `pager`.
+    pub(crate) fn after_wal_commit(&self, pager: &Pager) {
+        let Some(wal) = pager.wal.as_ref() else {
+            return;
+        };
+        #[cfg(feature = "background_checkpoint")]
+        if let Some(checkpointer) = self.db.background_checkpointer.get() {
+            if self
+                .wal_checkpoint_threshold()
+                .is_some_and(|threshold| wal.should_checkpoint(threshold))
+            {
+                checkpointer.request();
+            }
+        }
+        if let Some(hook) = self.wal_hook.read().as_ref() {
+            hook("main", wal.get_max_frame_in_wal());
+        }
+    }
+
     pub fn last_insert_rowid(&self) -> i64 {
         self.last_insert_rowid.load(Ordering::SeqCst)
     }
@@ -2060,6 +2436,10 @@ impl Connection {
 
     pub fn set_changes(&self, nchange: i64) {
         self.last_change.store(nchange, Ordering::SeqCst);
+        self.add_total_changes(nchange);
+    }
+
+    fn add_total_changes(&self, nchange: i64) {
         self.total_changes.fetch_add(nchange, Ordering::SeqCst);
     }
 
@@ -2095,6 +2475,21 @@ impl Connection {
         self.closed.load(Ordering::SeqCst)
     }
 
+    /// Requests that the statements currently running on this connection stop at their next step
+    /// with an interrupt, similar to `sqlite3_interrupt`. Safe to call from another thread.
+    /// Statements which start executing after this call are not affected.
+    pub fn interrupt(&self) {
+        self.interrupted.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        self.interrupted.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn clear_interrupt(&self) {
+        self.interrupted.store(false, Ordering::SeqCst);
+    }
+
     pub fn is_query_only(&self) -> bool {
         self.query_only.load(Ordering::SeqCst)
     }
@@ -2220,6 +2615,80 @@ impl Connection {
         stmt.run_collect_rows()
     }
 
+    /// Insert `rows` into `table`, reusing a single prepared statement for all of them, and
+    /// return the number of rows inserted.
+    ///
+    /// Every row must have a value for each column of the table, in declaration order. If
+    /// the connection is in autocommit mode the rows are inserted in one transaction which is
+    /// rolled back on the first error, otherwise they become part of the open transaction.
+    pub fn bulk_insert<R>(
+        self: &Arc<Connection>,
+        table: &str,
+        rows: impl IntoIterator<Item = R>,
+    ) -> Result<u64>
+    where
+        R: IntoIterator<Item = Value>,
+    {
+        if self.is_closed() {
+            return Err(LimboError::InternalError("Connection closed".to_string()));
+        }
+        let owns_tx = self.get_auto_commit();
+        if owns_tx {
+            self.execute("BEGIN")?;
+        }
+        match self.bulk_insert_rows(table, rows) {
+            Ok(count) => {
+                if owns_tx {
+                    self.execute("COMMIT")?;
+                }
+                Ok(count)
+            }
+            Err(e) => {
+                if owns_tx && !self.get_auto_commit() {
+                    self.execute("ROLLBACK")?;
+                }
+                Err(e)
+            }
+        }
+    }
+
+    fn bulk_insert_rows<R>(
+        self: &Arc<Connection>,
+        table: &str,
+        rows: impl IntoIterator<Item = R>,
+    ) -> Result<u64>
+    where
+        R: IntoIterator<Item = Value>,
+    {
+        let mut stmt: Option<(Statement, usize)> = None;
+        let mut count = 0;
+        for row in rows {
+            let row = row.into_iter().collect::<Vec<_>>();
+            if stmt.is_none() {
+                let placeholders = vec!["?"; row.len()].join(", ");
+                let sql = format!(
+                    "INSERT INTO \"{}\" VALUES ({placeholders})",
+                    table.replace('"', "\"\"")
+                );
+                stmt = Some((self.prepare(sql)?, row.len()));
+            }
+            let (stmt, width) = stmt.as_mut().unwrap();
+            if row.len() != *width {
+                return Err(LimboError::InvalidArgument(format!(
+                    "row {count} has {} values, expected {width}",
+                    row.len()
+                )));
+            }
+            stmt.reset();
+            for (i, value) in row.into_iter().enumerate() {
+                stmt.bind_at(std::num::NonZero::new(i + 1).unwrap(), value);
+            }
+            stmt.run_ignore_rows()?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
     pub fn experimental_views_enabled(&self) -> bool {
         self.db.experimental_views_enabled()
     }
@@ -2304,7 +2773,7 @@ impl Connection {
     }
 
     fn get_pager_from_database_index(&self, index: &usize) -> Arc<Pager> {
-        if *index < 2 {
+        if *index == 0 {
             self.pager.load().clone()
         } else {
             self.attached_databases.read().get_pager_by_index(index)
@@ -2358,9 +2827,7 @@ impl Connection {
         } else {
             Arc::new(PlatformIO::new()?)
         };
-        // FIXME: for now, only support read only attach
-        let main_db_flags = self.db.open_flags | OpenFlags::ReadOnly;
-        let db = Self::from_uri_attached(path, db_opts, main_db_flags, io)?;
+        let db = Self::from_uri_attached(path, db_opts, self.db.open_flags, io)?;
         let pager = Arc::new(db.init_pager(None)?);
         self.attached_databases.write().insert(alias, (db, pager));
 
@@ -2381,11 +2848,20 @@ impl Connection {
 
         // Remove from attached databases
         let mut attached_dbs = self.attached_databases.write();
-        if attached_dbs.remove(alias).is_none() {
+        let Some(&index) = attached_dbs.name_to_index.get(alias) else {
             return Err(LimboError::InvalidArgument(format!(
                 "no such database: {alias}"
             )));
+        };
+        if self.attached_transactions.read().contains_key(&index) {
+            return Err(LimboError::InvalidArgument(format!(
+                "database {alias} is locked"
+            )));
         }
+        attached_dbs.remove(alias);
+        drop(attached_dbs);
+        // The index may be reused by the next attached database.
+        self.database_schemas.write().remove(&index);
 
         Ok(())
     }
@@ -2407,6 +2883,18 @@ impl Connection {
 
     /// Resolve database ID from a qualified name
     pub(crate) fn resolve_database_id(&self, qualified_name: &ast::QualifiedName) -> Result<usize> {
+        self.resolve_object_database_id(qualified_name, |schema, name| {
+            schema.get_table(name).is_some()
+        })
+    }
+
+    /// Resolve the database of a schema object that is not necessarily a table. An unqualified
+    /// name resolves to the temp database if `in_schema` finds the object in the temp schema.
+    pub(crate) fn resolve_object_database_id(
+        &self,
+        qualified_name: &ast::QualifiedName,
+        in_schema: impl FnOnce(&Schema, &str) -> bool,
+    ) -> Result<usize> {
         use crate::util::normalize_ident;
 
         // Check if this is a qualified name (database.table) or unqualified
@@ -2415,7 +2903,10 @@ impl Connection {
             let name_bytes = db_name_normalized.as_bytes();
             match_ignore_ascii_case!(match name_bytes {
                 b"main" => Ok(0),
-                b"temp" => Ok(1),
+                b"temp" => {
+                    self.ensure_temp_database()?;
+                    Ok(1)
+                }
                 _ => {
                     // Look up attached database
                     if let Some((idx, _attached_db)) =
@@ -2429,43 +2920,280 @@ impl Connection {
                     }
                 }
             })
+        } else if self
+            .temp_database_has_object(&normalize_ident(qualified_name.name.as_str()), in_schema)
+        {
+            // Unqualified names look in the temp schema first, so temp objects shadow main ones.
+            Ok(TEMP_DB_INDEX)
         } else {
-            // Unqualified table name - use main database
             Ok(0)
         }
     }
 
+    /// Resolve the database a new object named by a CREATE statement goes into. Unlike
+    /// [Self::resolve_database_id], an unqualified name creates the object in main, unless the
+    /// statement is `TEMP`, in which case the object can only live in the temp schema.
+    pub(crate) fn resolve_create_database_id(
+        &self,
+        qualified_name: &ast::QualifiedName,
+        temporary: bool,
+    ) -> Result<usize> {
+        if !temporary {
+            return match qualified_name.db_name {
+                Some(_) => self.resolve_database_id(qualified_name),
+                None => Ok(0),
+            };
+        }
+        if let Some(db_name) = &qualified_name.db_name {
+            if !crate::util::normalize_ident(db_name.as_str()).eq_ignore_ascii_case("temp") {
+                return Err(LimboError::ParseError(
+                    "temporary table name must be unqualified".to_string(),
+                ));
+            }
+        }
+        self.ensure_temp_database()?;
+        Ok(TEMP_DB_INDEX)
+    }
+
+    fn temp_database_has_object(
+        &self,
+        name: &str,
+        in_schema: impl FnOnce(&Schema, &str) -> bool,
+    ) -> bool {
+        // An unqualified sqlite_schema is always the one of the main database.
+        if name.eq_ignore_ascii_case(schema::SCHEMA_TABLE_NAME)
+            || name.eq_ignore_ascii_case(schema::SCHEMA_TABLE_NAME_ALT)
+        {
+            return false;
+        }
+        if !self
+            .attached_databases
+            .read()
+            .index_to_data
+            .contains_key(&TEMP_DB_INDEX)
+        {
+            return false;
+        }
+        in_schema(&self.database_schema(TEMP_DB_INDEX), name)
+    }
+
+    /// The name SQL refers to a database by, the inverse of [Self::resolve_database_id].
+    pub(crate) fn database_name(&self, database_id: usize) -> String {
+        match database_id {
+            0 => "main".to_string(),
+            TEMP_DB_INDEX => "temp".to_string(),
+            _ => self
+                .attached_databases
+                .read()
+                .name_to_index
+                .iter()
+                .find_map(|(name, &index)| (index == database_id).then(|| name.clone()))
+                .expect("Database ID should be valid"),
+        }
+    }
+
+    /// The temp schema is kept in a private database, so that objects qualified with `temp.`
+    /// never resolve to the main database. It is created the first time it is referenced, in the
+    /// directory the connection keeps its other temporary files in, with the IO of the main
+    /// database, so it is a file when the main database is one and in memory otherwise. It lives
+    /// as long as the connection.
+    fn ensure_temp_database(&self) -> Result<()> {
+        if self
+            .attached_databases
+            .read()
+            .index_to_data
+            .contains_key(&TEMP_DB_INDEX)
+        {
+            return Ok(());
+        }
+        // WASM can't create files that were not registered in advance, see `TempFile::new`.
+        #[cfg(target_family = "wasm")]
+        let (io, path): (Arc<dyn IO>, String) = (Arc::new(MemoryIO::new()), ":memory:".into());
+        #[cfg(not(target_family = "wasm"))]
+        let (io, path): (Arc<dyn IO>, String) = {
+            let io = self.pager.load().io.clone();
+            if io.is_memory() {
+                // Nothing is written to disk, so there is no need for a directory either.
+                (Arc::new(MemoryIO::new()), ":memory:".into())
+            } else {
+                // The directory is kept alive by the connection, which removes the files with it.
+                let (_, path) = self.temp_directory.new_file_path("tursodb_temp_database")?;
+                (io, path)
+            }
+        };
+        let db_opts = DatabaseOpts::new()
+            .with_views(self.db.experimental_views_enabled())
+            .with_strict(self.db.experimental_strict_enabled());
+        let file = io.open_file(&path, OpenFlags::Create, false)?;
+        // Temp databases are private to the connection, so they stay out of the registry.
+        let db = Database::open_with_flags_bypass_registry_internal(
+            io,
+            &path,
+            &format!("{path}-wal"),
+            Arc::new(DatabaseFile::new(file)),
+            OpenFlags::default(),
+            db_opts,
+            None,
+        )?;
+        let pager = Arc::new(db.init_pager(None)?);
+        self.attached_databases
+            .write()
+            .index_to_data
+            .insert(TEMP_DB_INDEX, (db, pager));
+        Ok(())
+    }
+
+    /// The directory the temporary files of this connection are created in.
+    pub(crate) fn temp_directory(&self) -> &Arc<TempDirectory> {
+        &self.temp_directory
+    }
+
     /// Access schema for a database using a closure pattern to avoid cloning
     pub(crate) fn with_schema<T>(&self, database_id: usize, f: impl FnOnce(&Schema) -> T) -> T {
         if database_id == 0 {
             // Main database - use connection's schema which should be kept in sync
             let schema = self.schema.read();
             f(&schema)
-        } else if database_id == 1 {
-            // Temp database - uses same schema as main for now, but this will change later.
-            let schema = self.schema.read();
-            f(&schema)
         } else {
-            // Attached database - check cache first, then load from database
-            let mut schemas = self.database_schemas.write();
+            // Attached or temp database. The cache lock is released before calling `f`, which
+            // may resolve names in the same database again.
+            let schema = self.database_schema(database_id);
+            f(&schema)
+        }
+    }
 
-            if let Some(cached_schema) = schemas.get(&database_id) {
-                return f(cached_schema);
-            }
+    /// The schema of an attached or temp database as seen by this connection. It is loaded
+    /// lazily from the database and cached, so that changes made by this connection's
+    /// transaction stay private to it until commit.
+    pub(crate) fn database_schema(&self, database_id: usize) -> Arc<Schema> {
+        let mut schemas = self.database_schemas.write();
+        if let Some(cached_schema) = schemas.get(&database_id) {
+            return cached_schema.clone();
+        }
 
-            // Schema not cached, load it lazily from the attached database
-            let attached_dbs = self.attached_databases.read();
-            let (db, _pager) = attached_dbs
-                .index_to_data
-                .get(&database_id)
-                .expect("Database ID should be valid after resolve_database_id");
+        // Schema not cached, load it lazily from the attached database
+        let attached_dbs = self.attached_databases.read();
+        let (db, _pager) = attached_dbs
+            .index_to_data
+            .get(&database_id)
+            .expect("Database ID should be valid after resolve_database_id");
+        let schema = db.clone_schema();
 
-            let schema = db.schema.lock().clone();
+        // Cache the schema for future use
+        schemas.insert(database_id, schema.clone());
+        schema
+    }
 
-            // Cache the schema for future use
-            schemas.insert(database_id, schema.clone());
+    pub(crate) fn with_database_schema_mut<T>(
+        &self,
+        database_id: usize,
+        f: impl FnOnce(&mut Schema) -> T,
+    ) -> T {
+        if database_id == 0 {
+            return self.with_schema_mut(f);
+        }
+        let mut schema = self.database_schema(database_id);
+        let result = f(Arc::make_mut(&mut schema));
+        self.database_schemas.write().insert(database_id, schema);
+        result
+    }
 
-            f(&schema)
+    /// Drops the cached schemas of attached and temp databases so that they are reloaded from
+    /// their databases, except for databases with an open transaction.
+    pub(crate) fn reload_database_schemas(&self) {
+        let transactions = self.attached_transactions.read();
+        self.database_schemas
+            .write()
+            .retain(|database_id, _| transactions.contains_key(database_id));
+    }
+
+    pub(crate) fn get_attached_tx_state(&self, database_id: usize) -> TransactionState {
+        self.attached_transactions
+            .read()
+            .get(&database_id)
+            .copied()
+            .unwrap_or(TransactionState::None)
+    }
+
+    pub(crate) fn has_attached_transactions(&self) -> bool {
+        !self.attached_transactions.read().is_empty()
+    }
+
+    pub(crate) fn set_attached_tx_state(&self, database_id: usize, state: TransactionState) {
+        let mut transactions = self.attached_transactions.write();
+        if state == TransactionState::None {
+            transactions.remove(&database_id);
+        } else {
+            transactions.insert(database_id, state);
+        }
+    }
+
+    /// Commits the transactions open on attached and temp databases and ends their read
+    /// transactions.
+    ///
+    /// Databases are committed one at a time, in order of their index, after the main database
+    /// has been committed. As in SQLite in WAL mode, the commit is atomic for each database but
+    /// not across databases: if a commit fails, the databases before it stay committed and the
+    /// remaining ones are rolled back.
+    pub(crate) fn commit_attached_transactions(&self) -> Result<IOResult<()>> {
+        if self.is_nested_stmt() {
+            // Parent statement will handle the transaction commit.
+            return Ok(IOResult::Done(()));
+        }
+        loop {
+            let next = self
+                .attached_transactions
+                .read()
+                .iter()
+                .min_by_key(|(database_id, _)| **database_id)
+                .map(|(database_id, state)| (*database_id, *state));
+            let Some((database_id, state)) = next else {
+                return Ok(IOResult::Done(()));
+            };
+            let pager = self.get_pager_from_database_index(&database_id);
+            if let TransactionState::Write { schema_did_change } = state {
+                let threshold = self.wal_auto_checkpoint_threshold();
+                return_if_io!(pager.commit_tx_then(self, threshold, || {
+                    if schema_did_change {
+                        self.publish_database_schema(database_id);
+                    }
+                }));
+            } else {
+                pager.end_read_tx();
+            }
+            self.set_attached_tx_state(database_id, TransactionState::None);
+        }
+    }
+
+    /// Rolls back the transactions open on attached and temp databases.
+    pub(crate) fn rollback_attached_transactions(&self) {
+        if self.is_nested_stmt() {
+            // Parent statement will handle the transaction rollback.
+            return;
+        }
+        let transactions = std::mem::take(&mut *self.attached_transactions.write());
+        for (database_id, state) in transactions {
+            let pager = self.get_pager_from_database_index(&database_id);
+            let is_write = matches!(state, TransactionState::Write { .. });
+            pager.rollback_tx_with(self, is_write, false);
+            if state.is_ddl_write_tx() {
+                self.database_schemas.write().remove(&database_id);
+            }
+        }
+    }
+
+    /// Makes the schema changes committed by this connection to an attached or temp database
+    /// visible to the other connections of that database.
+    fn publish_database_schema(&self, database_id: usize) {
+        let Some(schema) = self.database_schemas.read().get(&database_id).cloned() else {
+            return;
+        };
+        if let Some(db) = self
+            .attached_databases
+            .read()
+            .get_database_by_index(database_id)
+        {
+            db.update_schema_if_newer(schema);
         }
     }
 
@@ -2534,6 +3262,14 @@ impl Connection {
             .load(std::sync::atomic::Ordering::SeqCst)
     }
 
+    pub fn get_group_commit_window(&self) -> std::time::Duration {
+        self.db.group_commit.window()
+    }
+
+    pub fn set_group_commit_window(&self, window: std::time::Duration) {
+        self.db.group_commit.set_window(window);
+    }
+
     pub fn set_data_sync_retry(&self, value: bool) {
         self.data_sync_retry
             .store(value, std::sync::atomic::Ordering::SeqCst);
@@ -2556,8 +3292,32 @@ impl Connection {
         self.set_encryption_context()
     }
 
+    /// Sets the number of bytes reserved at the end of every page, for example by codecs that
+    /// store per-page metadata there.
+    ///
+    /// The reserved space can only be chosen for a database that has not been created yet: the
+    /// reserved space of an existing database is read from its header, and setting it to another
+    /// value fails.
     pub fn set_reserved_bytes(&self, reserved_bytes: u8) -> Result<()> {
         let pager = self.pager.load();
+        if self.db.initialized() {
+            let current = match pager.get_reserved_space() {
+                Some(current) => current,
+                None => self.db.read_reserved_space_bytes_from_db_header()?,
+            };
+            if current != reserved_bytes {
+                return Err(LimboError::InvalidArgument(format!(
+                    "cannot change the reserved space of an existing database from {current} to {reserved_bytes} bytes"
+                )));
+            }
+            return Ok(());
+        }
+        if reserved_bytes != CHECKSUM_REQUIRED_RESERVED_BYTES
+            && self.encryption_key.read().is_none()
+        {
+            // Checksums are stored in the reserved space and need exactly that many bytes.
+            pager.reset_checksum_context();
+        }
         pager.set_reserved_space_bytes(reserved_bytes);
         Ok(())
     }
@@ -2732,6 +3492,16 @@ impl SymbolTable {
     }
 }
 
+/// Wall-clock time elapsed since `start` according to the pager's I/O clock.
+fn elapsed_since(pager: &Pager, start: Instant) -> std::time::Duration {
+    pager
+        .io
+        .now()
+        .to_system_time()
+        .duration_since(start.to_system_time())
+        .unwrap_or_default()
+}
+
 pub struct QueryRunner<'a> {
     parser: Parser<'a>,
     conn: &'a Arc<Connection>,
@@ -2754,6 +3524,8 @@ impl Iterator for QueryRunner<'_> {
     type Item = Result<Option<Statement>>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        let pager = self.conn.pager.load().clone();
+        let prepare_start = pager.io.now();
         match self.parser.next_cmd() {
             Ok(Some(cmd)) => {
                 let byte_offset_end = self.parser.offset();
@@ -2761,10 +3533,17 @@ impl Iterator for QueryRunner<'_> {
                     .unwrap()
                     .trim();
                 self.last_offset = byte_offset_end;
-                Some(self.conn.run_cmd(cmd, input))
+                let mut result = self.conn.run_cmd(cmd, input);
+                if let Ok(Some(stmt)) = result.as_mut() {
+                    stmt.set_prepare_time(elapsed_since(&pager, prepare_start));
+                }
+                Some(result)
             }
             Ok(None) => None,
-            Err(err) => Some(Result::Err(LimboError::from(err))),
+            Err(err) => Some(Result::Err(LimboError::from_parse_error(
+                err,
+                &String::from_utf8_lossy(self.statements),
+            ))),
         }
     }
 }
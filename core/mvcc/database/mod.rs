@@ -2076,6 +2076,28 @@ impl<Clock: LogicalClock> MvStore<Clock> {
         Ok(tx_id)
     }
 
+    /// Begins a read transaction that sees the same row versions as the active transaction
+    /// `tx_id`, by starting it at the same timestamp with a copy of its database header.
+    pub fn begin_tx_at_snapshot_of(&self, tx_id: TxID) -> Result<TxID> {
+        let (begin_ts, header) = {
+            let entry = self
+                .txs
+                .get(&tx_id)
+                .ok_or_else(|| LimboError::NoSuchTransactionID(tx_id.to_string()))?;
+            let tx = entry.value();
+            let header = *tx.header.read();
+            (tx.begin_ts, header)
+        };
+        if !self.blocking_checkpoint_lock.read() {
+            return Err(LimboError::Busy);
+        }
+        let new_tx_id = self.get_tx_id();
+        let tx = Transaction::new(new_tx_id, begin_ts, header);
+        tracing::trace!("begin_tx_at_snapshot_of(tx_id={new_tx_id}, of={tx_id})");
+        self.txs.insert(new_tx_id, tx);
+        Ok(new_tx_id)
+    }
+
     pub fn remove_tx(&self, tx_id: TxID) {
         self.txs.remove(&tx_id);
         self.blocking_checkpoint_lock.unlock();
@@ -0,0 +1,415 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        mpsc::{sync_channel, Receiver},
+        Arc,
+    },
+    thread::JoinHandle,
+};
+
+use turso_parser::{
+    ast::{self, Cmd, Stmt},
+    parser::Parser,
+};
+
+use crate::{
+    schema::BTreeTable, translate::planner::ROWID_STRS, Connection, LimboError, Result, Value,
+};
+
+/// A full scan of a table that is split across several threads, for analytical queries over
+/// large tables on machines with several cores.
+///
+/// The rowids of the table are divided into ranges of equal width, and each range is read by
+/// a worker thread with its own fork of a single [crate::Snapshot], so all the workers read the
+/// database at the same commit. The rows are streamed in rowid order, like the rows of a
+/// sequential scan. The uncommitted changes of the connection that runs the scan are not seen.
+///
+/// The scan is only available through this API: the planner never picks it for a query and
+/// there is no PRAGMA to turn it on, so SQL statements always run sequentially.
+///
+/// ```ignore
+/// for row in ParallelScan::new("events")
+///     .with_columns(&["kind", "duration"])
+///     .with_filter("duration > 100")
+///     .with_workers(4)
+///     .run(&conn)?
+/// {
+///     let row = row?;
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParallelScan {
+    table: String,
+    columns: Vec<String>,
+    filter: Option<String>,
+    workers: usize,
+}
+
+impl ParallelScan {
+    /// Creates a scan of all the columns of `table`, using a worker per available core.
+    pub fn new(table: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            columns: Vec::new(),
+            filter: None,
+            workers: std::thread::available_parallelism().map_or(1, |n| n.get()),
+        }
+    }
+
+    /// Only returns the given columns, in this order.
+    pub fn with_columns(mut self, columns: &[&str]) -> Self {
+        self.columns = columns.iter().map(|c| c.to_string()).collect();
+        self
+    }
+
+    /// Only returns the rows for which the SQL expression `filter` is true. The expression can
+    /// reference the columns of the table, but not parameters. [Self::run] fails if `filter` is
+    /// not a single expression.
+    pub fn with_filter(mut self, filter: impl Into<String>) -> Self {
+        self.filter = Some(filter.into());
+        self
+    }
+
+    /// Splits the scan across at most `workers` threads.
+    pub fn with_workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
+
+    /// Starts the scan with workers connected to the database of `conn`, and returns an
+    /// iterator over the rows it finds.
+    pub fn run(&self, conn: &Arc<Connection>) -> Result<ParallelScanRows> {
+        let (bounds_sql, sql) = self.queries(conn)?;
+
+        let snapshot = conn.db.snapshot()?;
+        let mut stmt = snapshot.prepare(bounds_sql)?;
+        let bounds = stmt.run_collect_rows()?;
+        drop(stmt);
+        let (Value::Integer(min), Value::Integer(max)) = (&bounds[0][0], &bounds[0][1]) else {
+            // The table is empty.
+            return Ok(ParallelScanRows {
+                workers: VecDeque::new(),
+            });
+        };
+        let ranges = rowid_ranges(*min, *max, self.workers);
+        // The forks are opened before any worker starts, while `snapshot` holds the read point.
+        let forks = ranges
+            .iter()
+            .map(|_| snapshot.fork())
+            .collect::<Result<Vec<_>>>()?;
+
+        let workers = ranges
+            .into_iter()
+            .zip(forks)
+            .map(|((start, end), fork)| {
+                let (sender, receiver) = sync_channel(WORKER_CHANNEL_CAPACITY);
+                let sql = sql.clone();
+                let handle = std::thread::spawn(move || {
+                    let scan = fork.prepare(&sql).and_then(|mut stmt| {
+                        stmt.bind_at(1.try_into().unwrap(), Value::Integer(start));
+                        stmt.bind_at(2.try_into().unwrap(), Value::Integer(end));
+                        stmt.run_with_row_callback(|row| {
+                            sender
+                                .send(Ok(row.get_values().cloned().collect()))
+                                // The rows are no longer wanted.
+                                .map_err(|_| LimboError::Interrupt)
+                        })
+                    });
+                    let scan = scan.and(fork.close());
+                    if let Err(err) = scan {
+                        if !matches!(err, LimboError::Interrupt) {
+                            let _ = sender.send(Err(err));
+                        }
+                    }
+                });
+                (receiver, Some(handle))
+            })
+            .collect();
+        Ok(ParallelScanRows { workers })
+    }
+
+    /// The query for the smallest and largest rowid, and the query run by each worker, with the
+    /// bounds of its rowid range as parameters 1 and 2.
+    fn queries(&self, conn: &Arc<Connection>) -> Result<(String, String)> {
+        let schema = conn.schema.read().clone();
+        let Some(table) = schema.get_btree_table(&self.table) else {
+            return Err(LimboError::ParseError(format!(
+                "no such table: {}",
+                self.table
+            )));
+        };
+        if !table.has_rowid {
+            return Err(LimboError::ParseError(format!(
+                "cannot scan WITHOUT ROWID table {} in parallel",
+                table.name
+            )));
+        }
+        let columns = if self.columns.is_empty() {
+            "*".to_string()
+        } else {
+            let mut columns = Vec::with_capacity(self.columns.len());
+            for name in &self.columns {
+                let Some((_, column)) = table.get_column(name) else {
+                    return Err(LimboError::ParseError(format!("no such column: {name}")));
+                };
+                columns.push(quote_ident(column.name.as_deref().unwrap_or(name)));
+            }
+            columns.join(", ")
+        };
+        let rowid = rowid_column(&table)?;
+        let table_name = quote_ident(&table.name);
+        let bounds_sql = format!("SELECT min({rowid}), max({rowid}) FROM {table_name}");
+        let mut sql =
+            format!("SELECT {columns} FROM {table_name} WHERE {rowid} >= ?1 AND {rowid} <= ?2");
+        if let Some(filter) = &self.filter {
+            sql.push_str(&format!(" AND ({})", parse_filter(filter)?));
+        }
+        Ok((bounds_sql, sql))
+    }
+}
+
+/// The number of rows a worker of a [ParallelScan] reads ahead of the rows returned by
+/// [ParallelScanRows].
+const WORKER_CHANNEL_CAPACITY: usize = 256;
+
+/// The rows of a [ParallelScan], in rowid order.
+///
+/// Each worker sends the rows of its range through a bounded channel, so it only reads ahead
+/// of the iterator by [WORKER_CHANNEL_CAPACITY] rows, and the channels are drained one after
+/// the other in the order of the ranges. Dropping the iterator stops the workers.
+pub struct ParallelScanRows {
+    workers: VecDeque<(Receiver<Result<Vec<Value>>>, Option<JoinHandle<()>>)>,
+}
+
+impl Iterator for ParallelScanRows {
+    type Item = Result<Vec<Value>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((receiver, handle)) = self.workers.front_mut() {
+            if let Ok(row) = receiver.recv() {
+                return Some(row);
+            }
+            // The worker is done with its range.
+            let panicked = handle.take().is_some_and(|handle| handle.join().is_err());
+            self.workers.pop_front();
+            if panicked {
+                self.workers.clear();
+                return Some(Err(LimboError::InternalError(
+                    "parallel scan worker panicked".to_string(),
+                )));
+            }
+        }
+        None
+    }
+}
+
+impl Drop for ParallelScanRows {
+    fn drop(&mut self) {
+        // Dropping a receiver makes the next send of its worker fail, which ends the worker.
+        for (receiver, handle) in self.workers.drain(..) {
+            drop(receiver);
+            if let Some(handle) = handle {
+                let _ = handle.join();
+            }
+        }
+    }
+}
+
+/// The name that refers to the rowid of `table`: its INTEGER PRIMARY KEY column if it has one,
+/// otherwise the first of `rowid`, `_rowid_` and `oid` that is not the name of a column.
+fn rowid_column(table: &BTreeTable) -> Result<String> {
+    if let Some((_, column)) = table.get_rowid_alias_column() {
+        if let Some(name) = &column.name {
+            return Ok(quote_ident(name));
+        }
+    }
+    ROWID_STRS
+        .iter()
+        .find(|name| table.get_column(name).is_none())
+        .map(|name| name.to_string())
+        .ok_or_else(|| {
+            LimboError::ParseError(format!(
+                "cannot scan table {} in parallel: its columns shadow the rowid",
+                table.name
+            ))
+        })
+}
+
+/// Parses `filter` as a single expression, so that it can't escape the parentheses it is
+/// wrapped in by the worker query.
+fn parse_filter(filter: &str) -> Result<Box<ast::Expr>> {
+    let sql = format!("SELECT {filter}");
+    let mut parser = Parser::new(sql.as_bytes());
+    let cmd = parser
+        .next_cmd()
+        .map_err(|e| LimboError::from_parse_error(e, &sql))?;
+    let rest = parser
+        .next_cmd()
+        .map_err(|e| LimboError::from_parse_error(e, &sql))?;
+    if let (
+        Some(Cmd::Stmt(Stmt::Select(ast::Select {
+            with: None,
+            body:
+                ast::SelectBody {
+                    select:
+                        ast::OneSelect::Select {
+                            distinctness: None,
+                            mut columns,
+                            from: None,
+                            where_clause: None,
+                            group_by: None,
+                            window_clause,
+                        },
+                    compounds,
+                },
+            order_by,
+            limit: None,
+        }))),
+        None,
+    ) = (cmd, rest)
+    {
+        if columns.len() == 1
+            && compounds.is_empty()
+            && order_by.is_empty()
+            && window_clause.is_empty()
+        {
+            if let Some(ast::ResultColumn::Expr(expr, None)) = columns.pop() {
+                return Ok(expr);
+            }
+        }
+    }
+    Err(LimboError::ParseError(format!(
+        "parallel scan filter is not a single expression: {filter}"
+    )))
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Splits the rowids from `min` to `max` into at most `n` ranges of equal width.
+fn rowid_ranges(min: i64, max: i64, n: usize) -> Vec<(i64, i64)> {
+    let span = max as i128 - min as i128 + 1;
+    let n = (n as i128).min(span);
+    (0..n)
+        .map(|i| {
+            let start = min as i128 + span * i / n;
+            let end = min as i128 + span * (i + 1) / n - 1;
+            (start as i64, end as i64)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Database, MemoryIO};
+
+    #[test]
+    fn test_rowid_ranges() {
+        assert_eq!(rowid_ranges(1, 10, 3), vec![(1, 3), (4, 6), (7, 10)]);
+        assert_eq!(rowid_ranges(5, 6, 4), vec![(5, 5), (6, 6)]);
+        assert_eq!(rowid_ranges(7, 7, 1), vec![(7, 7)]);
+        assert_eq!(
+            rowid_ranges(i64::MIN, i64::MAX, 2),
+            vec![(i64::MIN, -1), (0, i64::MAX)]
+        );
+    }
+
+    #[test]
+    fn test_parallel_scan_shadowed_rowid() {
+        let db = Database::open_file(Arc::new(MemoryIO::new()), ":memory:").unwrap();
+        let conn = db.connect().unwrap();
+        // A `rowid` column that is not the rowid.
+        conn.execute("CREATE TABLE t (rowid TEXT, x)").unwrap();
+        conn.execute("INSERT INTO t VALUES ('b', 1), ('a', 2), ('c', 3)")
+            .unwrap();
+        for workers in [1, 2] {
+            let rows = ParallelScan::new("t")
+                .with_columns(&["rowid", "x"])
+                .with_workers(workers)
+                .run(&conn)
+                .unwrap()
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+            assert_eq!(
+                rows,
+                vec![
+                    vec![Value::build_text("b"), Value::Integer(1)],
+                    vec![Value::build_text("a"), Value::Integer(2)],
+                    vec![Value::build_text("c"), Value::Integer(3)],
+                ]
+            );
+        }
+
+        conn.execute("CREATE TABLE u (rowid, _rowid_, oid)")
+            .unwrap();
+        conn.execute("INSERT INTO u VALUES (1, 2, 3)").unwrap();
+        assert!(matches!(
+            ParallelScan::new("u").run(&conn),
+            Err(LimboError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_parallel_scan() {
+        let db = Database::open_file(Arc::new(MemoryIO::new()), ":memory:").unwrap();
+        let conn = db.connect().unwrap();
+        conn.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, x, \"y z\")")
+            .unwrap();
+        conn.execute("BEGIN").unwrap();
+        for i in 1..=1000 {
+            conn.execute(format!(
+                "INSERT INTO t VALUES ({}, {}, 'v{i}')",
+                i * 3,
+                i % 7
+            ))
+            .unwrap();
+        }
+        conn.execute("COMMIT").unwrap();
+
+        let expected = conn
+            .prepare("SELECT x, \"y z\" FROM t WHERE x > 2")
+            .unwrap()
+            .run_collect_rows()
+            .unwrap();
+        for workers in [1, 3, 8] {
+            let rows = ParallelScan::new("T")
+                .with_columns(&["x", "Y Z"])
+                .with_filter("x > 2")
+                .with_workers(workers)
+                .run(&conn)
+                .unwrap()
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+            assert_eq!(rows, expected);
+        }
+
+        let rows = ParallelScan::new("t")
+            .with_workers(2)
+            .run(&conn)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(rows.len(), 1000);
+        assert_eq!(rows[999][0], Value::Integer(3000));
+
+        // The workers stop when the rows are dropped before the end of the scan.
+        let mut rows = ParallelScan::new("t").with_workers(4).run(&conn).unwrap();
+        assert_eq!(rows.next().unwrap().unwrap()[0], Value::Integer(3));
+        drop(rows);
+
+        conn.execute("DELETE FROM t").unwrap();
+        assert!(ParallelScan::new("t").run(&conn).unwrap().next().is_none());
+
+        for scan in [
+            ParallelScan::new("missing"),
+            ParallelScan::new("t").with_columns(&["missing"]),
+            ParallelScan::new("t").with_filter("1) OR (1"),
+            ParallelScan::new("t").with_filter("x > 2 ORDER BY x"),
+            ParallelScan::new("t").with_filter("x > 2; DELETE FROM t"),
+        ] {
+            assert!(matches!(scan.run(&conn), Err(LimboError::ParseError(_))));
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::num::NonZero;
 
 #[derive(Clone, Debug)]
@@ -41,10 +42,10 @@ impl Parameters {
         }
     }
 
+    /// Returns the largest parameter index, which like `sqlite3_bind_parameter_count` also
+    /// counts gaps left by numbered parameters and parameters used more than once.
     pub fn count(&self) -> usize {
-        let mut params = self.list.clone();
-        params.dedup();
-        params.len()
+        self.next_index.get() - 1
     }
 
     pub fn name(&self, index: NonZero<usize>) -> Option<String> {
@@ -65,6 +66,16 @@ impl Parameters {
             .copied()
     }
 
+    /// Returns the named parameters with their index, in order of first appearance and
+    /// without duplicates.
+    pub fn names(&self) -> impl Iterator<Item = (&str, NonZero<usize>)> {
+        let mut seen = HashSet::new();
+        self.list.iter().filter_map(move |p| match p {
+            Parameter::Named(name, index) if seen.insert(*index) => Some((name.as_str(), *index)),
+            _ => None,
+        })
+    }
+
     pub fn next_index(&self) -> NonZero<usize> {
         self.next_index
     }
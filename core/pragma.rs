@@ -51,6 +51,14 @@ pub fn pragma_for(pragma: &PragmaName) -> Pragma {
             &["data_sync_retry"],
         ),
         DatabaseList => Pragma::new(PragmaFlags::Result0, &["seq", "name", "file"]),
+        VdbeProfile => Pragma::new(
+            PragmaFlags::Result0,
+            &["opcode", "executions", "elapsed_us"],
+        ),
+        GroupCommitWindow => Pragma::new(
+            PragmaFlags::NoColumns1 | PragmaFlags::Result0,
+            &["group_commit_window"],
+        ),
         Encoding => Pragma::new(
             PragmaFlags::Result0 | PragmaFlags::NoColumns1,
             &["encoding"],
@@ -109,6 +117,10 @@ pub fn pragma_for(pragma: &PragmaName) -> Pragma {
             PragmaFlags::NoColumns1 | PragmaFlags::Result0,
             &["user_version"],
         ),
+        WalAutocheckpoint => Pragma::new(
+            PragmaFlags::NoColumns1 | PragmaFlags::Result0,
+            &["wal_autocheckpoint"],
+        ),
         WalCheckpoint => Pragma::new(PragmaFlags::NeedSchema, &["busy", "log", "checkpointed"]),
         AutoVacuum => Pragma::new(
             PragmaFlags::NoColumns1 | PragmaFlags::Result0,
@@ -118,6 +130,14 @@ pub fn pragma_for(pragma: &PragmaName) -> Pragma {
             PragmaFlags::NoColumns1 | PragmaFlags::Result0,
             &["busy_timeout"],
         ),
+        IndexInfo => Pragma::new(
+            PragmaFlags::NeedSchema | PragmaFlags::Result1 | PragmaFlags::SchemaOpt,
+            &["seqno", "cid", "name"],
+        ),
+        IndexList => Pragma::new(
+            PragmaFlags::NeedSchema | PragmaFlags::Result1 | PragmaFlags::SchemaOpt,
+            &["seq", "name", "unique", "origin", "partial"],
+        ),
         IntegrityCheck => Pragma::new(
             PragmaFlags::NeedSchema | PragmaFlags::ReadOnly | PragmaFlags::Result0,
             &["message"],
@@ -151,6 +171,11 @@ pub fn pragma_for(pragma: &PragmaName) -> Pragma {
             PragmaFlags::NoColumns1 | PragmaFlags::Result0,
             &["cache_spill"],
         ),
+        PragmaName::CaseSensitiveLike => Pragma::new(PragmaFlags::NoColumns, &[]),
+        PragmaName::AutomaticIndex => Pragma::new(
+            PragmaFlags::NoColumns1 | PragmaFlags::Result0,
+            &["automatic_index"],
+        ),
     }
 }
 
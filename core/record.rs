@@ -0,0 +1,197 @@
+//! Encoding and decoding of values in the SQLite record format.
+//!
+//! A record starts with a header made of a varint holding the header size followed by one
+//! serial type per column, and continues with the content of every column. This is the
+//! payload format of table and index b-tree cells, so tooling and virtual tables can use
+//! these functions to produce and consume payloads compatible with database files.
+//!
+//! See <https://www.sqlite.org/fileformat2.html#record_format>.
+
+use crate::storage::sqlite3_ondisk::{read_value, read_varint};
+use crate::types::{ImmutableRecord, SerialType, SerialTypeKind};
+use crate::{bail_corrupt_error, Result, Value};
+
+/// Encodes `values` into a record payload.
+pub fn encode(values: &[Value]) -> Vec<u8> {
+    ImmutableRecord::from_values(values, values.len()).into_payload()
+}
+
+/// Decodes a record payload into its values.
+///
+/// Unlike the lazy [crate::types::RecordCursor] used while executing statements, the whole
+/// payload is validated up front, so malformed input results in a [crate::LimboError::Corrupt]
+/// error instead of a panic.
+pub fn decode(payload: &[u8]) -> Result<Vec<Value>> {
+    let (header_size, mut header_offset) = read_varint(payload)?;
+    let header_size = match usize::try_from(header_size) {
+        Ok(size) if size >= header_offset && size <= payload.len() => size,
+        _ => bail_corrupt_error!(
+            "invalid record header size {header_size} for a payload of {} bytes",
+            payload.len()
+        ),
+    };
+
+    let mut serial_types = Vec::new();
+    while header_offset < header_size {
+        // Bounding the slice by the header size rejects serial types running past the header.
+        let (serial_type, n) = read_varint(&payload[header_offset..header_size])?;
+        header_offset += n;
+        serial_types.push(SerialType::try_from(serial_type)?);
+    }
+
+    let mut values = Vec::with_capacity(serial_types.len());
+    let mut offset = header_size;
+    for (idx, serial_type) in serial_types.into_iter().enumerate() {
+        let size = serial_type.size();
+        if size > payload.len() - offset {
+            bail_corrupt_error!(
+                "column {idx} of {size} bytes at offset {offset} overflows the {} byte payload",
+                payload.len()
+            );
+        }
+        let content = &payload[offset..offset + size];
+        // Text is read without validation while executing statements, as those payloads are
+        // always written by the database itself.
+        if matches!(serial_type.kind(), SerialTypeKind::Text)
+            && std::str::from_utf8(content).is_err()
+        {
+            bail_corrupt_error!("column {idx} is not valid UTF-8 text");
+        }
+        let (value, _) = read_value(content, serial_type)?;
+        values.push(value.to_owned());
+        offset += size;
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LimboError;
+
+    fn assert_round_trip(values: Vec<Value>) {
+        let payload = encode(&values);
+        let decoded = decode(&payload).unwrap();
+        assert_eq!(decoded, values, "payload: {payload:?}");
+        // The record must also be readable through the regular lazy cursor.
+        let record = ImmutableRecord::from_bin_record(payload);
+        let lazy = record
+            .get_values()
+            .iter()
+            .map(|value| value.to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(lazy, values);
+    }
+
+    #[test]
+    fn test_round_trip_integer_boundaries() {
+        let mut boundaries = vec![0i64, 1, i64::MIN, i64::MAX];
+        for bits in [8u32, 16, 24, 32, 48] {
+            let max = (1i64 << (bits - 1)) - 1;
+            let min = -(1i64 << (bits - 1));
+            boundaries.extend([max, max + 1, min, min - 1]);
+        }
+        for value in &boundaries {
+            assert_round_trip(vec![Value::Integer(*value)]);
+        }
+        assert_round_trip(boundaries.into_iter().map(Value::Integer).collect());
+    }
+
+    #[test]
+    fn test_round_trip_floats() {
+        let floats = [
+            0.0,
+            -0.0,
+            1.5,
+            -2.25,
+            f64::MIN_POSITIVE,
+            f64::MAX,
+            f64::MIN,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            5e-324,
+        ];
+        for value in floats {
+            assert_round_trip(vec![Value::Float(value)]);
+        }
+
+        let decoded = decode(&encode(&[Value::Float(f64::NAN)])).unwrap();
+        assert!(matches!(decoded[..], [Value::Float(f)] if f.is_nan()));
+    }
+
+    #[test]
+    fn test_round_trip_text_and_blob() {
+        for len in [0usize, 1, 56, 57, 58, 127, 128, 8192, 100_000] {
+            assert_round_trip(vec![Value::build_text("x".repeat(len))]);
+            assert_round_trip(vec![Value::Blob(vec![0xab; len])]);
+        }
+        assert_round_trip(vec![Value::build_text("héllo wörld ✓ 🦀")]);
+        assert_round_trip(vec![Value::Blob((0..=255).collect())]);
+    }
+
+    #[test]
+    fn test_round_trip_mixed_columns() {
+        assert_round_trip(vec![]);
+        assert_round_trip(vec![Value::Null]);
+        assert_round_trip(vec![
+            Value::Null,
+            Value::Integer(42),
+            Value::Float(3.5),
+            Value::build_text("text"),
+            Value::Blob(vec![1, 2, 3]),
+            Value::Null,
+        ]);
+        // Enough columns for the header size itself to need a two byte varint.
+        let wide = (0..500)
+            .map(|i| match i % 5 {
+                0 => Value::Null,
+                1 => Value::Integer(i),
+                2 => Value::Float(i as f64 / 3.0),
+                3 => Value::build_text(format!("value {i}")),
+                _ => Value::Blob(vec![i as u8; i as usize % 7]),
+            })
+            .collect();
+        assert_round_trip(wide);
+    }
+
+    #[test]
+    fn test_encode_matches_sqlite_format() {
+        // header size 2, serial type 9 (constant 1)
+        assert_eq!(encode(&[Value::Integer(1)]), vec![0x02, 0x09]);
+        // header size 3, serial types NULL and 1 byte integer
+        assert_eq!(
+            encode(&[Value::Null, Value::Integer(-1)]),
+            vec![0x03, 0x00, 0x01, 0xff]
+        );
+        // header size 2, serial type 13 + 2 * 2 for a 2 byte text
+        assert_eq!(
+            encode(&[Value::build_text("hi")]),
+            vec![0x02, 0x11, b'h', b'i']
+        );
+        // header size 2, serial type 12 + 2 * 1 for a 1 byte blob
+        assert_eq!(encode(&[Value::Blob(vec![7])]), vec![0x02, 0x0e, 0x07]);
+        assert_eq!(encode(&[]), vec![0x01]);
+    }
+
+    #[test]
+    fn test_decode_malformed_payloads() {
+        let is_corrupt = |payload: &[u8]| matches!(decode(payload), Err(LimboError::Corrupt(_)));
+        // empty payload
+        assert!(is_corrupt(&[]));
+        // header size larger than the payload
+        assert!(is_corrupt(&[0x05, 0x01]));
+        // 4 byte integer with only 2 bytes of content
+        assert!(is_corrupt(&[0x02, 0x04, 0x00, 0x01]));
+        // text column longer than the payload
+        assert!(is_corrupt(&[0x02, 0x21, b'a']));
+        // serial types 10 and 11 are reserved
+        assert!(decode(&[0x02, 0x0a]).is_err());
+        assert!(decode(&[0x02, 0x0b]).is_err());
+        // serial type varint running past the header
+        assert!(is_corrupt(&[0x02, 0x81, 0x01]));
+        // text that is not valid UTF-8
+        assert!(is_corrupt(&[0x02, 0x0f, 0xff]));
+        // truncated header size varint
+        assert!(decode(&[0x80]).is_err());
+    }
+}
@@ -123,6 +123,7 @@ use crate::translate::collate::CollationSeq;
 use crate::translate::plan::{SelectPlan, TableReferences};
 use crate::util::{
     module_args_from_sql, module_name_from_sql, type_from_name, IOExt, UnparsedFromSqlIndex,
+    PRIMARY_KEY_AUTOMATIC_INDEX_NAME_PREFIX,
 };
 use crate::Result;
 use crate::{
@@ -131,6 +132,7 @@ use crate::{
 };
 use core::fmt;
 use parking_lot::Mutex;
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::ops::Deref;
 use std::sync::Arc;
@@ -143,11 +145,19 @@ use turso_parser::{
     parser::Parser,
 };
 
-const SCHEMA_TABLE_NAME: &str = "sqlite_schema";
-const SCHEMA_TABLE_NAME_ALT: &str = "sqlite_master";
+pub(crate) const SCHEMA_TABLE_NAME: &str = "sqlite_schema";
+pub(crate) const SCHEMA_TABLE_NAME_ALT: &str = "sqlite_master";
 pub const SQLITE_SEQUENCE_TABLE_NAME: &str = "sqlite_sequence";
 pub const DBSP_TABLE_PREFIX: &str = "__turso_internal_dbsp_state_v";
 pub const TURSO_INTERNAL_PREFIX: &str = "__turso_internal_";
+/// Prefix of the internal tables that hold the schema table of each namespace.
+pub const NAMESPACE_TABLE_PREFIX: &str = "__turso_internal_namespace_";
+
+/// Name of the internal table, stored in the schema of the default namespace, whose b-tree is the
+/// schema table of the given namespace.
+pub fn namespace_table_name(namespace: &str) -> String {
+    format!("{NAMESPACE_TABLE_PREFIX}{}", normalize_ident(namespace))
+}
 
 /// Used to refer to the implicit rowid column in tables without an alias during UPDATE
 pub const ROWID_SENTINEL: usize = usize::MAX;
@@ -208,6 +218,9 @@ pub struct Schema {
 
     /// Track views that exist but have incompatible versions
     pub incompatible_views: HashSet<String>,
+
+    /// The namespace this schema belongs to, `None` for the default namespace.
+    pub namespace: Option<String>,
 }
 
 impl Default for Schema {
@@ -252,9 +265,30 @@ impl Schema {
             analyze_stats: AnalyzeStats::default(),
             table_to_materialized_views,
             incompatible_views,
+            namespace: None,
         }
     }
 
+    /// Creates the empty schema of a namespace whose schema table is stored at `root_page`.
+    pub fn new_namespace(namespace: &str, root_page: i64) -> Self {
+        let mut schema = Self::new();
+        let mut schema_table = sqlite_schema_table();
+        schema_table.root_page = root_page;
+        #[allow(clippy::arc_with_non_send_sync)]
+        schema.tables.insert(
+            SCHEMA_TABLE_NAME.to_string(),
+            Arc::new(Table::BTree(schema_table.into())),
+        );
+        schema.namespace = Some(normalize_ident(namespace));
+        schema
+    }
+
+    /// Root page of the schema table, which is not page 1 inside a namespace.
+    pub fn schema_table_root_page(&self) -> i64 {
+        self.get_btree_table(SCHEMA_TABLE_NAME)
+            .map_or(1, |table| table.root_page)
+    }
+
     pub fn is_unique_idx_name(&self, name: &str) -> bool {
         !self
             .indexes
@@ -696,6 +730,20 @@ impl Schema {
             // Hence, we can process the singles first (unique_set.columns.len() == 1), and then the compounds (unique_set.columns.len() > 1).
             let table = self.get_btree_table(&automatic_index.0).unwrap();
             let mut automatic_indexes = automatic_index.1;
+            // The primary key index of a WITHOUT ROWID table has no sqlite_schema entry; its
+            // root page is the root page of the table.
+            let without_rowid_pk_index = (!table.has_rowid).then(|| {
+                let name = (1..)
+                    .map(|n| {
+                        format!(
+                            "{PRIMARY_KEY_AUTOMATIC_INDEX_NAME_PREFIX}{}_{n}",
+                            table.name
+                        )
+                    })
+                    .find(|name| !automatic_indexes.iter().any(|(other, _)| other == name))
+                    .unwrap();
+                (name, table.root_page)
+            });
             automatic_indexes.reverse(); // reverse so we can pop() without shifting array elements, while still processing in left-to-right order
 
             // we must process unique_sets in this exact order in order to emit automatic indices schema entries in the same order
@@ -725,9 +773,13 @@ impl Schema {
                         }
                     }
 
+                    let auto_index = match &without_rowid_pk_index {
+                        Some(pk_index) => pk_index.clone(),
+                        None => automatic_indexes.pop().unwrap(),
+                    };
                     self.add_index(Arc::new(Index::automatic_from_primary_key(
                         table.as_ref(),
-                        automatic_indexes.pop().unwrap(),
+                        auto_index,
                         unique_set.columns.len(),
                     )?))?;
                 } else {
@@ -859,6 +911,11 @@ impl Schema {
                     self.add_virtual_table(vtab)?;
                 } else {
                     let table = BTreeTable::from_sql(sql, root_page)?;
+                    if !table.has_rowid {
+                        // Make sure the primary key index is created even if the table has no
+                        // automatic index entries in sqlite_schema.
+                        automatic_indices.entry(table.name.clone()).or_default();
+                    }
 
                     // Check if this is a DBSP state table
                     if table.name.starts_with(DBSP_TABLE_PREFIX) {
@@ -1051,9 +1108,10 @@ impl Schema {
     /// strategy (rowid vs. UNIQUE index or PK).
     pub fn resolved_fks_referencing(&self, table_name: &str) -> Result<Vec<ResolvedFkRef>> {
         let fk_mismatch_err = |child: &str, parent: &str| -> crate::LimboError {
-            crate::LimboError::Constraint(format!(
-                "foreign key mismatch - \"{child}\" referencing \"{parent}\""
-            ))
+            crate::LimboError::Constraint {
+                code: crate::error::SQLITE_ERROR,
+                message: format!("foreign key mismatch - \"{child}\" referencing \"{parent}\""),
+            }
         };
         let target = normalize_ident(table_name);
         let mut out = Vec::with_capacity(4); // arbitrary estimate
@@ -1169,9 +1227,10 @@ impl Schema {
     /// Compute all resolved FKs *declared by* `child_table`
     pub fn resolved_fks_for_child(&self, child_table: &str) -> crate::Result<Vec<ResolvedFkRef>> {
         let fk_mismatch_err = |child: &str, parent: &str| -> crate::LimboError {
-            crate::LimboError::Constraint(format!(
-                "foreign key mismatch - \"{child}\" referencing \"{parent}\""
-            ))
+            crate::LimboError::Constraint {
+                code: crate::error::SQLITE_ERROR,
+                message: format!("foreign key mismatch - \"{child}\" referencing \"{parent}\""),
+            }
         };
         let child_name = normalize_ident(child_table);
         let child = self
@@ -1414,6 +1473,7 @@ impl Clone for Schema {
             analyze_stats: self.analyze_stats.clone(),
             table_to_materialized_views: self.table_to_materialized_views.clone(),
             incompatible_views,
+            namespace: self.namespace.clone(),
         }
     }
 }
@@ -1716,6 +1776,14 @@ pub struct FromClauseSubquery {
     /// The start register for the result columns of the derived table;
     /// must be set before data is read from it.
     pub result_columns_start_reg: Option<usize>,
+    /// For a CTE, the internal id of the CTE itself, shared by all its references.
+    pub cte_id: Option<ast::TableInternalId>,
+    /// Whether the rows are computed once into an ephemeral table ([ast::Materialized::Yes]),
+    /// or the subquery is run as a coroutine every time the table is scanned.
+    pub materialized: ast::Materialized,
+    /// The cursor of the ephemeral table holding the rows of a materialized subquery;
+    /// set when the subquery is emitted.
+    pub materialized_cursor_id: Option<crate::vdbe::CursorID>,
 }
 
 pub fn create_table(tbl_name: &str, body: &CreateTableBody, root_page: i64) -> Result<BTreeTable> {
@@ -1899,6 +1967,10 @@ pub fn create_table(tbl_name: &str, body: &CreateTableBody, root_page: i64) -> R
                     .cloned()
                     .map(|ast::Type { name, .. }| name.clone())
                     .unwrap_or_default();
+                let ty_size = col_type
+                    .as_ref()
+                    .and_then(|t| t.size.as_ref())
+                    .map(|size| size.to_string());
 
                 let mut typename_exactly_integer = false;
                 let ty = match col_type {
@@ -2050,30 +2122,35 @@ pub fn create_table(tbl_name: &str, body: &CreateTableBody, root_page: i64) -> R
                     primary_key = true;
                 }
 
-                cols.push(Column::new(
-                    Some(normalize_ident(&name)),
-                    ty_str,
-                    default,
-                    None,
-                    ty,
-                    collation,
-                    ColDef {
-                        primary_key,
-                        rowid_alias: typename_exactly_integer
-                            && primary_key
-                            && !primary_key_desc_columns_constraint,
-                        notnull,
-                        unique,
-                        hidden: false,
-                    },
-                ));
+                cols.push(
+                    Column::new(
+                        Some(normalize_ident(&name)),
+                        ty_str,
+                        default,
+                        None,
+                        ty,
+                        collation,
+                        ColDef {
+                            primary_key,
+                            rowid_alias: typename_exactly_integer
+                                && primary_key
+                                && !primary_key_desc_columns_constraint,
+                            notnull,
+                            unique,
+                            hidden: false,
+                        },
+                    )
+                    .with_ty_size(ty_size),
+                );
             }
 
             if options.contains(TableOptions::WITHOUT_ROWID) {
                 has_rowid = false;
             }
         }
-        CreateTableBody::AsSelect(_) => todo!(),
+        CreateTableBody::AsSelect(_) => {
+            crate::bail_parse_error!("CREATE TABLE ... AS SELECT is not supported yet")
+        }
     };
 
     // flip is_rowid_alias back to false if the table has multiple primary key columns
@@ -2084,6 +2161,19 @@ pub fn create_table(tbl_name: &str, body: &CreateTableBody, root_page: i64) -> R
         }
     }
 
+    if !has_rowid {
+        if primary_key_columns.is_empty() {
+            crate::bail_parse_error!("PRIMARY KEY missing on table {}", table_name);
+        }
+        if has_autoincrement {
+            crate::bail_parse_error!("AUTOINCREMENT not allowed on WITHOUT ROWID tables");
+        }
+        // The primary key of a WITHOUT ROWID table is its b-tree key, so it can't be NULL.
+        for col in cols.iter_mut().filter(|col| col.primary_key()) {
+            col.set_notnull(true);
+        }
+    }
+
     if has_autoincrement {
         // only allow integers
         if primary_key_columns.len() != 1 {
@@ -2205,10 +2295,10 @@ impl ForeignKey {
             .iter()
             .any(|c| ROWID_STRS.iter().any(|&r| r.eq_ignore_ascii_case(c)))
         {
-            return Err(crate::LimboError::Constraint(format!(
-                "foreign key mismatch referencing \"{}\"",
-                self.parent_table
-            )));
+            return Err(crate::LimboError::Constraint {
+                code: crate::error::SQLITE_ERROR,
+                message: format!("foreign key mismatch referencing \"{}\"", self.parent_table),
+            });
         }
         Ok(())
     }
@@ -2288,6 +2378,8 @@ impl ResolvedFkRef {
 pub struct Column {
     pub name: Option<String>,
     pub ty_str: String,
+    /// The size arguments of the declared type, e.g. `32` for `VARCHAR(32)`
+    pub ty_size: Option<String>,
     pub default: Option<Box<Expr>>,
     pub generated: Option<Box<Expr>>,
     raw: u16,
@@ -2313,7 +2405,7 @@ const F_HIDDEN: u16 = 16;
 const TYPE_SHIFT: u16 = 5;
 const TYPE_MASK: u16 = 0b111 << TYPE_SHIFT;
 const COLL_SHIFT: u16 = TYPE_SHIFT + 3;
-const COLL_MASK: u16 = 0b11 << COLL_SHIFT;
+const COLL_MASK: u16 = 0xff << COLL_SHIFT;
 
 impl Column {
     pub fn affinity(&self) -> Affinity {
@@ -2362,7 +2454,7 @@ impl Column {
         let mut raw = 0u16;
         raw |= (ty as u16) << TYPE_SHIFT;
         if let Some(c) = col {
-            raw |= (c as u16) << COLL_SHIFT;
+            raw |= (c.to_bits() as u16) << COLL_SHIFT;
         }
         if coldef.primary_key {
             raw |= F_PRIMARY_KEY
@@ -2382,11 +2474,26 @@ impl Column {
         Self {
             name,
             ty_str,
+            ty_size: None,
             default,
             generated,
             raw,
         }
     }
+
+    pub fn with_ty_size(mut self, ty_size: Option<String>) -> Self {
+        self.ty_size = ty_size;
+        self
+    }
+
+    /// Returns the type of the column as declared, including any size arguments, e.g.
+    /// `VARCHAR(32)`. [Self::ty_str] holds the bare type name.
+    pub fn decl_type(&self) -> Cow<'_, str> {
+        match &self.ty_size {
+            Some(size) => Cow::Owned(format!("{}({size})", self.ty_str)),
+            None => Cow::Borrowed(&self.ty_str),
+        }
+    }
     #[inline]
     pub const fn ty(&self) -> Type {
         let v = ((self.raw & TYPE_MASK) >> TYPE_SHIFT) as u8;
@@ -2416,13 +2523,13 @@ impl Column {
     #[inline]
     pub const fn has_explicit_collation(&self) -> bool {
         let v = ((self.raw & COLL_MASK) >> COLL_SHIFT) as u8;
-        v != CollationSeq::Unset as u8
+        v != CollationSeq::Unset.to_bits()
     }
 
     #[inline]
     pub const fn set_collation(&mut self, c: Option<CollationSeq>) {
         if let Some(c) = c {
-            self.raw = (self.raw & !COLL_MASK) | (((c as u16) << COLL_SHIFT) & COLL_MASK);
+            self.raw = (self.raw & !COLL_MASK) | (((c.to_bits() as u16) << COLL_SHIFT) & COLL_MASK);
         }
     }
 
@@ -2523,6 +2630,11 @@ impl From<&ColumnDefinition> for Column {
             .as_ref()
             .map(|t| t.name.to_string())
             .unwrap_or_default();
+        let ty_size = value
+            .col_type
+            .as_ref()
+            .and_then(|t| t.size.as_ref())
+            .map(|size| size.to_string());
 
         let hidden = ty_str.contains("HIDDEN");
 
@@ -2541,6 +2653,7 @@ impl From<&ColumnDefinition> for Column {
                 hidden,
             },
         )
+        .with_ty_size(ty_size)
     }
 }
 
@@ -2616,8 +2729,8 @@ pub struct Index {
     /// Does the index have a rowid as the last column?
     /// This is the case for btree indexes (persistent or ephemeral) that
     /// have been created based on a table with a rowid.
-    /// For example, WITHOUT ROWID tables (not supported in Limbo yet),
-    /// and  SELECT DISTINCT ephemeral indexes will not have a rowid.
+    /// For example, indexes on WITHOUT ROWID tables, which end with the primary key instead,
+    /// and SELECT DISTINCT ephemeral indexes will not have a rowid.
     pub has_rowid: bool,
     pub where_clause: Option<Box<Expr>>,
     pub index_method: Option<Arc<dyn IndexMethodAttachment>>,
@@ -2640,6 +2753,19 @@ pub struct IndexColumn {
     pub expr: Option<Box<Expr>>,
 }
 
+impl IndexColumn {
+    fn from_table_column(pos_in_table: usize, column: &Column) -> Self {
+        Self {
+            name: normalize_ident(column.name.as_deref().unwrap_or_default()),
+            order: SortOrder::Asc,
+            pos_in_table,
+            collation: column.collation_opt(),
+            default: column.default.clone(),
+            expr: None,
+        }
+    }
+}
+
 impl Index {
     pub fn from_sql(
         syms: &SymbolTable,
@@ -2661,7 +2787,7 @@ impl Index {
                 ..
             })) => {
                 let index_name = normalize_ident(idx_name.name.as_str());
-                let index_columns = resolve_sorted_columns(table, &columns)?;
+                let mut index_columns = resolve_sorted_columns(table, &columns)?;
                 if let Some(using) = using {
                     if where_clause.is_some() {
                         bail_parse_error!("custom index module do not support partial indices");
@@ -2692,6 +2818,7 @@ impl Index {
                         index_method: Some(descriptor),
                     })
                 } else {
+                    Self::append_primary_key_columns(table, &mut index_columns)?;
                     Ok(Index {
                         name: index_name,
                         table_name: normalize_ident(tbl_name.as_str()),
@@ -2721,6 +2848,14 @@ impl Index {
             .is_some_and(|x| x.definition().backing_btree)
     }
 
+    /// Check if this index holds exactly one entry, carrying the rowid, for every row of its table.
+    pub fn has_entry_for_every_row(&self) -> bool {
+        self.has_rowid
+            && !self.ephemeral
+            && self.where_clause.is_none()
+            && self.index_method.is_none()
+    }
+
     pub fn automatic_from_primary_key(
         table: &BTreeTable,
         auto_index: (String, i64), // name, root_page
@@ -2752,6 +2887,16 @@ impl Index {
 
         assert!(primary_keys.len() == column_count);
 
+        // The primary key index of a WITHOUT ROWID table is the table itself, so its entries
+        // hold the remaining columns after the key columns.
+        if !table.has_rowid {
+            for (pos_in_table, column) in table.columns.iter().enumerate() {
+                if !primary_keys.iter().any(|c| c.pos_in_table == pos_in_table) {
+                    primary_keys.push(IndexColumn::from_table_column(pos_in_table, column));
+                }
+            }
+        }
+
         Ok(Index {
             name: normalize_ident(index_name.as_str()),
             table_name: table.name.clone(),
@@ -2794,6 +2939,7 @@ impl Index {
                 expr: None,
             });
         }
+        Self::append_primary_key_columns(table, &mut unique_cols)?;
 
         Ok(Index {
             name: normalize_ident(index_name.as_str()),
@@ -2808,6 +2954,84 @@ impl Index {
         })
     }
 
+    /// Entries of indexes on a WITHOUT ROWID table locate their row by primary key instead of
+    /// rowid, so the primary key columns that the index does not already contain are appended.
+    pub fn append_primary_key_columns(
+        table: &BTreeTable,
+        columns: &mut Vec<IndexColumn>,
+    ) -> Result<()> {
+        if table.has_rowid {
+            return Ok(());
+        }
+        for (col_name, _) in table.primary_key_columns.iter() {
+            let Some((pos_in_table, column)) = table.get_column(col_name) else {
+                return Err(crate::LimboError::ParseError(format!(
+                    "Column {} not found in table {}",
+                    col_name, table.name
+                )));
+            };
+            if !columns
+                .iter()
+                .any(|c| c.expr.is_none() && c.pos_in_table == pos_in_table)
+            {
+                columns.push(IndexColumn::from_table_column(pos_in_table, column));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check if this is the index that stores the rows of a WITHOUT ROWID table.
+    pub fn is_without_rowid_primary_key(&self, table: &BTreeTable) -> bool {
+        !table.has_rowid && self.root_page == table.root_page
+    }
+
+    /// The columns that UNIQUE and PRIMARY KEY constraints are enforced on. The primary key
+    /// index of a WITHOUT ROWID table stores the rest of the row after its key columns, and its
+    /// UNIQUE indexes end with the primary key columns their constraint does not contain, so
+    /// their key is found by matching the index against the UNIQUE constraints of the table.
+    pub fn key_columns(&self, table: &BTreeTable) -> &[IndexColumn] {
+        if self.is_without_rowid_primary_key(table) {
+            return &self.columns[..table.primary_key_columns.len()];
+        }
+        if table.has_rowid || !self.unique {
+            return &self.columns;
+        }
+        let column_pos = |name: &str| table.get_column(name).map(|(pos, _)| pos);
+        let key_len = table
+            .unique_sets
+            .iter()
+            .filter(|unique_set| !unique_set.is_primary_key)
+            .filter(|unique_set| {
+                if unique_set.columns.len() > self.columns.len() {
+                    return false;
+                }
+                let (declared, appended) = self.columns.split_at(unique_set.columns.len());
+                let same_columns =
+                    declared
+                        .iter()
+                        .zip(&unique_set.columns)
+                        .all(|(col, (name, _))| {
+                            col.expr.is_none() && column_pos(name) == Some(col.pos_in_table)
+                        });
+                let missing_primary_key = table
+                    .primary_key_columns
+                    .iter()
+                    .filter_map(|(name, _)| column_pos(name))
+                    .filter(|pos| !declared.iter().any(|col| col.pos_in_table == *pos));
+                same_columns
+                    && appended
+                        .iter()
+                        .map(|col| col.pos_in_table)
+                        .eq(missing_primary_key)
+            })
+            .map(|unique_set| unique_set.columns.len())
+            // Constraints that only differ by primary key columns share the same index layout,
+            // the shortest one is the strictest.
+            .min()
+            .unwrap_or(self.columns.len());
+        &self.columns[..key_len]
+    }
+
     /// Given a column position in the table, return the position in the index.
     /// Returns None if the column is not found in the index.
     /// For example, given:
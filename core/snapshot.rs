@@ -0,0 +1,195 @@
+use std::sync::Arc;
+
+use turso_parser::{
+    ast::{Cmd, Stmt},
+    parser::Parser,
+};
+
+use crate::{
+    translate::emitter::TransactionMode, Connection, Database, LimboError, Result, Statement,
+    TransactionState,
+};
+
+/// A read-only view of a database at the commit where it was opened, see [Database::snapshot].
+///
+/// A snapshot is a connection that keeps a read transaction open. In WAL mode, it keeps reading the
+/// WAL up to the last frame that was committed when it was opened, and in MVCC mode, it reads the
+/// row versions that were visible when its transaction began. Other connections can write to the
+/// database meanwhile, but checkpoints can't move the WAL past the frames the snapshot reads, so a
+/// snapshot should be closed as soon as it is no longer needed. Dropping a snapshot also closes it.
+pub struct Snapshot {
+    conn: Arc<Connection>,
+}
+
+impl Snapshot {
+    pub(crate) fn open(db: &Arc<Database>) -> Result<Self> {
+        let conn = db.connect()?;
+        conn.set_query_only(true);
+        let snapshot = Self { conn };
+        snapshot.conn.execute("BEGIN")?;
+        // A deferred transaction only starts reading with its first statement that accesses the database.
+        snapshot
+            .conn
+            .execute("SELECT 1 FROM sqlite_schema LIMIT 0")?;
+        Ok(snapshot)
+    }
+
+    /// Opens another snapshot that reads the database at the same commit as this one, even if
+    /// other connections committed since this snapshot was opened, e.g. to read a consistent view
+    /// of the database from several threads.
+    pub fn fork(&self) -> Result<Self> {
+        let conn = self.conn.db.connect()?;
+        conn.set_query_only(true);
+        *conn.schema.write() = self.conn.schema.read().clone();
+        let snapshot = Self { conn };
+        snapshot.conn.execute("BEGIN")?;
+        if let Some(mv_store) = self.conn.mv_store().as_ref() {
+            let (tx_id, _) = self
+                .conn
+                .get_mv_tx()
+                .expect("a snapshot keeps its transaction open");
+            let fork_tx_id = mv_store.begin_tx_at_snapshot_of(tx_id)?;
+            *snapshot.conn.mv_tx.write() = Some((fork_tx_id, TransactionMode::Read));
+        } else if let Some(point) = self.conn.pager.load().read_point() {
+            snapshot.conn.pager.load().begin_read_tx_at(&point)?;
+        }
+        snapshot.conn.set_tx_state(TransactionState::Read);
+        Ok(snapshot)
+    }
+
+    /// Prepares a query on the snapshot. Only `SELECT` statements, and the `EXPLAIN` of any
+    /// statement, can be run on a snapshot.
+    pub fn prepare(&self, sql: impl AsRef<str>) -> Result<Statement> {
+        let sql = sql.as_ref();
+        let mut parser = Parser::new(sql.as_bytes());
+        match parser
+            .next_cmd()
+            .map_err(|e| LimboError::from_parse_error(e, sql))?
+        {
+            Some(Cmd::Stmt(Stmt::Select(_)) | Cmd::Explain(_) | Cmd::ExplainQueryPlan(_)) => {
+                self.conn.prepare(sql)
+            }
+            Some(_) => Err(LimboError::InvalidArgument(
+                "only SELECT statements can be run on a snapshot".to_string(),
+            )),
+            None => Err(LimboError::InvalidArgument(
+                "The supplied SQL string contains no statements".to_string(),
+            )),
+        }
+    }
+
+    /// Ends the read transaction of the snapshot and closes its connection.
+    pub fn close(self) -> Result<()> {
+        self.end()
+    }
+
+    fn end(&self) -> Result<()> {
+        if self.conn.is_closed() {
+            return Ok(());
+        }
+        let result = self.conn.execute("ROLLBACK");
+        self.conn.close()?;
+        result
+    }
+}
+
+impl Drop for Snapshot {
+    fn drop(&mut self) {
+        let _ = self.end();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CheckpointMode, MemoryIO, Value};
+
+    fn count(stmt: Result<Statement>) -> i64 {
+        let rows = stmt.unwrap().run_collect_rows().unwrap();
+        let Value::Integer(count) = rows[0][0] else {
+            panic!("expected an integer count, got {rows:?}");
+        };
+        count
+    }
+
+    fn test_snapshot_isolation(journal_mode: &str) {
+        let db = Database::open_file(Arc::new(MemoryIO::new()), ":memory:").unwrap();
+        let conn = db.connect().unwrap();
+        conn.execute(format!("PRAGMA journal_mode = '{journal_mode}'"))
+            .unwrap();
+        conn.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, x)")
+            .unwrap();
+        conn.execute("INSERT INTO t VALUES (1, 'a'), (2, 'b')")
+            .unwrap();
+
+        let snapshot = db.snapshot().unwrap();
+        conn.execute("INSERT INTO t VALUES (3, 'c')").unwrap();
+        conn.execute("UPDATE t SET x = 'z' WHERE id = 1").unwrap();
+        conn.execute("DELETE FROM t WHERE id = 2").unwrap();
+        if journal_mode == "wal" {
+            // A passive checkpoint can't copy the pages the snapshot still reads from the WAL.
+            conn.checkpoint(CheckpointMode::Passive {
+                upper_bound_inclusive: None,
+            })
+            .unwrap();
+        }
+
+        let rows = snapshot
+            .prepare("SELECT id, x FROM t ORDER BY id")
+            .unwrap()
+            .run_collect_rows()
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                vec![Value::Integer(1), Value::build_text("a")],
+                vec![Value::Integer(2), Value::build_text("b")],
+            ]
+        );
+        assert_eq!(count(conn.prepare("SELECT count(*) FROM t")), 2);
+
+        // A fork reads at the commit of the snapshot it was forked from, not the latest one.
+        let fork = snapshot.fork().unwrap();
+        let fork_rows = fork
+            .prepare("SELECT id, x FROM t ORDER BY id")
+            .unwrap()
+            .run_collect_rows()
+            .unwrap();
+        assert_eq!(fork_rows, rows);
+        assert!(fork.prepare("INSERT INTO t VALUES (4, 'd')").is_err());
+        fork.close().unwrap();
+
+        let newer = db.snapshot().unwrap();
+        assert_eq!(
+            count(newer.prepare("SELECT count(*) FROM t WHERE x = 'z'")),
+            1
+        );
+        assert_eq!(
+            count(snapshot.prepare("SELECT count(*) FROM t WHERE x = 'z'")),
+            0
+        );
+
+        for sql in [
+            "INSERT INTO t VALUES (4, 'd')",
+            "COMMIT",
+            "PRAGMA query_only = 0",
+        ] {
+            assert!(snapshot.prepare(sql).is_err(), "{sql} should be rejected");
+        }
+        snapshot.close().unwrap();
+        drop(newer);
+
+        conn.execute("INSERT INTO t VALUES (4, 'd')").unwrap();
+        assert_eq!(count(conn.prepare("SELECT count(*) FROM t")), 3);
+    }
+
+    #[test]
+    fn test_snapshot_wal() {
+        test_snapshot_isolation("wal");
+    }
+
+    #[test]
+    fn test_snapshot_mvcc() {
+        test_snapshot_isolation("experimental_mvcc");
+    }
+}
@@ -4,6 +4,7 @@ use std::{
     ops::Deref,
     sync::{atomic::Ordering, Arc},
     task::Waker,
+    time::Duration,
 };
 
 use tracing::{instrument, Level};
@@ -17,10 +18,12 @@ use crate::{
     parameters,
     schema::Trigger,
     stats::refresh_analyze_stats,
+    storage::pager::PagerIoStats,
     translate::{self, display::PlanContext, emitter::TransactionMode},
     vdbe::{
         self,
-        explain::{EXPLAIN_COLUMNS_TYPE, EXPLAIN_QUERY_PLAN_COLUMNS_TYPE},
+        explain::{insn_to_row, EXPLAIN_COLUMNS_TYPE, EXPLAIN_QUERY_PLAN_COLUMNS_TYPE},
+        metrics::{InsnProfile, StatementMetrics},
     },
     LimboError, MvStore, Pager, QueryMode, Result, Value, EXPLAIN_COLUMNS,
     EXPLAIN_QUERY_PLAN_COLUMNS,
@@ -44,8 +47,21 @@ pub struct Statement {
     busy: bool,
     /// Busy handler state for tracking invocations and timeouts
     busy_handler_state: Option<BusyHandlerState>,
+    /// Time spent parsing and translating the statement
+    prepare_time: Duration,
+    /// Pager counters sampled when execution started, to report per-statement deltas
+    io_stats_start: PagerIoStats,
+    /// Counters of the executions that ended since the statement was prepared or
+    /// [Self::stats] last reset them
+    stats: StatementMetrics,
+    /// Counters of the current execution at the time [Self::stats] last reset them
+    stats_baseline: StatementMetrics,
+    /// Whether the current execution was already added to `stats`
+    execution_counted: bool,
 }
 
+crate::assert::assert_send!(Statement);
+
 impl std::fmt::Debug for Statement {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Statement").finish()
@@ -75,9 +91,18 @@ impl Statement {
             query_mode,
             busy: false,
             busy_handler_state: None,
+            prepare_time: Duration::ZERO,
+            io_stats_start: PagerIoStats::default(),
+            stats: StatementMetrics::default(),
+            stats_baseline: StatementMetrics::default(),
+            execution_counted: false,
         }
     }
 
+    pub(crate) fn set_prepare_time(&mut self, prepare_time: Duration) {
+        self.prepare_time = prepare_time;
+    }
+
     pub fn get_trigger(&self) -> Option<Arc<Trigger>> {
         self.program.trigger.clone()
     }
@@ -104,10 +129,75 @@ impl Statement {
         self.state.execution_state
     }
 
+    /// Returns `(address, opcode, profile)` for each instruction that ran since the statement
+    /// was last reset. Only collected while `PRAGMA vdbe_profile` is on.
+    pub fn vdbe_profile(&self) -> Vec<(usize, &'static str, InsnProfile)> {
+        self.state
+            .profile
+            .iter()
+            .enumerate()
+            .filter(|(_, profile)| profile.executions > 0)
+            .map(|(addr, profile)| {
+                let (insn, _) = &self.program.insns[addr];
+                (addr, insn_to_row(&self.program, insn).0, *profile)
+            })
+            .collect()
+    }
+
+    /// Returns the counters accumulated over all executions of the statement, including the
+    /// one in progress, similar to `sqlite3_stmt_status`. Unlike the statement itself, the
+    /// counters are not reset by [Self::reset]; pass `reset` to start them over from zero.
+    pub fn stats(&mut self, reset: bool) -> StatementMetrics {
+        let current = self.unfinished_execution_metrics();
+        let mut stats = self.stats.clone();
+        if let Some(current) = &current {
+            stats.merge(&current.since(&self.stats_baseline));
+        }
+        stats.prepare_time = self.prepare_time;
+        if reset {
+            self.stats = StatementMetrics::default();
+            self.stats_baseline = current.unwrap_or_default();
+        }
+        stats
+    }
+
+    /// Counters of the current execution, including the pager I/O done since it started.
+    fn execution_metrics(&self) -> StatementMetrics {
+        let io_stats = self.pager.io_stats().since(&self.io_stats_start);
+        let mut metrics = self.state.metrics.clone();
+        metrics.cache_hits = io_stats.cache_hits;
+        metrics.cache_misses = io_stats.cache_misses;
+        metrics.pages_read = io_stats.pages_read;
+        metrics.pages_written = io_stats.pages_written;
+        metrics.prepare_time = self.prepare_time;
+        metrics
+    }
+
+    /// Counters of the current execution if it started and has not been added to
+    /// [Self::stats] yet, which happens when it completes or the statement is reset.
+    fn unfinished_execution_metrics(&self) -> Option<StatementMetrics> {
+        if self.execution_counted
+            || matches!(self.state.execution_state, ProgramExecutionState::Init)
+        {
+            return None;
+        }
+        Some(self.execution_metrics())
+    }
+
+    fn end_execution_stats(&mut self, metrics: &StatementMetrics) {
+        self.stats.merge(&metrics.since(&self.stats_baseline));
+        self.stats_baseline = StatementMetrics::default();
+        self.execution_counted = true;
+    }
+
     pub fn mv_store(&self) -> impl Deref<Target = Option<Arc<MvStore>>> {
         self.program.connection.mv_store()
     }
 
+    #[cfg_attr(
+        feature = "query_tracing",
+        instrument(skip_all, fields(sql = self.program.sql.as_str()), level = Level::DEBUG)
+    )]
     fn _step(&mut self, waker: Option<&Waker>) -> Result<StepResult> {
         // If we're waiting for a busy handler timeout, check if we can proceed
         if let Some(busy_state) = self.busy_handler_state.as_ref() {
@@ -120,6 +210,11 @@ impl Statement {
             }
         }
 
+        if matches!(self.state.execution_state, ProgramExecutionState::Init) {
+            self.io_stats_start = self.pager.io_stats();
+            self.program.connection.clear_interrupt();
+        }
+
         let mut res = if !self.accesses_db {
             self.program
                 .step(&mut self.state, self.pager.clone(), self.query_mode, waker)
@@ -144,8 +239,15 @@ impl Statement {
 
         // Aggregate metrics when statement completes
         if matches!(res, Ok(StepResult::Done)) {
+            let metrics = self.execution_metrics();
+            if !self.execution_counted {
+                self.end_execution_stats(&metrics);
+            }
             let mut conn_metrics = self.program.connection.metrics.write();
-            conn_metrics.record_statement(self.state.metrics.clone());
+            conn_metrics.record_statement(metrics);
+            for (_, opcode, profile) in self.vdbe_profile() {
+                conn_metrics.record_insn_profile(opcode, &profile);
+            }
             self.busy = false;
             self.busy_handler_state = None; // Reset busy state on completion
             drop(conn_metrics);
@@ -156,7 +258,8 @@ impl Statement {
                 refresh_analyze_stats(&self.program.connection);
             }
         } else {
-            self.busy = true;
+            // A failed statement is halted and must be reset before it can run again.
+            self.busy = res.is_ok();
         }
 
         // Handle busy result by invoking the busy handler
@@ -268,7 +371,8 @@ impl Statement {
         tracing::trace!("repreparing statement");
         let conn = self.program.connection.clone();
 
-        *conn.schema.write() = conn.db.clone_schema();
+        *conn.schema.write() = conn.committed_schema()?;
+        conn.reload_database_schemas();
         self.program = {
             let mut parser = Parser::new(self.program.sql.as_bytes());
             let cmd = parser.next_cmd()?;
@@ -354,6 +458,37 @@ impl Statement {
         }
     }
 
+    /// Returns the type name the result column was declared with in its `CREATE TABLE`
+    /// statement, or `None` for expressions and columns declared without a type.
+    ///
+    /// Unlike [Self::get_column_type], the type is returned as declared, including any size
+    /// arguments, so a column declared as `VARCHAR(32)` reports `VARCHAR(32)`.
+    pub fn get_column_decl_type(&self, idx: usize) -> Option<Cow<'_, str>> {
+        if self.query_mode != QueryMode::Normal {
+            return None;
+        }
+        let column = &self.program.result_columns.get(idx).expect("No column");
+        match &column.expr {
+            turso_parser::ast::Expr::Column {
+                table,
+                column: column_idx,
+                ..
+            } => {
+                let (_, table_ref) = self
+                    .program
+                    .table_references
+                    .find_table_by_internal_id(*table)?;
+                let table_column = table_ref.get_column_at(*column_idx)?;
+                if table_column.ty_str.is_empty() {
+                    None
+                } else {
+                    Some(table_column.decl_type())
+                }
+            }
+            _ => None,
+        }
+    }
+
     pub fn get_column_type(&self, idx: usize) -> Option<String> {
         if self.query_mode == QueryMode::Explain {
             return Some(
@@ -412,10 +547,55 @@ impl Statement {
         self.state.bind_at(index, value);
     }
 
+    /// Binds `value` to the named parameter `name`, which includes its prefix, e.g. `:id`.
+    pub fn bind_by_name(&mut self, name: &str, value: Value) -> Result<()> {
+        let Some(index) = self.parameter_index(name) else {
+            return Err(LimboError::InvalidArgument(format!(
+                "no such parameter: {name}"
+            )));
+        };
+        self.bind_at(index, value);
+        Ok(())
+    }
+
+    /// Binds `values` to the parameters in order, starting at index 1.
+    pub fn bind_values(&mut self, values: impl IntoIterator<Item = Value>) -> Result<()> {
+        for (i, value) in values.into_iter().enumerate() {
+            if i >= self.parameters_count() {
+                return Err(LimboError::InvalidArgument(format!(
+                    "too many parameters, the statement expects {}",
+                    self.parameters_count()
+                )));
+            }
+            self.bind_at(NonZero::new(i + 1).unwrap(), value);
+        }
+        Ok(())
+    }
+
+    /// Binds each value to the named parameter it is paired with, see [Self::bind_by_name].
+    pub fn bind_named<S: AsRef<str>>(
+        &mut self,
+        values: impl IntoIterator<Item = (S, Value)>,
+    ) -> Result<()> {
+        for (name, value) in values {
+            self.bind_by_name(name.as_ref(), value)?;
+        }
+        Ok(())
+    }
+
+    /// Sets every parameter of the statement back to NULL.
+    ///
+    /// Unlike [Self::reset], this does not rewind the program, so it can be combined with it
+    /// to fully reuse a prepared statement with fresh parameters.
     pub fn clear_bindings(&mut self) {
         self.state.clear_bindings();
     }
 
+    /// Rewinds the statement so it can be executed again from the start.
+    ///
+    /// Write statements that are still running are first run to completion, then the open
+    /// cursors and the current row are released. Bound parameters are kept, use
+    /// [Self::clear_bindings] to reset them.
     pub fn reset(&mut self) {
         self.reset_internal(None, None);
     }
@@ -448,9 +628,13 @@ impl Statement {
                 Ok(vdbe::StepResult::Interrupt) | Ok(vdbe::StepResult::Busy) => break,
             }
         }
+        if let Some(metrics) = self.unfinished_execution_metrics() {
+            self.end_execution_stats(&metrics);
+        }
         // as abort uses auto_txn_cleanup value - it needs to be called before state.reset
         self.program.abort(&self.pager, None, &mut self.state);
         self.state.reset(max_registers, max_cursors);
+        self.execution_counted = false;
         self.program.n_change.store(0, Ordering::SeqCst);
         self.busy = false;
         self.busy_handler_state = None;
@@ -460,10 +644,18 @@ impl Statement {
         self.state.result_row.as_ref()
     }
 
-    pub fn get_sql(&self) -> &str {
+    /// Returns the SQL text the statement was prepared from.
+    pub fn sql(&self) -> &str {
         &self.program.sql
     }
 
+    #[deprecated(note = "use `Statement::sql` instead")]
+    pub fn get_sql(&self) -> &str {
+        self.sql()
+    }
+
+    /// Returns true if the statement has been stepped but neither ran to completion, failed
+    /// nor was reset since.
     pub fn is_busy(&self) -> bool {
         self.busy
     }
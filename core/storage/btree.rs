@@ -5370,6 +5370,9 @@ impl CursorTrait for BTreeCursor {
     ///
     /// Only supposed to be used in the context of a simple Count Select Statement
     fn count(&mut self) -> Result<IOResult<usize>> {
+        if self.valid_state == CursorValidState::Invalid {
+            return Ok(IOResult::Done(0));
+        }
         let mut mem_page;
         let mut contents;
 
@@ -7790,7 +7793,12 @@ mod tests {
         BufferPool, Completion, Connection, IOContext, StepResult, Wal, WalFile, WalFileShared,
     };
     use arc_swap::ArcSwapOption;
-    use std::{collections::HashSet, mem::transmute, ops::Deref, sync::Arc};
+    use std::{
+        collections::{BTreeMap, HashSet},
+        mem::transmute,
+        ops::Deref,
+        sync::Arc,
+    };
 
     use tempfile::TempDir;
 
@@ -8514,6 +8522,128 @@ mod tests {
         }
     }
 
+    /// Randomly inserts, overwrites and deletes rows of a table b-tree, so that interior pages
+    /// are split and merged and overflow chains are allocated and freed along the way.
+    fn btree_insert_delete_fuzz_run(
+        attempts: usize,
+        operations: usize,
+        size: impl Fn(&mut ChaCha8Rng) -> usize,
+        insert_chance: f64,
+    ) {
+        const VALIDATE_INTERVAL: usize = 500;
+        let (mut rng, seed) = rng_from_time_or_env();
+        tracing::info!("super seed: {}", seed);
+        let num_columns = 5;
+
+        for _ in 0..attempts {
+            let (pager, root_page, _db, conn) = empty_btree();
+            let mut cursor = BTreeCursor::new_table(pager.clone(), root_page, num_columns);
+            // rowid -> size of the blob stored in the row
+            let mut expected = BTreeMap::new();
+            tracing::info!("seed: {seed}");
+            for i in 0..operations {
+                pager.begin_read_tx().unwrap();
+                pager.io.block(|| pager.begin_write_tx()).unwrap();
+
+                let is_insert =
+                    expected.is_empty() || rng.next_u64() % 100 < (insert_chance * 100.0) as u64;
+                if is_insert {
+                    // A small key space so that some inserts overwrite existing rows.
+                    let key = (rng.next_u64() % (operations as u64 * 2)) as i64;
+                    let size = size(&mut rng);
+                    tracing::info!("INSERT INTO t VALUES ({key}, randomblob({size})); -- {i}");
+                    run_until_done(
+                        || cursor.seek(SeekKey::TableRowId(key), SeekOp::GE { eq_only: true }),
+                        pager.deref(),
+                    )
+                    .unwrap();
+                    let regs = &[Register::Value(Value::Blob(vec![(key % 256) as u8; size]))];
+                    let value = ImmutableRecord::from_registers(regs, regs.len());
+                    run_until_done(
+                        || cursor.insert(&BTreeKey::new_table_rowid(key, Some(&value))),
+                        pager.deref(),
+                    )
+                    .unwrap();
+                    expected.insert(key, size);
+                } else {
+                    let nth = rng.next_u64() as usize % expected.len();
+                    let key = *expected.keys().nth(nth).unwrap();
+                    tracing::info!("DELETE FROM t WHERE rowid = {key}; -- {i}");
+                    let seek_result = run_until_done(
+                        || cursor.seek(SeekKey::TableRowId(key), SeekOp::GE { eq_only: true }),
+                        pager.deref(),
+                    )
+                    .unwrap();
+                    assert!(
+                        matches!(seek_result, SeekResult::Found),
+                        "key {key} is not found, seed: {seed}"
+                    );
+                    run_until_done(|| cursor.delete(), pager.deref()).unwrap();
+                    expected.remove(&key);
+                }
+
+                let c = cursor.move_to_root().unwrap();
+                if let Some(c) = c {
+                    pager.io.wait_for_completion(c).unwrap();
+                }
+                pager.io.block(|| pager.commit_tx(&conn)).unwrap();
+
+                if i % VALIDATE_INTERVAL == 0 {
+                    validate_expected_rows(&pager, &mut cursor, root_page, &expected, seed);
+                }
+            }
+            validate_expected_rows(&pager, &mut cursor, root_page, &expected, seed);
+        }
+    }
+
+    fn validate_expected_rows(
+        pager: &Arc<Pager>,
+        cursor: &mut BTreeCursor,
+        root_page: i64,
+        expected: &BTreeMap<i64, usize>,
+        seed: u64,
+    ) {
+        pager.begin_read_tx().unwrap();
+        if matches!(validate_btree(pager.clone(), root_page), (_, false)) {
+            panic!(
+                "invalid btree, seed: {seed}\n{}",
+                format_btree(pager.clone(), root_page, 0)
+            );
+        }
+        let _c = cursor.move_to_root().unwrap();
+        run_until_done(|| cursor.rewind(), pager.deref()).unwrap();
+        for (key, size) in expected.iter() {
+            assert!(cursor.has_record(), "key {key} is not found, seed: {seed}");
+            let rowid = run_until_done(|| cursor.rowid(), pager.deref())
+                .unwrap()
+                .unwrap();
+            assert_eq!(rowid, *key, "unexpected rowid, seed: {seed}");
+            let record = loop {
+                match cursor.record().unwrap() {
+                    IOResult::Done(r) => break r,
+                    IOResult::IO(io) => io.wait(&*pager.io).unwrap(),
+                }
+            };
+            let record = record.as_ref().unwrap();
+            let values = record.get_values();
+            let Some(ValueRef::Blob(blob)) = values.first() else {
+                panic!("expected blob for key {key}, got {values:?}, seed: {seed}");
+            };
+            assert_eq!(
+                blob.len(),
+                *size,
+                "wrong payload for key {key}, seed: {seed}"
+            );
+            assert!(
+                blob.iter().all(|b| *b == (*key % 256) as u8),
+                "corrupted payload for key {key}, seed: {seed}"
+            );
+            run_until_done(|| cursor.next(), pager.deref()).unwrap();
+        }
+        assert!(!cursor.has_record(), "unexpected extra rows, seed: {seed}");
+        pager.end_read_tx();
+    }
+
     fn btree_index_insert_fuzz_run(attempts: usize, inserts: usize) {
         use crate::storage::pager::CreateBTreeFlags;
 
@@ -8986,6 +9116,16 @@ mod tests {
         );
     }
 
+    #[test]
+    pub fn btree_insert_delete_fuzz_run_small() {
+        btree_insert_delete_fuzz_run(4, 2000, |rng| (rng.next_u32() % 128) as usize, 0.6);
+    }
+
+    #[test]
+    pub fn btree_insert_delete_fuzz_run_overflow() {
+        btree_insert_delete_fuzz_run(4, 300, |rng| (rng.next_u32() % 32 * 1024) as usize, 0.6);
+    }
+
     #[test]
     pub fn btree_insert_fuzz_run_random() {
         btree_insert_fuzz_run(128, 16, |rng| (rng.next_u32() % 4096) as usize);
@@ -9036,6 +9176,12 @@ mod tests {
         );
     }
 
+    #[test]
+    #[ignore]
+    pub fn fuzz_long_btree_insert_delete_fuzz_run() {
+        btree_insert_delete_fuzz_run(2, 20_000, |rng| (rng.next_u32() % 8 * 1024) as usize, 0.6);
+    }
+
     #[test]
     #[ignore]
     pub fn fuzz_long_btree_insert_fuzz_run_random() {
@@ -0,0 +1,137 @@
+//! Checkpointing on a dedicated thread.
+//!
+//! With [crate::DatabaseOpts::with_background_checkpoint], a commit that pushes the WAL past the
+//! connection's `wal_autocheckpoint` threshold no longer backfills the WAL itself. It wakes up
+//! the checkpointer thread instead, which runs a passive checkpoint on its own connection.
+//! The checkpoint takes the same WAL locks as any other checkpoint, so it only backfills frames
+//! that no reader still needs and gives up with `Busy` instead of waiting for other
+//! checkpointers.
+use std::sync::{Arc, Weak};
+
+use parking_lot::{Condvar, Mutex};
+
+use crate::{CheckpointMode, Database, LimboError, Result};
+
+pub(crate) struct BackgroundCheckpointer {
+    shared: Arc<Shared>,
+}
+
+#[derive(Default)]
+struct Shared {
+    state: Mutex<State>,
+    wakeup: Condvar,
+}
+
+#[derive(Default)]
+struct State {
+    requested: bool,
+    shutdown: bool,
+}
+
+impl BackgroundCheckpointer {
+    /// Spawns the checkpointer thread. The thread holds only a weak reference to the database,
+    /// so it does not keep the database open, and exits once the database is dropped.
+    pub(crate) fn spawn(db: Weak<Database>) -> Result<Self> {
+        let shared = Arc::new(Shared::default());
+        let thread_shared = shared.clone();
+        std::thread::Builder::new()
+            .name("turso-checkpointer".to_string())
+            .spawn(move || run(db, thread_shared))?;
+        Ok(Self { shared })
+    }
+
+    /// Wakes up the checkpointer thread. Requests made while a checkpoint is already running
+    /// are coalesced into a single follow-up checkpoint.
+    pub(crate) fn request(&self) {
+        self.shared.state.lock().requested = true;
+        self.shared.wakeup.notify_one();
+    }
+}
+
+impl Drop for BackgroundCheckpointer {
+    fn drop(&mut self) {
+        self.shared.state.lock().shutdown = true;
+        self.shared.wakeup.notify_one();
+    }
+}
+
+fn run(db: Weak<Database>, shared: Arc<Shared>) {
+    loop {
+        {
+            let mut state = shared.state.lock();
+            while !state.requested && !state.shutdown {
+                shared.wakeup.wait(&mut state);
+            }
+            if state.shutdown {
+                return;
+            }
+            state.requested = false;
+        }
+        let Some(db) = db.upgrade() else {
+            return;
+        };
+        if let Err(e) = checkpoint(&db) {
+            tracing::error!("Background checkpoint failed: {e}");
+        }
+    }
+}
+
+fn checkpoint(db: &Arc<Database>) -> Result<()> {
+    // The connection is dropped without being closed, so it never runs the checkpoint that
+    // closing the last connection to a database does.
+    let conn = db.connect()?;
+    match conn.checkpoint(CheckpointMode::Passive {
+        upper_bound_inclusive: None,
+    }) {
+        Ok(_) | Err(LimboError::Busy) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{atomic::Ordering, Arc},
+        time::{Duration, Instant},
+    };
+
+    use crate::{Database, DatabaseOpts, OpenFlags, PlatformIO, IO};
+
+    #[test]
+    fn test_commit_wakes_background_checkpointer() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.db");
+        let io: Arc<dyn IO> = Arc::new(PlatformIO::new().unwrap());
+        let db = Database::open_file_with_flags(
+            io,
+            path.to_str().unwrap(),
+            OpenFlags::default(),
+            DatabaseOpts::new().with_background_checkpoint(true),
+            None,
+        )
+        .unwrap();
+        let conn = db.connect().unwrap();
+        conn.execute("PRAGMA wal_autocheckpoint = 1").unwrap();
+        conn.execute("CREATE TABLE t(x)").unwrap();
+        conn.execute("INSERT INTO t VALUES (1)").unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            let (max_frame, nbackfills) = {
+                let wal = db.shared_wal.read();
+                (
+                    wal.max_frame.load(Ordering::Acquire),
+                    wal.nbackfills.load(Ordering::Acquire),
+                )
+            };
+            if max_frame > 0 && nbackfills == max_frame {
+                break;
+            }
+            assert!(
+                Instant::now() < deadline,
+                "WAL was not backfilled: max_frame={max_frame}, nbackfills={nbackfills}"
+            );
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
@@ -12,6 +12,8 @@
 //! for the database, also either local or remote.
 pub(crate) mod btree;
 pub(crate) mod buffer_pool;
+#[cfg(feature = "background_checkpoint")]
+pub(crate) mod checkpointer;
 pub(crate) mod checksum;
 pub mod database;
 pub(crate) mod encryption;
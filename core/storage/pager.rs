@@ -2,7 +2,7 @@ use crate::assert::assert_send_sync;
 use crate::io::WriteBatch;
 use crate::storage::btree::PinGuard;
 use crate::storage::subjournal::Subjournal;
-use crate::storage::wal::PreparedFrames;
+use crate::storage::wal::{PreparedFrames, ReadPoint};
 use crate::storage::{
     buffer_pool::BufferPool,
     database::DatabaseStorage,
@@ -944,12 +944,22 @@ struct CheckpointState {
     phase: CheckpointPhase,
     /// The checkpoint result, set after WAL checkpoint completes
     result: Option<CheckpointResult>,
+    /// The WAL sync submitted in [CheckpointPhase::SyncWal].
+    wal_sync: Option<Completion>,
 }
 
 #[derive(Clone, Debug, Default, PartialEq)]
 enum CheckpointPhase {
     #[default]
     NotCheckpointing,
+    /// Sync the WAL before backfilling, as commits in NORMAL mode or inside a group commit
+    /// window may have left frames that were never synced. Skipped when sync_mode is Off, and
+    /// when the shared WAL has no unsynced frames, see [Wal::has_unsynced_frames].
+    SyncWal {
+        mode: CheckpointMode,
+        sync_mode: crate::SyncMode,
+        clear_page_cache: bool,
+    },
     Checkpoint {
         mode: CheckpointMode,
         sync_mode: crate::SyncMode,
@@ -1136,6 +1146,9 @@ pub struct Pager {
     checkpoint_state: RwLock<CheckpointState>,
     syncing: Arc<AtomicBool>,
     auto_vacuum_mode: AtomicU8,
+    /// Set when the database file has pointer map pages but autovacuum is not enabled, so that
+    /// they would go stale on write.
+    ptrmap_unmaintained: AtomicBool,
     /// Mutex for synchronizing database initialization to prevent race conditions
     init_lock: Arc<Mutex<()>>,
     /// The state of the current allocate page operation.
@@ -1167,10 +1180,158 @@ pub struct Pager {
     enable_encryption: AtomicBool,
     /// In Memory Page 1 for Empty Dbs
     init_page_1: Arc<ArcSwapOption<Page>>,
+    /// Page cache and I/O counters, sampled per statement for execution statistics.
+    io_counters: IoCounters,
+    /// Group commit window shared by all pagers of the database.
+    pub(crate) group_commit: Arc<GroupCommit>,
+    /// The commit of this pager waiting for a group commit sync, if any.
+    pending_group_sync: Mutex<Option<PendingGroupSync>>,
 }
 
 assert_send_sync!(Pager);
 
+/// Coalesces the WAL fsyncs of commits that happen close together.
+///
+/// With a non-zero window, a commit in FULL sync mode writes its frames and releases the write
+/// lock without syncing the WAL, then waits for a sync shared with other commits. The first
+/// commit after the last sync opens the window; once it has passed, the first waiting commit to
+/// notice submits one fsync that covers every commit written so far, and none of them completes
+/// before that fsync does. A zero window syncs each commit while it holds the write lock. See
+/// `PRAGMA group_commit_window`.
+#[derive(Debug, Default)]
+pub struct GroupCommit {
+    /// Length of the window in microseconds, zero syncs every commit.
+    window_us: AtomicU64,
+    state: Mutex<GroupCommitState>,
+}
+
+#[derive(Debug, Default)]
+struct GroupCommitState {
+    /// Ticket of the last commit that wrote its frames without syncing them.
+    last_ticket: u64,
+    /// Commits with a ticket up to this one are durable.
+    synced_ticket: u64,
+    /// When the oldest commit not covered by a submitted sync registered, in microseconds
+    /// since the epoch.
+    window_start_us: Option<u64>,
+    /// The sync in flight and the last ticket it covers.
+    in_flight: Option<(u64, Completion)>,
+}
+
+impl GroupCommit {
+    pub fn window(&self) -> std::time::Duration {
+        std::time::Duration::from_micros(self.window_us.load(Ordering::Relaxed))
+    }
+
+    pub fn set_window(&self, window: std::time::Duration) {
+        self.window_us
+            .store(window.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.window_us.load(Ordering::Relaxed) != 0
+    }
+
+    fn micros(now: crate::Instant) -> u64 {
+        (now.secs.max(0) as u64) * 1_000_000 + now.micros as u64
+    }
+
+    /// Registers a commit whose frames were written but not synced. Must be called while the
+    /// commit still holds the write lock, so tickets follow the order of the frames.
+    fn register(&self, now: crate::Instant) -> u64 {
+        let mut state = self.state.lock();
+        state.last_ticket += 1;
+        state.window_start_us.get_or_insert(Self::micros(now));
+        state.last_ticket
+    }
+
+    /// Waits until a WAL sync covers the commit holding `ticket`, submitting the sync through
+    /// `wal` if the window has passed and no sync is in flight.
+    fn wait(
+        &self,
+        ticket: u64,
+        now: crate::Instant,
+        wal: &dyn Wal,
+        data_sync_retry: bool,
+    ) -> Result<IOResult<()>> {
+        let mut state = self.state.lock();
+        loop {
+            if state.synced_ticket >= ticket {
+                return Ok(IOResult::Done(()));
+            }
+            if let Some((covers, sync_c)) = state.in_flight.clone() {
+                if !sync_c.finished() {
+                    io_yield_one!(sync_c);
+                }
+                state.in_flight = None;
+                if !sync_c.succeeded() {
+                    if !data_sync_retry {
+                        panic!(
+                            "fsync error (data_sync_retry=off): {:?}",
+                            sync_c.get_error()
+                        );
+                    }
+                    // The commits it covered are due for another sync right away.
+                    state.window_start_us = Some(0);
+                    return Err(std::io::Error::other("WAL fsync failed").into());
+                }
+                state.synced_ticket = state.synced_ticket.max(covers);
+                continue;
+            }
+            let now_us = Self::micros(now);
+            let window_start_us = *state.window_start_us.get_or_insert(now_us);
+            if now_us.saturating_sub(window_start_us) < self.window_us.load(Ordering::Relaxed) {
+                io_yield_one!(Completion::new_yield());
+            }
+            let sync_c = wal.sync()?;
+            state.in_flight = Some((state.last_ticket, sync_c));
+            state.window_start_us = None;
+        }
+    }
+}
+
+/// A commit that released the write lock and waits for a [GroupCommit] sync.
+#[derive(Debug)]
+struct PendingGroupSync {
+    ticket: u64,
+    /// Set once the commit has finished and released the write lock.
+    result: Option<PagerCommitResult>,
+}
+
+/// Cumulative page cache and I/O counters of a [Pager].
+#[derive(Debug, Default)]
+struct IoCounters {
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+    pages_read: AtomicU64,
+    pages_written: AtomicU64,
+}
+
+/// Snapshot of the page cache and I/O counters of a [Pager], see [Pager::io_stats].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PagerIoStats {
+    /// Page lookups served from the page cache.
+    pub cache_hits: u64,
+    /// Page lookups that had to go to the WAL or database file.
+    pub cache_misses: u64,
+    /// Pages read from the WAL or database file.
+    pub pages_read: u64,
+    /// Pages appended to the WAL.
+    pub pages_written: u64,
+}
+
+impl PagerIoStats {
+    /// Counters accumulated since the `earlier` snapshot was taken.
+    pub fn since(&self, earlier: &PagerIoStats) -> PagerIoStats {
+        PagerIoStats {
+            cache_hits: self.cache_hits.saturating_sub(earlier.cache_hits),
+            cache_misses: self.cache_misses.saturating_sub(earlier.cache_misses),
+            pages_read: self.pages_read.saturating_sub(earlier.pages_read),
+            pages_written: self.pages_written.saturating_sub(earlier.pages_written),
+        }
+    }
+}
+
 #[cfg(not(feature = "omit_autovacuum"))]
 pub struct VacuumState {
     /// State machine for [Pager::ptrmap_get]
@@ -1325,6 +1486,7 @@ impl Pager {
             checkpoint_state: RwLock::new(CheckpointState::default()),
             buffer_pool,
             auto_vacuum_mode: AtomicU8::new(AutoVacuumMode::None.into()),
+            ptrmap_unmaintained: AtomicBool::new(false),
             init_lock,
             allocate_page1_state,
             page_size: AtomicU32::new(0), // 0 means not set
@@ -1345,9 +1507,23 @@ impl Pager {
             io_ctx: RwLock::new(IOContext::default()),
             enable_encryption: AtomicBool::new(false),
             init_page_1,
+            io_counters: IoCounters::default(),
+            group_commit: Arc::new(GroupCommit::default()),
+            pending_group_sync: Mutex::new(None),
         })
     }
 
+    /// Returns the page cache and I/O counters accumulated over the pager's lifetime.
+    pub fn io_stats(&self) -> PagerIoStats {
+        let counters = &self.io_counters;
+        PagerIoStats {
+            cache_hits: counters.cache_hits.load(Ordering::Relaxed),
+            cache_misses: counters.cache_misses.load(Ordering::Relaxed),
+            pages_read: counters.pages_read.load(Ordering::Relaxed),
+            pages_written: counters.pages_written.load(Ordering::Relaxed),
+        }
+    }
+
     pub fn init_page_1(&self) -> Arc<ArcSwapOption<Page>> {
         self.init_page_1.clone()
     }
@@ -1673,6 +1849,23 @@ impl Pager {
         self.auto_vacuum_mode.store(mode.into(), Ordering::SeqCst);
     }
 
+    pub fn set_ptrmap_unmaintained(&self, unmaintained: bool) {
+        self.ptrmap_unmaintained
+            .store(unmaintained, Ordering::SeqCst);
+    }
+
+    /// Fails if writing to the database would leave its pointer map pages stale, which is the
+    /// case for `auto_vacuum` databases opened without experimental autovacuum support. Such
+    /// databases can still be read.
+    pub fn check_ptrmap_maintained(&self) -> Result<()> {
+        if self.ptrmap_unmaintained.load(Ordering::SeqCst) {
+            return Err(LimboError::InvalidArgument(
+                "cannot write to a database that uses auto_vacuum: enable experimental autovacuum to write to it".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
     /// Retrieves the pointer map entry for a given database page.
     /// `target_page_num` (1-indexed) is the page whose entry is sought.
     /// Returns `Ok(None)` if the page is not supposed to have a ptrmap entry (e.g. header, or a ptrmap page itself).
@@ -2135,6 +2328,23 @@ impl Pager {
         Ok(())
     }
 
+    /// The frames of the WAL seen by the current read transaction, see [Wal::read_point].
+    pub(crate) fn read_point(&self) -> Option<ReadPoint> {
+        self.wal.as_ref().and_then(|wal| wal.read_point())
+    }
+
+    /// Begins a read transaction at the same point as another open read transaction.
+    pub(crate) fn begin_read_tx_at(&self, point: &ReadPoint) -> Result<()> {
+        let Some(wal) = self.wal.as_ref() else {
+            return Ok(());
+        };
+        if wal.begin_read_tx_at(point)? {
+            self.clear_page_cache(false);
+            self.set_schema_cookie(None);
+        }
+        Ok(())
+    }
+
     #[instrument(skip_all, level = Level::DEBUG)]
     pub fn maybe_allocate_page1(&self) -> Result<IOResult<()>> {
         if !self.db_initialized() {
@@ -2166,6 +2376,28 @@ impl Pager {
 
     #[instrument(skip_all, level = Level::DEBUG)]
     pub fn commit_tx(&self, connection: &Connection) -> Result<IOResult<PagerCommitResult>> {
+        let schema_did_change = match connection.get_tx_state() {
+            TransactionState::Write { schema_did_change } => schema_did_change,
+            _ => false,
+        };
+        let threshold = connection.wal_auto_checkpoint_threshold();
+        self.commit_tx_then(connection, threshold, || {
+            if schema_did_change {
+                let schema = connection.schema.read().clone();
+                connection.db.update_schema_if_newer(schema);
+            }
+        })
+    }
+
+    /// Commits the write transaction and calls `after_commit` once its frames are in the WAL,
+    /// before waiting for a shared sync. The transaction state of the connection is not
+    /// consulted, so this also commits transactions on attached databases.
+    pub(crate) fn commit_tx_then(
+        &self,
+        connection: &Connection,
+        auto_checkpoint_threshold: Option<u64>,
+        after_commit: impl FnOnce(),
+    ) -> Result<IOResult<PagerCommitResult>> {
         if connection.is_nested_stmt() {
             // Parent statement will handle the transaction commit.
             return Ok(IOResult::Done(PagerCommitResult::Rollback));
@@ -2174,28 +2406,68 @@ impl Pager {
             // TODO: Unsure what the semantics of "end_tx" is for in-memory databases, ephemeral tables and ephemeral indexes.
             return Ok(IOResult::Done(PagerCommitResult::Rollback));
         };
-        let (_, schema_did_change) = match connection.get_tx_state() {
-            TransactionState::Write { schema_did_change } => (true, schema_did_change),
-            _ => (false, false),
-        };
-        tracing::trace!("commit_tx(schema_did_change={})", schema_did_change);
-        let commit_status = return_if_io!(self.commit_dirty_pages(
-            connection.is_wal_auto_checkpoint_disabled(),
-            connection.get_sync_mode(),
-            connection.get_data_sync_retry()
-        ));
-        wal.end_write_tx();
-        wal.end_read_tx();
+        let waiting_for_group_sync = self
+            .pending_group_sync
+            .lock()
+            .as_ref()
+            .is_some_and(|pending| pending.result.is_some());
+        if !waiting_for_group_sync {
+            tracing::trace!("commit_tx");
+            let commit_status = match self.commit_dirty_pages(
+                auto_checkpoint_threshold,
+                connection.get_sync_mode(),
+                connection.get_data_sync_retry(),
+            ) {
+                Ok(IOResult::Done(commit_status)) => commit_status,
+                Ok(IOResult::IO(io)) => return Ok(IOResult::IO(io)),
+                Err(e) => {
+                    self.pending_group_sync.lock().take();
+                    return Err(e);
+                }
+            };
+            wal.end_write_tx();
+            wal.end_read_tx();
 
-        if schema_did_change {
-            let schema = connection.schema.read().clone();
-            connection.db.update_schema_if_newer(schema);
+            after_commit();
+            match self.pending_group_sync.lock().as_mut() {
+                Some(pending) => pending.result = Some(commit_status),
+                None => return Ok(IOResult::Done(commit_status)),
+            }
         }
-        Ok(IOResult::Done(commit_status))
+        // The frames were written without a sync; hold the commit until a shared sync covers
+        // them.
+        let ticket = self.pending_group_sync.lock().as_ref().unwrap().ticket;
+        // If the sync fails, the pending sync is kept: the transaction already released the
+        // write lock with its frames committed, so the rollback that follows the error must
+        // not undo them or release a write lock that another connection may hold by now.
+        return_if_io!(self.group_commit.wait(
+            ticket,
+            self.io.now(),
+            wal.as_ref(),
+            connection.get_data_sync_retry(),
+        ));
+        let pending = self.pending_group_sync.lock().take().unwrap();
+        Ok(IOResult::Done(pending.result.unwrap()))
     }
 
     #[instrument(skip_all, level = Level::DEBUG)]
     pub fn rollback_tx(&self, connection: &Connection) {
+        let (is_write, schema_did_change) = match connection.get_tx_state() {
+            TransactionState::Write { schema_did_change } => (true, schema_did_change),
+            _ => (false, false),
+        };
+        self.rollback_tx_with(connection, is_write, schema_did_change);
+    }
+
+    /// Rolls back a transaction whose state is given explicitly instead of being read from the
+    /// connection. `schema_did_change` restores the schema of the connection's main database.
+    #[instrument(skip_all, level = Level::DEBUG)]
+    pub(crate) fn rollback_tx_with(
+        &self,
+        connection: &Connection,
+        is_write: bool,
+        schema_did_change: bool,
+    ) {
         if connection.is_nested_stmt() {
             // Parent statement will handle the transaction rollback.
             return;
@@ -2204,10 +2476,16 @@ impl Pager {
             // TODO: Unsure what the semantics of "end_tx" is for in-memory databases, ephemeral tables and ephemeral indexes.
             return;
         };
-        let (is_write, schema_did_change) = match connection.get_tx_state() {
-            TransactionState::Write { schema_did_change } => (true, schema_did_change),
-            _ => (false, false),
-        };
+        if self
+            .pending_group_sync
+            .lock()
+            .take()
+            .is_some_and(|pending| pending.result.is_some())
+        {
+            // The transaction committed and released its locks, only its sync was pending or
+            // failed.
+            return;
+        }
         tracing::trace!("rollback_tx(schema_did_change={})", schema_did_change);
         if is_write {
             self.clear_savepoints()
@@ -2243,6 +2521,7 @@ impl Pager {
         tracing::debug!("read_page_no_cache(page_idx = {})", page_idx);
         let page = Arc::new(Page::new(page_idx));
         let io_ctx = self.io_ctx.read();
+        self.io_counters.pages_read.fetch_add(1, Ordering::Relaxed);
         let Some(wal) = self.wal.as_ref() else {
             turso_assert!(
                 matches!(frame_watermark, Some(0) | None),
@@ -2287,9 +2566,13 @@ impl Pager {
                     "attempted to read page {page_idx} but got page {}",
                     page.get().id
                 );
+                self.io_counters.cache_hits.fetch_add(1, Ordering::Relaxed);
                 return Ok((page, None));
             }
         }
+        self.io_counters
+            .cache_misses
+            .fetch_add(1, Ordering::Relaxed);
 
         tracing::debug!("read_page(page_idx = {page_idx}) = reading page from disk");
         // Page not in cache, read from disk
@@ -2686,6 +2969,9 @@ impl Pager {
         for page in &pages {
             page.set_write_pending();
         }
+        self.io_counters
+            .pages_written
+            .fetch_add(pages.len() as u64, Ordering::Relaxed);
         match wal.append_frames_vectored(pages, page_sz) {
             Ok(completion) => {
                 state.completions.push(completion);
@@ -2752,6 +3038,9 @@ impl Pager {
                                     p.to_page()
                                 })
                                 .collect();
+                            self.io_counters
+                                .pages_written
+                                .fetch_add(wal_pages.len() as u64, Ordering::Relaxed);
                             let c = wal.append_frames_vectored(wal_pages, page_sz)?;
 
                             if c.succeeded() {
@@ -2926,12 +3215,13 @@ impl Pager {
 
     /// Flush all dirty pages to disk.
     /// In the base case, it will write the dirty pages to the WAL and then fsync the WAL.
-    /// If the WAL size is over the checkpoint threshold, it will checkpoint the WAL to
-    /// the database file and then fsync the database file.
+    /// If `auto_checkpoint_threshold` is set and the WAL holds more frames than that beyond the
+    /// last backfilled frame, it will checkpoint the WAL to the database file and then fsync the
+    /// database file.
     #[instrument(skip_all, level = Level::DEBUG)]
     pub fn commit_dirty_pages(
         &self,
-        wal_auto_checkpoint_disabled: bool,
+        auto_checkpoint_threshold: Option<u64>,
         sync_mode: SyncMode,
         data_sync_retry: bool,
     ) -> Result<IOResult<PagerCommitResult>> {
@@ -2947,11 +3237,7 @@ impl Pager {
             return Ok(IOResult::IO(c));
         }
 
-        match self.commit_dirty_pages_inner(
-            wal_auto_checkpoint_disabled,
-            sync_mode,
-            data_sync_retry,
-        ) {
+        match self.commit_dirty_pages_inner(auto_checkpoint_threshold, sync_mode, data_sync_retry) {
             r @ (Ok(IOResult::Done(..)) | Err(..)) => {
                 self.commit_info.write().reset();
                 r
@@ -2963,7 +3249,7 @@ impl Pager {
     #[instrument(skip_all, level = Level::DEBUG)]
     fn commit_dirty_pages_inner(
         &self,
-        wal_auto_checkpoint_disabled: bool,
+        auto_checkpoint_threshold: Option<u64>,
         sync_mode: SyncMode,
         data_sync_retry: bool,
     ) -> Result<IOResult<PagerCommitResult>> {
@@ -3147,8 +3433,19 @@ impl Pager {
                         .into());
                     }
                     commit_info.completions.clear();
-                    // Writes done, submit fsync if needed
-                    if sync_mode == SyncMode::Off {
+                    // Writes done, submit fsync if needed. In NORMAL mode the WAL is only
+                    // synced by checkpoints, and with group commit the sync is shared with
+                    // other commits once the write lock is released, see `commit_tx`.
+                    if sync_mode != SyncMode::Full {
+                        wal.mark_unsynced_frames();
+                        commit_info.state = CommitState::WalCommitDone;
+                    } else if self.group_commit.is_enabled() {
+                        wal.mark_unsynced_frames();
+                        let ticket = self.group_commit.register(self.io.now());
+                        *self.pending_group_sync.lock() = Some(PendingGroupSync {
+                            ticket,
+                            result: None,
+                        });
                         commit_info.state = CommitState::WalCommitDone;
                     } else {
                         let sync_c = wal.sync()?;
@@ -3196,7 +3493,9 @@ impl Pager {
                     self.dirty_pages.write().clear();
                     commit_info.prepared_frames.clear();
 
-                    if wal_auto_checkpoint_disabled || !wal.should_checkpoint() {
+                    if !auto_checkpoint_threshold
+                        .is_some_and(|threshold| wal.should_checkpoint(threshold))
+                    {
                         return Ok(IOResult::Done(PagerCommitResult::WalWritten));
                     }
                     commit_info.state = CommitState::AutoCheckpoint;
@@ -3205,7 +3504,7 @@ impl Pager {
                     let checkpoint_mode = CheckpointMode::Passive {
                         upper_bound_inclusive: None,
                     };
-                    match self.checkpoint(checkpoint_mode, sync_mode, false) {
+                    match self.checkpoint(checkpoint_mode, sync_mode, data_sync_retry, false) {
                         Err(LimboError::Busy) => {
                             tracing::debug!("Auto-checkpoint skipped: busy");
                             return Ok(IOResult::Done(PagerCommitResult::WalWritten));
@@ -3339,6 +3638,7 @@ impl Pager {
         let mut state = self.checkpoint_state.write();
         state.phase = CheckpointPhase::NotCheckpointing;
         state.result = None;
+        state.wal_sync = None;
         self.commit_info.write().state = CommitState::PrepareWal;
     }
 
@@ -3358,11 +3658,13 @@ impl Pager {
     /// Args:
     /// - mode: The checkpoint mode to use (PASSIVE, FULL, RESTART, TRUNCATE)
     /// - sync_mode: The fsync mode to use (OFF, NORMAL, FULL)
+    /// - data_sync_retry: Whether a failed WAL fsync is returned as an error instead of a panic
     /// - clear_page_cache: Whether to clear the page cache after checkpointing
     pub fn checkpoint(
         &self,
         mode: CheckpointMode,
         sync_mode: crate::SyncMode,
+        data_sync_retry: bool,
         clear_page_cache: bool,
     ) -> Result<IOResult<CheckpointResult>> {
         let Some(wal) = self.wal.as_ref() else {
@@ -3377,7 +3679,52 @@ impl Pager {
             let phase = self.checkpoint_state.read().phase.clone();
             match phase {
                 CheckpointPhase::NotCheckpointing => {
-                    self.checkpoint_state.write().phase = CheckpointPhase::Checkpoint {
+                    // The frames may come from connections in other sync modes, so the WAL
+                    // is synced unless no commit left frames unsynced since the last sync.
+                    let skip_wal_sync =
+                        sync_mode == crate::SyncMode::Off || !wal.has_unsynced_frames();
+                    self.checkpoint_state.write().phase = if skip_wal_sync {
+                        CheckpointPhase::Checkpoint {
+                            mode,
+                            sync_mode,
+                            clear_page_cache,
+                        }
+                    } else {
+                        CheckpointPhase::SyncWal {
+                            mode,
+                            sync_mode,
+                            clear_page_cache,
+                        }
+                    };
+                }
+                CheckpointPhase::SyncWal {
+                    mode,
+                    sync_mode,
+                    clear_page_cache,
+                } => {
+                    let pending = self.checkpoint_state.read().wal_sync.clone();
+                    let c = match pending {
+                        Some(c) => c,
+                        None => {
+                            let c = wal.sync()?;
+                            self.checkpoint_state.write().wal_sync = Some(c.clone());
+                            c
+                        }
+                    };
+                    if !c.finished() {
+                        io_yield_one!(c);
+                    }
+                    let mut state = self.checkpoint_state.write();
+                    state.wal_sync = None;
+                    if !c.succeeded() {
+                        if !data_sync_retry {
+                            panic!("fsync error (data_sync_retry=off): {:?}", c.get_error());
+                        }
+                        // Nothing was backfilled yet, the next checkpoint starts over.
+                        state.phase = CheckpointPhase::NotCheckpointing;
+                        return Err(std::io::Error::other("WAL fsync failed").into());
+                    }
+                    state.phase = CheckpointPhase::Checkpoint {
                         mode,
                         sync_mode,
                         clear_page_cache,
@@ -3543,6 +3890,7 @@ impl Pager {
         &self,
         wal_auto_checkpoint_disabled: bool,
         sync_mode: crate::SyncMode,
+        data_sync_retry: bool,
     ) -> Result<()> {
         let mut attempts = 0;
         {
@@ -3561,6 +3909,7 @@ impl Pager {
                     upper_bound_inclusive: None,
                 },
                 sync_mode,
+                data_sync_retry,
             ) {
                 if attempts == 3 {
                     // don't return error on `close` if we are unable to checkpoint, we can silently fail
@@ -3585,8 +3934,10 @@ impl Pager {
         &self,
         mode: CheckpointMode,
         sync_mode: crate::SyncMode,
+        data_sync_retry: bool,
     ) -> Result<CheckpointResult> {
-        self.io.block(|| self.checkpoint(mode, sync_mode, true))
+        self.io
+            .block(|| self.checkpoint(mode, sync_mode, data_sync_retry, true))
     }
 
     pub fn freepage_list(&self) -> u32 {
@@ -3739,10 +4090,14 @@ impl Pager {
                 default_header.database_size = 1.into();
 
                 // based on the IOContext set, we will set the reserved space bytes as required by
-                // either the encryption or checksum, or None if they are not set.
+                // either the encryption or checksum, or None if they are not set. Space reserved
+                // explicitly before the database was created (e.g. for a codec) is kept as long
+                // as it is enough for the IOContext.
                 let reserved_space_bytes = {
                     let io_ctx = self.io_ctx.read();
-                    io_ctx.get_reserved_space_bytes()
+                    let required = io_ctx.get_reserved_space_bytes();
+                    self.get_reserved_space()
+                        .map_or(required, |reserved| reserved.max(required))
                 };
                 default_header.reserved_space = reserved_space_bytes;
                 self.set_reserved_space(reserved_space_bytes);
@@ -4106,7 +4461,10 @@ impl Pager {
         // Invalidate cached schema cookie since rollback may have restored the database schema cookie
         self.set_schema_cookie(None);
         if schema_did_change {
-            *connection.schema.write() = connection.db.clone_schema();
+            // If the namespace was dropped meanwhile, the next statement fails to reprepare.
+            if let Ok(schema) = connection.committed_schema() {
+                *connection.schema.write() = schema;
+            }
         }
         if is_write {
             if let Some(wal) = self.wal.as_ref() {
@@ -4482,6 +4840,79 @@ mod tests {
         let page = cache.get(&page_key).unwrap();
         assert_eq!(page.unwrap().get().id, 1);
     }
+
+    #[test]
+    #[cfg(all(feature = "fs", not(target_family = "wasm")))]
+    fn test_page_size_on_disk() {
+        use crate::{Database, DatabaseOpts, OpenFlags, PlatformIO, Value, IO};
+
+        for page_size in [512u32, 65536] {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("test.db");
+            let path = path.to_str().unwrap();
+            {
+                let io: Arc<dyn IO> = Arc::new(PlatformIO::new().unwrap());
+                let db = Database::open_file_with_flags(
+                    io,
+                    path,
+                    OpenFlags::default(),
+                    DatabaseOpts::new().with_page_size(page_size),
+                    None,
+                )
+                .unwrap();
+                let conn = db.connect().unwrap();
+                conn.execute("CREATE TABLE t (x)").unwrap();
+                for i in 0..100 {
+                    conn.execute(format!("INSERT INTO t VALUES (randomblob(({i} + 1) * 10))"))
+                        .unwrap();
+                }
+                conn.execute("PRAGMA wal_checkpoint(TRUNCATE)").unwrap();
+                conn.close().unwrap();
+            }
+
+            let file = std::fs::read(path).unwrap();
+            assert!(!file.is_empty());
+            assert_eq!(file.len() % page_size as usize, 0);
+            // A page size of 65536 is stored as 1 in the two bytes of the header.
+            let header_page_size = u16::from_be_bytes([file[16], file[17]]);
+            assert_eq!(
+                header_page_size,
+                if page_size == 65536 {
+                    1
+                } else {
+                    page_size as u16
+                }
+            );
+
+            // The page size is read back from the file, whatever the options ask for.
+            let io: Arc<dyn IO> = Arc::new(PlatformIO::new().unwrap());
+            let db = Database::open_file(io, path).unwrap();
+            let conn = db.connect().unwrap();
+            let rows = conn
+                .prepare("PRAGMA page_size")
+                .unwrap()
+                .run_collect_rows()
+                .unwrap();
+            assert_eq!(rows, vec![vec![Value::Integer(page_size as i64)]]);
+            let rows = conn
+                .prepare("SELECT count(*), sum(length(x)) FROM t")
+                .unwrap()
+                .run_collect_rows()
+                .unwrap();
+            assert_eq!(rows, vec![vec![Value::Integer(100), Value::Integer(50500)]]);
+            let rows = conn
+                .prepare("PRAGMA page_count")
+                .unwrap()
+                .run_collect_rows()
+                .unwrap();
+            assert_eq!(
+                rows,
+                vec![vec![Value::Integer(
+                    (file.len() / page_size as usize) as i64
+                )]]
+            );
+        }
+    }
 }
 
 #[cfg(test)]
@@ -519,7 +519,7 @@ pub struct OverflowCell {
 
 /// Send read request for DB page read to the IO
 /// if allow_empty_read is set, than empty read will be raise error for the page, but will not panic
-#[instrument(skip_all, level = Level::DEBUG)]
+#[instrument(skip_all, fields(page_idx = page_idx), level = Level::DEBUG)]
 pub fn begin_read_page(
     db_file: &dyn DatabaseStorage,
     buffer_pool: Arc<BufferPool>,
@@ -564,9 +564,11 @@ pub fn begin_read_page(
     db_file.read_page(page_idx, io_ctx, c)
 }
 
-#[instrument(skip_all, level = Level::DEBUG)]
+#[instrument(skip_all, fields(page_idx = page_idx), level = Level::DEBUG)]
 pub fn finish_read_page(page_idx: usize, buffer: Arc<Buffer>, page: PageRef) {
     tracing::trace!("finish_read_page(page_idx = {page_idx})");
+    #[cfg(feature = "query_tracing")]
+    tracing::debug!(page_idx, bytes = buffer.len(), "page read");
     {
         let inner = page.get();
         inner.buffer = Some(buffer);
@@ -578,7 +580,7 @@ pub fn finish_read_page(page_idx: usize, buffer: Arc<Buffer>, page: PageRef) {
     }
 }
 
-#[instrument(skip_all, level = Level::DEBUG)]
+#[instrument(skip_all, fields(page_id = page.get().id), level = Level::DEBUG)]
 pub fn begin_write_btree_page(pager: &Pager, page: &PageRef) -> Result<Completion> {
     tracing::trace!("begin_write_btree_page(page={})", page.get().id);
     let page_source = &pager.db_file;
@@ -596,6 +598,8 @@ pub fn begin_write_btree_page(pager: &Pager, page: &PageRef) -> Result<Completio
                 return;
             };
             tracing::trace!("finish_write_btree_page");
+            #[cfg(feature = "query_tracing")]
+            tracing::debug!(page_id, bytes_written, "page written");
 
             page_finish.clear_dirty();
             turso_assert!(
@@ -1266,6 +1270,8 @@ pub fn build_shared_wal(
         checkpoint_lock: TursoRwLock::new(),
         initialized: AtomicBool::new(false),
         epoch: AtomicU32::new(0),
+        // Frames left by an earlier process may not have been synced.
+        unsynced_frames: Arc::new(AtomicBool::new(true)),
     }));
 
     if size < WAL_HEADER_SIZE as u64 {
@@ -1414,33 +1420,31 @@ impl StreamingWalReader {
             return;
         }
 
-        let (page_sz, c1, c2, use_native, ok) = {
-            let mut h = self.header.lock();
-            let s = buf.as_slice();
-            h.magic = u32::from_be_bytes(s[0..4].try_into().unwrap());
-            h.file_format = u32::from_be_bytes(s[4..8].try_into().unwrap());
-            h.page_size = u32::from_be_bytes(s[8..12].try_into().unwrap());
-            h.checkpoint_seq = u32::from_be_bytes(s[12..16].try_into().unwrap());
-            h.salt_1 = u32::from_be_bytes(s[16..20].try_into().unwrap());
-            h.salt_2 = u32::from_be_bytes(s[20..24].try_into().unwrap());
-            h.checksum_1 = u32::from_be_bytes(s[24..28].try_into().unwrap());
-            h.checksum_2 = u32::from_be_bytes(s[28..32].try_into().unwrap());
-            tracing::debug!("WAL header: {:?}", *h);
-
-            let use_native = cfg!(target_endian = "big") == ((h.magic & 1) != 0);
-            let calc = checksum_wal(&s[0..24], &h, (0, 0), use_native);
-            (
-                h.page_size,
-                h.checksum_1,
-                h.checksum_2,
-                use_native,
-                calc == (h.checksum_1, h.checksum_2),
-            )
+        let s = buf.as_slice();
+        let h = WalHeader {
+            magic: u32::from_be_bytes(s[0..4].try_into().unwrap()),
+            file_format: u32::from_be_bytes(s[4..8].try_into().unwrap()),
+            page_size: u32::from_be_bytes(s[8..12].try_into().unwrap()),
+            checkpoint_seq: u32::from_be_bytes(s[12..16].try_into().unwrap()),
+            salt_1: u32::from_be_bytes(s[16..20].try_into().unwrap()),
+            salt_2: u32::from_be_bytes(s[20..24].try_into().unwrap()),
+            checksum_1: u32::from_be_bytes(s[24..28].try_into().unwrap()),
+            checksum_2: u32::from_be_bytes(s[28..32].try_into().unwrap()),
         };
-        if PageSize::new(page_sz).is_none() || !ok {
+        tracing::debug!("WAL header: {:?}", h);
+
+        let use_native = cfg!(target_endian = "big") == ((h.magic & 1) != 0);
+        let calc = checksum_wal(&s[0..24], &h, (0, 0), use_native);
+        if PageSize::new(h.page_size).is_none() || calc != (h.checksum_1, h.checksum_2) {
+            // A header that was torn by a crash leaves the WAL empty. Its fields must not be
+            // reused, so the next writer starts a new WAL with a fresh header and salts, which
+            // the frames left in the file can't match.
+            tracing::debug!("WAL header is invalid, ignoring the frames of the WAL");
             self.finalize_loading();
             return;
         }
+        let (page_sz, c1, c2) = (h.page_size, h.checksum_1, h.checksum_2);
+        *self.header.lock() = h;
         {
             let mut st = self.state.write();
             st.page_size = page_sz as usize;
@@ -2110,4 +2114,100 @@ mod tests {
         assert_eq!(frame_cache.get(&1), Some(&vec![1u64]));
         assert!(frame_cache.get(&2).is_none());
     }
+
+    #[test]
+    fn streaming_reader_discards_torn_writes() {
+        let io: Arc<dyn crate::IO> = Arc::new(crate::MemoryIO::new());
+        let file = io
+            .open_file("torn-wal", crate::OpenFlags::Create, false)
+            .unwrap();
+
+        let page_size: usize = 1024;
+        let buffer_pool = BufferPool::begin_init(&io, BufferPool::TEST_ARENA_SIZE);
+        buffer_pool
+            .finalize_with_page_size(page_size)
+            .expect("initialize buffer pool");
+
+        let mut wal_header = WalHeader {
+            magic: WAL_MAGIC_LE,
+            file_format: 3007000,
+            page_size: page_size as u32,
+            checkpoint_seq: 0,
+            salt_1: 0x1234_5678,
+            salt_2: 0x9abc_def0,
+            checksum_1: 0,
+            checksum_2: 0,
+        };
+        let header_prefix = &wal_header.as_bytes()[..WAL_HEADER_SIZE - 8];
+        let use_native = (wal_header.magic & 1) != 0;
+        let (c1, c2) = checksum_wal(header_prefix, &wal_header, (0, 0), use_native);
+        wal_header.checksum_1 = c1;
+        wal_header.checksum_2 = c2;
+        io.wait_for_completion(begin_write_wal_header(file.as_ref(), &wal_header).unwrap())
+            .unwrap();
+
+        let frame_size = WAL_FRAME_HEADER_SIZE + page_size;
+        let write = |offset: usize, data: Vec<u8>| {
+            let c = file
+                .pwrite(
+                    offset as u64,
+                    Arc::new(Buffer::new(data)),
+                    Completion::new_write(|_| {}),
+                )
+                .unwrap();
+            io.wait_for_completion(c).unwrap();
+        };
+        // Every frame commits a transaction.
+        let mut checksum = (wal_header.checksum_1, wal_header.checksum_2);
+        let mut frames = Vec::new();
+        for page_number in 1..=3u32 {
+            let page = vec![page_number as u8; page_size];
+            let (next_checksum, frame) = prepare_wal_frame(
+                &buffer_pool,
+                &wal_header,
+                checksum,
+                wal_header.page_size,
+                page_number,
+                page_number,
+                &page,
+            );
+            checksum = next_checksum;
+            frames.push((next_checksum, frame.as_slice().to_vec()));
+        }
+        for (i, (_, frame)) in frames.iter().enumerate() {
+            write(WAL_HEADER_SIZE + i * frame_size, frame.clone());
+        }
+        let max_frame = || {
+            let shared = build_shared_wal(&file, &io).unwrap();
+            let guard = shared.read();
+            (
+                guard.max_frame.load(Ordering::Acquire),
+                guard.last_checksum,
+                guard.page_size(),
+            )
+        };
+        assert_eq!(max_frame(), (3, frames[2].0, page_size as u32));
+
+        // A frame that was partially written at the end of the WAL.
+        write(
+            WAL_HEADER_SIZE + 3 * frame_size,
+            frames[2].1[..frame_size / 2].to_vec(),
+        );
+        assert_eq!(max_frame(), (3, frames[2].0, page_size as u32));
+
+        // A frame whose page was only partially overwritten, and the frames after it.
+        let mut torn = frames[1].1.clone();
+        torn[frame_size - 100..].fill(0);
+        write(WAL_HEADER_SIZE + frame_size, torn);
+        assert_eq!(max_frame(), (1, frames[0].0, page_size as u32));
+
+        // A torn header invalidates the whole WAL, and none of its fields are kept.
+        write(8, vec![0xff; 4]);
+        assert_eq!(max_frame(), (0, (0, 0), 0));
+        let shared = build_shared_wal(&file, &io).unwrap();
+        let guard = shared.read();
+        assert!(!guard.is_initialized().unwrap());
+        let header = *guard.wal_header.lock();
+        assert_eq!((header.salt_1, header.salt_2), (0, 0));
+    }
 }
@@ -128,6 +128,14 @@ pub struct TursoRwLock(AtomicU64);
 pub const READMARK_NOT_USED: u32 = 0xffffffff;
 const NO_LOCK_HELD: usize = usize::MAX;
 
+/// Number of frames the WAL may hold beyond the last backfilled frame before a commit triggers
+/// an automatic checkpoint. Same default as SQLite's `wal_autocheckpoint`.
+pub const DEFAULT_WAL_AUTOCHECKPOINT: u32 = 1000;
+
+/// Called after every committed write transaction with the name of the database and the number
+/// of frames currently in its WAL. Set with [crate::Connection::set_wal_hook].
+pub type WalHookCallback = Box<dyn Fn(&str, u64) + Send + Sync>;
+
 impl TursoRwLock {
     /// Bit 0: Writer flag
     const WRITER: u64 = 0b1;
@@ -252,6 +260,14 @@ pub trait Wal: Debug + Send + Sync {
     /// Returns whether the database state has changed since the last read transaction.
     fn begin_read_tx(&self) -> Result<bool>;
 
+    /// The frames seen by the current read transaction, or `None` outside of a read transaction.
+    fn read_point(&self) -> Option<ReadPoint>;
+
+    /// Begin a read transaction that sees the same frames as the read transaction that returned
+    /// `point` from [Wal::read_point], which must still be open.
+    /// Returns whether the database state has changed since the last read transaction.
+    fn begin_read_tx_at(&self, point: &ReadPoint) -> Result<bool>;
+
     /// Begin a write transaction.
     fn begin_write_tx(&self) -> Result<()>;
 
@@ -324,11 +340,16 @@ pub trait Wal: Debug + Send + Sync {
     /// all changes were stored locally.
     fn finish_append_frames_commit(&self) -> Result<()>;
 
-    fn should_checkpoint(&self) -> bool;
+    /// Whether more than `threshold` frames have been appended since the last backfilled frame.
+    fn should_checkpoint(&self, threshold: u64) -> bool;
     fn checkpoint(&self, pager: &Pager, mode: CheckpointMode)
         -> Result<IOResult<CheckpointResult>>;
     fn sync(&self) -> Result<Completion>;
     fn is_syncing(&self) -> bool;
+    /// Record that frames were written to the WAL without syncing it.
+    fn mark_unsynced_frames(&self);
+    /// Whether the WAL may hold frames that no sync covered yet.
+    fn has_unsynced_frames(&self) -> bool;
     fn get_max_frame_in_wal(&self) -> u64;
     fn get_checkpoint_seq(&self) -> u32;
     fn get_max_frame(&self) -> u64;
@@ -625,7 +646,6 @@ pub struct WalFile {
 
     shared: Arc<RwLock<WalFileShared>>,
     ongoing_checkpoint: RwLock<OngoingCheckpoint>,
-    checkpoint_threshold: usize,
     // min and max frames for this connection
     /// This is the index to the read_lock in WalFileShared that we are holding. This lock contains
     /// the max frame for this connection.
@@ -655,7 +675,6 @@ impl fmt::Debug for WalFile {
             .field("page_size", &self.page_size())
             .field("shared", &self.shared)
             .field("ongoing_checkpoint", &*self.ongoing_checkpoint.read())
-            .field("checkpoint_threshold", &self.checkpoint_threshold)
             .field("max_frame_read_lock_index", &self.max_frame_read_lock_index)
             .field("max_frame", &self.max_frame)
             .field("min_frame", &self.min_frame)
@@ -756,6 +775,10 @@ pub struct WalFileShared {
     /// Increments on each checkpoint, used to prevent stale cached pages being used for
     /// backfilling.
     pub epoch: AtomicU32,
+    /// Set when frames are written without a sync, by commits in NORMAL or OFF mode, commits
+    /// waiting for a group sync and raw frame inserts. Cleared when a WAL sync is submitted and
+    /// set again if that sync fails.
+    pub unsynced_frames: Arc<AtomicBool>,
 }
 
 impl fmt::Debug for WalFileShared {
@@ -862,6 +885,17 @@ impl Drop for CheckpointLocks {
     }
 }
 
+/// The frames of the WAL seen by a read transaction, see [Wal::read_point].
+#[derive(Debug, Clone, Copy)]
+pub struct ReadPoint {
+    read_lock_index: usize,
+    min_frame: u64,
+    max_frame: u64,
+    last_checksum: (u32, u32),
+    checkpoint_seq: u32,
+    transaction_count: u64,
+}
+
 /// Result of try_begin_read_tx - either success or a retriable condition.
 enum TryBeginReadResult {
     /// Successfully started read transaction, returns whether DB changed
@@ -1078,6 +1112,58 @@ impl Wal for WalFile {
         }
     }
 
+    fn read_point(&self) -> Option<ReadPoint> {
+        let read_lock_index = self.max_frame_read_lock_index.load(Ordering::Acquire);
+        if read_lock_index == NO_LOCK_HELD {
+            return None;
+        }
+        Some(ReadPoint {
+            read_lock_index,
+            min_frame: self.min_frame.load(Ordering::Acquire),
+            max_frame: self.max_frame.load(Ordering::Acquire),
+            last_checksum: *self.last_checksum.read(),
+            checkpoint_seq: self.checkpoint_seq.load(Ordering::Acquire),
+            transaction_count: self.transaction_count.load(Ordering::Acquire),
+        })
+    }
+
+    #[instrument(skip_all, level = Level::DEBUG)]
+    fn begin_read_tx_at(&self, point: &ReadPoint) -> Result<bool> {
+        turso_assert!(
+            self.max_frame_read_lock_index
+                .load(Ordering::Acquire)
+                .eq(&NO_LOCK_HELD),
+            "cannot start a new read tx without ending an existing one"
+        );
+        // The reader we join still holds a shared lock on its slot, so the slot can't be bumped,
+        // checkpoints can't backfill past its mark and the WAL can't be restarted, which keeps
+        // every frame of the read point in place.
+        if !self.with_shared(|shared| shared.read_locks[point.read_lock_index].read()) {
+            return Err(LimboError::Busy);
+        }
+        let changed = self.max_frame.load(Ordering::Acquire) != point.max_frame
+            || self.min_frame.load(Ordering::Acquire) != point.min_frame
+            || *self.last_checksum.read() != point.last_checksum
+            || self.checkpoint_seq.load(Ordering::Acquire) != point.checkpoint_seq
+            || self.transaction_count.load(Ordering::Acquire) != point.transaction_count;
+        self.min_frame.store(point.min_frame, Ordering::Release);
+        self.max_frame.store(point.max_frame, Ordering::Release);
+        self.max_frame_read_lock_index
+            .store(point.read_lock_index, Ordering::Release);
+        *self.last_checksum.write() = point.last_checksum;
+        self.checkpoint_seq
+            .store(point.checkpoint_seq, Ordering::Release);
+        self.transaction_count
+            .store(point.transaction_count, Ordering::Release);
+        tracing::debug!(
+            "begin_read_tx_at(min={}, max={}, slot={})",
+            point.min_frame,
+            point.max_frame,
+            point.read_lock_index
+        );
+        Ok(changed)
+    }
+
     /// End a read transaction.
     #[inline(always)]
     #[instrument(skip_all, level = Level::DEBUG)]
@@ -1424,6 +1510,7 @@ impl Wal for WalFile {
         let c = Completion::new_write(|_| {});
         let c = file.pwrite(offset, frame_bytes, c)?;
         self.io.wait_for_completion(c)?;
+        self.mark_unsynced_frames();
         self.complete_append_frame(page_id, frame_id, checksums);
         if db_size > 0 {
             self.finish_append_frames_commit()?;
@@ -1432,11 +1519,11 @@ impl Wal for WalFile {
     }
 
     #[instrument(skip_all, level = Level::DEBUG)]
-    fn should_checkpoint(&self) -> bool {
+    fn should_checkpoint(&self, threshold: u64) -> bool {
         self.with_shared(|shared| {
-            let frame_id = shared.max_frame.load(Ordering::Acquire) as usize;
-            let nbackfills = shared.nbackfills.load(Ordering::Acquire) as usize;
-            frame_id > self.checkpoint_threshold + nbackfills
+            let frame_id = shared.max_frame.load(Ordering::Acquire);
+            let nbackfills = shared.nbackfills.load(Ordering::Acquire);
+            frame_id > threshold + nbackfills
         })
     }
 
@@ -1457,14 +1544,23 @@ impl Wal for WalFile {
     fn sync(&self) -> Result<Completion> {
         tracing::debug!("wal_sync");
         let syncing = self.syncing.clone();
-        let completion = Completion::new_sync(move |_| {
+        let (file, unsynced_frames) = self.with_shared(|shared| {
+            assert!(shared.enabled.load(Ordering::SeqCst), "WAL must be enabled");
+            (
+                shared.file.as_ref().unwrap().clone(),
+                shared.unsynced_frames.clone(),
+            )
+        });
+        // The sync covers every frame written so far, frames written after it is submitted
+        // set the flag again.
+        unsynced_frames.store(false, Ordering::SeqCst);
+        let completion = Completion::new_sync(move |res| {
             tracing::debug!("wal_sync finish");
+            if res.is_err() {
+                unsynced_frames.store(true, Ordering::SeqCst);
+            }
             syncing.store(false, Ordering::SeqCst);
         });
-        let file = self.with_shared(|shared| {
-            assert!(shared.enabled.load(Ordering::SeqCst), "WAL must be enabled");
-            shared.file.as_ref().unwrap().clone()
-        });
         self.syncing.store(true, Ordering::SeqCst);
         let c = file.sync(completion)?;
         Ok(c)
@@ -1475,6 +1571,14 @@ impl Wal for WalFile {
         self.syncing.load(Ordering::SeqCst)
     }
 
+    fn mark_unsynced_frames(&self) {
+        self.with_shared(|shared| shared.unsynced_frames.store(true, Ordering::SeqCst));
+    }
+
+    fn has_unsynced_frames(&self) -> bool {
+        self.with_shared(|shared| shared.unsynced_frames.load(Ordering::SeqCst))
+    }
+
     fn get_max_frame_in_wal(&self) -> u64 {
         self.with_shared(|shared| shared.max_frame.load(Ordering::Acquire))
     }
@@ -1918,7 +2022,6 @@ impl WalFile {
                 pages_to_checkpoint: Vec::new(),
                 inflight_reads: Vec::with_capacity(MAX_INFLIGHT_READS),
             }),
-            checkpoint_threshold: 1000,
             buffer_pool,
             checkpoint_seq: AtomicU32::new(0),
             syncing: Arc::new(AtomicBool::new(false)),
@@ -2710,6 +2813,7 @@ impl WalFileShared {
             loaded: AtomicBool::new(true),
             initialized: AtomicBool::new(false),
             epoch: AtomicU32::new(0),
+            unsynced_frames: Arc::new(AtomicBool::new(false)),
         };
         Arc::new(RwLock::new(shared))
     }
@@ -2742,6 +2846,7 @@ impl WalFileShared {
             loaded: AtomicBool::new(true),
             initialized: AtomicBool::new(false),
             epoch: AtomicU32::new(0),
+            unsynced_frames: Arc::new(AtomicBool::new(false)),
         };
         Ok(Arc::new(RwLock::new(shared)))
     }
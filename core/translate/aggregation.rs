@@ -27,13 +27,26 @@ pub fn emit_ungrouped_aggregation<'a>(
 ) -> Result<()> {
     let agg_start_reg = t_ctx.reg_agg_start.unwrap();
 
-    for (i, agg) in plan.aggregates.iter().enumerate() {
-        let agg_result_reg = agg_start_reg + i;
-        program.emit_insn(Insn::AggFinal {
-            register: agg_result_reg,
-            func: agg.func.clone(),
+    // Jump to the end if LIMIT 0, since there is no loop that the LIMIT check could skip.
+    let end_label = program.allocate_label();
+    if let Some(limit_ctx) = t_ctx.limit_ctx {
+        program.emit_insn(Insn::IfNot {
+            reg: limit_ctx.reg_limit,
+            target_pc: end_label,
+            jump_if_null: false,
         });
     }
+
+    // The count of a simple count query is final already, see [super::select::emit_simple_count].
+    if !plan.is_simple_count() {
+        for (i, agg) in plan.aggregates.iter().enumerate() {
+            let agg_result_reg = agg_start_reg + i;
+            program.emit_insn(Insn::AggFinal {
+                register: agg_result_reg,
+                func: agg.func.clone(),
+            });
+        }
+    }
     // we now have the agg results in (agg_start_reg..agg_start_reg + aggregates.len() - 1)
     // we need to call translate_expr on each result column, but replace the expr with a register copy in case any part of the
     // result column expression matches a) a group by column or b) an aggregation result.
@@ -45,9 +58,6 @@ pub fn emit_ungrouped_aggregation<'a>(
     }
     t_ctx.resolver.enable_expr_to_reg_cache();
 
-    // Allocate a label for the end (used by both HAVING and OFFSET to skip row emission)
-    let end_label = program.allocate_label();
-
     // Handle HAVING clause without GROUP BY for ungrouped aggregation
     if let Some(group_by) = &plan.group_by {
         if group_by.exprs.is_empty() {
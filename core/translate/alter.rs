@@ -31,11 +31,17 @@ pub fn translate_alter_table(
     connection: &Arc<crate::Connection>,
     input: &str,
 ) -> Result<ProgramBuilder> {
-    program.begin_write_operation();
     let ast::AlterTable {
         name: table_name,
         body: alter_table,
     } = alter;
+    let database_id = connection.resolve_database_id(&table_name)?;
+    // The generated statements that rewrite the schema table name its database, because an
+    // unqualified sqlite_schema is always the one of the main database.
+    let schema_table = format!(
+        "\"{}\".{SQLITE_TABLEID}",
+        connection.database_name(database_id)
+    );
     let table_name = table_name.name.as_str();
 
     // Check if someone is trying to ALTER a system table
@@ -86,6 +92,17 @@ pub fn translate_alter_table(
         )));
     }
 
+    // The rows of a WITHOUT ROWID table live in its primary key index, whose layout depends on
+    // the columns of the table.
+    if !original_btree.has_rowid
+        && matches!(
+            alter_table,
+            ast::AlterTableBody::AddColumn(_) | ast::AlterTableBody::DropColumn(_)
+        )
+    {
+        crate::bail_parse_error!("cannot add or drop columns of WITHOUT ROWID table {table_name}");
+    }
+
     let mut btree = (*original_btree).clone();
 
     Ok(match alter_table {
@@ -225,7 +242,7 @@ pub fn translate_alter_table(
 
             let stmt = format!(
                 r#"
-                    UPDATE {SQLITE_TABLEID}
+                    UPDATE {schema_table}
                     SET sql = '{sql}'
                     WHERE name = '{table_name}' COLLATE NOCASE AND type = 'table'
                 "#,
@@ -257,7 +274,7 @@ pub fn translate_alter_table(
                     program.emit_insn(Insn::OpenWrite {
                         cursor_id,
                         root_page: RegisterOrLiteral::Literal(root_page),
-                        db: 0,
+                        db: database_id,
                     });
 
                     program.cursor_loop(cursor_id, |program, rowid| {
@@ -301,13 +318,14 @@ pub fn translate_alter_table(
                     });
 
                     program.emit_insn(Insn::SetCookie {
-                        db: 0,
+                        db: database_id,
                         cookie: Cookie::SchemaVersion,
                         value: resolver.schema.schema_version as i32 + 1,
                         p5: 0,
                     });
 
                     program.emit_insn(Insn::DropColumn {
+                        db: database_id,
                         table: table_name,
                         column_index: dropped_index,
                     })
@@ -417,7 +435,7 @@ pub fn translate_alter_table(
 
             let stmt = format!(
                 r#"
-                    UPDATE {SQLITE_TABLEID}
+                    UPDATE {schema_table}
                     SET sql = '{escaped}'
                     WHERE name = '{table_name}' COLLATE NOCASE AND type = 'table'
                 "#,
@@ -441,12 +459,13 @@ pub fn translate_alter_table(
                 input,
                 |program| {
                     program.emit_insn(Insn::SetCookie {
-                        db: 0,
+                        db: database_id,
                         cookie: Cookie::SchemaVersion,
                         value: resolver.schema.schema_version as i32 + 1,
                         p5: 0,
                     });
                     program.emit_insn(Insn::AddColumn {
+                        db: database_id,
                         table: table_name.to_owned(),
                         column: Box::new(column),
                     });
@@ -484,7 +503,7 @@ pub fn translate_alter_table(
             program.emit_insn(Insn::OpenWrite {
                 cursor_id,
                 root_page: RegisterOrLiteral::Literal(sqlite_schema.root_page),
-                db: 0,
+                db: database_id,
             });
 
             program.cursor_loop(cursor_id, |program, rowid| {
@@ -535,13 +554,14 @@ pub fn translate_alter_table(
             });
 
             program.emit_insn(Insn::SetCookie {
-                db: 0,
+                db: database_id,
                 cookie: Cookie::SchemaVersion,
                 value: resolver.schema.schema_version as i32 + 1,
                 p5: 0,
             });
 
             program.emit_insn(Insn::RenameTable {
+                db: database_id,
                 from: table_name.to_owned(),
                 to: new_name.to_owned(),
             });
@@ -751,7 +771,7 @@ pub fn translate_alter_table(
             program.emit_insn(Insn::OpenWrite {
                 cursor_id,
                 root_page: RegisterOrLiteral::Literal(sqlite_schema.root_page),
-                db: 0,
+                db: database_id,
             });
 
             program.cursor_loop(cursor_id, |program, rowid| {
@@ -813,7 +833,7 @@ pub fn translate_alter_table(
                 let escaped_sql = new_sql.replace('\'', "''");
                 let update_stmt = format!(
                     r#"
-                        UPDATE {SQLITE_TABLEID}
+                        UPDATE {schema_table}
                         SET sql = '{escaped_sql}'
                         WHERE name = '{trigger_name}' COLLATE NOCASE AND type = 'trigger'
                     "#,
@@ -842,12 +862,13 @@ pub fn translate_alter_table(
             }
 
             program.emit_insn(Insn::SetCookie {
-                db: 0,
+                db: database_id,
                 cookie: Cookie::SchemaVersion,
                 value: resolver.schema.schema_version as i32 + 1,
                 p5: 0,
             });
             program.emit_insn(Insn::AlterColumn {
+                db: database_id,
                 table: table_name.to_owned(),
                 column_index,
                 definition: Box::new(definition),
@@ -946,6 +967,7 @@ fn translate_rename_virtual_table(
     });
 
     program.emit_insn(Insn::RenameTable {
+        db: 0,
         from: old_name.to_owned(),
         to: new_name_norm,
     });
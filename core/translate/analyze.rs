@@ -142,7 +142,7 @@ pub fn translate_analyze(
             program.alloc_cursor_id(CursorType::BTreeTable(table.clone()));
         program.emit_insn(Insn::OpenWrite {
             cursor_id: sqlite_schema_cursor_id,
-            root_page: 1i64.into(),
+            root_page: table.root_page.into(),
             db: 0,
         });
 
@@ -162,7 +162,7 @@ pub fn translate_analyze(
         let parse_schema_where_clause =
             "tbl_name = 'sqlite_stat1' AND type != 'trigger'".to_string();
         program.emit_insn(Insn::ParseSchema {
-            db: sqlite_schema_cursor_id,
+            db: 0,
             where_clause: Some(parse_schema_where_clause),
         });
 
@@ -1,5 +1,6 @@
-use std::{cmp::Ordering, str::FromStr as _};
+use std::{cmp::Ordering, fmt, str::FromStr, sync::Arc};
 
+use parking_lot::RwLock;
 use turso_parser::ast::Expr;
 
 use crate::{
@@ -7,25 +8,32 @@ use crate::{
         expr::{walk_expr, WalkControl},
         plan::TableReferences,
     },
-    Result,
+    LimboError, Result,
 };
 
-// TODO: in the future allow user to define collation sequences
-// Will have to meddle with ffi for this
-#[derive(
-    Debug, Clone, Copy, Eq, PartialEq, strum_macros::Display, strum_macros::EnumString, Default,
-)]
-#[strum(ascii_case_insensitive)]
-/// **Pre defined collation sequences**\
+/// Compares two strings for a collating sequence registered with [CollationSeq::register].
+pub type CollationFn = Arc<dyn Fn(&str, &str) -> Ordering + Send + Sync>;
+
+/// Collating sequences registered by extensions, in the order of their [CollationSeq::Custom]
+/// ids. The registry is shared by every database of the process, because the collation of a
+/// column is resolved whenever a schema is parsed, without a connection at hand.
+static CUSTOM_COLLATIONS: RwLock<Vec<(String, CollationFn)>> = RwLock::new(Vec::new());
+
+/// Number of bits used by the built-in collations in [CollationSeq::to_bits].
+const CUSTOM_BITS_START: u8 = 4;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+/// **Collation sequences**\
 /// Collating functions only matter when comparing string values.
 /// Numeric values are always compared numerically, and BLOBs are always compared byte-by-byte using memcmp().
-#[repr(u8)]
 pub enum CollationSeq {
-    Unset = 0,
+    Unset,
     #[default]
-    Binary = 1,
-    NoCase = 2,
-    Rtrim = 3,
+    Binary,
+    NoCase,
+    Rtrim,
+    /// A collation registered with [CollationSeq::register], by its position in the registry.
+    Custom(u8),
 }
 
 impl CollationSeq {
@@ -34,12 +42,57 @@ impl CollationSeq {
             crate::LimboError::ParseError(format!("no such collation sequence: {collation}"))
         })
     }
+
+    /// Registers a collating sequence that can be used with `COLLATE name`. Registering a name
+    /// again replaces its comparison function. The built-in collations can't be replaced.
+    pub fn register(name: &str, compare: CollationFn) -> Result<Self> {
+        if matches!(
+            Self::from_str(name),
+            Ok(CollationSeq::Unset
+                | CollationSeq::Binary
+                | CollationSeq::NoCase
+                | CollationSeq::Rtrim)
+        ) {
+            return Err(LimboError::InvalidArgument(format!(
+                "cannot replace built-in collation sequence: {name}"
+            )));
+        }
+        let mut collations = CUSTOM_COLLATIONS.write();
+        if let Some(id) = collations
+            .iter()
+            .position(|(existing, _)| existing.eq_ignore_ascii_case(name))
+        {
+            collations[id].1 = compare;
+            return Ok(CollationSeq::Custom(id as u8));
+        }
+        let id = collations.len();
+        if id > (u8::MAX - CUSTOM_BITS_START) as usize {
+            return Err(LimboError::InvalidArgument(format!(
+                "too many collation sequences, cannot register {name}"
+            )));
+        }
+        collations.push((name.to_string(), compare));
+        Ok(CollationSeq::Custom(id as u8))
+    }
+
+    #[inline]
+    pub const fn to_bits(self) -> u8 {
+        match self {
+            CollationSeq::Unset => 0,
+            CollationSeq::Binary => 1,
+            CollationSeq::NoCase => 2,
+            CollationSeq::Rtrim => 3,
+            CollationSeq::Custom(id) => CUSTOM_BITS_START + id,
+        }
+    }
+
     #[inline]
     /// Returns the collation, defaulting to BINARY if unset
     pub const fn from_bits(bits: u8) -> Self {
         match bits {
             2 => CollationSeq::NoCase,
             3 => CollationSeq::Rtrim,
+            bits if bits >= CUSTOM_BITS_START => CollationSeq::Custom(bits - CUSTOM_BITS_START),
             _ => CollationSeq::Binary,
         }
     }
@@ -50,6 +103,18 @@ impl CollationSeq {
             CollationSeq::Unset | CollationSeq::Binary => Self::binary_cmp(lhs, rhs),
             CollationSeq::NoCase => Self::nocase_cmp(lhs, rhs),
             CollationSeq::Rtrim => Self::rtrim_cmp(lhs, rhs),
+            CollationSeq::Custom(id) => Self::custom_cmp(*id, lhs, rhs),
+        }
+    }
+
+    fn custom_cmp(id: u8, lhs: &str, rhs: &str) -> Ordering {
+        let compare = CUSTOM_COLLATIONS
+            .read()
+            .get(id as usize)
+            .map(|(_, compare)| compare.clone());
+        match compare {
+            Some(compare) => compare(lhs, rhs),
+            None => Self::binary_cmp(lhs, rhs),
         }
     }
 
@@ -71,6 +136,44 @@ impl CollationSeq {
     }
 }
 
+impl FromStr for CollationSeq {
+    type Err = LimboError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        for builtin in [
+            CollationSeq::Unset,
+            CollationSeq::Binary,
+            CollationSeq::NoCase,
+            CollationSeq::Rtrim,
+        ] {
+            if s.eq_ignore_ascii_case(&builtin.to_string()) {
+                return Ok(builtin);
+            }
+        }
+        CUSTOM_COLLATIONS
+            .read()
+            .iter()
+            .position(|(name, _)| name.eq_ignore_ascii_case(s))
+            .map(|id| CollationSeq::Custom(id as u8))
+            .ok_or_else(|| LimboError::ParseError(format!("no such collation sequence: {s}")))
+    }
+}
+
+impl fmt::Display for CollationSeq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CollationSeq::Unset => f.write_str("Unset"),
+            CollationSeq::Binary => f.write_str("Binary"),
+            CollationSeq::NoCase => f.write_str("NoCase"),
+            CollationSeq::Rtrim => f.write_str("Rtrim"),
+            CollationSeq::Custom(id) => match CUSTOM_COLLATIONS.read().get(*id as usize) {
+                Some((name, _)) => f.write_str(name),
+                None => write!(f, "Custom({id})"),
+            },
+        }
+    }
+}
+
 /// Every column of every table has an associated collating function. If no collating function is explicitly defined,
 /// then the collating function defaults to BINARY.
 /// The COLLATE clause of the column definition is used to define alternative collating functions for a column.
@@ -158,6 +261,39 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_register_collation() {
+        let reverse = CollationSeq::register(
+            "test_reverse",
+            Arc::new(|lhs: &str, rhs: &str| rhs.cmp(lhs)),
+        )
+        .unwrap();
+        assert_eq!(CollationSeq::new("TEST_REVERSE").unwrap(), reverse);
+        assert_eq!(reverse.compare_strings("a", "b"), Ordering::Greater);
+        assert_eq!(reverse.to_string(), "test_reverse");
+        assert_eq!(CollationSeq::from_bits(reverse.to_bits()), reverse);
+
+        let mut column = Column::new_default_text(None, "TEXT".to_string(), None);
+        column.set_collation(Some(reverse));
+        assert_eq!(column.collation_opt(), Some(reverse));
+        assert_eq!(column.ty(), Type::Text);
+
+        // Registering the name again replaces the function
+        let replaced = CollationSeq::register(
+            "test_reverse",
+            Arc::new(|lhs: &str, rhs: &str| lhs.cmp(rhs)),
+        )
+        .unwrap();
+        assert_eq!(replaced, reverse);
+        assert_eq!(reverse.compare_strings("a", "b"), Ordering::Less);
+
+        assert!(
+            CollationSeq::register("NOCASE", Arc::new(|lhs: &str, rhs: &str| lhs.cmp(rhs)))
+                .is_err()
+        );
+        assert!(CollationSeq::new("no_such_collation").is_err());
+    }
+
     #[test]
     fn test_get_collseq_from_expr_single_table_single_column() {
         // plain column
@@ -1,8 +1,10 @@
-use crate::schema::{Index, IndexColumn, Schema};
+use crate::schema::{Index, IndexColumn, PseudoCursorType, Schema};
 use crate::translate::collate::get_collseq_from_expr;
 use crate::translate::emitter::{emit_query, LimitCtx, Resolver, TranslateCtx};
 use crate::translate::expr::translate_expr;
+use crate::translate::order_by::sorter_insert;
 use crate::translate::plan::{Plan, QueryDestination, SelectPlan};
+use crate::translate::result_row::emit_offset;
 use crate::vdbe::builder::{CursorType, ProgramBuilder};
 use crate::vdbe::insn::{to_u16, Insn};
 use crate::vdbe::BranchOffset;
@@ -17,7 +19,7 @@ use tracing::Level;
 pub fn emit_program_for_compound_select(
     program: &mut ProgramBuilder,
     resolver: &Resolver,
-    plan: Plan,
+    mut plan: Plan,
 ) -> crate::Result<()> {
     let Plan::CompoundSelect {
         left,
@@ -132,21 +134,240 @@ pub fn emit_program_for_compound_select(
         .table_references
         .extend(right_plan.table_references.clone());
 
+    let Plan::CompoundSelect { order_by, .. } = &mut plan else {
+        unreachable!()
+    };
+    match order_by.take() {
+        Some(order_by) => emit_sorted_compound_select(
+            program,
+            plan,
+            &order_by,
+            right_most_ctx.resolver.schema,
+            right_most_ctx.resolver.symbol_table,
+            limit_ctx,
+            offset_reg,
+            yield_reg,
+            reg_result_cols_start,
+        )?,
+        None => emit_compound_select(
+            program,
+            plan,
+            right_most_ctx.resolver.schema,
+            right_most_ctx.resolver.symbol_table,
+            limit_ctx,
+            offset_reg,
+            yield_reg,
+            reg_result_cols_start,
+        )?,
+    }
+    program.pop_current_parent_explain();
+
+    Ok(())
+}
+
+/// Emits bytecode for a compound SELECT statement that has an ORDER BY clause.
+///
+/// The compound SELECT runs as a coroutine and every row it yields is inserted into a sorter.
+/// The sorted rows are then read back, and the LIMIT and OFFSET are applied to the sorted
+/// result rather than to the individual subselects.
+#[allow(clippy::too_many_arguments)]
+fn emit_sorted_compound_select(
+    program: &mut ProgramBuilder,
+    mut plan: Plan,
+    order_by: &[(Expr, SortOrder)],
+    schema: &Schema,
+    syms: &SymbolTable,
+    limit_ctx: Option<LimitCtx>,
+    offset_reg: Option<usize>,
+    yield_reg: Option<usize>,
+    reg_result_cols_start: Option<usize>,
+) -> crate::Result<()> {
+    let Plan::CompoundSelect {
+        left, right_most, ..
+    } = &mut plan
+    else {
+        unreachable!()
+    };
+    let num_columns = right_most.result_columns.len();
+
+    // Sort keys use an explicit COLLATE from the ORDER BY term if there is one, and otherwise
+    // the collation of the column in the leftmost subselect.
+    let leftmost = &left[0].0;
+    let mut key_columns = Vec::with_capacity(order_by.len());
+    let mut order_and_collations = Vec::with_capacity(order_by.len());
+    for (expr, dir) in order_by {
+        let column_idx = compound_order_by_column(expr);
+        let collation = match get_collseq_from_expr(expr, &leftmost.table_references)? {
+            Some(collation) => Some(collation),
+            None => get_collseq_from_expr(
+                &leftmost.result_columns[column_idx].expr,
+                &leftmost.table_references,
+            )?,
+        };
+        key_columns.push(column_idx);
+        order_and_collations.push((*dir, collation));
+    }
+
+    let label_end = program.allocate_label();
+    if let Some(limit_ctx) = &limit_ctx {
+        program.emit_insn(Insn::IfNot {
+            reg: limit_ctx.reg_limit,
+            target_pc: label_end,
+            jump_if_null: false,
+        });
+    }
+    let sort_cursor = program.alloc_cursor_id(CursorType::Sorter);
+    program.emit_insn(Insn::SorterOpen {
+        cursor_id: sort_cursor,
+        columns: order_by.len(),
+        order_and_collations,
+    });
+
+    // The subselects yield their rows to the sorting loop below. The result column registers
+    // must directly follow the yield register, see read_deduplicated_union_or_except_rows().
+    let inner_yield_reg = program.alloc_register();
+    let inner_cols_start_reg = program.alloc_registers(num_columns);
+    let coroutine_start = program.allocate_label();
+    let coroutine_end = program.allocate_label();
+    let destination = QueryDestination::CoroutineYield {
+        yield_reg: inner_yield_reg,
+        coroutine_implementation_start: coroutine_start,
+    };
+    for (plan, _) in left.iter_mut() {
+        plan.query_destination = destination.clone();
+    }
+    right_most.query_destination = destination;
+
+    program.emit_insn(Insn::InitCoroutine {
+        yield_reg: inner_yield_reg,
+        jump_on_definition: coroutine_end,
+        start_offset: coroutine_start,
+    });
+    program.preassign_label_to_next_insn(coroutine_start);
     emit_compound_select(
         program,
         plan,
-        right_most_ctx.resolver.schema,
-        right_most_ctx.resolver.symbol_table,
-        limit_ctx,
-        offset_reg,
-        yield_reg,
-        reg_result_cols_start,
+        schema,
+        syms,
+        None,
+        None,
+        Some(inner_yield_reg),
+        Some(inner_cols_start_reg),
     )?;
-    program.pop_current_parent_explain();
+    program.emit_insn(Insn::EndCoroutine {
+        yield_reg: inner_yield_reg,
+    });
+    program.preassign_label_to_next_insn(coroutine_end);
+
+    // The sorter records hold the sort keys followed by all the result columns.
+    let sorter_column_count = order_by.len() + num_columns;
+    let record_start_reg = program.alloc_registers(sorter_column_count);
+    let reg_sorter_data = program.alloc_register();
+    let label_insert_loop = program.allocate_label();
+    let label_insert_done = program.allocate_label();
+    program.preassign_label_to_next_insn(label_insert_loop);
+    program.emit_insn(Insn::Yield {
+        yield_reg: inner_yield_reg,
+        end_offset: label_insert_done,
+    });
+    for (i, column_idx) in key_columns.iter().enumerate() {
+        program.emit_insn(Insn::Copy {
+            src_reg: inner_cols_start_reg + column_idx,
+            dst_reg: record_start_reg + i,
+            extra_amount: 0,
+        });
+    }
+    program.emit_insn(Insn::Copy {
+        src_reg: inner_cols_start_reg,
+        dst_reg: record_start_reg + order_by.len(),
+        extra_amount: num_columns - 1,
+    });
+    sorter_insert(
+        program,
+        record_start_reg,
+        sorter_column_count,
+        sort_cursor,
+        reg_sorter_data,
+    );
+    program.emit_insn(Insn::Goto {
+        target_pc: label_insert_loop,
+    });
+    program.preassign_label_to_next_insn(label_insert_done);
+
+    emit_explain!(program, false, "USE TEMP B-TREE FOR ORDER BY".to_owned());
+    let pseudo_cursor = program.alloc_cursor_id(CursorType::Pseudo(PseudoCursorType {
+        column_count: sorter_column_count,
+    }));
+    program.emit_insn(Insn::OpenPseudo {
+        cursor_id: pseudo_cursor,
+        content_reg: reg_sorter_data,
+        num_fields: sorter_column_count,
+    });
+    program.emit_insn(Insn::SorterSort {
+        cursor_id: sort_cursor,
+        pc_if_empty: label_end,
+    });
+    let label_sort_loop = program.allocate_label();
+    let label_sort_next = program.allocate_label();
+    program.preassign_label_to_next_insn(label_sort_loop);
+    emit_offset(program, label_sort_next, offset_reg);
+    program.emit_insn(Insn::SorterData {
+        cursor_id: sort_cursor,
+        dest_reg: reg_sorter_data,
+        pseudo_cursor,
+    });
+    let cols_start_reg = match (yield_reg, reg_result_cols_start) {
+        (Some(_), Some(start_reg)) => start_reg,
+        _ => program.alloc_registers(num_columns),
+    };
+    for i in 0..num_columns {
+        program.emit_insn(Insn::Column {
+            cursor_id: pseudo_cursor,
+            column: order_by.len() + i,
+            dest: cols_start_reg + i,
+            default: None,
+        });
+    }
+    if let Some(yield_reg) = yield_reg {
+        program.emit_insn(Insn::Yield {
+            yield_reg,
+            end_offset: BranchOffset::Offset(0),
+        });
+    } else {
+        program.emit_insn(Insn::ResultRow {
+            start_reg: cols_start_reg,
+            count: num_columns,
+        });
+    }
+    if let Some(limit_ctx) = limit_ctx {
+        program.emit_insn(Insn::DecrJumpZero {
+            reg: limit_ctx.reg_limit,
+            target_pc: label_end,
+        });
+    }
+    program.preassign_label_to_next_insn(label_sort_next);
+    program.emit_insn(Insn::SorterNext {
+        cursor_id: sort_cursor,
+        pc_if_next: label_sort_loop,
+    });
+    program.preassign_label_to_next_insn(label_end);
 
     Ok(())
 }
 
+/// Returns the 0-based result column index of a compound SELECT ORDER BY term.
+/// The planner resolves every term to a column number, optionally wrapped in a COLLATE.
+fn compound_order_by_column(expr: &Expr) -> usize {
+    match expr {
+        Expr::Collate(expr, _) => compound_order_by_column(expr),
+        Expr::Literal(Literal::Numeric(n)) => n
+            .parse::<usize>()
+            .expect("compound ORDER BY term must be a column number")
+            .saturating_sub(1),
+        _ => unreachable!("compound ORDER BY term must be resolved to a column number"),
+    }
+}
+
 // Emits bytecode for a compound SELECT statement. This function processes the rightmost part of
 // the compound SELECT and handles the left parts recursively based on the compound operator type.
 #[allow(clippy::too_many_arguments)]
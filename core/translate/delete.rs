@@ -1,4 +1,4 @@
-use crate::schema::{Schema, Table};
+use crate::schema::{BTreeTable, Schema, Table};
 use crate::translate::emitter::{emit_program, Resolver};
 use crate::translate::expr::process_returning_clause;
 use crate::translate::optimizer::optimize_plan;
@@ -25,6 +25,7 @@ pub fn translate_delete(
     mut program: ProgramBuilder,
     connection: &Arc<crate::Connection>,
 ) -> Result<ProgramBuilder> {
+    let database_id = connection.resolve_database_id(tbl_name)?;
     let tbl_name = normalize_ident(tbl_name.name.as_str());
 
     // Check if this is a system table that should be protected from direct writes
@@ -36,12 +37,18 @@ pub fn translate_delete(
         &mut program,
         resolver.schema,
         tbl_name,
+        database_id,
         where_clause,
         limit,
         returning,
         connection,
     )?;
-    optimize_plan(&mut program, &mut delete_plan, resolver.schema)?;
+    optimize_plan(
+        &mut program,
+        &mut delete_plan,
+        resolver.schema,
+        connection.automatic_index_enabled(),
+    )?;
     if let Plan::Delete(delete_plan_inner) = &mut delete_plan {
         // Rewrite the Delete plan after optimization whenever a RowSet is used (DELETE triggers
         // are present), so the joined table is treated as a plain table scan again.
@@ -80,10 +87,12 @@ pub fn translate_delete(
     Ok(program)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn prepare_delete_plan(
     program: &mut ProgramBuilder,
     schema: &Schema,
     tbl_name: String,
+    database_id: usize,
     where_clause: Option<Box<Expr>>,
     limit: Option<Limit>,
     mut returning: Vec<ResultColumn>,
@@ -113,6 +122,10 @@ pub fn prepare_delete_plan(
         );
     }
 
+    if let Some(btree) = table.btree().filter(|btree| !btree.has_rowid) {
+        validate_without_rowid_write(program, schema, &btree, TriggerEvent::Delete, connection)?;
+    }
+
     let btree_table_for_triggers = table.btree();
 
     let table = if let Some(table) = table.virtual_table() {
@@ -132,7 +145,7 @@ pub fn prepare_delete_plan(
         col_used_mask: ColumnUsedMask::default(),
         column_use_counts: Vec::new(),
         expression_index_usages: Vec::new(),
-        database_id: 0,
+        database_id,
     }];
     let mut table_references = TableReferences::new(joined_tables, vec![]);
 
@@ -149,6 +162,7 @@ pub fn prepare_delete_plan(
 
     let result_columns =
         process_returning_clause(&mut returning, &mut table_references, connection)?;
+    mark_without_rowid_columns_used(&mut table_references);
 
     // Parse the LIMIT/OFFSET clause
     let (resolved_limit, resolved_offset) =
@@ -204,6 +218,7 @@ pub fn prepare_delete_plan(
             values: vec![],
             window: None,
             non_from_clause_subqueries: vec![],
+            min_max_from_first_row: false,
         };
 
         Ok(Plan::Delete(DeletePlan {
@@ -234,6 +249,50 @@ pub fn prepare_delete_plan(
     }
 }
 
+/// Rejects the parts of DELETE and UPDATE that are not implemented yet for WITHOUT ROWID tables.
+/// They all identify the changed row by its rowid, which WITHOUT ROWID tables do not have.
+pub(crate) fn validate_without_rowid_write(
+    program: &ProgramBuilder,
+    schema: &Schema,
+    table: &BTreeTable,
+    event: TriggerEvent,
+    connection: &Arc<crate::Connection>,
+) -> Result<()> {
+    if has_relevant_triggers_type_only(schema, event, None, table) {
+        crate::bail_parse_error!("triggers on WITHOUT ROWID table are not supported");
+    }
+    if connection.foreign_keys_enabled()
+        && (schema.has_child_fks(&table.name) || schema.any_resolved_fks_referencing(&table.name))
+    {
+        crate::bail_parse_error!("foreign keys on WITHOUT ROWID table are not supported");
+    }
+    if program
+        .capture_data_changes_mode()
+        .table()
+        .is_some_and(|cdc_table| cdc_table != table.name)
+    {
+        crate::bail_parse_error!("change data capture on WITHOUT ROWID table is not supported");
+    }
+    if !schema
+        .get_dependent_materialized_views(&table.name)
+        .is_empty()
+    {
+        crate::bail_parse_error!("materialized views on WITHOUT ROWID table are not supported");
+    }
+    Ok(())
+}
+
+/// DELETE and UPDATE copy every column of the WITHOUT ROWID rows they change, so the table can
+/// only be read through an index that holds all of its columns.
+pub(crate) fn mark_without_rowid_columns_used(table_references: &mut TableReferences) {
+    let table = &mut table_references.joined_tables_mut()[0];
+    if table.btree().is_some_and(|btree| !btree.has_rowid) {
+        for column in 0..table.columns().len() {
+            table.mark_column_used(column);
+        }
+    }
+}
+
 fn estimate_num_instructions(plan: &DeletePlan) -> usize {
     let base = 20;
 
@@ -154,6 +154,14 @@ impl Display for SelectPlan {
                 Operation::HashJoin(_) => {
                     writeln!(f, "{indent}HASH JOIN")?;
                 }
+                Operation::MultiIndexScan(multi) => {
+                    writeln!(
+                        f,
+                        "{indent}MULTI-INDEX OR {} ({})",
+                        reference.identifier,
+                        multi.branch_descriptions()
+                    )?;
+                }
             }
         }
         Ok(())
@@ -231,6 +239,9 @@ impl Display for DeletePlan {
                 Operation::HashJoin(_) => {
                     unreachable!("Delete plan should not have hash joins");
                 }
+                Operation::MultiIndexScan(_) => {
+                    unreachable!("Delete plan should not have multi-index scans");
+                }
             }
         }
         Ok(())
@@ -321,6 +332,9 @@ impl fmt::Display for UpdatePlan {
                 Operation::HashJoin(_) => {
                     unreachable!("Update plan should not have hash joins");
                 }
+                Operation::MultiIndexScan(_) => {
+                    unreachable!("Update plan should not have multi-index scans");
+                }
             }
         }
         if !self.order_by.is_empty() {
@@ -1,6 +1,7 @@
 // This module contains code for emitting bytecode instructions for SQL query execution.
 // It handles translating high-level SQL operations into low-level bytecode that can be executed by the virtual machine.
 
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::num::NonZeroUsize;
 use std::sync::Arc;
@@ -9,16 +10,19 @@ use tracing::{instrument, Level};
 use turso_parser::ast::{self, Expr, Literal, TriggerEvent, TriggerTime};
 
 use super::aggregation::emit_ungrouped_aggregation;
-use super::expr::translate_expr;
+use super::collate::CollationSeq;
+use super::expr::{translate_expr, walk_expr};
 use super::group_by::{
     group_by_agg_phase, group_by_emit_row_phase, init_group_by, GroupByMetadata, GroupByRowSource,
 };
 use super::main_loop::{
     close_loop, emit_loop, init_distinct, init_loop, open_loop, LeftJoinMetadata, LoopLabels,
 };
+use super::optimizer::Optimizable;
 use super::order_by::{emit_order_by, init_order_by, SortMetadata};
 use super::plan::{
     Distinctness, JoinOrderMember, Operation, Scan, SelectPlan, TableReferences, UpdatePlan,
+    WhereTerm,
 };
 use super::select::emit_simple_count;
 use super::subquery::emit_from_clause_subqueries;
@@ -35,6 +39,7 @@ use crate::translate::fkeys::{
     emit_guarded_fk_decrement, fire_fk_delete_actions, fire_fk_update_actions, open_read_index,
     open_read_table, stabilize_new_row_for_fk,
 };
+use crate::translate::insert::format_key_violation_desc;
 use crate::translate::plan::{
     DeletePlan, EvalAt, JoinedTable, Plan, QueryDestination, ResultSetColumn, Search,
 };
@@ -61,6 +66,21 @@ pub struct Resolver<'a> {
     pub symbol_table: &'a SymbolTable,
     pub expr_to_reg_cache_enabled: bool,
     pub expr_to_reg_cache: Vec<(std::borrow::Cow<'a, ast::Expr>, usize)>,
+    /// Deterministic subexpressions evaluated so far in the current scope, see
+    /// [Resolver::with_subexpr_cache].
+    subexpr_cache: RefCell<Option<SubexprCache>>,
+}
+
+/// The registers holding the values of the deterministic subexpressions that were evaluated so
+/// far in a straight-line piece of code, such as the WHERE terms of one loop.
+#[derive(Default)]
+struct SubexprCache {
+    /// Each expression with the register of its value and the collation its translation set,
+    /// if it set one.
+    entries: Vec<(ast::Expr, usize, Option<Option<(CollationSeq, bool)>>)>,
+    /// Greater than zero while translating operands that are not always evaluated, like the
+    /// branches of a CASE. Their values are not cached, as a later use may run without them.
+    conditional_depth: usize,
 }
 
 impl<'a> Resolver<'a> {
@@ -70,6 +90,7 @@ impl<'a> Resolver<'a> {
             symbol_table,
             expr_to_reg_cache_enabled: false,
             expr_to_reg_cache: Vec::new(),
+            subexpr_cache: RefCell::new(None),
         }
     }
 
@@ -87,6 +108,78 @@ impl<'a> Resolver<'a> {
         self.expr_to_reg_cache_enabled = true;
     }
 
+    /// Runs `f` with a cache of deterministic subexpressions: the first evaluation of such an
+    /// expression is kept in a register, and [translate_expr] copies it for every later,
+    /// equivalent expression. The code emitted by `f` must run straight through on every
+    /// iteration, so that a value is always computed before it is copied. The cache is dropped
+    /// when `f` returns, and the cache of an enclosing call is not visible inside `f`.
+    pub(crate) fn with_subexpr_cache<T>(&self, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        let outer = self.subexpr_cache.replace(Some(SubexprCache::default()));
+        let result = f();
+        *self.subexpr_cache.borrow_mut() = outer;
+        result
+    }
+
+    pub(crate) fn has_subexpr_cache(&self) -> bool {
+        self.subexpr_cache.borrow().is_some()
+    }
+
+    /// Runs `f`, which translates operands that may be skipped at runtime, without caching the
+    /// subexpressions they evaluate.
+    pub(crate) fn conditionally<T>(&self, f: impl FnOnce() -> T) -> T {
+        self.adjust_conditional_depth(true);
+        let result = f();
+        self.adjust_conditional_depth(false);
+        result
+    }
+
+    fn adjust_conditional_depth(&self, enter: bool) {
+        if let Some(cache) = self.subexpr_cache.borrow_mut().as_mut() {
+            if enter {
+                cache.conditional_depth += 1;
+            } else {
+                cache.conditional_depth -= 1;
+            }
+        }
+    }
+
+    /// The register and collation of a cached subexpression equivalent to `expr`.
+    pub(crate) fn cached_subexpr(
+        &self,
+        expr: &ast::Expr,
+    ) -> Option<(usize, Option<Option<(CollationSeq, bool)>>)> {
+        let cache = self.subexpr_cache.borrow();
+        cache
+            .as_ref()?
+            .entries
+            .iter()
+            .find(|(e, _, _)| exprs_are_equivalent(expr, e))
+            .map(|(_, reg, collation)| (*reg, *collation))
+    }
+
+    /// Whether the value of `expr` should be kept for later equivalent expressions: the cache
+    /// is on, `expr` is evaluated unconditionally, and it is a deterministic computation rather
+    /// than a column, a literal or a constant that is hoisted out of the loop anyway.
+    pub(crate) fn should_cache_subexpr(&self, expr: &ast::Expr) -> bool {
+        let cacheable = self
+            .subexpr_cache
+            .borrow()
+            .as_ref()
+            .is_some_and(|cache| cache.conditional_depth == 0);
+        cacheable && !expr.is_constant(self) && is_deterministic_computation(expr, self)
+    }
+
+    pub(crate) fn cache_subexpr(
+        &self,
+        expr: &ast::Expr,
+        reg: usize,
+        collation: Option<Option<(CollationSeq, bool)>>,
+    ) {
+        if let Some(cache) = self.subexpr_cache.borrow_mut().as_mut() {
+            cache.entries.push((expr.clone(), reg, collation));
+        }
+    }
+
     pub fn resolve_cached_expr_reg(&self, expr: &ast::Expr) -> Option<usize> {
         if self.expr_to_reg_cache_enabled {
             self.expr_to_reg_cache
@@ -99,6 +192,49 @@ impl<'a> Resolver<'a> {
     }
 }
 
+/// Returns true if `expr` is worth evaluating only once when it appears several times, i.e. it
+/// is not a plain column reference or literal and always yields the same value for the same row.
+fn is_deterministic_computation(expr: &Expr, resolver: &Resolver) -> bool {
+    if matches!(
+        expr,
+        Expr::Column { .. }
+            | Expr::RowId { .. }
+            | Expr::Literal(_)
+            | Expr::Variable(_)
+            | Expr::Register(_)
+            | Expr::Parenthesized(_)
+    ) {
+        return false;
+    }
+    let mut deterministic = true;
+    let _ = walk_expr(expr, &mut |e: &Expr| -> Result<WalkControl> {
+        match e {
+            Expr::FunctionCall { name, args, .. } => {
+                deterministic &= resolver
+                    .resolve_function(name.as_str(), args.len())
+                    .is_some_and(|f| f.is_deterministic());
+            }
+            Expr::FunctionCallStar { name, .. } => {
+                deterministic &= resolver
+                    .resolve_function(name.as_str(), 0)
+                    .is_some_and(|f| f.is_deterministic());
+            }
+            Expr::Exists(_)
+            | Expr::InSelect { .. }
+            | Expr::Subquery(_)
+            | Expr::SubqueryResult { .. }
+            | Expr::Raise(..) => deterministic = false,
+            _ => {}
+        }
+        Ok(if deterministic {
+            WalkControl::Continue
+        } else {
+            WalkControl::SkipChildren
+        })
+    });
+    deterministic
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct LimitCtx {
     /// Register holding the LIMIT value (e.g. LIMIT 5)
@@ -431,33 +567,33 @@ pub fn emit_query<'a>(
     )?;
 
     if plan.is_simple_count() {
+        // The count is computed without a loop over the rows.
         emit_simple_count(program, t_ctx, plan)?;
-        return Ok(t_ctx.reg_result_cols_start.unwrap());
-    }
-
-    // Set up main query execution loop
-    open_loop(
-        program,
-        t_ctx,
-        &plan.table_references,
-        &plan.join_order,
-        &plan.where_clause,
-        None,
-        OperationMode::SELECT,
-        &mut plan.non_from_clause_subqueries,
-    )?;
+    } else {
+        // Set up main query execution loop
+        open_loop(
+            program,
+            t_ctx,
+            &plan.table_references,
+            &plan.join_order,
+            &plan.where_clause,
+            None,
+            OperationMode::SELECT,
+            &mut plan.non_from_clause_subqueries,
+        )?;
 
-    // Process result columns and expressions in the inner loop
-    emit_loop(program, t_ctx, plan)?;
+        // Process result columns and expressions in the inner loop
+        emit_loop(program, t_ctx, plan)?;
 
-    // Clean up and close the main execution loop
-    close_loop(
-        program,
-        t_ctx,
-        &plan.table_references,
-        &plan.join_order,
-        OperationMode::SELECT,
-    )?;
+        // Clean up and close the main execution loop
+        close_loop(
+            program,
+            t_ctx,
+            &plan.table_references,
+            &plan.join_order,
+            OperationMode::SELECT,
+        )?;
+    }
 
     program.preassign_label_to_next_insn(after_main_loop_label);
 
@@ -500,6 +636,9 @@ fn emit_program_for_delete(
     program: &mut ProgramBuilder,
     mut plan: DeletePlan,
 ) -> Result<()> {
+    if is_without_rowid_target(&plan.table_references) {
+        return emit_program_for_without_rowid_delete(resolver, program, plan);
+    }
     let mut t_ctx = TranslateCtx::new(
         program,
         resolver.schema,
@@ -843,6 +982,9 @@ fn emit_delete_insns<'a>(
         Operation::HashJoin(_) => {
             unreachable!("access through HashJoin is not supported for delete statements")
         }
+        Operation::MultiIndexScan(_) => {
+            unreachable!("access through MultiIndexScan is not supported for delete statements")
+        }
     };
     let btree_table = unsafe { &*table_reference }.btree();
     let main_table_cursor_id = program.resolve_cursor_id(&CursorKey::table(internal_id));
@@ -984,6 +1126,20 @@ fn emit_delete_row_common(
         let conflict_action = 0u16;
         let cursor_id = virtual_table_cursor_id.unwrap_or(main_table_cursor_id);
 
+        // Emit RETURNING results if specified (must be before DELETE)
+        if !result_columns.is_empty() {
+            let columns_start_reg = columns_start_reg
+                .expect("columns_start_reg must be provided when there are triggers or RETURNING");
+            emit_returning_results(
+                program,
+                table_references,
+                result_columns,
+                columns_start_reg,
+                rowid_reg,
+                &mut t_ctx.resolver,
+            )?;
+        }
+
         program.emit_insn(Insn::VUpdate {
             cursor_id,
             arg_count: 2,
@@ -1270,6 +1426,9 @@ fn emit_program_for_update(
     mut plan: UpdatePlan,
     after: impl FnOnce(&mut ProgramBuilder),
 ) -> Result<()> {
+    if is_without_rowid_target(&plan.table_references) {
+        return emit_program_for_without_rowid_update(resolver, program, plan, after);
+    }
     let mut t_ctx = TranslateCtx::new(
         program,
         resolver.schema,
@@ -1360,23 +1519,21 @@ fn emit_program_for_update(
     )?;
 
     // Prepare index cursors
+    let updated_table = plan.table_references.joined_tables().first().unwrap();
+    let (target_table_id, target_database_id) =
+        (updated_table.internal_id, updated_table.database_id);
     let mut index_cursors = Vec::with_capacity(plan.indexes_to_update.len());
     for index in &plan.indexes_to_update {
-        let index_cursor = if let Some(cursor) = program.resolve_cursor_id_safe(&CursorKey::index(
-            plan.table_references
-                .joined_tables()
-                .first()
-                .unwrap()
-                .internal_id,
-            index.clone(),
-        )) {
+        let index_cursor = if let Some(cursor) =
+            program.resolve_cursor_id_safe(&CursorKey::index(target_table_id, index.clone()))
+        {
             cursor
         } else {
             let cursor = program.alloc_cursor_index(None, index)?;
             program.emit_insn(Insn::OpenWrite {
                 cursor_id: cursor,
                 root_page: RegisterOrLiteral::Literal(index.root_page),
-                db: 0,
+                db: target_database_id,
             });
             cursor
         };
@@ -1439,6 +1596,495 @@ fn emit_program_for_update(
     Ok(())
 }
 
+/// Collects the rows of a WITHOUT ROWID table that a DELETE or UPDATE changes into an ephemeral
+/// table, one record per row with the columns in table order, and returns its cursor.
+///
+/// The rows live in the primary key index that the statement modifies, so all of them are
+/// collected before the first one is changed.
+fn emit_without_rowid_write_set(
+    program: &mut ProgramBuilder,
+    t_ctx: &mut TranslateCtx,
+    table_references: &TableReferences,
+    where_clause: &[WhereTerm],
+    limit: &Option<Box<Expr>>,
+    offset: &Option<Box<Expr>>,
+    contains_constant_false_condition: bool,
+) -> Result<usize> {
+    let table = table_references.joined_tables().first().unwrap();
+    let btree = table.btree().expect("WITHOUT ROWID table is a btree table");
+    let write_set_cursor_id = program.alloc_cursor_id(CursorType::BTreeTable(btree.clone()));
+    program.emit_insn(Insn::OpenEphemeral {
+        cursor_id: write_set_cursor_id,
+        is_table: true,
+    });
+
+    let write_set_done = program.allocate_label();
+    t_ctx.label_main_loop_end = Some(write_set_done);
+    init_limit(program, t_ctx, limit, offset)?;
+    if contains_constant_false_condition {
+        program.emit_insn(Insn::Goto {
+            target_pc: write_set_done,
+        });
+    }
+
+    let join_order = [JoinOrderMember {
+        table_id: table.internal_id,
+        original_idx: 0,
+        is_outer: false,
+    }];
+    init_loop(
+        program,
+        t_ctx,
+        table_references,
+        &mut [],
+        None,
+        OperationMode::SELECT,
+        where_clause,
+        &join_order,
+        &mut [],
+    )?;
+    open_loop(
+        program,
+        t_ctx,
+        table_references,
+        &join_order,
+        where_clause,
+        None,
+        OperationMode::SELECT,
+        &mut [],
+    )?;
+
+    if let Some(offset) = t_ctx.reg_offset {
+        program.emit_insn(Insn::IfPos {
+            reg: offset,
+            target_pc: t_ctx.labels_main_loop.first().unwrap().next,
+            decrement_by: 1,
+        });
+    }
+    let columns_start_reg = program.alloc_registers(btree.columns.len());
+    for (i, column) in btree.columns.iter().enumerate() {
+        translate_expr(
+            program,
+            Some(table_references),
+            &Expr::Column {
+                database: None,
+                table: table.internal_id,
+                column: i,
+                is_rowid_alias: column.is_rowid_alias(),
+            },
+            columns_start_reg + i,
+            &t_ctx.resolver,
+        )?;
+    }
+    let record_reg = program.alloc_register();
+    program.emit_insn(Insn::MakeRecord {
+        start_reg: to_u16(columns_start_reg),
+        count: to_u16(btree.columns.len()),
+        dest_reg: to_u16(record_reg),
+        index_name: None,
+        affinity_str: None,
+    });
+    let rowid_reg = program.alloc_register();
+    program.emit_insn(Insn::NewRowid {
+        cursor: write_set_cursor_id,
+        rowid_reg,
+        prev_largest_reg: 0,
+    });
+    program.emit_insn(Insn::Insert {
+        cursor: write_set_cursor_id,
+        key_reg: rowid_reg,
+        record_reg,
+        flag: InsertFlags::new().is_ephemeral_table_insert(),
+        table_name: "".to_string(),
+    });
+    if let Some(limit_ctx) = t_ctx.limit_ctx {
+        program.emit_insn(Insn::DecrJumpZero {
+            reg: limit_ctx.reg_limit,
+            target_pc: write_set_done,
+        });
+    }
+
+    close_loop(
+        program,
+        t_ctx,
+        table_references,
+        &join_order,
+        OperationMode::SELECT,
+    )?;
+    program.preassign_label_to_next_insn(write_set_done);
+
+    // The rows are written through separate cursors below.
+    let (table_cursor_id, index_cursor_id) =
+        table.resolve_cursors(program, OperationMode::SELECT)?;
+    for cursor_id in table_cursor_id.into_iter().chain(index_cursor_id) {
+        program.emit_insn(Insn::Close { cursor_id });
+    }
+    Ok(write_set_cursor_id)
+}
+
+fn open_index_for_write(
+    program: &mut ProgramBuilder,
+    index: &Arc<Index>,
+    database_id: usize,
+) -> Result<usize> {
+    let cursor_id = program.alloc_cursor_index(None, index)?;
+    program.emit_insn(Insn::OpenWrite {
+        cursor_id,
+        root_page: RegisterOrLiteral::Literal(index.root_page),
+        db: database_id,
+    });
+    Ok(cursor_id)
+}
+
+/// Copies the values of an index entry out of a row held in consecutive registers in table
+/// order, returning the first register of the entry.
+pub(crate) fn emit_without_rowid_index_entry(
+    program: &mut ProgramBuilder,
+    columns: &[IndexColumn],
+    columns_start_reg: usize,
+) -> usize {
+    let start_reg = program.alloc_registers(columns.len());
+    for (i, column) in columns.iter().enumerate() {
+        program.emit_insn(Insn::Copy {
+            src_reg: columns_start_reg + column.pos_in_table,
+            dst_reg: start_reg + i,
+            extra_amount: 0,
+        });
+    }
+    start_reg
+}
+
+/// Reads the current row of the write set into consecutive registers and positions the primary
+/// key index cursor on it, jumping to `row_done` if the row no longer exists.
+fn emit_without_rowid_seek_row(
+    program: &mut ProgramBuilder,
+    btree: &BTreeTable,
+    pk_index: &Index,
+    write_set_cursor_id: usize,
+    pk_cursor_id: usize,
+    row_done: BranchOffset,
+) -> usize {
+    let columns_start_reg = program.alloc_registers(btree.columns.len());
+    for i in 0..btree.columns.len() {
+        program.emit_column_or_rowid(write_set_cursor_id, i, columns_start_reg + i);
+    }
+    let key_columns = pk_index.key_columns(btree);
+    let key_start_reg = emit_without_rowid_index_entry(program, key_columns, columns_start_reg);
+    program.emit_insn(Insn::NotFound {
+        cursor_id: pk_cursor_id,
+        target_pc: row_done,
+        record_reg: key_start_reg,
+        num_regs: key_columns.len(),
+    });
+    columns_start_reg
+}
+
+fn is_without_rowid_target(table_references: &TableReferences) -> bool {
+    table_references
+        .joined_tables()
+        .first()
+        .and_then(|table| table.btree())
+        .is_some_and(|btree| !btree.has_rowid)
+}
+
+pub(crate) fn without_rowid_primary_key_index(
+    resolver: &Resolver,
+    btree: &BTreeTable,
+) -> Result<Arc<Index>> {
+    match resolver
+        .schema
+        .get_indices(&btree.name)
+        .find(|index| index.is_without_rowid_primary_key(btree))
+    {
+        Some(index) => Ok(index.clone()),
+        None => bail_parse_error!(
+            "primary key index of WITHOUT ROWID table {} not found",
+            btree.name
+        ),
+    }
+}
+
+/// DELETE from a WITHOUT ROWID table: every affected row is looked up in the primary key index
+/// by its key and removed from it and from the secondary indexes.
+fn emit_program_for_without_rowid_delete(
+    resolver: &Resolver,
+    program: &mut ProgramBuilder,
+    plan: DeletePlan,
+) -> Result<()> {
+    let mut t_ctx = TranslateCtx::new(program, resolver.schema, resolver.symbol_table, 1);
+    let table = plan.table_references.joined_tables().first().unwrap();
+    let btree = table.btree().expect("WITHOUT ROWID table is a btree table");
+    let pk_index = without_rowid_primary_key_index(resolver, &btree)?;
+
+    let write_set_cursor_id = emit_without_rowid_write_set(
+        program,
+        &mut t_ctx,
+        &plan.table_references,
+        &plan.where_clause,
+        &plan.limit,
+        &None,
+        plan.contains_constant_false_condition,
+    )?;
+    let pk_cursor_id = open_index_for_write(program, &pk_index, table.database_id)?;
+    let index_cursors = plan
+        .indexes
+        .iter()
+        .filter(|index| !index.is_without_rowid_primary_key(&btree))
+        .map(|index| {
+            Ok((
+                index.clone(),
+                open_index_for_write(program, index, table.database_id)?,
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let loop_start = program.allocate_label();
+    let row_done = program.allocate_label();
+    let loop_end = program.allocate_label();
+    program.emit_insn(Insn::Rewind {
+        cursor_id: write_set_cursor_id,
+        pc_if_empty: loop_end,
+    });
+    program.preassign_label_to_next_insn(loop_start);
+    // Expressions read the columns of the row from the write set.
+    program.set_cursor_override(table.internal_id, write_set_cursor_id);
+    let columns_start_reg = emit_without_rowid_seek_row(
+        program,
+        &btree,
+        &pk_index,
+        write_set_cursor_id,
+        pk_cursor_id,
+        row_done,
+    );
+
+    if !plan.result_columns.is_empty() {
+        let rowid_reg = program.alloc_register();
+        program.emit_null(rowid_reg, None);
+        emit_returning_results(
+            program,
+            &plan.table_references,
+            &plan.result_columns,
+            columns_start_reg,
+            rowid_reg,
+            &mut t_ctx.resolver,
+        )?;
+    }
+
+    for (index, index_cursor_id) in index_cursors {
+        let start_reg = emit_without_rowid_index_entry(program, &index.columns, columns_start_reg);
+        program.emit_insn(Insn::IdxDelete {
+            start_reg,
+            num_regs: index.columns.len(),
+            cursor_id: index_cursor_id,
+            raise_error_if_no_matching_entry: true,
+        });
+    }
+    program.emit_insn(Insn::Delete {
+        cursor_id: pk_cursor_id,
+        table_name: btree.name.clone(),
+        is_part_of_update: false,
+    });
+
+    program.preassign_label_to_next_insn(row_done);
+    program.emit_insn(Insn::Next {
+        cursor_id: write_set_cursor_id,
+        pc_if_next: loop_start,
+    });
+    program.preassign_label_to_next_insn(loop_end);
+    program.clear_cursor_override(table.internal_id);
+
+    program.result_columns = plan.result_columns;
+    program.table_references.extend(plan.table_references);
+    Ok(())
+}
+
+/// UPDATE of a WITHOUT ROWID table: every affected row is looked up in the primary key index by
+/// its key, removed, and written back with its new values, which may move it to a new key.
+fn emit_program_for_without_rowid_update(
+    resolver: &Resolver,
+    program: &mut ProgramBuilder,
+    plan: UpdatePlan,
+    after: impl FnOnce(&mut ProgramBuilder),
+) -> Result<()> {
+    let mut t_ctx = TranslateCtx::new(program, resolver.schema, resolver.symbol_table, 1);
+    let table = plan.table_references.joined_tables().first().unwrap();
+    let btree = table.btree().expect("WITHOUT ROWID table is a btree table");
+    let pk_index = without_rowid_primary_key_index(resolver, &btree)?;
+    let col_len = btree.columns.len();
+
+    let write_set_cursor_id = emit_without_rowid_write_set(
+        program,
+        &mut t_ctx,
+        &plan.table_references,
+        &plan.where_clause,
+        &plan.limit,
+        &plan.offset,
+        plan.contains_constant_false_condition,
+    )?;
+    let pk_cursor_id = open_index_for_write(program, &pk_index, table.database_id)?;
+    let index_cursors = plan
+        .indexes_to_update
+        .iter()
+        .filter(|index| !index.is_without_rowid_primary_key(&btree))
+        .map(|index| {
+            Ok((
+                index.clone(),
+                open_index_for_write(program, index, table.database_id)?,
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let loop_start = program.allocate_label();
+    let row_done = program.allocate_label();
+    let loop_end = program.allocate_label();
+    program.emit_insn(Insn::Rewind {
+        cursor_id: write_set_cursor_id,
+        pc_if_empty: loop_end,
+    });
+    program.preassign_label_to_next_insn(loop_start);
+    // Expressions read the old columns of the row from the write set.
+    program.set_cursor_override(table.internal_id, write_set_cursor_id);
+    let old_start_reg = emit_without_rowid_seek_row(
+        program,
+        &btree,
+        &pk_index,
+        write_set_cursor_id,
+        pk_cursor_id,
+        row_done,
+    );
+
+    let new_start_reg = program.alloc_registers(col_len);
+    for (i, column) in btree.columns.iter().enumerate() {
+        let target_reg = new_start_reg + i;
+        let Some((_, expr)) = plan.set_clauses.iter().find(|(idx, _)| *idx == i) else {
+            program.emit_insn(Insn::Copy {
+                src_reg: old_start_reg + i,
+                dst_reg: target_reg,
+                extra_amount: 0,
+            });
+            continue;
+        };
+        translate_expr(
+            program,
+            Some(&plan.table_references),
+            expr,
+            target_reg,
+            &t_ctx.resolver,
+        )?;
+        if column.notnull() {
+            use crate::error::SQLITE_CONSTRAINT_NOTNULL;
+            program.emit_insn(Insn::HaltIfNull {
+                target_reg,
+                err_code: SQLITE_CONSTRAINT_NOTNULL,
+                description: format!(
+                    "{}.{}",
+                    btree.name,
+                    column.name.as_ref().expect("Column name must be present")
+                ),
+            });
+        }
+    }
+    if btree.is_strict {
+        program.emit_insn(Insn::TypeCheck {
+            start_reg: new_start_reg,
+            count: col_len,
+            check_generated: true,
+            table_reference: Arc::clone(&btree),
+        });
+    } else if let Some(count) = NonZeroUsize::new(col_len) {
+        program.emit_insn(Insn::Affinity {
+            start_reg: new_start_reg,
+            count,
+            affinities: btree
+                .columns
+                .iter()
+                .map(|c| c.affinity().aff_mask())
+                .collect(),
+        });
+    }
+
+    for (index, index_cursor_id) in &index_cursors {
+        let start_reg = emit_without_rowid_index_entry(program, &index.columns, old_start_reg);
+        program.emit_insn(Insn::IdxDelete {
+            start_reg,
+            num_regs: index.columns.len(),
+            cursor_id: *index_cursor_id,
+            raise_error_if_no_matching_entry: true,
+        });
+    }
+    // IdxInsert does not count changes, so the row is counted as changed by its Delete.
+    program.emit_insn(Insn::Delete {
+        cursor_id: pk_cursor_id,
+        table_name: btree.name.clone(),
+        is_part_of_update: false,
+    });
+
+    // The new key may belong to another row.
+    let key_columns = pk_index.key_columns(&btree);
+    let pk_start_reg = emit_without_rowid_index_entry(program, &pk_index.columns, new_start_reg);
+    let no_conflict = program.allocate_label();
+    program.emit_insn(Insn::NoConflict {
+        cursor_id: pk_cursor_id,
+        target_pc: no_conflict,
+        record_reg: pk_start_reg,
+        num_regs: key_columns.len(),
+    });
+    program.emit_insn(Insn::Halt {
+        err_code: SQLITE_CONSTRAINT_PRIMARYKEY,
+        description: format_key_violation_desc(&btree.name, key_columns),
+    });
+    program.preassign_label_to_next_insn(no_conflict);
+
+    let mut entries = vec![(&pk_index, pk_cursor_id, pk_start_reg)];
+    for (index, cursor_id) in &index_cursors {
+        let start_reg = emit_without_rowid_index_entry(program, &index.columns, new_start_reg);
+        entries.push((index, *cursor_id, start_reg));
+    }
+    for (index, cursor_id, start_reg) in entries {
+        let record_reg = program.alloc_register();
+        program.emit_insn(Insn::MakeRecord {
+            start_reg: to_u16(start_reg),
+            count: to_u16(index.columns.len()),
+            dest_reg: to_u16(record_reg),
+            index_name: Some(index.name.clone()),
+            affinity_str: None,
+        });
+        program.emit_insn(Insn::IdxInsert {
+            cursor_id,
+            record_reg,
+            unpacked_start: Some(start_reg),
+            unpacked_count: Some(index.columns.len() as u16),
+            flags: IdxInsertFlags::new().nchange(true),
+        });
+    }
+
+    if let Some(returning) = plan.returning.as_ref() {
+        let rowid_reg = program.alloc_register();
+        program.emit_null(rowid_reg, None);
+        emit_returning_results(
+            program,
+            &plan.table_references,
+            returning,
+            new_start_reg,
+            rowid_reg,
+            &mut t_ctx.resolver,
+        )?;
+    }
+
+    program.preassign_label_to_next_insn(row_done);
+    program.emit_insn(Insn::Next {
+        cursor_id: write_set_cursor_id,
+        pc_if_next: loop_start,
+    });
+    program.preassign_label_to_next_insn(loop_end);
+    program.clear_cursor_override(table.internal_id);
+    after(program);
+
+    program.result_columns = plan.returning.unwrap_or_default();
+    program.table_references.extend(plan.table_references);
+    Ok(())
+}
+
 /// Helper function to evaluate SET expressions and read column values for UPDATE.
 /// This is invoked once for every UPDATE, but will be invoked again if there are
 /// any BEFORE UPDATE triggers that fired, because the triggers may have modified the row,
@@ -1658,6 +2304,9 @@ fn emit_update_insns<'a>(
         Operation::HashJoin(_) => {
             unreachable!("access through HashJoin is not supported for update operations")
         }
+        Operation::MultiIndexScan(_) => {
+            unreachable!("access through MultiIndexScan is not supported for update operations")
+        }
     };
 
     let beg = program.alloc_registers(
@@ -2486,6 +3135,18 @@ fn emit_update_insns<'a>(
             start_reg: beg,
             conflict_action: 0u16,
         });
+
+        if let Some(returning_columns) = &returning {
+            // The new rowid follows the old one in the VUpdate arguments.
+            emit_returning_results(
+                program,
+                table_references,
+                returning_columns,
+                start,
+                beg + 1,
+                &mut t_ctx.resolver,
+            )?;
+        }
     }
 
     if let Some(limit_ctx) = t_ctx.limit_ctx {
@@ -2494,7 +3155,6 @@ fn emit_update_insns<'a>(
             target_pc: t_ctx.label_main_loop_end.unwrap(),
         })
     }
-    // TODO(pthorpe): handle RETURNING clause
 
     if let Some(label) = check_rowid_not_exists_label {
         program.preassign_label_to_next_insn(label);
@@ -422,13 +422,16 @@ pub fn translate_condition_expr(
                 resolver,
             )?;
             program.preassign_label_to_next_insn(jump_target_when_true);
-            translate_condition_expr(
-                program,
-                referenced_tables,
-                rhs,
-                condition_metadata,
-                resolver,
-            )?;
+            // The right operand only runs when the left one is true.
+            resolver.conditionally(|| {
+                translate_condition_expr(
+                    program,
+                    referenced_tables,
+                    rhs,
+                    condition_metadata,
+                    resolver,
+                )
+            })?;
         }
         ast::Expr::Binary(lhs, ast::Operator::Or, rhs) => {
             // In a binary OR, never jump to the parent 'jump_target_when_false' label on the first condition, because
@@ -447,15 +450,27 @@ pub fn translate_condition_expr(
                 resolver,
             )?;
             program.preassign_label_to_next_insn(jump_target_when_false);
-            translate_condition_expr(
-                program,
-                referenced_tables,
-                rhs,
-                condition_metadata,
-                resolver,
-            )?;
+            // The right operand only runs when the left one is not true.
+            resolver.conditionally(|| {
+                translate_condition_expr(
+                    program,
+                    referenced_tables,
+                    rhs,
+                    condition_metadata,
+                    resolver,
+                )
+            })?;
         }
         ast::Expr::Binary(e1, op, e2) => {
+            if let Some(expanded) = expand_row_value_comparison(e1, *op, e2)? {
+                return translate_condition_expr(
+                    program,
+                    referenced_tables,
+                    &expanded,
+                    condition_metadata,
+                    resolver,
+                );
+            }
             let result_reg = program.alloc_register();
             binary_expr_shared(
                 program,
@@ -506,14 +521,17 @@ pub fn translate_condition_expr(
                 (condition_metadata, None, None)
             };
 
-            translate_in_list(
-                program,
-                Some(referenced_tables),
-                lhs,
-                rhs,
-                adjusted_metadata,
-                resolver,
-            )?;
+            // The list stops at the first match.
+            resolver.conditionally(|| {
+                translate_in_list(
+                    program,
+                    Some(referenced_tables),
+                    lhs,
+                    rhs,
+                    adjusted_metadata,
+                    resolver,
+                )
+            })?;
 
             if *not {
                 // When IN is TRUE (match found), NOT IN should be FALSE
@@ -645,6 +663,80 @@ pub fn translate_expr(
     expr: &ast::Expr,
     target_register: usize,
     resolver: &Resolver,
+) -> Result<usize> {
+    if let Some((reg, collation)) = resolver.cached_subexpr(expr) {
+        program.constant_span_end_all();
+        program.emit_insn(Insn::Copy {
+            src_reg: reg,
+            dst_reg: target_register,
+            extra_amount: 0,
+        });
+        if let Some(collation) = collation {
+            program.set_collation(collation);
+        }
+        return Ok(target_register);
+    }
+    if !resolver.should_cache_subexpr(expr) {
+        return translate_expr_scoped(program, referenced_tables, expr, target_register, resolver);
+    }
+    // The value gets a register of its own, as callers may overwrite the target register.
+    let reg = program.alloc_register();
+    let collation_before = program.curr_collation_ctx();
+    let value_reg = translate_expr_scoped(program, referenced_tables, expr, reg, resolver)?;
+    let collation = program.curr_collation_ctx();
+    resolver.cache_subexpr(
+        expr,
+        value_reg,
+        (collation != collation_before).then_some(collation),
+    );
+    program.emit_insn(Insn::Copy {
+        src_reg: value_reg,
+        dst_reg: target_register,
+        extra_amount: 0,
+    });
+    Ok(target_register)
+}
+
+/// Translates `expr`, without caching the subexpressions of the operands it may skip.
+fn translate_expr_scoped(
+    program: &mut ProgramBuilder,
+    referenced_tables: Option<&TableReferences>,
+    expr: &ast::Expr,
+    target_register: usize,
+    resolver: &Resolver,
+) -> Result<usize> {
+    if resolver.has_subexpr_cache() && evaluates_operands_conditionally(expr, resolver) {
+        resolver.conditionally(|| {
+            translate_expr_uncached(program, referenced_tables, expr, target_register, resolver)
+        })
+    } else {
+        translate_expr_uncached(program, referenced_tables, expr, target_register, resolver)
+    }
+}
+
+/// Whether the code of `expr` may skip the evaluation of some of its operands, like the
+/// branches of a CASE or the arguments of coalesce() after the first non-NULL one.
+fn evaluates_operands_conditionally(expr: &ast::Expr, resolver: &Resolver) -> bool {
+    match expr {
+        ast::Expr::Case { .. } | ast::Expr::InList { .. } | ast::Expr::SubqueryResult { .. } => {
+            true
+        }
+        ast::Expr::FunctionCall { name, args, .. } => matches!(
+            resolver.resolve_function(name.as_str(), args.len()),
+            Some(Func::Scalar(
+                ScalarFunc::Coalesce | ScalarFunc::IfNull | ScalarFunc::Iif
+            ))
+        ),
+        _ => false,
+    }
+}
+
+fn translate_expr_uncached(
+    program: &mut ProgramBuilder,
+    referenced_tables: Option<&TableReferences>,
+    expr: &ast::Expr,
+    target_register: usize,
+    resolver: &Resolver,
 ) -> Result<usize> {
     let constant_span = if expr.is_constant(resolver) {
         if !program.constant_span_is_open() {
@@ -855,6 +947,15 @@ pub fn translate_expr(
             crate::bail_parse_error!("expression should have been rewritten in optmizer")
         }
         ast::Expr::Binary(e1, op, e2) => {
+            if let Some(expanded) = expand_row_value_comparison(e1, *op, e2)? {
+                return translate_expr(
+                    program,
+                    referenced_tables,
+                    &expanded,
+                    target_register,
+                    resolver,
+                );
+            }
             binary_expr_shared(
                 program,
                 referenced_tables,
@@ -887,6 +988,7 @@ pub fn translate_expr(
             // case statement we're processing.
             let base_reg = base.as_ref().map(|_| program.alloc_register());
             let expr_reg = program.alloc_register();
+            let mut base_collation_ctx = None;
             if let Some(base_expr) = base {
                 translate_expr(
                     program,
@@ -895,8 +997,10 @@ pub fn translate_expr(
                     base_reg.unwrap(),
                     resolver,
                 )?;
+                base_collation_ctx = program.curr_collation_ctx();
             };
             for (when_expr, then_expr) in when_then_pairs {
+                program.reset_collation();
                 translate_expr_no_constant_opt(
                     program,
                     referenced_tables,
@@ -905,18 +1009,24 @@ pub fn translate_expr(
                     resolver,
                     NoConstantOptReason::RegisterReuse,
                 )?;
-                match base_reg {
-                    // CASE 1 WHEN 0 THEN 0 ELSE 1 becomes 1==0, Ne branch to next clause
-                    Some(base_reg) => program.emit_insn(Insn::Ne {
+                let when_collation_ctx = program.curr_collation_ctx();
+                program.reset_collation();
+                match (base, base_reg) {
+                    // CASE 1 WHEN 0 THEN 0 ELSE 1 becomes 1==0, Ne branch to next clause.
+                    // The comparison uses the same affinity and collation as `base = when`.
+                    (Some(base_expr), Some(base_reg)) => program.emit_insn(Insn::Ne {
                         lhs: base_reg,
                         rhs: expr_reg,
                         target_pc: next_case_label,
                         // A NULL result is considered untrue when evaluating WHEN terms.
-                        flags: CmpInsFlags::default().jump_if_null(),
-                        collation: program.curr_collation(),
+                        flags: CmpInsFlags::default().jump_if_null().with_affinity(
+                            comparison_affinity(base_expr, when_expr, referenced_tables),
+                        ),
+                        collation: comparison_collation_ctx(base_collation_ctx, when_collation_ctx)
+                            .map(|(collation, _)| collation),
                     }),
                     // CASE WHEN 0 THEN 0 ELSE 1 becomes ifnot 0 branch to next clause
-                    None => program.emit_insn(Insn::IfNot {
+                    _ => program.emit_insn(Insn::IfNot {
                         reg: expr_reg,
                         target_pc: next_case_label,
                         jump_if_null: true,
@@ -1548,6 +1658,49 @@ pub fn translate_expr(
                             });
                             Ok(target_register)
                         }
+                        #[cfg(feature = "uuid")]
+                        ScalarFunc::Uuid4 | ScalarFunc::Uuid4Str | ScalarFunc::Ulid => {
+                            if !args.is_empty() {
+                                crate::bail_parse_error!(
+                                    "{} function with arguments",
+                                    srf.to_string()
+                                );
+                            }
+                            let regs = program.alloc_register();
+                            program.emit_insn(Insn::Function {
+                                constant_mask: 0,
+                                start_reg: regs,
+                                dest: target_register,
+                                func: func_ctx,
+                            });
+                            Ok(target_register)
+                        }
+                        #[cfg(feature = "uuid")]
+                        ScalarFunc::Uuid7 | ScalarFunc::Uuid7Str => {
+                            if args.len() > 1 {
+                                crate::bail_parse_error!(
+                                    "{} function called with more than 1 arguments",
+                                    srf.to_string()
+                                );
+                            }
+                            let start_reg = program.alloc_registers(args.len().max(1));
+                            if let Some(arg) = args.first() {
+                                translate_expr(
+                                    program,
+                                    referenced_tables,
+                                    arg,
+                                    start_reg,
+                                    resolver,
+                                )?;
+                            }
+                            program.emit_insn(Insn::Function {
+                                constant_mask: 0,
+                                start_reg,
+                                dest: target_register,
+                                func: func_ctx,
+                            });
+                            Ok(target_register)
+                        }
                         ScalarFunc::Random => {
                             if !args.is_empty() {
                                 crate::bail_parse_error!(
@@ -1861,6 +2014,40 @@ pub fn translate_expr(
                             });
                             Ok(target_register)
                         }
+                        #[cfg(feature = "regexp")]
+                        ScalarFunc::Regexp => {
+                            let args = expect_arguments_exact!(args, 2, srf);
+                            let start_reg = program.alloc_registers(2);
+                            translate_expr(
+                                program,
+                                referenced_tables,
+                                &args[0],
+                                start_reg,
+                                resolver,
+                            )?;
+                            // The compiled pattern is cached when it is a literal.
+                            let constant_mask = if matches!(args[0].as_ref(), ast::Expr::Literal(_))
+                            {
+                                program.mark_last_insn_constant();
+                                1
+                            } else {
+                                0
+                            };
+                            translate_expr(
+                                program,
+                                referenced_tables,
+                                &args[1],
+                                start_reg + 1,
+                                resolver,
+                            )?;
+                            program.emit_insn(Insn::Function {
+                                constant_mask,
+                                start_reg,
+                                dest: target_register,
+                                func: func_ctx,
+                            });
+                            Ok(target_register)
+                        }
                         ScalarFunc::Replace => {
                             if !args.len() == 3 {
                                 crate::bail_parse_error!(
@@ -2541,11 +2728,9 @@ pub fn translate_expr(
                     resolver,
                 )?;
             } else {
-                // Parenthesized expressions with multiple arguments are reserved for special cases
-                // like `(a, b) IN ((1, 2), (3, 4))`.
-                crate::bail_parse_error!(
-                    "TODO: parenthesized expression with multiple arguments not yet supported"
-                );
+                // Row values can only be compared with each other, or be the left-hand side of
+                // an IN (SELECT ...).
+                crate::bail_parse_error!("row value misused");
             }
             Ok(target_register)
         }
@@ -2733,18 +2918,7 @@ fn binary_expr_shared(
          *
          * 3. Otherwise, the BINARY collating function is used for comparison.
          */
-        let collation_ctx = {
-            match (left_collation_ctx, right_collation_ctx) {
-                (Some((c_left, true)), _) => Some((c_left, true)),
-                (_, Some((c_right, true))) => Some((c_right, true)),
-                (Some((c_left, from_collate_left)), None) => Some((c_left, from_collate_left)),
-                (None, Some((c_right, from_collate_right))) => Some((c_right, from_collate_right)),
-                (Some((c_left, from_collate_left)), Some((_, false))) => {
-                    Some((c_left, from_collate_left))
-                }
-                _ => None,
-            }
-        };
+        let collation_ctx = comparison_collation_ctx(left_collation_ctx, right_collation_ctx);
         program.set_collation(collation_ctx);
 
         emit_fn(
@@ -2763,6 +2937,22 @@ fn binary_expr_shared(
     }
 }
 
+/// Picks the collation of a comparison from the collation contexts of its operands, following
+/// the rules described in [binary_expr_shared].
+fn comparison_collation_ctx(
+    left_collation_ctx: Option<(CollationSeq, bool)>,
+    right_collation_ctx: Option<(CollationSeq, bool)>,
+) -> Option<(CollationSeq, bool)> {
+    match (left_collation_ctx, right_collation_ctx) {
+        (Some((c_left, true)), _) => Some((c_left, true)),
+        (_, Some((c_right, true))) => Some((c_right, true)),
+        (Some((c_left, from_collate_left)), None) => Some((c_left, from_collate_left)),
+        (None, Some((c_right, from_collate_right))) => Some((c_right, from_collate_right)),
+        (Some((c_left, from_collate_left)), Some((_, false))) => Some((c_left, from_collate_left)),
+        _ => None,
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn emit_binary_insn(
     program: &mut ProgramBuilder,
@@ -3015,7 +3205,9 @@ fn emit_binary_insn(
                 dest: target_register,
             });
         }
-        other_unimplemented => todo!("{:?}", other_unimplemented),
+        other_unimplemented => {
+            crate::bail_parse_error!("binary operator {:?} is not supported", other_unimplemented)
+        }
     }
 
     Ok(())
@@ -3286,7 +3478,9 @@ fn emit_binary_condition_insn(
             });
             eval_result(program, target_register);
         }
-        other_unimplemented => todo!("{:?}", other_unimplemented),
+        other_unimplemented => {
+            crate::bail_parse_error!("binary operator {:?} is not supported", other_unimplemented)
+        }
     }
 
     Ok(())
@@ -3345,6 +3539,31 @@ fn translate_like_base(
             });
         }
         ast::LikeOperator::Match => crate::bail_parse_error!("MATCH in LIKE is not supported"),
+        #[cfg(feature = "regexp")]
+        ast::LikeOperator::Regexp => {
+            if escape.is_some() {
+                crate::bail_parse_error!("ESCAPE is not supported with REGEXP");
+            }
+            // Like in SQLite, `x REGEXP y` is evaluated as regexp(y, x).
+            let start_reg = program.alloc_registers(2);
+            translate_expr(program, referenced_tables, rhs, start_reg, resolver)?;
+            let mut constant_mask = 0;
+            if matches!(rhs.as_ref(), ast::Expr::Literal(_)) {
+                program.mark_last_insn_constant();
+                constant_mask = 1;
+            }
+            translate_expr(program, referenced_tables, lhs, start_reg + 1, resolver)?;
+            program.emit_insn(Insn::Function {
+                constant_mask,
+                start_reg,
+                dest: target_register,
+                func: FuncCtx {
+                    func: Func::Scalar(ScalarFunc::Regexp),
+                    arg_count: 2,
+                },
+            });
+        }
+        #[cfg(not(feature = "regexp"))]
         ast::LikeOperator::Regexp => crate::bail_parse_error!("REGEXP in LIKE is not supported"),
     }
 
@@ -3440,6 +3659,91 @@ pub fn sanitize_string(input: &str) -> String {
     inner.replace("''", "'")
 }
 
+/// The elements of `expr` if it is a row value: a parenthesized list of two or more expressions,
+/// or a subquery that returns two or more columns.
+fn row_value_elements(expr: &ast::Expr) -> Option<Vec<ast::Expr>> {
+    match expr {
+        ast::Expr::Parenthesized(exprs) if exprs.len() > 1 => {
+            Some(exprs.iter().map(|e| e.as_ref().clone()).collect())
+        }
+        ast::Expr::Parenthesized(exprs) if exprs.len() == 1 => row_value_elements(&exprs[0]),
+        ast::Expr::SubqueryResult {
+            query_type:
+                SubqueryType::RowValue {
+                    result_reg_start,
+                    num_regs,
+                },
+            ..
+        } if *num_regs > 1 => Some(
+            (0..*num_regs)
+                .map(|i| ast::Expr::Register(result_reg_start + i))
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+/// Rewrites a comparison of two row values as a comparison of their elements, e.g.
+/// `(a, b) = (1, 2)` as `a = 1 AND b = 2`, and `(a, b) < (1, 2)` as
+/// `a < 1 OR (a = 1 AND b < 2)`. Like in SQLite, row values are ordered by their first
+/// unequal element, and the result is NULL if a NULL element is compared before the result is
+/// known. Returns `None` if neither operand is a row value.
+pub fn expand_row_value_comparison(
+    lhs: &ast::Expr,
+    op: ast::Operator,
+    rhs: &ast::Expr,
+) -> Result<Option<ast::Expr>> {
+    let (lhs, rhs) = match (row_value_elements(lhs), row_value_elements(rhs)) {
+        (None, None) => return Ok(None),
+        (Some(lhs), Some(rhs)) if lhs.len() == rhs.len() => (lhs, rhs),
+        _ => crate::bail_parse_error!("row value misused"),
+    };
+    let binary = |lhs: &ast::Expr, op: ast::Operator, rhs: &ast::Expr| {
+        ast::Expr::Binary(Box::new(lhs.clone()), op, Box::new(rhs.clone()))
+    };
+    let pairs = lhs.iter().zip(rhs.iter());
+    let expanded = match op {
+        ast::Operator::Equals
+        | ast::Operator::Is
+        | ast::Operator::NotEquals
+        | ast::Operator::IsNot => {
+            let join = if matches!(op, ast::Operator::Equals | ast::Operator::Is) {
+                ast::Operator::And
+            } else {
+                ast::Operator::Or
+            };
+            pairs
+                .map(|(l, r)| binary(l, op, r))
+                .reduce(|acc, e| ast::Expr::Binary(Box::new(acc), join, Box::new(e)))
+                .expect("row values have at least two elements")
+        }
+        ast::Operator::Less
+        | ast::Operator::LessEquals
+        | ast::Operator::Greater
+        | ast::Operator::GreaterEquals => {
+            let strict = match op {
+                ast::Operator::Less | ast::Operator::LessEquals => ast::Operator::Less,
+                _ => ast::Operator::Greater,
+            };
+            let mut pairs = pairs.rev();
+            let (l, r) = pairs.next().expect("row values have at least two elements");
+            pairs.fold(binary(l, op, r), |rest, (l, r)| {
+                ast::Expr::Binary(
+                    Box::new(binary(l, strict, r)),
+                    ast::Operator::Or,
+                    Box::new(ast::Expr::Binary(
+                        Box::new(binary(l, ast::Operator::Equals, r)),
+                        ast::Operator::And,
+                        Box::new(rest),
+                    )),
+                )
+            })
+        }
+        _ => crate::bail_parse_error!("row value misused"),
+    };
+    Ok(Some(expanded))
+}
+
 /// Returns the components of a binary expression
 /// e.g. t.x = 5 -> Some((t.x, =, 5))
 pub fn as_binary_components(
@@ -3793,8 +4097,8 @@ pub fn bind_and_rewrite_expr<'a>(
                             }
                         // only if we haven't found a match, check for explicit rowid reference
                         } else {
-                            let is_btree_table = matches!(joined_table.table, Table::BTree(_));
-                            if is_btree_table {
+                            let has_rowid = matches!(&joined_table.table, Table::BTree(btree) if btree.has_rowid);
+                            if has_rowid {
                                 if let Some(row_id_expr) = parse_row_id(
                                     &normalized_id,
                                     referenced_tables.joined_tables()[0].internal_id,
@@ -3903,10 +4207,13 @@ pub fn bind_and_rewrite_expr<'a>(
                             .as_ref()
                             .is_some_and(|name| name.eq_ignore_ascii_case(&normalized_id))
                     });
-                    if let Some(row_id_expr) = parse_row_id(&normalized_id, tbl_id, || false)? {
-                        *expr = row_id_expr;
+                    let has_rowid = tbl.btree().is_none_or(|btree| btree.has_rowid);
+                    if has_rowid {
+                        if let Some(row_id_expr) = parse_row_id(&normalized_id, tbl_id, || false)? {
+                            *expr = row_id_expr;
 
-                        return Ok(WalkControl::Continue);
+                            return Ok(WalkControl::Continue);
+                        }
                     }
                     let Some(col_idx) = col_idx else {
                         crate::bail_parse_error!("no such column: {}", normalized_id);
@@ -4598,7 +4905,7 @@ pub fn expr_vector_size(expr: &Expr) -> Result<usize> {
             1
         }
         Expr::DoublyQualified(..) => 1,
-        Expr::Exists(_) => todo!(),
+        Expr::Exists(_) => 1,
         Expr::FunctionCall { name, args, .. } => {
             for (pos, arg) in args.iter().enumerate() {
                 let evs_arg = expr_vector_size(arg)?;
@@ -4669,7 +4976,9 @@ pub fn expr_vector_size(expr: &Expr) -> Result<usize> {
         Expr::Parenthesized(exprs) => exprs.len(),
         Expr::Qualified(..) => 1,
         Expr::Raise(..) => crate::bail_parse_error!("RAISE is not supported"),
-        Expr::Subquery(_) => todo!(),
+        Expr::Subquery(_) => {
+            crate::bail_parse_error!("subqueries are not supported in this context")
+        }
         Expr::Unary(unary_operator, expr) => {
             let evs_expr = expr_vector_size(expr)?;
             if evs_expr != 1 {
@@ -17,6 +17,7 @@ use crate::translate::{
     optimizer::Optimizable,
 };
 use crate::{
+    emit_explain,
     schema::PseudoCursorType,
     translate::collate::{get_collseq_from_expr, CollationSeq},
     util::exprs_are_equivalent,
@@ -25,7 +26,7 @@ use crate::{
         insn::Insn,
         BranchOffset,
     },
-    Result,
+    QueryMode, Result,
 };
 use crate::{translate::plan::ResultSetColumn, types::KeyInfo};
 
@@ -714,6 +715,7 @@ pub fn group_by_agg_phase(
     let label_sort_loop_end = labels.label_sort_loop_end;
 
     if matches!(row_source, GroupByRowSource::Sorter { .. }) {
+        emit_explain!(program, false, "USE TEMP B-TREE FOR GROUP BY".to_owned());
         emit_group_by_sort_loop_start(program, row_source, label_sort_loop_end)?;
     }
 
@@ -35,12 +35,48 @@ use turso_parser::ast::{self, Expr, SortOrder, SortedColumn};
 
 use super::schema::{emit_schema_entry, SchemaEntryType, SQLITE_TABLEID};
 
+/// The database a new index goes into: the one its name is qualified with, otherwise the one
+/// holding its table, since the table name itself cannot be qualified.
+pub(crate) fn create_index_database_id(
+    idx_name: &ast::QualifiedName,
+    tbl_name: &ast::Name,
+    connection: &crate::Connection,
+) -> crate::Result<usize> {
+    if idx_name.db_name.is_some() {
+        return connection.resolve_database_id(idx_name);
+    }
+    connection.resolve_database_id(&ast::QualifiedName::single(tbl_name.clone()))
+}
+
+/// The database the index named by a DROP INDEX statement lives in.
+pub(crate) fn drop_index_database_id(
+    idx_name: &ast::QualifiedName,
+    connection: &crate::Connection,
+) -> crate::Result<usize> {
+    connection.resolve_object_database_id(idx_name, |schema, name| {
+        schema
+            .indexes
+            .values()
+            .flatten()
+            .any(|index| index.name == name)
+    })
+}
+
 pub fn translate_create_index(
     mut program: ProgramBuilder,
     connection: &Arc<crate::Connection>,
     resolver: &Resolver,
-    stmt: ast::Stmt,
+    mut stmt: ast::Stmt,
 ) -> crate::Result<ProgramBuilder> {
+    let ast::Stmt::CreateIndex {
+        idx_name, tbl_name, ..
+    } = &mut stmt
+    else {
+        panic!("translate_create_index must be called with CreateIndex AST node");
+    };
+    let database_id = create_index_database_id(idx_name, tbl_name, connection)?;
+    // Like SQLite, the stored SQL doesn't name the database the index lives in.
+    idx_name.db_name = None;
     let sql = stmt.to_string();
     let ast::Stmt::CreateIndex {
         unique,
@@ -102,7 +138,35 @@ pub fn translate_create_index(
     let Some(tbl) = table.btree() else {
         crate::bail_parse_error!("Error: table '{tbl_name}' is not a b-tree table.");
     };
-    let columns = resolve_sorted_columns(&tbl, &columns)?;
+    let mut columns = resolve_sorted_columns(&tbl, &columns)?;
+    // Entries of an index on a WITHOUT ROWID table point at their row by its primary key, which
+    // is read from the primary key index the rows are stored in.
+    let primary_key_index = if tbl.has_rowid {
+        None
+    } else {
+        if unique
+            || using.is_some()
+            || where_clause.is_some()
+            || columns.iter().any(|col| col.expr.is_some())
+        {
+            crate::bail_parse_error!(
+                "UNIQUE, partial, expression and custom indexes on WITHOUT ROWID tables are not supported"
+            );
+        }
+        Index::append_primary_key_columns(&tbl, &mut columns)?;
+        let primary_key_index = resolver
+            .schema
+            .get_indices(&tbl.name)
+            .find(|index| index.is_without_rowid_primary_key(&tbl))
+            .cloned()
+            .ok_or_else(|| {
+                crate::LimboError::InternalError(format!(
+                    "primary key index of WITHOUT ROWID table {} not found",
+                    tbl.name
+                ))
+            })?;
+        Some(primary_key_index)
+    };
     if !with_clause.is_empty() && using.is_none() {
         crate::bail_parse_error!(
             "Error: additional parameters are allowed only for custom module indices: '{idx_name}' is not custom module index"
@@ -163,10 +227,13 @@ pub fn translate_create_index(
         program.alloc_cursor_id(CursorType::BTreeTable(sqlite_table.clone()));
     let table_ref = program.table_reference_counter.next();
     let index_cursor_id = program.alloc_cursor_index(None, &idx)?;
-    let table_cursor_id = program.alloc_cursor_id_keyed(
-        CursorKey::table(table_ref),
-        CursorType::BTreeTable(tbl.clone()),
-    );
+    let table_cursor_id = match &primary_key_index {
+        Some(primary_key_index) => program.alloc_cursor_index(None, primary_key_index)?,
+        None => program.alloc_cursor_id_keyed(
+            CursorKey::table(table_ref),
+            CursorType::BTreeTable(tbl.clone()),
+        ),
+    };
     let sorter_cursor_id = program.alloc_cursor_id(CursorType::Sorter);
     let pseudo_cursor_id = program.alloc_cursor_id(CursorType::Pseudo(PseudoCursorType {
         column_count: tbl.columns.len(),
@@ -185,7 +252,7 @@ pub fn translate_create_index(
             col_used_mask: ColumnUsedMask::default(),
             column_use_counts: Vec::new(),
             expression_index_usages: Vec::new(),
-            database_id: 0,
+            database_id,
         }],
         vec![],
     );
@@ -195,14 +262,14 @@ pub fn translate_create_index(
     let root_page_reg = program.alloc_register();
     if idx.index_method.is_some() && !idx.is_backing_btree_index() {
         program.emit_insn(Insn::IndexMethodCreate {
-            db: 0,
+            db: database_id,
             cursor_id: index_cursor_id,
         });
         // index method sqlite_schema row always has root_page equals to zero in the schema (same as virtual tables)
         program.emit_int(0, root_page_reg);
     } else {
         program.emit_insn(Insn::CreateBtree {
-            db: 0,
+            db: database_id,
             root: root_page_reg,
             flags: CreateBTreeFlags::new_index(),
         });
@@ -212,7 +279,7 @@ pub fn translate_create_index(
     program.emit_insn(Insn::OpenWrite {
         cursor_id: sqlite_schema_cursor_id,
         root_page: RegisterOrLiteral::Literal(sqlite_table.root_page),
-        db: 0,
+        db: database_id,
     });
     let cdc_table = prepare_cdc_if_necessary(&mut program, resolver.schema, SQLITE_TABLEID)?;
     emit_schema_entry(
@@ -235,7 +302,7 @@ pub fn translate_create_index(
         program.emit_insn(Insn::OpenRead {
             cursor_id: table_cursor_id,
             root_page: tbl.root_page,
-            db: 0,
+            db: database_id,
         });
 
         // Open the index btree we created for writing to insert the
@@ -243,7 +310,7 @@ pub fn translate_create_index(
         program.emit_insn(Insn::OpenWrite {
             cursor_id: index_cursor_id,
             root_page: RegisterOrLiteral::Register(root_page_reg),
-            db: 0,
+            db: database_id,
         });
 
         let loop_start_label = program.allocate_label();
@@ -333,14 +400,14 @@ pub fn translate_create_index(
         program.emit_insn(Insn::OpenPseudo {
             cursor_id: pseudo_cursor_id,
             content_reg,
-            num_fields: columns.len() + 1,
+            num_fields: columns.len() + usize::from(tbl.has_rowid),
         });
 
         // open the table we are creating the index on for reading
         program.emit_insn(Insn::OpenRead {
             cursor_id: table_cursor_id,
             root_page: tbl.root_page,
-            db: 0,
+            db: database_id,
         });
 
         let loop_start_label = program.allocate_label();
@@ -374,8 +441,16 @@ pub fn translate_create_index(
             skip_row_label = Some(label);
         }
 
-        let start_reg = program.alloc_registers(columns.len() + 1);
+        let record_len = columns.len() + usize::from(tbl.has_rowid);
+        let start_reg = program.alloc_registers(record_len);
         for (i, col) in columns.iter().enumerate() {
+            if let Some(primary_key_index) = &primary_key_index {
+                let pos_in_primary_key = primary_key_index
+                    .column_table_pos_to_index_pos(col.pos_in_table)
+                    .expect("primary key index stores every column of the table");
+                program.emit_column_or_rowid(table_cursor_id, pos_in_primary_key, start_reg + i);
+                continue;
+            }
             emit_index_column_value_from_cursor(
                 &mut program,
                 resolver,
@@ -386,15 +461,16 @@ pub fn translate_create_index(
                 start_reg + i,
             )?;
         }
-        let rowid_reg = start_reg + columns.len();
-        program.emit_insn(Insn::RowId {
-            cursor_id: table_cursor_id,
-            dest: rowid_reg,
-        });
+        if tbl.has_rowid {
+            program.emit_insn(Insn::RowId {
+                cursor_id: table_cursor_id,
+                dest: start_reg + columns.len(),
+            });
+        }
         let record_reg = program.alloc_register();
         program.emit_insn(Insn::MakeRecord {
             start_reg: to_u16(start_reg),
-            count: to_u16(columns.len() + 1),
+            count: to_u16(record_len),
             dest_reg: to_u16(record_reg),
             index_name: Some(idx_name.clone()),
             affinity_str: None,
@@ -418,7 +494,7 @@ pub fn translate_create_index(
         program.emit_insn(Insn::OpenWrite {
             cursor_id: index_cursor_id,
             root_page: RegisterOrLiteral::Register(root_page_reg),
-            db: 0,
+            db: database_id,
         });
 
         let sorted_loop_start = program.allocate_label();
@@ -488,7 +564,7 @@ pub fn translate_create_index(
     program.close_cursors(&[sorter_cursor_id, table_cursor_id, index_cursor_id]);
 
     program.emit_insn(Insn::SetCookie {
-        db: 0,
+        db: database_id,
         cookie: Cookie::SchemaVersion,
         value: resolver.schema.schema_version as i32 + 1,
         p5: 0,
@@ -496,7 +572,7 @@ pub fn translate_create_index(
     // Parse the schema table to get the index root page and add new index to Schema
     let parse_schema_where_clause = format!("name = '{idx_name}' AND type = 'index'");
     program.emit_insn(Insn::ParseSchema {
-        db: sqlite_schema_cursor_id,
+        db: database_id,
         where_clause: Some(parse_schema_where_clause),
     });
     // Close the final sqlite_schema cursor
@@ -721,12 +797,14 @@ pub fn resolve_index_method_parameters(
 }
 
 pub fn translate_drop_index(
-    idx_name: &str,
+    idx_name: &ast::QualifiedName,
     resolver: &Resolver,
     if_exists: bool,
     mut program: ProgramBuilder,
+    connection: &Arc<crate::Connection>,
 ) -> crate::Result<ProgramBuilder> {
-    let idx_name = normalize_ident(idx_name);
+    let database_id = drop_index_database_id(idx_name, connection)?;
+    let idx_name = normalize_ident(idx_name.name.as_str());
     let opts = crate::vdbe::builder::ProgramBuilderOpts {
         num_cursors: 5,
         approx_num_insns: 40,
@@ -790,11 +868,11 @@ pub fn translate_drop_index(
     let sqlite_schema_cursor_id =
         program.alloc_cursor_id(CursorType::BTreeTable(sqlite_table.clone()));
 
-    // Open root=1 iDb=0; sqlite_schema for writing
+    // Open root=1; sqlite_schema of the index's database for writing
     program.emit_insn(Insn::OpenWrite {
         cursor_id: sqlite_schema_cursor_id,
         root_page: RegisterOrLiteral::Literal(sqlite_table.root_page),
-        db: 0,
+        db: database_id,
     });
 
     let loop_start_label = program.allocate_label();
@@ -882,7 +960,7 @@ pub fn translate_drop_index(
     program.resolve_label(loop_end_label, program.offset());
 
     program.emit_insn(Insn::SetCookie {
-        db: 0,
+        db: database_id,
         cookie: Cookie::SchemaVersion,
         value: resolver.schema.schema_version as i32 + 1,
         p5: 0,
@@ -891,20 +969,23 @@ pub fn translate_drop_index(
     let index = maybe_index.unwrap();
     if index.index_method.is_some() && !index.is_backing_btree_index() {
         let cursor_id = program.alloc_cursor_index(None, index)?;
-        program.emit_insn(Insn::IndexMethodDestroy { db: 0, cursor_id });
+        program.emit_insn(Insn::IndexMethodDestroy {
+            db: database_id,
+            cursor_id,
+        });
     } else {
         // Destroy index btree
         program.emit_insn(Insn::Destroy {
             root: index.root_page,
             former_root_reg: 0,
-            is_temp: 0,
+            db: database_id,
         });
     }
 
     // Remove from the Schema any mention of the index
     program.emit_insn(Insn::DropIndex {
         index: index.clone(),
-        db: 0,
+        db: database_id,
     });
 
     Ok(program)
@@ -3,8 +3,9 @@ use crate::{
     schema::{self, BTreeTable, ColDef, Column, Index, IndexColumn, ResolvedFkRef, Schema, Table},
     translate::{
         emitter::{
-            emit_cdc_full_record, emit_cdc_insns, emit_cdc_patch_record, prepare_cdc_if_necessary,
-            OperationMode, Resolver,
+            emit_cdc_full_record, emit_cdc_insns, emit_cdc_patch_record,
+            emit_without_rowid_index_entry, prepare_cdc_if_necessary,
+            without_rowid_primary_key_index, OperationMode, Resolver,
         },
         expr::{
             bind_and_rewrite_expr, emit_returning_results, process_returning_clause,
@@ -26,8 +27,8 @@ use crate::{
             TriggerContext,
         },
         upsert::{
-            collect_set_clauses_for_upsert, emit_upsert, resolve_upsert_target,
-            ResolvedUpsertTarget,
+            collect_set_clauses_for_upsert, emit_upsert, emit_without_rowid_upsert,
+            resolve_upsert_target, ResolvedUpsertTarget,
         },
     },
     util::normalize_ident,
@@ -53,7 +54,7 @@ use turso_parser::ast::{
 };
 
 /// Validate anything with this insert statement that should throw an early parse error
-fn validate(table_name: &str, resolver: &Resolver, table: &Table) -> Result<()> {
+fn validate(table_name: &str, resolver: &Resolver) -> Result<()> {
     // Check if this is a system table that should be protected from direct writes
     if !crate::schema::can_write_to_table(table_name) {
         crate::bail_parse_error!("table {} may not be modified", table_name);
@@ -76,13 +77,39 @@ fn validate(table_name: &str, resolver: &Resolver, table: &Table) -> Result<()>
     if resolver.schema.is_materialized_view(table_name) {
         crate::bail_parse_error!("cannot modify materialized view {}", table_name);
     }
-    if table.btree().is_some_and(|t| !t.has_rowid) {
-        crate::bail_parse_error!("INSERT into WITHOUT ROWID table is not supported");
-    }
 
     Ok(())
 }
 
+/// Rejects the parts of INSERT that are not implemented yet for WITHOUT ROWID tables. They all
+/// rely on locating rows by rowid, which WITHOUT ROWID tables do not have.
+fn validate_without_rowid_insert(
+    resolver: &Resolver,
+    table: &BTreeTable,
+    has_fks: bool,
+    has_cdc: bool,
+) -> Result<()> {
+    if has_fks {
+        crate::bail_parse_error!(
+            "INSERT into WITHOUT ROWID table with foreign keys is not supported"
+        );
+    }
+    if has_cdc {
+        crate::bail_parse_error!("change data capture on WITHOUT ROWID table is not supported");
+    }
+    if has_relevant_triggers_type_only(resolver.schema, TriggerEvent::Insert, None, table) {
+        crate::bail_parse_error!("triggers on WITHOUT ROWID table are not supported");
+    }
+    if !resolver
+        .schema
+        .get_dependent_materialized_views(&table.name)
+        .is_empty()
+    {
+        crate::bail_parse_error!("materialized views on WITHOUT ROWID table are not supported");
+    }
+    Ok(())
+}
+
 pub struct TempTableCtx {
     cursor_id: usize,
     loop_start_label: BranchOffset,
@@ -129,8 +156,15 @@ pub struct InsertEmitCtx<'a> {
     pub yield_reg_opt: Option<usize>,
     /// The register to hold the rowid of a conflicting row
     pub conflict_rowid_reg: usize,
+    /// The index that stores the rows of a WITHOUT ROWID table
+    pub without_rowid_pk_index: Option<Arc<Index>>,
+    /// The first of the registers holding the primary key of a conflicting row of a WITHOUT
+    /// ROWID table, in the order of its primary key index
+    pub conflict_key_reg: usize,
     /// The cursor id of the table being inserted into
     pub cursor_id: usize,
+    /// The database holding the table being inserted into
+    pub database_id: usize,
 
     /// Label to jump to on HALT
     pub halt_label: BranchOffset,
@@ -146,10 +180,12 @@ pub struct InsertEmitCtx<'a> {
 }
 
 impl<'a> InsertEmitCtx<'a> {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         program: &mut ProgramBuilder,
         resolver: &Resolver,
         table: &'a Arc<BTreeTable>,
+        database_id: usize,
         on_conflict: Option<ResolveType>,
         cdc_table: Option<(usize, Arc<BTreeTable>)>,
         num_values: usize,
@@ -175,6 +211,16 @@ impl<'a> InsertEmitCtx<'a> {
             key_ready_for_check: program.allocate_label(),
             key_generation: program.allocate_label(),
         };
+        let without_rowid_pk_index = if table.has_rowid {
+            None
+        } else {
+            Some(without_rowid_primary_key_index(resolver, table)?)
+        };
+        let conflict_key_reg = program.alloc_registers(if table.has_rowid {
+            0
+        } else {
+            table.primary_key_columns.len()
+        });
         Ok(Self {
             table,
             idx_cursors,
@@ -182,7 +228,10 @@ impl<'a> InsertEmitCtx<'a> {
             on_conflict: on_conflict.unwrap_or(ResolveType::Abort),
             yield_reg_opt: None,
             conflict_rowid_reg: program.alloc_register(),
+            without_rowid_pk_index,
+            conflict_key_reg,
             cursor_id: 0, // set later in emit_source_emission
+            database_id,
             halt_label: program.allocate_label(),
             loop_labels,
             key_labels,
@@ -211,12 +260,13 @@ pub fn translate_insert(
     };
     program.extend(&opts);
 
+    let database_id = connection.resolve_database_id(&tbl_name)?;
     let table_name = &tbl_name.name;
     let table = match resolver.schema.get_table(table_name.as_str()) {
         Some(table) => table,
         None => crate::bail_parse_error!("no such table: {}", table_name),
     };
-    validate(table_name.as_str(), resolver, &table)?;
+    validate(table_name.as_str(), resolver)?;
 
     let fk_enabled = connection.foreign_keys_enabled();
     if let Some(virtual_table) = &table.virtual_table() {
@@ -225,8 +275,10 @@ pub fn translate_insert(
             virtual_table.clone(),
             columns,
             body,
+            returning,
             on_conflict,
             resolver,
+            connection,
         )?;
         return Ok(program);
     }
@@ -249,7 +301,7 @@ pub fn translate_insert(
     )?;
 
     if inserting_multiple_rows && btree_table.has_autoincrement {
-        ensure_sequence_initialized(&mut program, resolver.schema, &btree_table)?;
+        ensure_sequence_initialized(&mut program, resolver.schema, &btree_table, database_id)?;
     }
 
     let cdc_table = prepare_cdc_if_necessary(&mut program, resolver.schema, table.get_name())?;
@@ -268,7 +320,7 @@ pub fn translate_insert(
             col_used_mask: ColumnUsedMask::default(),
             column_use_counts: Vec::new(),
             expression_index_usages: Vec::new(),
-            database_id: 0,
+            database_id,
         }],
         vec![],
     );
@@ -282,10 +334,15 @@ pub fn translate_insert(
                 .schema
                 .any_resolved_fks_referencing(table_name.as_str()));
 
+    if !btree_table.has_rowid {
+        validate_without_rowid_insert(resolver, &btree_table, has_fks, cdc_table.is_some())?;
+    }
+
     let mut ctx = InsertEmitCtx::new(
         &mut program,
         resolver,
         &btree_table,
+        database_id,
         on_conflict,
         cdc_table,
         values.len(),
@@ -384,7 +441,16 @@ pub fn translate_insert(
 
     program.preassign_label_to_next_insn(ctx.key_labels.key_generation);
 
-    emit_rowid_generation(&mut program, resolver, &ctx, &insertion)?;
+    if btree_table.has_rowid {
+        emit_rowid_generation(&mut program, resolver, &ctx, &insertion)?;
+    } else {
+        // Rows of a WITHOUT ROWID table are keyed by their primary key, which is written
+        // together with the rest of the row into the primary key index below.
+        program.emit_insn(Insn::Null {
+            dest: insertion.key_register(),
+            dest_end: None,
+        });
+    }
 
     program.preassign_label_to_next_insn(ctx.key_labels.key_ready_for_check);
 
@@ -459,13 +525,15 @@ pub fn translate_insert(
     if let Some(lbl) = notnull_resume_label {
         program.preassign_label_to_next_insn(lbl);
     }
-    program.emit_insn(Insn::MakeRecord {
-        start_reg: to_u16(insertion.first_col_register()),
-        count: to_u16(insertion.col_mappings.len()),
-        dest_reg: to_u16(insertion.record_register()),
-        index_name: None,
-        affinity_str: Some(affinity_str),
-    });
+    if btree_table.has_rowid {
+        program.emit_insn(Insn::MakeRecord {
+            start_reg: to_u16(insertion.first_col_register()),
+            count: to_u16(insertion.col_mappings.len()),
+            dest_reg: to_u16(insertion.record_register()),
+            index_name: None,
+            affinity_str: Some(affinity_str),
+        });
+    }
 
     if has_upsert {
         emit_commit_phase(&mut program, resolver, &insertion, &ctx)?;
@@ -490,13 +558,15 @@ pub fn translate_insert(
     if on_replace {
         insert_flags = insert_flags.require_seek();
     }
-    program.emit_insn(Insn::Insert {
-        cursor: ctx.cursor_id,
-        key_reg: insertion.key_register(),
-        record_reg: insertion.record_register(),
-        flag: insert_flags,
-        table_name: table_name.to_string(),
-    });
+    if btree_table.has_rowid {
+        program.emit_insn(Insn::Insert {
+            cursor: ctx.cursor_id,
+            key_reg: insertion.key_register(),
+            record_reg: insertion.record_register(),
+            flag: insert_flags,
+            table_name: table_name.to_string(),
+        });
+    }
 
     // Fire AFTER INSERT triggers
     let relevant_after_triggers = get_relevant_triggers_type_and_time(
@@ -735,29 +805,13 @@ fn emit_commit_phase(
         // Re-evaluate partial predicate on the would-be inserted image
         let commit_skip_label = emit_partial_index_check(program, resolver, index, insertion)?;
 
-        let num_cols = index.columns.len();
-        let idx_start_reg = program.alloc_registers(num_cols + 1);
-
-        // Build [key cols..., rowid] from insertion registers
-        for (i, idx_col) in index.columns.iter().enumerate() {
-            emit_index_column_value_for_insert(
-                program,
-                resolver,
-                insertion,
-                idx_col,
-                idx_start_reg + i,
-            )?;
-        }
-        program.emit_insn(Insn::Copy {
-            src_reg: insertion.key_register(),
-            dst_reg: idx_start_reg + num_cols,
-            extra_amount: 0,
-        });
+        let (idx_start_reg, record_len) =
+            emit_index_record_values(program, resolver, insertion, index, ctx.table)?;
 
         let record_reg = program.alloc_register();
         program.emit_insn(Insn::MakeRecord {
             start_reg: to_u16(idx_start_reg),
-            count: to_u16(num_cols + 1),
+            count: to_u16(record_len),
             dest_reg: to_u16(record_reg),
             index_name: Some(index.name.clone()),
             affinity_str: None,
@@ -766,7 +820,7 @@ fn emit_commit_phase(
             cursor_id: idx_cursor_id,
             record_reg,
             unpacked_start: Some(idx_start_reg),
-            unpacked_count: Some((num_cols + 1) as u16),
+            unpacked_count: Some(record_len as u16),
             flags: IdxInsertFlags::new().nchange(true),
         });
 
@@ -801,7 +855,7 @@ fn translate_rows_and_open_tables(
         program.emit_insn(Insn::OpenWrite {
             cursor_id: ctx.cursor_id,
             root_page: RegisterOrLiteral::Literal(ctx.table.root_page),
-            db: 0,
+            db: ctx.database_id,
         });
 
         translate_rows_single(program, values, insertion, resolver)?;
@@ -812,7 +866,7 @@ fn translate_rows_and_open_tables(
         program.emit_insn(Insn::OpenWrite {
             cursor_id: idx_cursor.2,
             root_page: idx_cursor.1.into(),
-            db: 0,
+            db: ctx.database_id,
         });
     }
     Ok(())
@@ -919,6 +973,20 @@ fn resolve_upserts(
             // Normalize SET pairs once
             let mut rewritten_sets = collect_set_clauses_for_upsert(table, sets)?;
 
+            if !ctx.table.has_rowid {
+                emit_without_rowid_upsert(
+                    program,
+                    table,
+                    ctx,
+                    insertion,
+                    &mut rewritten_sets,
+                    where_clause,
+                    resolver,
+                    result_columns,
+                    table_references,
+                )?;
+                continue;
+            }
             emit_upsert(
                 program,
                 table,
@@ -956,7 +1024,7 @@ fn init_autoincrement(
     program.emit_insn(Insn::OpenWrite {
         cursor_id: seq_cursor_id,
         root_page: seq_table.root_page.into(),
-        db: 0,
+        db: ctx.database_id,
     });
 
     let table_name_reg = program.emit_string8_new_reg(ctx.table.name.clone());
@@ -1441,13 +1509,13 @@ fn init_source_emission<'a>(
                     program.emit_insn(Insn::OpenWrite {
                         cursor_id,
                         root_page: RegisterOrLiteral::Literal(ctx.table.root_page),
-                        db: 0,
+                        db: ctx.database_id,
                     });
                 } else {
                     program.emit_insn(Insn::OpenWrite {
                         cursor_id,
                         root_page: RegisterOrLiteral::Literal(ctx.table.root_page),
-                        db: 0,
+                        db: ctx.database_id,
                     });
 
                     program.preassign_label_to_next_insn(ctx.loop_labels.loop_start);
@@ -1689,9 +1757,10 @@ fn build_insertion<'a>(
                 } else if column_mappings[idx_in_table].value_index.is_none() {
                     column_mappings[idx_in_table].value_index = Some(value_index);
                 }
-            } else if ROWID_STRS
-                .iter()
-                .any(|s| s.eq_ignore_ascii_case(&column_name))
+            } else if table.btree().is_none_or(|btree| btree.has_rowid)
+                && ROWID_STRS
+                    .iter()
+                    .any(|s| s.eq_ignore_ascii_case(&column_name))
             {
                 // Explicit use of the 'rowid' keyword
                 if let Some(col_in_table) = table.columns().iter().find(|c| c.is_rowid_alias()) {
@@ -1978,26 +2047,8 @@ fn emit_index_uniqueness_check(
     let maybe_skip_probe_label = emit_partial_index_check(program, resolver, index, insertion)?;
 
     let num_cols = index.columns.len();
-    // allocate scratch registers for the index columns plus rowid
-    let idx_start_reg = program.alloc_registers(num_cols + 1);
-
-    // build unpacked key [idx_start_reg .. idx_start_reg+num_cols-1], and rowid in last reg,
-    // copy each index column from the table's column registers into these scratch regs
-    for (i, idx_col) in index.columns.iter().enumerate() {
-        emit_index_column_value_for_insert(
-            program,
-            resolver,
-            insertion,
-            idx_col,
-            idx_start_reg + i,
-        )?;
-    }
-    // last register is the rowid
-    program.emit_insn(Insn::Copy {
-        src_reg: insertion.key_register(),
-        dst_reg: idx_start_reg + num_cols,
-        extra_amount: 0,
-    });
+    let (idx_start_reg, record_len) =
+        emit_index_record_values(program, resolver, insertion, index, ctx.table)?;
 
     if index.unique {
         emit_unique_index_check(
@@ -2019,7 +2070,7 @@ fn emit_index_uniqueness_check(
             let record_reg = program.alloc_register();
             program.emit_insn(Insn::MakeRecord {
                 start_reg: to_u16(idx_start_reg),
-                count: to_u16(num_cols + 1),
+                count: to_u16(record_len),
                 dest_reg: to_u16(record_reg),
                 index_name: Some(index.name.clone()),
                 affinity_str: None,
@@ -2028,7 +2079,7 @@ fn emit_index_uniqueness_check(
                 cursor_id: idx_cursor_id,
                 record_reg,
                 unpacked_start: Some(idx_start_reg),
-                unpacked_count: Some((num_cols + 1) as u16),
+                unpacked_count: Some(record_len as u16),
                 flags: IdxInsertFlags::new().nchange(true),
             });
         }
@@ -2055,6 +2106,15 @@ fn emit_unique_index_check(
     upsert_catch_all: Option<usize>,
     preflight: &mut PreflightCtx,
 ) -> Result<()> {
+    let key_columns = index.key_columns(ctx.table);
+    let num_key_cols = key_columns.len();
+    let record_len = num_cols + usize::from(ctx.table.has_rowid);
+    let err_code = if index.is_without_rowid_primary_key(ctx.table) {
+        SQLITE_CONSTRAINT_PRIMARYKEY
+    } else {
+        SQLITE_CONSTRAINT_UNIQUE
+    };
+    let description = format_key_violation_desc(ctx.table.name.as_str(), key_columns);
     let aff = index
         .columns
         .iter()
@@ -2078,7 +2138,7 @@ fn emit_unique_index_check(
             cursor_id: idx_cursor_id,
             target_pc: next_check,
             record_reg: idx_start_reg,
-            num_regs: num_cols,
+            num_regs: num_key_cols,
         });
         // Conflict detected, figure out if this UPSERT handles the conflict
         if let Some(position) = position.or(upsert_catch_all) {
@@ -2091,10 +2151,14 @@ fn emit_unique_index_check(
                 }
                 UpsertDo::Set { .. } => {
                     // Route to DO UPDATE: capture conflicting rowid then jump
-                    program.emit_insn(Insn::IdxRowId {
-                        cursor_id: idx_cursor_id,
-                        dest: ctx.conflict_rowid_reg,
-                    });
+                    if ctx.table.has_rowid {
+                        program.emit_insn(Insn::IdxRowId {
+                            cursor_id: idx_cursor_id,
+                            dest: ctx.conflict_rowid_reg,
+                        });
+                    } else {
+                        emit_conflicting_primary_key(program, ctx, index, idx_cursor_id);
+                    }
                     program.emit_insn(Insn::Goto {
                         target_pc: preflight.upsert_actions[position].1,
                     });
@@ -2104,8 +2168,8 @@ fn emit_unique_index_check(
         // No matching UPSERT handler so we emit constraint error
         // (if conflict clause matched - VM will jump to later instructions and skip halt)
         program.emit_insn(Insn::Halt {
-            err_code: SQLITE_CONSTRAINT_UNIQUE,
-            description: format_unique_violation_desc(ctx.table.name.as_str(), index),
+            err_code,
+            description,
         });
 
         // continue preflight with next constraint
@@ -2117,9 +2181,13 @@ fn emit_unique_index_check(
             cursor_id: idx_cursor_id,
             target_pc: ok,
             record_reg: idx_start_reg,
-            num_regs: num_cols,
+            num_regs: num_key_cols,
         });
-        if preflight.on_replace {
+        if preflight.on_replace && !ctx.table.has_rowid {
+            emit_conflicting_primary_key(program, ctx, index, idx_cursor_id);
+            emit_without_rowid_replace_delete_conflicting_row(program, resolver, ctx);
+            program.emit_insn(Insn::Goto { target_pc: ok });
+        } else if preflight.on_replace {
             program.emit_insn(Insn::IdxRowId {
                 cursor_id: idx_cursor_id,
                 dest: ctx.conflict_rowid_reg,
@@ -2135,8 +2203,8 @@ fn emit_unique_index_check(
         } else {
             // Unique violation without ON CONFLICT clause -> error
             program.emit_insn(Insn::Halt {
-                err_code: SQLITE_CONSTRAINT_UNIQUE,
-                description: format_unique_violation_desc(ctx.table.name.as_str(), index),
+                err_code,
+                description,
             });
         }
         program.preassign_label_to_next_insn(ok);
@@ -2145,7 +2213,7 @@ fn emit_unique_index_check(
         let record_reg = program.alloc_register();
         program.emit_insn(Insn::MakeRecord {
             start_reg: to_u16(idx_start_reg),
-            count: to_u16(num_cols + 1),
+            count: to_u16(record_len),
             dest_reg: to_u16(record_reg),
             index_name: Some(index.name.clone()),
             affinity_str: None,
@@ -2154,7 +2222,7 @@ fn emit_unique_index_check(
             cursor_id: idx_cursor_id,
             record_reg,
             unpacked_start: Some(idx_start_reg),
-            unpacked_count: Some((num_cols + 1) as u16),
+            unpacked_count: Some(record_len as u16),
             flags: IdxInsertFlags::new().nchange(true),
         });
     }
@@ -2207,27 +2275,145 @@ fn emit_preflight_constraint_checks(
     Ok(())
 }
 
-// TODO: comeback here later to apply the same improvements on select
+#[allow(clippy::too_many_arguments)]
 fn translate_virtual_table_insert(
     mut program: ProgramBuilder,
     virtual_table: Arc<VirtualTable>,
     columns: Vec<ast::Name>,
-    mut body: InsertBody,
+    body: InsertBody,
+    mut returning: Vec<ResultColumn>,
     on_conflict: Option<ResolveType>,
-    resolver: &Resolver,
+    resolver: &mut Resolver,
+    connection: &Arc<crate::Connection>,
 ) -> Result<ProgramBuilder> {
     if virtual_table.readonly() {
         crate::bail_constraint_error!("Table is read-only: {}", virtual_table.name);
     }
-    let (num_values, value) = match &mut body {
-        InsertBody::Select(select, None) => match &mut select.body.select {
-            OneSelect::Values(values) => (values[0].len(), values.pop().unwrap()),
-            _ => crate::bail_parse_error!("Virtual tables only support VALUES clause in INSERT"),
-        },
-        InsertBody::DefaultValues => (0, vec![]),
+    let table = Table::Virtual(virtual_table.clone());
+    let mut table_references = TableReferences::new(
+        vec![JoinedTable {
+            table: table.clone(),
+            identifier: virtual_table.name.clone(),
+            internal_id: program.table_reference_counter.next(),
+            op: Operation::default_scan_for(&table),
+            join_info: None,
+            col_used_mask: ColumnUsedMask::default(),
+            column_use_counts: Vec::new(),
+            expression_index_usages: Vec::new(),
+            database_id: 0,
+        }],
+        vec![],
+    );
+    let result_columns =
+        process_returning_clause(&mut returning, &mut table_references, connection)?;
+    let halt_label = program.allocate_label();
+
+    // Like for btree tables, the rows of INSERT ... SELECT and of a multi-row VALUES are produced
+    // by a coroutine, so that each of them is passed to the module with its own xUpdate call.
+    let mut values = Vec::new();
+    let mut row_source = None;
+    let num_values = match body {
+        InsertBody::DefaultValues => 0,
+        InsertBody::Select(mut select, None)
+            if select.body.compounds.is_empty()
+                && matches!(&select.body.select, OneSelect::Values(rows) if rows.len() <= 1) =>
+        {
+            if let OneSelect::Values(rows) = &mut select.body.select {
+                values = rows.pop().unwrap_or_default();
+            }
+            values.len()
+        }
+        InsertBody::Select(select, None) => {
+            let yield_reg = program.alloc_register();
+            let jump_on_definition_label = program.allocate_label();
+            let start_offset_label = program.allocate_label();
+            program.emit_insn(Insn::InitCoroutine {
+                yield_reg,
+                jump_on_definition: jump_on_definition_label,
+                start_offset: start_offset_label,
+            });
+            program.preassign_label_to_next_insn(start_offset_label);
+
+            let query_destination = QueryDestination::CoroutineYield {
+                yield_reg,
+                coroutine_implementation_start: halt_label,
+            };
+            program.incr_nesting();
+            let result =
+                translate_select(select, resolver, program, query_destination, connection)?;
+            program = result.program;
+            program.decr_nesting();
+
+            program.emit_insn(Insn::EndCoroutine { yield_reg });
+            program.preassign_label_to_next_insn(jump_on_definition_label);
+
+            let select_result_start_reg = program.reg_result_cols_start.unwrap_or(yield_reg + 1);
+            // A SELECT that reads the virtual table itself is run to completion into an
+            // ephemeral table first, so that it does not see the rows being inserted.
+            let temp_table_ctx = if program.is_table_open(&table) {
+                let temp_table = Arc::new(BTreeTable {
+                    root_page: 0,
+                    name: format!("insert_buffer_{}", virtual_table.name),
+                    has_rowid: true,
+                    primary_key_columns: vec![],
+                    columns: table.columns().to_vec(),
+                    is_strict: false,
+                    unique_sets: vec![],
+                    has_autoincrement: false,
+                    foreign_keys: vec![],
+                });
+                let temp_cursor_id = program.alloc_cursor_id(CursorType::BTreeTable(temp_table));
+                let temp_table_ctx = TempTableCtx {
+                    cursor_id: temp_cursor_id,
+                    loop_start_label: program.allocate_label(),
+                    loop_end_label: program.allocate_label(),
+                };
+                program.emit_insn(Insn::OpenEphemeral {
+                    cursor_id: temp_cursor_id,
+                    is_table: true,
+                });
+                let fill_loop_start = program.allocate_label();
+                let fill_loop_end = program.allocate_label();
+                program.preassign_label_to_next_insn(fill_loop_start);
+                program.emit_insn(Insn::Yield {
+                    yield_reg,
+                    end_offset: fill_loop_end,
+                });
+                let record_reg = program.alloc_register();
+                program.emit_insn(Insn::MakeRecord {
+                    start_reg: to_u16(select_result_start_reg),
+                    count: to_u16(result.num_result_cols),
+                    dest_reg: to_u16(record_reg),
+                    index_name: None,
+                    affinity_str: None,
+                });
+                let rowid_reg = program.alloc_register();
+                program.emit_insn(Insn::NewRowid {
+                    cursor: temp_cursor_id,
+                    rowid_reg,
+                    prev_largest_reg: 0,
+                });
+                program.emit_insn(Insn::Insert {
+                    cursor: temp_cursor_id,
+                    key_reg: rowid_reg,
+                    record_reg,
+                    flag: InsertFlags::new().require_seek(),
+                    table_name: "".to_string(),
+                });
+                program.emit_insn(Insn::Goto {
+                    target_pc: fill_loop_start,
+                });
+                program.preassign_label_to_next_insn(fill_loop_end);
+                Some(temp_table_ctx)
+            } else {
+                None
+            };
+            row_source = Some((yield_reg, select_result_start_reg, temp_table_ctx));
+            result.num_result_cols
+        }
         _ => crate::bail_parse_error!("Unsupported INSERT body for virtual tables"),
     };
-    let table = Table::Virtual(virtual_table.clone());
+
     let cursor_id = program.alloc_cursor_id(CursorType::VirtualTable(virtual_table.clone()));
     program.emit_insn(Insn::VOpen { cursor_id });
     program.emit_insn(Insn::VBegin { cursor_id });
@@ -2246,21 +2432,72 @@ fn translate_virtual_table_insert(
      * */
     let insertion = build_insertion(&mut program, &table, &columns, num_values)?;
 
-    translate_rows_single(&mut program, &value, &insertion, resolver)?;
-    let conflict_action = on_conflict.as_ref().map(|c| c.bit_value()).unwrap_or(0) as u16;
+    let loop_start = program.allocate_label();
+    let loop_end = program.allocate_label();
+    match &row_source {
+        Some((yield_reg, select_result_start_reg, temp_table_ctx)) => {
+            if temp_table_ctx.is_none() {
+                program.preassign_label_to_next_insn(loop_start);
+                program.emit_insn(Insn::Yield {
+                    yield_reg: *yield_reg,
+                    end_offset: loop_end,
+                });
+            }
+            translate_rows_multiple(
+                &mut program,
+                &insertion,
+                *select_result_start_reg,
+                resolver,
+                temp_table_ctx,
+            )?;
+        }
+        None => translate_rows_single(&mut program, &values, &insertion, resolver)?,
+    }
 
     program.emit_insn(Insn::VUpdate {
         cursor_id,
         arg_count: insertion.col_mappings.len() + 2, // +1 for NULL, +1 for rowid
         start_reg: registers_start,
-        conflict_action,
+        conflict_action: on_conflict.as_ref().map(|c| c.bit_value()).unwrap_or(0) as u16,
     });
 
+    // The values are returned as they were passed to the virtual table, so a rowid assigned
+    // by the module itself is not visible.
+    emit_returning_results(
+        &mut program,
+        &table_references,
+        &result_columns,
+        insertion.first_col_register(),
+        insertion.key_register(),
+        resolver,
+    )?;
+
+    match &row_source {
+        Some((_, _, Some(temp_table_ctx))) => {
+            program.emit_insn(Insn::Next {
+                cursor_id: temp_table_ctx.cursor_id,
+                pc_if_next: temp_table_ctx.loop_start_label,
+            });
+            program.preassign_label_to_next_insn(temp_table_ctx.loop_end_label);
+            program.emit_insn(Insn::Close {
+                cursor_id: temp_table_ctx.cursor_id,
+            });
+        }
+        Some((_, _, None)) => {
+            program.emit_insn(Insn::Goto {
+                target_pc: loop_start,
+            });
+        }
+        None => {}
+    }
+    program.preassign_label_to_next_insn(loop_end);
+
     program.emit_insn(Insn::Close { cursor_id });
 
-    let halt_label = program.allocate_label();
     program.resolve_label(halt_label, program.offset());
 
+    program.result_columns = result_columns;
+    program.table_references.extend(table_references);
     Ok(program)
 }
 
@@ -2269,6 +2506,7 @@ fn ensure_sequence_initialized(
     program: &mut ProgramBuilder,
     schema: &Schema,
     table: &schema::BTreeTable,
+    database_id: usize,
 ) -> Result<()> {
     let seq_table = schema.get_btree_table("sqlite_sequence").ok_or_else(|| {
         crate::error::LimboError::InternalError("sqlite_sequence table not found".to_string())
@@ -2279,7 +2517,7 @@ fn ensure_sequence_initialized(
     program.emit_insn(Insn::OpenWrite {
         cursor_id: seq_cursor_id,
         root_page: seq_table.root_page.into(),
-        db: 0,
+        db: database_id,
     });
 
     let table_name_reg = program.emit_string8_new_reg(table.name.clone());
@@ -2375,19 +2613,22 @@ fn ensure_sequence_initialized(
 /// single column: `t.c1`
 /// multi-column:  `t.(k, c1)`
 pub fn format_unique_violation_desc(table_name: &str, index: &Index) -> String {
-    if index.columns.len() == 1 {
-        let mut s = String::with_capacity(table_name.len() + 1 + index.columns[0].name.len());
+    format_key_violation_desc(table_name, &index.columns)
+}
+
+pub(crate) fn format_key_violation_desc(table_name: &str, columns: &[IndexColumn]) -> String {
+    if columns.len() == 1 {
+        let mut s = String::with_capacity(table_name.len() + 1 + columns[0].name.len());
         s.push_str(table_name);
         s.push('.');
-        s.push_str(&index.columns[0].name);
+        s.push_str(&columns[0].name);
         s
     } else {
-        let mut s = String::with_capacity(table_name.len() + 3 + 4 * index.columns.len());
+        let mut s = String::with_capacity(table_name.len() + 3 + 4 * columns.len());
         s.push_str(table_name);
         s.push_str(".(");
         s.push_str(
-            &index
-                .columns
+            &columns
                 .iter()
                 .map(|c| c.name.as_str())
                 .collect::<Vec<_>>()
@@ -2494,6 +2735,39 @@ fn rewrite_index_expr_for_insertion(expr: &mut ast::Expr, insertion: &Insertion)
     Ok(())
 }
 
+/// Evaluates the values of an index record for the row being inserted into consecutive
+/// registers, returning the first register and the number of values. Records of rowid tables end
+/// with the rowid, while WITHOUT ROWID records end with the primary key columns, which are
+/// already part of `index.columns`.
+fn emit_index_record_values(
+    program: &mut ProgramBuilder,
+    resolver: &Resolver,
+    insertion: &Insertion,
+    index: &Index,
+    table: &BTreeTable,
+) -> Result<(usize, usize)> {
+    let num_cols = index.columns.len();
+    let record_len = num_cols + usize::from(table.has_rowid);
+    let idx_start_reg = program.alloc_registers(record_len);
+    for (i, idx_col) in index.columns.iter().enumerate() {
+        emit_index_column_value_for_insert(
+            program,
+            resolver,
+            insertion,
+            idx_col,
+            idx_start_reg + i,
+        )?;
+    }
+    if table.has_rowid {
+        program.emit_insn(Insn::Copy {
+            src_reg: insertion.key_register(),
+            dst_reg: idx_start_reg + num_cols,
+            extra_amount: 0,
+        });
+    }
+    Ok((idx_start_reg, record_len))
+}
+
 fn emit_index_column_value_for_insert(
     program: &mut ProgramBuilder,
     resolver: &Resolver,
@@ -2565,6 +2839,18 @@ fn build_constraints_to_check(
             .position(|(target, ..)| matches!(target, ResolvedUpsertTarget::Index(x) if Arc::ptr_eq(x, index)));
         constraints_to_check.push((ResolvedUpsertTarget::Index(index.clone()), position));
     }
+    // The primary key of a WITHOUT ROWID table plays the role of the rowid: check it before
+    // the secondary indexes, so that REPLACE never deletes entries inserted for the new row.
+    if let Some(table) = resolver.schema.get_btree_table(table_name) {
+        if !table.has_rowid {
+            if let Some(pk_pos) = constraints_to_check.iter().position(|(target, _)| {
+                matches!(target, ResolvedUpsertTarget::Index(x) if x.is_without_rowid_primary_key(&table))
+            }) {
+                let pk_constraint = constraints_to_check.remove(pk_pos);
+                constraints_to_check.insert(0, pk_constraint);
+            }
+        }
+    }
 
     constraints_to_check.sort_by(|(_, p1), (_, p2)| match (p1, p2) {
         (Some(p1), Some(p2)) => p1.cmp(p2),
@@ -2796,6 +3082,81 @@ fn emit_replace_delete_conflicting_row(
     Ok(())
 }
 
+/// Copies the primary key of the WITHOUT ROWID row that the cursor of `index` points at into
+/// the conflict key registers.
+fn emit_conflicting_primary_key(
+    program: &mut ProgramBuilder,
+    ctx: &InsertEmitCtx,
+    index: &Index,
+    idx_cursor_id: usize,
+) {
+    let pk_index = ctx
+        .without_rowid_pk_index
+        .as_ref()
+        .expect("WITHOUT ROWID table has a primary key index");
+    for (i, key_column) in pk_index.key_columns(ctx.table).iter().enumerate() {
+        let pos_in_index = index
+            .columns
+            .iter()
+            .position(|col| col.expr.is_none() && col.pos_in_table == key_column.pos_in_table)
+            .expect("index entries of WITHOUT ROWID tables contain the primary key");
+        program.emit_column_or_rowid(idx_cursor_id, pos_in_index, ctx.conflict_key_reg + i);
+    }
+}
+
+/// OR REPLACE on a WITHOUT ROWID table: deletes the row whose primary key is in the conflict key
+/// registers from its primary key index and from the secondary indexes.
+fn emit_without_rowid_replace_delete_conflicting_row(
+    program: &mut ProgramBuilder,
+    resolver: &Resolver,
+    ctx: &InsertEmitCtx,
+) {
+    let pk_index = ctx
+        .without_rowid_pk_index
+        .as_ref()
+        .expect("WITHOUT ROWID table has a primary key index");
+    let cursor_of = |name: &str| {
+        ctx.idx_cursors
+            .iter()
+            .find(|(idx_name, _, _)| idx_name == name)
+            .map(|(_, _, c_id)| *c_id)
+            .expect("no cursor found for index")
+    };
+    let pk_cursor_id = cursor_of(&pk_index.name);
+    let row_deleted = program.allocate_label();
+    program.emit_insn(Insn::NotFound {
+        cursor_id: pk_cursor_id,
+        target_pc: row_deleted,
+        record_reg: ctx.conflict_key_reg,
+        num_regs: pk_index.key_columns(ctx.table).len(),
+    });
+    let columns_start_reg = program.alloc_registers(ctx.table.columns.len());
+    for i in 0..ctx.table.columns.len() {
+        let pos_in_index = pk_index
+            .column_table_pos_to_index_pos(i)
+            .expect("primary key index stores every column of the table");
+        program.emit_column_or_rowid(pk_cursor_id, pos_in_index, columns_start_reg + i);
+    }
+    for index in resolver.schema.get_indices(ctx.table.name.as_str()) {
+        if index.is_without_rowid_primary_key(ctx.table) {
+            continue;
+        }
+        let start_reg = emit_without_rowid_index_entry(program, &index.columns, columns_start_reg);
+        program.emit_insn(Insn::IdxDelete {
+            start_reg,
+            num_regs: index.columns.len(),
+            cursor_id: cursor_of(&index.name),
+            raise_error_if_no_matching_entry: true,
+        });
+    }
+    program.emit_insn(Insn::Delete {
+        cursor_id: pk_cursor_id,
+        table_name: ctx.table.name.clone(),
+        is_part_of_update: true,
+    });
+    program.preassign_label_to_next_insn(row_deleted);
+}
+
 /// Child-side FK checks for INSERT of a single row:
 /// For each outgoing FK on `child_tbl`, if the NEW tuple's FK columns are all non-NULL,
 /// verify that the referenced parent key exists.
@@ -1,34 +1,82 @@
 use crate::{
-    schema::Schema,
-    vdbe::{builder::ProgramBuilder, insn::Insn},
+    schema::{Schema, NAMESPACE_TABLE_PREFIX},
+    vdbe::{builder::CursorType, builder::ProgramBuilder, insn::Insn},
 };
 
 /// Maximum number of errors to report with integrity check. If we exceed this number we will short
 /// circuit the procedure and return early to not waste time.
 const MAX_INTEGRITY_CHECK_ERRORS: usize = 10;
 
+/// Translates `PRAGMA integrity_check`. `schema` is the schema of the default namespace, the
+/// objects of the other namespaces are found by reading their schema tables at run time.
 pub fn translate_integrity_check(
     schema: &Schema,
     program: &mut ProgramBuilder,
 ) -> crate::Result<()> {
     let mut root_pages = Vec::with_capacity(schema.tables.len() + schema.indexes.len());
+    let mut namespace_tables = Vec::new();
     // Collect root pages to run integrity check on
     for table in schema.tables.values() {
         if let crate::schema::Table::BTree(table) = table.as_ref() {
             root_pages.push(table.root_page);
+            if table.name.starts_with(NAMESPACE_TABLE_PREFIX) {
+                namespace_tables.push(table.clone());
+            }
             if let Some(indexes) = schema.indexes.get(table.name.as_str()) {
                 for index in indexes.iter() {
-                    if index.root_page > 0 {
+                    // The primary key index of a WITHOUT ROWID table is the table b-tree.
+                    if index.root_page > 0 && !index.is_without_rowid_primary_key(table) {
                         root_pages.push(index.root_page);
                     }
                 }
             }
         };
     }
+    let roots_rowset = if namespace_tables.is_empty() {
+        None
+    } else {
+        let rowset_reg = program.alloc_register();
+        let root_page_reg = program.alloc_register();
+        for table in namespace_tables {
+            let cursor_id = program.alloc_cursor_id(CursorType::BTreeTable(table.clone()));
+            program.emit_insn(Insn::OpenRead {
+                cursor_id,
+                root_page: table.root_page,
+                db: 0,
+            });
+            let loop_start = program.allocate_label();
+            let loop_next = program.allocate_label();
+            let loop_end = program.allocate_label();
+            program.emit_insn(Insn::Rewind {
+                cursor_id,
+                pc_if_empty: loop_end,
+            });
+            program.preassign_label_to_next_insn(loop_start);
+            // The rootpage column is 0 for views and triggers.
+            program.emit_column_or_rowid(cursor_id, 3, root_page_reg);
+            program.emit_insn(Insn::IfNot {
+                reg: root_page_reg,
+                target_pc: loop_next,
+                jump_if_null: true,
+            });
+            program.emit_insn(Insn::RowSetAdd {
+                rowset_reg,
+                value_reg: root_page_reg,
+            });
+            program.preassign_label_to_next_insn(loop_next);
+            program.emit_insn(Insn::Next {
+                cursor_id,
+                pc_if_next: loop_start,
+            });
+            program.preassign_label_to_next_insn(loop_end);
+        }
+        Some(rowset_reg)
+    };
     let message_register = program.alloc_register();
     program.emit_insn(Insn::IntegrityCk {
         max_errors: MAX_INTEGRITY_CHECK_ERRORS,
         roots: root_pages,
+        roots_rowset,
         message_register,
     });
     program.emit_insn(Insn::ResultRow {
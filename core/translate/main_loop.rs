@@ -20,7 +20,7 @@ use super::{
     },
 };
 use crate::{
-    schema::{Index, IndexColumn, Table},
+    schema::{FromClauseSubquery, Index, IndexColumn, Table},
     translate::{
         collate::{get_collseq_from_expr, CollationSeq},
         emitter::{prepare_cdc_if_necessary, HashCtx},
@@ -158,7 +158,7 @@ pub fn init_loop(
     {
         assert!(
             agg.args.len() == 1,
-            "DISTINCT aggregate functions must have exactly one argument"
+            "DISTINCT aggregates must have exactly one argument"
         );
         let index_name = format!(
             "distinct_agg_{}_{}",
@@ -436,6 +436,38 @@ pub fn init_loop(
                     _ => unreachable!("Hash joins should only occur in SELECT operations"),
                 }
             }
+            Operation::MultiIndexScan(multi) => {
+                assert!(
+                    matches!(mode, OperationMode::SELECT),
+                    "Multi-index scans should only occur in SELECT operations"
+                );
+                if let Some(table_cursor_id) = table_cursor_id {
+                    program.emit_insn(Insn::OpenRead {
+                        cursor_id: table_cursor_id,
+                        root_page: table.table.get_root_page(),
+                        db: table.database_id,
+                    });
+                }
+                // Several branches may search the same index; they share one cursor.
+                for branch in multi.branches.iter() {
+                    let Search::Seek {
+                        index: Some(index), ..
+                    } = branch
+                    else {
+                        continue;
+                    };
+                    let key = CursorKey::index(table.internal_id, index.clone());
+                    if program.resolve_cursor_id_safe(&key).is_some() {
+                        continue;
+                    }
+                    let cursor_id = program.alloc_cursor_index(Some(key), index)?;
+                    program.emit_insn(Insn::OpenRead {
+                        cursor_id,
+                        root_page: index.root_page,
+                        db: table.database_id,
+                    });
+                }
+            }
         }
     }
 
@@ -732,6 +764,29 @@ pub fn open_loop(
                         });
                         program.preassign_label_to_next_insn(loop_start);
                     }
+                    (Scan::Subquery, Table::FromClauseSubquery(from_clause_subquery))
+                        if from_clause_subquery.materialized_cursor_id.is_some() =>
+                    {
+                        // A materialized subquery is read back from its ephemeral table,
+                        // into the same registers the coroutine would have yielded its rows in.
+                        let cursor_id = from_clause_subquery.materialized_cursor_id.unwrap();
+                        let start_reg = from_clause_subquery
+                            .result_columns_start_reg
+                            .expect("Subquery result_columns_start_reg must be set");
+                        program.emit_insn(Insn::Rewind {
+                            cursor_id,
+                            pc_if_empty: loop_end,
+                        });
+                        program.preassign_label_to_next_insn(loop_start);
+                        for column in 0..from_clause_subquery.columns.len() {
+                            program.emit_insn(Insn::Column {
+                                cursor_id,
+                                column,
+                                dest: start_reg + column,
+                                default: None,
+                            });
+                        }
+                    }
                     (Scan::Subquery, Table::FromClauseSubquery(from_clause_subquery)) => {
                         let (yield_reg, coroutine_implementation_start) =
                             match &from_clause_subquery.plan.query_destination {
@@ -1029,6 +1084,119 @@ pub fn open_loop(
                     });
                 }
             }
+            Operation::MultiIndexScan(multi) => {
+                let table_cursor_id =
+                    table_cursor_id.expect("MultiIndexScan requires a table cursor");
+                // First run every branch's search and collect the matching rowids into a RowSet.
+                // The RowSet drops duplicates, so a row matched by several OR arms is only visited once.
+                let rowset_reg = program.alloc_register();
+                program.emit_null(rowset_reg, None);
+                let rowid_reg = program.alloc_register();
+                for branch in multi.branches.iter() {
+                    let branch_end = program.allocate_label();
+                    match branch {
+                        Search::RowidEq { cmp_expr } => {
+                            let src_reg = program.alloc_register();
+                            translate_expr(
+                                program,
+                                Some(table_references),
+                                cmp_expr,
+                                src_reg,
+                                &t_ctx.resolver,
+                            )?;
+                            program.emit_insn(Insn::SeekRowid {
+                                cursor_id: table_cursor_id,
+                                src_reg,
+                                target_pc: branch_end,
+                            });
+                            program.emit_insn(Insn::RowId {
+                                cursor_id: table_cursor_id,
+                                dest: rowid_reg,
+                            });
+                            program.emit_insn(Insn::RowSetAdd {
+                                rowset_reg,
+                                value_reg: rowid_reg,
+                            });
+                        }
+                        Search::Seek { index, seek_def } => {
+                            let branch_start = program.allocate_label();
+                            let seek_cursor_id = match index {
+                                Some(index) => program.resolve_cursor_id(&CursorKey::index(
+                                    table.internal_id,
+                                    index.clone(),
+                                )),
+                                None => table_cursor_id,
+                            };
+                            let max_registers = seek_def
+                                .size(&seek_def.start)
+                                .max(seek_def.size(&seek_def.end));
+                            let start_reg = program.alloc_registers(max_registers);
+                            emit_seek(
+                                program,
+                                table_references,
+                                seek_def,
+                                t_ctx,
+                                seek_cursor_id,
+                                start_reg,
+                                branch_end,
+                                index.as_ref(),
+                                false,
+                            )?;
+                            emit_seek_termination(
+                                program,
+                                table_references,
+                                seek_def,
+                                t_ctx,
+                                seek_cursor_id,
+                                start_reg,
+                                branch_start,
+                                branch_end,
+                                index.as_ref(),
+                            )?;
+                            if index.is_some() {
+                                program.emit_insn(Insn::IdxRowId {
+                                    cursor_id: seek_cursor_id,
+                                    dest: rowid_reg,
+                                });
+                            } else {
+                                program.emit_insn(Insn::RowId {
+                                    cursor_id: seek_cursor_id,
+                                    dest: rowid_reg,
+                                });
+                            }
+                            program.emit_insn(Insn::RowSetAdd {
+                                rowset_reg,
+                                value_reg: rowid_reg,
+                            });
+                            if seek_def.iter_dir == IterationDirection::Backwards {
+                                program.emit_insn(Insn::Prev {
+                                    cursor_id: seek_cursor_id,
+                                    pc_if_prev: branch_start,
+                                });
+                            } else {
+                                program.emit_insn(Insn::Next {
+                                    cursor_id: seek_cursor_id,
+                                    pc_if_next: branch_start,
+                                });
+                            }
+                        }
+                    }
+                    program.preassign_label_to_next_insn(branch_end);
+                }
+
+                // Then visit the collected rows in rowid order.
+                program.preassign_label_to_next_insn(loop_start);
+                program.emit_insn(Insn::RowSetRead {
+                    rowset_reg,
+                    pc_if_empty: loop_end,
+                    dest_reg: rowid_reg,
+                });
+                program.emit_insn(Insn::SeekRowid {
+                    cursor_id: table_cursor_id,
+                    src_reg: rowid_reg,
+                    target_pc: next,
+                });
+            }
         }
 
         let condition_fail_target = if let Operation::HashJoin(ref hj) = table.op {
@@ -1130,31 +1298,36 @@ fn emit_conditions(
     from_outer_join: bool,
     subqueries: &[NonFromClauseSubquery],
 ) -> Result<()> {
-    for cond in predicates
-        .iter()
-        .filter(|cond| cond.from_outer_join.is_some() == from_outer_join)
-        .filter(|cond| {
-            cond.should_eval_at_loop(join_index, join_order, subqueries, Some(table_references))
-        })
-    {
-        let jump_target_when_true = program.allocate_label();
-        let condition_metadata = ConditionMetadata {
-            jump_if_condition_is_true: false,
-            jump_target_when_true,
-            jump_target_when_false: next,
-            jump_target_when_null: next,
-        };
-        translate_condition_expr(
-            program,
-            table_references,
-            &cond.expr,
-            condition_metadata,
-            &t_ctx.resolver,
-        )?;
-        program.preassign_label_to_next_insn(jump_target_when_true);
-    }
-
-    Ok(())
+    // A row that fails a condition skips the rest, so the subexpressions evaluated by one
+    // condition can be reused by the next ones. Nothing jumps into the middle of them, but the
+    // NULL row of a LEFT JOIN enters between the outer join conditions and the WHERE ones, so
+    // each call has a cache of its own.
+    t_ctx.resolver.with_subexpr_cache(|| {
+        for cond in predicates
+            .iter()
+            .filter(|cond| cond.from_outer_join.is_some() == from_outer_join)
+            .filter(|cond| {
+                cond.should_eval_at_loop(join_index, join_order, subqueries, Some(table_references))
+            })
+        {
+            let jump_target_when_true = program.allocate_label();
+            let condition_metadata = ConditionMetadata {
+                jump_if_condition_is_true: false,
+                jump_target_when_true,
+                jump_target_when_false: next,
+                jump_target_when_null: next,
+            };
+            translate_condition_expr(
+                program,
+                table_references,
+                &cond.expr,
+                condition_metadata,
+                &t_ctx.resolver,
+            )?;
+            program.preassign_label_to_next_insn(jump_target_when_true);
+        }
+        Ok(())
+    })
 }
 
 /// SQLite (and so Turso) processes joins as a nested loop.
@@ -1372,6 +1545,13 @@ fn emit_loop_source(
                 program.emit_int(1, flag);
             }
 
+            // The first row holds the MIN or MAX, see [crate::translate::optimizer::min_max].
+            if plan.min_max_from_first_row {
+                program.emit_insn(Insn::Goto {
+                    target_pc: t_ctx.label_main_loop_end.unwrap(),
+                });
+            }
+
             Ok(())
         }
         LoopEmitTarget::QueryResult => {
@@ -1478,12 +1658,24 @@ pub fn close_loop(
                         });
                     }
                     Scan::Subquery => {
-                        // A subquery has no cursor to call Next on, so it just emits a Goto
-                        // to the Yield instruction, which in turn jumps back to the main loop of the subquery,
-                        // so that the next row from the subquery can be read.
-                        program.emit_insn(Insn::Goto {
-                            target_pc: loop_labels.loop_start,
-                        });
+                        if let Table::FromClauseSubquery(FromClauseSubquery {
+                            materialized_cursor_id: Some(cursor_id),
+                            ..
+                        }) = &table.table
+                        {
+                            // A materialized subquery is scanned from its ephemeral table.
+                            program.emit_insn(Insn::Next {
+                                cursor_id: *cursor_id,
+                                pc_if_next: loop_labels.loop_start,
+                            });
+                        } else {
+                            // A subquery has no cursor to call Next on, so it just emits a Goto
+                            // to the Yield instruction, which in turn jumps back to the main loop of the subquery,
+                            // so that the next row from the subquery can be read.
+                            program.emit_insn(Insn::Goto {
+                                target_pc: loop_labels.loop_start,
+                            });
+                        }
                     }
                 }
                 program.preassign_label_to_next_insn(loop_labels.loop_end);
@@ -1536,6 +1728,13 @@ pub fn close_loop(
                 });
                 program.preassign_label_to_next_insn(loop_labels.loop_end);
             }
+            Operation::MultiIndexScan(_) => {
+                program.resolve_label(loop_labels.next, program.offset());
+                program.emit_insn(Insn::Goto {
+                    target_pc: loop_labels.loop_start,
+                });
+                program.preassign_label_to_next_insn(loop_labels.loop_end);
+            }
             Operation::HashJoin(ref hash_join_op) => {
                 // Probe table: emit logic for iterating through hash matches
                 if let Some(hash_ctx) = t_ctx.hash_table_contexts.get(&hash_join_op.build_table_idx)
@@ -1748,14 +1947,18 @@ fn emit_seek(
                 // and if so, jump to the loop end.
                 // This is to avoid returning rows for e.g. SELECT * FROM t WHERE t.x > NULL,
                 // which would erroneously return all rows from t, as NULL is lower than any non-NULL value in index key comparisons.
-                if !expr.is_nonnull(tables) {
+                // Only the first element of a row value is checked: e.g. (t.x, t.y) > (1, NULL) can still
+                // seek to (1, NULL), and the rows after it are filtered by the comparison itself.
+                if i <= seek_def.prefix.len() && !expr.is_nonnull(tables) {
                     program.emit_insn(Insn::IsNull {
                         reg,
                         target_pc: loop_end,
                     });
                 }
             }
-            SeekKeyComponent::None => unreachable!("None component is not possible in iterator"),
+            SeekKeyComponent::Row(..) | SeekKeyComponent::None => {
+                unreachable!("Row and None components are not possible in iterator")
+            }
         }
     }
     let num_regs = seek_def.size(&seek_def.start);
@@ -1904,6 +2107,25 @@ fn emit_seek_termination(
                 });
             }
         }
+        SeekKeyComponent::Row(elements) => {
+            for (i, (expr, _)) in elements.iter().enumerate() {
+                translate_expr_no_constant_opt(
+                    program,
+                    Some(tables),
+                    expr,
+                    last_reg + i,
+                    &t_ctx.resolver,
+                    NoConstantOptReason::RegisterReuse,
+                )?;
+            }
+            // As in [emit_seek], only the first element of a row value is checked for NULL.
+            if !elements[0].0.is_nonnull(tables) {
+                program.emit_insn(Insn::IsNull {
+                    reg: last_reg,
+                    target_pc: loop_end,
+                });
+            }
+        }
         SeekKeyComponent::None => {}
     }
     program.preassign_label_to_next_insn(loop_start);
@@ -25,6 +25,7 @@ pub(crate) mod insert;
 pub(crate) mod integrity_check;
 pub(crate) mod logical;
 pub(crate) mod main_loop;
+pub(crate) mod namespace;
 pub(crate) mod optimizer;
 pub(crate) mod order_by;
 pub(crate) mod plan;
@@ -47,10 +48,11 @@ mod window;
 use crate::schema::Schema;
 use crate::storage::pager::Pager;
 use crate::translate::delete::translate_delete;
-use crate::translate::emitter::Resolver;
+use crate::translate::emitter::{Resolver, TransactionMode};
+use crate::util::normalize_ident;
 use crate::vdbe::builder::{ProgramBuilder, ProgramBuilderOpts, QueryMode};
 use crate::vdbe::Program;
-use crate::{bail_parse_error, Connection, Result, SymbolTable};
+use crate::{bail_parse_error, Connection, Result, SymbolTable, TEMP_DB_INDEX};
 use alter::translate_alter_table;
 use analyze::translate_analyze;
 use index::{translate_create_index, translate_drop_index};
@@ -126,6 +128,7 @@ pub fn translate_inner(
         ast::Stmt::AlterTable { .. }
             | ast::Stmt::Analyze { .. }
             | ast::Stmt::CreateIndex { .. }
+            | ast::Stmt::CreateNamespace { .. }
             | ast::Stmt::CreateTable { .. }
             | ast::Stmt::CreateTrigger { .. }
             | ast::Stmt::CreateView { .. }
@@ -133,7 +136,9 @@ pub fn translate_inner(
             | ast::Stmt::CreateVirtualTable(..)
             | ast::Stmt::Delete { .. }
             | ast::Stmt::DropIndex { .. }
+            | ast::Stmt::DropNamespace { .. }
             | ast::Stmt::DropTable { .. }
+            | ast::Stmt::DropTrigger { .. }
             | ast::Stmt::DropView { .. }
             | ast::Stmt::Reindex { .. }
             | ast::Stmt::Update { .. }
@@ -143,10 +148,53 @@ pub fn translate_inner(
     if is_write && connection.get_query_only() {
         bail_parse_error!("Cannot execute write statement in query_only mode")
     }
+    let write_database_id = if is_write {
+        write_target_database(&stmt, connection)?
+    } else {
+        0
+    };
 
     let is_select = matches!(stmt, ast::Stmt::Select { .. });
 
-    let mut program = match stmt {
+    let mut program = if write_database_id == 0 {
+        translate_stmt(stmt, resolver, program, connection, input)?
+    } else {
+        // A statement writing to another database is translated against the schema of that
+        // database, so that its tables, indexes and constraints are the ones maintained.
+        let schema = connection.database_schema(write_database_id);
+        let mut resolver = Resolver::new(&schema, resolver.symbol_table);
+        let mut program = translate_stmt(stmt, &mut resolver, program, connection, input)?;
+        program.begin_attached_operation(
+            write_database_id,
+            TransactionMode::Write,
+            schema.schema_version,
+        );
+        // The statement may still read tables of the main database.
+        program.begin_read_operation();
+        program
+    };
+
+    // Indicate write operations so that in the epilogue we can emit the correct type of transaction
+    if is_write && write_database_id == 0 {
+        program.begin_write_operation();
+    }
+
+    // Indicate read operations so that in the epilogue we can emit the correct type of transaction
+    if is_select && !program.table_references.is_empty() {
+        program.begin_read_operation();
+    }
+
+    Ok(program)
+}
+
+fn translate_stmt(
+    stmt: ast::Stmt,
+    resolver: &mut Resolver,
+    program: ProgramBuilder,
+    connection: &Arc<Connection>,
+    input: &str,
+) -> Result<ProgramBuilder> {
+    let program = match stmt {
         ast::Stmt::AlterTable(alter) => {
             translate_alter_table(alter, resolver, program, connection, input)?
         }
@@ -159,6 +207,16 @@ pub fn translate_inner(
         ast::Stmt::CreateIndex { .. } => {
             translate_create_index(program, connection, resolver, stmt)?
         }
+        ast::Stmt::CreateNamespace {
+            if_not_exists,
+            name,
+        } => namespace::translate_create_namespace(
+            &name,
+            if_not_exists,
+            resolver,
+            program,
+            connection,
+        )?,
         ast::Stmt::CreateTable {
             temporary,
             if_not_exists,
@@ -265,7 +323,10 @@ pub fn translate_inner(
         ast::Stmt::DropIndex {
             if_exists,
             idx_name,
-        } => translate_drop_index(idx_name.name.as_str(), resolver, if_exists, program)?,
+        } => translate_drop_index(&idx_name, resolver, if_exists, program, connection)?,
+        ast::Stmt::DropNamespace { if_exists, name } => {
+            namespace::translate_drop_namespace(&name, if_exists, resolver, program, connection)?
+        }
         ast::Stmt::DropTable {
             if_exists,
             tbl_name,
@@ -307,6 +368,7 @@ pub fn translate_inner(
             .program
         }
         ast::Stmt::Update(update) => translate_update(update, resolver, program, connection)?,
+        ast::Stmt::Use { name } => namespace::translate_use(&name, resolver, program, connection)?,
         ast::Stmt::Vacuum { .. } => bail_parse_error!("VACUUM not supported yet"),
         ast::Stmt::Insert {
             with,
@@ -331,16 +393,131 @@ pub fn translate_inner(
             )?
         }
     };
+    Ok(program)
+}
+
+/// The database a write statement modifies. Statements writing to an attached or the temp
+/// database are translated against its schema; only plain table and index maintenance is
+/// supported there, since change capture, triggers, foreign keys and MVCC all assume the main
+/// database.
+fn write_target_database(stmt: &ast::Stmt, connection: &Arc<Connection>) -> Result<usize> {
+    let (database_id, table_name) = match stmt {
+        ast::Stmt::CreateTable {
+            tbl_name,
+            temporary,
+            ..
+        } => (
+            connection.resolve_create_database_id(tbl_name, *temporary)?,
+            None,
+        ),
+        ast::Stmt::CreateIndex {
+            idx_name, tbl_name, ..
+        } => (
+            index::create_index_database_id(idx_name, tbl_name, connection)?,
+            Some(tbl_name),
+        ),
+        ast::Stmt::Delete { tbl_name, .. } | ast::Stmt::Insert { tbl_name, .. } => (
+            connection.resolve_database_id(tbl_name)?,
+            Some(&tbl_name.name),
+        ),
+        ast::Stmt::Update(update) => (
+            connection.resolve_database_id(&update.tbl_name)?,
+            Some(&update.tbl_name.name),
+        ),
+        ast::Stmt::AlterTable(ast::AlterTable { name, .. })
+        | ast::Stmt::DropTable { tbl_name: name, .. } => (
+            schema_change_database(connection.resolve_database_id(name)?)?,
+            Some(&name.name),
+        ),
+        ast::Stmt::DropIndex { idx_name, .. } => (
+            schema_change_database(index::drop_index_database_id(idx_name, connection)?)?,
+            None,
+        ),
+        ast::Stmt::DropTrigger { trigger_name, .. } => {
+            return main_database_only(
+                connection.resolve_object_database_id(trigger_name, |schema, name| {
+                    schema.get_trigger(name).is_some()
+                })?,
+            );
+        }
+        ast::Stmt::DropView { view_name, .. } => {
+            return main_database_only(
+                connection.resolve_object_database_id(view_name, |schema, name| {
+                    schema.get_view(name).is_some() || schema.is_materialized_view(name)
+                })?,
+            );
+        }
+        ast::Stmt::CreateTrigger {
+            trigger_name: name, ..
+        }
+        | ast::Stmt::CreateView {
+            view_name: name, ..
+        }
+        | ast::Stmt::CreateMaterializedView {
+            view_name: name, ..
+        }
+        | ast::Stmt::CreateVirtualTable(ast::CreateVirtualTable { tbl_name: name, .. }) => {
+            return main_database_only(connection.resolve_create_database_id(name, false)?);
+        }
+        _ => return Ok(0),
+    };
+    if database_id == 0 {
+        return Ok(0);
+    }
+    if connection.mvcc_enabled() {
+        bail_parse_error!(
+            "writing to {} is not supported with MVCC",
+            database_description(database_id)
+        );
+    }
+    if connection.get_capture_data_changes().table().is_some() {
+        bail_parse_error!(
+            "writing to {} is not supported with change capture",
+            database_description(database_id)
+        );
+    }
+    if let Some(table_name) = table_name {
+        let table_name = normalize_ident(table_name.as_str());
+        let has_unsupported_constraints = connection.with_schema(database_id, |schema| {
+            schema.get_triggers_for_table(&table_name).next().is_some()
+                || (connection.foreign_keys_enabled()
+                    && (schema.has_child_fks(&table_name)
+                        || schema.any_resolved_fks_referencing(&table_name)))
+        });
+        if has_unsupported_constraints {
+            bail_parse_error!(
+                "writing to tables with triggers or foreign keys is not supported on {} yet",
+                database_description(database_id)
+            );
+        }
+    }
+    Ok(database_id)
+}
 
-    // Indicate write operations so that in the epilogue we can emit the correct type of transaction
-    if is_write {
-        program.begin_write_operation();
+/// Triggers, views and virtual tables only live in the main database so far.
+fn main_database_only(database_id: usize) -> Result<usize> {
+    if database_id != 0 {
+        bail_parse_error!(
+            "statement not supported on {} yet",
+            database_description(database_id)
+        );
     }
+    Ok(0)
+}
 
-    // Indicate read operations so that in the epilogue we can emit the correct type of transaction
-    if is_select && !program.table_references.is_empty() {
-        program.begin_read_operation();
+/// ALTER and DROP are supported on the temp database, but not on attached databases yet.
+fn schema_change_database(database_id: usize) -> Result<usize> {
+    if database_id != 0 && database_id != TEMP_DB_INDEX {
+        bail_parse_error!("statement not supported on attached databases yet");
     }
+    Ok(database_id)
+}
 
-    Ok(program)
+/// How errors about writes that are not supported outside the main database name the database.
+fn database_description(database_id: usize) -> &'static str {
+    if database_id == TEMP_DB_INDEX {
+        "the temp database"
+    } else {
+        "attached databases"
+    }
 }
@@ -0,0 +1,177 @@
+//! Namespaces split one database file into independent sets of tables, indexes, views and
+//! triggers. The schema table of a namespace is the b-tree of an internal table of the default
+//! namespace, named after [crate::schema::NAMESPACE_TABLE_PREFIX]; the b-trees of its objects
+//! live in the same file.
+
+use std::sync::Arc;
+
+use crate::error::SQLITE_CONSTRAINT;
+use crate::schema::namespace_table_name;
+use crate::storage::pager::CreateBTreeFlags;
+use crate::translate::emitter::Resolver;
+use crate::translate::schema::{
+    emit_drop_table, emit_schema_entry, SchemaEntryType, SQLITE_TABLEID,
+};
+use crate::util::normalize_ident;
+use crate::vdbe::builder::{CursorType, ProgramBuilder};
+use crate::vdbe::insn::{Cookie, Insn};
+use crate::{bail_parse_error, Connection, Result};
+use turso_parser::ast;
+
+/// The name `USE` accepts for the default namespace.
+const DEFAULT_NAMESPACE_NAME: &str = "main";
+
+fn validate_namespace_ddl(resolver: &Resolver, connection: &Connection) -> Result<()> {
+    if connection.mvcc_enabled() {
+        bail_parse_error!("namespaces are not supported with MVCC");
+    }
+    if resolver.schema.namespace.is_some() {
+        bail_parse_error!("namespaces can only be created and dropped from the default namespace");
+    }
+    Ok(())
+}
+
+pub fn translate_create_namespace(
+    name: &ast::Name,
+    if_not_exists: bool,
+    resolver: &Resolver,
+    mut program: ProgramBuilder,
+    connection: &Arc<Connection>,
+) -> Result<ProgramBuilder> {
+    validate_namespace_ddl(resolver, connection)?;
+    let namespace = normalize_ident(name.as_str());
+    if namespace == DEFAULT_NAMESPACE_NAME || namespace == "temp" {
+        bail_parse_error!("namespace name {namespace} is reserved");
+    }
+    let table_name = namespace_table_name(&namespace);
+    if resolver.schema.get_table(&table_name).is_some() {
+        if if_not_exists {
+            return Ok(program);
+        }
+        bail_parse_error!("namespace {namespace} already exists");
+    }
+
+    let root_reg = program.alloc_register();
+    program.emit_insn(Insn::CreateBtree {
+        db: 0,
+        root: root_reg,
+        flags: CreateBTreeFlags::new_table(),
+    });
+
+    let table = resolver.schema.get_btree_table(SQLITE_TABLEID).unwrap();
+    let sqlite_schema_cursor_id = program.alloc_cursor_id(CursorType::BTreeTable(table.clone()));
+    program.emit_insn(Insn::OpenWrite {
+        cursor_id: sqlite_schema_cursor_id,
+        root_page: table.root_page.into(),
+        db: 0,
+    });
+
+    // The internal table has the columns of sqlite_schema, as its b-tree is the schema table of
+    // the namespace.
+    let table_ident = ast::Name::exact(table_name.clone()).as_ident();
+    let sql = format!(
+        "CREATE TABLE {table_ident} (type TEXT, name TEXT, tbl_name TEXT, rootpage INT, sql TEXT)"
+    );
+    emit_schema_entry(
+        &mut program,
+        resolver,
+        sqlite_schema_cursor_id,
+        None, // cdc_table_cursor_id, no cdc for namespaces
+        SchemaEntryType::Table,
+        &table_name,
+        &table_name,
+        root_reg,
+        Some(sql),
+    )?;
+
+    program.emit_insn(Insn::ParseSchema {
+        db: 0,
+        where_clause: Some(format!("name = '{}'", table_name.replace('\'', "''"))),
+    });
+
+    program.emit_insn(Insn::SetCookie {
+        db: 0,
+        cookie: Cookie::SchemaVersion,
+        value: (resolver.schema.schema_version + 1) as i32,
+        p5: 0,
+    });
+
+    Ok(program)
+}
+
+pub fn translate_drop_namespace(
+    name: &ast::Name,
+    if_exists: bool,
+    resolver: &mut Resolver,
+    mut program: ProgramBuilder,
+    connection: &Arc<Connection>,
+) -> Result<ProgramBuilder> {
+    validate_namespace_ddl(resolver, connection)?;
+    let namespace = normalize_ident(name.as_str());
+    let table_name = namespace_table_name(&namespace);
+    let (Some(table), Some(btree)) = (
+        resolver.schema.get_table(&table_name),
+        resolver.schema.get_btree_table(&table_name),
+    ) else {
+        if if_exists {
+            return Ok(program);
+        }
+        bail_parse_error!("no such namespace: {namespace}");
+    };
+
+    // The objects of a namespace are not dropped with it, so its schema table must be empty.
+    let cursor_id = program.alloc_cursor_id(CursorType::BTreeTable(btree.clone()));
+    program.emit_insn(Insn::OpenRead {
+        cursor_id,
+        root_page: btree.root_page,
+        db: 0,
+    });
+    let empty_label = program.allocate_label();
+    program.emit_insn(Insn::Rewind {
+        cursor_id,
+        pc_if_empty: empty_label,
+    });
+    program.emit_insn(Insn::Halt {
+        err_code: SQLITE_CONSTRAINT,
+        description: format!("namespace {namespace} is not empty"),
+    });
+    program.preassign_label_to_next_insn(empty_label);
+    program.emit_insn(Insn::Close { cursor_id });
+
+    emit_drop_table(&table_name, table, 0, resolver, program, connection)
+}
+
+pub fn translate_use(
+    name: &ast::Name,
+    resolver: &Resolver,
+    mut program: ProgramBuilder,
+    connection: &Arc<Connection>,
+) -> Result<ProgramBuilder> {
+    if connection.mvcc_enabled() {
+        bail_parse_error!("namespaces are not supported with MVCC");
+    }
+    if !connection.get_auto_commit() {
+        bail_parse_error!("cannot change namespace within a transaction");
+    }
+    let namespace = normalize_ident(name.as_str());
+    let namespace = if namespace == DEFAULT_NAMESPACE_NAME {
+        None
+    } else {
+        let default_schema = connection.default_namespace_schema();
+        if default_schema
+            .get_btree_table(&namespace_table_name(&namespace))
+            .is_none()
+        {
+            bail_parse_error!("no such namespace: {namespace}");
+        }
+        Some(namespace)
+    };
+    if namespace == resolver.schema.namespace {
+        return Ok(program);
+    }
+
+    program.emit_insn(Insn::UseNamespace { namespace });
+    // The schema table of the namespace may have to be read.
+    program.begin_read_operation();
+    Ok(program)
+}
@@ -9,7 +9,6 @@ use crate::translate::optimizer::constraints::{
 };
 use crate::translate::optimizer::cost::{RowCountEstimate, ESTIMATED_HARDCODED_ROWS_PER_PAGE};
 use crate::translate::plan::{HashJoinKey, NonFromClauseSubquery, SubqueryState, WhereTerm};
-use crate::util::exprs_are_equivalent;
 use crate::vdbe::hash_table::DEFAULT_MEM_BUDGET;
 use crate::{
     schema::{Index, Table},
@@ -21,7 +20,7 @@ use crate::{
 use super::{
     constraints::{usable_constraints_for_join_order, TableConstraints},
     cost::{estimate_cost_for_scan_or_seek, Cost, IndexInfo},
-    order::OrderTarget,
+    order::{match_index_to_order_target, num_leading_eq_constraints, OrderTarget},
 };
 use crate::translate::optimizer::order::ColumnTarget;
 
@@ -116,17 +115,46 @@ fn find_best_access_method_for_btree(
     base_row_count: RowCountEstimate,
 ) -> Result<Option<AccessMethod>> {
     let table_no = join_order.last().unwrap().table_id;
-    let mut best_cost =
-        estimate_cost_for_scan_or_seek(None, &[], &[], input_cardinality, base_row_count);
+    // The rows of a WITHOUT ROWID table live in its primary key index, so there is no rowid
+    // access path, and the primary key index takes the place of the full table scan.
+    let without_rowid_pk_index = match &rhs_table.table {
+        Table::BTree(btree) if !btree.has_rowid => rhs_constraints
+            .candidates
+            .iter()
+            .filter_map(|candidate| candidate.index.as_ref())
+            .find(|index| index.is_without_rowid_primary_key(btree)),
+        _ => None,
+    };
+    let mut best_cost = estimate_cost_for_scan_or_seek(
+        without_rowid_pk_index.map(|index| IndexInfo {
+            unique: true,
+            covering: true,
+            column_count: index.columns.len(),
+        }),
+        &[],
+        &[],
+        input_cardinality,
+        base_row_count,
+    );
     let mut best_params = AccessMethodParams::BTreeTable {
         iter_dir: IterationDirection::Forwards,
-        index: None,
+        index: without_rowid_pk_index.cloned(),
         constraint_refs: vec![],
     };
     let rowid_column_idx = rhs_table.columns().iter().position(|c| c.is_rowid_alias());
 
     // Estimate cost for each candidate index (including the rowid index) and replace best_access_method if the cost is lower.
     for candidate in rhs_constraints.candidates.iter() {
+        // Other indexes of a WITHOUT ROWID table can only be used when they cover the query,
+        // as rows cannot be looked up from them by rowid.
+        if without_rowid_pk_index.is_some()
+            && !candidate
+                .index
+                .as_ref()
+                .is_some_and(|index| rhs_table.index_is_covering(index))
+        {
+            continue;
+        }
         let usable_constraint_refs = usable_constraints_for_join_order(
             &rhs_constraints.constraints,
             &candidate.refs,
@@ -155,63 +183,60 @@ fn find_best_access_method_for_btree(
         );
 
         // All other things being equal, prefer an access method that satisfies the order target.
-        let (iter_dir, order_satisfiability_bonus) = if let Some(order_target) = maybe_order_target
-        {
-            // If the index delivers rows in the same direction (or the exact reverse direction) as the order target, then it
-            // satisfies the order target.
-            let mut all_same_direction = true;
-            let mut all_opposite_direction = true;
-            for i in 0..order_target.0.len().min(index_info.column_count) {
-                let target = &order_target.0[i];
-                let correct_table = target.table_id == table_no;
-                let correct_column = match (&target.target, &candidate.index) {
-                    // Regular column target on normal index, simply check column number
-                    // against index column's pos_in_table
-                    (ColumnTarget::Column(col_no), Some(index)) => {
-                        index.columns[i].expr.is_none() && index.columns[i].pos_in_table == *col_no
-                    }
-                    // Expression column target on expression index, check expr equivalence
-                    (ColumnTarget::Expr(expr), Some(index)) => index.columns[i]
-                        .expr
-                        .as_ref()
-                        .is_some_and(|e| exprs_are_equivalent(e, unsafe { &**expr })),
-                    // Normal column target on rowid index, check if column is rowid
-                    (ColumnTarget::Column(col_no), None) => {
-                        rowid_column_idx.is_some_and(|idx| idx == *col_no)
-                    }
-                    _ => false,
-                };
-                if !correct_table || !correct_column {
-                    all_same_direction = false;
-                    all_opposite_direction = false;
-                    break;
-                }
-                let correct_order = {
-                    match &candidate.index {
-                        Some(index) => target.order == index.columns[i].order,
-                        None => target.order == SortOrder::Asc,
-                    }
+        let (iter_dir, order_satisfiability_bonus) = match (maybe_order_target, &candidate.index) {
+            (Some(order_target), Some(index)) => {
+                // Equality constraints are only constant for the whole scan in the outermost loop.
+                let num_eq_cols = if join_order.len() == 1 {
+                    num_leading_eq_constraints(&usable_constraint_refs)
+                } else {
+                    0
                 };
-                if correct_order {
-                    all_opposite_direction = false;
+                let order_match = match_index_to_order_target(
+                    &order_target.0,
+                    table_no,
+                    index,
+                    rowid_column_idx,
+                    num_eq_cols,
+                    order_target.ignores_direction(),
+                );
+                // The index delivers rows in the target order as far as it goes.
+                let satisfies = order_match.consumed > 0
+                    && (order_match.consumed == order_target.0.len()
+                        || order_match.exhausted_index);
+                if satisfies {
+                    (
+                        order_match
+                            .direction
+                            .unwrap_or(IterationDirection::Forwards),
+                        Cost(1.0),
+                    )
                 } else {
-                    all_same_direction = false;
+                    (IterationDirection::Forwards, Cost(0.0))
                 }
             }
-            if all_same_direction || all_opposite_direction {
-                (
-                    if all_same_direction {
-                        IterationDirection::Forwards
-                    } else {
-                        IterationDirection::Backwards
-                    },
-                    Cost(1.0),
-                )
-            } else {
-                (IterationDirection::Forwards, Cost(0.0))
+            (Some(order_target), None) => {
+                // The rowid index delivers rows in ascending rowid order.
+                match order_target.0.first() {
+                    Some(target)
+                        if target.table_id == table_no
+                            && matches!(
+                                target.target,
+                                ColumnTarget::Column(col_no) if Some(col_no) == rowid_column_idx
+                            ) =>
+                    {
+                        (
+                            if target.order == SortOrder::Asc {
+                                IterationDirection::Forwards
+                            } else {
+                                IterationDirection::Backwards
+                            },
+                            Cost(1.0),
+                        )
+                    }
+                    _ => (IterationDirection::Forwards, Cost(0.0)),
+                }
             }
-        } else {
-            (IterationDirection::Forwards, Cost(0.0))
+            (None, _) => (IterationDirection::Forwards, Cost(0.0)),
         };
         if cost < best_cost + order_satisfiability_bonus {
             best_cost = cost;
@@ -429,9 +454,13 @@ pub fn try_hash_join_access_method(
     // Avoid hash join on self-joins over the same underlying table. The current
     // implementation assumes distinct build/probe sources; sharing storage can
     // lead to incorrect matches.
-    let probe_root_page = probe_table.table.btree().expect("table is BTree").root_page;
-    let build_root_page = build_table.table.btree().expect("table is BTree").root_page;
-    if build_root_page == probe_root_page {
+    let probe_btree = probe_table.table.btree().expect("table is BTree");
+    let build_btree = build_table.table.btree().expect("table is BTree");
+    if build_btree.root_page == probe_btree.root_page {
+        return None;
+    }
+    // Hash joins key their payloads by rowid, which WITHOUT ROWID tables do not have.
+    if !build_btree.has_rowid || !probe_btree.has_rowid {
         return None;
     }
     // Hash joins only support INNER JOIN semantics.
@@ -4,7 +4,10 @@ use crate::{
         collate::get_collseq_from_expr,
         expr::{as_binary_components, comparison_affinity},
         expression_index::normalize_expr_for_index_matching,
-        plan::{JoinOrderMember, JoinedTable, NonFromClauseSubquery, TableReferences, WhereTerm},
+        plan::{
+            JoinOrderMember, JoinedTable, NonFromClauseSubquery, SeekKeyComponent, TableReferences,
+            WhereTerm,
+        },
         planner::{table_mask_from_expr, TableMask},
     },
     util::exprs_are_equivalent,
@@ -62,6 +65,10 @@ pub struct Constraint {
     /// Whether the constraint is usable for an index seek.
     /// This is explicitly set to false if the constraint has a different collation than the constrained column.
     pub usable: bool,
+    /// For a comparison of row values, e.g. `(t.x, t.y) > (1, 2)`, the positions in the table's schema
+    /// of the columns compared after the constrained column, or None where the element of the row value
+    /// is not a column of the table. Empty for comparisons of single values.
+    pub row_value_columns: Vec<Option<usize>>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -72,45 +79,97 @@ pub enum BinaryExprSide {
 
 impl Constraint {
     /// Get the constraining expression and operator, e.g. ('>=', '2+3') from 't.x >= 2+3'
+    ///
+    /// For a comparison of row values, this is the first element of the constraining row value,
+    /// e.g. ('>', '1') from '(t.x, t.y) > (1, 2)'. Note that the operator is the one of the row
+    /// value comparison, which is too strict for the first element alone.
     pub fn get_constraining_expr(
         &self,
         where_clause: &[WhereTerm],
         referenced_tables: Option<&TableReferences>,
     ) -> (ast::Operator, ast::Expr, Affinity) {
-        let (idx, side) = self.where_clause_pos;
-        let where_term = &where_clause[idx];
-        let Ok(Some((lhs, op, rhs))) = as_binary_components(&where_term.expr) else {
-            panic!("Expected a valid binary expression");
+        let (lhs, op, rhs) = self.binary_components(where_clause);
+        let (lhs, rhs) = match (lhs, rhs) {
+            (ast::Expr::Parenthesized(lhs), ast::Expr::Parenthesized(rhs))
+                if self.is_row_value() =>
+            {
+                (lhs[0].as_ref(), rhs[0].as_ref())
+            }
+            _ => (lhs, rhs),
         };
-        let mut affinity = Affinity::Blob;
-        if op.is_comparison() && self.table_col_pos.is_some() {
-            affinity = comparison_affinity(lhs, rhs, referenced_tables);
-        }
+        let (expr, affinity) = self.constraining_expr_and_affinity(lhs, op, rhs, referenced_tables);
+        (self.operator, expr, affinity)
+    }
 
-        if side == BinaryExprSide::Lhs {
-            if affinity.expr_needs_no_affinity_change(lhs) {
-                affinity = Affinity::Blob;
-            }
-            (self.operator, lhs.clone(), affinity)
+    /// Get the first `len` elements of the constraining row value of a comparison of row values,
+    /// with the affinity of the comparison of each element, e.g. [('1', _), ('2', _)] from
+    /// '(t.x, t.y, t.z) > (1, 2, 3)' for a `len` of 2.
+    pub fn get_constraining_row(
+        &self,
+        where_clause: &[WhereTerm],
+        referenced_tables: Option<&TableReferences>,
+        len: usize,
+    ) -> Vec<(ast::Expr, Affinity)> {
+        let (lhs, op, rhs) = self.binary_components(where_clause);
+        let (ast::Expr::Parenthesized(lhs), ast::Expr::Parenthesized(rhs)) = (lhs, rhs) else {
+            panic!("Expected a comparison of row values");
+        };
+        lhs.iter()
+            .zip(rhs.iter())
+            .take(len)
+            .map(|(lhs, rhs)| self.constraining_expr_and_affinity(lhs, op, rhs, referenced_tables))
+            .collect()
+    }
+
+    pub fn get_constraining_expr_ref<'a>(&self, where_clause: &'a [WhereTerm]) -> &'a ast::Expr {
+        let (lhs, _, rhs) = self.binary_components(where_clause);
+        let expr = if self.where_clause_pos.1 == BinaryExprSide::Lhs {
+            lhs
         } else {
-            if affinity.expr_needs_no_affinity_change(rhs) {
-                affinity = Affinity::Blob;
-            }
-            (self.operator, rhs.clone(), affinity)
+            rhs
+        };
+        match expr {
+            ast::Expr::Parenthesized(row) if self.is_row_value() => &row[0],
+            _ => expr,
         }
     }
 
-    pub fn get_constraining_expr_ref<'a>(&self, where_clause: &'a [WhereTerm]) -> &'a ast::Expr {
-        let (idx, side) = self.where_clause_pos;
-        let where_term = &where_clause[idx];
-        let Ok(Some((lhs, _, rhs))) = as_binary_components(&where_term.expr) else {
+    /// Whether the constraint is a comparison of row values, e.g. `(t.x, t.y) > (1, 2)`.
+    pub fn is_row_value(&self) -> bool {
+        !self.row_value_columns.is_empty()
+    }
+
+    fn binary_components<'a>(
+        &self,
+        where_clause: &'a [WhereTerm],
+    ) -> (&'a ast::Expr, ast::Operator, &'a ast::Expr) {
+        let where_term = &where_clause[self.where_clause_pos.0];
+        let Ok(Some(components)) = as_binary_components(&where_term.expr) else {
             panic!("Expected a valid binary expression");
         };
-        if side == BinaryExprSide::Lhs {
+        components
+    }
+
+    fn constraining_expr_and_affinity(
+        &self,
+        lhs: &ast::Expr,
+        op: ast::Operator,
+        rhs: &ast::Expr,
+        referenced_tables: Option<&TableReferences>,
+    ) -> (ast::Expr, Affinity) {
+        let mut affinity = Affinity::Blob;
+        if op.is_comparison() && self.table_col_pos.is_some() {
+            affinity = comparison_affinity(lhs, rhs, referenced_tables);
+        }
+        let expr = if self.where_clause_pos.1 == BinaryExprSide::Lhs {
             lhs
         } else {
             rhs
+        };
+        if affinity.expr_needs_no_affinity_change(expr) {
+            affinity = Affinity::Blob;
         }
+        (expr.clone(), affinity)
     }
 }
 
@@ -125,6 +184,10 @@ pub struct ConstraintRef {
     pub index_col_pos: usize,
     /// The sort order of the constrained column in the index. Always ascending for rowid indices.
     pub sort_order: SortOrder,
+    /// The number of consecutive index columns, starting at `index_col_pos`, that are compared by
+    /// the constraint. This is more than 1 only for comparisons of row values, e.g. `(x, y) > (1, 2)`
+    /// over an index on `(x, y)`.
+    pub row_value_len: usize,
 }
 
 /// A collection of [ConstraintRef]s for a given index, or if index is None, for the table's rowid index.
@@ -142,11 +205,13 @@ pub struct ConstraintRef {
 ///             constraint_vec_pos: 0, // y = 10
 ///             index_col_pos: 0, // y
 ///             sort_order: SortOrder::Desc,
+///             row_value_len: 1,
 ///         },
 ///         ConstraintRef {
 ///             constraint_vec_pos: 1, // z = 20
 ///             index_col_pos: 1, // z
 ///             sort_order: SortOrder::Asc,
+///             row_value_len: 1,
 ///         },
 ///     ],
 /// }
@@ -335,6 +400,22 @@ pub fn constraints_from_where_clause(
                 }
             }
 
+            // A comparison of row values, e.g. (t.x, t.y) > (1, 2), constrains the first column of
+            // the row value, and possibly the columns that follow it in an index.
+            if let Some(constraint) = row_value_constraint(
+                i,
+                lhs,
+                operator,
+                rhs,
+                table_reference,
+                table_references,
+                subqueries,
+                rowid_alias_column,
+            )? {
+                cs.constraints.push(constraint);
+                continue;
+            }
+
             // If either the LHS or RHS of the constraint is a column from the table, add the constraint.
             let table_name = table_reference.table.get_name();
             match lhs {
@@ -356,6 +437,7 @@ pub fn constraints_from_where_clause(
                                 operator,
                             ),
                             usable: true,
+                            row_value_columns: Vec::new(),
                         });
                     }
                 }
@@ -381,6 +463,7 @@ pub fn constraints_from_where_clause(
                                 operator,
                             ),
                             usable: true,
+                            row_value_columns: Vec::new(),
                         });
                     }
                 }
@@ -399,6 +482,7 @@ pub fn constraints_from_where_clause(
                         lhs_mask: table_mask_from_expr(rhs, table_references, subqueries)?,
                         selectivity: SELECTIVITY_OTHER,
                         usable: true,
+                        row_value_columns: Vec::new(),
                     });
                 }
                 _ => {}
@@ -422,6 +506,7 @@ pub fn constraints_from_where_clause(
                                 operator,
                             ),
                             usable: true,
+                            row_value_columns: Vec::new(),
                         });
                     }
                 }
@@ -444,6 +529,7 @@ pub fn constraints_from_where_clause(
                                 operator,
                             ),
                             usable: true,
+                            row_value_columns: Vec::new(),
                         });
                     }
                 }
@@ -462,6 +548,7 @@ pub fn constraints_from_where_clause(
                         lhs_mask: table_mask_from_expr(lhs, table_references, subqueries)?,
                         selectivity: SELECTIVITY_OTHER,
                         usable: true,
+                        row_value_columns: Vec::new(),
                     });
                 }
                 _ => {}
@@ -514,6 +601,7 @@ pub fn constraints_from_where_clause(
                     constraint_vec_pos: i,
                     index_col_pos: 0,
                     sort_order: SortOrder::Asc,
+                    row_value_len: 1,
                 });
             }
             for index in available_indexes
@@ -555,6 +643,12 @@ pub fn constraints_from_where_clause(
                             constraint_vec_pos: i,
                             index_col_pos: position_in_index,
                             sort_order: index.columns[position_in_index].order,
+                            row_value_len: row_value_len_in_index(
+                                constraint,
+                                index,
+                                position_in_index,
+                                table_reference,
+                            ),
                         });
                     }
                 }
@@ -580,6 +674,125 @@ pub fn constraints_from_where_clause(
     Ok(constraints)
 }
 
+/// Build a [Constraint] for a comparison of row values whose left or right side starts with a column
+/// of the table, e.g. `(t.x, t.y) > (1, 2)`. Only comparisons that can bound an index range are considered;
+/// equalities of row values are already split into equalities of their elements by the planner.
+#[allow(clippy::too_many_arguments)]
+fn row_value_constraint(
+    where_clause_pos: usize,
+    lhs: &ast::Expr,
+    operator: ast::Operator,
+    rhs: &ast::Expr,
+    table_reference: &JoinedTable,
+    table_references: &TableReferences,
+    subqueries: &[NonFromClauseSubquery],
+    rowid_alias_column: Option<usize>,
+) -> Result<Option<Constraint>> {
+    let (ast::Expr::Parenthesized(lhs_row), ast::Expr::Parenthesized(rhs_row)) = (lhs, rhs) else {
+        return Ok(None);
+    };
+    if lhs_row.len() < 2
+        || lhs_row.len() != rhs_row.len()
+        || !matches!(
+            operator,
+            ast::Operator::Greater
+                | ast::Operator::GreaterEquals
+                | ast::Operator::Less
+                | ast::Operator::LessEquals
+        )
+    {
+        return Ok(None);
+    }
+    let column_of_table = |expr: &ast::Expr| match expr {
+        ast::Expr::Column { table, column, .. } if *table == table_reference.internal_id => {
+            Some(*column)
+        }
+        ast::Expr::RowId { table, .. } if *table == table_reference.internal_id => {
+            rowid_alias_column
+        }
+        _ => None,
+    };
+    for (side, row, other_row, other, operator) in [
+        (BinaryExprSide::Rhs, lhs_row, rhs_row, rhs, operator),
+        (
+            BinaryExprSide::Lhs,
+            rhs_row,
+            lhs_row,
+            lhs,
+            opposite_cmp_op(operator),
+        ),
+    ] {
+        let Some(table_col_pos) = column_of_table(&row[0]) else {
+            continue;
+        };
+        let mut row_value_columns = Vec::with_capacity(row.len() - 1);
+        for (expr, other_expr) in row.iter().zip(other_row.iter()).skip(1) {
+            let column = column_of_table(expr).filter(|&pos| {
+                // The element can only be part of an index key if it is compared with the collation of the column.
+                let collation = table_reference.table.columns()[pos].collation();
+                get_collseq_from_expr(other_expr, table_references)
+                    .is_ok_and(|c| c.is_none_or(|c| c == collation))
+            });
+            row_value_columns.push(column);
+        }
+        return Ok(Some(Constraint {
+            where_clause_pos: (where_clause_pos, side),
+            operator,
+            table_col_pos: Some(table_col_pos),
+            expr: None,
+            lhs_mask: table_mask_from_expr(other, table_references, subqueries)?,
+            selectivity: SELECTIVITY_RANGE_FALLBACK,
+            usable: true,
+            row_value_columns,
+        }));
+    }
+    Ok(None)
+}
+
+/// The number of consecutive columns of `index`, starting at `position_in_index`, that are compared by
+/// the constraint in the same order as they appear in the index. Always 1 unless the constraint is a
+/// comparison of row values, e.g. `(x, y) > (1, 2)` covers 2 columns of an index on `(x, y)` but only
+/// 1 column of an index on `(x, z)` or `(x, y DESC)`.
+///
+/// Index entries end with the rowid, so e.g. `(x, id) > (1, 2)`, where `id` is the rowid alias, also
+/// covers 2 columns of an index on `(x)`.
+fn row_value_len_in_index(
+    constraint: &Constraint,
+    index: &Index,
+    position_in_index: usize,
+    table_reference: &JoinedTable,
+) -> usize {
+    let sort_order = index.columns[position_in_index].order;
+    let len = 1 + constraint
+        .row_value_columns
+        .iter()
+        .zip(index.columns.iter().skip(position_in_index + 1))
+        .take_while(|(table_col_pos, index_column)| {
+            table_col_pos.is_some_and(|pos| {
+                index_column.expr.is_none()
+                    && index_column.pos_in_table == pos
+                    && index_column.order == sort_order
+                    && index_column.collation.unwrap_or_default()
+                        == table_reference.table.columns()[pos].collation()
+            })
+        })
+        .count();
+    let compares_rowid = len == index.columns.len() - position_in_index
+        && index.has_rowid
+        && sort_order == SortOrder::Asc
+        && constraint
+            .row_value_columns
+            .get(len - 1)
+            .copied()
+            .flatten()
+            .is_some_and(|pos| table_reference.table.columns()[pos].is_rowid_alias());
+    if compares_rowid {
+        len + 1
+    } else {
+        len
+    }
+}
+
 #[derive(Clone, Debug)]
 /// A reference to a [Constraint]s in a [TableConstraints] for single column.
 ///
@@ -603,6 +816,10 @@ pub struct RangeConstraintRef {
     pub lower_bound: Option<usize>,
     /// upper bound constraint (either < or <=)
     pub upper_bound: Option<usize>,
+    /// number of index columns compared by the lower bound constraint, see [ConstraintRef::row_value_len]
+    pub lower_bound_len: usize,
+    /// number of index columns compared by the upper bound constraint, see [ConstraintRef::row_value_len]
+    pub upper_bound_len: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -610,8 +827,8 @@ pub struct RangeConstraintRef {
 pub struct SeekRangeConstraint {
     pub sort_order: SortOrder,
     pub eq: Option<(ast::Operator, ast::Expr, Affinity)>,
-    pub lower_bound: Option<(ast::Operator, ast::Expr, Affinity)>,
-    pub upper_bound: Option<(ast::Operator, ast::Expr, Affinity)>,
+    pub lower_bound: Option<(ast::Operator, SeekKeyComponent<ast::Expr>, Affinity)>,
+    pub upper_bound: Option<(ast::Operator, SeekKeyComponent<ast::Expr>, Affinity)>,
 }
 
 impl SeekRangeConstraint {
@@ -625,8 +842,8 @@ impl SeekRangeConstraint {
     }
     pub fn new_range(
         sort_order: SortOrder,
-        lower_bound: Option<(ast::Operator, ast::Expr, Affinity)>,
-        upper_bound: Option<(ast::Operator, ast::Expr, Affinity)>,
+        lower_bound: Option<(ast::Operator, SeekKeyComponent<ast::Expr>, Affinity)>,
+        upper_bound: Option<(ast::Operator, SeekKeyComponent<ast::Expr>, Affinity)>,
     ) -> Self {
         assert!(lower_bound.is_some() || upper_bound.is_some());
         Self {
@@ -654,14 +871,59 @@ impl RangeConstraintRef {
         }
         SeekRangeConstraint::new_range(
             self.sort_order,
-            self.lower_bound
-                .map(|x| constraints[x].get_constraining_expr(where_clause, referenced_tables)),
-            self.upper_bound
-                .map(|x| constraints[x].get_constraining_expr(where_clause, referenced_tables)),
+            self.lower_bound.map(|x| {
+                seek_bound(
+                    &constraints[x],
+                    self.lower_bound_len,
+                    where_clause,
+                    referenced_tables,
+                )
+            }),
+            self.upper_bound.map(|x| {
+                seek_bound(
+                    &constraints[x],
+                    self.upper_bound_len,
+                    where_clause,
+                    referenced_tables,
+                )
+            }),
         )
     }
 }
 
+/// The bound of a seek key for a range constraint that compares `len` index columns.
+///
+/// A comparison of row values compares the row values lexicographically, so e.g. `(x, y, z) > (1, 2, 3)`
+/// over an index on `(x, y)` can seek to the first entry with `(x, y) >= (1, 2)`, and the rows
+/// before `(x, y, z) > (1, 2, 3)` are then filtered out by the comparison itself, which is never consumed.
+fn seek_bound(
+    constraint: &Constraint,
+    len: usize,
+    where_clause: &[WhereTerm],
+    referenced_tables: Option<&TableReferences>,
+) -> (ast::Operator, SeekKeyComponent<ast::Expr>, Affinity) {
+    if !constraint.is_row_value() {
+        let (op, expr, affinity) =
+            constraint.get_constraining_expr(where_clause, referenced_tables);
+        return (op, SeekKeyComponent::Expr(expr), affinity);
+    }
+    let op = if len == constraint.row_value_columns.len() + 1 {
+        constraint.operator
+    } else {
+        match constraint.operator {
+            ast::Operator::Greater => ast::Operator::GreaterEquals,
+            ast::Operator::Less => ast::Operator::LessEquals,
+            op => op,
+        }
+    };
+    if len == 1 {
+        let (_, expr, affinity) = constraint.get_constraining_expr(where_clause, referenced_tables);
+        return (op, SeekKeyComponent::Expr(expr), affinity);
+    }
+    let row = constraint.get_constraining_row(where_clause, referenced_tables, len);
+    (op, SeekKeyComponent::Row(row), Affinity::Blob)
+}
+
 /// Find which [Constraint]s are usable for a given join order.
 /// Returns a slice of the references to the constraints that are usable.
 /// A constraint is considered usable for a given table if all of the other tables referenced by the constraint
@@ -708,9 +970,11 @@ pub fn usable_constraints_for_join_order<'a>(
             match constraints[cref.constraint_vec_pos].operator {
                 ast::Operator::Greater | ast::Operator::GreaterEquals => {
                     usable.last_mut().unwrap().lower_bound = Some(cref.constraint_vec_pos);
+                    usable.last_mut().unwrap().lower_bound_len = cref.row_value_len;
                 }
                 ast::Operator::Less | ast::Operator::LessEquals => {
                     usable.last_mut().unwrap().upper_bound = Some(cref.constraint_vec_pos);
+                    usable.last_mut().unwrap().upper_bound_len = cref.row_value_len;
                 }
                 _ => {}
             }
@@ -743,6 +1007,8 @@ pub fn usable_constraints_for_join_order<'a>(
                 eq: Some(cref.constraint_vec_pos),
                 lower_bound: None,
                 upper_bound: None,
+                lower_bound_len: 1,
+                upper_bound_len: 1,
             },
             ast::Operator::Greater | ast::Operator::GreaterEquals => RangeConstraintRef {
                 table_col_pos,
@@ -751,6 +1017,8 @@ pub fn usable_constraints_for_join_order<'a>(
                 eq: None,
                 lower_bound: Some(cref.constraint_vec_pos),
                 upper_bound: None,
+                lower_bound_len: cref.row_value_len,
+                upper_bound_len: 1,
             },
             ast::Operator::Less | ast::Operator::LessEquals => RangeConstraintRef {
                 table_col_pos,
@@ -759,6 +1027,8 @@ pub fn usable_constraints_for_join_order<'a>(
                 eq: None,
                 lower_bound: None,
                 upper_bound: Some(cref.constraint_vec_pos),
+                lower_bound_len: 1,
+                upper_bound_len: cref.row_value_len,
             },
             _ => continue,
         };
@@ -798,6 +1068,9 @@ pub fn convert_to_vtab_constraint(
         .iter()
         .enumerate()
         .filter_map(|(i, constraint)| {
+            if constraint.is_row_value() {
+                return None;
+            }
             let table_col_pos = constraint.table_col_pos?;
             let other_side_refers_to_self = constraint.lhs_mask.contains_table(table_idx);
             if other_side_refers_to_self {
@@ -99,6 +99,7 @@ pub fn join_lhs_and_rhs<'a>(
             let has_usable_constraints = rhs_constraints.constraints.iter().any(|c| {
                 c.usable
                     && c.table_col_pos.is_some()
+                    && !c.is_row_value()
                     && lhs_mask_for_ephemeral.contains_all(&c.lhs_mask)
             });
 
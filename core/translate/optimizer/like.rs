@@ -0,0 +1,120 @@
+use turso_parser::ast::{self, Expr, LikeOperator, Literal};
+
+use crate::{
+    schema::Table,
+    translate::{
+        collate::CollationSeq,
+        expr::sanitize_string,
+        plan::{TableReferences, WhereTerm},
+    },
+    vdbe::affinity::Affinity,
+};
+
+/// Adds the range terms implied by `LIKE` and `GLOB` patterns that start with a fixed prefix,
+/// so that the optimizer can use an index on the column to seek to the first matching row.
+///
+/// For example, `x GLOB 'abc*'` can only be true if `x >= 'abc'`. The original `LIKE`/`GLOB`
+/// term is kept and still evaluated for every row, so the derived range only has to contain all
+/// the matching values, it does not have to be exact. There is no upper bound such as
+/// `x < 'abd'`: BLOBs sort after all strings, and a BLOB whose bytes start with the prefix
+/// matches the pattern too.
+///
+/// The derived terms are appended to the end of the WHERE clause. Returns the position of the
+/// first derived term, which must be passed to [consume_like_prefix_range_terms] once the
+/// access methods have been chosen.
+pub fn add_like_prefix_range_terms(
+    where_clause: &mut Vec<WhereTerm>,
+    table_references: &TableReferences,
+) -> usize {
+    let first_derived_term = where_clause.len();
+    for i in 0..first_derived_term {
+        let term = &where_clause[i];
+        if term.consumed {
+            continue;
+        }
+        let Some((column, prefix)) = like_prefix(&term.expr, table_references) else {
+            continue;
+        };
+        let from_outer_join = term.from_outer_join;
+        where_clause.push(WhereTerm {
+            expr: Expr::Binary(
+                Box::new(column),
+                ast::Operator::GreaterEquals,
+                Box::new(Expr::Literal(Literal::String(format!(
+                    "'{}'",
+                    prefix.replace('\'', "''")
+                )))),
+            ),
+            from_outer_join,
+            consumed: false,
+        });
+    }
+    first_derived_term
+}
+
+/// Marks all the terms added by [add_like_prefix_range_terms] as consumed.
+///
+/// The terms that were used as index seek keys are already consumed. The others are redundant
+/// with the `LIKE`/`GLOB` term, so they must not be evaluated as filters.
+pub fn consume_like_prefix_range_terms(where_clause: &mut [WhereTerm], first_derived_term: usize) {
+    for term in where_clause[first_derived_term..].iter_mut() {
+        term.consumed = true;
+    }
+}
+
+/// Returns the column constrained by a `LIKE` or `GLOB` term and the smallest value it can
+/// match, if the pattern starts with a usable prefix.
+fn like_prefix(expr: &Expr, table_references: &TableReferences) -> Option<(Expr, String)> {
+    let Expr::Like {
+        lhs,
+        not: false,
+        op,
+        rhs,
+        escape: None,
+    } = expr
+    else {
+        return None;
+    };
+    let Expr::Column { table, column, .. } = lhs.as_ref() else {
+        return None;
+    };
+    let Expr::Literal(Literal::String(pattern)) = rhs.as_ref() else {
+        return None;
+    };
+    let joined_table = table_references.find_joined_table_by_internal_id(*table)?;
+    if !matches!(joined_table.table, Table::BTree(_)) {
+        return None;
+    }
+    // Values of columns without TEXT affinity may be numbers, which sort before all strings
+    // even when their text representation matches the pattern.
+    let table_column = joined_table.table.columns().get(*column)?;
+    if table_column.affinity() != Affinity::Text {
+        return None;
+    }
+
+    let pattern = sanitize_string(pattern);
+    let wildcards: &[char] = match op {
+        LikeOperator::Glob => &['*', '?', '['],
+        LikeOperator::Like => &['%', '_'],
+        LikeOperator::Match | LikeOperator::Regexp => return None,
+    };
+    let prefix = pattern
+        .chars()
+        .take_while(|c| !wildcards.contains(c))
+        .collect::<String>();
+    if prefix.is_empty() {
+        return None;
+    }
+
+    // LIKE folds the case of ASCII letters. Unless the column compares them case-insensitively,
+    // the smallest value it can match starts with the uppercase form of the prefix, because
+    // uppercase letters sort before lowercase ones.
+    let prefix = match (op, table_column.collation()) {
+        (LikeOperator::Like, CollationSeq::Binary | CollationSeq::Rtrim) => {
+            prefix.to_ascii_uppercase()
+        }
+        _ => prefix,
+    };
+
+    Some((lhs.as_ref().clone(), prefix))
+}
@@ -0,0 +1,83 @@
+use turso_parser::ast::{Expr, SortOrder};
+
+use crate::{
+    function::AggFunc,
+    schema::Table,
+    translate::plan::{SelectPlan, WhereTerm},
+};
+
+pub struct MinMaxOrderTarget {
+    /// The position of the `x IS NOT NULL` term added for MIN.
+    not_null_term: Option<usize>,
+}
+
+/// Prepares a query like `SELECT min(x) FROM t` to read a single row from an index on `x`
+/// instead of scanning the whole table, like SQLite does. The rows are requested in the order
+/// of `x`: ascending for MIN, skipping the NULLs that sort first, and descending for MAX. If
+/// the access method chosen for the table provides that order, the first row holds the result,
+/// and [finish_min_max_order_target] sets [SelectPlan::min_max_from_first_row] so that the loop
+/// stops there.
+///
+/// Returns `None` if the query isn't a MIN or MAX query. Otherwise the return value must be
+/// passed to [finish_min_max_order_target] once the access methods have been chosen.
+pub fn add_min_max_order_target(plan: &mut SelectPlan) -> Option<MinMaxOrderTarget> {
+    let [aggregate] = plan.aggregates.as_slice() else {
+        return None;
+    };
+    let [table] = plan.table_references.joined_tables() else {
+        return None;
+    };
+    if plan.group_by.is_some()
+        || plan.window.is_some()
+        || !plan.order_by.is_empty()
+        || !matches!(table.table, Table::BTree(_))
+    {
+        return None;
+    }
+    let sort_order = match aggregate.func {
+        AggFunc::Min => SortOrder::Asc,
+        AggFunc::Max => SortOrder::Desc,
+        _ => return None,
+    };
+    let [arg @ (Expr::Column {
+        table: table_id, ..
+    }
+    | Expr::RowId {
+        table: table_id, ..
+    })] = aggregate.args.as_slice()
+    else {
+        return None;
+    };
+    if *table_id != table.internal_id {
+        return None;
+    }
+
+    let arg = arg.clone();
+    plan.order_by = vec![(Box::new(arg.clone()), sort_order)];
+    if sort_order == SortOrder::Desc {
+        return Some(MinMaxOrderTarget {
+            not_null_term: None,
+        });
+    }
+    plan.where_clause.push(WhereTerm {
+        expr: Expr::NotNull(Box::new(arg)),
+        from_outer_join: None,
+        consumed: false,
+    });
+    Some(MinMaxOrderTarget {
+        not_null_term: Some(plan.where_clause.len() - 1),
+    })
+}
+
+/// Undoes [add_min_max_order_target] if the rows aren't read in the requested order, in which
+/// case the ORDER BY wasn't eliminated, and otherwise marks the plan to stop after the first row.
+pub fn finish_min_max_order_target(plan: &mut SelectPlan, target: MinMaxOrderTarget) {
+    if plan.order_by.is_empty() {
+        plan.min_max_from_first_row = true;
+        return;
+    }
+    plan.order_by.clear();
+    if let Some(term) = target.not_null_term {
+        plan.where_clause[term].consumed = true;
+    }
+}
@@ -10,7 +10,13 @@ use constraints::{
 use cost::{Cost, ESTIMATED_HARDCODED_ROWS_PER_TABLE};
 use join::{compute_best_join_order, BestJoinOrderResult};
 use lift_common_subexpressions::lift_common_subexpressions_from_binary_or_terms;
+use like::{add_like_prefix_range_terms, consume_like_prefix_range_terms};
+use min_max::{add_min_max_order_target, finish_min_max_order_target};
+use multi_index::use_multi_index_scan_for_or_terms;
 use order::{compute_order_target, plan_satisfies_order_target, EliminatesSortBy};
+use outer_join::reduce_outer_joins;
+use pushdown::push_predicates_into_subqueries;
+use simplify::simplify_where_clause;
 use turso_ext::{ConstraintInfo, ConstraintUsage};
 use turso_parser::ast::{self, Expr, SortOrder, TriggerEvent};
 
@@ -54,20 +60,31 @@ pub(crate) mod constraints;
 pub(crate) mod cost;
 pub(crate) mod join;
 pub(crate) mod lift_common_subexpressions;
+pub(crate) mod like;
+pub(crate) mod min_max;
+pub(crate) mod multi_index;
 pub(crate) mod order;
+pub(crate) mod outer_join;
+pub(crate) mod pushdown;
+pub(crate) mod simplify;
 
 #[tracing::instrument(skip_all, level = tracing::Level::DEBUG)]
-pub fn optimize_plan(program: &mut ProgramBuilder, plan: &mut Plan, schema: &Schema) -> Result<()> {
+pub fn optimize_plan(
+    program: &mut ProgramBuilder,
+    plan: &mut Plan,
+    schema: &Schema,
+    automatic_index: bool,
+) -> Result<()> {
     match plan {
-        Plan::Select(plan) => optimize_select_plan(plan, schema)?,
-        Plan::Delete(plan) => optimize_delete_plan(plan, schema)?,
-        Plan::Update(plan) => optimize_update_plan(program, plan, schema)?,
+        Plan::Select(plan) => optimize_select_plan(plan, schema, automatic_index)?,
+        Plan::Delete(plan) => optimize_delete_plan(plan, schema, automatic_index)?,
+        Plan::Update(plan) => optimize_update_plan(program, plan, schema, automatic_index)?,
         Plan::CompoundSelect {
             left, right_most, ..
         } => {
-            optimize_select_plan(right_most, schema)?;
+            optimize_select_plan(right_most, schema, automatic_index)?;
             for (plan, _) in left {
-                optimize_select_plan(plan, schema)?;
+                optimize_select_plan(plan, schema, automatic_index)?;
             }
         }
     }
@@ -81,8 +98,15 @@ pub fn optimize_plan(program: &mut ProgramBuilder, plan: &mut Plan, schema: &Sch
  * TODO: these could probably be done in less passes,
  * but having them separate makes them easier to understand
  */
-pub fn optimize_select_plan(plan: &mut SelectPlan, schema: &Schema) -> Result<()> {
-    optimize_subqueries(plan, schema)?;
+pub fn optimize_select_plan(
+    plan: &mut SelectPlan,
+    schema: &Schema,
+    automatic_index: bool,
+) -> Result<()> {
+    reduce_outer_joins(plan);
+    push_predicates_into_subqueries(plan);
+    optimize_subqueries(plan, schema, automatic_index)?;
+    simplify_where_clause(&mut plan.where_clause);
     lift_common_subexpressions_from_binary_or_terms(&mut plan.where_clause)?;
     if let ConstantConditionEliminationResult::ImpossibleCondition =
         eliminate_constant_conditions(&mut plan.where_clause)?
@@ -91,6 +115,11 @@ pub fn optimize_select_plan(plan: &mut SelectPlan, schema: &Schema) -> Result<()
         return Ok(());
     }
 
+    let order_sensitive =
+        !plan.order_by.is_empty() || plan.group_by.is_some() || plan.window.is_some();
+    let min_max_order_target = add_min_max_order_target(plan);
+    let like_range_terms_start =
+        add_like_prefix_range_terms(&mut plan.where_clause, &plan.table_references);
     let best_join_order = optimize_table_access(
         schema,
         &mut plan.result_columns,
@@ -102,16 +131,84 @@ pub fn optimize_select_plan(plan: &mut SelectPlan, schema: &Schema) -> Result<()
         &plan.non_from_clause_subqueries,
         &mut plan.limit,
         &mut plan.offset,
+        automatic_index,
     )?;
+    consume_like_prefix_range_terms(&mut plan.where_clause, like_range_terms_start);
+    if let Some(min_max_order_target) = min_max_order_target {
+        finish_min_max_order_target(plan, min_max_order_target);
+    }
 
     if let Some(best_join_order) = best_join_order {
         plan.join_order = best_join_order;
     }
+    use_multi_index_scan_for_or_terms(plan, schema)?;
+    if !order_sensitive && !plan.min_max_from_first_row {
+        use_smallest_index_to_enumerate_rows(plan, schema);
+    }
 
     Ok(())
 }
 
-fn optimize_delete_plan(plan: &mut DeletePlan, schema: &Schema) -> Result<()> {
+/// Plans that collect rows for a DELETE or UPDATE share cursors with the statement that
+/// modifies the table afterwards, so they keep to the access paths chosen by the join planner.
+fn collects_rows_for_dml(plan: &SelectPlan) -> bool {
+    matches!(
+        plan.query_destination,
+        QueryDestination::RowSet { .. }
+            | QueryDestination::EphemeralTable { .. }
+            | QueryDestination::EphemeralIndex {
+                is_delete: true,
+                ..
+            }
+    )
+}
+
+/// When a full table scan reads no columns of the table, e.g. `SELECT count(*) FROM t`, any
+/// index with an entry per row can enumerate the rows instead. Index entries are narrower than
+/// table rows, so the smallest index is scanned as a covering index, provided it has fewer
+/// columns than the table itself.
+fn use_smallest_index_to_enumerate_rows(plan: &mut SelectPlan, schema: &Schema) {
+    if collects_rows_for_dml(plan) {
+        return;
+    }
+    let [table] = plan.table_references.joined_tables_mut().as_mut_slice() else {
+        return;
+    };
+    let Table::BTree(btree) = &table.table else {
+        return;
+    };
+    if !btree.has_rowid
+        || !table.col_used_mask.is_empty()
+        || !table.expression_index_usages.is_empty()
+        || !matches!(
+            table.op,
+            Operation::Scan(Scan::BTreeTable { index: None, .. })
+        )
+    {
+        return;
+    }
+    let Some(index) = schema
+        .get_indices(&btree.name)
+        .filter(|index| {
+            index.has_entry_for_every_row() && index.columns.len() < btree.columns.len()
+        })
+        .min_by_key(|index| index.columns.len())
+        .cloned()
+    else {
+        return;
+    };
+    table.op = Operation::Scan(Scan::BTreeTable {
+        iter_dir: IterationDirection::Forwards,
+        index: Some(index),
+    });
+}
+
+fn optimize_delete_plan(
+    plan: &mut DeletePlan,
+    schema: &Schema,
+    automatic_index: bool,
+) -> Result<()> {
+    simplify_where_clause(&mut plan.where_clause);
     lift_common_subexpressions_from_binary_or_terms(&mut plan.where_clause)?;
     if let ConstantConditionEliminationResult::ImpossibleCondition =
         eliminate_constant_conditions(&mut plan.where_clause)?
@@ -121,9 +218,11 @@ fn optimize_delete_plan(plan: &mut DeletePlan, schema: &Schema) -> Result<()> {
     }
 
     if let Some(rowset_plan) = plan.rowset_plan.as_mut() {
-        optimize_select_plan(rowset_plan, schema)?;
+        optimize_select_plan(rowset_plan, schema, automatic_index)?;
     }
 
+    let like_range_terms_start =
+        add_like_prefix_range_terms(&mut plan.where_clause, &plan.table_references);
     let _ = optimize_table_access(
         schema,
         &mut plan.result_columns,
@@ -135,7 +234,9 @@ fn optimize_delete_plan(plan: &mut DeletePlan, schema: &Schema) -> Result<()> {
         &[],
         &mut plan.limit,
         &mut plan.offset,
+        automatic_index,
     )?;
+    consume_like_prefix_range_terms(&mut plan.where_clause, like_range_terms_start);
 
     Ok(())
 }
@@ -144,7 +245,9 @@ fn optimize_update_plan(
     program: &mut ProgramBuilder,
     plan: &mut UpdatePlan,
     schema: &Schema,
+    automatic_index: bool,
 ) -> Result<()> {
+    simplify_where_clause(&mut plan.where_clause);
     lift_common_subexpressions_from_binary_or_terms(&mut plan.where_clause)?;
     if let ConstantConditionEliminationResult::ImpossibleCondition =
         eliminate_constant_conditions(&mut plan.where_clause)?
@@ -152,6 +255,8 @@ fn optimize_update_plan(
         plan.contains_constant_false_condition = true;
         return Ok(());
     }
+    let like_range_terms_start =
+        add_like_prefix_range_terms(&mut plan.where_clause, &plan.table_references);
     let _ = optimize_table_access(
         schema,
         &mut [],
@@ -163,7 +268,9 @@ fn optimize_update_plan(
         &[],
         &mut plan.limit,
         &mut plan.offset,
+        automatic_index,
     )?;
+    consume_like_prefix_range_terms(&mut plan.where_clause, like_range_terms_start);
 
     let table_ref = &mut plan.table_references.joined_tables_mut()[0];
 
@@ -180,6 +287,10 @@ fn optimize_update_plan(
             break 'requires false;
         };
         let btree_table = btree_table_arc.as_ref();
+        // The rows of a WITHOUT ROWID table are always collected before they are changed.
+        if !btree_table.has_rowid {
+            break 'requires false;
+        }
 
         // Check if there are UPDATE triggers
         let updated_cols: HashSet<usize> = plan.set_clauses.iter().map(|(i, _)| *i).collect();
@@ -336,6 +447,7 @@ fn add_ephemeral_table_to_update_plan(
         values: vec![],
         window: None,
         non_from_clause_subqueries: vec![],
+        min_max_from_first_row: false,
     };
 
     plan.ephemeral_plan = Some(ephemeral_plan);
@@ -343,10 +455,14 @@ fn add_ephemeral_table_to_update_plan(
     Ok(())
 }
 
-fn optimize_subqueries(plan: &mut SelectPlan, schema: &Schema) -> Result<()> {
+fn optimize_subqueries(
+    plan: &mut SelectPlan,
+    schema: &Schema,
+    automatic_index: bool,
+) -> Result<()> {
     for table in plan.table_references.joined_tables_mut() {
         if let Table::FromClauseSubquery(from_clause_subquery) = &mut table.table {
-            optimize_select_plan(&mut from_clause_subquery.plan, schema)?;
+            optimize_select_plan(&mut from_clause_subquery.plan, schema, automatic_index)?;
         }
     }
 
@@ -594,6 +710,8 @@ fn base_row_estimate(schema: &Schema, table: &JoinedTable) -> RowCountEstimate {
 /// - Using those constraints, computes the best join order for the list of [TableReference]s
 ///   and selects the best [crate::translate::optimizer::access_method::AccessMethod] for each table in the join order.
 /// - Mutates the [Operation]s in `joined_tables` to use the selected access methods.
+///   When `automatic_index` is set, inner tables that would otherwise be scanned in full are
+///   searched through an ephemeral index built on their join key.
 /// - Removes predicates from the `where_clause` that are now redundant due to the selected access methods.
 /// - Removes sorting operations if the selected join order and access methods satisfy the [crate::translate::optimizer::order::OrderTarget].
 ///
@@ -610,6 +728,7 @@ fn optimize_table_access(
     subqueries: &[NonFromClauseSubquery],
     limit: &mut Option<Box<Expr>>,
     offset: &mut Option<Box<Expr>>,
+    automatic_index: bool,
 ) -> Result<Option<Vec<JoinOrderMember>>> {
     if table_references.joined_tables().is_empty() {
         return Ok(None);
@@ -654,7 +773,12 @@ fn optimize_table_access(
     }
 
     let mut access_methods_arena = Vec::new();
-    let maybe_order_target = compute_order_target(order_by, group_by.as_mut(), table_references);
+    let maybe_order_target = compute_order_target(
+        order_by,
+        group_by.as_mut(),
+        table_references,
+        available_indexes,
+    );
     let constraints_per_table = constraints_from_where_clause(
         where_clause,
         table_references,
@@ -824,7 +948,8 @@ fn optimize_table_access(
                 if constraint_refs.is_empty() {
                     let is_leftmost_table = i == 0;
                     let uses_index = index.is_some();
-                    let try_to_build_ephemeral_index = !is_leftmost_table && !uses_index;
+                    let try_to_build_ephemeral_index =
+                        automatic_index && !is_leftmost_table && !uses_index;
 
                     if !try_to_build_ephemeral_index {
                         table_references.joined_tables_mut()[table_idx].op =
@@ -850,12 +975,13 @@ fn optimize_table_access(
                     };
                     // Ephemeral indexes mirror rowid/column lookups. If the constraint targets an
                     // expression (table_col_pos == None) we cannot derive a seek key that matches
-                    // the row layout, so fall back to a scan in that situation.
+                    // the row layout, so fall back to a scan in that situation. Comparisons of row values
+                    // are not used either, as they are never consumed.
                     let usable: Vec<(usize, &Constraint)> = table_constraints
                         .constraints
                         .iter()
                         .enumerate()
-                        .filter(|(_, c)| c.usable && c.table_col_pos.is_some())
+                        .filter(|(_, c)| c.usable && c.table_col_pos.is_some() && !c.is_row_value())
                         .collect();
                     // Find this table's position in best_join_order (which excludes build tables)
                     let join_order_pos = best_join_order
@@ -899,6 +1025,7 @@ fn optimize_table_access(
                                 constraint_vec_pos: *orig_idx, // index in the original constraints vec
                                 index_col_pos,
                                 sort_order: SortOrder::Asc,
+                                row_value_len: 1,
                             }
                         })
                         .collect();
@@ -947,6 +1074,11 @@ fn optimize_table_access(
                             };
                             let constraint =
                                 &constraints_per_table[table_idx].constraints[*constraint_vec_pos];
+                            if constraint.is_row_value() {
+                                // A comparison of row values only narrows down the range of the seek,
+                                // which may not use all of its elements, so it is still evaluated for each row.
+                                continue;
+                            }
                             let where_term = &mut where_clause[constraint.where_clause_pos.0];
                             assert!(
                                 !where_term.consumed,
@@ -1170,6 +1302,10 @@ fn maybe_remove_index_candidate(
     if !sort_eliminated {
         return;
     }
+    // A WITHOUT ROWID table can only be read through one of its indexes.
+    if matches!(&table_reference.table, Table::BTree(btree) if !btree.has_rowid) {
+        return;
+    }
     if let Some((idx, order_target)) = index.as_mut().zip(order_target) {
         for col_order in &order_target.0 {
             // Only check columns from this table
@@ -1652,14 +1788,14 @@ fn build_seek_def(
                         // Forwards, Asc, GT: (x=10 AND y>20)
                         // Start key: start from the first GT(x:10, y:20)
                         Some((ast::Operator::Greater, bound, affinity)) => SeekKey {
-                            last_component: SeekKeyComponent::Expr(bound),
+                            last_component: bound,
                             op: SeekOp::GT,
                             affinity,
                         },
                         // Forwards, Asc, GE: (x=10 AND y>=20)
                         // Start key: start from the first GE(x:10, y:20)
                         Some((ast::Operator::GreaterEquals, bound, affinity)) => SeekKey {
-                            last_component: SeekKeyComponent::Expr(bound),
+                            last_component: bound,
                             op: SeekOp::GE { eq_only: false },
                             affinity,
                         },
@@ -1678,14 +1814,14 @@ fn build_seek_def(
                         // Forwards, Asc, LT, (x=10 AND y<30)
                         // End key: end at first GE(x:10, y:30)
                         Some((ast::Operator::Less, bound, affinity)) => SeekKey {
-                            last_component: SeekKeyComponent::Expr(bound),
+                            last_component: bound,
                             op: SeekOp::GE { eq_only: false },
                             affinity,
                         },
                         // Forwards, Asc, LE, (x=10 AND y<=30)
                         // End key: end at first GT(x:10, y:30)
                         Some((ast::Operator::LessEquals, bound, affinity)) => SeekKey {
-                            last_component: SeekKeyComponent::Expr(bound),
+                            last_component: bound,
                             op: SeekOp::GT,
                             affinity,
                         },
@@ -1707,14 +1843,14 @@ fn build_seek_def(
                         // Forwards, Desc, LT: (x=10 AND y<30)
                         // Start key: start from the first GT(x:10, y:30)
                         Some((ast::Operator::Less, bound, affinity)) => SeekKey {
-                            last_component: SeekKeyComponent::Expr(bound),
+                            last_component: bound,
                             op: SeekOp::GT,
                             affinity,
                         },
                         // Forwards, Desc, LE: (x=10 AND y<=30)
                         // Start key: start from the first GE(x:10, y:30)
                         Some((ast::Operator::LessEquals, bound, affinity)) => SeekKey {
-                            last_component: SeekKeyComponent::Expr(bound),
+                            last_component: bound,
                             op: SeekOp::GE { eq_only: false },
                             affinity,
                         },
@@ -1733,14 +1869,14 @@ fn build_seek_def(
                         // Forwards, Asc, GT, (x=10 AND y>20)
                         // End key: end at first GE(x:10, y:20)
                         Some((ast::Operator::Greater, bound, affinity)) => SeekKey {
-                            last_component: SeekKeyComponent::Expr(bound),
+                            last_component: bound,
                             op: SeekOp::GE { eq_only: false },
                             affinity,
                         },
                         // Forwards, Asc, GE, (x=10 AND y>=20)
                         // End key: end at first GT(x:10, y:20)
                         Some((ast::Operator::GreaterEquals, bound, affinity)) => SeekKey {
-                            last_component: SeekKeyComponent::Expr(bound),
+                            last_component: bound,
                             op: SeekOp::GT,
                             affinity,
                         },
@@ -1772,14 +1908,14 @@ fn build_seek_def(
                         // Backwards, Asc, LT: (x=10 AND y<30)
                         // Start key: start from the first LT(x:10, y:30)
                         Some((ast::Operator::Less, bound, affinity)) => SeekKey {
-                            last_component: SeekKeyComponent::Expr(bound),
+                            last_component: bound,
                             op: SeekOp::LT,
                             affinity,
                         },
                         // Backwards, Asc, LT: (x=10 AND y<=30)
                         // Start key: start from the first LE(x:10, y:30)
                         Some((ast::Operator::LessEquals, bound, affinity)) => SeekKey {
-                            last_component: SeekKeyComponent::Expr(bound),
+                            last_component: bound,
                             op: SeekOp::LE { eq_only: false },
                             affinity,
                         },
@@ -1798,14 +1934,14 @@ fn build_seek_def(
                         // Backwards, Asc, GT, (x=10 AND y>20)
                         // End key: end at first LE(x:10, y:20)
                         Some((ast::Operator::Greater, bound, affinity)) => SeekKey {
-                            last_component: SeekKeyComponent::Expr(bound),
+                            last_component: bound,
                             op: SeekOp::LE { eq_only: false },
                             affinity,
                         },
                         // Backwards, Asc, GT, (x=10 AND y>=20)
                         // End key: end at first LT(x:10, y:20)
                         Some((ast::Operator::GreaterEquals, bound, affinity)) => SeekKey {
-                            last_component: SeekKeyComponent::Expr(bound),
+                            last_component: bound,
                             op: SeekOp::LT,
                             affinity,
                         },
@@ -1827,14 +1963,14 @@ fn build_seek_def(
                         // Backwards, Desc, LT: (x=10 AND y>20)
                         // Start key: start from the first LT(x:10, y:20)
                         Some((ast::Operator::Greater, bound, affinity)) => SeekKey {
-                            last_component: SeekKeyComponent::Expr(bound),
+                            last_component: bound,
                             op: SeekOp::LT,
                             affinity,
                         },
                         // Backwards, Desc, LE: (x=10 AND y>=20)
                         // Start key: start from the first LE(x:10, y:20)
                         Some((ast::Operator::GreaterEquals, bound, affinity)) => SeekKey {
-                            last_component: SeekKeyComponent::Expr(bound),
+                            last_component: bound,
                             op: SeekOp::LE { eq_only: false },
                             affinity,
                         },
@@ -1853,14 +1989,14 @@ fn build_seek_def(
                         // Backwards, Desc, LT, (x=10 AND y<30)
                         // End key: end at first LE(x:10, y:30)
                         Some((ast::Operator::Less, bound, affinity)) => SeekKey {
-                            last_component: SeekKeyComponent::Expr(bound),
+                            last_component: bound,
                             op: SeekOp::LE { eq_only: false },
                             affinity,
                         },
                         // Backwards, Desc, LT, (x=10 AND y<=30)
                         // End key: end at first LT(x:10, y:30)
                         Some((ast::Operator::LessEquals, bound, affinity)) => SeekKey {
-                            last_component: SeekKeyComponent::Expr(bound),
+                            last_component: bound,
                             op: SeekOp::LT,
                             affinity,
                         },
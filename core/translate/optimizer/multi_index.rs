@@ -0,0 +1,216 @@
+use turso_parser::ast::{Expr, Operator};
+
+use crate::{
+    schema::{Schema, Table},
+    translate::{
+        expr::{walk_expr, WalkControl},
+        plan::{
+            IterationDirection, JoinOrderMember, JoinedTable, MultiIndexScan,
+            NonFromClauseSubquery, Operation, Scan, Search, SelectPlan, TableReferences, WhereTerm,
+        },
+    },
+    Result,
+};
+
+use super::{
+    access_method::{find_best_access_method_for_join_order, AccessMethodParams},
+    base_row_estimate, build_seek_def_from_constraints, collects_rows_for_dml,
+    constraints::constraints_from_where_clause,
+    cost::{estimate_cost_for_scan_or_seek, Cost, RowCountEstimate},
+};
+
+/// Replaces the full table scan of a single-table SELECT with a [MultiIndexScan] when the WHERE
+/// clause contains a term like `a = 1 OR b = 2` and every arm of the OR can be answered by an
+/// index or rowid search on its own.
+///
+/// The rowids found by the searches are merged and deduplicated at runtime, so each matching row
+/// is read once, in rowid order, which is the same order a full table scan returns rows in.
+/// The OR term is not consumed and is still evaluated for every row, so an arm only has to be
+/// partially covered by its search, e.g. `(a = 1 AND c > 5) OR b = 2` only needs indexes on
+/// `a` and `b`.
+///
+/// Of all the OR terms that qualify, the one with the lowest estimated cost is used, and only if
+/// it is estimated to be cheaper than scanning the table.
+pub fn use_multi_index_scan_for_or_terms(plan: &mut SelectPlan, schema: &Schema) -> Result<()> {
+    if collects_rows_for_dml(plan) {
+        return Ok(());
+    }
+    let [table] = plan.table_references.joined_tables() else {
+        return Ok(());
+    };
+    let Table::BTree(btree) = &table.table else {
+        return Ok(());
+    };
+    if !btree.has_rowid
+        || !matches!(
+            table.op,
+            Operation::Scan(Scan::BTreeTable {
+                iter_dir: IterationDirection::Forwards,
+                index: None,
+            })
+        )
+    {
+        return Ok(());
+    }
+
+    let join_order = [JoinOrderMember {
+        table_id: table.internal_id,
+        original_idx: 0,
+        is_outer: false,
+    }];
+    let base_row_count = base_row_estimate(schema, table);
+    let scan_cost = estimate_cost_for_scan_or_seek(None, &[], &[], 1.0, base_row_count);
+
+    let mut best: Option<(Cost, Vec<Search>)> = None;
+    for term in plan
+        .where_clause
+        .iter()
+        .filter(|term| !term.consumed && term.from_outer_join.is_none())
+    {
+        if !matches!(term.expr, Expr::Binary(_, Operator::Or, _)) {
+            continue;
+        }
+        let Some((cost, branches)) = search_or_term_arms(
+            &term.expr,
+            table,
+            &join_order,
+            &plan.table_references,
+            &plan.non_from_clause_subqueries,
+            schema,
+            base_row_count,
+        )?
+        else {
+            continue;
+        };
+        if cost < scan_cost && best.as_ref().is_none_or(|(best_cost, _)| cost < *best_cost) {
+            best = Some((cost, branches));
+        }
+    }
+
+    if let Some((_, branches)) = best {
+        plan.table_references.joined_tables_mut()[0].op =
+            Operation::MultiIndexScan(MultiIndexScan { branches });
+    }
+    Ok(())
+}
+
+/// Finds the best search for each arm of the OR expression `expr`, returning their total cost.
+/// Returns None if any arm cannot be answered with a search.
+fn search_or_term_arms(
+    expr: &Expr,
+    table: &JoinedTable,
+    join_order: &[JoinOrderMember],
+    table_references: &TableReferences,
+    subqueries: &[NonFromClauseSubquery],
+    schema: &Schema,
+    base_row_count: RowCountEstimate,
+) -> Result<Option<(Cost, Vec<Search>)>> {
+    let mut arms = Vec::new();
+    flatten_binary(expr, Operator::Or, &mut arms);
+
+    let mut total_cost = Cost(0.0);
+    let mut branches = Vec::with_capacity(arms.len());
+    for arm in arms {
+        if contains_subquery(arm) {
+            return Ok(None);
+        }
+        let mut conjuncts = Vec::new();
+        flatten_binary(arm, Operator::And, &mut conjuncts);
+        let arm_terms = conjuncts
+            .into_iter()
+            .map(|conjunct| WhereTerm {
+                expr: conjunct.clone(),
+                from_outer_join: None,
+                consumed: false,
+            })
+            .collect::<Vec<_>>();
+
+        let constraints = constraints_from_where_clause(
+            &arm_terms,
+            table_references,
+            &schema.indexes,
+            subqueries,
+            schema,
+        )?;
+        let table_constraints = &constraints[0];
+        let Some(access_method) = find_best_access_method_for_join_order(
+            table,
+            table_constraints,
+            join_order,
+            None,
+            1.0,
+            base_row_count,
+        )?
+        else {
+            return Ok(None);
+        };
+        let AccessMethodParams::BTreeTable {
+            iter_dir,
+            index,
+            constraint_refs,
+        } = access_method.params
+        else {
+            return Ok(None);
+        };
+        if constraint_refs.is_empty()
+            || index
+                .as_ref()
+                .is_some_and(|index| index.ephemeral || index.index_method.is_some())
+        {
+            return Ok(None);
+        }
+
+        let search = match (index, constraint_refs[0].eq) {
+            (None, Some(eq)) => Search::RowidEq {
+                cmp_expr: table_constraints.constraints[eq]
+                    .get_constraining_expr(&arm_terms, Some(table_references))
+                    .1,
+            },
+            (index, _) => Search::Seek {
+                index,
+                seek_def: build_seek_def_from_constraints(
+                    &table_constraints.constraints,
+                    &constraint_refs,
+                    iter_dir,
+                    &arm_terms,
+                    Some(table_references),
+                )?,
+            },
+        };
+        total_cost = total_cost + access_method.cost;
+        branches.push(search);
+    }
+    Ok(Some((total_cost, branches)))
+}
+
+/// Splits a chain of `op` (e.g. `a OR (b OR c)`) into its operands, looking through parentheses.
+fn flatten_binary<'a>(expr: &'a Expr, op: Operator, out: &mut Vec<&'a Expr>) {
+    match expr {
+        Expr::Binary(lhs, binary_op, rhs) if *binary_op == op => {
+            flatten_binary(lhs, op, out);
+            flatten_binary(rhs, op, out);
+        }
+        Expr::Parenthesized(exprs) if exprs.len() == 1 => flatten_binary(&exprs[0], op, out),
+        _ => out.push(expr),
+    }
+}
+
+/// The searches all run before the loop over the table starts, when the results of subqueries
+/// in the WHERE clause may not have been computed yet.
+fn contains_subquery(expr: &Expr) -> bool {
+    let mut found = false;
+    let _ = walk_expr(expr, &mut |e: &Expr| -> Result<WalkControl> {
+        if matches!(
+            e,
+            Expr::Exists(_)
+                | Expr::InSelect { .. }
+                | Expr::Subquery(_)
+                | Expr::SubqueryResult { .. }
+        ) {
+            found = true;
+            return Ok(WalkControl::SkipChildren);
+        }
+        Ok(WalkControl::Continue)
+    });
+    found
+}
@@ -1,4 +1,10 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+
 use crate::{
+    schema::{Index, Table},
     translate::{
         collate::{get_collseq_from_expr, CollationSeq},
         optimizer::access_method::AccessMethodParams,
@@ -9,7 +15,7 @@ use crate::{
 };
 use turso_parser::ast::{self, SortOrder, TableInternalId};
 
-use super::{access_method::AccessMethod, join::JoinN};
+use super::{access_method::AccessMethod, constraints::RangeConstraintRef, join::JoinN};
 
 /// Target component in an ORDER BY/GROUP BY that may be a plain column or an expression.
 #[derive(Debug, PartialEq, Clone)]
@@ -44,6 +50,12 @@ pub enum EliminatesSortBy {
 pub struct OrderTarget(pub Vec<ColumnOrder>, pub EliminatesSortBy);
 
 impl OrderTarget {
+    /// GROUP BY only needs rows with equal keys to be adjacent, so when it is the only thing
+    /// being ordered for, the direction of each column does not matter.
+    pub fn ignores_direction(&self) -> bool {
+        self.1 == EliminatesSortBy::Group
+    }
+
     /// Build an `OrderTarget` from a list of expressions if they can all be
     /// satisfied by a single-table ordering (needed for index satisfaction).
     fn maybe_from_iterator<'a>(
@@ -74,6 +86,7 @@ pub fn compute_order_target(
     order_by: &mut Vec<(Box<ast::Expr>, SortOrder)>,
     group_by_opt: Option<&mut GroupBy>,
     tables: &TableReferences,
+    available_indexes: &HashMap<String, VecDeque<Arc<Index>>>,
 ) -> Option<OrderTarget> {
     match (order_by.is_empty(), group_by_opt) {
         // No ordering demands - we don't care what order the joined result rows are in
@@ -85,11 +98,14 @@ pub fn compute_order_target(
             EliminatesSortBy::Order,
         ),
         // Only GROUP BY - we would like the joined result rows to be in the order specified by the GROUP BY
-        (true, Some(group_by)) => OrderTarget::maybe_from_iterator(
-            group_by.exprs.iter().map(|expr| (expr, SortOrder::Asc)),
-            tables,
-            EliminatesSortBy::Group,
-        ),
+        (true, Some(group_by)) => {
+            align_group_by_with_index(group_by, tables, available_indexes);
+            OrderTarget::maybe_from_iterator(
+                group_by.exprs.iter().map(|expr| (expr, SortOrder::Asc)),
+                tables,
+                EliminatesSortBy::Group,
+            )
+        }
         // Both ORDER BY and GROUP BY:
         // If the GROUP BY does not contain all the expressions in the ORDER BY,
         // then we must separately sort the result rows for ORDER BY anyway.
@@ -109,6 +125,7 @@ pub fn compute_order_target(
             });
             // If not, let's try to target an ordering that matches the group by -- we don't care about ASC/DESC
             if !group_by_contains_all {
+                align_group_by_with_index(group_by, tables, available_indexes);
                 return OrderTarget::maybe_from_iterator(
                     group_by.exprs.iter().map(|expr| (expr, SortOrder::Asc)),
                     tables,
@@ -158,6 +175,87 @@ pub fn compute_order_target(
     }
 }
 
+/// Reorders the GROUP BY terms to match the leading columns of an index, e.g. `GROUP BY b, a`
+/// becomes `GROUP BY a, b` when there is an index on `(a, b)`. Grouping only needs rows with
+/// equal keys to be adjacent, so the order of the terms doesn't change the groups, but rows in
+/// the order of the index can be aggregated as they are read, one group at a time, instead of
+/// being sorted first.
+fn align_group_by_with_index(
+    group_by: &mut GroupBy,
+    tables: &TableReferences,
+    available_indexes: &HashMap<String, VecDeque<Arc<Index>>>,
+) {
+    if group_by.exprs.len() < 2 {
+        return;
+    }
+    let Some(targets) = group_by
+        .exprs
+        .iter()
+        .map(|expr| expr_to_column_order(expr, SortOrder::Asc, tables))
+        .collect::<Option<Vec<_>>>()
+    else {
+        return;
+    };
+    let table_id = targets[0].table_id;
+    if targets.iter().any(|target| target.table_id != table_id) {
+        return;
+    }
+    let Some(Table::BTree(btree)) = tables
+        .find_joined_table_by_internal_id(table_id)
+        .map(|t| &t.table)
+    else {
+        return;
+    };
+    let Some(indexes) = available_indexes.get(&btree.name) else {
+        return;
+    };
+    for index in indexes {
+        if index.columns.len() < targets.len() {
+            continue;
+        }
+        // The position of the term that matches each of the leading columns of the index.
+        let mut permutation: Vec<usize> = Vec::with_capacity(targets.len());
+        for idx_pos in 0..targets.len() {
+            let Some(term) = (0..targets.len()).find(|term| {
+                !permutation.contains(term) && index_column_matches(&targets[*term], index, idx_pos)
+            }) else {
+                break;
+            };
+            permutation.push(term);
+        }
+        if permutation.len() < targets.len() {
+            continue;
+        }
+        let exprs = std::mem::take(&mut group_by.exprs);
+        let mut exprs: Vec<Option<ast::Expr>> = exprs.into_iter().map(Some).collect();
+        group_by.exprs = permutation
+            .iter()
+            .map(|term| exprs[*term].take().expect("each term is used once"))
+            .collect();
+        if let Some(sort_order) = group_by.sort_order.as_mut() {
+            *sort_order = permutation.iter().map(|term| sort_order[*term]).collect();
+        }
+        return;
+    }
+}
+
+/// Whether the column of `index` at `idx_pos` provides the order of `target_col`.
+fn index_column_matches(target_col: &ColumnOrder, index: &Index, idx_pos: usize) -> bool {
+    let idx_col = &index.columns[idx_pos];
+    let same_column = match (&target_col.target, &idx_col.expr) {
+        (ColumnTarget::Column(col_no), None) => idx_col.pos_in_table == *col_no,
+        (ColumnTarget::Expr(expr), Some(idx_expr)) => {
+            exprs_are_equivalent(unsafe { &**expr }, idx_expr)
+        }
+        _ => false,
+    };
+    // If ORDER BY collation doesn't match index collation, this index can't satisfy the ordering
+    same_column
+        && !idx_col
+            .collation
+            .is_some_and(|idx_collation| target_col.collation != idx_collation)
+}
+
 /// Check if the plan's row iteration order matches the [OrderTarget]'s column order.
 /// If yes, and this plan is selected, then a sort operation can be eliminated.
 pub fn plan_satisfies_order_target(
@@ -168,7 +266,8 @@ pub fn plan_satisfies_order_target(
 ) -> bool {
     let mut target_col_idx = 0;
     let num_cols_in_order_target = order_target.0.len();
-    for (table_index, access_method_index) in plan.data.iter() {
+    for (i, (table_index, access_method_index)) in plan.data.iter().enumerate() {
+        let is_outermost = i == 0;
         let access_method = &access_methods_arena[*access_method_index];
         let table_ref = &joined_tables[*table_index];
 
@@ -177,7 +276,7 @@ pub fn plan_satisfies_order_target(
             AccessMethodParams::BTreeTable {
                 iter_dir,
                 index: index_opt,
-                ..
+                constraint_refs,
             } => match index_opt {
                 None => {
                     // Only rowid order is available without an index.
@@ -213,48 +312,29 @@ pub fn plan_satisfies_order_target(
                     1
                 }
                 Some(index) => {
-                    let mut col_idx = 0;
-                    while target_col_idx + col_idx < num_cols_in_order_target
-                        && col_idx < index.columns.len()
-                    {
-                        let target_col = &order_target.0[target_col_idx + col_idx];
-                        if target_col.table_id != table_ref.internal_id {
-                            break;
-                        }
-                        let idx_col = &index.columns[col_idx];
-                        let column_matches = match (&target_col.target, &idx_col.expr) {
-                            (ColumnTarget::Column(col_no), None) => idx_col.pos_in_table == *col_no,
-                            (ColumnTarget::Expr(expr), Some(idx_expr)) => {
-                                exprs_are_equivalent(unsafe { &**expr }, idx_expr)
-                            }
-                            _ => false,
-                        };
-                        if !column_matches {
-                            break;
-                        }
-
-                        // If ORDER BY collation doesn't match index collation, this index can't satisfy the ordering
-                        if idx_col
-                            .collation
-                            .is_some_and(|idx_collation| target_col.collation != idx_collation)
-                        {
-                            break;
-                        }
-
-                        let correct_order = if *iter_dir == IterationDirection::Forwards {
-                            target_col.order == idx_col.order
-                        } else {
-                            target_col.order != idx_col.order
-                        };
-                        if !correct_order {
-                            break;
-                        }
-                        col_idx += 1;
-                    }
-                    if col_idx == 0 {
+                    // Equality constraints are only constant for the whole scan in the outermost loop;
+                    // in inner loops they depend on the current row of the outer tables.
+                    let num_eq_cols = if target_col_idx == 0 && is_outermost {
+                        num_leading_eq_constraints(constraint_refs)
+                    } else {
+                        0
+                    };
+                    let order_match = match_index_to_order_target(
+                        &order_target.0[target_col_idx..],
+                        table_ref.internal_id,
+                        index,
+                        table_ref.columns().iter().position(|c| c.is_rowid_alias()),
+                        num_eq_cols,
+                        order_target.ignores_direction(),
+                    );
+                    let consumed = match order_match.direction {
+                        Some(direction) if direction != *iter_dir => 0,
+                        _ => order_match.consumed,
+                    };
+                    if consumed == 0 {
                         return false;
                     }
-                    col_idx
+                    consumed
                 }
             },
             _ => return false,
@@ -268,6 +348,102 @@ pub fn plan_satisfies_order_target(
     target_col_idx == num_cols_in_order_target
 }
 
+/// How far an index traversal delivers rows in the order of an [OrderTarget].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexOrderMatch {
+    /// Number of leading [ColumnOrder]s the index delivers rows in.
+    pub consumed: usize,
+    /// Direction the index must be traversed in, or None if either direction works,
+    /// e.g. because every matched column is fixed by an equality constraint.
+    pub direction: Option<IterationDirection>,
+    /// Whether every column of the index was matched or skipped.
+    pub exhausted_index: bool,
+}
+
+/// Matches the columns of `index` against the leading columns of `target` that belong to
+/// `table_id`.
+///
+/// The first `num_eq_cols` index columns are fixed by equality constraints, so they have a
+/// single value during the traversal: they can be skipped when the target does not mention them,
+/// and satisfy target columns that refer to them in either direction. A DESC target column
+/// matches an ASC index column (and vice versa) when the index is traversed backwards; all
+/// non-fixed columns must agree on the traversal direction, unless `ignore_direction` is set,
+/// which is the case for GROUP BY, which only needs equal values to be adjacent.
+///
+/// Index entries end with the rowid, so after the last column of the index, a target column
+/// that is the rowid alias `rowid_alias_col` of the table is delivered in ascending order too,
+/// e.g. an index on `(x)` delivers rows in the order of `ORDER BY x, id`.
+pub fn match_index_to_order_target(
+    target: &[ColumnOrder],
+    table_id: TableInternalId,
+    index: &Index,
+    rowid_alias_col: Option<usize>,
+    num_eq_cols: usize,
+    ignore_direction: bool,
+) -> IndexOrderMatch {
+    let column_matches =
+        |target_col: &ColumnOrder, idx_pos: usize| index_column_matches(target_col, index, idx_pos);
+
+    let mut direction = None;
+    let mut consumed = 0;
+    let mut idx_pos = 0;
+    while consumed < target.len() {
+        let target_col = &target[consumed];
+        if target_col.table_id != table_id {
+            break;
+        }
+        if (0..num_eq_cols.min(index.columns.len())).any(|pos| column_matches(target_col, pos)) {
+            consumed += 1;
+            continue;
+        }
+        if idx_pos < num_eq_cols {
+            idx_pos += 1;
+            continue;
+        }
+        let idx_col_order = if idx_pos < index.columns.len() {
+            if !column_matches(target_col, idx_pos) {
+                break;
+            }
+            index.columns[idx_pos].order
+        } else if idx_pos == index.columns.len()
+            && index.has_rowid
+            && rowid_alias_col
+                .is_some_and(|col_no| target_col.target == ColumnTarget::Column(col_no))
+        {
+            SortOrder::Asc
+        } else {
+            break;
+        };
+        if !ignore_direction {
+            let needed = if target_col.order == idx_col_order {
+                IterationDirection::Forwards
+            } else {
+                IterationDirection::Backwards
+            };
+            if direction.is_some_and(|direction| direction != needed) {
+                break;
+            }
+            direction = Some(needed);
+        }
+        consumed += 1;
+        idx_pos += 1;
+    }
+    IndexOrderMatch {
+        consumed,
+        direction,
+        exhausted_index: idx_pos.max(num_eq_cols) >= index.columns.len(),
+    }
+}
+
+/// Number of leading index columns that are fixed by an equality constraint.
+pub fn num_leading_eq_constraints(constraint_refs: &[RangeConstraintRef]) -> usize {
+    constraint_refs
+        .iter()
+        .enumerate()
+        .take_while(|(i, cref)| cref.index_col_pos == *i && cref.eq.is_some())
+        .count()
+}
+
 fn expr_to_column_order(
     expr: &ast::Expr,
     order: SortOrder,
@@ -0,0 +1,229 @@
+use turso_parser::ast::{Expr, Literal, Operator, TableInternalId, UnaryOperator};
+
+use crate::translate::plan::SelectPlan;
+
+/// Turns LEFT JOINs into inner joins when the WHERE clause rejects the rows where the right-hand
+/// table is NULL, e.g. in `SELECT * FROM a LEFT JOIN b ON a.id = b.a_id WHERE b.x = 1` the
+/// NULL-extended rows of `b` can never satisfy `b.x = 1`, so the query is equivalent to an inner
+/// join. Inner joins can be reordered freely, and their ON terms can be used to constrain either
+/// table, so the join order search has more plans to choose from.
+pub(crate) fn reduce_outer_joins(plan: &mut SelectPlan) {
+    let outer_tables: Vec<TableInternalId> = plan
+        .table_references
+        .joined_tables()
+        .iter()
+        .filter(|t| t.join_info.as_ref().is_some_and(|j| j.outer))
+        .map(|t| t.internal_id)
+        .collect();
+    for table_id in outer_tables {
+        let null_rejected = plan.where_clause.iter().any(|term| {
+            !term.consumed
+                && term.from_outer_join.is_none()
+                && is_null_rejecting(&term.expr, table_id)
+        });
+        if !null_rejected {
+            continue;
+        }
+        for table in plan.table_references.joined_tables_mut() {
+            if table.internal_id == table_id {
+                if let Some(join_info) = table.join_info.as_mut() {
+                    join_info.outer = false;
+                }
+            }
+        }
+        for member in plan.join_order.iter_mut() {
+            if member.table_id == table_id {
+                member.is_outer = false;
+            }
+        }
+        for term in plan.where_clause.iter_mut() {
+            if term.from_outer_join == Some(table_id) {
+                term.from_outer_join = None;
+            }
+        }
+    }
+}
+
+/// Whether the predicate `expr` is never true when the columns of `table_id` are all NULL.
+fn is_null_rejecting(expr: &Expr, table_id: TableInternalId) -> bool {
+    match expr {
+        Expr::Binary(lhs, Operator::And, rhs) => {
+            is_null_rejecting(lhs, table_id) || is_null_rejecting(rhs, table_id)
+        }
+        Expr::Binary(lhs, Operator::Or, rhs) => {
+            is_null_rejecting(lhs, table_id) && is_null_rejecting(rhs, table_id)
+        }
+        Expr::Binary(
+            lhs,
+            Operator::Equals
+            | Operator::NotEquals
+            | Operator::Less
+            | Operator::LessEquals
+            | Operator::Greater
+            | Operator::GreaterEquals,
+            rhs,
+        ) => is_null_if_table_null(lhs, table_id) || is_null_if_table_null(rhs, table_id),
+        // `x IS NOT NULL`, and `x IS y` where y is a literal other than NULL.
+        Expr::Binary(lhs, op @ (Operator::Is | Operator::IsNot), rhs) => {
+            let other_is_null = |other: &Expr| matches!(other, Expr::Literal(Literal::Null));
+            let other_is_value = |other: &Expr| matches!(other, Expr::Literal(literal) if !matches!(literal, Literal::Null));
+            let rejects = |operand: &Expr, other: &Expr| {
+                is_null_if_table_null(operand, table_id)
+                    && match op {
+                        Operator::Is => other_is_value(other),
+                        _ => other_is_null(other),
+                    }
+            };
+            rejects(lhs, rhs) || rejects(rhs, lhs)
+        }
+        Expr::NotNull(expr) => is_null_if_table_null(expr, table_id),
+        Expr::Between {
+            lhs,
+            not,
+            start,
+            end,
+        } => {
+            is_null_if_table_null(lhs, table_id)
+                || (!not
+                    && (is_null_if_table_null(start, table_id)
+                        || is_null_if_table_null(end, table_id)))
+        }
+        // `x NOT IN ()` is true even when x is NULL.
+        Expr::InList { lhs, not, rhs } => {
+            is_null_if_table_null(lhs, table_id) && (!not || !rhs.is_empty())
+        }
+        Expr::Like { lhs, rhs, .. } => {
+            is_null_if_table_null(lhs, table_id) || is_null_if_table_null(rhs, table_id)
+        }
+        Expr::Parenthesized(exprs) if exprs.len() == 1 => is_null_rejecting(&exprs[0], table_id),
+        _ => is_null_if_table_null(expr, table_id),
+    }
+}
+
+/// Whether `expr` evaluates to NULL when the columns of `table_id` are all NULL.
+fn is_null_if_table_null(expr: &Expr, table_id: TableInternalId) -> bool {
+    match expr {
+        Expr::Column { table, .. } | Expr::RowId { table, .. } => *table == table_id,
+        Expr::Binary(
+            lhs,
+            Operator::Add
+            | Operator::Subtract
+            | Operator::Multiply
+            | Operator::Divide
+            | Operator::Modulus
+            | Operator::Concat
+            | Operator::BitwiseAnd
+            | Operator::BitwiseOr
+            | Operator::LeftShift
+            | Operator::RightShift
+            | Operator::Equals
+            | Operator::NotEquals
+            | Operator::Less
+            | Operator::LessEquals
+            | Operator::Greater
+            | Operator::GreaterEquals,
+            rhs,
+        ) => is_null_if_table_null(lhs, table_id) || is_null_if_table_null(rhs, table_id),
+        Expr::Unary(
+            UnaryOperator::Negative
+            | UnaryOperator::Positive
+            | UnaryOperator::BitwiseNot
+            | UnaryOperator::Not,
+            expr,
+        ) => is_null_if_table_null(expr, table_id),
+        Expr::Cast { expr, .. } | Expr::Collate(expr, _) => is_null_if_table_null(expr, table_id),
+        Expr::Parenthesized(exprs) if exprs.len() == 1 => {
+            is_null_if_table_null(&exprs[0], table_id)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use turso_parser::ast::{FunctionTail, Name};
+
+    fn column(table: usize) -> Box<Expr> {
+        Box::new(Expr::Column {
+            database: None,
+            table: TableInternalId::from(table),
+            column: 0,
+            is_rowid_alias: false,
+        })
+    }
+
+    fn literal(value: &str) -> Box<Expr> {
+        Box::new(Expr::Literal(Literal::Numeric(value.to_string())))
+    }
+
+    fn null() -> Box<Expr> {
+        Box::new(Expr::Literal(Literal::Null))
+    }
+
+    #[test]
+    fn test_null_rejecting_predicates() {
+        let b = TableInternalId::from(2);
+        let rejecting = [
+            Expr::Binary(column(2), Operator::Equals, literal("1")),
+            Expr::Binary(
+                Box::new(Expr::Binary(column(2), Operator::Add, literal("1"))),
+                Operator::Greater,
+                column(1),
+            ),
+            Expr::Binary(column(2), Operator::IsNot, null()),
+            Expr::Binary(column(2), Operator::Is, literal("1")),
+            Expr::NotNull(column(2)),
+            Expr::Binary(
+                Box::new(Expr::Binary(column(1), Operator::Equals, literal("1"))),
+                Operator::And,
+                Box::new(Expr::Binary(column(2), Operator::Less, literal("1"))),
+            ),
+            Expr::InList {
+                lhs: column(2),
+                not: true,
+                rhs: vec![literal("1")],
+            },
+            *column(2),
+        ];
+        for expr in rejecting {
+            assert!(is_null_rejecting(&expr, b), "{expr:?}");
+        }
+
+        let not_rejecting = [
+            Expr::Binary(column(1), Operator::Equals, literal("1")),
+            Expr::Binary(column(2), Operator::Is, null()),
+            Expr::Binary(column(2), Operator::IsNot, literal("1")),
+            Expr::IsNull(column(2)),
+            Expr::Binary(
+                Box::new(Expr::Binary(column(1), Operator::Equals, literal("1"))),
+                Operator::Or,
+                Box::new(Expr::Binary(column(2), Operator::Less, literal("1"))),
+            ),
+            Expr::InList {
+                lhs: column(2),
+                not: true,
+                rhs: vec![],
+            },
+            Expr::Between {
+                lhs: column(1),
+                not: true,
+                start: column(2),
+                end: literal("1"),
+            },
+            Expr::FunctionCall {
+                name: Name::exact("coalesce".to_string()),
+                distinctness: None,
+                args: vec![column(2), literal("1")],
+                order_by: vec![],
+                filter_over: FunctionTail {
+                    filter_clause: None,
+                    over_clause: None,
+                },
+            },
+        ];
+        for expr in not_rejecting {
+            assert!(!is_null_rejecting(&expr, b), "{expr:?}");
+        }
+    }
+}
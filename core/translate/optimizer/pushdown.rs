@@ -0,0 +1,276 @@
+use turso_parser::ast::{Expr, Literal, Operator, TableInternalId, UnaryOperator};
+
+use crate::{
+    function::Func,
+    schema::{FromClauseSubquery, Table},
+    translate::{
+        expr::{get_expr_affinity, walk_expr, walk_expr_mut, WalkControl},
+        plan::{SelectPlan, WhereTerm},
+    },
+    vdbe::affinity::Affinity,
+};
+
+/// Copies the WHERE terms that only reference the columns of a FROM clause subquery (or a CTE)
+/// into the WHERE clause of the subquery, so that the rows are filtered before they are
+/// returned to the outer query, and the filter can use the indexes of the tables in the
+/// subquery. E.g. `SELECT * FROM (SELECT a, b FROM t) WHERE a = 1` becomes
+/// `SELECT * FROM (SELECT a, b FROM t WHERE a = 1) WHERE a = 1`.
+///
+/// The outer term is kept, so the rewrite only needs to be sure that the pushed term filters
+/// out no row that the outer term would keep. Terms are not pushed into subqueries whose rows
+/// depend on the rows that were filtered out, i.e. those with aggregates, windows, DISTINCT or
+/// LIMIT, nor into the right-hand side of a LEFT JOIN or a materialized CTE, whose rows are
+/// shared with the other references to the CTE.
+pub(crate) fn push_predicates_into_subqueries(plan: &mut SelectPlan) {
+    for term_idx in 0..plan.where_clause.len() {
+        let term = &plan.where_clause[term_idx];
+        if term.consumed || term.from_outer_join.is_some() {
+            continue;
+        }
+        let Some(table_id) = single_referenced_table(&term.expr) else {
+            continue;
+        };
+        let Some(table) = plan
+            .table_references
+            .joined_tables_mut()
+            .iter_mut()
+            .find(|t| t.internal_id == table_id)
+        else {
+            continue;
+        };
+        if table.join_info.as_ref().is_some_and(|j| j.outer) {
+            continue;
+        }
+        let Table::FromClauseSubquery(subquery) = &mut table.table else {
+            continue;
+        };
+        if !accepts_pushed_terms(subquery) {
+            continue;
+        }
+        let Some(expr) =
+            substitute_result_columns(&plan.where_clause[term_idx].expr, table_id, subquery)
+        else {
+            continue;
+        };
+        subquery.plan.where_clause.push(WhereTerm {
+            expr,
+            from_outer_join: None,
+            consumed: false,
+        });
+    }
+}
+
+/// Returns the table whose columns are referenced by `expr`, if there is exactly one, and the
+/// expression can be evaluated anywhere the columns are available.
+fn single_referenced_table(expr: &Expr) -> Option<TableInternalId> {
+    let mut table_id = None;
+    let mut pushable = true;
+    let _ = walk_expr(expr, &mut |e: &Expr| -> crate::Result<WalkControl> {
+        match e {
+            Expr::Column { table, .. } => match table_id {
+                None => table_id = Some(*table),
+                Some(id) => pushable &= id == *table,
+            },
+            // The rowid of a subquery is only known to the outer query.
+            Expr::RowId { .. } | Expr::SubqueryResult { .. } | Expr::Register(_) => {
+                pushable = false
+            }
+            Expr::FunctionCall { .. } | Expr::FunctionCallStar { .. } => {
+                pushable &= is_deterministic_function(e)
+            }
+            _ => {}
+        }
+        Ok(if pushable {
+            WalkControl::Continue
+        } else {
+            WalkControl::SkipChildren
+        })
+    });
+    table_id.filter(|_| pushable)
+}
+
+fn is_deterministic_function(expr: &Expr) -> bool {
+    let (name, argc) = match expr {
+        Expr::FunctionCall {
+            name,
+            args,
+            filter_over,
+            ..
+        } => {
+            if filter_over.over_clause.is_some() {
+                return false;
+            }
+            (name, args.len())
+        }
+        Expr::FunctionCallStar { name, .. } => (name, 0),
+        _ => return true,
+    };
+    Func::resolve_function(name.as_str(), argc).is_ok_and(|f| f.is_deterministic())
+}
+
+fn accepts_pushed_terms(subquery: &FromClauseSubquery) -> bool {
+    let plan = &subquery.plan;
+    !matches!(subquery.materialized, turso_parser::ast::Materialized::Yes)
+        && plan.aggregates.is_empty()
+        && plan.group_by.is_none()
+        && plan.window.is_none()
+        && plan.limit.is_none()
+        && plan.offset.is_none()
+        && plan.values.is_empty()
+        && !plan.distinctness.is_distinct()
+        && !plan.contains_constant_false_condition
+}
+
+/// Rewrites `expr` in terms of the tables of the subquery, by replacing each column of the
+/// subquery with the expression of the corresponding result column. Returns `None` if that
+/// could change the value of the expression.
+fn substitute_result_columns(
+    expr: &Expr,
+    table_id: TableInternalId,
+    subquery: &FromClauseSubquery,
+) -> Option<Expr> {
+    let result_columns = &subquery.plan.result_columns;
+    let mut pushable = true;
+    let _ = walk_expr(expr, &mut |e: &Expr| -> crate::Result<WalkControl> {
+        match e {
+            Expr::Column { column, .. } => {
+                let Some(result_column) = result_columns.get(*column) else {
+                    pushable = false;
+                    return Ok(WalkControl::SkipChildren);
+                };
+                pushable &= is_deterministic(&result_column.expr);
+            }
+            _ => pushable &= comparison_affinity_is_unchanged(e, table_id, subquery),
+        }
+        Ok(if pushable {
+            WalkControl::Continue
+        } else {
+            WalkControl::SkipChildren
+        })
+    });
+    if !pushable {
+        return None;
+    }
+
+    let mut expr = expr.clone();
+    let _ = walk_expr_mut(
+        &mut expr,
+        &mut |e: &mut Expr| -> crate::Result<WalkControl> {
+            if let Expr::Column { table, column, .. } = e {
+                if *table == table_id {
+                    *e = result_columns[*column].expr.clone();
+                    return Ok(WalkControl::SkipChildren);
+                }
+            }
+            Ok(WalkControl::Continue)
+        },
+    );
+    Some(expr)
+}
+
+fn is_deterministic(expr: &Expr) -> bool {
+    let mut deterministic = true;
+    let _ = walk_expr(expr, &mut |e: &Expr| -> crate::Result<WalkControl> {
+        match e {
+            Expr::FunctionCall { .. } | Expr::FunctionCallStar { .. } => {
+                deterministic &= is_deterministic_function(e)
+            }
+            Expr::Exists(_)
+            | Expr::InSelect { .. }
+            | Expr::Subquery(_)
+            | Expr::SubqueryResult { .. }
+            | Expr::Register(_) => deterministic = false,
+            _ => {}
+        }
+        Ok(if deterministic {
+            WalkControl::Continue
+        } else {
+            WalkControl::SkipChildren
+        })
+    });
+    deterministic
+}
+
+/// The columns of a subquery have no affinity, while the expressions they are replaced with
+/// may have one, e.g. `a` in `SELECT a FROM t` has the affinity of the column `t.a`. A
+/// comparison with an operand that has an affinity converts the other operand, which can
+/// change its result, e.g. `'10' > 9` is true, but `t.a > 9` is false for a TEXT column `t.a`
+/// holding `'10'`, because 9 is converted to `'9'`. The conversion doesn't change literals of
+/// the matching kind, so only those are allowed next to such an operand.
+fn comparison_affinity_is_unchanged(
+    expr: &Expr,
+    table_id: TableInternalId,
+    subquery: &FromClauseSubquery,
+) -> bool {
+    let operands: Vec<&Expr> = match expr {
+        Expr::Binary(
+            lhs,
+            Operator::Equals
+            | Operator::NotEquals
+            | Operator::Less
+            | Operator::LessEquals
+            | Operator::Greater
+            | Operator::GreaterEquals
+            | Operator::Is
+            | Operator::IsNot,
+            rhs,
+        ) => vec![lhs, rhs],
+        Expr::Between {
+            lhs, start, end, ..
+        } => vec![lhs, start, end],
+        Expr::InList { lhs, rhs, .. } => std::iter::once(lhs).chain(rhs).map(|e| &**e).collect(),
+        _ => return true,
+    };
+    let affinities: Vec<Option<Affinity>> = operands
+        .iter()
+        .map(|operand| substituted_affinity(operand, table_id, subquery))
+        .collect();
+    affinities.iter().enumerate().all(|(i, affinity)| {
+        let Some(affinity) = affinity.filter(|a| a.has_affinity()) else {
+            return true;
+        };
+        operands
+            .iter()
+            .enumerate()
+            .all(|(j, other)| i == j || literal_unchanged_by_affinity(other, affinity))
+    })
+}
+
+/// The affinity that `operand` gets once the subquery columns are substituted, if it is a
+/// column of the subquery.
+fn substituted_affinity(
+    operand: &Expr,
+    table_id: TableInternalId,
+    subquery: &FromClauseSubquery,
+) -> Option<Affinity> {
+    match operand {
+        Expr::Column { table, column, .. } if *table == table_id => {
+            let result_column = subquery.plan.result_columns.get(*column)?;
+            Some(get_expr_affinity(
+                &result_column.expr,
+                Some(&subquery.plan.table_references),
+            ))
+        }
+        Expr::Collate(expr, _) => substituted_affinity(expr, table_id, subquery),
+        Expr::Parenthesized(exprs) if exprs.len() == 1 => {
+            substituted_affinity(&exprs[0], table_id, subquery)
+        }
+        _ => None,
+    }
+}
+
+fn literal_unchanged_by_affinity(expr: &Expr, affinity: Affinity) -> bool {
+    match expr {
+        Expr::Literal(Literal::Null) => true,
+        Expr::Literal(Literal::Numeric(_)) => affinity.is_numeric(),
+        Expr::Literal(Literal::String(_)) => affinity == Affinity::Text,
+        Expr::Unary(UnaryOperator::Negative, expr) => {
+            matches!(**expr, Expr::Literal(Literal::Numeric(_))) && affinity.is_numeric()
+        }
+        Expr::Collate(expr, _) => literal_unchanged_by_affinity(expr, affinity),
+        Expr::Parenthesized(exprs) if exprs.len() == 1 => {
+            literal_unchanged_by_affinity(&exprs[0], affinity)
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,362 @@
+use std::cmp::Ordering;
+
+use turso_parser::ast::{Expr, Literal, Operator, UnaryOperator};
+
+use crate::translate::{plan::WhereTerm, planner::break_predicate_at_and_boundaries};
+
+/// Rewrites the WHERE clause terms into simpler, equivalent forms before the access methods are
+/// chosen, so that generated predicates compile to tighter bytecode and expose more terms that
+/// can be used as index constraints. The rewrites are:
+///
+/// - `(x)` becomes `x`,
+/// - comparisons between numeric or NULL literals are folded, e.g. `1 < 2` becomes `1`,
+/// - `x AND 1` and `x OR 0` become `x` and `NOT NOT x` becomes `x`, where only the truth value
+///   of the expression matters,
+/// - duplicate literals are removed from IN lists, and `x IN (y)` becomes `x = y`.
+///
+/// Terms that become a conjunction are split into separate [WhereTerm]s, e.g.
+/// `(a = 1 AND b = 2) OR 0` becomes the two terms `a = 1` and `b = 2`.
+pub(crate) fn simplify_where_clause(where_clause: &mut Vec<WhereTerm>) {
+    let mut i = 0;
+    while i < where_clause.len() {
+        if where_clause[i].consumed {
+            i += 1;
+            continue;
+        }
+        simplify_expr(&mut where_clause[i].expr, true);
+        if !matches!(where_clause[i].expr, Expr::Binary(_, Operator::And, _)) {
+            i += 1;
+            continue;
+        }
+        let term = where_clause.remove(i);
+        let mut conjuncts: Vec<Expr> = Vec::new();
+        break_predicate_at_and_boundaries(&term.expr, &mut conjuncts);
+        let count = conjuncts.len();
+        where_clause.splice(
+            i..i,
+            conjuncts.into_iter().map(|expr| WhereTerm {
+                expr,
+                from_outer_join: term.from_outer_join,
+                consumed: false,
+            }),
+        );
+        i += count;
+    }
+}
+
+/// Simplifies an expression in place. When `is_condition` is true, only the truth value of the
+/// expression matters (true, false or NULL), not its exact value, e.g. for the operands of
+/// `AND`, `OR` and `NOT`.
+fn simplify_expr(expr: &mut Expr, is_condition: bool) {
+    match expr {
+        Expr::Parenthesized(exprs) if exprs.len() == 1 => {
+            let inner = std::mem::replace(exprs[0].as_mut(), Expr::Literal(Literal::Null));
+            *expr = inner;
+            simplify_expr(expr, is_condition);
+        }
+        Expr::Binary(lhs, op @ (Operator::And | Operator::Or), rhs) => {
+            simplify_expr(lhs, true);
+            simplify_expr(rhs, true);
+            if !is_condition {
+                return;
+            }
+            // `x AND 1` and `x OR 0` have the same truth value as `x`, but not necessarily the
+            // same value: `2 AND 1` is 1.
+            let identity = if *op == Operator::And {
+                is_true_literal
+            } else {
+                is_false_literal
+            };
+            if identity(rhs) {
+                *expr = lhs.as_ref().clone();
+            } else if identity(lhs) {
+                *expr = rhs.as_ref().clone();
+            }
+        }
+        Expr::Binary(lhs, op, rhs) => {
+            simplify_expr(lhs, false);
+            simplify_expr(rhs, false);
+            if let Some(folded) = fold_literal_comparison(lhs, *op, rhs) {
+                *expr = folded;
+            }
+        }
+        Expr::Unary(UnaryOperator::Not, inner) => {
+            simplify_expr(inner, true);
+            if !is_condition {
+                return;
+            }
+            if let Expr::Unary(UnaryOperator::Not, inner) = inner.as_mut() {
+                let inner = std::mem::replace(inner.as_mut(), Expr::Literal(Literal::Null));
+                *expr = inner;
+            }
+        }
+        Expr::InList { lhs, not, rhs } => {
+            simplify_expr(lhs, false);
+            let mut i = 0;
+            while i < rhs.len() {
+                let is_duplicate = matches!(rhs[i].as_ref(), Expr::Literal(lit) if rhs[..i]
+                    .iter()
+                    .any(|prev| matches!(prev.as_ref(), Expr::Literal(prev) if prev == lit)));
+                if is_duplicate {
+                    rhs.remove(i);
+                } else {
+                    i += 1;
+                }
+            }
+            // A row value on the left side must be compared with a row value, and an explicit
+            // COLLATE on the value would pick the collation of `=` differently than for `IN`.
+            let is_row_value =
+                matches!(lhs.as_ref(), Expr::Parenthesized(exprs) if exprs.len() > 1);
+            if rhs.len() == 1 && !is_row_value && matches!(rhs[0].as_ref(), Expr::Literal(_)) {
+                let op = if *not {
+                    Operator::NotEquals
+                } else {
+                    Operator::Equals
+                };
+                let value = rhs.pop().unwrap();
+                let lhs = std::mem::replace(lhs.as_mut(), Expr::Literal(Literal::Null));
+                *expr = Expr::Binary(Box::new(lhs), op, value);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A numeric literal, compared the same way SQLite compares the values it represents.
+#[derive(Clone, Copy)]
+enum NumericLiteral {
+    Integer(i64),
+    Float(f64),
+}
+
+impl NumericLiteral {
+    fn parse(expr: &Expr) -> Option<Self> {
+        let Expr::Literal(Literal::Numeric(text)) = expr else {
+            return None;
+        };
+        if let Ok(value) = text.parse::<i64>() {
+            return Some(Self::Integer(value));
+        }
+        // Hexadecimal literals and integers that do not fit in an i64 are left alone.
+        if text.starts_with("0x") || text.starts_with("0X") || !text.contains(['.', 'e', 'E']) {
+            return None;
+        }
+        text.parse::<f64>().ok().map(Self::Float)
+    }
+
+    fn compare(self, other: Self) -> Option<Ordering> {
+        // Integers beyond 2^53 are not exactly representable as floats, so comparing them
+        // with a float is left to the VDBE.
+        const MAX_EXACT: i64 = 1 << 53;
+        match (self, other) {
+            (Self::Integer(a), Self::Integer(b)) => Some(a.cmp(&b)),
+            (Self::Float(a), Self::Float(b)) => a.partial_cmp(&b),
+            (Self::Integer(a), Self::Float(b)) if a.abs() <= MAX_EXACT => {
+                (a as f64).partial_cmp(&b)
+            }
+            (Self::Float(a), Self::Integer(b)) if b.abs() <= MAX_EXACT => {
+                a.partial_cmp(&(b as f64))
+            }
+            _ => None,
+        }
+    }
+
+    fn is_zero(self) -> bool {
+        match self {
+            Self::Integer(value) => value == 0,
+            Self::Float(value) => value == 0.0,
+        }
+    }
+}
+
+fn is_true_literal(expr: &Expr) -> bool {
+    NumericLiteral::parse(expr).is_some_and(|value| !value.is_zero())
+}
+
+fn is_false_literal(expr: &Expr) -> bool {
+    NumericLiteral::parse(expr).is_some_and(|value| value.is_zero())
+}
+
+/// Folds a comparison whose operands are both numeric or NULL literals into its result.
+fn fold_literal_comparison(lhs: &Expr, op: Operator, rhs: &Expr) -> Option<Expr> {
+    let lhs_is_null = matches!(lhs, Expr::Literal(Literal::Null));
+    let rhs_is_null = matches!(rhs, Expr::Literal(Literal::Null));
+    let result = match op {
+        Operator::Is | Operator::IsNot if lhs_is_null || rhs_is_null => {
+            let is_same = if lhs_is_null && rhs_is_null {
+                true
+            } else if NumericLiteral::parse(lhs).is_some() || NumericLiteral::parse(rhs).is_some() {
+                false
+            } else {
+                return None;
+            };
+            is_same == (op == Operator::Is)
+        }
+        Operator::Equals
+        | Operator::NotEquals
+        | Operator::Less
+        | Operator::LessEquals
+        | Operator::Greater
+        | Operator::GreaterEquals
+            if lhs_is_null || rhs_is_null =>
+        {
+            let other = if lhs_is_null { rhs } else { lhs };
+            if matches!(other, Expr::Literal(Literal::Null))
+                || NumericLiteral::parse(other).is_some()
+            {
+                return Some(Expr::Literal(Literal::Null));
+            }
+            return None;
+        }
+        _ => {
+            let ordering = NumericLiteral::parse(lhs)?.compare(NumericLiteral::parse(rhs)?)?;
+            match op {
+                Operator::Equals | Operator::Is => ordering == Ordering::Equal,
+                Operator::NotEquals | Operator::IsNot => ordering != Ordering::Equal,
+                Operator::Less => ordering == Ordering::Less,
+                Operator::LessEquals => ordering != Ordering::Greater,
+                Operator::Greater => ordering == Ordering::Greater,
+                Operator::GreaterEquals => ordering != Ordering::Less,
+                _ => return None,
+            }
+        }
+    };
+    Some(Expr::Literal(Literal::Numeric(
+        if result { "1" } else { "0" }.to_string(),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use turso_parser::ast::Name;
+
+    fn id(name: &str) -> Box<Expr> {
+        Box::new(Expr::Id(Name::exact(name.to_string())))
+    }
+
+    fn num(value: &str) -> Box<Expr> {
+        Box::new(Expr::Literal(Literal::Numeric(value.to_string())))
+    }
+
+    fn binary(lhs: Box<Expr>, op: Operator, rhs: Box<Expr>) -> Box<Expr> {
+        Box::new(Expr::Binary(lhs, op, rhs))
+    }
+
+    fn simplify(expr: Box<Expr>) -> Vec<Expr> {
+        let mut where_clause = vec![WhereTerm::from(*expr)];
+        simplify_where_clause(&mut where_clause);
+        where_clause.into_iter().map(|term| term.expr).collect()
+    }
+
+    #[test]
+    fn test_fold_literal_comparisons() {
+        assert_eq!(
+            simplify(binary(num("1"), Operator::Less, num("2.5"))),
+            vec![*num("1")]
+        );
+        assert_eq!(
+            simplify(binary(num("1.0"), Operator::NotEquals, num("1"))),
+            vec![*num("0")]
+        );
+        assert_eq!(
+            simplify(binary(
+                Box::new(Expr::Literal(Literal::Null)),
+                Operator::Equals,
+                num("1")
+            )),
+            vec![Expr::Literal(Literal::Null)]
+        );
+        assert_eq!(
+            simplify(binary(
+                Box::new(Expr::Literal(Literal::Null)),
+                Operator::IsNot,
+                num("1")
+            )),
+            vec![*num("1")]
+        );
+        // Hexadecimal literals are not folded.
+        let expr = binary(num("0x10"), Operator::Equals, num("16"));
+        assert_eq!(simplify(expr.clone()), vec![*expr]);
+    }
+
+    #[test]
+    fn test_boolean_identities_split_into_terms() {
+        let a = binary(id("a"), Operator::Equals, num("1"));
+        let b = binary(id("b"), Operator::Equals, num("2"));
+        // (a = 1 AND b = 2) OR 1 = 2
+        let expr = binary(
+            Box::new(Expr::Parenthesized(vec![binary(
+                a.clone(),
+                Operator::And,
+                b.clone(),
+            )])),
+            Operator::Or,
+            binary(num("1"), Operator::Equals, num("2")),
+        );
+        assert_eq!(simplify(expr), vec![*a.clone(), *b]);
+
+        // NOT NOT (a = 1 AND 1)
+        let expr = Box::new(Expr::Unary(
+            UnaryOperator::Not,
+            Box::new(Expr::Unary(
+                UnaryOperator::Not,
+                Box::new(Expr::Parenthesized(vec![binary(
+                    a.clone(),
+                    Operator::And,
+                    num("1"),
+                )])),
+            )),
+        ));
+        assert_eq!(simplify(expr), vec![*a]);
+    }
+
+    #[test]
+    fn test_identities_keep_values_outside_conditions() {
+        // (a AND 1) = 1 must not become a = 1, since `2 AND 1` is 1.
+        let expr = binary(
+            Box::new(Expr::Parenthesized(vec![binary(
+                id("a"),
+                Operator::And,
+                num("1"),
+            )])),
+            Operator::Equals,
+            num("1"),
+        );
+        assert_eq!(
+            simplify(expr),
+            vec![*binary(
+                binary(id("a"), Operator::And, num("1")),
+                Operator::Equals,
+                num("1")
+            )]
+        );
+    }
+
+    #[test]
+    fn test_in_list() {
+        let expr = Box::new(Expr::InList {
+            lhs: id("a"),
+            not: true,
+            rhs: vec![num("1"), num("1")],
+        });
+        assert_eq!(
+            simplify(expr),
+            vec![*binary(id("a"), Operator::NotEquals, num("1"))]
+        );
+
+        let expr = Box::new(Expr::InList {
+            lhs: id("a"),
+            not: false,
+            rhs: vec![num("1"), num("2"), num("1")],
+        });
+        assert_eq!(
+            simplify(expr),
+            vec![Expr::InList {
+                lhs: id("a"),
+                not: false,
+                rhs: vec![num("1"), num("2")],
+            }]
+        );
+    }
+}
@@ -9,9 +9,12 @@ use crate::{
     translate::{
         collate::get_collseq_from_expr,
         emitter::UpdateRowSource,
-        expr::as_binary_components,
+        expr::{as_binary_components, walk_expr, WalkControl},
         expression_index::{normalize_expr_for_index_matching, single_table_column_usage},
-        optimizer::constraints::{BinaryExprSide, SeekRangeConstraint},
+        optimizer::{
+            constraints::{BinaryExprSide, SeekRangeConstraint},
+            Optimizable,
+        },
         planner::determine_where_to_eval_term,
     },
     vdbe::{
@@ -396,6 +399,9 @@ pub struct SelectPlan {
     pub window: Option<Window>,
     /// Subqueries that appear in any part of the query apart from the FROM clause
     pub non_from_clause_subqueries: Vec<NonFromClauseSubquery>,
+    /// The only aggregate is a MIN or MAX, and the rows are read sorted by its argument, so the
+    /// loop stops after the first row; see [crate::translate::optimizer::min_max].
+    pub min_max_from_first_row: bool,
 }
 
 impl SelectPlan {
@@ -418,20 +424,24 @@ impl SelectPlan {
 
     /// Reference: https://github.com/sqlite/sqlite/blob/5db695197b74580c777b37ab1b787531f15f7f9f/src/select.c#L8613
     ///
-    /// Checks to see if the query is of the format `SELECT count(*) FROM <tbl>`
+    /// Checks to see if the query is of the format `SELECT count(*) FROM <tbl>`, in which case
+    /// the rows are counted from the cells of the b-tree pages without reading them one by one.
+    /// The result columns may be any expressions of the count, e.g. `SELECT count(*) * 2 AS n`,
+    /// as long as they don't reference the columns of the table.
     pub fn is_simple_count(&self) -> bool {
-        if !self.where_clause.is_empty()
+        // Terms consumed by a seek still filter the rows, so only terms that are always true,
+        // and were removed by the constant elimination, may remain.
+        if self
+            .where_clause
+            .iter()
+            .any(|term| !term.expr.is_always_true().unwrap_or(false))
             || self.aggregates.len() != 1
-            || matches!(
-                self.query_destination,
-                QueryDestination::CoroutineYield { .. }
-            )
             || self.table_references.joined_tables().len() != 1
             || !self.table_references.outer_query_refs().is_empty()
-            || self.result_columns.len() != 1
+            || !self.non_from_clause_subqueries.is_empty()
             || self.group_by.is_some()
+            || self.window.is_some()
             || self.contains_constant_false_condition
-        // TODO: (pedrocarlo) maybe can optimize to use the count optmization with more columns
         {
             return false;
         }
@@ -439,33 +449,37 @@ impl SelectPlan {
         if !matches!(table_ref.table, crate::schema::Table::BTree(..)) {
             return false;
         }
+        // Only a full scan of the table, or of an index that has an entry for every row, reads
+        // every row.
+        match &table_ref.op {
+            Operation::Scan(Scan::BTreeTable { index, .. }) => {
+                if index
+                    .as_ref()
+                    .is_some_and(|index| index.where_clause.is_some())
+                {
+                    return false;
+                }
+            }
+            _ => return false,
+        }
         let agg = self.aggregates.first().unwrap();
         if !matches!(agg.func, AggFunc::Count0) {
             return false;
         }
 
-        let count = ast::Expr::FunctionCall {
-            name: ast::Name::exact("count".to_string()),
-            distinctness: None,
-            args: vec![],
-            order_by: vec![],
-            filter_over: ast::FunctionTail {
-                filter_clause: None,
-                over_clause: None,
-            },
-        };
-        let count_star = ast::Expr::FunctionCallStar {
-            name: ast::Name::exact("count".to_string()),
-            filter_over: ast::FunctionTail {
-                filter_clause: None,
-                over_clause: None,
-            },
-        };
-        let result_col_expr = &self.result_columns.first().unwrap().expr;
-        if *result_col_expr != count && *result_col_expr != count_star {
-            return false;
+        let mut references_columns = false;
+        for result_column in &self.result_columns {
+            let _ = walk_expr(
+                &result_column.expr,
+                &mut |expr: &Expr| -> Result<WalkControl> {
+                    if matches!(expr, Expr::Column { .. } | Expr::RowId { .. }) {
+                        references_columns = true;
+                    }
+                    Ok(WalkControl::Continue)
+                },
+            );
         }
-        true
+        !references_columns
     }
 }
 
@@ -1112,6 +1126,10 @@ pub enum Operation {
     // The build table is accessed normally (via Scan), and the probe table
     // uses this operation to indicate it should probe the hash table.
     HashJoin(HashJoinOp),
+    // Multi-index OR operation
+    // This operation is used to access a table through the union of several
+    // searches, one for each arm of an OR term in the WHERE clause.
+    MultiIndexScan(MultiIndexScan),
 }
 
 impl Operation {
@@ -1138,6 +1156,7 @@ impl Operation {
             Operation::Scan(_) => None,
             Operation::Search(Search::RowidEq { .. }) => None,
             Operation::HashJoin(_) => None,
+            Operation::MultiIndexScan(_) => None,
         }
     }
 }
@@ -1192,6 +1211,9 @@ impl JoinedTable {
             plan: Box::new(plan),
             columns,
             result_columns_start_reg: None,
+            cte_id: None,
+            materialized: ast::Materialized::Any,
+            materialized_cursor_id: None,
         });
         Ok(Self {
             op: Operation::default_scan_for(&table),
@@ -1397,12 +1419,18 @@ impl JoinedTable {
         let Table::BTree(btree) = &self.table else {
             return false;
         };
-        if self.col_used_mask.is_empty() {
-            return false;
+        // The primary key index of a WITHOUT ROWID table holds every column of the table.
+        if index.is_without_rowid_primary_key(btree) {
+            return true;
         }
         if index.index_method.is_some() {
             return false;
         }
+        if self.col_used_mask.is_empty() {
+            // Entries of every index on a WITHOUT ROWID table carry the primary key, which is
+            // enough to enumerate the rows of the table.
+            return !btree.has_rowid;
+        }
 
         if self.expression_index_usages.is_empty() {
             Self::index_covers_columns(index, btree, &self.col_used_mask)
@@ -1459,6 +1487,14 @@ impl JoinedTable {
         let Some(index) = self.op.index() else {
             return false;
         };
+        // When no column of the table is read, the index is only used to enumerate rowids.
+        if self.col_used_mask.is_empty()
+            && self.expression_index_usages.is_empty()
+            && matches!(&self.table, Table::BTree(btree) if btree.has_rowid)
+            && index.has_entry_for_every_row()
+        {
+            return true;
+        }
         self.index_is_covering(index.as_ref())
     }
 
@@ -1503,13 +1539,16 @@ impl<'a> Iterator for SeekDefKeyIterator<'a, SeekKeyComponent<&'a ast::Expr>> {
             Some(SeekKeyComponent::Expr(
                 &self.seek_def.prefix[self.pos].eq.as_ref().unwrap().1,
             ))
-        } else if self.pos == self.seek_def.prefix.len() {
+        } else {
             match &self.seek_key.last_component {
-                SeekKeyComponent::Expr(expr) => Some(SeekKeyComponent::Expr(expr)),
-                SeekKeyComponent::None => None,
+                SeekKeyComponent::Expr(expr) if self.pos == self.seek_def.prefix.len() => {
+                    Some(SeekKeyComponent::Expr(expr))
+                }
+                SeekKeyComponent::Row(elements) => elements
+                    .get(self.pos - self.seek_def.prefix.len())
+                    .map(|(expr, _)| SeekKeyComponent::Expr(expr)),
+                _ => None,
             }
-        } else {
-            None
         };
         self.pos += 1;
         result
@@ -1522,13 +1561,16 @@ impl<'a> Iterator for SeekDefKeyIterator<'a, Affinity> {
     fn next(&mut self) -> Option<Self::Item> {
         let result = if self.pos < self.seek_def.prefix.len() {
             Some(self.seek_def.prefix[self.pos].eq.as_ref().unwrap().2)
-        } else if self.pos == self.seek_def.prefix.len() {
+        } else {
             match &self.seek_key.last_component {
-                SeekKeyComponent::Expr(..) => Some(self.seek_key.affinity),
-                SeekKeyComponent::None => None,
+                SeekKeyComponent::Expr(..) if self.pos == self.seek_def.prefix.len() => {
+                    Some(self.seek_key.affinity)
+                }
+                SeekKeyComponent::Row(elements) => elements
+                    .get(self.pos - self.seek_def.prefix.len())
+                    .map(|(_, affinity)| *affinity),
+                _ => None,
             }
-        } else {
-            None
         };
         self.pos += 1;
         result
@@ -1540,8 +1582,9 @@ impl SeekDef {
     /// - so, for SELECT * FROM t WHERE x = 10 AND y = 20 AND y >= 30 there will be 3 values (10, 20, 30)
     pub fn size(&self, key: &SeekKey) -> usize {
         self.prefix.len()
-            + match key.last_component {
+            + match &key.last_component {
                 SeekKeyComponent::Expr(_) => 1,
+                SeekKeyComponent::Row(elements) => elements.len(),
                 SeekKeyComponent::None => 0,
             }
     }
@@ -1575,10 +1618,16 @@ impl SeekDef {
 /// For now - we don't need this and it's enough to just either use some user-provided expression or omit last component of the key completely
 /// But as separate enum is almost never a harm - I decided to keep it here.
 ///
+/// The last component can also be a row value, which spans several columns of the index, when the seek is bounded
+/// by a comparison of row values, e.g. `(x, y) > (1, 2)` over an index on `(x, y)`.
+///
 /// This enum accepts generic argument E in order to use both SeekKeyComponent<ast::Expr> and SeekKeyComponent<&ast::Expr>
 #[derive(Debug, Clone)]
 pub enum SeekKeyComponent<E> {
     Expr(E),
+    /// The elements of a row value and their affinities, e.g. `(1, 2)` in `(x, y) > (1, 2)`,
+    /// which constrain consecutive index columns.
+    Row(Vec<(E, Affinity)>),
     None,
 }
 
@@ -1633,6 +1682,36 @@ pub enum Search {
     },
 }
 
+/// A union of searches used to evaluate a WHERE term of the form `a OR b OR ...`, where every
+/// arm of the OR can be answered by its own index or rowid search.
+///
+/// The rowids found by each branch are collected into a RowSet, which removes duplicates and
+/// returns them in rowid order, and each row is then read from the table by rowid. The OR term
+/// itself is still evaluated for every row.
+#[derive(Clone, Debug)]
+pub struct MultiIndexScan {
+    /// One search per arm of the OR term.
+    pub branches: Vec<Search>,
+}
+
+impl MultiIndexScan {
+    /// Describes the access path of each branch, e.g. `INDEX idx_a, INTEGER PRIMARY KEY`.
+    pub fn branch_descriptions(&self) -> String {
+        self.branches
+            .iter()
+            .map(|branch| match branch {
+                Search::RowidEq { .. } | Search::Seek { index: None, .. } => {
+                    "INTEGER PRIMARY KEY".to_string()
+                }
+                Search::Seek {
+                    index: Some(index), ..
+                } => format!("INDEX {}", index.name),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
 #[allow(clippy::large_enum_variant)]
 #[derive(Clone, Debug)]
 pub struct IndexMethodQuery {
@@ -11,7 +11,7 @@ use super::{
     select::prepare_select_plan,
 };
 use crate::translate::{
-    emitter::Resolver,
+    emitter::{Resolver, TransactionMode},
     expr::{BindingBehavior, WalkControl},
     plan::{NonFromClauseSubquery, SubqueryState},
 };
@@ -273,7 +273,7 @@ fn add_aggregate_if_not_exists(
     func: AggFunc,
 ) -> Result<()> {
     if distinctness.is_distinct() && args.len() != 1 {
-        crate::bail_parse_error!("DISTINCT aggregate functions must have exactly one argument");
+        crate::bail_parse_error!("DISTINCT aggregates must have exactly one argument");
     }
     if aggs
         .iter()
@@ -290,7 +290,7 @@ fn parse_from_clause_table(
     program: &mut ProgramBuilder,
     table_references: &mut TableReferences,
     vtab_predicates: &mut Vec<Expr>,
-    ctes: &mut Vec<JoinedTable>,
+    ctes: &mut Vec<Cte>,
     connection: &Arc<crate::Connection>,
 ) -> Result<()> {
     match table {
@@ -319,7 +319,7 @@ fn parse_from_clause_table(
                 .cloned()
                 .chain(
                     ctes.iter()
-                        .cloned()
+                        .map(|cte| cte.table.clone())
                         .map(|t: JoinedTable| OuterQueryReference {
                             identifier: t.identifier,
                             internal_id: t.internal_id,
@@ -366,7 +366,9 @@ fn parse_from_clause_table(
             &args,
             connection,
         ),
-        _ => todo!(),
+        ast::SelectTable::Sub(..) => {
+            crate::bail_parse_error!("Parenthesized joins in the FROM clause are not supported yet")
+        }
     }
 }
 
@@ -375,7 +377,7 @@ fn parse_table(
     table_references: &mut TableReferences,
     resolver: &Resolver,
     program: &mut ProgramBuilder,
-    ctes: &mut Vec<JoinedTable>,
+    ctes: &mut Vec<Cte>,
     vtab_predicates: &mut Vec<Expr>,
     qualified_name: &QualifiedName,
     maybe_alias: Option<&As>,
@@ -387,12 +389,17 @@ fn parse_table(
     let table_name = &qualified_name.name;
 
     // Check if the FROM clause table is referring to a CTE in the current scope.
-    if let Some(cte_idx) = ctes
-        .iter()
-        .position(|cte| cte.identifier == normalized_qualified_name)
+    if let Some(cte) = ctes
+        .iter_mut()
+        .find(|cte| cte.table.identifier == normalized_qualified_name)
     {
-        // TODO: what if the CTE is referenced multiple times?
-        let mut cte_table = ctes.remove(cte_idx);
+        // Each further reference to the CTE gets its own plan, so that it has its own cursors.
+        let mut cte_table = if cte.referenced {
+            cte.plan(resolver, program, connection)?
+        } else {
+            cte.referenced = true;
+            cte.table.clone()
+        };
 
         // If there's an alias provided, update the identifier to use that alias
         if let Some(a) = maybe_alias {
@@ -408,9 +415,14 @@ fn parse_table(
     };
 
     // Resolve table using connection's with_schema method
-    let table = connection.with_schema(database_id, |schema| schema.get_table(table_name.as_str()));
+    let (table, schema_cookie) = connection.with_schema(database_id, |schema| {
+        (schema.get_table(table_name.as_str()), schema.schema_version)
+    });
 
     if let Some(table) = table {
+        if database_id != 0 {
+            program.begin_attached_operation(database_id, TransactionMode::Read, schema_cookie);
+        }
         let alias = maybe_alias
             .map(|a| match a {
                 ast::As::As(id) => id,
@@ -645,7 +657,7 @@ pub fn parse_from(
         return Ok(());
     }
 
-    let mut ctes_as_subqueries: Vec<JoinedTable> = vec![];
+    let mut ctes_as_subqueries: Vec<Cte> = vec![];
 
     if let Some(with) = with {
         if with.recursive {
@@ -653,9 +665,6 @@ pub fn parse_from(
         }
 
         for cte in with.ctes {
-            if cte.materialized == Materialized::Yes {
-                crate::bail_parse_error!("Materialized CTEs are not yet supported");
-            }
             if !cte.columns.is_empty() {
                 crate::bail_parse_error!("CTE columns are not yet supported");
             }
@@ -666,7 +675,7 @@ pub fn parse_from(
             let cte_name_normalized = normalize_ident(cte.tbl_name.as_str());
             if ctes_as_subqueries
                 .iter()
-                .any(|t| t.table.get_name() == cte_name_normalized)
+                .any(|cte| cte.table.table.get_name() == cte_name_normalized)
             {
                 crate::bail_parse_error!("duplicate WITH table name: {}", cte.tbl_name.as_str());
             }
@@ -690,33 +699,24 @@ pub fn parse_from(
             }
 
             let mut outer_query_refs_for_cte = table_references.outer_query_refs().to_vec();
-            outer_query_refs_for_cte.extend(ctes_as_subqueries.iter().map(|t: &JoinedTable| {
+            outer_query_refs_for_cte.extend(ctes_as_subqueries.iter().map(|cte| {
                 OuterQueryReference {
-                    identifier: t.identifier.clone(),
-                    internal_id: t.internal_id,
-                    table: t.table.clone(),
+                    identifier: cte.table.identifier.clone(),
+                    internal_id: cte.table.internal_id,
+                    table: cte.table.table.clone(),
                     col_used_mask: ColumnUsedMask::default(),
                 }
             }));
 
             // CTE can refer to other CTEs that came before it, plus any schema tables or tables in the outer scope.
-            let cte_plan = prepare_select_plan(
+            ctes_as_subqueries.push(Cte::new(
+                cte_name_normalized,
                 cte.select,
+                cte.materialized,
+                outer_query_refs_for_cte,
                 resolver,
                 program,
-                &outer_query_refs_for_cte,
-                QueryDestination::placeholder_for_subquery(),
                 connection,
-            )?;
-            let Plan::Select(cte_plan) = cte_plan else {
-                crate::bail_parse_error!("Only SELECT queries are currently supported in CTEs");
-            };
-
-            ctes_as_subqueries.push(JoinedTable::new_subquery(
-                cte_name_normalized,
-                cte_plan,
-                None,
-                program.table_reference_counter.next(),
             )?);
         }
     }
@@ -747,9 +747,128 @@ pub fn parse_from(
         )?;
     }
 
+    materialize_repeated_ctes(table_references);
+
     Ok(())
 }
 
+/// A CTE in the scope of a FROM clause.
+///
+/// CTEs are transformed into FROM clause subqueries, and all the references to a CTE share
+/// the internal id of the first one in [crate::schema::FromClauseSubquery::cte_id].
+struct Cte {
+    /// The FROM clause subquery for the first reference to the CTE.
+    table: JoinedTable,
+    /// Whether the CTE has been referenced in the FROM clause.
+    referenced: bool,
+    select: ast::Select,
+    outer_query_refs: Vec<OuterQueryReference>,
+}
+
+impl Cte {
+    fn new(
+        name: String,
+        select: ast::Select,
+        materialized: Materialized,
+        outer_query_refs: Vec<OuterQueryReference>,
+        resolver: &Resolver,
+        program: &mut ProgramBuilder,
+        connection: &Arc<crate::Connection>,
+    ) -> Result<Self> {
+        let table = Self::plan_subquery(
+            name,
+            None,
+            materialized,
+            &select,
+            &outer_query_refs,
+            resolver,
+            program,
+            connection,
+        )?;
+        Ok(Self {
+            table,
+            referenced: false,
+            select,
+            outer_query_refs,
+        })
+    }
+
+    /// Plans the CTE again for another reference, with new internal ids.
+    fn plan(
+        &self,
+        resolver: &Resolver,
+        program: &mut ProgramBuilder,
+        connection: &Arc<crate::Connection>,
+    ) -> Result<JoinedTable> {
+        let Table::FromClauseSubquery(subquery) = &self.table.table else {
+            unreachable!("CTEs are FROM clause subqueries");
+        };
+        Self::plan_subquery(
+            subquery.name.clone(),
+            subquery.cte_id,
+            subquery.materialized.clone(),
+            &self.select,
+            &self.outer_query_refs,
+            resolver,
+            program,
+            connection,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn plan_subquery(
+        name: String,
+        cte_id: Option<TableInternalId>,
+        materialized: Materialized,
+        select: &ast::Select,
+        outer_query_refs: &[OuterQueryReference],
+        resolver: &Resolver,
+        program: &mut ProgramBuilder,
+        connection: &Arc<crate::Connection>,
+    ) -> Result<JoinedTable> {
+        let Plan::Select(plan) = prepare_select_plan(
+            select.clone(),
+            resolver,
+            program,
+            outer_query_refs,
+            QueryDestination::placeholder_for_subquery(),
+            connection,
+        )?
+        else {
+            crate::bail_parse_error!("Only SELECT queries are currently supported in CTEs");
+        };
+        let internal_id = program.table_reference_counter.next();
+        let mut table = JoinedTable::new_subquery(name, plan, None, internal_id)?;
+        if let Table::FromClauseSubquery(subquery) = &mut table.table {
+            subquery.cte_id = Some(cte_id.unwrap_or(internal_id));
+            subquery.materialized = materialized;
+        }
+        Ok(table)
+    }
+}
+
+/// Like SQLite, a CTE without a `MATERIALIZED` or `NOT MATERIALIZED` hint is materialized
+/// when it's referenced more than once in the FROM clause, so that it's computed only once
+/// instead of once per scan.
+fn materialize_repeated_ctes(table_references: &mut TableReferences) {
+    let cte_ids = table_references
+        .joined_tables()
+        .iter()
+        .filter_map(|t| match &t.table {
+            Table::FromClauseSubquery(subquery) => subquery.cte_id,
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    for table in table_references.joined_tables_mut() {
+        if let Table::FromClauseSubquery(subquery) = &mut table.table {
+            let references = cte_ids.iter().filter(|&&id| Some(id) == subquery.cte_id);
+            if subquery.materialized == Materialized::Any && references.count() > 1 {
+                subquery.materialized = Materialized::Yes;
+            }
+        }
+    }
+}
+
 pub fn parse_where(
     where_clause: Option<&Expr>,
     table_references: &mut TableReferences,
@@ -1038,7 +1157,7 @@ fn parse_join(
     join: ast::JoinedSelectTable,
     resolver: &Resolver,
     program: &mut ProgramBuilder,
-    ctes: &mut Vec<JoinedTable>,
+    ctes: &mut Vec<Cte>,
     out_where_clause: &mut Vec<WhereTerm>,
     vtab_predicates: &mut Vec<Expr>,
     table_references: &mut TableReferences,
@@ -1263,6 +1382,21 @@ pub fn break_predicate_at_and_boundaries<T: From<Expr>>(
             break_predicate_at_and_boundaries(left, out_predicates);
             break_predicate_at_and_boundaries(right, out_predicates);
         }
+        // `(a, b) = (1, 2)` is true exactly when `a = 1 AND b = 2` is, and each equality can
+        // constrain an index column on its own.
+        Expr::Binary(left, ast::Operator::Equals, right) => match (left.as_ref(), right.as_ref()) {
+            (Expr::Parenthesized(l), Expr::Parenthesized(r))
+                if l.len() > 1 && l.len() == r.len() =>
+            {
+                for (l, r) in l.iter().zip(r.iter()) {
+                    break_predicate_at_and_boundaries(
+                        &Expr::Binary(l.clone(), ast::Operator::Equals, r.clone()),
+                        out_predicates,
+                    );
+                }
+            }
+            _ => out_predicates.push(predicate.clone().into()),
+        },
         _ => {
             out_predicates.push(predicate.clone().into());
         }
@@ -9,7 +9,7 @@ use turso_parser::ast::{PragmaName, QualifiedName};
 
 use super::integrity_check::translate_integrity_check;
 use crate::pragma::pragma_for;
-use crate::schema::Schema;
+use crate::schema::{BTreeTable, Index, Schema};
 use crate::storage::encryption::{CipherMode, EncryptionKey};
 use crate::storage::pager::AutoVacuumMode;
 use crate::storage::pager::Pager;
@@ -60,7 +60,10 @@ pub fn translate_pragma(
     let (mut program, mode) = match body {
         None => query_pragma(pragma, resolver.schema, None, pager, connection, program)?,
         Some(ast::PragmaBody::Equals(value) | ast::PragmaBody::Call(value)) => match pragma {
-            PragmaName::TableInfo | PragmaName::TableXinfo => query_pragma(
+            PragmaName::TableInfo
+            | PragmaName::TableXinfo
+            | PragmaName::IndexInfo
+            | PragmaName::IndexList => query_pragma(
                 pragma,
                 resolver.schema,
                 Some(*value),
@@ -151,6 +154,11 @@ fn update_pragma(
             connection.get_pager().set_spill_enabled(enabled);
             Ok((program, TransactionMode::None))
         }
+        PragmaName::CaseSensitiveLike => {
+            let enabled = parse_pragma_enabled(&value);
+            connection.set_case_sensitive_like(enabled);
+            Ok((program, TransactionMode::None))
+        }
         PragmaName::Encoding => {
             let year = chrono::Local::now().year();
             bail_parse_error!("It's {year}. UTF-8 won.");
@@ -174,6 +182,16 @@ fn update_pragma(
             Ok((program, TransactionMode::None))
         }
         PragmaName::LegacyFileFormat => Ok((program, TransactionMode::None)),
+        PragmaName::WalAutocheckpoint => {
+            // Like SQLite, zero or a negative value turns automatic checkpointing off.
+            let frames = match parse_signed_number(&value)? {
+                Value::Integer(frames) => frames,
+                Value::Float(frames) => frames as i64,
+                _ => bail_parse_error!("Invalid value for wal_autocheckpoint pragma"),
+            };
+            connection.set_wal_autocheckpoint(frames.clamp(0, u32::MAX as i64) as u32);
+            Ok((program, TransactionMode::None))
+        }
         PragmaName::WalCheckpoint => query_pragma(
             PragmaName::WalCheckpoint,
             resolver.schema,
@@ -328,6 +346,11 @@ fn update_pragma(
             });
             Ok((program, TransactionMode::None))
         }
+        PragmaName::IndexInfo | PragmaName::IndexList => {
+            // like table_info, these pragmas take a table or index name and only query
+            // the schema, so translate_pragma always calls query_pragma for them
+            unreachable!();
+        }
         PragmaName::IntegrityCheck => unreachable!("integrity_check cannot be set"),
         PragmaName::UnstableCaptureDataChangesConn => {
             let value = parse_string(&value)?;
@@ -389,10 +412,19 @@ fn update_pragma(
         }
         PragmaName::Synchronous => {
             use crate::SyncMode;
-            let mode = match parse_pragma_enabled(&value) {
-                true => SyncMode::Full,
-                false => SyncMode::Off,
+            let name_bytes = match &value {
+                Expr::Literal(Literal::Numeric(n)) => n.as_bytes(),
+                Expr::Literal(Literal::Keyword(name)) => name.as_bytes(),
+                Expr::Name(name) | Expr::Id(name) => name.as_str().as_bytes(),
+                _ => "".as_bytes(),
             };
+            // The same names and levels as getSafetyLevel() in SQLite, where unknown values select
+            // NORMAL. EXTRA has no journal to sync in WAL mode, so it behaves like FULL.
+            let mode = match_ignore_ascii_case!(match name_bytes {
+                b"OFF" | b"NO" | b"FALSE" | b"0" => SyncMode::Off,
+                b"FULL" | b"EXTRA" | b"2" | b"3" => SyncMode::Full,
+                _ => SyncMode::Normal,
+            });
             connection.set_sync_mode(mode);
             Ok((program, TransactionMode::None))
         }
@@ -401,6 +433,19 @@ fn update_pragma(
             connection.set_data_sync_retry(retry_enabled);
             Ok((program, TransactionMode::None))
         }
+        PragmaName::VdbeProfile => {
+            let enabled = parse_pragma_enabled(&value);
+            connection.set_vdbe_profile_enabled(enabled);
+            Ok((program, TransactionMode::None))
+        }
+        PragmaName::GroupCommitWindow => {
+            let window_ms = match parse_signed_number(&value)? {
+                Value::Integer(ms) if ms >= 0 => ms as u64,
+                _ => bail_parse_error!("group_commit_window must be a non-negative integer"),
+            };
+            connection.set_group_commit_window(std::time::Duration::from_millis(window_ms));
+            Ok((program, TransactionMode::None))
+        }
         PragmaName::MvccCheckpointThreshold => {
             let threshold = match parse_signed_number(&value)? {
                 Value::Integer(size) if size >= -1 => size,
@@ -417,6 +462,11 @@ fn update_pragma(
             connection.set_foreign_keys_enabled(enabled);
             Ok((program, TransactionMode::None))
         }
+        PragmaName::AutomaticIndex => {
+            let enabled = parse_pragma_enabled(&value);
+            connection.set_automatic_index_enabled(enabled);
+            Ok((program, TransactionMode::None))
+        }
     }
 }
 
@@ -459,6 +509,8 @@ fn query_pragma(
             program.add_pragma_result_column(pragma.to_string());
             Ok((program, TransactionMode::None))
         }
+        // Like SQLite, case_sensitive_like can only be set, not queried.
+        PragmaName::CaseSensitiveLike => Ok((program, TransactionMode::None)),
         PragmaName::DatabaseList => {
             let base_reg = register;
             program.alloc_registers(2);
@@ -507,6 +559,12 @@ fn query_pragma(
             Ok((program, TransactionMode::None))
         }
         PragmaName::LegacyFileFormat => Ok((program, TransactionMode::None)),
+        PragmaName::WalAutocheckpoint => {
+            program.emit_int(connection.wal_autocheckpoint() as i64, register);
+            program.emit_result_row(register, 1);
+            program.add_pragma_result_column(pragma.to_string());
+            Ok((program, TransactionMode::None))
+        }
         PragmaName::WalCheckpoint => {
             // Checkpoint uses 3 registers: P1, P2, P3. Ref Insn::Checkpoint for more info.
             // Allocate two more here as one was allocated at the top.
@@ -672,8 +730,68 @@ fn query_pragma(
             program.emit_result_row(register, 1);
             Ok((program, TransactionMode::None))
         }
+        PragmaName::IndexInfo => {
+            let name = match value {
+                Some(ast::Expr::Name(name)) => Some(normalize_ident(name.as_str())),
+                _ => None,
+            };
+
+            let base_reg = register;
+            program.alloc_registers(2);
+            let index = name.and_then(|name| {
+                schema
+                    .indexes
+                    .values()
+                    .flatten()
+                    .find(|index| index.name.eq_ignore_ascii_case(&name))
+            });
+            if let Some(index) = index {
+                for (seqno, column) in index.columns.iter().enumerate() {
+                    program.emit_int(seqno as i64, base_reg);
+                    // Like SQLite, columns that are expressions are reported with cid -2
+                    // and no name.
+                    if column.expr.is_some() {
+                        program.emit_int(-2, base_reg + 1);
+                        program.emit_null(base_reg + 2, None);
+                    } else {
+                        program.emit_int(column.pos_in_table as i64, base_reg + 1);
+                        program.emit_string8(column.name.clone(), base_reg + 2);
+                    }
+                    program.emit_result_row(base_reg, 3);
+                }
+            }
+            for name in pragma_for(&pragma).columns {
+                program.add_pragma_result_column(name.to_string());
+            }
+            Ok((program, TransactionMode::None))
+        }
+        PragmaName::IndexList => {
+            let name = match value {
+                Some(ast::Expr::Name(name)) => Some(normalize_ident(name.as_str())),
+                _ => None,
+            };
+
+            let base_reg = register;
+            program.alloc_registers(4);
+            if let Some(table) = name.and_then(|name| schema.get_btree_table(&name)) {
+                // Indexes are listed newest first, like in SQLite.
+                for (seq, index) in schema.get_indices(&table.name).enumerate() {
+                    program.emit_int(seq as i64, base_reg);
+                    program.emit_string8(index.name.clone(), base_reg + 1);
+                    program.emit_bool(index.unique, base_reg + 2);
+                    program.emit_string8(index_origin(&table, index).to_string(), base_reg + 3);
+                    program.emit_bool(index.where_clause.is_some(), base_reg + 4);
+                    program.emit_result_row(base_reg, 5);
+                }
+            }
+            for name in pragma_for(&pragma).columns {
+                program.add_pragma_result_column(name.to_string());
+            }
+            Ok((program, TransactionMode::None))
+        }
         PragmaName::IntegrityCheck => {
-            translate_integrity_check(schema, &mut program)?;
+            // The whole file is checked, including the objects of the other namespaces.
+            translate_integrity_check(&connection.default_namespace_schema(), &mut program)?;
             Ok((program, TransactionMode::Read))
         }
         PragmaName::UnstableCaptureDataChangesConn => {
@@ -771,6 +889,39 @@ fn query_pragma(
             program.add_pragma_result_column(pragma.to_string());
             Ok((program, TransactionMode::None))
         }
+        PragmaName::VdbeProfile => {
+            let mut opcodes = connection
+                .metrics
+                .read()
+                .opcode_profile
+                .iter()
+                .map(|(opcode, profile)| (*opcode, *profile))
+                .collect::<Vec<_>>();
+            opcodes.sort_by(|a, b| b.1.elapsed.cmp(&a.1.elapsed).then(a.0.cmp(b.0)));
+
+            let base_reg = register;
+            program.alloc_registers(2);
+            for (opcode, profile) in opcodes {
+                program.emit_string8(opcode.to_string(), base_reg);
+                program.emit_int(profile.executions as i64, base_reg + 1);
+                program.emit_int(profile.elapsed.as_micros() as i64, base_reg + 2);
+                program.emit_result_row(base_reg, 3);
+            }
+
+            let pragma = pragma_for(&pragma);
+            for col_name in pragma.columns.iter() {
+                program.add_pragma_result_column(col_name.to_string());
+            }
+            Ok((program, TransactionMode::None))
+        }
+        PragmaName::GroupCommitWindow => {
+            let window = connection.get_group_commit_window();
+            let register = program.alloc_register();
+            program.emit_int(window.as_millis() as i64, register);
+            program.emit_result_row(register, 1);
+            program.add_pragma_result_column(pragma.to_string());
+            Ok((program, TransactionMode::None))
+        }
         PragmaName::MvccCheckpointThreshold => {
             let threshold = connection.mvcc_checkpoint_threshold()?;
             let register = program.alloc_register();
@@ -787,6 +938,13 @@ fn query_pragma(
             program.add_pragma_result_column(pragma.to_string());
             Ok((program, TransactionMode::None))
         }
+        PragmaName::AutomaticIndex => {
+            let enabled = connection.automatic_index_enabled();
+            program.emit_int(enabled as i64, register);
+            program.emit_result_row(register, 1);
+            program.add_pragma_result_column(pragma.to_string());
+            Ok((program, TransactionMode::None))
+        }
     }
 }
 
@@ -832,7 +990,7 @@ fn emit_columns_for_table_info(
         program.emit_string8(column.name.clone().unwrap_or_default(), base_reg + 1);
 
         // type
-        program.emit_string8(column.ty_str.clone(), base_reg + 2);
+        program.emit_string8(column.decl_type().into_owned(), base_reg + 2);
 
         // notnull
         program.emit_bool(column.notnull(), base_reg + 3);
@@ -858,6 +1016,25 @@ fn emit_columns_for_table_info(
     }
 }
 
+/// Returns how an index was created, as reported by PRAGMA index_list: "c" for CREATE INDEX,
+/// "u" for a UNIQUE constraint and "pk" for a PRIMARY KEY constraint.
+fn index_origin(table: &BTreeTable, index: &Index) -> &'static str {
+    if !index.name.starts_with("sqlite_autoindex_") {
+        return "c";
+    }
+    let is_primary_key = index.columns.len() == table.primary_key_columns.len()
+        && index
+            .columns
+            .iter()
+            .zip(table.primary_key_columns.iter())
+            .all(|(column, (pk_column, _))| column.name.eq_ignore_ascii_case(pk_column));
+    if is_primary_key {
+        "pk"
+    } else {
+        "u"
+    }
+}
+
 fn update_auto_vacuum_mode(
     auto_vacuum_mode: AutoVacuumMode,
     largest_root_page_number: u32,
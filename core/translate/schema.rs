@@ -22,8 +22,6 @@ use crate::vdbe::insn::{
 use crate::Connection;
 use crate::{bail_parse_error, Result};
 
-use turso_ext::VTabKind;
-
 fn validate(body: &ast::CreateTableBody, connection: &Connection) -> Result<()> {
     if let ast::CreateTableBody::ColumnsAndConstraints {
         options, columns, ..
@@ -69,6 +67,8 @@ fn validate(body: &ast::CreateTableBody, connection: &Connection) -> Result<()>
                 }
             }
         }
+    } else {
+        bail_parse_error!("CREATE TABLE ... AS SELECT is not supported yet");
     }
     Ok(())
 }
@@ -82,10 +82,8 @@ pub fn translate_create_table(
     mut program: ProgramBuilder,
     connection: &Connection,
 ) -> Result<ProgramBuilder> {
+    let database_id = connection.resolve_create_database_id(&tbl_name, temporary)?;
     let normalized_tbl_name = normalize_ident(tbl_name.name.as_str());
-    if temporary {
-        bail_parse_error!("TEMPORARY table not supported yet");
-    }
     validate(&body, connection)?;
 
     let opts = ProgramBuilderOpts {
@@ -165,8 +163,8 @@ pub fn translate_create_table(
         program.alloc_cursor_id(CursorType::BTreeTable(schema_master_table.clone()));
     program.emit_insn(Insn::OpenWrite {
         cursor_id: sqlite_schema_cursor_id,
-        root_page: 1i64.into(),
-        db: 0,
+        root_page: schema_master_table.root_page.into(),
+        db: database_id,
     });
     let cdc_table = prepare_cdc_if_necessary(&mut program, resolver.schema, SQLITE_TABLEID)?;
 
@@ -178,7 +176,7 @@ pub fn translate_create_table(
     {
         let seq_table_root_reg = program.alloc_register();
         program.emit_insn(Insn::CreateBtree {
-            db: 0,
+            db: database_id,
             root: seq_table_root_reg,
             flags: CreateBTreeFlags::new_table(),
         });
@@ -208,11 +206,24 @@ pub fn translate_create_table(
     // TODO: SetCookie
     // TODO: SetCookie
 
+    // The rows of a WITHOUT ROWID table are stored in an index B-tree keyed by the PRIMARY KEY.
+    let without_rowid = matches!(
+        &body,
+        ast::CreateTableBody::ColumnsAndConstraints { options, .. }
+            if options.contains(ast::TableOptions::WITHOUT_ROWID)
+    );
+    if without_rowid && connection.mvcc_enabled() {
+        bail_parse_error!("WITHOUT ROWID tables are not supported with MVCC");
+    }
     let table_root_reg = program.alloc_register();
     program.emit_insn(Insn::CreateBtree {
-        db: 0,
+        db: database_id,
         root: table_root_reg,
-        flags: CreateBTreeFlags::new_table(),
+        flags: if without_rowid {
+            CreateBTreeFlags::new_index()
+        } else {
+            CreateBTreeFlags::new_table()
+        },
     });
 
     // Create an automatic index B-tree if needed
@@ -243,7 +254,7 @@ pub fn translate_create_table(
     if let Some(index_regs) = index_regs.as_ref() {
         for index_reg in index_regs.iter() {
             program.emit_insn(Insn::CreateBtree {
-                db: 0,
+                db: database_id,
                 root: *index_reg,
                 flags: CreateBTreeFlags::new_index(),
             });
@@ -254,8 +265,8 @@ pub fn translate_create_table(
     let sqlite_schema_cursor_id = program.alloc_cursor_id(CursorType::BTreeTable(table.clone()));
     program.emit_insn(Insn::OpenWrite {
         cursor_id: sqlite_schema_cursor_id,
-        root_page: 1i64.into(),
-        db: 0,
+        root_page: table.root_page.into(),
+        db: database_id,
     });
 
     let cdc_table = prepare_cdc_if_necessary(&mut program, resolver.schema, SQLITE_TABLEID)?;
@@ -296,7 +307,7 @@ pub fn translate_create_table(
     program.resolve_label(parse_schema_label, program.offset());
     // TODO: SetCookie
     program.emit_insn(Insn::SetCookie {
-        db: 0,
+        db: database_id,
         cookie: Cookie::SchemaVersion,
         value: resolver.schema.schema_version as i32 + 1,
         p5: 0,
@@ -310,7 +321,7 @@ pub fn translate_create_table(
     }
 
     program.emit_insn(Insn::ParseSchema {
-        db: sqlite_schema_cursor_id,
+        db: database_id,
         where_clause: Some(parse_schema_where_clause),
     });
 
@@ -428,6 +439,7 @@ pub fn emit_schema_entry(
 /// - The table has no PRIMARY KEY
 /// - The table has a single-column PRIMARY KEY whose typename is _exactly_ "INTEGER" e.g. not "INT".
 ///   In this case, the PRIMARY KEY column becomes an alias for the rowid.
+/// - The table is WITHOUT ROWID, in which case the table B-tree is the PRIMARY KEY index.
 ///
 /// Otherwise, an automatic PRIMARY KEY index is required.
 fn collect_autoindexes(
@@ -447,7 +459,8 @@ fn collect_autoindexes(
         };
 
         let needs_index = if us.is_primary_key {
-            !(col.primary_key() && col.is_rowid_alias())
+            // The primary key of a WITHOUT ROWID table is stored in the table B-tree itself.
+            table.has_rowid && !(col.primary_key() && col.is_rowid_alias())
         } else {
             // UNIQUE single needs an index
             true
@@ -458,7 +471,11 @@ fn collect_autoindexes(
         }
     }
 
-    for _us in table.unique_sets.iter().filter(|us| us.columns.len() > 1) {
+    for _us in table
+        .unique_sets
+        .iter()
+        .filter(|us| us.columns.len() > 1 && (table.has_rowid || !us.is_primary_key))
+    {
         regs.push(program.alloc_register());
     }
     if regs.is_empty() {
@@ -477,7 +494,9 @@ fn create_table_body_to_str(tbl_name: &ast::QualifiedName, body: &ast::CreateTab
             constraints: _,
             options: _,
         } => {}
-        ast::CreateTableBody::AsSelect(_select) => todo!("as select not yet supported"),
+        ast::CreateTableBody::AsSelect(_select) => {
+            unreachable!("CREATE TABLE ... AS SELECT is rejected by validate()")
+        }
     }
     sql
 }
@@ -542,7 +561,7 @@ pub fn translate_create_virtual_table(
     let Some(vtab_module) = resolver.symbol_table.vtab_modules.get(&module_name_str) else {
         bail_parse_error!("no such module: {}", module_name_str);
     };
-    if !vtab_module.module_kind.eq(&VTabKind::VirtualTable) {
+    if !vtab_module.can_create_table() {
         bail_parse_error!("module {} is not a virtual table", module_name_str);
     };
     if resolver.schema.get_table(&table_name).is_some() {
@@ -591,7 +610,7 @@ pub fn translate_create_virtual_table(
     let sqlite_schema_cursor_id = program.alloc_cursor_id(CursorType::BTreeTable(table.clone()));
     program.emit_insn(Insn::OpenWrite {
         cursor_id: sqlite_schema_cursor_id,
-        root_page: 1i64.into(),
+        root_page: table.root_page.into(),
         db: 0,
     });
 
@@ -617,7 +636,7 @@ pub fn translate_create_virtual_table(
     });
     let parse_schema_where_clause = format!("tbl_name = '{table_name}' AND type != 'trigger'");
     program.emit_insn(Insn::ParseSchema {
-        db: sqlite_schema_cursor_id,
+        db: 0,
         where_clause: Some(parse_schema_where_clause),
     });
 
@@ -643,16 +662,11 @@ pub fn translate_drop_table(
     tbl_name: ast::QualifiedName,
     resolver: &mut Resolver,
     if_exists: bool,
-    mut program: ProgramBuilder,
+    program: ProgramBuilder,
     connection: &Arc<Connection>,
 ) -> Result<ProgramBuilder> {
+    let database_id = connection.resolve_database_id(&tbl_name)?;
     let name = tbl_name.name.as_str();
-    let opts = ProgramBuilderOpts {
-        num_cursors: 4,
-        approx_num_insns: 40,
-        approx_num_labels: 4,
-    };
-    program.extend(&opts);
     let Some(table) = resolver.schema.get_table(name) else {
         if if_exists {
             return Ok(program);
@@ -660,6 +674,26 @@ pub fn translate_drop_table(
         bail_parse_error!("No such table: {name}");
     };
     validate_drop_table(resolver, name)?;
+    emit_drop_table(name, table, database_id, resolver, program, connection)
+}
+
+/// Emits the removal of a table, its indexes and triggers from the database `database_id`,
+/// whose schema `resolver` translates against. The caller validates that the table may be
+/// dropped.
+pub(crate) fn emit_drop_table(
+    name: &str,
+    table: Arc<Table>,
+    database_id: usize,
+    resolver: &mut Resolver,
+    mut program: ProgramBuilder,
+    connection: &Arc<Connection>,
+) -> Result<ProgramBuilder> {
+    let opts = ProgramBuilderOpts {
+        num_cursors: 4,
+        approx_num_insns: 40,
+        approx_num_labels: 4,
+    };
+    program.extend(&opts);
     // Check if foreign keys are enabled and if this table is referenced by foreign keys
     // Fire FK actions (CASCADE, SET NULL, SET DEFAULT) or check for violations (RESTRICT, NO ACTION)
     if connection.foreign_keys_enabled() && resolver.schema.any_resolved_fks_referencing(name) {
@@ -670,8 +704,7 @@ pub fn translate_drop_table(
     let null_reg = program.alloc_register(); //  r1
     program.emit_null(null_reg, None);
     let table_name_and_root_page_register = program.alloc_register(); //  r2, this register is special because it's first used to track table name and then moved root page
-    let table_reg =
-        program.emit_string8_new_reg(normalize_ident(tbl_name.name.as_str()).to_string()); //  r3
+    let table_reg = program.emit_string8_new_reg(normalize_ident(name).to_string()); //  r3
     program.mark_last_insn_constant();
     let _table_type = program.emit_string8_new_reg("trigger".to_string()); //  r4
     program.mark_last_insn_constant();
@@ -684,8 +717,8 @@ pub fn translate_drop_table(
     );
     program.emit_insn(Insn::OpenWrite {
         cursor_id: sqlite_schema_cursor_id_0,
-        root_page: 1i64.into(),
-        db: 0,
+        root_page: schema_table.root_page.into(),
+        db: database_id,
     });
 
     //  1. Remove all entries from the schema table related to the table we are dropping (including triggers)
@@ -769,12 +802,19 @@ pub fn translate_drop_table(
     // end of loop on schema table
 
     //  2. Destroy the indices within a loop
-    let indices = resolver.schema.get_indices(tbl_name.name.as_str());
+    let indices = resolver.schema.get_indices(name);
     for index in indices {
+        // The primary key index of a WITHOUT ROWID table is destroyed with the table below.
+        if table
+            .btree()
+            .is_some_and(|btree| index.is_without_rowid_primary_key(&btree))
+        {
+            continue;
+        }
         program.emit_insn(Insn::Destroy {
             root: index.root_page,
             former_root_reg: 0, //  no autovacuum (https://www.sqlite.org/opcode.html#Destroy)
-            is_temp: 0,
+            db: database_id,
         });
 
         //  3. TODO: Open an ephemeral table, and read over triggers from schema table into ephemeral table
@@ -790,7 +830,7 @@ pub fn translate_drop_table(
             program.emit_insn(Insn::Destroy {
                 root: table.root_page,
                 former_root_reg: table_name_and_root_page_register,
-                is_temp: 0,
+                db: database_id,
             });
         }
         Table::Virtual(vtab) => {
@@ -805,7 +845,7 @@ pub fn translate_drop_table(
             }
             program.emit_insn(Insn::VDestroy {
                 table_name: vtab.name.clone(),
-                db: 0, // TODO change this for multiple databases
+                db: database_id,
             });
         }
         Table::FromClauseSubquery(..) => panic!("FromClauseSubquery can't be dropped"),
@@ -855,8 +895,8 @@ pub fn translate_drop_table(
         });
         program.emit_insn(Insn::OpenRead {
             cursor_id: sqlite_schema_cursor_id_1,
-            root_page: 1i64,
-            db: 0,
+            root_page: schema_table.root_page,
+            db: database_id,
         });
 
         let schema_column_0_register = program.alloc_register();
@@ -912,8 +952,8 @@ pub fn translate_drop_table(
         // 5. Open a write cursor to the schema table and re-insert the records placed in the ephemeral table but insert the correct root page now
         program.emit_insn(Insn::OpenWrite {
             cursor_id: sqlite_schema_cursor_id_1,
-            root_page: 1i64.into(),
-            db: 0,
+            root_page: schema_table.root_page.into(),
+            db: database_id,
         });
 
         // Loop to copy over row id's from the ephemeral table and then re-insert into the schema table with the correct root page
@@ -982,14 +1022,13 @@ pub fn translate_drop_table(
     {
         let seq_cursor_id = program.alloc_cursor_id(CursorType::BTreeTable(seq_table.clone()));
         let seq_table_name_reg = program.alloc_register();
-        let dropped_table_name_reg =
-            program.emit_string8_new_reg(tbl_name.name.as_str().to_string());
+        let dropped_table_name_reg = program.emit_string8_new_reg(name.to_string());
         program.mark_last_insn_constant();
 
         program.emit_insn(Insn::OpenWrite {
             cursor_id: seq_cursor_id,
             root_page: seq_table.root_page.into(),
-            db: 0,
+            db: database_id,
         });
 
         let end_loop_label = program.allocate_label();
@@ -1030,14 +1069,14 @@ pub fn translate_drop_table(
 
     // Drop the in-memory structures for the table
     program.emit_insn(Insn::DropTable {
-        db: 0,
+        db: database_id,
         _p2: 0,
         _p3: 0,
-        table_name: tbl_name.name.as_str().to_string(),
+        table_name: name.to_string(),
     });
 
     program.emit_insn(Insn::SetCookie {
-        db: 0,
+        db: database_id,
         cookie: Cookie::SchemaVersion,
         value: resolver.schema.schema_version as i32 + 1,
         p5: 0,
@@ -5,7 +5,9 @@ use super::plan::{
 };
 use crate::schema::Table;
 use crate::translate::emitter::{OperationMode, Resolver};
-use crate::translate::expr::{bind_and_rewrite_expr, expr_vector_size, BindingBehavior};
+use crate::translate::expr::{
+    bind_and_rewrite_expr, expr_vector_size, translate_expr, BindingBehavior,
+};
 use crate::translate::group_by::compute_group_by_sort_order;
 use crate::translate::optimizer::optimize_plan;
 use crate::translate::plan::{GroupBy, Plan, ResultSetColumn, SelectPlan};
@@ -44,7 +46,12 @@ pub fn translate_select(
         query_destination,
         connection,
     )?;
-    optimize_plan(&mut program, &mut select_plan, resolver.schema)?;
+    optimize_plan(
+        &mut program,
+        &mut select_plan,
+        resolver.schema,
+        connection.automatic_index_enabled(),
+    )?;
     let num_result_cols;
     let opts = match &select_plan {
         Plan::Select(select) => {
@@ -160,16 +167,17 @@ pub fn prepare_select_plan(
                 .limit
                 .map_or(Ok((None, None)), |l| parse_limit(l, connection))?;
 
-            // FIXME: handle ORDER BY for compound selects
-            if !select.order_by.is_empty() {
-                crate::bail_parse_error!("ORDER BY is not supported for compound SELECTs yet");
-            }
+            let order_by = if select.order_by.is_empty() {
+                None
+            } else {
+                Some(resolve_compound_order_by(select.order_by, &left, &last)?)
+            };
             Ok(Plan::CompoundSelect {
                 left,
                 right_most: last,
                 limit,
                 offset,
-                order_by: None,
+                order_by,
             })
         }
     }
@@ -281,6 +289,7 @@ fn prepare_one_select_plan(
                 values: vec![],
                 window: None,
                 non_from_clause_subqueries: vec![],
+                min_max_from_first_row: false,
             };
 
             let mut windows = Vec::with_capacity(window_clause.len());
@@ -560,6 +569,7 @@ fn prepare_one_select_plan(
                     .collect(),
                 window: None,
                 non_from_clause_subqueries: vec![],
+                min_max_from_first_row: false,
             };
 
             validate_expr_correct_column_counts(&plan)?;
@@ -698,6 +708,85 @@ fn replace_column_number_with_copy_of_column_expr(
     Ok(())
 }
 
+/// Resolves the ORDER BY terms of a compound SELECT to result column numbers.
+///
+/// Unlike a simple SELECT, the ORDER BY of a compound SELECT can only refer to the columns of
+/// the result set, either by their 1-based position or by their name. Names are looked up in the
+/// leftmost SELECT first, then in the SELECTs to its right. Each resolved term is rewritten to its
+/// column number, keeping an explicit COLLATE if there is one.
+fn resolve_compound_order_by(
+    order_by: Vec<ast::SortedColumn>,
+    left: &[(SelectPlan, ast::CompoundOperator)],
+    right_most: &SelectPlan,
+) -> Result<Vec<(ast::Expr, ast::SortOrder)>> {
+    if order_by
+        .iter()
+        .filter_map(|o| o.nulls)
+        .any(|n| n == ast::NullsOrder::Last)
+    {
+        crate::bail_parse_error!("NULLS LAST is not supported yet in ORDER BY");
+    }
+    let num_columns = right_most.result_columns.len();
+    let selects = left
+        .iter()
+        .map(|(plan, _)| plan)
+        .chain(std::iter::once(right_most));
+    let mut key = Vec::with_capacity(order_by.len());
+    for (i, o) in order_by.into_iter().enumerate() {
+        let (term, collation) = match *o.expr {
+            ast::Expr::Collate(term, collation) => (term, Some(collation)),
+            term => (Box::new(term), None),
+        };
+        let column_number = match term.as_ref() {
+            ast::Expr::Literal(ast::Literal::Numeric(num)) => match num.parse::<usize>() {
+                Ok(n) if (1..=num_columns).contains(&n) => Some(n),
+                _ => crate::bail_parse_error!(
+                    "{} ORDER BY term out of range - should be between 1 and {}",
+                    ordinal(i + 1),
+                    num_columns
+                ),
+            },
+            ast::Expr::Id(name) | ast::Expr::Name(name) | ast::Expr::Qualified(_, name) => {
+                let name = normalize_ident(name.as_str());
+                selects.clone().find_map(|plan| {
+                    plan.result_columns
+                        .iter()
+                        .position(|rc| {
+                            rc.name(&plan.table_references)
+                                .is_some_and(|n| normalize_ident(n) == name)
+                        })
+                        .map(|idx| idx + 1)
+                })
+            }
+            _ => None,
+        };
+        let Some(column_number) = column_number else {
+            crate::bail_parse_error!(
+                "{} ORDER BY term does not match any column in the result set",
+                ordinal(i + 1)
+            );
+        };
+        let mut expr = ast::Expr::Literal(ast::Literal::Numeric(column_number.to_string()));
+        if let Some(collation) = collation {
+            expr = ast::Expr::Collate(Box::new(expr), collation);
+        }
+        key.push((expr, o.order.unwrap_or(ast::SortOrder::Asc)));
+    }
+    Ok(key)
+}
+
+/// Formats a 1-based position the way SQLite does in its error messages, e.g. "1st", "2nd".
+fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{n}{suffix}")
+}
+
 fn count_plan_required_cursors(plan: &SelectPlan) -> usize {
     let num_table_cursors: usize = plan
         .joined_tables()
@@ -710,6 +799,7 @@ fn count_plan_required_cursors(plan: &SelectPlan) -> usize {
             }
             Operation::IndexMethodQuery(_) => 1,
             Operation::HashJoin(_) => 2,
+            Operation::MultiIndexScan(multi) => 1 + multi.branches.len(),
         } + if let Table::FromClauseSubquery(from_clause_subquery) = &t.table {
             count_plan_required_cursors(&from_clause_subquery.plan)
         } else {
@@ -735,6 +825,7 @@ fn estimate_num_instructions(select: &SelectPlan) -> usize {
             Operation::Search(_) => 15,
             Operation::IndexMethodQuery(_) => 15,
             Operation::HashJoin(_) => 20,
+            Operation::MultiIndexScan(multi) => 10 + 15 * multi.branches.len(),
         } + if let Table::FromClauseSubquery(from_clause_subquery) = &t.table {
             10 + estimate_num_instructions(&from_clause_subquery.plan)
         } else {
@@ -760,6 +851,7 @@ fn estimate_num_labels(select: &SelectPlan) -> usize {
             Operation::Search(_) => 3,
             Operation::IndexMethodQuery(_) => 3,
             Operation::HashJoin(_) => 3,
+            Operation::MultiIndexScan(multi) => 3 + 2 * multi.branches.len(),
         } + if let Table::FromClauseSubquery(from_clause_subquery) = &t.table {
             3 + estimate_num_labels(&from_clause_subquery.plan)
         } else {
@@ -775,9 +867,13 @@ fn estimate_num_labels(select: &SelectPlan) -> usize {
     init_halt_labels + table_labels + group_by_labels + order_by_labels + condition_labels
 }
 
+/// Counts the rows of the table of a [SelectPlan::is_simple_count] query into the aggregate
+/// register, where [super::aggregation::emit_ungrouped_aggregation] expects the final value.
+/// The other result columns don't reference the table, so they are evaluated once here instead
+/// of in the loop over the rows.
 pub fn emit_simple_count(
     program: &mut ProgramBuilder,
-    _t_ctx: &mut TranslateCtx,
+    t_ctx: &mut TranslateCtx,
     plan: &SelectPlan,
 ) -> Result<()> {
     let cursors = plan
@@ -793,23 +889,31 @@ pub fn emit_simple_count(
         }
     };
 
-    // TODO: I think this allocation can be avoided if we are smart with the `TranslateCtx`
-    let target_reg = program.alloc_register();
-
     program.emit_insn(Insn::Count {
         cursor_id,
-        target_reg,
+        target_reg: t_ctx
+            .reg_agg_start
+            .expect("aggregate registers must be initialized"),
         exact: true,
     });
 
     program.emit_insn(Insn::Close { cursor_id });
-    let output_reg = program.alloc_register();
-    program.emit_insn(Insn::Copy {
-        src_reg: target_reg,
-        dst_reg: output_reg,
-        extra_amount: 0,
-    });
-    program.emit_result_row(output_reg, 1);
+
+    let col_start = t_ctx.reg_result_cols_start.unwrap();
+    for (i, rc) in plan
+        .result_columns
+        .iter()
+        .enumerate()
+        .filter(|(_, rc)| !rc.contains_aggregates)
+    {
+        translate_expr(
+            program,
+            Some(&plan.table_references),
+            &rc.expr,
+            col_start + i,
+            &t_ctx.resolver,
+        )?;
+    }
     Ok(())
 }
 
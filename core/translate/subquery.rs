@@ -1,10 +1,10 @@
 use std::sync::Arc;
 
-use turso_parser::ast::{self, SortOrder, SubqueryType};
+use turso_parser::ast::{self, SortOrder, SubqueryType, TableInternalId};
 
 use crate::{
     emit_explain,
-    schema::{Index, IndexColumn, Table},
+    schema::{BTreeTable, FromClauseSubquery, Index, IndexColumn, Table},
     translate::{
         collate::get_collseq_from_expr,
         emitter::emit_program_for_select,
@@ -18,7 +18,8 @@ use crate::{
     },
     vdbe::{
         builder::{CursorType, ProgramBuilder},
-        insn::Insn,
+        insn::{to_u16, InsertFlags, Insn},
+        BranchOffset, CursorID,
     },
     Connection, QueryMode, Result,
 };
@@ -26,7 +27,7 @@ use crate::{
 use super::{
     emitter::{emit_query, Resolver, TranslateCtx},
     main_loop::LoopLabels,
-    plan::{Operation, QueryDestination, Scan, Search, SelectPlan, TableReferences},
+    plan::{Operation, QueryDestination, Scan, Search, SeekDef, SelectPlan, TableReferences},
 };
 
 // Compute query plans for subqueries occurring in any position other than the FROM clause.
@@ -182,6 +183,52 @@ fn plan_subqueries_with_outer_query_access<'a>(
     Ok(())
 }
 
+/// Rewrite `x IN table` and `x IN tvf(args)` as `x IN (SELECT * FROM table)` and
+/// `x IN (SELECT * FROM tvf(args))`, so that they are planned like any other IN subquery.
+fn rewrite_in_table(expr: &mut ast::Expr) {
+    if !matches!(expr, ast::Expr::InTable { .. }) {
+        return;
+    }
+    let ast::Expr::InTable {
+        lhs,
+        not,
+        rhs,
+        args,
+    } = std::mem::replace(expr, ast::Expr::Literal(ast::Literal::Null))
+    else {
+        unreachable!();
+    };
+    let table = if args.is_empty() {
+        ast::SelectTable::Table(rhs, None, None)
+    } else {
+        ast::SelectTable::TableCall(rhs, args, None)
+    };
+    let select = ast::Select {
+        with: None,
+        body: ast::SelectBody {
+            select: ast::OneSelect::Select {
+                distinctness: None,
+                columns: vec![ast::ResultColumn::Star],
+                from: Some(ast::FromClause {
+                    select: Box::new(table),
+                    joins: vec![],
+                }),
+                where_clause: None,
+                group_by: None,
+                window_clause: vec![],
+            },
+            compounds: vec![],
+        },
+        order_by: vec![],
+        limit: None,
+    };
+    *expr = ast::Expr::InSelect {
+        lhs,
+        not,
+        rhs: select,
+    };
+}
+
 /// Create a closure that will walk the AST and replace subqueries with [ast::Expr::SubqueryResult] expressions.
 fn get_subquery_parser<'a>(
     program: &'a mut ProgramBuilder,
@@ -203,6 +250,7 @@ fn get_subquery_parser<'a>(
     }
 
     move |expr: &mut ast::Expr| -> Result<WalkControl> {
+        rewrite_in_table(expr);
         match expr {
             ast::Expr::Exists(_) => {
                 let subquery_id = program.table_reference_counter.next();
@@ -233,7 +281,11 @@ fn get_subquery_parser<'a>(
                         "compound SELECT queries not supported yet in WHERE clause subqueries"
                     );
                 };
-                optimize_select_plan(&mut plan, resolver.schema)?;
+                optimize_select_plan(
+                    &mut plan,
+                    resolver.schema,
+                    connection.automatic_index_enabled(),
+                )?;
                 // EXISTS subqueries are satisfied after at most 1 row has been returned.
                 plan.limit = Some(Box::new(ast::Expr::Literal(ast::Literal::Numeric(
                     "1".to_string(),
@@ -281,7 +333,11 @@ fn get_subquery_parser<'a>(
                         "compound SELECT queries not supported yet in WHERE clause subqueries"
                     );
                 };
-                optimize_select_plan(&mut plan, resolver.schema)?;
+                optimize_select_plan(
+                    &mut plan,
+                    resolver.schema,
+                    connection.automatic_index_enabled(),
+                )?;
                 let reg_count = plan.result_columns.len();
                 let reg_start = program.alloc_registers(reg_count);
 
@@ -349,7 +405,11 @@ fn get_subquery_parser<'a>(
                         "compound SELECT queries not supported yet in WHERE clause subqueries"
                     );
                 };
-                optimize_select_plan(&mut plan, resolver.schema)?;
+                optimize_select_plan(
+                    &mut plan,
+                    resolver.schema,
+                    connection.automatic_index_enabled(),
+                )?;
                 // e.g. (x,y) IN (SELECT ...)
                 // or x IN (SELECT ...)
                 let lhs_column_count = match unwrap_parens(lhs.as_ref())? {
@@ -481,7 +541,16 @@ pub fn emit_from_clause_subqueries(
         emit_explain!(program, false, "SCAN CONSTANT ROW".to_owned());
     }
 
+    // The ephemeral tables of the materialized CTEs, so that the CTE is computed only once
+    // even if it's referenced more than once.
+    let mut materialized_ctes: Vec<(TableInternalId, CursorID)> = vec![];
+
     for table_reference in tables.joined_tables_mut() {
+        let is_primary_key_index = |index: &Index| {
+            table_reference
+                .btree()
+                .is_some_and(|btree| index.is_without_rowid_primary_key(&btree))
+        };
         emit_explain!(
             program,
             true,
@@ -501,7 +570,9 @@ pub fn emit_from_clause_subqueries(
                     match scan {
                         Scan::BTreeTable { index, .. } => {
                             if let Some(index) = index {
-                                if table_reference.utilizes_covering_index() {
+                                if is_primary_key_index(index) {
+                                    format!("SCAN {table_name}")
+                                } else if table_reference.utilizes_covering_index() {
                                     format!("SCAN {table_name} USING COVERING INDEX {}", index.name)
                                 } else {
                                     format!("SCAN {table_name} USING INDEX {}", index.name)
@@ -522,6 +593,26 @@ pub fn emit_from_clause_subqueries(
                             table_reference.identifier
                         )
                     }
+                    Search::Seek {
+                        index: Some(index),
+                        seek_def,
+                    } if index.ephemeral => {
+                        format!(
+                            "SEARCH {} USING AUTOMATIC COVERING INDEX{}",
+                            table_reference.identifier,
+                            seek_key_detail(index, seek_def)
+                        )
+                    }
+                    Search::Seek {
+                        index: Some(index),
+                        seek_def,
+                    } if is_primary_key_index(index) => {
+                        format!(
+                            "SEARCH {} USING PRIMARY KEY{}",
+                            table_reference.identifier,
+                            seek_key_detail(index, seek_def)
+                        )
+                    }
                     Search::Seek {
                         index: Some(index), ..
                     } => {
@@ -539,13 +630,27 @@ pub fn emit_from_clause_subqueries(
                     )
                 }
                 Operation::HashJoin(_) => "HASH JOIN".to_string(),
+                Operation::MultiIndexScan(multi) => format!(
+                    "MULTI-INDEX OR {} ({})",
+                    table_reference.identifier,
+                    multi.branch_descriptions()
+                ),
             }
         );
 
         if let Table::FromClauseSubquery(from_clause_subquery) = &mut table_reference.table {
             // Emit the subquery and get the start register of the result columns.
             let result_columns_start =
-                emit_from_clause_subquery(program, &mut from_clause_subquery.plan, t_ctx)?;
+                if from_clause_subquery.materialized == ast::Materialized::Yes {
+                    emit_materialized_from_clause_subquery(
+                        program,
+                        from_clause_subquery,
+                        t_ctx,
+                        &mut materialized_ctes,
+                    )?
+                } else {
+                    emit_from_clause_subquery(program, &mut from_clause_subquery.plan, t_ctx)?
+                };
             // Set the start register of the subquery's result columns.
             // This is done so that translate_expr() can read the result columns of the subquery,
             // as if it were reading from a regular table.
@@ -557,6 +662,22 @@ pub fn emit_from_clause_subqueries(
     Ok(())
 }
 
+/// Describes the equality prefix of a seek as e.g. ` (a=? AND b=?)`, or nothing if the seek has
+/// no equality prefix.
+fn seek_key_detail(index: &Index, seek_def: &SeekDef) -> String {
+    if seek_def.prefix.is_empty() {
+        return String::new();
+    }
+    let key = index
+        .columns
+        .iter()
+        .take(seek_def.prefix.len())
+        .map(|col| format!("{}=?", col.name))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    format!(" ({key})")
+}
+
 /// Emit a FROM clause subquery and return the start register of the result columns.
 /// This is done by emitting a coroutine that stores the result columns in sequential registers.
 /// Each FROM clause subquery has its own separate SelectPlan which is wrapped in a coroutine.
@@ -624,6 +745,102 @@ pub fn emit_from_clause_subquery(
     Ok(result_column_start_reg)
 }
 
+/// Emit a FROM clause subquery that is computed once into an ephemeral table, and return the
+/// start register of the result columns.
+///
+/// The subquery is emitted as a coroutine like in [emit_from_clause_subquery], which is run to
+/// completion right away to store its rows in the ephemeral table. The main query loop then
+/// scans the ephemeral table, reading each row into the result column registers, instead of
+/// running the subquery again every time it needs the rows.
+///
+/// Further references to the same CTE open another cursor on the same ephemeral table.
+fn emit_materialized_from_clause_subquery(
+    program: &mut ProgramBuilder,
+    subquery: &mut FromClauseSubquery,
+    t_ctx: &mut TranslateCtx,
+    materialized_ctes: &mut Vec<(TableInternalId, CursorID)>,
+) -> Result<usize> {
+    let table = Arc::new(BTreeTable {
+        root_page: 0,
+        name: subquery.name.clone(),
+        has_rowid: true,
+        primary_key_columns: vec![],
+        columns: subquery.columns.clone(),
+        is_strict: false,
+        unique_sets: vec![],
+        has_autoincrement: false,
+        foreign_keys: vec![],
+    });
+    let cursor_id = program.alloc_cursor_id(CursorType::BTreeTable(table.clone()));
+    subquery.materialized_cursor_id = Some(cursor_id);
+
+    if let Some((_, original_cursor_id)) = materialized_ctes
+        .iter()
+        .find(|(cte_id, _)| Some(*cte_id) == subquery.cte_id)
+    {
+        program.emit_insn(Insn::OpenDup {
+            new_cursor_id: cursor_id,
+            original_cursor_id: *original_cursor_id,
+        });
+        return Ok(program.alloc_registers(subquery.columns.len()));
+    }
+
+    let result_columns_start = emit_from_clause_subquery(program, &mut subquery.plan, t_ctx)?;
+    let QueryDestination::CoroutineYield {
+        yield_reg,
+        coroutine_implementation_start,
+    } = subquery.plan.query_destination
+    else {
+        unreachable!("Subquery table with non-subquery query type");
+    };
+    program.emit_insn(Insn::OpenEphemeral {
+        cursor_id,
+        is_table: true,
+    });
+    program.emit_insn(Insn::InitCoroutine {
+        yield_reg,
+        jump_on_definition: BranchOffset::Offset(0),
+        start_offset: coroutine_implementation_start,
+    });
+    let loop_start = program.allocate_label();
+    let loop_end = program.allocate_label();
+    program.preassign_label_to_next_insn(loop_start);
+    program.emit_insn(Insn::Yield {
+        yield_reg,
+        end_offset: loop_end,
+    });
+    let record_reg = program.alloc_register();
+    let rowid_reg = program.alloc_register();
+    program.emit_insn(Insn::MakeRecord {
+        start_reg: to_u16(result_columns_start),
+        count: to_u16(subquery.columns.len()),
+        dest_reg: to_u16(record_reg),
+        index_name: None,
+        affinity_str: None,
+    });
+    program.emit_insn(Insn::NewRowid {
+        cursor: cursor_id,
+        rowid_reg,
+        prev_largest_reg: 0,
+    });
+    program.emit_insn(Insn::Insert {
+        cursor: cursor_id,
+        key_reg: rowid_reg,
+        record_reg,
+        flag: InsertFlags::new(),
+        table_name: table.name.clone(),
+    });
+    program.emit_insn(Insn::Goto {
+        target_pc: loop_start,
+    });
+    program.preassign_label_to_next_insn(loop_end);
+
+    if let Some(cte_id) = subquery.cte_id {
+        materialized_ctes.push((cte_id, cursor_id));
+    }
+    Ok(result_columns_start)
+}
+
 /// Translate a subquery that is not part of the FROM clause.
 /// If a subquery is uncorrelated (i.e. does not reference columns from the outer query),
 /// it will be executed only once.
@@ -149,7 +149,7 @@ pub fn translate_create_trigger(
     let sqlite_schema_cursor_id = program.alloc_cursor_id(CursorType::BTreeTable(table.clone()));
     program.emit_insn(Insn::OpenWrite {
         cursor_id: sqlite_schema_cursor_id,
-        root_page: 1i64.into(),
+        root_page: table.root_page.into(),
         db: 0,
     });
 
@@ -176,7 +176,7 @@ pub fn translate_create_trigger(
 
     // Parse schema to load the new trigger
     program.emit_insn(Insn::ParseSchema {
-        db: sqlite_schema_cursor_id,
+        db: 0,
         where_clause: Some(format!("name = '{normalized_trigger_name}'")),
     });
 
@@ -222,7 +222,7 @@ pub fn translate_drop_trigger(
     let sqlite_schema_cursor_id = program.alloc_cursor_id(CursorType::BTreeTable(table.clone()));
     program.emit_insn(Insn::OpenWrite {
         cursor_id: sqlite_schema_cursor_id,
-        root_page: 1i64.into(),
+        root_page: table.root_page.into(),
         db: 0,
     });
 
@@ -2,6 +2,7 @@ use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use crate::schema::ROWID_SENTINEL;
+use crate::translate::delete::{mark_without_rowid_columns_used, validate_without_rowid_write};
 use crate::translate::emitter::Resolver;
 use crate::translate::expr::{bind_and_rewrite_expr, walk_expr, BindingBehavior, WalkControl};
 use crate::translate::plan::{Operation, Scan};
@@ -13,7 +14,7 @@ use crate::{
     vdbe::builder::{ProgramBuilder, ProgramBuilderOpts},
     Connection,
 };
-use turso_parser::ast::{self, Expr, Indexed, SortOrder};
+use turso_parser::ast::{self, Expr, Indexed, SortOrder, TriggerEvent};
 
 use super::emitter::emit_program;
 use super::expr::process_returning_clause;
@@ -58,7 +59,12 @@ pub fn translate_update(
     connection: &Arc<crate::Connection>,
 ) -> crate::Result<ProgramBuilder> {
     let mut plan = prepare_update_plan(&mut program, resolver.schema, body, connection, false)?;
-    optimize_plan(&mut program, &mut plan, resolver.schema)?;
+    optimize_plan(
+        &mut program,
+        &mut plan,
+        resolver.schema,
+        connection.automatic_index_enabled(),
+    )?;
     let opts = ProgramBuilderOpts {
         num_cursors: 1,
         approx_num_insns: 20,
@@ -85,7 +91,12 @@ pub fn translate_update_for_schema_change(
         }
     }
 
-    optimize_plan(&mut program, &mut plan, resolver.schema)?;
+    optimize_plan(
+        &mut program,
+        &mut plan,
+        resolver.schema,
+        connection.automatic_index_enabled(),
+    )?;
     let opts = ProgramBuilderOpts {
         num_cursors: 1,
         approx_num_insns: 20,
@@ -154,6 +165,7 @@ pub fn prepare_update_plan(
     connection: &Arc<crate::Connection>,
     is_internal_schema_change: bool,
 ) -> crate::Result<Plan> {
+    let database_id = connection.resolve_database_id(&body.tbl_name)?;
     let table_name = &body.tbl_name.name;
     let table = match schema.get_table(table_name.as_str()) {
         Some(table) => table,
@@ -165,6 +177,17 @@ pub fn prepare_update_plan(
         table_name.as_str(),
         is_internal_schema_change,
     )?;
+    if let Some(btree) = table.btree().filter(|btree| !btree.has_rowid) {
+        validate_without_rowid_write(program, schema, &btree, TriggerEvent::Update, connection)?;
+        if schema
+            .get_indices(&btree.name)
+            .any(|index| index.unique && !index.is_without_rowid_primary_key(&btree))
+        {
+            bail_parse_error!(
+                "UPDATE of WITHOUT ROWID table with UNIQUE constraints is not supported"
+            );
+        }
+    }
     let table_name = table.get_name();
     let iter_dir = body
         .order_by
@@ -193,7 +216,7 @@ pub fn prepare_update_plan(
         col_used_mask: ColumnUsedMask::default(),
         column_use_counts: Vec::new(),
         expression_index_usages: Vec::new(),
-        database_id: 0,
+        database_id,
     }];
     let mut table_references = TableReferences::new(joined_tables, vec![]);
 
@@ -237,7 +260,9 @@ pub fn prepare_update_plan(
                 Some(idx) => *idx,
                 None => {
                     // Check if this is the 'rowid' keyword
-                    if ROWID_STRS.iter().any(|s| s.eq_ignore_ascii_case(&ident)) {
+                    if ROWID_STRS.iter().any(|s| s.eq_ignore_ascii_case(&ident))
+                        && table.btree().is_none_or(|btree| btree.has_rowid)
+                    {
                         // Find the rowid alias column if it exists
                         if let Some((idx, _col)) = table
                             .columns()
@@ -273,6 +298,7 @@ pub fn prepare_update_plan(
 
     let result_columns =
         process_returning_clause(&mut body.returning, &mut table_references, connection)?;
+    mark_without_rowid_columns_used(&mut table_references);
 
     let order_by = body
         .order_by
@@ -4,14 +4,16 @@ use std::{collections::HashMap, sync::Arc};
 
 use turso_parser::ast::{self, TriggerEvent, TriggerTime, Upsert};
 
-use crate::error::SQLITE_CONSTRAINT_PRIMARYKEY;
+use crate::error::{SQLITE_CONSTRAINT_PRIMARYKEY, SQLITE_CONSTRAINT_UNIQUE};
 use crate::schema::{IndexColumn, ROWID_SENTINEL};
 use crate::translate::emitter::UpdateRowSource;
 use crate::translate::expr::{walk_expr, WalkControl};
 use crate::translate::fkeys::{
     emit_fk_child_update_counters, emit_parent_key_change_checks, fire_fk_update_actions,
 };
-use crate::translate::insert::{format_unique_violation_desc, InsertEmitCtx};
+use crate::translate::insert::{
+    format_key_violation_desc, format_unique_violation_desc, InsertEmitCtx,
+};
 use crate::translate::planner::ROWID_STRS;
 use crate::translate::trigger_exec::{
     fire_trigger, get_relevant_triggers_type_and_time, TriggerContext,
@@ -24,7 +26,8 @@ use crate::{
     schema::{Index, Schema, Table},
     translate::{
         emitter::{
-            emit_cdc_full_record, emit_cdc_insns, emit_cdc_patch_record, OperationMode, Resolver,
+            emit_cdc_full_record, emit_cdc_insns, emit_cdc_patch_record,
+            emit_without_rowid_index_entry, OperationMode, Resolver,
         },
         expr::{
             emit_returning_results, translate_expr, translate_expr_no_constant_opt, walk_expr_mut,
@@ -247,16 +250,18 @@ pub fn upsert_matches_index(upsert: &Upsert, index: &Index, table: &Table) -> bo
     let Some(target) = upsert.index.as_ref() else {
         return true;
     };
+    let key_columns = match table.btree() {
+        Some(btree) => index.key_columns(&btree),
+        None => &index.columns,
+    };
     // must be a non-partial UNIQUE index with identical arity
-    if !index.unique || index.where_clause.is_some() || target.targets.len() != index.columns.len()
-    {
+    if !index.unique || index.where_clause.is_some() || target.targets.len() != key_columns.len() {
         return false;
     }
 
     // Build a multiset of index columns: (normalized name, effective collation)
     // effective collation = index collation if set, else table column default, else "binary"
-    let mut idx_cols: Vec<(String, String)> = index
-        .columns
+    let mut idx_cols: Vec<(String, String)> = key_columns
         .iter()
         .map(|ic| {
             (
@@ -1069,6 +1074,222 @@ pub fn emit_upsert(
     Ok(())
 }
 
+/// Emit the bytecode of the `DO UPDATE` arm of an UPSERT on a WITHOUT ROWID table.
+///
+/// The conflicting row is looked up in the primary key index by the key left in
+/// `ctx.conflict_key_reg`, removed from every index and written back with its new values,
+/// which may move it to a new primary key.
+#[allow(clippy::too_many_arguments)]
+pub fn emit_without_rowid_upsert(
+    program: &mut ProgramBuilder,
+    table: &Table,
+    ctx: &InsertEmitCtx,
+    insertion: &Insertion,
+    set_pairs: &mut [(usize, Box<ast::Expr>)],
+    where_clause: &mut Option<Box<ast::Expr>>,
+    resolver: &mut Resolver,
+    returning: &mut [ResultSetColumn],
+    table_references: &mut TableReferences,
+) -> crate::Result<()> {
+    let pk_index = ctx
+        .without_rowid_pk_index
+        .as_ref()
+        .expect("WITHOUT ROWID table has a primary key index");
+    let cursor_of = |name: &str| {
+        ctx.idx_cursors
+            .iter()
+            .find(|(idx_name, _, _)| idx_name == name)
+            .map(|(_, _, c_id)| *c_id)
+            .expect("no cursor found for index")
+    };
+    let pk_cursor_id = cursor_of(&pk_index.name);
+    let key_columns = pk_index.key_columns(ctx.table);
+
+    // Seek & snapshot CURRENT
+    program.emit_insn(Insn::NotFound {
+        cursor_id: pk_cursor_id,
+        target_pc: ctx.loop_labels.row_done,
+        record_reg: ctx.conflict_key_reg,
+        num_regs: key_columns.len(),
+    });
+    let num_cols = ctx.table.columns.len();
+    let current_start = program.alloc_registers(num_cols);
+    for i in 0..num_cols {
+        let pos_in_index = pk_index
+            .column_table_pos_to_index_pos(i)
+            .expect("primary key index stores every column of the table");
+        program.emit_column_or_rowid(pk_cursor_id, pos_in_index, current_start + i);
+    }
+    // There is no rowid to refer to.
+    let rowid_reg = program.alloc_register();
+    program.emit_null(rowid_reg, None);
+
+    // NEW = CURRENT, then apply SET
+    let new_start = program.alloc_registers(num_cols);
+    program.emit_insn(Insn::Copy {
+        src_reg: current_start,
+        dst_reg: new_start,
+        extra_amount: num_cols - 1,
+    });
+
+    // WHERE on target row
+    if let Some(pred) = where_clause.as_mut() {
+        rewrite_expr_to_registers(
+            pred,
+            table,
+            current_start,
+            rowid_reg,
+            Some(table.get_name()),
+            Some(insertion),
+            true,
+        )?;
+        let pr = program.alloc_register();
+        translate_expr(program, None, pred, pr, resolver)?;
+        program.emit_insn(Insn::IfNot {
+            reg: pr,
+            target_pc: ctx.loop_labels.row_done,
+            jump_if_null: true,
+        });
+    }
+
+    for (col_idx, expr) in set_pairs.iter_mut() {
+        rewrite_expr_to_registers(
+            expr,
+            table,
+            current_start,
+            rowid_reg,
+            Some(table.get_name()),
+            Some(insertion),
+            true,
+        )?;
+        translate_expr_no_constant_opt(
+            program,
+            None,
+            expr,
+            new_start + *col_idx,
+            resolver,
+            NoConstantOptReason::RegisterReuse,
+        )?;
+        let col = &ctx.table.columns[*col_idx];
+        if col.notnull() {
+            program.emit_insn(Insn::HaltIfNull {
+                target_reg: new_start + *col_idx,
+                err_code: SQLITE_CONSTRAINT_NOTNULL,
+                description: format!(
+                    "{}.{}",
+                    ctx.table.name,
+                    col.name.as_ref().expect("Column name must be present")
+                ),
+            });
+        }
+    }
+
+    if ctx.table.is_strict {
+        program.emit_insn(Insn::TypeCheck {
+            start_reg: new_start,
+            count: num_cols,
+            check_generated: true,
+            table_reference: Arc::clone(ctx.table),
+        });
+    } else if let Some(count) = NonZeroUsize::new(num_cols) {
+        program.emit_insn(Insn::Affinity {
+            start_reg: new_start,
+            count,
+            affinities: ctx
+                .table
+                .columns
+                .iter()
+                .map(|c| c.affinity().aff_mask())
+                .collect(),
+        });
+    }
+
+    // Remove the old row from every index. IdxInsert does not count changes, so the row is
+    // counted as changed by the Delete of its primary key entry.
+    let secondary_indexes = resolver
+        .schema
+        .get_indices(table.get_name())
+        .filter(|index| !index.is_without_rowid_primary_key(ctx.table))
+        .cloned()
+        .collect::<Vec<_>>();
+    for index in &secondary_indexes {
+        let start_reg = emit_without_rowid_index_entry(program, &index.columns, current_start);
+        program.emit_insn(Insn::IdxDelete {
+            start_reg,
+            num_regs: index.columns.len(),
+            cursor_id: cursor_of(&index.name),
+            raise_error_if_no_matching_entry: true,
+        });
+    }
+    program.emit_insn(Insn::Delete {
+        cursor_id: pk_cursor_id,
+        table_name: ctx.table.name.clone(),
+        is_part_of_update: false,
+    });
+
+    // The new values may belong to another row.
+    let mut entries = vec![(pk_index, pk_cursor_id)];
+    entries.extend(
+        secondary_indexes
+            .iter()
+            .map(|index| (index, cursor_of(&index.name))),
+    );
+    for (index, cursor_id) in entries {
+        let start_reg = emit_without_rowid_index_entry(program, &index.columns, new_start);
+        if index.unique {
+            let index_key_columns = index.key_columns(ctx.table);
+            let no_conflict = program.allocate_label();
+            program.emit_insn(Insn::NoConflict {
+                cursor_id,
+                target_pc: no_conflict,
+                record_reg: start_reg,
+                num_regs: index_key_columns.len(),
+            });
+            program.emit_insn(Insn::Halt {
+                err_code: if index.is_without_rowid_primary_key(ctx.table) {
+                    SQLITE_CONSTRAINT_PRIMARYKEY
+                } else {
+                    SQLITE_CONSTRAINT_UNIQUE
+                },
+                description: format_key_violation_desc(table.get_name(), index_key_columns),
+            });
+            program.preassign_label_to_next_insn(no_conflict);
+        }
+        let record_reg = program.alloc_register();
+        program.emit_insn(Insn::MakeRecord {
+            start_reg: to_u16(start_reg),
+            count: to_u16(index.columns.len()),
+            dest_reg: to_u16(record_reg),
+            index_name: Some(index.name.clone()),
+            affinity_str: None,
+        });
+        program.emit_insn(Insn::IdxInsert {
+            cursor_id,
+            record_reg,
+            unpacked_start: Some(start_reg),
+            unpacked_count: Some(index.columns.len() as u16),
+            flags: IdxInsertFlags::new().nchange(true),
+        });
+    }
+
+    // RETURNING from NEW image
+    if !returning.is_empty() {
+        emit_returning_results(
+            program,
+            table_references,
+            returning,
+            new_start,
+            rowid_reg,
+            resolver,
+        )?;
+    }
+
+    program.emit_insn(Insn::Goto {
+        target_pc: ctx.loop_labels.row_done,
+    });
+    Ok(())
+}
+
 /// Normalize the `SET` clause into `(column_index, Expr)` pairs using table layout.
 ///
 /// Supports multi-target row-value SETs: `SET (a, b) = (expr1, expr2)`.
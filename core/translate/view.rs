@@ -127,7 +127,7 @@ pub fn translate_create_materialized_view(
     let sqlite_schema_cursor_id = program.alloc_cursor_id(CursorType::BTreeTable(table.clone()));
     program.emit_insn(Insn::OpenWrite {
         cursor_id: sqlite_schema_cursor_id,
-        root_page: 1i64.into(),
+        root_page: table.root_page.into(),
         db: 0,
     });
 
@@ -207,7 +207,7 @@ pub fn translate_create_materialized_view(
 
     // Parse schema to load the new view and DBSP state table
     program.emit_insn(Insn::ParseSchema {
-        db: sqlite_schema_cursor_id,
+        db: 0,
         where_clause: Some(format!(
             "name = '{normalized_view_name}' OR name = '{dbsp_table_name}' OR name = '{dbsp_index_name}'"
         )),
@@ -275,7 +275,7 @@ pub fn translate_create_view(
     let sqlite_schema_cursor_id = program.alloc_cursor_id(CursorType::BTreeTable(table.clone()));
     program.emit_insn(Insn::OpenWrite {
         cursor_id: sqlite_schema_cursor_id,
-        root_page: 1i64.into(),
+        root_page: table.root_page.into(),
         db: 0,
     });
 
@@ -294,7 +294,7 @@ pub fn translate_create_view(
 
     // Parse schema to load the new view
     program.emit_insn(Insn::ParseSchema {
-        db: sqlite_schema_cursor_id,
+        db: 0,
         where_clause: Some(format!("name = '{normalized_view_name}'")),
     });
 
@@ -345,7 +345,7 @@ pub fn translate_drop_view(
                 program.emit_insn(Insn::Destroy {
                     root: btree_table.root_page,
                     former_root_reg: 0, // No autovacuum
-                    is_temp: 0,
+                    db: 0,
                 });
             }
         }
@@ -367,7 +367,7 @@ pub fn translate_drop_view(
             program.emit_insn(Insn::Destroy {
                 root: index.root_page,
                 former_root_reg: 0, // No autovacuum
-                is_temp: 0,
+                db: 0,
             });
         }
 
@@ -377,7 +377,7 @@ pub fn translate_drop_view(
                 program.emit_insn(Insn::Destroy {
                     root: dbsp_btree_table.root_page,
                     former_root_reg: 0, // No autovacuum
-                    is_temp: 0,
+                    db: 0,
                 });
             }
         }
@@ -389,7 +389,7 @@ pub fn translate_drop_view(
         program.alloc_cursor_id(CursorType::BTreeTable(schema_table.clone()));
     program.emit_insn(Insn::OpenWrite {
         cursor_id: sqlite_schema_cursor_id,
-        root_page: 1i64.into(),
+        root_page: schema_table.root_page.into(),
         db: 0,
     });
 
@@ -211,6 +211,7 @@ fn prepare_window_subquery(
         values: vec![],
         window: None,
         non_from_clause_subqueries: vec![],
+        min_max_from_first_row: false,
     };
 
     prepare_window_subquery(
@@ -24,6 +24,7 @@ use std::cell::UnsafeCell;
 use std::fmt::{Debug, Display};
 use std::iter::Peekable;
 use std::ops::Deref;
+use std::sync::Arc;
 use std::task::Waker;
 
 /// SQLite by default uses 2000 as maximum numbers in a row.
@@ -63,10 +64,172 @@ pub enum TextSubtype {
     Json,
 }
 
+/// The longest string that [TextBuf] stores without a heap allocation.
+const INLINE_TEXT_CAPACITY: usize = 22;
+
+/// The bytes of a [Text] value.
+///
+/// Most text values read during a scan are short, e.g. names, codes or dates, so they are
+/// stored inline instead of in a separate allocation. Longer strings are kept in a shared
+/// buffer, so that copying a value between registers, into a sorter or into a result row
+/// doesn't copy the string; the buffer is only copied if a shared value is modified.
+#[derive(Clone)]
+pub struct TextBuf(TextRepr);
+
+/// Kept private so that the only way to make an inline string is to copy it from a `str`,
+/// which [TextBuf::as_str] relies on.
+#[derive(Clone)]
+enum TextRepr {
+    Static(&'static str),
+    Inline {
+        len: u8,
+        bytes: [u8; INLINE_TEXT_CAPACITY],
+    },
+    Shared(Arc<String>),
+}
+
+impl TextBuf {
+    pub const fn new() -> Self {
+        Self(TextRepr::Static(""))
+    }
+
+    /// Copies `value`, which is only allocated on the heap if it doesn't fit inline.
+    pub fn copy_from(value: &str) -> Self {
+        Self::inline(value).unwrap_or_else(|| Self(TextRepr::Shared(Arc::new(value.to_string()))))
+    }
+
+    fn inline(value: &str) -> Option<Self> {
+        if value.len() > INLINE_TEXT_CAPACITY {
+            return None;
+        }
+        let mut bytes = [0; INLINE_TEXT_CAPACITY];
+        bytes[..value.len()].copy_from_slice(value.as_bytes());
+        Some(Self(TextRepr::Inline {
+            len: value.len() as u8,
+            bytes,
+        }))
+    }
+
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            TextRepr::Static(value) => value,
+            // SAFETY: the inline bytes are always copied from a `str`.
+            TextRepr::Inline { len, bytes } => unsafe {
+                std::str::from_utf8_unchecked(&bytes[..*len as usize])
+            },
+            TextRepr::Shared(value) => value,
+        }
+    }
+
+    /// Returns the string for modification, copying it first if it isn't owned by this value.
+    pub fn to_mut(&mut self) -> &mut String {
+        if !matches!(self.0, TextRepr::Shared(_)) {
+            self.0 = TextRepr::Shared(Arc::new(self.as_str().to_string()));
+        }
+        let TextRepr::Shared(value) = &mut self.0 else {
+            unreachable!()
+        };
+        Arc::make_mut(value)
+    }
+
+    pub fn into_owned(self) -> String {
+        match self.0 {
+            TextRepr::Shared(value) => Arc::unwrap_or_clone(value),
+            _ => self.as_str().to_string(),
+        }
+    }
+
+    /// Replaces the string with `value`, reusing the buffer when it is owned by this value.
+    pub fn set(&mut self, value: &str) {
+        if let TextRepr::Shared(buf) = &mut self.0 {
+            if let Some(buf) = Arc::get_mut(buf) {
+                buf.clear();
+                buf.push_str(value);
+                return;
+            }
+        }
+        *self = Self::copy_from(value);
+    }
+}
+
+impl Default for TextBuf {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for TextBuf {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for TextBuf {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for TextBuf {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Debug for TextBuf {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl Display for TextBuf {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self.as_str(), f)
+    }
+}
+
+impl From<&'static str> for TextBuf {
+    fn from(value: &'static str) -> Self {
+        Self(TextRepr::Static(value))
+    }
+}
+
+impl From<String> for TextBuf {
+    fn from(value: String) -> Self {
+        Self::inline(&value).unwrap_or_else(|| Self(TextRepr::Shared(Arc::new(value))))
+    }
+}
+
+impl From<Cow<'static, str>> for TextBuf {
+    fn from(value: Cow<'static, str>) -> Self {
+        match value {
+            Cow::Borrowed(value) => value.into(),
+            Cow::Owned(value) => value.into(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for TextBuf {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for TextBuf {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Text {
-    pub value: Cow<'static, str>,
+    pub value: TextBuf,
     pub subtype: TextSubtype,
 }
 
@@ -79,7 +242,7 @@ impl Display for Text {
 impl Text {
     pub fn new(value: impl Into<Cow<'static, str>>) -> Self {
         Self {
-            value: value.into(),
+            value: TextBuf::from(value.into()),
             subtype: TextSubtype::Text,
         }
     }
@@ -136,9 +299,7 @@ pub trait Extendable<T> {
 impl<T: AnyText> Extendable<T> for Text {
     #[inline(always)]
     fn do_extend(&mut self, other: &T) {
-        let value = self.value.to_mut();
-        value.clear();
-        value.push_str(other.as_ref());
+        self.value.set(other.as_ref());
         self.subtype = other.subtype();
     }
 }
@@ -192,7 +353,7 @@ impl AsRef<str> for Text {
 impl From<&str> for Text {
     fn from(value: &str) -> Self {
         Text {
-            value: value.to_owned().into(),
+            value: TextBuf::copy_from(value),
             subtype: TextSubtype::Text,
         }
     }
@@ -201,7 +362,7 @@ impl From<&str> for Text {
 impl From<String> for Text {
     fn from(value: String) -> Self {
         Text {
-            value: Cow::from(value),
+            value: value.into(),
             subtype: TextSubtype::Text,
         }
     }
@@ -554,14 +715,20 @@ impl FromValue for Value {
 }
 impl Sealed for crate::Value {}
 
+fn invalid_conversion(val: &Value, to: &'static str) -> LimboError {
+    LimboError::InvalidConversion(val.value_type(), to)
+}
+
 macro_rules! impl_int_from_value {
-    ($ty:ty, $cast:expr) => {
+    ($ty:ty) => {
         impl FromValue for $ty {
             fn from_sql(val: Value) -> Result<Self> {
                 match val {
                     Value::Null => Err(LimboError::NullValue),
-                    Value::Integer(i) => Ok($cast(i)),
-                    _ => unreachable!("invalid value type"),
+                    Value::Integer(i) => {
+                        <$ty>::try_from(i).map_err(|_| invalid_conversion(&val, stringify!($ty)))
+                    }
+                    _ => Err(invalid_conversion(&val, stringify!($ty))),
                 }
             }
         }
@@ -570,17 +737,28 @@ macro_rules! impl_int_from_value {
     };
 }
 
-impl_int_from_value!(i32, |i| i as i32);
-impl_int_from_value!(u32, |i| i as u32);
-impl_int_from_value!(i64, |i| i);
-impl_int_from_value!(u64, |i| i as u64);
+impl_int_from_value!(i32);
+impl_int_from_value!(u32);
+impl_int_from_value!(u64);
+
+impl FromValue for i64 {
+    fn from_sql(val: Value) -> Result<Self> {
+        match val {
+            Value::Null => Err(LimboError::NullValue),
+            Value::Integer(i) => Ok(i),
+            _ => Err(invalid_conversion(&val, "i64")),
+        }
+    }
+}
+impl Sealed for i64 {}
 
 impl FromValue for f64 {
     fn from_sql(val: Value) -> Result<Self> {
         match val {
             Value::Null => Err(LimboError::NullValue),
             Value::Float(f) => Ok(f),
-            _ => unreachable!("invalid value type"),
+            Value::Integer(i) => Ok(i as f64),
+            _ => Err(invalid_conversion(&val, "f64")),
         }
     }
 }
@@ -591,7 +769,7 @@ impl FromValue for Vec<u8> {
         match val {
             Value::Null => Err(LimboError::NullValue),
             Value::Blob(blob) => Ok(blob),
-            _ => unreachable!("invalid value type"),
+            _ => Err(invalid_conversion(&val, "Vec<u8>")),
         }
     }
 }
@@ -602,7 +780,7 @@ impl<const N: usize> FromValue for [u8; N] {
         match val {
             Value::Null => Err(LimboError::NullValue),
             Value::Blob(blob) => blob.try_into().map_err(|_| LimboError::InvalidBlobSize(N)),
-            _ => unreachable!("invalid value type"),
+            _ => Err(invalid_conversion(&val, "[u8; N]")),
         }
     }
 }
@@ -613,7 +791,7 @@ impl FromValue for String {
         match val {
             Value::Null => Err(LimboError::NullValue),
             Value::Text(s) => Ok(s.to_string()),
-            _ => unreachable!("invalid value type"),
+            _ => Err(invalid_conversion(&val, "String")),
         }
     }
 }
@@ -628,7 +806,7 @@ impl FromValue for bool {
                 1 => Ok(true),
                 _ => Err(LimboError::InvalidColumnType),
             },
-            _ => unreachable!("invalid value type"),
+            _ => Err(invalid_conversion(&val, "bool")),
         }
     }
 }
@@ -1637,7 +1815,7 @@ impl<'a> ValueRef<'a> {
             ValueRef::Integer(i) => Value::Integer(*i),
             ValueRef::Float(f) => Value::Float(*f),
             ValueRef::Text(text) => Value::Text(Text {
-                value: text.value.to_string().into(),
+                value: TextBuf::copy_from(text.value),
                 subtype: text.subtype,
             }),
             ValueRef::Blob(b) => Value::Blob(b.to_vec()),
@@ -3714,4 +3892,88 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_from_value_conversions() {
+        assert_eq!(i64::from_sql(Value::Integer(-7)).unwrap(), -7);
+        assert_eq!(i32::from_sql(Value::Integer(42)).unwrap(), 42);
+        assert_eq!(f64::from_sql(Value::Float(1.5)).unwrap(), 1.5);
+        assert_eq!(f64::from_sql(Value::Integer(3)).unwrap(), 3.0);
+        assert_eq!(String::from_sql(Value::build_text("hi")).unwrap(), "hi");
+        assert_eq!(
+            Vec::<u8>::from_sql(Value::Blob(vec![1, 2])).unwrap(),
+            vec![1, 2]
+        );
+        assert_eq!(Option::<i64>::from_sql(Value::Null).unwrap(), None);
+        assert_eq!(Option::<i64>::from_sql(Value::Integer(1)).unwrap(), Some(1));
+        assert!(matches!(
+            i64::from_sql(Value::Null),
+            Err(LimboError::NullValue)
+        ));
+
+        // Mismatched types and out of range integers are reported instead of panicking.
+        assert!(matches!(
+            i64::from_sql(Value::build_text("1")),
+            Err(LimboError::InvalidConversion(ValueType::Text, "i64"))
+        ));
+        assert!(matches!(
+            i32::from_sql(Value::Integer(i64::MAX)),
+            Err(LimboError::InvalidConversion(ValueType::Integer, "i32"))
+        ));
+        assert!(matches!(
+            u64::from_sql(Value::Integer(-1)),
+            Err(LimboError::InvalidConversion(ValueType::Integer, "u64"))
+        ));
+        assert!(matches!(
+            String::from_sql(Value::Blob(vec![])),
+            Err(LimboError::InvalidConversion(ValueType::Blob, "String"))
+        ));
+        assert!(matches!(
+            Vec::<u8>::from_sql(Value::Float(1.0)),
+            Err(LimboError::InvalidConversion(ValueType::Float, "Vec<u8>"))
+        ));
+        assert!(matches!(
+            Option::<String>::from_sql(Value::Integer(1)),
+            Err(LimboError::InvalidConversion(ValueType::Integer, "String"))
+        ));
+    }
+
+    #[test]
+    fn test_text_buf() {
+        // A Value is no larger than before short strings were stored inline.
+        assert_eq!(std::mem::size_of::<Value>(), 32);
+
+        let short = TextBuf::copy_from("héllo");
+        assert!(matches!(short.0, TextRepr::Inline { .. }));
+        assert_eq!(short.as_str(), "héllo");
+        let long = TextBuf::from("x".repeat(INLINE_TEXT_CAPACITY + 1));
+        assert!(matches!(long.0, TextRepr::Shared(_)));
+        assert!(matches!(
+            TextBuf::from("x".repeat(INLINE_TEXT_CAPACITY)).0,
+            TextRepr::Inline { .. }
+        ));
+
+        // Clones of a long string share its buffer until one of them is modified.
+        let mut copy = long.clone();
+        let (TextRepr::Shared(a), TextRepr::Shared(b)) = (&long.0, &copy.0) else {
+            unreachable!()
+        };
+        assert!(Arc::ptr_eq(a, b));
+        copy.to_mut().push('y');
+        assert_eq!(long.len(), INLINE_TEXT_CAPACITY + 1);
+        assert_eq!(copy.len(), INLINE_TEXT_CAPACITY + 2);
+
+        // Setting a value reuses an unshared buffer, even for a short string.
+        let TextRepr::Shared(buf) = &copy.0 else {
+            unreachable!()
+        };
+        let ptr = buf.as_ptr();
+        copy.set("abc");
+        assert!(matches!(&copy.0, TextRepr::Shared(buf) if buf.as_ptr() == ptr));
+        assert_eq!(copy.as_str(), "abc");
+
+        let mut static_text = TextBuf::from("static");
+        static_text.set("changed");
+        assert_eq!(static_text.into_owned(), "changed");
+    }
 }
@@ -740,6 +740,10 @@ pub struct OpenOptions<'a> {
     pub cipher: Option<String>,
     // The encryption key in hex format
     pub hexkey: Option<String>,
+    /// offset=N opens the database stored at byte N of the file, see [crate::EmbeddedIO]
+    pub offset: Option<u64>,
+    /// length=N limits a database opened with `offset` to N bytes
+    pub length: Option<u64>,
 }
 
 pub const MEMORY_PATH: &str = ":memory:";
@@ -893,6 +897,8 @@ fn parse_query_params(query: &str, opts: &mut OpenOptions) -> Result<()> {
                 "vfs" => opts.vfs = Some(decoded_value),
                 "cipher" => opts.cipher = Some(decoded_value),
                 "hexkey" => opts.hexkey = Some(decoded_value),
+                "offset" => opts.offset = Some(parse_byte_count(key, &decoded_value)?),
+                "length" => opts.length = Some(parse_byte_count(key, &decoded_value)?),
                 _ => {}
             }
         }
@@ -900,6 +906,14 @@ fn parse_query_params(query: &str, opts: &mut OpenOptions) -> Result<()> {
     Ok(())
 }
 
+fn parse_byte_count(key: &str, value: &str) -> Result<u64> {
+    value.parse().map_err(|_| {
+        LimboError::InvalidArgument(format!(
+            "Invalid {key}: '{value}'. Expected a number of bytes"
+        ))
+    })
+}
+
 /// Decodes percent-encoded characters
 /// this function was adapted from the 'urlencoding' crate. MIT
 pub fn decode_percent(uri: &str) -> String {
@@ -1145,21 +1159,29 @@ pub fn cast_real_to_integer(float: f64) -> std::result::Result<i64, ()> {
 }
 
 // we don't need to verify the numeric literal here, as it is already verified by the parser
-pub fn parse_numeric_literal(text: &str) -> Result<Value> {
+pub fn parse_numeric_literal(literal: &str) -> Result<Value> {
     // a single extra underscore ("_") character can exist between any two digits
-    let text = if text.contains('_') {
-        std::borrow::Cow::Owned(text.replace('_', ""))
+    let text = if literal.contains('_') {
+        std::borrow::Cow::Owned(literal.replace('_', ""))
     } else {
-        std::borrow::Cow::Borrowed(text)
+        std::borrow::Cow::Borrowed(literal)
     };
 
+    // Hex literals are 64-bit two's complement integers, so 0xffffffffffffffff is -1, but
+    // they cannot have more than 64 bits, and the negation of 0x8000000000000000 overflows.
+    let parse_hex = |digits: &str| match u64::from_str_radix(digits, 16) {
+        Ok(value) => Ok(value as i64),
+        Err(e) if *e.kind() == std::num::IntErrorKind::PosOverflow => {
+            crate::bail_parse_error!("hex literal too big: {literal}")
+        }
+        Err(e) => Err(e.into()),
+    };
     if text.starts_with("0x") || text.starts_with("0X") {
-        let value = u64::from_str_radix(&text[2..], 16)? as i64;
-        return Ok(Value::Integer(value));
+        return Ok(Value::Integer(parse_hex(&text[2..])?));
     } else if text.starts_with("-0x") || text.starts_with("-0X") {
-        let value = u64::from_str_radix(&text[3..], 16)? as i64;
+        let value = parse_hex(&text[3..])?;
         if value == i64::MIN {
-            return Err(LimboError::IntegerOverflow);
+            crate::bail_parse_error!("hex literal too big: {literal}");
         }
         return Ok(Value::Integer(-value));
     }
@@ -1882,6 +1904,15 @@ pub mod tests {
         assert!(opts.immutable);
     }
 
+    #[test]
+    fn test_uri_with_offset_and_length() {
+        let opts = OpenOptions::parse("file:app.bin?offset=4096&length=8192").unwrap();
+        assert_eq!(opts.path, "app.bin");
+        assert_eq!(opts.offset, Some(4096));
+        assert_eq!(opts.length, Some(8192));
+        assert!(OpenOptions::parse("file:app.bin?offset=-1").is_err());
+    }
+
     #[test]
     fn test_uri_with_fragment() {
         let uri = "file:/home/user/db.sqlite#section1";
@@ -2492,7 +2523,13 @@ pub mod tests {
             Value::Integer(-4660)
         );
         // too big hex
-        assert!(parse_numeric_literal("-0x8000000000000000").is_err());
+        for literal in ["-0x8000000000000000", "0x10000000000000000"] {
+            let err = parse_numeric_literal(literal).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!("Parse error: hex literal too big: {literal}")
+            );
+        }
     }
 
     #[test]
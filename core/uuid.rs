@@ -1,88 +1,45 @@
 use crate::ext::register_scalar_function;
-use turso_ext::{scalar, ExtensionApi, ResultCode, Value, ValueType};
+use turso_ext::{scalar, ExtensionApi, Value, ValueType};
 
 pub fn register_extension(ext_api: &mut ExtensionApi) {
     // FIXME: Add macro magic to register functions automatically.
     unsafe {
-        register_scalar_function(ext_api.ctx, c"uuid4_str".as_ptr(), uuid4_str);
-        register_scalar_function(ext_api.ctx, c"gen_random_uuid".as_ptr(), uuid4_str);
-        register_scalar_function(ext_api.ctx, c"uuid4".as_ptr(), uuid4_blob);
-        register_scalar_function(ext_api.ctx, c"uuid7_str".as_ptr(), uuid7_str);
-        register_scalar_function(ext_api.ctx, c"uuid7".as_ptr(), uuid7);
         register_scalar_function(ext_api.ctx, c"uuid7_timestamp_ms".as_ptr(), uuid7_ts);
         register_scalar_function(ext_api.ctx, c"uuid_str".as_ptr(), uuid_str);
         register_scalar_function(ext_api.ctx, c"uuid_blob".as_ptr(), uuid_blob);
     }
 }
 
-#[scalar(name = "uuid4_str", alias = "gen_random_uuid")]
-fn uuid4_str(_args: &[Value]) -> Value {
-    let uuid = uuid::Uuid::new_v4().to_string();
-    Value::from_text(uuid)
+/// Builds a random (version 4) UUID from the bytes written by `fill_bytes`.
+///
+/// uuid4(), uuid4_str(), uuid7(), uuid7_str() and ulid() are built-in scalar functions rather
+/// than extension functions, because they take their randomness and time from the connection's
+/// IO.
+pub(crate) fn new_uuid4(fill_bytes: impl FnOnce(&mut [u8])) -> uuid::Uuid {
+    let mut bytes = [0u8; 16];
+    fill_bytes(&mut bytes);
+    uuid::Builder::from_random_bytes(bytes).into_uuid()
 }
 
-#[scalar(name = "uuid4")]
-fn uuid4_blob(_args: &[Value]) -> Value {
-    let uuid = uuid::Uuid::new_v4();
-    let bytes = uuid.as_bytes();
-    Value::from_blob(bytes.to_vec())
+/// Builds a time-ordered (version 7) UUID from a unix timestamp in milliseconds and 74 random
+/// bits taken from the bytes written by `fill_bytes`.
+pub(crate) fn new_uuid7(unix_ms: u64, fill_bytes: impl FnOnce(&mut [u8])) -> uuid::Uuid {
+    let mut bytes = [0u8; 10];
+    fill_bytes(&mut bytes);
+    uuid::Builder::from_unix_timestamp_millis(unix_ms, &bytes).into_uuid()
 }
 
-#[scalar(name = "uuid7_str")]
-fn uuid7_str(args: &[Value]) -> Value {
-    let timestamp = if args.is_empty() {
-        let ctx = uuid::ContextV7::new();
-        uuid::Timestamp::now(ctx)
-    } else {
-        match args[0].value_type() {
-            ValueType::Integer => {
-                let ctx = uuid::ContextV7::new();
-                let Some(int) = args[0].to_integer() else {
-                    return Value::error(ResultCode::InvalidArgs);
-                };
-                uuid::Timestamp::from_unix(ctx, int as u64, 0)
-            }
-            ValueType::Text => {
-                let Some(text) = args[0].to_text() else {
-                    return Value::error(ResultCode::InvalidArgs);
-                };
-                match text.parse::<i64>() {
-                    Ok(unix) => {
-                        if unix <= 0 {
-                            return Value::error_with_message("Invalid timestamp".to_string());
-                        }
-                        uuid::Timestamp::from_unix(uuid::ContextV7::new(), unix as u64, 0)
-                    }
-                    Err(_) => return Value::error(ResultCode::InvalidArgs),
-                }
-            }
-            _ => return Value::error(ResultCode::InvalidArgs),
-        }
-    };
-    let uuid = uuid::Uuid::new_v7(timestamp);
-    Value::from_text(uuid.to_string())
-}
-
-#[scalar(name = "uuid7")]
-fn uuid7(&self, args: &[Value]) -> Value {
-    let timestamp = if args.is_empty() {
-        let ctx = uuid::ContextV7::new();
-        uuid::Timestamp::now(ctx)
-    } else {
-        match args[0].value_type() {
-            ValueType::Integer => {
-                let ctx = uuid::ContextV7::new();
-                let Some(int) = args[0].to_integer() else {
-                    return Value::null();
-                };
-                uuid::Timestamp::from_unix(ctx, int as u64, 0)
-            }
-            _ => return Value::null(),
-        }
-    };
-    let uuid = uuid::Uuid::new_v7(timestamp);
-    let bytes = uuid.as_bytes();
-    Value::from_blob(bytes.to_vec())
+/// Builds a ULID from a unix timestamp in milliseconds and 80 random bits written by
+/// `fill_bytes`, encoded as 26 characters of Crockford's base32.
+pub(crate) fn new_ulid(unix_ms: u64, fill_bytes: impl FnOnce(&mut [u8])) -> String {
+    const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+    let mut bytes = [0u8; 16];
+    bytes[..6].copy_from_slice(&unix_ms.to_be_bytes()[2..]);
+    fill_bytes(&mut bytes[6..]);
+    let value = u128::from_be_bytes(bytes);
+    (0..26)
+        .map(|i| ALPHABET[((value >> (125 - 5 * i)) & 0x1f) as usize] as char)
+        .collect()
 }
 
 #[scalar(name = "uuid7_timestamp_ms")]
@@ -144,3 +101,31 @@ fn uuid_to_unix(uuid: &[u8; 16]) -> u64 {
         | ((uuid[4] as u64) << 8)
         | (uuid[5] as u64)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ulid_encoding() {
+        let ulid = new_ulid(1469918176385, |dest| dest.fill(0));
+        assert_eq!(ulid, "01ARYZ6S410000000000000000");
+        let ulid = new_ulid(0, |dest| dest.fill(0xff));
+        assert_eq!(ulid, "0000000000ZZZZZZZZZZZZZZZZ");
+    }
+
+    #[test]
+    fn test_uuid7_timestamp() {
+        let uuid = new_uuid7(1736720789000, |dest| dest.fill(0xab));
+        assert_eq!(uuid.get_version_num(), 7);
+        assert_eq!(uuid.get_variant(), uuid::Variant::RFC4122);
+        assert_eq!(uuid_to_unix(uuid.as_bytes()), 1736720789000);
+    }
+
+    #[test]
+    fn test_uuid4_version() {
+        let uuid = new_uuid4(|dest| dest.fill(0xab));
+        assert_eq!(uuid.get_version_num(), 4);
+        assert_eq!(uuid.get_variant(), uuid::Variant::RFC4122);
+    }
+}
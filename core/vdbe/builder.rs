@@ -17,7 +17,7 @@ use crate::{
         emitter::TransactionMode,
         plan::{ResultSetColumn, TableReferences},
     },
-    CaptureDataChangesMode, Connection, Value, VirtualTable,
+    turso_assert, CaptureDataChangesMode, Connection, Value, VirtualTable,
 };
 
 #[derive(Default)]
@@ -133,8 +133,11 @@ pub struct ProgramBuilder {
     init_label: BranchOffset,
     start_offset: BranchOffset,
     capture_data_changes_mode: CaptureDataChangesMode,
-    // TODO: when we support multiple dbs, this should be a write mask to track which DBs need to be written
+    /// The transaction of the main database
     txn_mode: TransactionMode,
+    /// The transactions of attached and temp databases: database index, mode and the schema
+    /// cookie the program was compiled against.
+    attached_txns: Vec<(usize, TransactionMode, u32)>,
     rollback: bool,
     /// The mode in which the query is being executed.
     query_mode: QueryMode,
@@ -324,6 +327,7 @@ impl ProgramBuilder {
             start_offset: BranchOffset::Placeholder,
             capture_data_changes_mode,
             txn_mode: TransactionMode::None,
+            attached_txns: Vec::new(),
             rollback: false,
             query_mode,
             current_parent_explain_idx: None,
@@ -758,185 +762,43 @@ impl ProgramBuilder {
             Ok(())
         };
         for (insn, _) in self.insns.iter_mut() {
-            match insn {
-                Insn::Init { target_pc } => {
-                    resolve(target_pc, "Init")?;
-                }
-                Insn::Eq {
-                    lhs: _lhs,
-                    rhs: _rhs,
-                    target_pc,
-                    ..
-                } => {
-                    resolve(target_pc, "Eq")?;
-                }
-                Insn::Ne {
-                    lhs: _lhs,
-                    rhs: _rhs,
-                    target_pc,
-                    ..
-                } => {
-                    resolve(target_pc, "Ne")?;
-                }
-                Insn::Lt {
-                    lhs: _lhs,
-                    rhs: _rhs,
-                    target_pc,
-                    ..
-                } => {
-                    resolve(target_pc, "Lt")?;
-                }
-                Insn::Le {
-                    lhs: _lhs,
-                    rhs: _rhs,
-                    target_pc,
-                    ..
-                } => {
-                    resolve(target_pc, "Le")?;
-                }
-                Insn::Gt {
-                    lhs: _lhs,
-                    rhs: _rhs,
-                    target_pc,
-                    ..
-                } => {
-                    resolve(target_pc, "Gt")?;
-                }
-                Insn::Ge {
-                    lhs: _lhs,
-                    rhs: _rhs,
-                    target_pc,
-                    ..
-                } => {
-                    resolve(target_pc, "Ge")?;
-                }
-                Insn::If {
-                    reg: _reg,
-                    target_pc,
-                    jump_if_null: _,
-                } => {
-                    resolve(target_pc, "If")?;
-                }
-                Insn::IfNot {
-                    reg: _reg,
-                    target_pc,
-                    jump_if_null: _,
-                } => {
-                    resolve(target_pc, "IfNot")?;
-                }
-                Insn::Rewind { pc_if_empty, .. } => {
-                    resolve(pc_if_empty, "Rewind")?;
-                }
-                Insn::Last { pc_if_empty, .. } => {
-                    resolve(pc_if_empty, "Last")?;
-                }
-                Insn::Goto { target_pc } => {
-                    resolve(target_pc, "Goto")?;
-                }
-                Insn::DecrJumpZero {
-                    reg: _reg,
-                    target_pc,
-                } => {
-                    resolve(target_pc, "DecrJumpZero")?;
-                }
-                Insn::SorterNext {
-                    cursor_id: _cursor_id,
-                    pc_if_next,
-                } => {
-                    resolve(pc_if_next, "SorterNext")?;
-                }
-                Insn::SorterSort { pc_if_empty, .. } => {
-                    resolve(pc_if_empty, "SorterSort")?;
-                }
-                Insn::SorterCompare {
-                    pc_when_nonequal: target_pc,
-                    ..
-                } => {
-                    resolve(target_pc, "SorterCompare")?;
-                }
-                Insn::NotNull {
-                    reg: _reg,
-                    target_pc,
-                } => {
-                    resolve(target_pc, "NotNull")?;
-                }
-                Insn::IfPos { target_pc, .. } => {
-                    resolve(target_pc, "IfPos")?;
-                }
-                Insn::Next { pc_if_next, .. } => {
-                    resolve(pc_if_next, "Next")?;
-                }
-                Insn::Once {
-                    target_pc_when_reentered,
-                    ..
-                } => {
-                    resolve(target_pc_when_reentered, "Once")?;
-                }
-                Insn::Prev { pc_if_prev, .. } => {
-                    resolve(pc_if_prev, "Prev")?;
-                }
-                Insn::InitCoroutine {
-                    yield_reg: _,
-                    jump_on_definition,
-                    start_offset,
-                } => {
-                    resolve(jump_on_definition, "InitCoroutine")?;
-                    resolve(start_offset, "InitCoroutine")?;
-                }
-                Insn::NotExists {
-                    cursor: _,
-                    rowid_reg: _,
-                    target_pc,
-                } => {
-                    resolve(target_pc, "NotExists")?;
-                }
-                Insn::Yield {
-                    yield_reg: _,
-                    end_offset,
-                } => {
-                    resolve(end_offset, "Yield")?;
-                }
-                Insn::SeekRowid { target_pc, .. } => {
-                    resolve(target_pc, "SeekRowid")?;
-                }
-                Insn::Gosub { target_pc, .. } => {
-                    resolve(target_pc, "Gosub")?;
-                }
-                Insn::Jump {
-                    target_pc_eq,
-                    target_pc_lt,
-                    target_pc_gt,
-                } => {
-                    resolve(target_pc_eq, "Jump")?;
-                    resolve(target_pc_lt, "Jump")?;
-                    resolve(target_pc_gt, "Jump")?;
-                }
-                Insn::SeekGE { target_pc, .. } => resolve(target_pc, "SeekGE")?,
-                Insn::SeekGT { target_pc, .. } => resolve(target_pc, "SeekGT")?,
-                Insn::SeekLE { target_pc, .. } => resolve(target_pc, "SeekLE")?,
-                Insn::SeekLT { target_pc, .. } => resolve(target_pc, "SeekLT")?,
-                Insn::IdxGE { target_pc, .. } => resolve(target_pc, "IdxGE")?,
-                Insn::IdxLE { target_pc, .. } => resolve(target_pc, "IdxLE")?,
-                Insn::IdxGT { target_pc, .. } => resolve(target_pc, "IdxGT")?,
-                Insn::IdxLT { target_pc, .. } => resolve(target_pc, "IdxLT")?,
-                Insn::IndexMethodQuery { pc_if_empty, .. } => {
-                    resolve(pc_if_empty, "IndexMethodQuery")?;
+            visit_jump_targets(insn, &resolve)?;
+        }
+        self.label_to_resolved_offset.clear();
+        Ok(())
+    }
+
+    /// Redirects jumps that land on a `Goto` to the final target of the `Goto` chain, so
+    /// the nested loop and subroutine exits produced by codegen cost a single branch.
+    fn thread_jumps(&mut self) -> crate::Result<()> {
+        let goto_targets = self
+            .insns
+            .iter()
+            .map(|(insn, _)| match insn {
+                Insn::Goto {
+                    target_pc: BranchOffset::Offset(target),
+                } => Some(*target),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let final_target = |mut pc: InsnReference| {
+            // Bounded so that a Goto cycle (an intentional infinite loop) is left alone.
+            for _ in 0..goto_targets.len() {
+                match goto_targets.get(pc as usize) {
+                    Some(Some(target)) if *target != pc => pc = *target,
+                    _ => break,
                 }
-                Insn::IsNull { reg: _, target_pc } => resolve(target_pc, "IsNull")?,
-                Insn::VNext { pc_if_next, .. } => resolve(pc_if_next, "VNext")?,
-                Insn::VFilter { pc_if_empty, .. } => resolve(pc_if_empty, "VFilter")?,
-                Insn::RowSetRead { pc_if_empty, .. } => resolve(pc_if_empty, "RowSetRead")?,
-                Insn::NoConflict { target_pc, .. } => resolve(target_pc, "NoConflict")?,
-                Insn::Found { target_pc, .. } => resolve(target_pc, "Found")?,
-                Insn::NotFound { target_pc, .. } => resolve(target_pc, "NotFound")?,
-                Insn::FkIfZero { target_pc, .. } => resolve(target_pc, "FkIfZero")?,
-                Insn::Filter { target_pc, .. } => resolve(target_pc, "Filter")?,
-                Insn::HashProbe { target_pc, .. } => resolve(target_pc, "HashProbe")?,
-                Insn::HashNext { target_pc, .. } => resolve(target_pc, "HashNext")?,
-                _ => {}
             }
+            pc
+        };
+        for (insn, _) in self.insns.iter_mut() {
+            visit_jump_targets(insn, |pc, _| {
+                if let BranchOffset::Offset(target) = pc {
+                    *target = final_target(*target);
+                }
+                Ok(())
+            })?;
         }
-        self.label_to_resolved_offset.clear();
         Ok(())
     }
 
@@ -1068,12 +930,40 @@ impl ProgramBuilder {
     }
 
     /// Tries to mirror: https://github.com/sqlite/sqlite/blob/e77e589a35862f6ac9c4141cfd1beb2844b84c61/src/build.c#L5379
-    /// TODO: as we currently do not support multiple dbs
-    /// this function just sets a write operation/transaction for the current db
+    /// for the main database, see [Self::begin_attached_operation] for the other databases.
     pub fn begin_write_operation(&mut self) {
         self.txn_mode = TransactionMode::Write;
     }
 
+    /// Makes the program begin a transaction on an attached or temp database and check that
+    /// its schema cookie is still `schema_cookie`, like sqlite3CodeVerifySchema and
+    /// sqlite3BeginWriteOperation do for any database.
+    pub fn begin_attached_operation(
+        &mut self,
+        database_id: usize,
+        tx_mode: TransactionMode,
+        schema_cookie: u32,
+    ) {
+        turso_assert!(
+            database_id != 0,
+            "main database transaction is set with txn_mode"
+        );
+        match self
+            .attached_txns
+            .iter_mut()
+            .find(|(id, _, _)| *id == database_id)
+        {
+            Some((_, mode, _)) => {
+                if matches!(tx_mode, TransactionMode::Write) {
+                    *mode = tx_mode;
+                }
+            }
+            None => self
+                .attached_txns
+                .push((database_id, tx_mode, schema_cookie)),
+        }
+    }
+
     pub fn begin_read_operation(&mut self) {
         // Just override the transaction mode when it is None
         if matches!(self.txn_mode, TransactionMode::None) {
@@ -1119,6 +1009,16 @@ impl ProgramBuilder {
                     schema_cookie: schema.schema_version,
                 });
             }
+            let mut attached_txns = std::mem::take(&mut self.attached_txns);
+            attached_txns.sort_by_key(|(database_id, _, _)| *database_id);
+            for &(db, tx_mode, schema_cookie) in attached_txns.iter() {
+                self.emit_insn(Insn::Transaction {
+                    db,
+                    tx_mode,
+                    schema_cookie,
+                });
+            }
+            self.attached_txns = attached_txns;
 
             if !self.constant_spans.is_empty() {
                 self.emit_constant_insns();
@@ -1243,6 +1143,7 @@ impl ProgramBuilder {
         sql: &str,
     ) -> crate::Result<Program> {
         self.resolve_labels()?;
+        self.thread_jumps()?;
 
         self.parameters.list.dedup();
 
@@ -1269,7 +1170,8 @@ impl ProgramBuilder {
             result_columns: self.result_columns,
             table_references: self.table_references,
             sql: sql.to_string(),
-            accesses_db: !matches!(self.txn_mode, TransactionMode::None),
+            accesses_db: !matches!(self.txn_mode, TransactionMode::None)
+                || !self.attached_txns.is_empty(),
             needs_stmt_subtransactions: self.needs_stmt_subtransactions,
             trigger: self.trigger.take(),
             is_subprogram: self.is_subprogram,
@@ -1279,3 +1181,287 @@ impl ProgramBuilder {
         })
     }
 }
+
+/// Calls `f` with every jump target of `insn` and the name of the instruction.
+fn visit_jump_targets(
+    insn: &mut Insn,
+    mut f: impl FnMut(&mut BranchOffset, &str) -> crate::Result<()>,
+) -> crate::Result<()> {
+    match insn {
+        Insn::Init { target_pc } => {
+            f(target_pc, "Init")?;
+        }
+        Insn::Eq {
+            lhs: _lhs,
+            rhs: _rhs,
+            target_pc,
+            ..
+        } => {
+            f(target_pc, "Eq")?;
+        }
+        Insn::Ne {
+            lhs: _lhs,
+            rhs: _rhs,
+            target_pc,
+            ..
+        } => {
+            f(target_pc, "Ne")?;
+        }
+        Insn::Lt {
+            lhs: _lhs,
+            rhs: _rhs,
+            target_pc,
+            ..
+        } => {
+            f(target_pc, "Lt")?;
+        }
+        Insn::Le {
+            lhs: _lhs,
+            rhs: _rhs,
+            target_pc,
+            ..
+        } => {
+            f(target_pc, "Le")?;
+        }
+        Insn::Gt {
+            lhs: _lhs,
+            rhs: _rhs,
+            target_pc,
+            ..
+        } => {
+            f(target_pc, "Gt")?;
+        }
+        Insn::Ge {
+            lhs: _lhs,
+            rhs: _rhs,
+            target_pc,
+            ..
+        } => {
+            f(target_pc, "Ge")?;
+        }
+        Insn::If {
+            reg: _reg,
+            target_pc,
+            jump_if_null: _,
+        } => {
+            f(target_pc, "If")?;
+        }
+        Insn::IfNot {
+            reg: _reg,
+            target_pc,
+            jump_if_null: _,
+        } => {
+            f(target_pc, "IfNot")?;
+        }
+        Insn::Rewind { pc_if_empty, .. } => {
+            f(pc_if_empty, "Rewind")?;
+        }
+        Insn::Last { pc_if_empty, .. } => {
+            f(pc_if_empty, "Last")?;
+        }
+        Insn::Goto { target_pc } => {
+            f(target_pc, "Goto")?;
+        }
+        Insn::DecrJumpZero {
+            reg: _reg,
+            target_pc,
+        } => {
+            f(target_pc, "DecrJumpZero")?;
+        }
+        Insn::SorterNext {
+            cursor_id: _cursor_id,
+            pc_if_next,
+        } => {
+            f(pc_if_next, "SorterNext")?;
+        }
+        Insn::SorterSort { pc_if_empty, .. } => {
+            f(pc_if_empty, "SorterSort")?;
+        }
+        Insn::SorterCompare {
+            pc_when_nonequal: target_pc,
+            ..
+        } => {
+            f(target_pc, "SorterCompare")?;
+        }
+        Insn::NotNull {
+            reg: _reg,
+            target_pc,
+        } => {
+            f(target_pc, "NotNull")?;
+        }
+        Insn::IfPos { target_pc, .. } => {
+            f(target_pc, "IfPos")?;
+        }
+        Insn::Next { pc_if_next, .. } => {
+            f(pc_if_next, "Next")?;
+        }
+        Insn::Once {
+            target_pc_when_reentered,
+            ..
+        } => {
+            f(target_pc_when_reentered, "Once")?;
+        }
+        Insn::Prev { pc_if_prev, .. } => {
+            f(pc_if_prev, "Prev")?;
+        }
+        Insn::InitCoroutine {
+            yield_reg: _,
+            jump_on_definition,
+            start_offset,
+        } => {
+            f(jump_on_definition, "InitCoroutine")?;
+            f(start_offset, "InitCoroutine")?;
+        }
+        Insn::NotExists {
+            cursor: _,
+            rowid_reg: _,
+            target_pc,
+        } => {
+            f(target_pc, "NotExists")?;
+        }
+        Insn::Yield {
+            yield_reg: _,
+            end_offset,
+        } => {
+            f(end_offset, "Yield")?;
+        }
+        Insn::SeekRowid { target_pc, .. } => {
+            f(target_pc, "SeekRowid")?;
+        }
+        Insn::Gosub { target_pc, .. } => {
+            f(target_pc, "Gosub")?;
+        }
+        Insn::Jump {
+            target_pc_eq,
+            target_pc_lt,
+            target_pc_gt,
+        } => {
+            f(target_pc_eq, "Jump")?;
+            f(target_pc_lt, "Jump")?;
+            f(target_pc_gt, "Jump")?;
+        }
+        Insn::SeekGE { target_pc, .. } => f(target_pc, "SeekGE")?,
+        Insn::SeekGT { target_pc, .. } => f(target_pc, "SeekGT")?,
+        Insn::SeekLE { target_pc, .. } => f(target_pc, "SeekLE")?,
+        Insn::SeekLT { target_pc, .. } => f(target_pc, "SeekLT")?,
+        Insn::IdxGE { target_pc, .. } => f(target_pc, "IdxGE")?,
+        Insn::IdxLE { target_pc, .. } => f(target_pc, "IdxLE")?,
+        Insn::IdxGT { target_pc, .. } => f(target_pc, "IdxGT")?,
+        Insn::IdxLT { target_pc, .. } => f(target_pc, "IdxLT")?,
+        Insn::IndexMethodQuery { pc_if_empty, .. } => {
+            f(pc_if_empty, "IndexMethodQuery")?;
+        }
+        Insn::IsNull { reg: _, target_pc } => f(target_pc, "IsNull")?,
+        Insn::VNext { pc_if_next, .. } => f(pc_if_next, "VNext")?,
+        Insn::VFilter { pc_if_empty, .. } => f(pc_if_empty, "VFilter")?,
+        Insn::RowSetRead { pc_if_empty, .. } => f(pc_if_empty, "RowSetRead")?,
+        Insn::NoConflict { target_pc, .. } => f(target_pc, "NoConflict")?,
+        Insn::Found { target_pc, .. } => f(target_pc, "Found")?,
+        Insn::NotFound { target_pc, .. } => f(target_pc, "NotFound")?,
+        Insn::FkIfZero { target_pc, .. } => f(target_pc, "FkIfZero")?,
+        Insn::Filter { target_pc, .. } => f(target_pc, "Filter")?,
+        Insn::HashProbe { target_pc, .. } => f(target_pc, "HashProbe")?,
+        Insn::HashNext { target_pc, .. } => f(target_pc, "HashNext")?,
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builder() -> ProgramBuilder {
+        ProgramBuilder::new(
+            QueryMode::Normal,
+            CaptureDataChangesMode::Off,
+            ProgramBuilderOpts {
+                num_cursors: 0,
+                approx_num_insns: 8,
+                approx_num_labels: 0,
+            },
+        )
+    }
+
+    fn goto(target: InsnReference) -> Insn {
+        Insn::Goto {
+            target_pc: BranchOffset::Offset(target),
+        }
+    }
+
+    fn halt() -> Insn {
+        Insn::Halt {
+            err_code: 0,
+            description: String::new(),
+        }
+    }
+
+    /// The jump target of each instruction, if it has one.
+    fn jump_targets(builder: &mut ProgramBuilder) -> Vec<Option<InsnReference>> {
+        builder
+            .insns
+            .iter_mut()
+            .map(|(insn, _)| {
+                let mut target = None;
+                visit_jump_targets(insn, |pc, _| {
+                    target = Some(pc.as_offset_int());
+                    Ok(())
+                })
+                .unwrap();
+                target
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_thread_jumps_follows_goto_chains() {
+        let mut builder = builder();
+        let label = builder.allocate_label();
+        builder.emit_insn(Insn::IfPos {
+            reg: 1,
+            target_pc: label,
+            decrement_by: 0,
+        });
+        builder.emit_insn(halt());
+        builder.preassign_label_to_next_insn(label);
+        builder.emit_insn(goto(4));
+        builder.emit_insn(halt());
+        builder.emit_insn(goto(6));
+        builder.emit_insn(halt());
+        builder.emit_insn(halt());
+        builder.resolve_labels().unwrap();
+        builder.thread_jumps().unwrap();
+
+        assert_eq!(
+            jump_targets(&mut builder),
+            vec![Some(6), None, Some(6), None, Some(6), None, None]
+        );
+    }
+
+    #[test]
+    fn test_thread_jumps_leaves_goto_cycles() {
+        let mut builder = builder();
+        builder.emit_insn(goto(1));
+        builder.emit_insn(goto(0));
+        builder.emit_insn(goto(2));
+        builder.emit_insn(Insn::IfPos {
+            reg: 1,
+            target_pc: BranchOffset::Offset(0),
+            decrement_by: 0,
+        });
+        builder.emit_insn(Insn::IfPos {
+            reg: 1,
+            target_pc: BranchOffset::Offset(2),
+            decrement_by: 0,
+        });
+        builder.thread_jumps().unwrap();
+
+        let targets = jump_targets(&mut builder);
+        // The jumps stay inside their cycles, and a Goto to itself is not changed.
+        assert!(matches!(targets[0], Some(0 | 1)));
+        assert!(matches!(targets[1], Some(0 | 1)));
+        assert_eq!(targets[2], Some(2));
+        assert!(matches!(targets[3], Some(0 | 1)));
+        assert_eq!(targets[4], Some(2));
+    }
+}
@@ -38,7 +38,8 @@ use crate::vector::{
 };
 use crate::{
     error::{
-        LimboError, SQLITE_CONSTRAINT, SQLITE_CONSTRAINT_NOTNULL, SQLITE_CONSTRAINT_PRIMARYKEY,
+        LimboError, SQLITE_CONSTRAINT, SQLITE_CONSTRAINT_FOREIGNKEY, SQLITE_CONSTRAINT_NOTNULL,
+        SQLITE_CONSTRAINT_PRIMARYKEY,
     },
     ext::ExtValue,
     function::{AggFunc, ExtFunc, MathFunc, MathFuncArity, ScalarFunc, VectorFunc},
@@ -55,7 +56,6 @@ use crate::{get_cursor, CheckpointMode, Completion, Connection, DatabaseStorage,
 use branches::unlikely;
 use either::Either;
 use std::any::Any;
-use std::env::temp_dir;
 use std::str::FromStr;
 use std::{
     borrow::BorrowMut,
@@ -261,10 +261,10 @@ pub fn op_drop_index(
     insn: &Insn,
     pager: &Arc<Pager>,
 ) -> Result<InsnFunctionStepResult> {
-    load_insn!(DropIndex { index, db: _ }, insn);
+    load_insn!(DropIndex { index, db }, insn);
     program
         .connection
-        .with_schema_mut(|schema| schema.remove_index(index));
+        .with_database_schema_mut(*db, |schema| schema.remove_index(index));
     state.pc += 1;
     Ok(InsnFunctionStepResult::Step)
 }
@@ -396,6 +396,7 @@ pub fn op_checkpoint(
     let step_result = program.connection.pager.load().checkpoint(
         *checkpoint_mode,
         program.connection.get_sync_mode(),
+        program.connection.get_data_sync_retry(),
         true,
     );
     match step_result {
@@ -432,12 +433,16 @@ pub fn op_null(
 ) -> Result<InsnFunctionStepResult> {
     match insn {
         Insn::Null { dest, dest_end } | Insn::BeginSubrtn { dest, dest_end } => {
-            if let Some(dest_end) = dest_end {
-                for i in *dest..=*dest_end {
-                    state.registers[i] = Register::Value(Value::Null);
+            let dest_end = dest_end.unwrap_or(*dest);
+            for i in *dest..=dest_end {
+                state.registers[i] = Register::Value(Value::Null);
+            }
+            // Like SQLite, setting a register to NULL releases the RowSet it holds, so a loop
+            // that collects rowids starts from an empty set every time it is entered.
+            if !state.rowsets.is_empty() {
+                for i in *dest..=dest_end {
+                    state.rowsets.remove(&i);
                 }
-            } else {
-                state.registers[*dest] = Register::Value(Value::Null);
             }
         }
         _ => unreachable!("unexpected Insn {:?}", insn),
@@ -1923,6 +1928,7 @@ pub fn op_result_row(
         count: *count,
     };
     state.result_row = Some(row);
+    state.metrics.rows_returned = state.metrics.rows_returned.saturating_add(1);
     state.pc += 1;
     Ok(InsnFunctionStepResult::Row)
 }
@@ -2034,25 +2040,29 @@ pub fn halt(
     }
     match err_code {
         0 => {}
-        SQLITE_CONSTRAINT_PRIMARYKEY => {
-            return Err(LimboError::Constraint(format!(
-                "UNIQUE constraint failed: {description} (19)"
-            )));
+        SQLITE_CONSTRAINT_PRIMARYKEY | SQLITE_CONSTRAINT_UNIQUE => {
+            return Err(LimboError::Constraint {
+                code: err_code,
+                message: format!("UNIQUE constraint failed: {description} (19)"),
+            });
         }
         SQLITE_CONSTRAINT_NOTNULL => {
-            return Err(LimboError::Constraint(format!(
-                "NOT NULL constraint failed: {description} (19)"
-            )));
+            return Err(LimboError::Constraint {
+                code: err_code,
+                message: format!("NOT NULL constraint failed: {description} (19)"),
+            });
         }
-        SQLITE_CONSTRAINT_UNIQUE => {
-            return Err(LimboError::Constraint(format!(
-                "UNIQUE constraint failed: {description} (19)"
-            )));
+        SQLITE_CONSTRAINT | SQLITE_CONSTRAINT_FOREIGNKEY => {
+            return Err(LimboError::Constraint {
+                code: err_code,
+                message: description.to_string(),
+            });
         }
         _ => {
-            return Err(LimboError::Constraint(format!(
-                "undocumented halt error code {description}"
-            )));
+            return Err(LimboError::Constraint {
+                code: err_code,
+                message: format!("undocumented halt error code {description}"),
+            });
         }
     }
 
@@ -2067,12 +2077,18 @@ pub fn halt(
             .load(Ordering::Acquire)
             > 0
     {
-        return Err(LimboError::Constraint(
-            "foreign key constraint failed".to_string(),
-        ));
+        return Err(LimboError::Constraint {
+            code: SQLITE_CONSTRAINT_FOREIGNKEY,
+            message: "foreign key constraint failed".to_string(),
+        });
     }
 
     if program.is_trigger_subprogram() {
+        // Changes made by subprograms count towards total_changes() but not changes(), which
+        // only reports the rows changed by the top level statement.
+        program
+            .connection
+            .add_total_changes(program.n_change.load(Ordering::SeqCst));
         return Ok(InsnFunctionStepResult::Done);
     }
 
@@ -2088,10 +2104,12 @@ pub fn halt(
                 vtab_rollback_all(&program.connection, state)?;
                 pager.rollback_tx(&program.connection);
                 program.connection.set_tx_state(TransactionState::None);
+                program.connection.rollback_attached_transactions();
                 program.connection.auto_commit.store(true, Ordering::SeqCst);
-                return Err(LimboError::Constraint(
-                    "foreign key constraint failed".to_string(),
-                ));
+                return Err(LimboError::Constraint {
+                    code: SQLITE_CONSTRAINT_FOREIGNKEY,
+                    message: "foreign key constraint failed".to_string(),
+                });
             }
         }
         state.end_statement(&program.connection, pager, EndStatement::ReleaseSavepoint)?;
@@ -2220,6 +2238,9 @@ pub fn op_transaction_inner(
             "Transaction instruction should not be used in trigger subprograms"
         );
     }
+    if *db != 0 {
+        return op_transaction_attached(program, state, *db, *tx_mode, *schema_cookie);
+    }
     let pager = program.get_pager_from_database_index(db);
     let mv_store = program.connection.mv_store();
     loop {
@@ -2230,6 +2251,9 @@ pub fn op_transaction_inner(
                 if write && conn.db.open_flags.contains(OpenFlags::ReadOnly) {
                     return Err(LimboError::ReadOnly);
                 }
+                if write {
+                    pager.check_ptrmap_maintained()?;
+                }
 
                 // 1. We try to upgrade current version
                 let current_state = conn.get_tx_state();
@@ -2413,6 +2437,94 @@ pub fn op_transaction_inner(
     }
 }
 
+/// Begins the transaction of an attached or temp database. These databases don't use MVCC, so
+/// their transactions are always pager transactions, tracked per database by the connection.
+fn op_transaction_attached(
+    program: &Program,
+    state: &mut ProgramState,
+    db: usize,
+    tx_mode: TransactionMode,
+    schema_cookie: u32,
+) -> Result<InsnFunctionStepResult> {
+    let conn = &program.connection;
+    let pager = program.get_pager_from_database_index(&db);
+    let write = matches!(tx_mode, TransactionMode::Write);
+    loop {
+        match state.op_transaction_state {
+            OpTransactionState::Start => {
+                if write && conn.is_readonly(db) {
+                    return Err(LimboError::ReadOnly);
+                }
+                if write {
+                    pager.check_ptrmap_maintained()?;
+                }
+                if matches!(tx_mode, TransactionMode::Concurrent) {
+                    return Err(LimboError::TxError(
+                        "Concurrent transaction mode is only supported when MVCC is enabled"
+                            .to_string(),
+                    ));
+                }
+                if !conn.is_nested_stmt() {
+                    let current_state = conn.get_attached_tx_state(db);
+                    if current_state == TransactionState::None {
+                        pager.begin_read_tx()?;
+                        conn.set_attached_tx_state(db, TransactionState::Read);
+                        state.auto_txn_cleanup = TxnCleanup::RollbackTxn;
+                    }
+                    if write
+                        && !matches!(
+                            conn.get_attached_tx_state(db),
+                            TransactionState::Write { .. }
+                        )
+                    {
+                        match pager.begin_write_tx() {
+                            Err(err @ (LimboError::Busy | LimboError::BusySnapshot)) => {
+                                // Leave the database as it was before this instruction.
+                                if current_state == TransactionState::None {
+                                    pager.end_read_tx();
+                                    conn.set_attached_tx_state(db, TransactionState::None);
+                                }
+                                return Err(err);
+                            }
+                            Err(err) => return Err(err),
+                            Ok(IOResult::IO(io)) => return Ok(InsnFunctionStepResult::IO(io)),
+                            Ok(IOResult::Done(())) => {}
+                        }
+                        conn.set_attached_tx_state(
+                            db,
+                            TransactionState::Write {
+                                schema_did_change: false,
+                            },
+                        );
+                    }
+                }
+                state.op_transaction_state = OpTransactionState::CheckSchemaCookie;
+            }
+            OpTransactionState::CheckSchemaCookie => {
+                match pager.get_schema_cookie() {
+                    Ok(IOResult::Done(header_schema_cookie)) => {
+                        if header_schema_cookie != schema_cookie {
+                            return Err(LimboError::SchemaUpdated);
+                        }
+                    }
+                    Ok(IOResult::IO(io)) => return Ok(InsnFunctionStepResult::IO(io)),
+                    Err(LimboError::Page1NotAlloc) => {}
+                    Err(err) => return Err(err),
+                }
+                state.op_transaction_state = OpTransactionState::BeginStatement;
+            }
+            OpTransactionState::BeginStatement => {
+                if program.needs_stmt_subtransactions && write {
+                    return_if_io!(state.begin_attached_statement(&pager, db));
+                }
+                state.pc += 1;
+                state.op_transaction_state = OpTransactionState::Start;
+                return Ok(InsnFunctionStepResult::Step);
+            }
+        }
+    }
+}
+
 pub fn op_auto_commit(
     program: &Program,
     state: &mut ProgramState,
@@ -2433,7 +2545,10 @@ pub fn op_auto_commit(
     let had_autocommit = conn.auto_commit.load(Ordering::SeqCst); // true, not in tx
 
     // Drive any multi-step commit/rollback that's already in progress.
-    if matches!(state.commit_state, CommitState::Committing) {
+    if matches!(
+        state.commit_state,
+        CommitState::Committing | CommitState::CommittingAttached
+    ) {
         let res = program
             .commit_txn(pager.clone(), state, mv_store.as_ref(), *rollback)
             .map(Into::into);
@@ -2541,9 +2656,10 @@ fn check_deferred_fk_on_commit(conn: &Connection) -> Result<()> {
         return Ok(());
     }
     if conn.get_deferred_foreign_key_violations() > 0 {
-        return Err(LimboError::Constraint(
-            "FOREIGN KEY constraint failed".into(),
-        ));
+        return Err(LimboError::Constraint {
+            code: SQLITE_CONSTRAINT_FOREIGNKEY,
+            message: "FOREIGN KEY constraint failed".into(),
+        });
     }
     Ok(())
 }
@@ -2629,6 +2745,9 @@ pub enum OpProgramState {
     /// Step state tracks whether we're executing a trigger subprogram (vs FK action subprogram)
     Step {
         is_trigger: bool,
+        /// The connection's last inserted rowid before the subprogram started, restored once
+        /// it finishes so rows inserted by triggers are not visible to `last_insert_rowid()`.
+        last_insert_rowid: i64,
     },
 }
 
@@ -2686,10 +2805,17 @@ pub fn op_program(
                     }
                 }
 
-                state.op_program_state = OpProgramState::Step { is_trigger };
+                state.op_program_state = OpProgramState::Step {
+                    is_trigger,
+                    last_insert_rowid: program.connection.last_insert_rowid(),
+                };
             }
-            OpProgramState::Step { is_trigger } => {
+            OpProgramState::Step {
+                is_trigger,
+                last_insert_rowid,
+            } => {
                 let is_trigger = *is_trigger;
+                let last_insert_rowid = *last_insert_rowid;
                 loop {
                     let mut statement = subprogram.write();
                     let res = statement.step();
@@ -2706,9 +2832,9 @@ pub fn op_program(
                                 return Err(LimboError::Busy);
                             }
                         },
-                        Err(LimboError::Constraint(constraint_err)) => {
+                        Err(err @ LimboError::Constraint { .. }) => {
                             if program.resolve_type != ResolveType::Ignore {
-                                return Err(LimboError::Constraint(constraint_err));
+                                return Err(err);
                             }
                             break;
                         }
@@ -2722,6 +2848,7 @@ pub fn op_program(
                 if is_trigger {
                     program.connection.end_trigger_execution();
                 }
+                program.connection.update_last_rowid(last_insert_rowid);
 
                 state.op_program_state = OpProgramState::Start;
                 state.pc += 1;
@@ -3793,7 +3920,7 @@ pub fn op_agg_step(
             AggFunc::Max => Register::Aggregate(AggContext::Max(None)),
             AggFunc::Min => Register::Aggregate(AggContext::Min(None)),
             AggFunc::GroupConcat | AggFunc::StringAgg => {
-                Register::Aggregate(AggContext::GroupConcat(Value::build_text("")))
+                Register::Aggregate(AggContext::GroupConcat(Value::Null))
             }
             #[cfg(feature = "json")]
             AggFunc::JsonGroupArray | AggFunc::JsonbGroupArray => {
@@ -3864,17 +3991,15 @@ pub fn op_agg_step(
                                 match acc_i.checked_add(i) {
                                     Some(sum) => *acc = Value::Integer(sum),
                                     None => {
-                                        if matches!(func, AggFunc::Total) {
-                                            // Total() never throw an integer overflow -> switch to float with KBN summation
-                                            let acc_f = *acc_i as f64;
-                                            *acc = Value::Float(acc_f);
-                                            sum_state.approx = true;
-                                            sum_state.ovrfl = true;
-
-                                            apply_kbn_step_int(acc, i, sum_state);
-                                        } else {
-                                            return Err(LimboError::IntegerOverflow);
-                                        }
+                                        // Switch to float with KBN summation. Total() never
+                                        // throws an integer overflow, and sum() only does so
+                                        // when finalized, if no non-integer value followed.
+                                        let acc_f = *acc_i as f64;
+                                        *acc = Value::Float(acc_f);
+                                        sum_state.approx = true;
+                                        sum_state.ovrfl = true;
+
+                                        apply_kbn_step_int(acc, i, sum_state);
                                     }
                                 }
                             }
@@ -3897,6 +4022,7 @@ pub fn op_agg_step(
                             }
                             Value::Float(_) => {
                                 sum_state.approx = true;
+                                sum_state.ovrfl = false;
                                 apply_kbn_step(acc, f, sum_state);
                             }
                             _ => unreachable!(),
@@ -3987,28 +4113,26 @@ pub fn op_agg_step(
             }
         }
         AggFunc::GroupConcat | AggFunc::StringAgg => {
-            let col = state.registers[*col].get_value().clone();
-            let delimiter = state.registers[*delimiter].clone();
-            let Register::Aggregate(agg) = state.registers[*acc_reg].borrow_mut() else {
-                unreachable!();
-            };
-            let AggContext::GroupConcat(acc) = agg.borrow_mut() else {
-                unreachable!();
-            };
-            if acc.to_string().is_empty() {
-                *acc = col;
-            } else {
-                match col {
-                    Value::Null => {}
-                    _ => {
-                        match delimiter {
-                            Register::Value(value) => {
-                                *acc += value;
-                            }
-                            _ => unreachable!(),
-                        }
-                        *acc += col;
+            // NULL values are skipped. The others are concatenated as text, each one preceded by
+            // the separator of its own row, except for the first one. The accumulator stays NULL
+            // until the first value, so that empty strings are not mistaken for a missing value.
+            let col = state.registers[*col].get_value();
+            if !matches!(col, Value::Null) {
+                let col = col.to_string();
+                let delimiter = state.registers[*delimiter].get_value().to_string();
+                let Register::Aggregate(agg) = state.registers[*acc_reg].borrow_mut() else {
+                    unreachable!();
+                };
+                let AggContext::GroupConcat(acc) = agg.borrow_mut() else {
+                    unreachable!();
+                };
+                match acc {
+                    Value::Text(text) => {
+                        let text = text.value.to_mut();
+                        text.push_str(&delimiter);
+                        text.push_str(&col);
                     }
+                    _ => *acc = Value::build_text(col),
                 }
             }
         }
@@ -4126,6 +4250,9 @@ pub fn op_agg_final(
                 let AggContext::Sum(acc, sum_state) = agg else {
                     unreachable!();
                 };
+                if sum_state.ovrfl {
+                    return Err(LimboError::IntegerOverflow);
+                }
                 let value = match acc {
                     Value::Null => match sum_state.approx {
                         true => Value::Float(0.0),
@@ -4280,6 +4407,7 @@ pub fn op_sorter_open(
         max_buffer_size_bytes,
         page_size,
         pager.io.clone(),
+        program.connection.temp_directory().clone(),
     );
     let cursors = &mut state.cursors;
     cursors
@@ -4973,6 +5101,28 @@ pub fn op_function(
                 };
                 state.registers[*dest] = Register::Value(result);
             }
+            #[cfg(feature = "regexp")]
+            ScalarFunc::Regexp => {
+                let pattern = state.registers[*start_reg].get_value();
+                let text = state.registers[*start_reg + 1].get_value();
+                let result = match (pattern, text) {
+                    (Value::Null, _) | (_, Value::Null) => Value::Null,
+                    (pattern, text) => {
+                        let cache = if *constant_mask > 0 {
+                            Some(&mut state.regex_cache.regexp)
+                        } else {
+                            None
+                        };
+                        let matched = crate::vdbe::likeop::exec_regexp(
+                            cache,
+                            &pattern.to_string(),
+                            &text.to_string(),
+                        )?;
+                        Value::Integer(matched as i64)
+                    }
+                };
+                state.registers[*dest] = Register::Value(result);
+            }
             ScalarFunc::IfNull => {}
             ScalarFunc::Iif => {}
             ScalarFunc::Instr => {
@@ -4998,15 +5148,24 @@ pub fn op_function(
                     _ => &match_expression.get_value().exec_cast("TEXT"),
                 };
 
-                let result = match (pattern, match_expression) {
-                    (Value::Text(pattern), Value::Text(match_expression)) if arg_count == 3 => {
-                        let escape =
-                            construct_like_escape_arg(state.registers[*start_reg + 2].get_value())?;
+                let escape = if arg_count == 3 {
+                    construct_like_escape_arg(state.registers[*start_reg + 2].get_value())?
+                } else {
+                    None
+                };
+                let case_sensitive = program.connection.case_sensitive_like();
 
+                let result = match (pattern, match_expression) {
+                    // A NULL escape character makes the result NULL.
+                    _ if arg_count == 3 && escape.is_none() => Value::Null,
+                    (Value::Text(pattern), Value::Text(match_expression))
+                        if escape.is_some() || case_sensitive =>
+                    {
                         Value::Integer(exec_like_with_escape(
                             pattern.as_str(),
                             match_expression.as_str(),
                             escape,
+                            case_sensitive,
                         ) as i64)
                     }
                     (Value::Text(pattern), Value::Text(match_expression)) => {
@@ -5082,6 +5241,72 @@ pub fn op_function(
                 state.registers[*dest] =
                     Register::Value(Value::exec_random(|| pager.io.generate_random_number()));
             }
+            // The randomness and the clock come from the IO layer, so that the values are
+            // reproducible in the simulator.
+            #[cfg(feature = "uuid")]
+            ScalarFunc::Uuid4 => {
+                let uuid = crate::uuid::new_uuid4(|dest| pager.io.fill_bytes(dest));
+                state.registers[*dest] =
+                    Register::Value(Value::from_blob(uuid.as_bytes().to_vec()));
+            }
+            #[cfg(feature = "uuid")]
+            ScalarFunc::Uuid4Str => {
+                let uuid = crate::uuid::new_uuid4(|dest| pager.io.fill_bytes(dest));
+                state.registers[*dest] = Register::Value(Value::build_text(uuid.to_string()));
+            }
+            #[cfg(feature = "uuid")]
+            ScalarFunc::Ulid => {
+                let now = pager.io.now();
+                let unix_ms = (now.secs * 1000 + (now.micros / 1000) as i64).max(0) as u64;
+                let ulid = crate::uuid::new_ulid(unix_ms, |dest| pager.io.fill_bytes(dest));
+                state.registers[*dest] = Register::Value(Value::build_text(ulid));
+            }
+            #[cfg(feature = "uuid")]
+            ScalarFunc::Uuid7 | ScalarFunc::Uuid7Str => {
+                let as_text = matches!(scalar_func, ScalarFunc::Uuid7Str);
+                // The optional argument is a unix timestamp in seconds. uuid7() returns NULL for
+                // anything but an integer, uuid7_str() also accepts it as text.
+                let unix_ms = if arg_count == 0 {
+                    let now = pager.io.now();
+                    Some((now.secs * 1000 + (now.micros / 1000) as i64).max(0) as u64)
+                } else {
+                    match state.registers[*start_reg].get_value() {
+                        Value::Integer(secs) => Some((*secs as u64).saturating_mul(1000)),
+                        Value::Text(text) if as_text => match text.as_str().parse::<i64>() {
+                            Ok(secs) if secs > 0 => Some((secs as u64).saturating_mul(1000)),
+                            Ok(_) => {
+                                return Err(LimboError::InvalidArgument(
+                                    "Invalid timestamp".to_string(),
+                                ))
+                            }
+                            Err(_) => {
+                                return Err(LimboError::InvalidArgument(format!(
+                                    "invalid argument to {scalar_func}()"
+                                )))
+                            }
+                        },
+                        _ if as_text => {
+                            return Err(LimboError::InvalidArgument(format!(
+                                "invalid argument to {scalar_func}()"
+                            )))
+                        }
+                        _ => None,
+                    }
+                };
+                let result = match unix_ms {
+                    Some(unix_ms) => {
+                        let uuid =
+                            crate::uuid::new_uuid7(unix_ms, |dest| pager.io.fill_bytes(dest));
+                        if as_text {
+                            Value::build_text(uuid.to_string())
+                        } else {
+                            Value::from_blob(uuid.as_bytes().to_vec())
+                        }
+                    }
+                    None => Value::Null,
+                };
+                state.registers[*dest] = Register::Value(result);
+            }
             ScalarFunc::Trim => {
                 let reg_value = &state.registers[*start_reg];
                 let pattern_value = if func.arg_count == 2 {
@@ -5251,7 +5476,7 @@ pub fn op_function(
             #[cfg(feature = "fs")]
             #[cfg(not(target_family = "wasm"))]
             ScalarFunc::LoadExtension => {
-                if !program.connection.db.can_load_extensions() {
+                if !program.connection.load_extension_enabled() {
                     crate::bail_parse_error!("runtime extension loading is disabled");
                 }
                 let extension = &state.registers[*start_reg];
@@ -6742,13 +6967,19 @@ pub fn op_idx_delete(
                 state.op_idx_delete_state = Some(OpIdxDeleteState::Verifying);
             }
             Some(OpIdxDeleteState::Verifying) => {
-                let rowid = {
+                let has_entry = {
                     let cursor = state.get_cursor(*cursor_id);
                     let cursor = cursor.as_btree_mut();
-                    return_if_io!(cursor.rowid())
+                    // Entries of indexes on WITHOUT ROWID tables end with the primary key
+                    // instead of a rowid.
+                    if cursor.has_rowid() {
+                        return_if_io!(cursor.rowid()).is_some()
+                    } else {
+                        cursor.has_record()
+                    }
                 };
 
-                if rowid.is_none() && *raise_error_if_no_matching_entry {
+                if !has_entry && *raise_error_if_no_matching_entry {
                     let reg_values = (*start_reg..*start_reg + *num_regs)
                         .map(|i| &state.registers[i])
                         .collect::<Vec<_>>();
@@ -6902,9 +7133,10 @@ pub fn op_idx_insert(
                     if flags.has(IdxInsertFlags::NO_OP_DUPLICATE) {
                         break 'i true;
                     }
-                    return Err(LimboError::Constraint(
-                        "UNIQUE constraint failed: duplicate key".into(),
-                    ));
+                    return Err(LimboError::Constraint {
+                        code: SQLITE_CONSTRAINT_UNIQUE,
+                        message: "UNIQUE constraint failed: duplicate key".into(),
+                    });
                 }
 
                 false
@@ -7585,18 +7817,13 @@ pub fn op_create_btree(
 ) -> Result<InsnFunctionStepResult> {
     load_insn!(CreateBtree { db, root, flags }, insn);
 
-    assert_eq!(*db, 0);
-
     if program.connection.is_readonly(*db) {
         return Err(LimboError::ReadOnly);
     }
-    if *db > 0 {
-        // TODO: implement temp databases
-        todo!("temp databases not implemented yet");
-    }
+    let pager = program.get_pager_from_database_index(db);
     let mv_store = program.connection.mv_store();
 
-    if let Some(mv_store) = mv_store.as_ref() {
+    if let Some(mv_store) = mv_store.as_ref().filter(|_| *db == 0) {
         let root_page = mv_store.get_next_table_id();
         state.registers[*root] = Register::Value(Value::Integer(root_page));
         state.pc += 1;
@@ -7622,7 +7849,9 @@ pub fn op_index_method_create(
     }
     let mv_store = program.connection.mv_store();
     if let Some(_mv_store) = mv_store.as_ref() {
-        todo!("MVCC is not supported yet");
+        return Err(LimboError::InternalError(
+            "index methods are not supported with MVCC yet".to_string(),
+        ));
     }
     if let (_, CursorType::IndexMethod(module)) = &program.cursor_ref[*cursor_id] {
         if state.cursors[*cursor_id].is_none() {
@@ -7654,7 +7883,9 @@ pub fn op_index_method_destroy(
     }
     let mv_store = program.connection.mv_store();
     if let Some(_mv_store) = mv_store.as_ref() {
-        todo!("MVCC is not supported yet");
+        return Err(LimboError::InternalError(
+            "index methods are not supported with MVCC yet".to_string(),
+        ));
     }
     if let (_, CursorType::IndexMethod(module)) = &program.cursor_ref[*cursor_id] {
         if state.cursors[*cursor_id].is_none() {
@@ -7695,7 +7926,9 @@ pub fn op_index_method_query(
     }
     let mv_store = program.connection.mv_store();
     if let Some(_mv_store) = mv_store.as_ref() {
-        todo!("MVCC is not supported yet");
+        return Err(LimboError::InternalError(
+            "index methods are not supported with MVCC yet".to_string(),
+        ));
     }
     let cursor = state.cursors[*cursor_id]
         .as_mut()
@@ -7726,19 +7959,17 @@ pub fn op_destroy(
         Destroy {
             root,
             former_root_reg,
-            is_temp,
+            db,
         },
         insn
     );
-    if *is_temp == 1 {
-        todo!("temp databases not implemented yet.");
-    }
     let mv_store = program.connection.mv_store();
-    if mv_store.is_some() {
+    if mv_store.is_some() && *db == 0 {
         // MVCC only does pager operations in checkpoint
         state.pc += 1;
         return Ok(InsnFunctionStepResult::Step);
     }
+    let pager = program.get_pager_from_database_index(db);
 
     loop {
         match state.op_destroy_state {
@@ -7797,12 +8028,9 @@ pub fn op_drop_table(
     pager: &Arc<Pager>,
 ) -> Result<InsnFunctionStepResult> {
     load_insn!(DropTable { db, table_name, .. }, insn);
-    if *db > 0 {
-        todo!("temp databases not implemented yet");
-    }
     let conn = program.connection.clone();
     {
-        conn.with_schema_mut(|schema| {
+        conn.with_database_schema_mut(*db, |schema| {
             schema.remove_indices_for_table(table_name);
             schema.remove_triggers_for_table(table_name);
             schema.remove_table(table_name);
@@ -7820,7 +8048,9 @@ pub fn op_drop_view(
 ) -> Result<InsnFunctionStepResult> {
     load_insn!(DropView { db, view_name }, insn);
     if *db > 0 {
-        todo!("temp databases not implemented yet");
+        return Err(LimboError::InternalError(
+            "temp databases not implemented yet".to_string(),
+        ));
     }
     let conn = program.connection.clone();
     conn.with_schema_mut(|schema| {
@@ -7913,7 +8143,9 @@ pub fn op_page_count(
     load_insn!(PageCount { db, dest }, insn);
     if *db > 0 {
         // TODO: implement temp databases
-        todo!("temp databases not implemented yet");
+        return Err(LimboError::InternalError(
+            "temp databases not implemented yet".to_string(),
+        ));
     }
     let mv_store = program.connection.mv_store();
     let count = match with_header(pager, mv_store.as_ref(), program, |header| {
@@ -7934,13 +8166,7 @@ pub fn op_parse_schema(
     insn: &Insn,
     pager: &Arc<Pager>,
 ) -> Result<InsnFunctionStepResult> {
-    load_insn!(
-        ParseSchema {
-            db: _,
-            where_clause,
-        },
-        insn
-    );
+    load_insn!(ParseSchema { db, where_clause }, insn);
 
     let conn = program.connection.clone();
     // set auto commit to false in order for parse schema to not commit changes as transaction state is stored in connection,
@@ -7949,39 +8175,32 @@ pub fn op_parse_schema(
     conn.auto_commit.store(false, Ordering::SeqCst);
 
     let enable_triggers = conn.experimental_triggers_enabled();
-    let maybe_nested_stmt_err = if let Some(where_clause) = where_clause {
-        let stmt = conn.prepare(format!("SELECT * FROM sqlite_schema WHERE {where_clause}"))?;
-
-        conn.with_schema_mut(|schema| {
-            // TODO: This function below is synchronous, make it async
-            let existing_views = schema.incremental_views.clone();
-            conn.start_nested();
-            parse_schema_rows(
-                stmt,
-                schema,
-                &conn.syms.read(),
-                program.connection.get_mv_tx(),
-                existing_views,
-                enable_triggers,
-            )
-        })
+    let sqlite_schema = if *db == 0 {
+        "sqlite_schema".to_string()
     } else {
-        let stmt = conn.prepare("SELECT * FROM sqlite_schema")?;
-
-        conn.with_schema_mut(|schema| {
-            // TODO: This function below is synchronous, make it async
-            let existing_views = schema.incremental_views.clone();
-            conn.start_nested();
-            parse_schema_rows(
-                stmt,
-                schema,
-                &conn.syms.read(),
-                program.connection.get_mv_tx(),
-                existing_views,
-                enable_triggers,
-            )
-        })
+        format!(
+            "\"{}\".sqlite_schema",
+            conn.database_name(*db).replace('"', "\"\"")
+        )
+    };
+    let sql = match where_clause {
+        Some(where_clause) => format!("SELECT * FROM {sqlite_schema} WHERE {where_clause}"),
+        None => format!("SELECT * FROM {sqlite_schema}"),
     };
+    let stmt = conn.prepare(sql)?;
+    let maybe_nested_stmt_err = conn.with_database_schema_mut(*db, |schema| {
+        // TODO: This function below is synchronous, make it async
+        let existing_views = schema.incremental_views.clone();
+        conn.start_nested();
+        parse_schema_rows(
+            stmt,
+            schema,
+            &conn.syms.read(),
+            program.connection.get_mv_tx(),
+            existing_views,
+            enable_triggers,
+        )
+    });
     conn.end_nested();
     conn.auto_commit
         .store(previous_auto_commit, Ordering::SeqCst);
@@ -7991,6 +8210,41 @@ pub fn op_parse_schema(
     Ok(InsnFunctionStepResult::Step)
 }
 
+pub fn op_use_namespace(
+    program: &Program,
+    state: &mut ProgramState,
+    insn: &Insn,
+    _pager: &Arc<Pager>,
+) -> Result<InsnFunctionStepResult> {
+    load_insn!(UseNamespace { namespace }, insn);
+
+    let conn = program.connection.clone();
+    let schema = match namespace {
+        None => conn.db.clone_schema(),
+        Some(namespace) => match conn.db.namespace_schema(namespace) {
+            Some(schema) => schema,
+            None => {
+                // The schema table of the namespace is read with a nested statement, see
+                // op_parse_schema.
+                let previous_auto_commit = conn.auto_commit.load(Ordering::SeqCst);
+                conn.auto_commit.store(false, Ordering::SeqCst);
+                conn.start_nested();
+                let result = conn.parse_namespace_schema(namespace, &conn.db.clone_schema());
+                conn.end_nested();
+                conn.auto_commit
+                    .store(previous_auto_commit, Ordering::SeqCst);
+                let schema = Arc::new(result?);
+                conn.db.update_schema_if_newer(schema.clone());
+                schema
+            }
+        },
+    };
+    *conn.schema.write() = schema;
+
+    state.pc += 1;
+    Ok(InsnFunctionStepResult::Step)
+}
+
 pub fn op_populate_materialized_views(
     program: &Program,
     state: &mut ProgramState,
@@ -8076,7 +8330,9 @@ pub fn op_read_cookie(
     load_insn!(ReadCookie { db, dest, cookie }, insn);
     if *db > 0 {
         // TODO: implement temp databases
-        todo!("temp databases not implemented yet");
+        return Err(LimboError::InternalError(
+            "temp databases not implemented yet".to_string(),
+        ));
     }
     let mv_store = program.connection.mv_store();
 
@@ -8112,14 +8368,12 @@ pub fn op_set_cookie(
         },
         insn
     );
-    if *db > 0 {
-        todo!("temp databases not implemented yet");
-    }
+    let pager = program.get_pager_from_database_index(db);
     let mv_store = program.connection.mv_store();
 
     return_if_io!(with_header_mut(
-        pager,
-        mv_store.as_ref(),
+        &pager,
+        mv_store.as_ref().filter(|_| *db == 0),
         program,
         |header| {
             match cookie {
@@ -8131,6 +8385,18 @@ pub fn op_set_cookie(
                 Cookie::IncrementalVacuum => {
                     header.incremental_vacuum_enabled = (*value as u32).into()
                 }
+                Cookie::SchemaVersion if *db != 0 => {
+                    program.connection.set_attached_tx_state(
+                        *db,
+                        TransactionState::Write {
+                            schema_did_change: true,
+                        },
+                    );
+                    program.connection.with_database_schema_mut(*db, |schema| {
+                        schema.schema_version = *value as u32
+                    });
+                    header.schema_cookie = (*value as u32).into();
+                }
                 Cookie::SchemaVersion => {
                     // we update transaction state to indicate that the schema has changed
                     match program.connection.get_tx_state() {
@@ -8366,7 +8632,6 @@ pub fn op_open_ephemeral(
                 }));
             let conn = program.connection.clone();
             let io = conn.pager.load().io.clone();
-            let rand_num = io.generate_random_number();
             let db_file: Arc<dyn DatabaseStorage>;
             let db_file_io: Arc<dyn crate::IO>;
 
@@ -8383,17 +8648,12 @@ pub fn op_open_ephemeral(
             }
             #[cfg(not(target_family = "wasm"))]
             {
-                let temp_dir = temp_dir();
-                let rand_path =
-                    std::path::Path::new(&temp_dir).join(format!("tursodb-ephemeral-{rand_num}"));
-                let Some(rand_path_str) = rand_path.to_str() else {
-                    return Err(LimboError::InternalError(
-                        "Failed to convert path to string".to_string(),
-                    ));
-                };
-                let file = io.open_file(rand_path_str, OpenFlags::Create, false)?;
-                db_file = Arc::new(DatabaseFile::new(file));
+                // The file lives in the temp directory of the connection, next to the temp
+                // database and the sorter chunks, and is removed when the statement is reset.
+                let temp_file = crate::io::TempFile::new(&io, conn.temp_directory())?;
+                db_file = Arc::new(DatabaseFile::new(temp_file.file.clone()));
                 db_file_io = io;
+                state.ephemeral_files.push(temp_file);
             }
 
             let buffer_pool = program.connection.db.buffer_pool.clone();
@@ -8766,6 +9026,7 @@ pub enum OpIntegrityCheckState {
     Start,
     Checking {
         errors: Vec<IntegrityCheckError>,
+        roots: Vec<i64>,
         current_root_idx: usize,
         state: IntegrityCheckState,
     },
@@ -8780,6 +9041,7 @@ pub fn op_integrity_check(
         IntegrityCk {
             max_errors,
             roots,
+            roots_rowset,
             message_register,
         },
         insn
@@ -8792,15 +9054,25 @@ pub fn op_integrity_check(
                     header.freelist_trunk_page.get(),
                     header.database_size.get()
                 )));
+            let expected_freelist_count = if freelist_trunk_page > 0 {
+                return_if_io!(with_header(pager, mv_store.as_ref(), program, |header| {
+                    header.freelist_pages.get()
+                }))
+            } else {
+                0
+            };
             let mut errors = Vec::new();
             let mut integrity_check_state = IntegrityCheckState::new(db_size as usize);
             let mut current_root_idx = 0;
+            // Drained only once no IO is pending, as the rowset can't be read again.
+            let mut roots = roots.clone();
+            if let Some(rowset) = roots_rowset.and_then(|reg| state.rowsets.get_mut(&reg)) {
+                while let Some(root) = rowset.smallest() {
+                    roots.push(root);
+                }
+            }
             // check freelist pages first, if there are any for database
             if freelist_trunk_page > 0 {
-                let expected_freelist_count =
-                    return_if_io!(with_header(pager, mv_store.as_ref(), program, |header| {
-                        header.freelist_pages.get()
-                    }));
                 integrity_check_state.set_expected_freelist_count(expected_freelist_count as usize);
                 integrity_check_state.start(
                     freelist_trunk_page as i64,
@@ -8813,12 +9085,14 @@ pub fn op_integrity_check(
             }
             state.op_integrity_check_state = OpIntegrityCheckState::Checking {
                 errors,
+                roots,
                 state: integrity_check_state,
                 current_root_idx,
             };
         }
         OpIntegrityCheckState::Checking {
             errors,
+            roots,
             current_root_idx,
             state: integrity_check_state,
         } => {
@@ -8928,14 +9202,14 @@ pub fn op_rename_table(
     insn: &Insn,
     pager: &Arc<Pager>,
 ) -> Result<InsnFunctionStepResult> {
-    load_insn!(RenameTable { from, to }, insn);
+    load_insn!(RenameTable { db, from, to }, insn);
 
     let normalized_from = normalize_ident(from.as_str());
     let normalized_to = normalize_ident(to.as_str());
 
     let conn = program.connection.clone();
 
-    conn.with_schema_mut(|schema| {
+    conn.with_database_schema_mut(*db, |schema| {
         if let Some(mut indexes) = schema.indexes.remove(&normalized_from) {
             indexes.iter_mut().for_each(|index| {
                 let index = Arc::make_mut(index);
@@ -8999,6 +9273,7 @@ pub fn op_drop_column(
 ) -> Result<InsnFunctionStepResult> {
     load_insn!(
         DropColumn {
+            db,
             table,
             column_index
         },
@@ -9009,8 +9284,7 @@ pub fn op_drop_column(
 
     let normalized_table_name = normalize_ident(table.as_str());
 
-    let column_name = {
-        let schema = conn.schema.read();
+    let column_name = conn.with_schema(*db, |schema| {
         let table = schema
             .tables
             .get(&normalized_table_name)
@@ -9022,15 +9296,15 @@ pub fn op_drop_column(
             .as_ref()
             .expect("column being ALTERed should be named")
             .clone()
-    };
+    });
 
-    conn.with_schema_mut(|schema| {
+    conn.with_database_schema_mut(*db, |schema| {
         let table = schema
             .tables
             .get_mut(&normalized_table_name)
             .expect("table being renamed should be in schema");
 
-        let table = Arc::get_mut(table).expect("this should be the only strong reference");
+        let table = Arc::make_mut(table);
 
         let Table::BTree(btree) = table else {
             panic!("only btree tables can be renamed");
@@ -9040,8 +9314,7 @@ pub fn op_drop_column(
         btree.columns.remove(*column_index)
     });
 
-    {
-        let schema = conn.schema.read();
+    conn.with_schema(*db, |schema| {
         if let Some(indexes) = schema.indexes.get(&normalized_table_name) {
             for index in indexes {
                 if index
@@ -9055,14 +9328,15 @@ pub fn op_drop_column(
                 }
             }
         }
-    }
+        Ok(())
+    })?;
 
     // Update index.pos_in_table for all indexes.
     // For example, if the dropped column had index 2, then anything that was indexed on column 3 or higher should be decremented by 1.
-    conn.with_schema_mut(|schema| {
+    conn.with_database_schema_mut(*db, |schema| {
         if let Some(indexes) = schema.indexes.get_mut(&normalized_table_name) {
             for index in indexes {
-                let index = Arc::get_mut(index).expect("this should be the only strong reference");
+                let index = Arc::make_mut(index);
                 for index_column in index.columns.iter_mut() {
                     if index_column.pos_in_table > *column_index {
                         index_column.pos_in_table -= 1;
@@ -9072,8 +9346,7 @@ pub fn op_drop_column(
         }
     });
 
-    {
-        let schema = conn.schema.read();
+    conn.with_schema(*db, |schema| {
         for (view_name, view) in schema.views.iter() {
             let view_select_sql = format!("SELECT * FROM {view_name}");
             let _ = conn.prepare(view_select_sql.as_str()).map_err(|e| {
@@ -9083,7 +9356,8 @@ pub fn op_drop_column(
                 ))
             })?;
         }
-    }
+        Ok::<(), LimboError>(())
+    })?;
 
     state.pc += 1;
     Ok(InsnFunctionStepResult::Step)
@@ -9095,11 +9369,11 @@ pub fn op_add_column(
     insn: &Insn,
     pager: &Arc<Pager>,
 ) -> Result<InsnFunctionStepResult> {
-    load_insn!(AddColumn { table, column }, insn);
+    load_insn!(AddColumn { db, table, column }, insn);
 
     let conn = program.connection.clone();
 
-    conn.with_schema_mut(|schema| {
+    conn.with_database_schema_mut(*db, |schema| {
         let table = schema
             .tables
             .get_mut(table)
@@ -9127,6 +9401,7 @@ pub fn op_alter_column(
 ) -> Result<InsnFunctionStepResult> {
     load_insn!(
         AlterColumn {
+            db,
             table: table_name,
             column_index,
             definition,
@@ -9138,8 +9413,7 @@ pub fn op_alter_column(
     let conn = program.connection.clone();
 
     let normalized_table_name = normalize_ident(table_name.as_str());
-    let old_column_name = {
-        let schema = conn.schema.read();
+    let old_column_name = conn.with_schema(*db, |schema| {
         let table = schema
             .tables
             .get(&normalized_table_name)
@@ -9151,11 +9425,11 @@ pub fn op_alter_column(
             .as_ref()
             .expect("column being ALTERed should be named")
             .clone()
-    };
+    });
     let new_column = crate::schema::Column::from(definition.as_ref());
     let new_name = definition.col_name.as_str().to_owned();
 
-    conn.with_schema_mut(|schema| {
+    conn.with_database_schema_mut(*db, |schema| {
         let table_arc = schema
             .tables
             .get_mut(&normalized_table_name)
@@ -9244,25 +9518,27 @@ pub fn op_alter_column(
         }
     });
 
-    let schema = conn.schema.read();
     if *rename {
-        let table = schema
-            .tables
-            .get(&normalized_table_name)
-            .expect("table being ALTERed should be in schema");
-        let column = table
-            .get_column_at(*column_index)
-            .expect("column being ALTERed should be in schema");
-        for (view_name, view) in schema.views.iter() {
-            let view_select_sql = format!("SELECT * FROM {view_name}");
-            // FIXME: this should rewrite the view to reference the new column name
-            let _ = conn.prepare(view_select_sql.as_str()).map_err(|e| {
-                LimboError::ParseError(format!(
-                    "cannot rename column \"{}\": referenced in VIEW {view_name}: {}",
-                    old_column_name, view.sql,
-                ))
-            })?;
-        }
+        conn.with_schema(*db, |schema| {
+            let table = schema
+                .tables
+                .get(&normalized_table_name)
+                .expect("table being ALTERed should be in schema");
+            let column = table
+                .get_column_at(*column_index)
+                .expect("column being ALTERed should be in schema");
+            for (view_name, view) in schema.views.iter() {
+                let view_select_sql = format!("SELECT * FROM {view_name}");
+                // FIXME: this should rewrite the view to reference the new column name
+                let _ = conn.prepare(view_select_sql.as_str()).map_err(|e| {
+                    LimboError::ParseError(format!(
+                        "cannot rename column \"{}\": referenced in VIEW {view_name}: {}",
+                        old_column_name, view.sql,
+                    ))
+                })?;
+            }
+            Ok::<(), LimboError>(())
+        })?;
     }
 
     state.pc += 1;
@@ -9317,6 +9593,7 @@ fn handle_text_sum(acc: &mut Value, sum_state: &mut SumAggState, parsed_number:
             _ => unreachable!(),
         },
         ParsedNumber::Float(f) => {
+            sum_state.ovrfl = false;
             if !sum_state.approx {
                 if let Value::Integer(current_sum) = *acc {
                     *acc = Value::Float(current_sum as f64);
@@ -9328,6 +9605,7 @@ fn handle_text_sum(acc: &mut Value, sum_state: &mut SumAggState, parsed_number:
             apply_kbn_step(acc, f, sum_state);
         }
         ParsedNumber::None => {
+            sum_state.ovrfl = false;
             if !sum_state.approx {
                 if let Value::Integer(current_sum) = *acc {
                     *acc = Value::Float(current_sum as f64);
@@ -9448,7 +9726,11 @@ pub fn op_hash_build(
                 num_keys: data.num_keys,
                 collations: data.collations.clone(),
             };
-            HashTable::new(config, pager.io.clone())
+            HashTable::new(
+                config,
+                pager.io.clone(),
+                program.connection.temp_directory().clone(),
+            )
         });
 
     // Read pre-computed key values directly from registers
@@ -10064,6 +10346,7 @@ fn op_journal_mode_inner(
                             upper_bound_inclusive: None,
                         },
                         program.connection.get_sync_mode(),
+                        program.connection.get_data_sync_retry(),
                         false, // Don't clear cache yet, we'll do it in Finalize
                     );
                     return_if_io!(checkpoint_result);
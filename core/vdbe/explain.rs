@@ -1387,16 +1387,16 @@ pub fn insn_to_row(
             Insn::Destroy {
                 root,
                 former_root_reg,
-                is_temp,
+                db,
             } => (
                 "Destroy",
                 *root,
                 *former_root_reg as i64,
-                *is_temp as i64,
+                *db as i64,
                 Value::build_text(""),
                 0,
                 format!(
-                    "root iDb={root} former_root={former_root_reg} is_temp={is_temp}"
+                    "root iDb={root} former_root={former_root_reg} db={db}"
                 ),
             ),
             Insn::ResetSorter { cursor_id } => (
@@ -1488,6 +1488,15 @@ pub fn insn_to_row(
                 0,
                 where_clause.clone().unwrap_or_else(|| "NULL".to_string()),
             ),
+            Insn::UseNamespace { namespace } => (
+                "UseNamespace",
+                0,
+                0,
+                0,
+                Value::build_text(namespace.clone().unwrap_or_else(|| "main".to_string())),
+                0,
+                format!("namespace={}", namespace.as_deref().unwrap_or("main")),
+            ),
             Insn::PopulateMaterializedViews { cursors } => (
                 "PopulateMaterializedViews",
                 0,
@@ -1795,6 +1804,7 @@ pub fn insn_to_row(
             Insn::IntegrityCk {
                 max_errors,
                 roots,
+                roots_rowset,
                 message_register,
             } => (
                 "IntegrityCk",
@@ -1803,7 +1813,12 @@ pub fn insn_to_row(
                 0,
                 Value::build_text(""),
                 0,
-                format!("roots={roots:?} message_register={message_register}"),
+                match roots_rowset {
+                    Some(rowset_reg) => format!(
+                        "roots={roots:?} roots_rowset=r[{rowset_reg}] message_register={message_register}"
+                    ),
+                    None => format!("roots={roots:?} message_register={message_register}"),
+                },
             ),
             Insn::RowData { cursor_id, dest } => (
                 "RowData",
@@ -1823,36 +1838,36 @@ pub fn insn_to_row(
                 0,
                 format!("affinity(r[{}]={:?})", *reg, affinity),
             ),
-            Insn::RenameTable { from, to } => (
+            Insn::RenameTable { db, from, to } => (
                 "RenameTable",
-                0,
+                *db as i64,
                 0,
                 0,
                 Value::build_text(""),
                 0,
                 format!("rename_table({from}, {to})"),
             ),
-            Insn::DropColumn { table, column_index } => (
+            Insn::DropColumn { db, table, column_index } => (
                 "DropColumn",
-                0,
+                *db as i64,
                 0,
                 0,
                 Value::build_text(""),
                 0,
                 format!("drop_column({table}, {column_index})"),
             ),
-            Insn::AddColumn { table, column } => (
+            Insn::AddColumn { db, table, column } => (
                 "AddColumn",
-                0,
+                *db as i64,
                 0,
                 0,
                 Value::build_text(""),
                 0,
                 format!("add_column({table}, {column:?})"),
             ),
-            Insn::AlterColumn { table, column_index, definition: column, rename } => (
+            Insn::AlterColumn { db, table, column_index, definition: column, rename } => (
                 "AlterColumn",
-                0,
+                *db as i64,
                 0,
                 0,
                 Value::build_text(""),
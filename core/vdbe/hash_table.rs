@@ -1,6 +1,6 @@
 use crate::{
     error::LimboError,
-    io::{Buffer, Completion, TempFile, IO},
+    io::{Buffer, Completion, TempDirectory, TempFile, IO},
     io_yield_one,
     storage::sqlite3_ondisk::{read_varint, write_varint},
     translate::collate::CollationSeq,
@@ -83,6 +83,9 @@ fn hash_join_key(key_values: &[ValueRef], collations: &[CollationSeq]) -> u64 {
                     CollationSeq::Binary | CollationSeq::Unset => {
                         hasher.write(text.as_bytes());
                     }
+                    // Strings that a registered collation considers equal can differ in any
+                    // way, so they all share a hash and are told apart by `keys_equal`.
+                    CollationSeq::Custom(_) => {}
                 }
             }
             ValueRef::Blob(blob) => {
@@ -619,14 +622,14 @@ struct SpillState {
 }
 
 impl SpillState {
-    fn new(io: &Arc<dyn IO>) -> Result<Self> {
+    fn new(io: &Arc<dyn IO>, temp_directory: &TempDirectory) -> Result<Self> {
         Ok(SpillState {
             partition_buffers: (0..NUM_PARTITIONS)
                 .map(|_| PartitionBuffer::new())
                 .collect(),
             partitions: Vec::new(),
             next_spill_offset: 0,
-            temp_file: TempFile::new(io)?,
+            temp_file: TempFile::new(io, temp_directory)?,
         })
     }
 
@@ -784,6 +787,8 @@ pub struct HashTable {
     state: HashTableState,
     /// IO object for disk operations.
     io: Arc<dyn IO>,
+    /// Directory of the connection that spilled partitions are written to.
+    temp_directory: Arc<TempDirectory>,
     /// Current probe position bucket index.
     probe_bucket_idx: usize,
     /// Current probe entry index within bucket.
@@ -821,7 +826,11 @@ enum SpillAction {
 
 impl HashTable {
     /// Create a new hash table.
-    pub fn new(config: HashTableConfig, io: Arc<dyn IO>) -> Self {
+    pub fn new(
+        config: HashTableConfig,
+        io: Arc<dyn IO>,
+        temp_directory: Arc<TempDirectory>,
+    ) -> Self {
         let buckets = (0..config.initial_buckets)
             .map(|_| HashBucket::new())
             .collect();
@@ -834,6 +843,7 @@ impl HashTable {
             collations: config.collations,
             state: HashTableState::Building,
             io,
+            temp_directory,
             probe_bucket_idx: 0,
             probe_entry_idx: 0,
             current_probe_keys: None,
@@ -891,7 +901,7 @@ impl HashTable {
                 );
                 // First time exceeding budget, trigger spill
                 // Move all existing bucket entries into partition buffers
-                self.spill_state = Some(SpillState::new(&self.io)?);
+                self.spill_state = Some(SpillState::new(&self.io, &self.temp_directory)?);
                 self.redistribute_to_partitions();
                 self.state = HashTableState::Spilled;
             };
@@ -1718,7 +1728,7 @@ mod hashtests {
             num_keys: 1,
             collations: vec![CollationSeq::Binary],
         };
-        let mut ht = HashTable::new(config, io);
+        let mut ht = HashTable::new(config, io, Arc::new(TempDirectory::default()));
 
         // Insert some entries (late materialization - only store rowids)
         let key1 = vec![Value::Integer(1)];
@@ -1757,7 +1767,7 @@ mod hashtests {
             num_keys: 1,
             collations: vec![CollationSeq::Binary],
         };
-        let mut ht = HashTable::new(config, io);
+        let mut ht = HashTable::new(config, io, Arc::new(TempDirectory::default()));
 
         // Insert multiple entries (late materialization - only store rowids)
         for i in 0..10 {
@@ -1786,7 +1796,7 @@ mod hashtests {
             num_keys: 1,
             collations: vec![CollationSeq::Binary],
         };
-        let mut ht = HashTable::new(config, io);
+        let mut ht = HashTable::new(config, io, Arc::new(TempDirectory::default()));
 
         // Insert multiple entries with the same key
         let key = vec![Value::Integer(42)];
@@ -2003,7 +2013,7 @@ mod hashtests {
             num_keys: 1,
             collations: vec![CollationSeq::Binary],
         };
-        let mut ht = HashTable::new(config, io);
+        let mut ht = HashTable::new(config, io, Arc::new(TempDirectory::default()));
 
         // Insert enough fat rows to exceed budget and force spills
         insert_many_force_spill(&mut ht, 0, 1024);
@@ -2042,7 +2052,7 @@ mod hashtests {
             num_keys: 1,
             collations: vec![CollationSeq::Binary],
         };
-        let mut ht = HashTable::new(config, io);
+        let mut ht = HashTable::new(config, io, Arc::new(TempDirectory::default()));
 
         // Insert two disjoint key ranges that will hash to different partitions
         insert_many_force_spill(&mut ht, 0, 256);
@@ -2083,7 +2093,7 @@ mod hashtests {
             num_keys: 1,
             collations: vec![CollationSeq::Binary],
         };
-        let mut ht = HashTable::new(config, io);
+        let mut ht = HashTable::new(config, io, Arc::new(TempDirectory::default()));
 
         let key = vec![Value::Integer(42)];
         for i in 0..1024 {
@@ -2127,7 +2137,7 @@ mod hashtests {
             num_keys: 1,
             collations: vec![CollationSeq::Binary],
         };
-        let mut ht = HashTable::new(config, io);
+        let mut ht = HashTable::new(config, io, Arc::new(TempDirectory::default()));
 
         // Insert entries with payload values (simulating cached result columns)
         let key1 = vec![Value::Integer(1)];
@@ -2178,7 +2188,7 @@ mod hashtests {
             num_keys: 1,
             collations: vec![CollationSeq::Binary],
         };
-        let mut ht = HashTable::new(config, io);
+        let mut ht = HashTable::new(config, io, Arc::new(TempDirectory::default()));
 
         // Insert entry with NULL values in payload
         let key = vec![Value::Integer(1)];
@@ -2207,7 +2217,7 @@ mod hashtests {
             num_keys: 2,
             collations: vec![CollationSeq::Binary, CollationSeq::Binary],
         };
-        let mut ht = HashTable::new(config, io);
+        let mut ht = HashTable::new(config, io, Arc::new(TempDirectory::default()));
 
         // Insert entry with NULL key - should be silently skipped
         let null_key = vec![Value::Null, Value::Integer(1)];
@@ -2249,7 +2259,7 @@ mod hashtests {
             num_keys: 1,
             collations: vec![CollationSeq::Binary],
         };
-        let mut ht = HashTable::new(config, io);
+        let mut ht = HashTable::new(config, io, Arc::new(TempDirectory::default()));
 
         // Insert entry with blob payload
         let key = vec![Value::Integer(1)];
@@ -2276,7 +2286,7 @@ mod hashtests {
             num_keys: 1,
             collations: vec![CollationSeq::Binary],
         };
-        let mut ht = HashTable::new(config, io);
+        let mut ht = HashTable::new(config, io, Arc::new(TempDirectory::default()));
 
         // Insert multiple entries with the same key but different payloads
         let key = vec![Value::Integer(42)];
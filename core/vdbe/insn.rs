@@ -1000,8 +1000,8 @@ pub enum Insn {
         root: i64,
         /// Register to store the former value of any moved root page (for AUTOVACUUM)
         former_root_reg: usize,
-        /// Whether this is a temporary table (1) or main database table (0)
-        is_temp: usize,
+        /// The database the table/index is in (P3)
+        db: usize,
     },
 
     /// Deletes all contents from the ephemeral table that the cursor points to.
@@ -1081,6 +1081,13 @@ pub enum Insn {
         where_clause: Option<String>,
     },
 
+    /// Make the connection use the given namespace, or the default namespace if `None`, as its
+    /// main database. The schema of the namespace is read from its schema table if no other
+    /// connection loaded it yet.
+    UseNamespace {
+        namespace: Option<String>,
+    },
+
     /// Populate all materialized views after schema parsing
     /// The cursors parameter contains a mapping of view names to cursor IDs that have been
     /// opened to the view's btree for writing the materialized data
@@ -1236,24 +1243,30 @@ pub enum Insn {
     /// At most reg(P1) errors will be reported. In other words, the analysis stops as soon as reg(P1) errors are seen.
     /// Reg(P1) is updated with the number of errors remaining. The root page numbers of all tables in the database are integers
     /// stored in P4_INTARRAY argument. If P5 is not zero, the check is done on the auxiliary database file, not the main database file. This opcode is used to implement the integrity_check pragma.
+    /// The root pages found in the RowSet held in `roots_rowset`, if any, are checked after `roots`.
     IntegrityCk {
         max_errors: usize,
         roots: Vec<i64>,
+        roots_rowset: Option<usize>,
         message_register: usize,
     },
     RenameTable {
+        db: usize,
         from: String,
         to: String,
     },
     DropColumn {
+        db: usize,
         table: String,
         column_index: usize,
     },
     AddColumn {
+        db: usize,
         table: String,
         column: Box<Column>,
     },
     AlterColumn {
+        db: usize,
         table: String,
         column_index: usize,
         definition: Box<turso_parser::ast::ColumnDefinition>,
@@ -1508,6 +1521,7 @@ impl InsnVariants {
             InsnVariants::IsNull => execute::op_is_null,
             InsnVariants::CollSeq => execute::op_coll_seq,
             InsnVariants::ParseSchema => execute::op_parse_schema,
+            InsnVariants::UseNamespace => execute::op_use_namespace,
             InsnVariants::PopulateMaterializedViews => execute::op_populate_materialized_views,
             InsnVariants::ShiftRight => execute::op_shift_right,
             InsnVariants::ShiftLeft => execute::op_shift_left,
@@ -1,32 +1,46 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 
 use regex::{Regex, RegexBuilder};
 
-use crate::{types::Value, LimboError};
-
-pub fn construct_like_escape_arg(escape_value: &Value) -> Result<char, LimboError> {
-    match escape_value {
-        Value::Text(text) => {
-            let mut escape_chars = text.as_str().chars();
-            match (escape_chars.next(), escape_chars.next()) {
-                (Some(escape), None) => Ok(escape),
-                _ => Err(LimboError::Constraint(
-                    "ESCAPE expression must be a single character".to_string(),
-                )),
-            }
-        }
-        _ => {
-            unreachable!("Like on non-text registers");
-        }
+use crate::{error::SQLITE_ERROR, types::Value, LimboError};
+
+/// Returns the escape character of a LIKE expression, or `None` if the escape is NULL,
+/// in which case the whole LIKE expression evaluates to NULL.
+pub fn construct_like_escape_arg(escape_value: &Value) -> Result<Option<char>, LimboError> {
+    let escape_value = match escape_value {
+        Value::Null => return Ok(None),
+        Value::Text(_) => Cow::Borrowed(escape_value),
+        _ => Cow::Owned(escape_value.exec_cast("TEXT")),
+    };
+    let Value::Text(text) = escape_value.as_ref() else {
+        unreachable!("escape value must have been cast to text");
+    };
+    let mut escape_chars = text.as_str().chars();
+    match (escape_chars.next(), escape_chars.next()) {
+        (Some(escape), None) => Ok(Some(escape)),
+        _ => Err(LimboError::Constraint {
+            code: SQLITE_ERROR,
+            message: "ESCAPE expression must be a single character".to_string(),
+        }),
     }
 }
 
-// Implements LIKE pattern matching with escape
-pub fn exec_like_with_escape(pattern: &str, text: &str, escape: char) -> bool {
-    construct_like_regex_with_escape(pattern, escape).is_match(text)
+// Implements LIKE pattern matching with an optional escape character
+pub fn exec_like_with_escape(
+    pattern: &str,
+    text: &str,
+    escape: Option<char>,
+    case_sensitive: bool,
+) -> bool {
+    construct_like_regex_with_escape(pattern, escape, case_sensitive).is_match(text)
 }
 
-fn construct_like_regex_with_escape(pattern: &str, escape: char) -> Regex {
+fn construct_like_regex_with_escape(
+    pattern: &str,
+    escape: Option<char>,
+    case_sensitive: bool,
+) -> Regex {
     let mut regex_pattern = String::with_capacity(pattern.len() * 2);
 
     regex_pattern.push('^');
@@ -35,7 +49,7 @@ fn construct_like_regex_with_escape(pattern: &str, escape: char) -> Regex {
 
     while let Some(ch) = chars.next() {
         match ch {
-            esc_ch if esc_ch == escape => {
+            esc_ch if Some(esc_ch) == escape => {
                 if let Some(escaped_char) = chars.next() {
                     if regex_syntax::is_meta_character(escaped_char) {
                         regex_pattern.push('\\');
@@ -46,7 +60,8 @@ fn construct_like_regex_with_escape(pattern: &str, escape: char) -> Regex {
             '%' => regex_pattern.push_str(".*"),
             '_' => regex_pattern.push('.'),
             c => {
-                if c.is_ascii_alphabetic() {
+                // LIKE only folds the case of ASCII characters, like SQLite does.
+                if c.is_ascii_alphabetic() && !case_sensitive {
                     regex_pattern.push('[');
                     regex_pattern.push(c.to_ascii_lowercase());
                     regex_pattern.push(c.to_ascii_uppercase());
@@ -94,6 +109,32 @@ pub fn exec_glob(
     }
 }
 
+/// Implements the REGEXP operator. The pattern is a regular expression in the syntax of the
+/// `regex` crate, and matches if it is found anywhere in the text. Caches the compiled regex
+/// if a cache is provided.
+#[cfg(feature = "regexp")]
+pub fn exec_regexp(
+    regex_cache: Option<&mut HashMap<String, Regex>>,
+    pattern: &str,
+    text: &str,
+) -> Result<bool, LimboError> {
+    let compile = |pattern: &str| {
+        Regex::new(pattern).map_err(|e| LimboError::InvalidArgument(format!("regexp: {e}")))
+    };
+    match regex_cache {
+        Some(cache) => match cache.get(pattern) {
+            Some(re) => Ok(re.is_match(text)),
+            None => {
+                let re = compile(pattern)?;
+                let res = re.is_match(text);
+                cache.insert(pattern.to_string(), re);
+                Ok(res)
+            }
+        },
+        None => Ok(compile(pattern)?.is_match(text)),
+    }
+}
+
 fn push_char_to_regex_pattern(c: char, regex_pattern: &mut String) {
     if regex_syntax::is_meta_character(c) {
         regex_pattern.push('\\');
@@ -182,9 +223,10 @@ fn construct_glob_regex(pattern: &str) -> Result<Regex, LimboError> {
         Regex::new(&regex_pattern)
             .map_err(|e| LimboError::InternalError(format!("invalid GLOB regex pattern: {e}")))
     } else {
-        Err(LimboError::Constraint(
-            "blob pattern is not closed".to_string(),
-        ))
+        Err(LimboError::Constraint {
+            code: SQLITE_ERROR,
+            message: "blob pattern is not closed".to_string(),
+        })
     }
 }
 
@@ -194,20 +236,28 @@ mod test {
 
     #[test]
     fn test_exec_like_with_escape() {
-        assert!(exec_like_with_escape("abcX%", "abc%", 'X'));
-        assert!(!exec_like_with_escape("abcX%", "abc5", 'X'));
-        assert!(!exec_like_with_escape("abcX%", "abc", 'X'));
-        assert!(!exec_like_with_escape("abcX%", "abcX%", 'X'));
-        assert!(!exec_like_with_escape("abcX%", "abc%%", 'X'));
-        assert!(exec_like_with_escape("abcX_", "abc_", 'X'));
-        assert!(!exec_like_with_escape("abcX_", "abc5", 'X'));
-        assert!(!exec_like_with_escape("abcX_", "abc", 'X'));
-        assert!(!exec_like_with_escape("abcX_", "abcX_", 'X'));
-        assert!(!exec_like_with_escape("abcX_", "abc__", 'X'));
-        assert!(exec_like_with_escape("abcXX", "abcX", 'X'));
-        assert!(!exec_like_with_escape("abcXX", "abc5", 'X'));
-        assert!(!exec_like_with_escape("abcXX", "abc", 'X'));
-        assert!(!exec_like_with_escape("abcXX", "abcXX", 'X'));
+        assert!(exec_like_with_escape("abcX%", "abc%", Some('X'), false));
+        assert!(!exec_like_with_escape("abcX%", "abc5", Some('X'), false));
+        assert!(!exec_like_with_escape("abcX%", "abc", Some('X'), false));
+        assert!(!exec_like_with_escape("abcX%", "abcX%", Some('X'), false));
+        assert!(!exec_like_with_escape("abcX%", "abc%%", Some('X'), false));
+        assert!(exec_like_with_escape("abcX_", "abc_", Some('X'), false));
+        assert!(!exec_like_with_escape("abcX_", "abc5", Some('X'), false));
+        assert!(!exec_like_with_escape("abcX_", "abc", Some('X'), false));
+        assert!(!exec_like_with_escape("abcX_", "abcX_", Some('X'), false));
+        assert!(!exec_like_with_escape("abcX_", "abc__", Some('X'), false));
+        assert!(exec_like_with_escape("abcXX", "abcX", Some('X'), false));
+        assert!(!exec_like_with_escape("abcXX", "abc5", Some('X'), false));
+        assert!(!exec_like_with_escape("abcXX", "abc", Some('X'), false));
+        assert!(!exec_like_with_escape("abcXX", "abcXX", Some('X'), false));
+    }
+
+    #[test]
+    fn test_exec_like_case_sensitive() {
+        assert!(exec_like_with_escape("ab%", "ABC", None, false));
+        assert!(!exec_like_with_escape("ab%", "ABC", None, true));
+        assert!(exec_like_with_escape("Ab_", "Abc", None, true));
+        assert!(!exec_like_with_escape("ä", "Ä", None, false));
     }
 
     #[test]
@@ -221,4 +271,16 @@ mod test {
         assert!(exec_glob(None, r#"abc[^][*?]efg"#, r#"abcdefg"#));
         assert!(!exec_glob(None, r#"abc[^][*?]efg"#, r#"abc]efg"#));
     }
+
+    #[test]
+    #[cfg(feature = "regexp")]
+    fn test_regexp_cache() {
+        let mut cache = HashMap::new();
+        assert!(exec_regexp(Some(&mut cache), "^ab+", "abbb").unwrap());
+        assert!(!exec_regexp(Some(&mut cache), "^ab+", "xab").unwrap());
+        assert_eq!(cache.len(), 1);
+        assert!(exec_regexp(None, "b$", "xab").unwrap());
+        assert!(exec_regexp(Some(&mut cache), "(", "x").is_err());
+        assert_eq!(cache.len(), 1);
+    }
 }
@@ -1,4 +1,4 @@
-use std::fmt;
+use std::{collections::HashMap, fmt, time::Duration};
 
 /// Statement-level execution metrics
 ///
@@ -10,6 +10,7 @@ pub struct StatementMetrics {
     // Row operations
     pub rows_read: u64,
     pub rows_written: u64,
+    pub rows_returned: u64,
 
     // Execution statistics
     pub vm_steps: u64,
@@ -27,6 +28,15 @@ pub struct StatementMetrics {
     pub btree_seeks: u64,
     pub btree_next: u64,
     pub btree_prev: u64,
+
+    // Page cache and I/O
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    pub pages_read: u64,
+    pub pages_written: u64,
+
+    /// Time spent parsing and translating the statement
+    pub prepare_time: Duration,
 }
 
 impl StatementMetrics {
@@ -43,6 +53,7 @@ impl StatementMetrics {
     pub fn merge(&mut self, other: &StatementMetrics) {
         self.rows_read = self.rows_read.saturating_add(other.rows_read);
         self.rows_written = self.rows_written.saturating_add(other.rows_written);
+        self.rows_returned = self.rows_returned.saturating_add(other.rows_returned);
         self.vm_steps = self.vm_steps.saturating_add(other.vm_steps);
         self.insn_executed = self.insn_executed.saturating_add(other.insn_executed);
         self.fullscan_steps = self.fullscan_steps.saturating_add(other.fullscan_steps);
@@ -54,6 +65,36 @@ impl StatementMetrics {
         self.btree_seeks = self.btree_seeks.saturating_add(other.btree_seeks);
         self.btree_next = self.btree_next.saturating_add(other.btree_next);
         self.btree_prev = self.btree_prev.saturating_add(other.btree_prev);
+        self.cache_hits = self.cache_hits.saturating_add(other.cache_hits);
+        self.cache_misses = self.cache_misses.saturating_add(other.cache_misses);
+        self.pages_read = self.pages_read.saturating_add(other.pages_read);
+        self.pages_written = self.pages_written.saturating_add(other.pages_written);
+        self.prepare_time = self.prepare_time.saturating_add(other.prepare_time);
+    }
+
+    /// Counters accumulated since the `earlier` snapshot was taken. The prepare time is kept.
+    pub fn since(&self, earlier: &StatementMetrics) -> StatementMetrics {
+        StatementMetrics {
+            rows_read: self.rows_read.saturating_sub(earlier.rows_read),
+            rows_written: self.rows_written.saturating_sub(earlier.rows_written),
+            rows_returned: self.rows_returned.saturating_sub(earlier.rows_returned),
+            vm_steps: self.vm_steps.saturating_sub(earlier.vm_steps),
+            insn_executed: self.insn_executed.saturating_sub(earlier.insn_executed),
+            fullscan_steps: self.fullscan_steps.saturating_sub(earlier.fullscan_steps),
+            index_steps: self.index_steps.saturating_sub(earlier.index_steps),
+            sort_operations: self.sort_operations.saturating_sub(earlier.sort_operations),
+            filter_operations: self
+                .filter_operations
+                .saturating_sub(earlier.filter_operations),
+            btree_seeks: self.btree_seeks.saturating_sub(earlier.btree_seeks),
+            btree_next: self.btree_next.saturating_sub(earlier.btree_next),
+            btree_prev: self.btree_prev.saturating_sub(earlier.btree_prev),
+            cache_hits: self.cache_hits.saturating_sub(earlier.cache_hits),
+            cache_misses: self.cache_misses.saturating_sub(earlier.cache_misses),
+            pages_read: self.pages_read.saturating_sub(earlier.pages_read),
+            pages_written: self.pages_written.saturating_sub(earlier.pages_written),
+            prepare_time: self.prepare_time,
+        }
     }
 
     /// Reset all counters to zero
@@ -68,6 +109,7 @@ impl fmt::Display for StatementMetrics {
         writeln!(f, "  Row Operations:")?;
         writeln!(f, "    Rows read:        {}", self.rows_read)?;
         writeln!(f, "    Rows written:     {}", self.rows_written)?;
+        writeln!(f, "    Rows returned:    {}", self.rows_returned)?;
         writeln!(f, "  Execution:")?;
         writeln!(f, "    VM steps:         {}", self.vm_steps)?;
         writeln!(f, "    Instructions:     {}", self.insn_executed)?;
@@ -81,10 +123,33 @@ impl fmt::Display for StatementMetrics {
         writeln!(f, "    Seeks:            {}", self.btree_seeks)?;
         writeln!(f, "    Next:             {}", self.btree_next)?;
         writeln!(f, "    Prev:             {}", self.btree_prev)?;
+        writeln!(f, "  Page Cache and I/O:")?;
+        writeln!(f, "    Cache hits:       {}", self.cache_hits)?;
+        writeln!(f, "    Cache misses:     {}", self.cache_misses)?;
+        writeln!(f, "    Pages read:       {}", self.pages_read)?;
+        writeln!(f, "    Pages written:    {}", self.pages_written)?;
+        writeln!(f, "  Timing:")?;
+        writeln!(f, "    Prepare time:     {:?}", self.prepare_time)?;
         Ok(())
     }
 }
 
+/// Executions and time spent in one instruction, collected while `PRAGMA vdbe_profile` is on.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct InsnProfile {
+    /// Number of times the instruction ran, including resumptions after I/O
+    pub executions: u64,
+    /// Cumulative time spent in the instruction, measured with the I/O clock
+    pub elapsed: Duration,
+}
+
+impl InsnProfile {
+    pub fn merge(&mut self, other: &InsnProfile) {
+        self.executions = self.executions.saturating_add(other.executions);
+        self.elapsed = self.elapsed.saturating_add(other.elapsed);
+    }
+}
+
 /// Connection-level metrics aggregation
 #[derive(Debug, Default, Clone)]
 pub struct ConnectionMetrics {
@@ -100,6 +165,9 @@ pub struct ConnectionMetrics {
     /// High-water marks for monitoring
     pub max_vm_steps_per_statement: u64,
     pub max_rows_read_per_statement: u64,
+
+    /// Per-opcode profile of completed statements, see `PRAGMA vdbe_profile`
+    pub opcode_profile: HashMap<&'static str, InsnProfile>,
 }
 
 impl ConnectionMetrics {
@@ -122,6 +190,14 @@ impl ConnectionMetrics {
         self.last_statement = Some(metrics);
     }
 
+    /// Add the profile of one instruction of a completed statement to its opcode's totals
+    pub fn record_insn_profile(&mut self, opcode: &'static str, profile: &InsnProfile) {
+        self.opcode_profile
+            .entry(opcode)
+            .or_default()
+            .merge(profile);
+    }
+
     /// Reset connection metrics
     pub fn reset(&mut self) {
         *self = Self::default();
@@ -170,10 +246,14 @@ mod tests {
         let mut m2 = StatementMetrics::new();
         m2.rows_read = 200;
         m2.vm_steps = 75;
+        m2.cache_hits = 3;
+        m2.prepare_time = Duration::from_micros(10);
 
         m1.merge(&m2);
         assert_eq!(m1.rows_read, 300);
         assert_eq!(m1.vm_steps, 125);
+        assert_eq!(m1.cache_hits, 3);
+        assert_eq!(m1.prepare_time, Duration::from_micros(10));
     }
 
     #[test]
@@ -196,4 +276,25 @@ mod tests {
         assert_eq!(conn_metrics.aggregate.vm_steps, 175);
         assert_eq!(conn_metrics.aggregate.rows_read, 150);
     }
+
+    #[test]
+    fn test_connection_metrics_opcode_profile() {
+        let mut conn_metrics = ConnectionMetrics::new();
+        let profile = InsnProfile {
+            executions: 3,
+            elapsed: Duration::from_micros(7),
+        };
+        conn_metrics.record_insn_profile("Column", &profile);
+        conn_metrics.record_insn_profile("Column", &profile);
+        conn_metrics.record_insn_profile("Next", &profile);
+
+        assert_eq!(
+            conn_metrics.opcode_profile["Column"],
+            InsnProfile {
+                executions: 6,
+                elapsed: Duration::from_micros(14),
+            }
+        );
+        assert_eq!(conn_metrics.opcode_profile["Next"], profile);
+    }
 }
@@ -48,7 +48,7 @@ use crate::{
             OpNoConflictState, OpProgramState, OpRowIdState, OpSeekState, OpTransactionState,
         },
         hash_table::HashTable,
-        metrics::StatementMetrics,
+        metrics::{InsnProfile, StatementMetrics},
     },
     ValueRef,
 };
@@ -60,6 +60,7 @@ use crate::{
     vdbe::{builder::CursorType, insn::Insn},
 };
 
+use crate::io::TempFile;
 #[cfg(feature = "json")]
 use crate::json::JsonCacheCell;
 use crate::{Connection, MvStore, Result, TransactionState};
@@ -195,6 +196,8 @@ pub enum StepResult {
 struct RegexCache {
     like: HashMap<String, Regex>,
     glob: HashMap<String, Regex>,
+    #[cfg(feature = "regexp")]
+    regexp: HashMap<String, Regex>,
 }
 
 impl RegexCache {
@@ -202,6 +205,8 @@ impl RegexCache {
         Self {
             like: HashMap::new(),
             glob: HashMap::new(),
+            #[cfg(feature = "regexp")]
+            regexp: HashMap::new(),
         }
     }
 }
@@ -217,6 +222,7 @@ impl RegexCache {
 enum CommitState {
     Ready,
     Committing,
+    CommittingAttached,
     CommitingMvcc {
         state_machine: StateMachine<CommitStateMachine<LocalClock>>,
     },
@@ -343,7 +349,12 @@ pub struct ProgramState {
     op_integrity_check_state: OpIntegrityCheckState,
     /// Metrics collected during statement execution
     pub metrics: StatementMetrics,
+    /// Per-instruction profile of the current run, indexed by address. Only collected while
+    /// `PRAGMA vdbe_profile` is on, empty otherwise.
+    pub profile: Vec<InsnProfile>,
     op_open_ephemeral_state: OpOpenEphemeralState,
+    /// Files of the ephemeral tables opened by this run, removed on reset with their cursors.
+    ephemeral_files: Vec<TempFile>,
     op_program_state: OpProgramState,
     op_new_rowid_state: OpNewRowidState,
     op_idx_insert_state: OpIdxInsertState,
@@ -378,6 +389,8 @@ pub struct ProgramState {
     op_hash_probe_state: Option<OpHashProbeState>,
     hash_tables: HashMap<usize, HashTable>,
     uses_subjournal: bool,
+    /// Attached and temp databases holding a savepoint for the current statement.
+    attached_savepoints: Vec<usize>,
 }
 
 impl std::fmt::Debug for Program {
@@ -422,7 +435,9 @@ impl ProgramState {
             op_idx_delete_state: None,
             op_integrity_check_state: OpIntegrityCheckState::Start,
             metrics: StatementMetrics::new(),
+            profile: Vec::new(),
             op_open_ephemeral_state: OpOpenEphemeralState::Start,
+            ephemeral_files: Vec::new(),
             op_program_state: OpProgramState::Start,
             op_new_rowid_state: OpNewRowidState::Start,
             op_idx_insert_state: OpIdxInsertState::MaybeSeek,
@@ -447,6 +462,7 @@ impl ProgramState {
             bloom_filters: HashMap::new(),
             hash_tables: HashMap::new(),
             uses_subjournal: false,
+            attached_savepoints: Vec::new(),
         }
     }
 
@@ -497,9 +513,11 @@ impl ProgramState {
         self.cursors.iter_mut().for_each(|c| {
             let _ = c.take();
         });
+        self.ephemeral_files.clear();
         self.registers
             .iter_mut()
             .for_each(|r| *r = Register::Value(Value::Null));
+        self.result_row = None;
         self.last_compare = None;
         self.deferred_seeks.iter_mut().for_each(|s| *s = None);
         self.ended_coroutine.clear();
@@ -517,6 +535,7 @@ impl ProgramState {
         self.op_idx_delete_state = None;
         self.op_integrity_check_state = OpIntegrityCheckState::Start;
         self.metrics = StatementMetrics::new();
+        self.profile.clear();
         self.op_open_ephemeral_state = OpOpenEphemeralState::Start;
         self.op_new_rowid_state = OpNewRowidState::Start;
         self.op_idx_insert_state = OpIdxInsertState::MaybeSeek;
@@ -558,14 +577,7 @@ impl ProgramState {
         write: bool,
     ) -> Result<IOResult<()>> {
         if write {
-            let db_size = return_if_io!(pager.with_header(|header| header.database_size.get()));
-            pager.open_subjournal()?;
-            pager.try_use_subjournal()?;
-            let result = pager.open_savepoint(db_size);
-            if result.is_err() {
-                pager.stop_use_subjournal();
-            }
-            result?;
+            return_if_io!(open_statement_savepoint(pager));
             self.uses_subjournal = true;
         }
 
@@ -582,6 +594,18 @@ impl ProgramState {
         Ok(IOResult::Done(()))
     }
 
+    /// Begin the statement subtransaction of an attached or temp database written by the
+    /// statement. It ends together with the statement subtransaction of the main database.
+    pub(crate) fn begin_attached_statement(
+        &mut self,
+        pager: &Arc<Pager>,
+        database_id: usize,
+    ) -> Result<IOResult<()>> {
+        return_if_io!(open_statement_savepoint(pager));
+        self.attached_savepoints.push(database_id);
+        Ok(IOResult::Done(()))
+    }
+
     /// End a statement subtransaction.
     pub fn end_statement(
         &mut self,
@@ -615,6 +639,16 @@ impl ProgramState {
             pager.stop_use_subjournal();
             self.uses_subjournal = false;
         }
+        let mut result = result;
+        for database_id in std::mem::take(&mut self.attached_savepoints) {
+            let pager = connection.get_pager_from_database_index(&database_id);
+            let attached_result = match end_statement {
+                EndStatement::ReleaseSavepoint => pager.release_savepoint(),
+                EndStatement::RollbackSavepoint => pager.rollback_to_newest_savepoint().map(|_| ()),
+            };
+            pager.stop_use_subjournal();
+            result = result.and(attached_result);
+        }
         result
     }
 
@@ -656,6 +690,19 @@ impl ProgramState {
     }
 }
 
+/// Opens the savepoint that a statement subtransaction rolls back to on error.
+fn open_statement_savepoint(pager: &Arc<Pager>) -> Result<IOResult<()>> {
+    let db_size = return_if_io!(pager.with_header(|header| header.database_size.get()));
+    pager.open_subjournal()?;
+    pager.try_use_subjournal()?;
+    let result = pager.open_savepoint(db_size);
+    if result.is_err() {
+        pager.stop_use_subjournal();
+    }
+    result?;
+    Ok(IOResult::Done(()))
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 /// Action to take at the end of a statement subtransaction.
 pub enum EndStatement {
@@ -984,6 +1031,7 @@ impl Program {
         waker: Option<&Waker>,
     ) -> Result<StepResult> {
         let enable_tracing = tracing::enabled!(tracing::Level::TRACE);
+        let enable_profile = self.connection.vdbe_profile_enabled();
         loop {
             if self.connection.is_closed() {
                 // Connection is closed for whatever reason, rollback the transaction.
@@ -993,7 +1041,9 @@ impl Program {
                 }
                 return Err(LimboError::InternalError("Connection closed".to_string()));
             }
-            if matches!(state.execution_state, ProgramExecutionState::Interrupting) {
+            if matches!(state.execution_state, ProgramExecutionState::Interrupting)
+                || self.connection.is_interrupted()
+            {
                 self.abort(&pager, None, state);
                 return Ok(StepResult::Interrupt);
             }
@@ -1031,7 +1081,26 @@ impl Program {
             // Always increment VM steps for every loop iteration
             state.metrics.vm_steps = state.metrics.vm_steps.saturating_add(1);
 
-            match insn_function(self, state, insn, &pager) {
+            let pc = state.pc as usize;
+            let profile_start = enable_profile.then(|| pager.io.now());
+            let result = insn_function(self, state, insn, &pager);
+            if let Some(start) = profile_start {
+                if state.profile.len() < self.insns.len() {
+                    state
+                        .profile
+                        .resize(self.insns.len(), InsnProfile::default());
+                }
+                let profile = &mut state.profile[pc];
+                profile.executions += 1;
+                profile.elapsed += pager
+                    .io
+                    .now()
+                    .to_system_time()
+                    .duration_since(start.to_system_time())
+                    .unwrap_or_default();
+            }
+
+            match result {
                 Ok(InsnFunctionStepResult::Step) => {
                     // Instruction completed, moving to next
                     state.metrics.insn_executed = state.metrics.insn_executed.saturating_add(1);
@@ -1168,12 +1237,46 @@ impl Program {
         }
     }
 
+    /// Ends the transaction of the main database, then those of the attached and temp databases.
+    #[cfg_attr(
+        feature = "query_tracing",
+        instrument(skip_all, fields(rollback), level = Level::DEBUG)
+    )]
     pub fn commit_txn(
         &self,
         pager: Arc<Pager>,
         program_state: &mut ProgramState,
         mv_store: Option<&Arc<MvStore>>,
         rollback: bool,
+    ) -> Result<IOResult<()>> {
+        if !matches!(program_state.commit_state, CommitState::CommittingAttached) {
+            return_if_io!(self.commit_main_txn(pager, program_state, mv_store, rollback));
+        }
+        let connection = &self.connection;
+        if !connection.has_attached_transactions() || !connection.get_auto_commit() {
+            return Ok(IOResult::Done(()));
+        }
+        if rollback {
+            connection.rollback_attached_transactions();
+            return Ok(IOResult::Done(()));
+        }
+        program_state.commit_state = CommitState::CommittingAttached;
+        return_if_io!(connection.commit_attached_transactions());
+        program_state.commit_state = CommitState::Ready;
+        // The main database may not have been written, in which case its commit did not
+        // record the changes.
+        if self.change_cnt_on {
+            connection.set_changes(self.n_change.load(Ordering::SeqCst));
+        }
+        Ok(IOResult::Done(()))
+    }
+
+    fn commit_main_txn(
+        &self,
+        pager: Arc<Pager>,
+        program_state: &mut ProgramState,
+        mv_store: Option<&Arc<MvStore>>,
+        rollback: bool,
     ) -> Result<IOResult<()>> {
         // Apply view deltas with I/O handling
         match self.apply_view_deltas(program_state, rollback, &pager)? {
@@ -1285,6 +1388,7 @@ impl Program {
                     // CheckpointFailed means the WAL commit succeeded but autocheckpoint failed.
                     // The transaction is durable - clean up transaction state and propagate the error.
                     tracing::warn!("Commit succeeded but autocheckpoint failed: {}", msg);
+                    connection.after_wal_commit(pager);
                     if self.change_cnt_on {
                         self.connection
                             .set_changes(self.n_change.load(Ordering::SeqCst));
@@ -1307,7 +1411,10 @@ impl Program {
             IOResult::Done(PagerCommitResult::Rollback)
         };
         match cacheflush_status {
-            IOResult::Done(_) => {
+            IOResult::Done(result) => {
+                if !matches!(result, PagerCommitResult::Rollback) {
+                    connection.after_wal_commit(pager);
+                }
                 if self.change_cnt_on {
                     self.connection
                         .set_changes(self.n_change.load(Ordering::SeqCst));
@@ -1380,7 +1487,7 @@ impl Program {
                 // Constraint errors do not cause a rollback of the transaction for interacative transactions;
                 // Instead individual statement subtransactions will roll back
                 // In the auto-commit mode - we rollback current active transaction
-                Some(LimboError::Constraint(_)) => {
+                Some(LimboError::Constraint { .. }) => {
                     if self.connection.get_auto_commit() {
                         self.rollback_current_txn(pager);
                     }
@@ -1406,6 +1513,7 @@ impl Program {
             self.connection.auto_commit.store(true, Ordering::SeqCst);
         }
         self.connection.set_tx_state(TransactionState::None);
+        self.connection.rollback_attached_transactions();
     }
 
     pub fn is_trigger_subprogram(&self) -> bool {
@@ -8,7 +8,7 @@ use std::ptr::NonNull;
 use std::rc::Rc;
 use std::sync::{atomic, Arc};
 
-use crate::io::TempFile;
+use crate::io::{TempDirectory, TempFile};
 use crate::types::IOCompletions;
 use crate::{
     error::LimboError,
@@ -70,6 +70,8 @@ pub struct Sorter {
     max_payload_size_in_buffer: usize,
     /// The IO object.
     io: Arc<dyn IO>,
+    /// The directory of the connection that the chunk file is created in.
+    temp_directory: Arc<TempDirectory>,
     /// The temporary file for chunks.
     temp_file: Option<TempFile>,
     /// Offset where the next chunk will be placed in the `temp_file`
@@ -91,6 +93,7 @@ impl Sorter {
         max_buffer_size_bytes: usize,
         min_chunk_read_buffer_size_bytes: usize,
         io: Arc<dyn IO>,
+        temp_directory: Arc<TempDirectory>,
     ) -> Self {
         assert_eq!(order.len(), collations.len());
         Self {
@@ -115,6 +118,7 @@ impl Sorter {
             min_chunk_read_buffer_size: min_chunk_read_buffer_size_bytes,
             max_payload_size_in_buffer: 0,
             io,
+            temp_directory,
             temp_file: None,
             next_chunk_offset: 0,
             sort_state: SortState::Start,
@@ -375,7 +379,7 @@ impl Sorter {
         let chunk_file = match &self.temp_file {
             Some(temp_file) => temp_file.file.clone(),
             None => {
-                let temp_file = TempFile::new(&self.io)?;
+                let temp_file = TempFile::new(&self.io, &self.temp_directory)?;
                 let chunk_file = temp_file.file.clone();
                 self.temp_file = Some(temp_file);
                 chunk_file
@@ -951,6 +955,7 @@ mod tests {
                 256,
                 64,
                 io.clone(),
+                Arc::new(TempDirectory::default()),
             );
 
             let num_records = 1000 + rng.next_u64() % 2000;
@@ -372,7 +372,7 @@ impl Value {
         use std::fmt::Write;
         match self {
             Value::Null => Value::build_text("NULL"),
-            Value::Integer(_) | Value::Float(_) => self.to_owned(),
+            Value::Integer(_) | Value::Float(_) => Value::build_text(self.to_string()),
             Value::Blob(b) => {
                 // SQLite returns X'hexdigits' for blobs
                 let mut quoted = String::with_capacity(3 + b.len() * 2);
@@ -543,8 +543,9 @@ impl Value {
             }
         };
 
+        // The position of text is counted in characters, not bytes.
         match reg.find(pattern) {
-            Some(position) => Value::Integer(position as i64 + 1),
+            Some(position) => Value::Integer(reg[..position].chars().count() as i64 + 1),
             None => Value::Integer(0),
         }
     }
@@ -571,33 +572,27 @@ impl Value {
     }
 
     pub fn exec_unhex(&self, ignored_chars: Option<&Value>) -> Value {
-        match self {
-            Value::Null => Value::Null,
-            _ => match ignored_chars {
-                None => match self
-                    .cast_text()
-                    .map(|s| hex::decode(&s[0..s.find('\0').unwrap_or(s.len())]))
-                {
-                    Some(Ok(bytes)) => Value::Blob(bytes),
-                    _ => Value::Null,
-                },
-                Some(ignore) => match ignore {
-                    Value::Text(_) => {
-                        let pat = ignore.to_string();
-                        let trimmed = self
-                            .to_string()
-                            .trim_start_matches(|x| pat.contains(x))
-                            .trim_end_matches(|x| pat.contains(x))
-                            .to_string();
-                        match hex::decode(trimmed) {
-                            Ok(bytes) => Value::Blob(bytes),
-                            _ => Value::Null,
-                        }
-                    }
-                    _ => Value::Null,
-                },
-            },
+        if matches!(self, Value::Null) || matches!(ignored_chars, Some(Value::Null)) {
+            return Value::Null;
         }
+        // Characters of `ignored_chars` may appear before, after and between pairs of hex
+        // digits, but not inside of a pair.
+        let ignored_chars = ignored_chars.map(|v| v.to_string()).unwrap_or_default();
+        let text = self.to_string();
+        let text = &text[..text.find('\0').unwrap_or(text.len())];
+        let mut bytes = Vec::with_capacity(text.len() / 2);
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if let Some(high) = c.to_digit(16) {
+                let Some(low) = chars.next().and_then(|c| c.to_digit(16)) else {
+                    return Value::Null;
+                };
+                bytes.push((high << 4 | low) as u8);
+            } else if !ignored_chars.contains(c) {
+                return Value::Null;
+            }
+        }
+        Value::Blob(bytes)
     }
 
     pub fn exec_unicode(&self) -> Value {
@@ -644,16 +639,18 @@ impl Value {
     }
 
     fn _exec_trim(&self, pattern: Option<&Value>, trim_type: TrimType) -> Value {
+        // Like SQLite, the value is always trimmed as text, even if it is a number or a blob.
         match (self, pattern) {
-            (Value::Text(_) | Value::Integer(_) | Value::Float(_), Some(pattern)) => {
+            (Value::Null, _) | (_, Some(Value::Null)) => Value::Null,
+            (_, Some(pattern)) => {
                 let pattern_chars: Vec<char> = pattern.to_string().chars().collect();
                 let text = self.to_string();
                 Value::build_text(trim_type.trim(&text, &pattern_chars).to_string())
             }
-            (Value::Text(t), None) => {
-                Value::build_text(trim_type.trim(t.as_str(), &[' ']).to_string())
+            (_, None) => {
+                let text = self.to_string();
+                Value::build_text(trim_type.trim(&text, &[' ']).to_string())
             }
-            (reg, _) => reg.to_owned(),
         }
     }
 
@@ -1055,7 +1052,7 @@ impl Value {
         for val in registers {
             match val {
                 Value::Null => continue,
-                Value::Blob(_) => todo!("TODO concat blob"),
+                Value::Blob(b) => result.push_str(&String::from_utf8_lossy(b)),
                 v => result.push_str(&format!("{v}")),
             }
         }
@@ -1520,7 +1517,11 @@ mod tests {
         assert_eq!(input.exec_quote(), expected);
 
         let input = Value::Integer(123);
-        let expected = Value::Integer(123);
+        let expected = Value::build_text("123");
+        assert_eq!(input.exec_quote(), expected);
+
+        let input = Value::Float(1.5);
+        let expected = Value::build_text("1.5");
         assert_eq!(input.exec_quote(), expected);
 
         let input = Value::build_text("hello''world");
@@ -1636,6 +1637,17 @@ mod tests {
         let input_str = Value::build_text("\na");
         let expected_str = Value::build_text("\na");
         assert_eq!(input_str.exec_trim(None), expected_str);
+
+        let input = Value::Integer(1221);
+        let pattern = Value::Integer(1);
+        assert_eq!(input.exec_trim(None), Value::build_text("1221"));
+        assert_eq!(input.exec_trim(Some(&pattern)), Value::build_text("22"));
+
+        let input = Value::Blob(b" ab ".to_vec());
+        assert_eq!(input.exec_trim(None), Value::build_text("ab"));
+
+        let input_str = Value::build_text(" a ");
+        assert_eq!(input_str.exec_trim(Some(&Value::Null)), Value::Null);
     }
 
     #[test]
@@ -1780,6 +1792,17 @@ mod tests {
         let input = Value::Null;
         let expected = Value::Null;
         assert_eq!(input.exec_unhex(None), expected);
+
+        let ignored = Value::build_text("- ");
+        let input = Value::build_text("-12--34 ");
+        let expected = Value::Blob(vec![0x12, 0x34]);
+        assert_eq!(input.exec_unhex(Some(&ignored)), expected);
+
+        let input = Value::build_text("1-234");
+        assert_eq!(input.exec_unhex(Some(&ignored)), Value::Null);
+
+        let input = Value::build_text("1234");
+        assert_eq!(input.exec_unhex(Some(&Value::Null)), Value::Null);
     }
 
     #[test]
@@ -2087,6 +2110,11 @@ mod tests {
         let expected = Value::Integer(2);
         assert_eq!(input.exec_instr(&pattern), expected);
 
+        let input = Value::build_text("héllo");
+        let pattern = Value::build_text("l");
+        let expected = Value::Integer(3);
+        assert_eq!(input.exec_instr(&pattern), expected);
+
         let input = Value::build_text("limbo");
         let pattern = Value::build_text("limbo");
         let expected = Value::Integer(1);
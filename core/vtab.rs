@@ -55,12 +55,28 @@ impl VirtualTable {
             })
             .collect();
 
+        vtables.push(Self::dbstat_virtual_table());
+
         #[cfg(feature = "json")]
         vtables.extend(Self::json_virtual_tables());
 
         vtables
     }
 
+    fn dbstat_virtual_table() -> Arc<VirtualTable> {
+        use crate::dbstat::DbStatVirtualTable;
+
+        let dbstat = DbStatVirtualTable;
+        Arc::new(VirtualTable {
+            name: dbstat.name(),
+            columns: Self::resolve_columns(dbstat.sql())
+                .expect("internal table-valued function schema resolution should not fail"),
+            kind: VTabKind::TableValuedFunction,
+            vtab_type: VirtualTableType::Internal(Arc::new(RwLock::new(dbstat))),
+            vtab_id: 0,
+        })
+    }
+
     #[cfg(feature = "json")]
     fn json_virtual_tables() -> Vec<Arc<VirtualTable>> {
         use crate::json::vtab::JsonVirtualTable;
@@ -342,7 +358,9 @@ impl ExtVirtualTable {
         let module = module.ok_or_else(|| {
             LimboError::ExtensionError(format!("Virtual table module not found: {module_name}"))
         })?;
-        if kind != module.module_kind {
+        if kind != module.module_kind
+            && !(kind == VTabKind::VirtualTable && module.can_create_table())
+        {
             let expected = match kind {
                 VTabKind::VirtualTable => "virtual table",
                 VTabKind::TableValuedFunction => "table-valued function",
@@ -9,6 +9,17 @@ pub type ScalarFunction = unsafe extern "C" fn(argc: i32, *const Value) -> Value
 pub type RegisterScalarFn =
     unsafe extern "C" fn(ctx: *mut c_void, name: *const c_char, func: ScalarFunction) -> ResultCode;
 
+/// Compares two UTF-8 strings, returning a negative number, zero or a positive number if `lhs`
+/// sorts before, equal to or after `rhs`.
+pub type CollationFunction =
+    unsafe extern "C" fn(lhs: *const u8, lhs_len: usize, rhs: *const u8, rhs_len: usize) -> i32;
+
+pub type RegisterCollationFn = unsafe extern "C" fn(
+    ctx: *mut c_void,
+    name: *const c_char,
+    func: CollationFunction,
+) -> ResultCode;
+
 pub type RegisterAggFn = unsafe extern "C" fn(
     ctx: *mut c_void,
     name: *const c_char,
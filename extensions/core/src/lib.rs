@@ -4,13 +4,14 @@ mod types;
 mod vfs_modules;
 mod vtabs;
 pub use functions::{
-    AggCtx, AggFunc, FinalizeFunction, InitAggFunction, ScalarFunction, StepFunction,
+    AggCtx, AggFunc, CollationFunction, FinalizeFunction, InitAggFunction, ScalarFunction,
+    StepFunction,
 };
-use functions::{RegisterAggFn, RegisterScalarFn};
+use functions::{RegisterAggFn, RegisterCollationFn, RegisterScalarFn};
 use std::os::raw::c_void;
 #[cfg(feature = "vfs")]
 pub use turso_macros::VfsDerive;
-pub use turso_macros::{register_extension, scalar, AggregateDerive, VTabModuleDerive};
+pub use turso_macros::{collation, register_extension, scalar, AggregateDerive, VTabModuleDerive};
 pub use types::{ResultCode, StepResult, Value, ValueType};
 #[cfg(feature = "vfs")]
 pub use vfs_modules::{
@@ -34,6 +35,7 @@ pub struct ExtensionApi {
     pub register_scalar_function: RegisterScalarFn,
     pub register_aggregate_function: RegisterAggFn,
     pub register_vtab_module: RegisterModuleFn,
+    pub register_collation: RegisterCollationFn,
     #[cfg(feature = "vfs")]
     pub vfs_interface: VfsInterface,
 }
@@ -2,6 +2,7 @@ use crate::Error;
 use blake3::Hasher;
 use data_encoding::{BASE32, BASE64, HEXLOWER};
 use ring::digest::{self, digest};
+use sha3::{Digest, Sha3_224, Sha3_256, Sha3_384, Sha3_512};
 use std::{borrow::Cow, error::Error as StdError};
 use turso_ext::{Value, ValueType};
 
@@ -56,6 +57,28 @@ pub fn sha1(data: &Value) -> Result<Vec<u8>, Error> {
     }
 }
 
+/// SHA3 with an optional output size in bits (224, 256, 384 or 512), which defaults to 256.
+pub fn sha3(data: &Value, bits: Option<&Value>) -> Result<Vec<u8>, Error> {
+    let bits = match bits.map(|bits| bits.to_integer()) {
+        None => 256,
+        Some(Some(bits @ (224 | 256 | 384 | 512))) => bits,
+        Some(_) => return Err(Error::InvalidType),
+    };
+    match data.value_type() {
+        ValueType::Error | ValueType::Null => Err(Error::InvalidType),
+        _ => {
+            let data = data.as_bytes();
+            let hash = match bits {
+                224 => Sha3_224::digest(&data).to_vec(),
+                256 => Sha3_256::digest(&data).to_vec(),
+                384 => Sha3_384::digest(&data).to_vec(),
+                _ => Sha3_512::digest(&data).to_vec(),
+            };
+            Ok(hash)
+        }
+    }
+}
+
 pub fn md5(data: &Value) -> Result<Vec<u8>, Error> {
     match data.value_type() {
         ValueType::Error | ValueType::Null => Err(Error::InvalidType),
@@ -1,4 +1,4 @@
-use crypto::{blake3, decode, encode, md5, sha1, sha256, sha384, sha512};
+use crypto::{blake3, decode, encode, md5, sha1, sha256, sha3, sha384, sha512};
 use turso_ext::{register_extension, scalar, ResultCode, Value};
 
 mod crypto;
@@ -50,6 +50,19 @@ fn crypto_sha384(args: &[Value]) -> Value {
     Value::from_blob(hash)
 }
 
+#[scalar(name = "crypto_sha3", alias = "crypto_sha3")]
+fn crypto_sha3(args: &[Value]) -> Value {
+    if args.is_empty() || args.len() > 2 {
+        return Value::error(ResultCode::Error);
+    }
+
+    let Ok(hash) = sha3(&args[0], args.get(1)) else {
+        return Value::error(ResultCode::Error);
+    };
+
+    Value::from_blob(hash)
+}
+
 #[scalar(name = "crypto_blake3", alias = "crypto_blake3")]
 fn crypto_blake3(args: &[Value]) -> Value {
     if args.len() != 1 {
@@ -116,5 +129,5 @@ fn crypto_decode(args: &[Value]) -> Value {
 }
 
 register_extension! {
-    scalars: { crypto_sha256, crypto_sha512, crypto_sha384, crypto_blake3, crypto_sha1, crypto_md5, crypto_encode, crypto_decode },
+    scalars: { crypto_sha256, crypto_sha512, crypto_sha384, crypto_sha3, crypto_blake3, crypto_sha1, crypto_md5, crypto_encode, crypto_decode },
 }
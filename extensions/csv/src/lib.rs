@@ -20,6 +20,9 @@
 //!   accepts `yes`/`no`, `on`/`off`, `true`/`false`, or `1`/`0`
 //! - `columns` — number of columns
 //! - `schema` — optional custom SQL `CREATE TABLE` schema
+//! - `infer_types` — when no `schema` is given, sample the first rows and declare each column
+//!   as `INTEGER`, `REAL` or `TEXT` instead of always `TEXT`; values of typed columns are
+//!   converted the same way column affinity would convert them
 use std::fs::File;
 use std::io::{Read, Seek, SeekFrom};
 use std::sync::Arc;
@@ -32,6 +35,64 @@ register_extension! {
     vtabs: { CsvVTabModule }
 }
 
+/// Number of data rows inspected by `infer_types=yes`
+const INFER_SAMPLE_ROWS: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ColumnType {
+    Integer,
+    Real,
+    Text,
+}
+
+impl ColumnType {
+    fn sql_name(&self) -> &'static str {
+        match self {
+            ColumnType::Integer => "INTEGER",
+            ColumnType::Real => "REAL",
+            ColumnType::Text => "TEXT",
+        }
+    }
+
+    /// Narrowest type able to hold both `self` and the given non-empty field
+    fn widen(self, field: &str) -> Self {
+        let field = field.trim();
+        match self {
+            ColumnType::Integer if field.parse::<i64>().is_ok() => ColumnType::Integer,
+            ColumnType::Integer | ColumnType::Real if parse_real(field).is_some() => {
+                ColumnType::Real
+            }
+            _ => ColumnType::Text,
+        }
+    }
+
+    fn value(&self, field: &str) -> Value {
+        let trimmed = field.trim();
+        match self {
+            ColumnType::Integer => {
+                if let Ok(i) = trimmed.parse::<i64>() {
+                    return Value::from_integer(i);
+                }
+                if let Some(f) = parse_real(trimmed) {
+                    return Value::from_float(f);
+                }
+            }
+            ColumnType::Real => {
+                if let Some(f) = parse_real(trimmed) {
+                    return Value::from_float(f);
+                }
+            }
+            ColumnType::Text => {}
+        }
+        Value::from_text(field.to_owned())
+    }
+}
+
+fn parse_real(s: &str) -> Option<f64> {
+    // reject "inf"/"nan" spellings accepted by the Rust parser
+    s.parse::<f64>().ok().filter(|f| f.is_finite())
+}
+
 #[derive(Debug, VTabModuleDerive, Default)]
 struct CsvVTabModule;
 
@@ -120,6 +181,7 @@ impl VTabModule for CsvVTabModule {
         let mut schema = None;
         let mut column_count = None;
         let mut header = None;
+        let mut infer_types = None;
 
         for arg in args {
             let (name, value) = Self::parse_arg(arg)?;
@@ -158,6 +220,12 @@ impl VTabModule for CsvVTabModule {
                     }
                     header = Some(Self::parse_boolean(value).ok_or(ResultCode::InvalidArgs)?);
                 }
+                "infer_types" => {
+                    if infer_types.is_some() {
+                        return Err(ResultCode::InvalidArgs);
+                    }
+                    infer_types = Some(Self::parse_boolean(value).ok_or(ResultCode::InvalidArgs)?);
+                }
                 _ => {
                     return Err(ResultCode::InvalidArgs);
                 }
@@ -176,6 +244,7 @@ impl VTabModule for CsvVTabModule {
             data,
             header: header.unwrap_or(false),
             first_row_position: csv::Position::new(),
+            column_types: Vec::new(),
         };
 
         if table.header || (column_count.is_none() && schema.is_none()) {
@@ -210,11 +279,16 @@ impl VTabModule for CsvVTabModule {
         }
 
         if schema.is_none() {
+            if infer_types.unwrap_or(false) {
+                table.column_types = table.infer_column_types(columns.len())?;
+            }
             let mut sql = String::from("CREATE TABLE x (");
             for (i, col) in columns.iter().enumerate() {
+                let column_type = table.column_types.get(i).unwrap_or(&ColumnType::Text);
                 sql.push('"');
                 sql.push_str(col);
-                sql.push_str("\" TEXT");
+                sql.push_str("\" ");
+                sql.push_str(column_type.sql_name());
                 if i < columns.len() - 1 {
                     sql.push_str(", ");
                 }
@@ -233,6 +307,8 @@ struct CsvTable {
     header: bool,
     column_count: Option<u32>,
     first_row_position: csv::Position,
+    /// Inferred column types, empty unless `infer_types` is enabled
+    column_types: Vec<ColumnType>,
 }
 
 impl CsvTable {
@@ -252,6 +328,31 @@ impl CsvTable {
             _ => Err(ResultCode::Internal),
         }
     }
+
+    fn infer_column_types(&self, column_count: usize) -> Result<Vec<ColumnType>, ResultCode> {
+        // None until the first non-empty field of the column is seen
+        let mut types: Vec<Option<ColumnType>> = vec![None; column_count];
+        let mut reader = self.new_reader()?;
+        let mut record = csv::StringRecord::new();
+        for _ in 0..INFER_SAMPLE_ROWS {
+            if !reader
+                .read_record(&mut record)
+                .map_err(|_| ResultCode::Error)?
+            {
+                break;
+            }
+            for (column_type, field) in types.iter_mut().zip(record.iter()) {
+                if field.trim().is_empty() {
+                    continue;
+                }
+                *column_type = Some(column_type.unwrap_or(ColumnType::Integer).widen(field));
+            }
+        }
+        Ok(types
+            .into_iter()
+            .map(|t| t.unwrap_or(ColumnType::Text))
+            .collect())
+    }
 }
 
 impl VTable for CsvTable {
@@ -296,6 +397,7 @@ struct CsvCursor {
     current_row: csv::StringRecord,
     eof: bool,
     first_row_position: csv::Position,
+    column_types: Vec<ColumnType>,
 }
 
 impl CsvCursor {
@@ -307,6 +409,7 @@ impl CsvCursor {
             current_row: csv::StringRecord::new(),
             eof: false,
             first_row_position: table.first_row_position.clone(),
+            column_types: table.column_types.clone(),
         }
     }
 }
@@ -333,10 +436,13 @@ impl VTabCursor for CsvCursor {
                 return Ok(Value::null());
             }
         }
-        let value = self
-            .current_row
-            .get(idx as usize)
-            .map_or(Value::null(), |s| Value::from_text(s.to_owned()));
+        let Some(field) = self.current_row.get(idx as usize) else {
+            return Ok(Value::null());
+        };
+        let value = match self.column_types.get(idx as usize) {
+            Some(column_type) => column_type.value(field),
+            None => Value::from_text(field.to_owned()),
+        };
         Ok(value)
     }
 
@@ -852,4 +958,63 @@ mod tests {
             "CREATE TABLE x (\"id\" TEXT, \"first\"\"name\" TEXT)"
         );
     }
+
+    #[test]
+    fn test_infer_types() {
+        let (schema, table) = try_new_table(vec![
+            "data=id,price,name,note\n1,1.5,Alice,\n2,3,Bob,\n-3, 1e2 ,7,\n",
+            "header=yes",
+            "infer_types=yes",
+        ])
+        .unwrap();
+        assert_eq!(
+            schema,
+            "CREATE TABLE x (\"id\" INTEGER, \"price\" REAL, \"name\" TEXT, \"note\" TEXT)"
+        );
+        let mut cursor = table.open(None).unwrap();
+        cursor.filter(&[], None);
+        let mut rows = vec![];
+        while !cursor.eof() {
+            let id = cursor.column(0).unwrap();
+            let price = cursor.column(1).unwrap();
+            let name = cursor.column(2).unwrap();
+            assert_eq!(id.value_type(), ValueType::Integer);
+            assert_eq!(price.value_type(), ValueType::Float);
+            assert_eq!(name.value_type(), ValueType::Text);
+            rows.push((
+                id.to_integer().unwrap(),
+                price.to_float().unwrap(),
+                name.to_text().unwrap().to_owned(),
+            ));
+            cursor.next();
+        }
+        assert_eq!(
+            rows,
+            vec![
+                (1, 1.5, "Alice".to_owned()),
+                (2, 3.0, "Bob".to_owned()),
+                (-3, 100.0, "7".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_infer_types_ignored_with_schema() {
+        let (schema, table) = try_new_table(vec![
+            "data=1,2\n",
+            "infer_types=yes",
+            "schema='CREATE TABLE x (a, b)'",
+        ])
+        .unwrap();
+        assert_eq!(schema, "CREATE TABLE x (a, b)");
+        let cursor = table.open(None).unwrap();
+        let rows = read_rows(cursor, 2);
+        assert_eq!(rows, vec![vec![cell!("1"), cell!("2")]]);
+    }
+
+    #[test]
+    fn test_infer_types_disabled_by_default() {
+        let (schema, _) = try_new_table(vec!["data=1,2.5\n"]).unwrap();
+        assert_eq!(schema, "CREATE TABLE x (\"c0\" TEXT, \"c1\" TEXT)");
+    }
 }
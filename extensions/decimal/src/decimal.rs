@@ -0,0 +1,450 @@
+//! Arbitrary precision decimal numbers.
+//!
+//! A number is stored as an integer coefficient and the number of its digits that come after
+//! the decimal point, e.g. `-1.25` is `-125` with a scale of 2.
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Largest exponent accepted in text such as `1e100`, which bounds the number of digits.
+const MAX_EXPONENT: usize = 100_000;
+/// Largest power of two accepted by [Decimal::pow2], in either direction.
+pub const MAX_POW2: i64 = 20_000;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Decimal {
+    negative: bool,
+    /// Digits of the coefficient, least significant first, without leading zeros, so that
+    /// zero has no digits.
+    digits: Vec<u8>,
+    /// How many digits of the coefficient come after the decimal point. Trailing zeros after
+    /// the decimal point are removed, so this is 0 for integers.
+    scale: usize,
+}
+
+impl Decimal {
+    /// Parses a decimal number such as `-12.5`, `.5` or `1.5e-3`, with optional surrounding
+    /// whitespace.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (negative, text) = match text.as_bytes().first()? {
+            b'-' => (true, &text[1..]),
+            b'+' => (false, &text[1..]),
+            _ => (false, text),
+        };
+        let (mantissa, exponent) = match text.find(['e', 'E']) {
+            Some(e) => (&text[..e], Some(&text[e + 1..])),
+            None => (text, None),
+        };
+        let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        if integer.is_empty() && fraction.is_empty() {
+            return None;
+        }
+        let mut digits = Vec::with_capacity(integer.len() + fraction.len());
+        for c in fraction.bytes().rev().chain(integer.bytes().rev()) {
+            if !c.is_ascii_digit() {
+                return None;
+            }
+            digits.push(c - b'0');
+        }
+        let mut decimal = Self {
+            negative,
+            digits,
+            scale: fraction.len(),
+        };
+        if let Some(exponent) = exponent {
+            let (exp_negative, exponent) = match exponent.as_bytes().first()? {
+                b'-' => (true, &exponent[1..]),
+                b'+' => (false, &exponent[1..]),
+                _ => (false, exponent),
+            };
+            if exponent.is_empty() || !exponent.bytes().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            let exponent = exponent
+                .parse::<usize>()
+                .ok()
+                .filter(|e| *e <= MAX_EXPONENT)?;
+            if exp_negative {
+                decimal.scale += exponent;
+            } else if exponent <= decimal.scale {
+                decimal.scale -= exponent;
+            } else {
+                decimal.shift_left(exponent - decimal.scale);
+                decimal.scale = 0;
+            }
+        }
+        decimal.normalize();
+        Some(decimal)
+    }
+
+    pub fn from_integer(i: i64) -> Self {
+        let mut magnitude = i.unsigned_abs();
+        let mut digits = Vec::new();
+        while magnitude > 0 {
+            digits.push((magnitude % 10) as u8);
+            magnitude /= 10;
+        }
+        Self {
+            negative: i < 0,
+            digits,
+            scale: 0,
+        }
+    }
+
+    /// The exact value of a float, e.g. `0.1` is
+    /// `0.1000000000000000055511151231257827021181583404541015625`. Returns `None` for
+    /// infinities and NaN.
+    pub fn from_float(f: f64) -> Option<Self> {
+        if !f.is_finite() {
+            return None;
+        }
+        let bits = f.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i64;
+        let fraction = bits & ((1 << 52) - 1);
+        // f = mantissa * 2^exponent
+        let (mantissa, exponent) = if exponent == 0 {
+            (fraction, -1074)
+        } else {
+            (fraction | (1 << 52), exponent - 1075)
+        };
+        let mut decimal = Self::pow2(exponent);
+        decimal.multiply_small(mantissa);
+        decimal.negative = f.is_sign_negative();
+        decimal.normalize();
+        Some(decimal)
+    }
+
+    /// `2^exponent`, exactly.
+    pub fn pow2(exponent: i64) -> Self {
+        let mut decimal = Self::from_integer(1);
+        // 2^-n = 5^n / 10^n
+        let (factor, scale) = if exponent >= 0 {
+            (2, 0)
+        } else {
+            (5, exponent.unsigned_abs() as usize)
+        };
+        let mut remaining = exponent.unsigned_abs();
+        // Multiply by the largest power of the factor that fits in a u32 at a time.
+        let (chunk_power, chunk) = if factor == 2 {
+            (31, 1 << 31)
+        } else {
+            (13, 5u64.pow(13))
+        };
+        while remaining > 0 {
+            let power = remaining.min(chunk_power);
+            let multiplier = if power == chunk_power {
+                chunk
+            } else {
+                (factor as u64).pow(power as u32)
+            };
+            decimal.multiply_small(multiplier);
+            remaining -= power;
+        }
+        decimal.scale = scale;
+        decimal.normalize();
+        decimal
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        let (mut a, mut b) = (self.clone(), other.clone());
+        Self::align(&mut a, &mut b);
+        let mut result = if a.negative == b.negative {
+            Self {
+                negative: a.negative,
+                digits: add_magnitudes(&a.digits, &b.digits),
+                scale: a.scale,
+            }
+        } else {
+            match compare_magnitudes(&a.digits, &b.digits) {
+                Ordering::Less => Self {
+                    negative: b.negative,
+                    digits: subtract_magnitudes(&b.digits, &a.digits),
+                    scale: a.scale,
+                },
+                _ => Self {
+                    negative: a.negative,
+                    digits: subtract_magnitudes(&a.digits, &b.digits),
+                    scale: a.scale,
+                },
+            }
+        };
+        result.normalize();
+        result
+    }
+
+    pub fn subtract(&self, other: &Self) -> Self {
+        self.add(&other.negate())
+    }
+
+    pub fn multiply(&self, other: &Self) -> Self {
+        let mut result = Self {
+            negative: self.negative != other.negative,
+            digits: multiply_magnitudes(&self.digits, &other.digits),
+            scale: self.scale + other.scale,
+        };
+        result.normalize();
+        result
+    }
+
+    pub fn negate(&self) -> Self {
+        let mut result = self.clone();
+        result.negative = !result.negative && !result.is_zero();
+        result
+    }
+
+    /// Formats the number in exponential notation, e.g. `1.25e+02` for `125`.
+    pub fn to_exponential(&self) -> String {
+        let sign = if self.negative { "-" } else { "" };
+        if self.is_zero() {
+            return "0.0e+00".to_string();
+        }
+        let mut mantissa = self.digits.iter().rev().map(|d| (b'0' + d) as char);
+        let first = mantissa.next().unwrap_or('0');
+        let mut rest = mantissa.collect::<String>();
+        while rest.len() > 1 && rest.ends_with('0') {
+            rest.pop();
+        }
+        if rest.is_empty() {
+            rest.push('0');
+        }
+        let exponent = self.digits.len() as i64 - 1 - self.scale as i64;
+        let exp_sign = if exponent < 0 { '-' } else { '+' };
+        format!("{sign}{first}.{rest}e{exp_sign}{:02}", exponent.abs())
+    }
+
+    /// Multiplies the coefficient by `10^n`.
+    fn shift_left(&mut self, n: usize) {
+        if !self.digits.is_empty() {
+            self.digits.splice(0..0, std::iter::repeat_n(0, n));
+        }
+    }
+
+    /// Brings both numbers to the same scale.
+    fn align(a: &mut Self, b: &mut Self) {
+        match a.scale.cmp(&b.scale) {
+            Ordering::Less => {
+                a.shift_left(b.scale - a.scale);
+                a.scale = b.scale;
+            }
+            Ordering::Greater => {
+                b.shift_left(a.scale - b.scale);
+                b.scale = a.scale;
+            }
+            Ordering::Equal => {}
+        }
+    }
+
+    fn multiply_small(&mut self, factor: u64) {
+        let mut carry = 0u64;
+        for digit in self.digits.iter_mut() {
+            let product = *digit as u64 * factor + carry;
+            *digit = (product % 10) as u8;
+            carry = product / 10;
+        }
+        while carry > 0 {
+            self.digits.push((carry % 10) as u8);
+            carry /= 10;
+        }
+    }
+
+    fn normalize(&mut self) {
+        while self.digits.last() == Some(&0) {
+            self.digits.pop();
+        }
+        let trailing_zeros = self
+            .digits
+            .iter()
+            .take(self.scale)
+            .take_while(|d| **d == 0)
+            .count();
+        self.digits.drain(..trailing_zeros);
+        self.scale -= trailing_zeros;
+        if self.digits.is_empty() {
+            self.negative = false;
+            self.scale = 0;
+        }
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => return Ordering::Greater,
+            (true, false) => return Ordering::Less,
+            _ => {}
+        }
+        let (mut a, mut b) = (self.clone(), other.clone());
+        Self::align(&mut a, &mut b);
+        let ordering = compare_magnitudes(&a.digits, &b.digits);
+        if self.negative {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negative {
+            f.write_str("-")?;
+        }
+        let digits = self
+            .digits
+            .iter()
+            .rev()
+            .map(|d| (b'0' + d) as char)
+            .collect::<String>();
+        if self.scale == 0 {
+            return f.write_str(if digits.is_empty() { "0" } else { &digits });
+        }
+        match digits.len().checked_sub(self.scale) {
+            Some(0) | None => write!(f, "0.{}{digits}", "0".repeat(self.scale - digits.len())),
+            Some(integer) => write!(f, "{}.{}", &digits[..integer], &digits[integer..]),
+        }
+    }
+}
+
+fn compare_magnitudes(a: &[u8], b: &[u8]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitudes(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0;
+    for i in 0..a.len().max(b.len()) {
+        let sum = a.get(i).unwrap_or(&0) + b.get(i).unwrap_or(&0) + carry;
+        result.push(sum % 10);
+        carry = sum / 10;
+    }
+    if carry > 0 {
+        result.push(carry);
+    }
+    result
+}
+
+/// `a - b`, where `a >= b`.
+fn subtract_magnitudes(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(a.len());
+    let mut borrow = 0;
+    for (i, digit) in a.iter().enumerate() {
+        let subtrahend = b.get(i).unwrap_or(&0) + borrow;
+        if *digit >= subtrahend {
+            result.push(digit - subtrahend);
+            borrow = 0;
+        } else {
+            result.push(digit + 10 - subtrahend);
+            borrow = 1;
+        }
+    }
+    result
+}
+
+fn multiply_magnitudes(a: &[u8], b: &[u8]) -> Vec<u8> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut result = vec![0u32; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0;
+        for (j, y) in b.iter().enumerate() {
+            let product = result[i + j] + *x as u32 * *y as u32 + carry;
+            result[i + j] = product % 10;
+            carry = product / 10;
+        }
+        result[i + b.len()] += carry;
+    }
+    result.into_iter().map(|d| d as u8).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(text: &str) -> Decimal {
+        Decimal::parse(text).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_format() {
+        for (input, expected) in [
+            ("0", "0"),
+            ("-0.000", "0"),
+            ("007.50", "7.5"),
+            (" .5 ", "0.5"),
+            ("-12.", "-12"),
+            ("+1.5e3", "1500"),
+            ("1.5E-3", "0.0015"),
+            ("123e-1", "12.3"),
+            ("0.00100", "0.001"),
+        ] {
+            assert_eq!(d(input).to_string(), expected, "{input}");
+        }
+        for input in ["", ".", "-", "1.2.3", "1e", "1e+", "abc", "1 2", "1e100001"] {
+            assert_eq!(Decimal::parse(input), None, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(d("0.1").add(&d("0.2")).to_string(), "0.3");
+        assert_eq!(d("1").subtract(&d("1.0001")).to_string(), "-0.0001");
+        assert_eq!(d("-5.5").add(&d("5.5")).to_string(), "0");
+        assert_eq!(d("999.99").add(&d("0.01")).to_string(), "1000");
+        assert_eq!(d("-2.5").multiply(&d("4")).to_string(), "-10");
+        assert_eq!(d("0.001").multiply(&d("0.002")).to_string(), "0.000002");
+        assert_eq!(
+            d("123456789012345678901234567890")
+                .multiply(&d("987654321098765432109876543210"))
+                .to_string(),
+            "121932631137021795226185032733622923332237463801111263526900"
+        );
+    }
+
+    #[test]
+    fn test_compare() {
+        assert!(d("0.10") == d("0.1"));
+        assert!(d("-1") < d("0"));
+        assert!(d("-1.5") < d("-1.25"));
+        assert!(d("10") > d("9.999"));
+        assert!(d("0.01") < d("0.1"));
+    }
+
+    #[test]
+    fn test_floats_and_powers() {
+        assert_eq!(
+            Decimal::from_float(0.1).unwrap().to_string(),
+            "0.1000000000000000055511151231257827021181583404541015625"
+        );
+        assert_eq!(Decimal::from_float(-2.5).unwrap().to_string(), "-2.5");
+        assert_eq!(Decimal::from_float(0.0).unwrap().to_string(), "0");
+        assert_eq!(Decimal::from_float(f64::NAN), None);
+        assert_eq!(
+            Decimal::from_integer(i64::MIN).to_string(),
+            "-9223372036854775808"
+        );
+        assert_eq!(Decimal::pow2(10).to_string(), "1024");
+        assert_eq!(Decimal::pow2(-3).to_string(), "0.125");
+        assert_eq!(Decimal::pow2(64).to_string(), "18446744073709551616");
+    }
+
+    #[test]
+    fn test_exponential() {
+        assert_eq!(d("125").to_exponential(), "1.25e+02");
+        assert_eq!(d("-0.00125").to_exponential(), "-1.25e-03");
+        assert_eq!(d("1000").to_exponential(), "1.0e+03");
+        assert_eq!(d("0").to_exponential(), "0.0e+00");
+    }
+}
@@ -0,0 +1,253 @@
+//! Port of SQLite's decimal extension: <https://sqlite.org/floatingpoint.html#the_decimal_c_extension>
+//!
+//! Exact arithmetic on decimal numbers of any size, for amounts of money and other values
+//! that can't tolerate the rounding of floating point. Decimals are passed around as text,
+//! e.g. `decimal_add('0.1', '0.2')` is `'0.3'`, while `0.1 + 0.2` is `0.30000000000000004`.
+//!
+//! ## Functions:
+//! - `decimal(X)` — X as a decimal in canonical form
+//! - `decimal_exp(X)` — X in exponential notation, e.g. `1.25e+02`
+//! - `decimal_add(A, B)`, `decimal_sub(A, B)`, `decimal_mul(A, B)`
+//! - `decimal_cmp(A, B)` — -1, 0 or 1 as A is less than, equal to or greater than B
+//! - `decimal_pow2(N)` — 2 to the power of the integer N, which must be within ±20000
+//! - `decimal_sum(X)` — aggregate sum of decimals
+//!
+//! The `decimal` collation sorts text as decimal numbers.
+//!
+//! Arguments can be text, integers or floats. Floats are converted exactly, so
+//! `decimal(0.1)` shows the binary value actually stored for `0.1`. Text that isn't a
+//! decimal number, like NULL, makes the result NULL.
+mod decimal;
+
+use decimal::Decimal;
+use std::cmp::Ordering;
+use turso_ext::{
+    collation, register_extension, scalar, AggFunc, AggregateDerive, ResultCode, Value, ValueType,
+};
+
+register_extension! {
+    scalars: { decimal, decimal_exp, decimal_add, decimal_sub, decimal_mul, decimal_cmp, decimal_pow2 },
+    aggregates: { DecimalSum },
+    collations: { decimal_collation },
+}
+
+fn to_decimal(value: &Value) -> Option<Decimal> {
+    match value.value_type() {
+        ValueType::Integer => value.to_integer().map(Decimal::from_integer),
+        ValueType::Float => value.to_float().and_then(Decimal::from_float),
+        ValueType::Text => value.to_text().and_then(Decimal::parse),
+        _ => None,
+    }
+}
+
+/// Applies `f` to the decimal arguments, returning NULL if one of them isn't a decimal.
+fn with_decimals<const N: usize>(args: &[Value], f: impl FnOnce([Decimal; N]) -> Value) -> Value {
+    let Ok(args) = <&[Value; N]>::try_from(args) else {
+        return Value::error(ResultCode::InvalidArgs);
+    };
+    let mut decimals = Vec::with_capacity(N);
+    for arg in args {
+        match to_decimal(arg) {
+            Some(decimal) => decimals.push(decimal),
+            None => return Value::null(),
+        }
+    }
+    match decimals.try_into() {
+        Ok(decimals) => f(decimals),
+        Err(_) => Value::null(),
+    }
+}
+
+#[scalar(name = "decimal")]
+fn decimal(args: &[Value]) -> Value {
+    with_decimals(args, |[x]| Value::from_text(x.to_string()))
+}
+
+#[scalar(name = "decimal_exp")]
+fn decimal_exp(args: &[Value]) -> Value {
+    with_decimals(args, |[x]| Value::from_text(x.to_exponential()))
+}
+
+#[scalar(name = "decimal_add")]
+fn decimal_add(args: &[Value]) -> Value {
+    with_decimals(args, |[a, b]| Value::from_text(a.add(&b).to_string()))
+}
+
+#[scalar(name = "decimal_sub")]
+fn decimal_sub(args: &[Value]) -> Value {
+    with_decimals(args, |[a, b]| Value::from_text(a.subtract(&b).to_string()))
+}
+
+#[scalar(name = "decimal_mul")]
+fn decimal_mul(args: &[Value]) -> Value {
+    with_decimals(args, |[a, b]| Value::from_text(a.multiply(&b).to_string()))
+}
+
+#[scalar(name = "decimal_cmp")]
+fn decimal_cmp(args: &[Value]) -> Value {
+    with_decimals(args, |[a, b]| Value::from_integer(a.cmp(&b) as i64))
+}
+
+#[scalar(name = "decimal_pow2")]
+fn decimal_pow2(args: &[Value]) -> Value {
+    if args.len() != 1 {
+        return Value::error(ResultCode::InvalidArgs);
+    }
+    match args[0].value_type() {
+        ValueType::Integer => match args[0].to_integer() {
+            Some(n) if (-decimal::MAX_POW2..=decimal::MAX_POW2).contains(&n) => {
+                Value::from_text(Decimal::pow2(n).to_string())
+            }
+            _ => Value::null(),
+        },
+        _ => Value::null(),
+    }
+}
+
+#[derive(AggregateDerive)]
+struct DecimalSum;
+
+impl AggFunc for DecimalSum {
+    /// The sum so far, and whether a value that isn't a decimal was seen, which makes the
+    /// sum NULL.
+    type State = (Option<Decimal>, bool);
+    type Error = &'static str;
+    const NAME: &'static str = "decimal_sum";
+    const ARGS: i32 = 1;
+
+    fn step(state: &mut Self::State, args: &[Value]) {
+        let Some(arg) = args
+            .first()
+            .filter(|arg| arg.value_type() != ValueType::Null)
+        else {
+            return;
+        };
+        let (sum, invalid) = state;
+        match to_decimal(arg) {
+            Some(x) => *sum = Some(sum.take().map_or(x.clone(), |sum| sum.add(&x))),
+            None => *invalid = true,
+        }
+    }
+
+    fn finalize(state: Self::State) -> Result<Value, Self::Error> {
+        Ok(match state {
+            (Some(sum), false) => Value::from_text(sum.to_string()),
+            _ => Value::null(),
+        })
+    }
+}
+
+/// Text that isn't a decimal number sorts after all numbers.
+#[collation(name = "decimal")]
+fn decimal_collation(lhs: &str, rhs: &str) -> Ordering {
+    match (Decimal::parse(lhs), Decimal::parse(rhs)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => lhs.cmp(rhs),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Value {
+        Value::from_text(s.to_string())
+    }
+
+    fn call(f: unsafe extern "C" fn(i32, *const Value) -> Value, args: &[Value]) -> Value {
+        unsafe { f(args.len() as i32, args.as_ptr()) }
+    }
+
+    fn text_result(f: unsafe extern "C" fn(i32, *const Value) -> Value, args: &[Value]) -> String {
+        call(f, args).to_text().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_scalars() {
+        assert_eq!(text_result(decimal, &[text("01.50")]), "1.5");
+        assert_eq!(text_result(decimal, &[Value::from_float(0.5)]), "0.5");
+        assert_eq!(
+            text_result(decimal_exp, &[Value::from_integer(-125)]),
+            "-1.25e+02"
+        );
+        assert_eq!(text_result(decimal_add, &[text("0.1"), text("0.2")]), "0.3");
+        assert_eq!(
+            text_result(decimal_sub, &[Value::from_integer(1), text("0.99")]),
+            "0.01"
+        );
+        assert_eq!(
+            text_result(decimal_mul, &[text("1.1"), text("1.1")]),
+            "1.21"
+        );
+        assert_eq!(text_result(decimal_pow2, &[Value::from_integer(-1)]), "0.5");
+        assert_eq!(
+            call(decimal_cmp, &[text("2"), text("10")]).to_integer(),
+            Some(-1)
+        );
+        assert_eq!(
+            call(decimal_cmp, &[text("1.0"), Value::from_integer(1)]).to_integer(),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        for args in [
+            vec![text("abc")],
+            vec![Value::null()],
+            vec![Value::from_blob(vec![1])],
+        ] {
+            assert_eq!(call(decimal, &args).value_type(), ValueType::Null);
+        }
+        assert_eq!(
+            call(decimal_add, &[text("1"), Value::null()]).value_type(),
+            ValueType::Null
+        );
+        assert_eq!(
+            call(decimal_pow2, &[Value::from_integer(20_001)]).value_type(),
+            ValueType::Null
+        );
+        assert_eq!(
+            call(decimal_add, &[text("1")]).value_type(),
+            ValueType::Error
+        );
+    }
+
+    #[test]
+    fn test_sum() {
+        let mut state = Default::default();
+        for value in [
+            text("0.1"),
+            Value::null(),
+            text("0.2"),
+            Value::from_integer(3),
+        ] {
+            DecimalSum::step(&mut state, &[value]);
+        }
+        let sum = DecimalSum::finalize(state).unwrap();
+        assert_eq!(sum.to_text(), Some("3.3"));
+
+        let empty = DecimalSum::finalize(Default::default()).unwrap();
+        assert_eq!(empty.value_type(), ValueType::Null);
+
+        let mut state = Default::default();
+        DecimalSum::step(&mut state, &[text("1")]);
+        DecimalSum::step(&mut state, &[text("x")]);
+        let invalid = DecimalSum::finalize(state).unwrap();
+        assert_eq!(invalid.value_type(), ValueType::Null);
+    }
+
+    #[test]
+    fn test_collation() {
+        let mut values = vec!["10", "9.5", "abc", "-1", "0009.50001"];
+        values.sort_by(|a, b| decimal_collation_cmp(a, b));
+        assert_eq!(values, ["-1", "9.5", "0009.50001", "10", "abc"]);
+    }
+
+    fn decimal_collation_cmp(lhs: &str, rhs: &str) -> Ordering {
+        let result = unsafe { decimal_collation(lhs.as_ptr(), lhs.len(), rhs.as_ptr(), rhs.len()) };
+        result.cmp(&0)
+    }
+}
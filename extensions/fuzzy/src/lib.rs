@@ -7,14 +7,18 @@ mod editdist;
 mod phonetic;
 mod rsoundex;
 mod soundex;
+mod spellfix;
 mod translit;
 
+use spellfix::SpellfixVTabModule;
+
 register_extension! {
-    scalars: {levenshtein, damerau_levenshtein, edit_distance, hamming, jaronwin, osadist, fuzzy_soundex, fuzzy_phonetic, fuzzy_caver, fuzzy_rsoundex, fuzzy_translit, fuzzy_script}
+    scalars: {levenshtein, damerau_levenshtein, edit_distance, hamming, jaronwin, osadist, fuzzy_soundex, fuzzy_phonetic, fuzzy_caver, fuzzy_rsoundex, fuzzy_translit, fuzzy_script},
+    vtabs: { SpellfixVTabModule }
 }
 
 /// Calculates and returns the Levenshtein distance of two non NULL strings.
-#[scalar(name = "fuzzy_leven")]
+#[scalar(name = "fuzzy_leven", alias = "levenshtein")]
 fn levenshtein(args: &[Value]) -> Value {
     let Some(arg1) = args[0].to_text() else {
         return Value::error(ResultCode::InvalidArgs);
@@ -160,14 +164,14 @@ fn damlev(s1: &str, s2: &str) -> i64 {
     matrix[rows - 1][cols - 1] as i64
 }
 //
-// fuzzy_editdist(A,B)
+// fuzzy_editdist(A,B), or editdist(A,B)
 //
 // Return the cost of transforming string A into string B.  Both strings
 // must be pure ASCII text.  If A ends with '*' then it is assumed to be
 // a prefix of B and extra characters on the end of B have minimal additional
 // cost.
 //
-#[scalar(name = "fuzzy_editdist")]
+#[scalar(name = "fuzzy_editdist", alias = "editdist")]
 fn edit_distance(args: &[Value]) {
     let Some(arg1) = args[0].to_text() else {
         return Value::error(ResultCode::InvalidArgs);
@@ -0,0 +1,457 @@
+//! A `spellfix1`-style virtual table, after SQLite's: <https://sqlite.org/spellfix1.html>
+//!
+//! The table holds a vocabulary of words and finds the ones closest to a misspelled word,
+//! e.g. to suggest corrections in a search box.
+//!
+//! ```sql
+//! CREATE VIRTUAL TABLE dict USING spellfix1;
+//! INSERT INTO dict(word, rank) VALUES ('kennesaw', 10), ('kenosha', 5);
+//! SELECT word, distance FROM dict WHERE search = 'kennasaw' AND top = 3;
+//! ```
+//!
+//! ## Columns:
+//! - `word` — a word of the vocabulary
+//! - `rank` — how common the word is, 1 if not given. Among words as close to the search,
+//!   higher ranks come first.
+//! - `distance` — the edit distance from the search to the word, see `fuzzy_editdist`
+//! - `score` — `distance` adjusted by `rank`; results are sorted by it
+//! - `phonehash` — the phonetic hash of the word, see `fuzzy_phonetic`
+//! - `search` (hidden) — the misspelled word to search for. Ending it with `*` searches for
+//!   words starting with it.
+//! - `top` (hidden) — the maximum number of results, 20 by default
+//!
+//! Words are transliterated to lowercase ASCII before they are compared, so `Café` matches
+//! `cafe`. Without a `search` constraint, the whole vocabulary is returned in rowid order,
+//! with `distance` and `score` NULL.
+//!
+//! Unlike SQLite, where the search is written as `word MATCH 'kennasaw'`, the search goes in
+//! the `search` column, and every word of the vocabulary is compared instead of only those
+//! with a similar phonetic hash. As with the `rtree` extension, the vocabulary is kept in
+//! memory and lost when the connection is closed.
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::sync::Arc;
+use turso_ext::{
+    Connection, ConstraintInfo, ConstraintOp, ConstraintUsage, IndexInfo, OrderByInfo, ResultCode,
+    VTabCursor, VTabKind, VTabModule, VTabModuleDerive, VTable, Value, ValueType,
+};
+
+use crate::{editdist, phonetic, translit};
+
+const WORD_COLUMN: u32 = 0;
+const RANK_COLUMN: u32 = 1;
+const DISTANCE_COLUMN: u32 = 2;
+const SCORE_COLUMN: u32 = 3;
+const PHONEHASH_COLUMN: u32 = 4;
+const SEARCH_COLUMN: u32 = 5;
+const TOP_COLUMN: u32 = 6;
+
+const DEFAULT_TOP: i64 = 20;
+
+/// `filter` searches for the words closest to its first argument. If `idx_str` is
+/// [TOP_GIVEN], the second argument is the maximum number of results.
+const IDX_SEARCH: i32 = 1;
+const TOP_GIVEN: &str = "top";
+
+#[derive(Debug, VTabModuleDerive, Default)]
+pub struct SpellfixVTabModule;
+
+impl VTabModule for SpellfixVTabModule {
+    type Table = SpellfixTable;
+    const VTAB_KIND: VTabKind = VTabKind::VirtualTable;
+    const NAME: &'static str = "spellfix1";
+    const READONLY: bool = false;
+
+    fn create(args: &[Value]) -> Result<(String, Self::Table), ResultCode> {
+        if !args.is_empty() {
+            return Err(ResultCode::InvalidArgs);
+        }
+        let schema = "CREATE TABLE x(word TEXT, rank INTEGER, distance INTEGER, score INTEGER, \
+                      phonehash TEXT, search TEXT HIDDEN, top INTEGER HIDDEN)"
+            .to_string();
+        let table = SpellfixTable {
+            words: Rc::new(RefCell::new(BTreeMap::new())),
+            savepoint: None,
+        };
+        Ok((schema, table))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Word {
+    word: String,
+    rank: i64,
+    /// The word transliterated to lowercase ASCII, which searches compare against.
+    key: String,
+    phonehash: String,
+}
+
+impl Word {
+    fn new(word: String, rank: i64) -> Self {
+        let key = search_key(&word);
+        let phonehash = phonetic::phonetic_hash_str(Some(&key)).unwrap_or_default();
+        Self {
+            word,
+            rank,
+            key,
+            phonehash,
+        }
+    }
+}
+
+fn search_key(word: &str) -> String {
+    translit::transliterate_str(word).to_ascii_lowercase()
+}
+
+/// SQLite's spellfix1 score: the distance, plus a penalty that shrinks as the rank grows.
+fn score(distance: i64, rank: i64) -> i64 {
+    let log2 = if rank > 0 {
+        64 - rank.leading_zeros()
+    } else {
+        0
+    };
+    distance + 32 - log2 as i64
+}
+
+type Words = BTreeMap<i64, Word>;
+
+pub struct SpellfixTable {
+    words: Rc<RefCell<Words>>,
+    /// Words at the start of the current transaction, restored on rollback.
+    savepoint: Option<Words>,
+}
+
+impl SpellfixTable {
+    fn word_from_values(values: &[Value]) -> Result<Word, String> {
+        let word = values
+            .get(WORD_COLUMN as usize)
+            .filter(|word| word.value_type() == ValueType::Text)
+            .and_then(|word| word.to_text())
+            .ok_or_else(|| "spellfix1: word must be text".to_string())?;
+        let rank = match values.get(RANK_COLUMN as usize) {
+            None => 1,
+            Some(rank) if rank.value_type() == ValueType::Null => 1,
+            Some(rank) if rank.value_type() == ValueType::Integer => rank.to_integer().unwrap_or(1),
+            Some(_) => return Err("spellfix1: rank must be an integer".to_string()),
+        };
+        Ok(Word::new(word.to_string(), rank))
+    }
+}
+
+impl VTable for SpellfixTable {
+    type Cursor = SpellfixCursor;
+    type Error = String;
+
+    fn open(&self, _conn: Option<Arc<Connection>>) -> Result<Self::Cursor, Self::Error> {
+        Ok(SpellfixCursor {
+            words: self.words.clone(),
+            search: None,
+            rows: Vec::new(),
+            index: 0,
+        })
+    }
+
+    fn best_index(
+        constraints: &[ConstraintInfo],
+        _order_by: &[OrderByInfo],
+    ) -> Result<IndexInfo, ResultCode> {
+        let unused = ConstraintUsage {
+            argv_index: None,
+            omit: false,
+        };
+        let mut constraint_usages = vec![unused; constraints.len()];
+        let position = |column| {
+            constraints
+                .iter()
+                .position(|c| c.usable && c.op == ConstraintOp::Eq && c.column_index == column)
+        };
+        let Some(search) = position(SEARCH_COLUMN) else {
+            return Ok(IndexInfo {
+                constraint_usages,
+                ..Default::default()
+            });
+        };
+        constraint_usages[search] = ConstraintUsage {
+            argv_index: Some(1),
+            omit: true,
+        };
+        let top = position(TOP_COLUMN);
+        if let Some(top) = top {
+            constraint_usages[top] = ConstraintUsage {
+                argv_index: Some(2),
+                omit: true,
+            };
+        }
+        Ok(IndexInfo {
+            idx_num: IDX_SEARCH,
+            idx_str: top.map(|_| TOP_GIVEN.to_string()),
+            estimated_cost: 1000.0,
+            estimated_rows: DEFAULT_TOP as u32,
+            constraint_usages,
+            ..Default::default()
+        })
+    }
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        self.savepoint = Some(self.words.borrow().clone());
+        Ok(())
+    }
+
+    fn insert(&mut self, args: &[Value]) -> Result<i64, Self::Error> {
+        let word = Self::word_from_values(args)?;
+        let mut words = self.words.borrow_mut();
+        let rowid = words
+            .last_key_value()
+            .map_or(1, |(rowid, _)| rowid.saturating_add(1));
+        words.insert(rowid, word);
+        Ok(rowid)
+    }
+
+    fn update(&mut self, rowid: i64, args: &[Value]) -> Result<(), Self::Error> {
+        let word = Self::word_from_values(args)?;
+        match self.words.borrow_mut().get_mut(&rowid) {
+            Some(existing) => *existing = word,
+            None => return Err(format!("spellfix1: no row with rowid {rowid}")),
+        }
+        Ok(())
+    }
+
+    fn delete(&mut self, rowid: i64) -> Result<(), Self::Error> {
+        self.words.borrow_mut().remove(&rowid);
+        Ok(())
+    }
+
+    fn commit(&mut self) -> Result<(), Self::Error> {
+        self.savepoint = None;
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> Result<(), Self::Error> {
+        if let Some(savepoint) = self.savepoint.take() {
+            *self.words.borrow_mut() = savepoint;
+        }
+        Ok(())
+    }
+}
+
+struct Row {
+    rowid: i64,
+    word: Word,
+    /// Distance and score, for searches.
+    score: Option<(i64, i64)>,
+}
+
+/// The search and limit of the current query, returned as the hidden columns.
+struct Search {
+    search: String,
+    top: i64,
+}
+
+pub struct SpellfixCursor {
+    words: Rc<RefCell<Words>>,
+    search: Option<Search>,
+    /// Snapshot of the matching rows taken by `filter`.
+    rows: Vec<Row>,
+    index: usize,
+}
+
+impl SpellfixCursor {
+    fn search(&self, search: &str, top: i64) -> Vec<Row> {
+        let key = search_key(search);
+        let mut rows = self
+            .words
+            .borrow()
+            .iter()
+            .filter_map(|(rowid, word)| {
+                // Both keys are ASCII, so this doesn't fail.
+                let distance = editdist::edit_distance(&key, &word.key).ok()? as i64;
+                Some(Row {
+                    rowid: *rowid,
+                    word: word.clone(),
+                    score: Some((distance, score(distance, word.rank))),
+                })
+            })
+            .collect::<Vec<_>>();
+        rows.sort_by_key(|row| (row.score.map(|(_, score)| score), row.rowid));
+        rows.truncate(top.max(0) as usize);
+        rows
+    }
+}
+
+impl VTabCursor for SpellfixCursor {
+    type Error = String;
+
+    fn filter(&mut self, args: &[Value], idx_info: Option<(&str, i32)>) -> ResultCode {
+        self.index = 0;
+        self.search = None;
+        self.rows = match idx_info {
+            Some((idx_str, IDX_SEARCH)) => {
+                let top = if idx_str == TOP_GIVEN {
+                    args.get(1).and_then(|top| top.to_integer())
+                } else {
+                    Some(DEFAULT_TOP)
+                };
+                match (args.first().and_then(|search| search.to_text()), top) {
+                    (Some(search), Some(top)) => {
+                        self.search = Some(Search {
+                            search: search.to_string(),
+                            top,
+                        });
+                        self.search(search, top)
+                    }
+                    _ => Vec::new(),
+                }
+            }
+            _ => self
+                .words
+                .borrow()
+                .iter()
+                .map(|(rowid, word)| Row {
+                    rowid: *rowid,
+                    word: word.clone(),
+                    score: None,
+                })
+                .collect(),
+        };
+        if self.rows.is_empty() {
+            ResultCode::EOF
+        } else {
+            ResultCode::OK
+        }
+    }
+
+    fn rowid(&self) -> i64 {
+        self.rows.get(self.index).map_or(-1, |row| row.rowid)
+    }
+
+    fn column(&self, idx: u32) -> Result<Value, Self::Error> {
+        let Some(row) = self.rows.get(self.index) else {
+            return Err("cursor out of range".to_string());
+        };
+        let value = match idx {
+            WORD_COLUMN => Value::from_text(row.word.word.clone()),
+            RANK_COLUMN => Value::from_integer(row.word.rank),
+            DISTANCE_COLUMN => row
+                .score
+                .map_or_else(Value::null, |(distance, _)| Value::from_integer(distance)),
+            SCORE_COLUMN => row
+                .score
+                .map_or_else(Value::null, |(_, score)| Value::from_integer(score)),
+            PHONEHASH_COLUMN => Value::from_text(row.word.phonehash.clone()),
+            SEARCH_COLUMN => self
+                .search
+                .as_ref()
+                .map_or_else(Value::null, |s| Value::from_text(s.search.clone())),
+            TOP_COLUMN => self
+                .search
+                .as_ref()
+                .map_or_else(Value::null, |s| Value::from_integer(s.top)),
+            _ => Value::null(),
+        };
+        Ok(value)
+    }
+
+    fn eof(&self) -> bool {
+        self.index >= self.rows.len()
+    }
+
+    fn next(&mut self) -> ResultCode {
+        self.index += 1;
+        if self.eof() {
+            ResultCode::EOF
+        } else {
+            ResultCode::OK
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(words: &[(&str, i64)]) -> SpellfixTable {
+        let mut table = SpellfixVTabModule::create(&[]).unwrap().1;
+        for (word, rank) in words {
+            table
+                .insert(&[
+                    Value::from_text(word.to_string()),
+                    Value::from_integer(*rank),
+                ])
+                .unwrap();
+        }
+        table
+    }
+
+    fn search(table: &SpellfixTable, search: &str, top: Option<i64>) -> Vec<(String, i64)> {
+        let mut cursor = table.open(None).unwrap();
+        let mut args = vec![Value::from_text(search.to_string())];
+        args.extend(top.map(Value::from_integer));
+        let idx_str = if top.is_some() { TOP_GIVEN } else { "" };
+        let mut found = Vec::new();
+        if cursor.filter(&args, Some((idx_str, IDX_SEARCH))) == ResultCode::OK {
+            while !cursor.eof() {
+                let word = cursor.column(WORD_COLUMN).unwrap();
+                let distance = cursor.column(DISTANCE_COLUMN).unwrap();
+                found.push((
+                    word.to_text().unwrap().to_string(),
+                    distance.to_integer().unwrap(),
+                ));
+                cursor.next();
+            }
+        }
+        found
+    }
+
+    #[test]
+    fn test_search() {
+        let table = table(&[
+            ("kennesaw", 1),
+            ("kenosha", 1),
+            ("kenmore", 1),
+            ("boston", 1),
+        ]);
+        let found = search(&table, "kennasaw", Some(2));
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].0, "kennesaw");
+        assert!(found[0].1 < found[1].1);
+        assert_eq!(search(&table, "boston", None)[0], ("boston".to_string(), 0));
+        assert_eq!(search(&table, "ken*", None).len(), 4);
+        assert!(search(&table, "kenosha", Some(0)).is_empty());
+    }
+
+    #[test]
+    fn test_rank_breaks_ties() {
+        let table = table(&[("cat", 1), ("cot", 1000)]);
+        let found = search(&table, "cut", None);
+        assert_eq!(found[0].0, "cot");
+        assert_eq!(found[0].1, found[1].1);
+    }
+
+    #[test]
+    fn test_transliterated_search() {
+        let table = table(&[("Café", 1), ("cafeteria", 1)]);
+        assert_eq!(search(&table, "CAFE", None)[0], ("Café".to_string(), 0));
+    }
+
+    #[test]
+    fn test_writes() {
+        let mut table = table(&[("apple", 1)]);
+        assert!(table.insert(&[Value::null()]).is_err());
+        assert!(table
+            .insert(&[
+                Value::from_text("pear".to_string()),
+                Value::from_text("high".to_string())
+            ])
+            .is_err());
+        table.begin().unwrap();
+        table
+            .update(1, &[Value::from_text("banana".to_string()), Value::null()])
+            .unwrap();
+        assert_eq!(search(&table, "banana", None)[0].1, 0);
+        table.rollback().unwrap();
+        assert_eq!(search(&table, "apple", None)[0].1, 0);
+        table.delete(1).unwrap();
+        assert!(search(&table, "apple", None).is_empty());
+    }
+}
@@ -0,0 +1,347 @@
+//! Unicode-aware case mapping, `LIKE` and collations, modeled on SQLite's ICU extension:
+//! <https://sqlite.org/src/dir/ext/icu>
+//!
+//! The built-in `upper()`, `lower()`, `LIKE` and `NOCASE` only handle ASCII. This extension
+//! uses the Unicode data of ICU4X instead, so the case of letters like `é`, `ß` or `Σ` is
+//! mapped, and strings sort the way users of a language expect.
+//!
+//! ## Functions:
+//! - `icu_upper(X [, LOCALE])`, `icu_lower(X [, LOCALE])` — full case mapping, with the rules
+//!   of LOCALE if given, e.g. `icu_upper('i', 'tr')` is `İ`
+//! - `icu_casefold(X)` — case folding, for comparing strings without regard to case
+//! - `icu_like(PATTERN, X [, ESCAPE])` — `X LIKE PATTERN` ignoring case for all of Unicode
+//!
+//! ## Collations:
+//! - `unicode` — the Unicode collation algorithm with the root locale
+//! - `unicode_nocase` — the same, ignoring case
+//! - `unicode_<language>`, e.g. `unicode_sv` — with the rules of a language, for the languages
+//!   listed in [LOCALE_COLLATIONS]
+//!
+//! ```sql
+//! SELECT name FROM people ORDER BY name COLLATE unicode_de;
+//! ```
+use icu_casemap::CaseMapper;
+use icu_collator::{Collator, CollatorOptions, Strength};
+use icu_locid::{LanguageIdentifier, Locale};
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::str::FromStr;
+use turso_ext::{collation, register_extension, scalar, ResultCode, Value, ValueType};
+
+/// Registers the extension, along with a collation for each locale in the list.
+macro_rules! register_with_locale_collations {
+    ($($ident:ident => $name:literal, $locale:literal;)*) => {
+        register_extension! {
+            scalars: { icu_upper, icu_lower, icu_casefold, icu_like },
+            collations: { unicode, unicode_nocase, $($ident),* },
+        }
+
+        /// Languages with a `unicode_<language>` collation.
+        pub const LOCALE_COLLATIONS: &[&str] = &[$($locale),*];
+
+        $(
+            #[collation(name = $name)]
+            fn $ident(lhs: &str, rhs: &str) -> Ordering {
+                compare($name, $locale, Strength::Tertiary, lhs, rhs)
+            }
+        )*
+    };
+}
+
+register_with_locale_collations! {
+    unicode_da => "unicode_da", "da";
+    unicode_de => "unicode_de", "de";
+    unicode_en => "unicode_en", "en";
+    unicode_es => "unicode_es", "es";
+    unicode_fi => "unicode_fi", "fi";
+    unicode_fr => "unicode_fr", "fr";
+    unicode_it => "unicode_it", "it";
+    unicode_ja => "unicode_ja", "ja";
+    unicode_ko => "unicode_ko", "ko";
+    unicode_nb => "unicode_nb", "nb";
+    unicode_nl => "unicode_nl", "nl";
+    unicode_pl => "unicode_pl", "pl";
+    unicode_pt => "unicode_pt", "pt";
+    unicode_ru => "unicode_ru", "ru";
+    unicode_sv => "unicode_sv", "sv";
+    unicode_tr => "unicode_tr", "tr";
+    unicode_zh => "unicode_zh", "zh";
+}
+
+#[collation(name = "unicode")]
+fn unicode(lhs: &str, rhs: &str) -> Ordering {
+    compare("unicode", "und", Strength::Tertiary, lhs, rhs)
+}
+
+#[collation(name = "unicode_nocase")]
+fn unicode_nocase(lhs: &str, rhs: &str) -> Ordering {
+    compare("unicode_nocase", "und", Strength::Secondary, lhs, rhs)
+}
+
+/// Compares two strings with the collator of a collation, which is created on first use.
+fn compare(name: &'static str, locale: &str, strength: Strength, lhs: &str, rhs: &str) -> Ordering {
+    thread_local! {
+        static COLLATORS: RefCell<HashMap<&'static str, Option<Collator>>> =
+            RefCell::new(HashMap::new());
+    }
+    COLLATORS.with_borrow_mut(|collators| {
+        let collator = collators.entry(name).or_insert_with(|| {
+            let locale = Locale::from_str(locale).ok()?;
+            let mut options = CollatorOptions::new();
+            options.strength = Some(strength);
+            Collator::try_new(&(&locale).into(), options).ok()
+        });
+        match collator {
+            Some(collator) => collator.compare(lhs, rhs),
+            None => lhs.cmp(rhs),
+        }
+    })
+}
+
+/// The language of the optional LOCALE argument of the case mapping functions.
+fn language(args: &[Value], index: usize) -> Result<LanguageIdentifier, ResultCode> {
+    match args.get(index) {
+        None => Ok(LanguageIdentifier::UND),
+        Some(locale) => locale
+            .to_text()
+            .and_then(|locale| LanguageIdentifier::from_str(&locale.replace('_', "-")).ok())
+            .ok_or(ResultCode::InvalidArgs),
+    }
+}
+
+/// Applies a case mapping to a text argument. Other values are returned unchanged, except
+/// that blobs are read as text.
+fn map_case(
+    args: &[Value],
+    map: impl Fn(&CaseMapper, &str, &LanguageIdentifier) -> String,
+) -> Value {
+    if args.is_empty() || args.len() > 2 {
+        return Value::error(ResultCode::InvalidArgs);
+    }
+    let language = match language(args, 1) {
+        Ok(language) => language,
+        Err(code) => return Value::error(code),
+    };
+    let value = &args[0];
+    let text = match value.value_type() {
+        ValueType::Text => value.to_text().unwrap_or_default().to_string(),
+        ValueType::Blob => String::from_utf8_lossy(&value.to_blob().unwrap_or_default()).into(),
+        ValueType::Integer => return Value::from_integer(value.to_integer().unwrap_or_default()),
+        ValueType::Float => return Value::from_float(value.to_float().unwrap_or_default()),
+        _ => return Value::null(),
+    };
+    Value::from_text(map(&CaseMapper::new(), &text, &language))
+}
+
+#[scalar(name = "icu_upper")]
+fn icu_upper(args: &[Value]) -> Value {
+    map_case(args, |mapper, text, language| {
+        mapper.uppercase_to_string(text, language)
+    })
+}
+
+#[scalar(name = "icu_lower")]
+fn icu_lower(args: &[Value]) -> Value {
+    map_case(args, |mapper, text, language| {
+        mapper.lowercase_to_string(text, language)
+    })
+}
+
+#[scalar(name = "icu_casefold")]
+fn icu_casefold(args: &[Value]) -> Value {
+    if args.len() != 1 {
+        return Value::error(ResultCode::InvalidArgs);
+    }
+    map_case(args, |mapper, text, _| mapper.fold_string(text))
+}
+
+#[scalar(name = "icu_like")]
+fn icu_like(args: &[Value]) -> Value {
+    if args.len() != 2 && args.len() != 3 {
+        return Value::error(ResultCode::InvalidArgs);
+    }
+    if args.iter().any(|arg| arg.value_type() == ValueType::Null) {
+        return Value::null();
+    }
+    let escape = match args.get(2).map(|escape| escape.to_text()) {
+        None => None,
+        Some(Some(escape)) if escape.chars().count() == 1 => escape.chars().next(),
+        Some(_) => return Value::error(ResultCode::InvalidArgs),
+    };
+    let (Some(pattern), Some(text)) = (text_of(&args[0]), text_of(&args[1])) else {
+        return Value::null();
+    };
+    Value::from_integer(like(&pattern, &text, escape) as i64)
+}
+
+fn text_of(value: &Value) -> Option<String> {
+    match value.value_type() {
+        ValueType::Text => value.to_text().map(str::to_string),
+        ValueType::Integer => value.to_integer().map(|i| i.to_string()),
+        ValueType::Float => value.to_float().map(|f| f.to_string()),
+        ValueType::Blob => value
+            .to_blob()
+            .map(|blob| String::from_utf8_lossy(&blob).into()),
+        _ => None,
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    /// `%`
+    Many,
+    /// `_`
+    One,
+    /// A character, case folded
+    Char(char),
+}
+
+/// Whether `text` matches the LIKE `pattern`, comparing characters by their simple case
+/// folding.
+fn like(pattern: &str, text: &str, escape: Option<char>) -> bool {
+    let mapper = CaseMapper::new();
+    let mut tokens = Vec::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        tokens.push(match c {
+            c if Some(c) == escape => match chars.next() {
+                Some(escaped) => Token::Char(mapper.simple_fold(escaped)),
+                // A pattern ending with the escape character matches nothing.
+                None => return false,
+            },
+            '%' => Token::Many,
+            '_' => Token::One,
+            c => Token::Char(mapper.simple_fold(c)),
+        });
+    }
+    let text = text
+        .chars()
+        .map(|c| mapper.simple_fold(c))
+        .collect::<Vec<_>>();
+
+    // Match greedily, going back to the last `%` to let it match one more character when the
+    // rest of the pattern doesn't match.
+    let (mut p, mut t) = (0, 0);
+    let mut last_many = None;
+    while t < text.len() {
+        match tokens.get(p) {
+            Some(Token::Many) => {
+                last_many = Some((p + 1, t));
+                p += 1;
+                continue;
+            }
+            Some(Token::One) => {
+                p += 1;
+                t += 1;
+                continue;
+            }
+            Some(Token::Char(c)) if *c == text[t] => {
+                p += 1;
+                t += 1;
+                continue;
+            }
+            _ => {}
+        }
+        match last_many {
+            Some((after_many, start)) => {
+                p = after_many;
+                t = start + 1;
+                last_many = Some((after_many, start + 1));
+            }
+            None => return false,
+        }
+    }
+    tokens[p..].iter().all(|token| *token == Token::Many)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Value {
+        Value::from_text(s.to_string())
+    }
+
+    fn call(f: fn(&[Value]) -> Value, args: &[Value]) -> Option<String> {
+        f(args).to_text().map(str::to_string)
+    }
+
+    #[test]
+    fn test_case_mapping() {
+        let upper = |args: &[Value]| unsafe { icu_upper(args.len() as i32, args.as_ptr()) };
+        let lower = |args: &[Value]| unsafe { icu_lower(args.len() as i32, args.as_ptr()) };
+        let casefold = |args: &[Value]| unsafe { icu_casefold(args.len() as i32, args.as_ptr()) };
+        assert_eq!(call(upper, &[text("straße")]), Some("STRASSE".to_string()));
+        assert_eq!(call(lower, &[text("ÉCOLE")]), Some("école".to_string()));
+        assert_eq!(call(upper, &[text("i"), text("tr")]), Some("İ".to_string()));
+        assert_eq!(call(upper, &[text("i")]), Some("I".to_string()));
+        assert_eq!(call(lower, &[text("ΣΑΣ")]), Some("σας".to_string()));
+        assert_eq!(
+            call(casefold, &[text("Straße")]),
+            Some("strasse".to_string())
+        );
+        assert_eq!(upper(&[Value::null()]).value_type(), ValueType::Null);
+        assert_eq!(
+            upper(&[text("a"), text("not a locale!")]).value_type(),
+            ValueType::Error
+        );
+    }
+
+    #[test]
+    fn test_like() {
+        assert!(like("élan%", "ÉLAN VITAL", None));
+        assert!(like("_ber", "Über", None));
+        assert!(like("%σ%", "ΦΙΛΟΣΟΦΙΑ", None));
+        assert!(!like("a_c", "ac", None));
+        assert!(like("a%b%c", "aXbYbZc", None));
+        assert!(!like("a%b%c", "aXbYbZ", None));
+        assert!(like("100\\%", "100%", Some('\\')));
+        assert!(!like("100\\%", "1000", Some('\\')));
+        assert!(!like("abc\\", "abc", Some('\\')));
+        assert!(like("", "", None));
+        assert!(like("%", "", None));
+    }
+
+    #[test]
+    fn test_collations() {
+        // Accented letters sort with their base letter instead of after `z`.
+        assert_eq!(unicode_cmp("éclair", "zebra"), Ordering::Less);
+        assert_eq!("éclair".cmp("zebra"), Ordering::Greater);
+        assert_ne!(unicode_cmp("a", "A"), Ordering::Equal);
+        assert_eq!(
+            compare(
+                "unicode_nocase",
+                "und",
+                Strength::Secondary,
+                "apple",
+                "Banana"
+            ),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare(
+                "unicode_nocase",
+                "und",
+                Strength::Secondary,
+                "École",
+                "école"
+            ),
+            Ordering::Equal
+        );
+        // In Swedish, `ä` sorts after `z`.
+        assert_eq!(
+            compare("unicode_sv", "sv", Strength::Tertiary, "ä", "z"),
+            Ordering::Greater
+        );
+        assert_eq!(
+            compare("unicode_de", "de", Strength::Tertiary, "ä", "z"),
+            Ordering::Less
+        );
+    }
+
+    fn unicode_cmp(lhs: &str, rhs: &str) -> Ordering {
+        let result = unsafe { unicode(lhs.as_ptr(), lhs.len(), rhs.as_ptr(), rhs.len()) };
+        result.cmp(&0)
+    }
+}
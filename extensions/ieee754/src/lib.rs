@@ -0,0 +1,329 @@
+//! Port of SQLite's ieee754 extension: <https://sqlite.org/src/file/ext/misc/ieee754.c>, along
+//! with functions for the variable-length integers of the database file format:
+//! <https://www.sqlite.org/fileformat2.html#varint>
+//!
+//! They show exactly how numbers are stored, which helps when debugging rounding or decoding
+//! records by hand.
+//!
+//! ## Functions:
+//! - `ieee754(X)` — the float X as text `ieee754(M,E)`, where X = M × 2^E, e.g.
+//!   `ieee754(0.75)` is `ieee754(3,-2)` and `ieee754(2.0)` is `ieee754(2,0)`
+//! - `ieee754(M, E)` — the float M × 2^E
+//! - `ieee754_mantissa(X)`, `ieee754_exponent(X)` — M and E of `ieee754(X)`
+//! - `ieee754_to_blob(X)` — the 8 big-endian bytes of the float X
+//! - `ieee754_from_blob(B)` — the float stored in the 8 big-endian bytes B
+//! - `varint_encode(I)` — the integer I as a varint blob
+//! - `varint_decode(B [, OFFSET])` — the integer of the varint at byte OFFSET (0 by default)
+//!   of the blob B, or NULL if B doesn't hold a complete varint there
+//!
+//! The single-argument functions also accept an 8-byte blob as the float, and return NULL
+//! for values that aren't numbers.
+use turso_ext::{register_extension, scalar, ResultCode, Value, ValueType};
+
+register_extension! {
+    scalars: { ieee754, ieee754_mantissa, ieee754_exponent, ieee754_to_blob, ieee754_from_blob, varint_encode, varint_decode },
+}
+
+/// Bias of the exponent of a float, plus the 52 bits of its fraction.
+const EXPONENT_OFFSET: i64 = 1075;
+
+/// The float of a single argument: a number, or 8 bytes as stored by `ieee754_to_blob`.
+fn float_arg(args: &[Value]) -> Result<Option<f64>, ResultCode> {
+    let [arg] = args else {
+        return Err(ResultCode::InvalidArgs);
+    };
+    Ok(match arg.value_type() {
+        ValueType::Integer | ValueType::Float => arg.to_float(),
+        ValueType::Text => arg.to_text().and_then(|text| text.trim().parse().ok()),
+        ValueType::Blob => arg
+            .to_blob()
+            .and_then(|blob| <[u8; 8]>::try_from(blob).ok())
+            .map(f64::from_be_bytes),
+        _ => None,
+    })
+}
+
+/// Splits a float into a mantissa and an exponent, such that f = m × 2^e. As in SQLite, trailing
+/// zero bits of the mantissa are only moved to the exponent while the exponent is negative.
+fn decompose(f: f64) -> (i64, i64) {
+    if f == 0.0 {
+        return (0, 0);
+    }
+    let bits = f.abs().to_bits();
+    let mut exponent = (bits >> 52) as i64;
+    let mut mantissa = (bits & ((1 << 52) - 1)) as i64;
+    if exponent == 0 {
+        mantissa <<= 1;
+    } else {
+        mantissa |= 1 << 52;
+    }
+    while exponent < EXPONENT_OFFSET && mantissa > 0 && mantissa & 1 == 0 {
+        mantissa >>= 1;
+        exponent += 1;
+    }
+    if f < 0.0 {
+        mantissa = -mantissa;
+    }
+    (mantissa, exponent - EXPONENT_OFFSET)
+}
+
+/// The float closest to m × 2^e, as computed by SQLite.
+fn compose(mantissa: i64, exponent: i64) -> f64 {
+    let mut exponent = exponent.clamp(-10000, 10000);
+    let negative = mantissa < 0;
+    let mut mantissa = mantissa.unsigned_abs();
+    if mantissa == 0 && exponent.abs() < 1000 {
+        return 0.0;
+    }
+    // Normalize the mantissa to 53 bits.
+    while mantissa >> 53 != 0 {
+        mantissa >>= 1;
+        exponent += 1;
+    }
+    while mantissa != 0 && mantissa >> 52 == 0 {
+        mantissa <<= 1;
+        exponent -= 1;
+    }
+    exponent += EXPONENT_OFFSET;
+    if exponent <= 0 {
+        // Subnormal
+        mantissa = if 1 - exponent >= 64 {
+            0
+        } else {
+            mantissa >> (1 - exponent)
+        };
+        exponent = 0;
+    } else if exponent > 0x7ff {
+        exponent = 0x7ff;
+    }
+    let mut bits = (mantissa & ((1 << 52) - 1)) | ((exponent as u64) << 52);
+    if negative {
+        bits |= 1 << 63;
+    }
+    f64::from_bits(bits)
+}
+
+#[scalar(name = "ieee754")]
+fn ieee754(args: &[Value]) -> Value {
+    if let [mantissa, exponent] = args {
+        return match (mantissa.to_integer(), exponent.to_integer()) {
+            (Some(mantissa), Some(exponent)) => Value::from_float(compose(mantissa, exponent)),
+            _ => Value::null(),
+        };
+    }
+    match float_arg(args) {
+        Ok(Some(f)) => {
+            let (mantissa, exponent) = decompose(f);
+            Value::from_text(format!("ieee754({mantissa},{exponent})"))
+        }
+        Ok(None) => Value::null(),
+        Err(code) => Value::error(code),
+    }
+}
+
+#[scalar(name = "ieee754_mantissa")]
+fn ieee754_mantissa(args: &[Value]) -> Value {
+    match float_arg(args) {
+        Ok(Some(f)) => Value::from_integer(decompose(f).0),
+        Ok(None) => Value::null(),
+        Err(code) => Value::error(code),
+    }
+}
+
+#[scalar(name = "ieee754_exponent")]
+fn ieee754_exponent(args: &[Value]) -> Value {
+    match float_arg(args) {
+        Ok(Some(f)) => Value::from_integer(decompose(f).1),
+        Ok(None) => Value::null(),
+        Err(code) => Value::error(code),
+    }
+}
+
+#[scalar(name = "ieee754_to_blob")]
+fn ieee754_to_blob(args: &[Value]) -> Value {
+    match float_arg(args) {
+        Ok(Some(f)) => Value::from_blob(f.to_be_bytes().to_vec()),
+        Ok(None) => Value::null(),
+        Err(code) => Value::error(code),
+    }
+}
+
+#[scalar(name = "ieee754_from_blob")]
+fn ieee754_from_blob(args: &[Value]) -> Value {
+    let [arg] = args else {
+        return Value::error(ResultCode::InvalidArgs);
+    };
+    if arg.value_type() != ValueType::Blob {
+        return Value::null();
+    }
+    match arg
+        .to_blob()
+        .and_then(|blob| <[u8; 8]>::try_from(blob).ok())
+    {
+        Some(bytes) => Value::from_float(f64::from_be_bytes(bytes)),
+        None => Value::null(),
+    }
+}
+
+/// Encodes an integer as a big-endian varint of 1 to 9 bytes. The first 8 bytes hold 7 bits
+/// each, with the high bit set if more bytes follow, and the 9th byte holds 8 bits.
+fn encode_varint(value: u64) -> Vec<u8> {
+    if value >> 56 != 0 {
+        let mut bytes = vec![0; 9];
+        bytes[8] = value as u8;
+        let mut rest = value >> 8;
+        for byte in bytes[..8].iter_mut().rev() {
+            *byte = (rest & 0x7f) as u8 | 0x80;
+            rest >>= 7;
+        }
+        return bytes;
+    }
+    let mut bytes = vec![(value & 0x7f) as u8];
+    let mut rest = value >> 7;
+    while rest != 0 {
+        bytes.push((rest & 0x7f) as u8 | 0x80);
+        rest >>= 7;
+    }
+    bytes.reverse();
+    bytes
+}
+
+/// Decodes the varint at the start of `bytes`, returning None if it is incomplete.
+fn decode_varint(bytes: &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+    for i in 0..8 {
+        let byte = *bytes.get(i)?;
+        value = (value << 7) | (byte & 0x7f) as u64;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    Some((value << 8) | *bytes.get(8)? as u64)
+}
+
+#[scalar(name = "varint_encode")]
+fn varint_encode(args: &[Value]) -> Value {
+    let [arg] = args else {
+        return Value::error(ResultCode::InvalidArgs);
+    };
+    if arg.value_type() != ValueType::Integer {
+        return Value::null();
+    }
+    match arg.to_integer() {
+        Some(i) => Value::from_blob(encode_varint(i as u64)),
+        None => Value::null(),
+    }
+}
+
+#[scalar(name = "varint_decode")]
+fn varint_decode(args: &[Value]) -> Value {
+    let (blob, offset) = match args {
+        [blob] => (blob, Some(0)),
+        [blob, offset] if offset.value_type() == ValueType::Integer => (
+            blob,
+            offset.to_integer().and_then(|o| usize::try_from(o).ok()),
+        ),
+        [_, _] => return Value::null(),
+        _ => return Value::error(ResultCode::InvalidArgs),
+    };
+    if blob.value_type() != ValueType::Blob {
+        return Value::null();
+    }
+    let bytes = blob.to_blob().unwrap_or_default();
+    match offset
+        .and_then(|offset| bytes.get(offset..))
+        .and_then(decode_varint)
+    {
+        Some(value) => Value::from_integer(value as i64),
+        None => Value::null(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decompose() {
+        assert_eq!(decompose(2.0), (2, 0));
+        assert_eq!(decompose(1e20), (6103515625000000, 14));
+        assert_eq!(decompose(-0.75), (-3, -2));
+        assert_eq!(decompose(0.0), (0, 0));
+        assert_eq!(decompose(0.1), (3602879701896397, -55));
+        assert_eq!(decompose(f64::MIN_POSITIVE / 4.0), (1, -1024));
+        for f in [
+            1.0,
+            -123.456,
+            0.1,
+            1e300,
+            -1e-300,
+            f64::MIN_POSITIVE / 3.0,
+            f64::MAX,
+        ] {
+            let (mantissa, exponent) = decompose(f);
+            assert_eq!(compose(mantissa, exponent), f, "{f}");
+        }
+    }
+
+    #[test]
+    fn test_compose() {
+        assert_eq!(compose(1, 1), 2.0);
+        assert_eq!(compose(0, 5), 0.0);
+        assert_eq!(compose(-5, -1), -2.5);
+        // More bits than a float can hold are truncated.
+        assert_eq!(compose((1 << 54) + 1, 0), (1u64 << 54) as f64);
+        assert_eq!(compose(1, -1080), 0.0);
+        assert_eq!(compose(1, 5000), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_varint() {
+        for (value, encoded) in [
+            (0u64, vec![0x00]),
+            (0x7f, vec![0x7f]),
+            (0x80, vec![0x81, 0x00]),
+            (0x3fff, vec![0xff, 0x7f]),
+            (0x4000, vec![0x81, 0x80, 0x00]),
+            (u64::MAX, vec![0xff; 9]),
+        ] {
+            assert_eq!(encode_varint(value), encoded, "{value:#x}");
+            assert_eq!(decode_varint(&encoded), Some(value), "{value:#x}");
+        }
+        for value in [1u64 << 55, (1 << 56) - 1, 1 << 56, 1 << 63, 12345678901234] {
+            assert_eq!(
+                decode_varint(&encode_varint(value)),
+                Some(value),
+                "{value:#x}"
+            );
+        }
+        assert_eq!(decode_varint(&[0x81]), None);
+        assert_eq!(decode_varint(&[]), None);
+    }
+
+    #[test]
+    fn test_functions() {
+        let call = |f: unsafe extern "C" fn(i32, *const Value) -> Value, args: &[Value]| unsafe {
+            f(args.len() as i32, args.as_ptr())
+        };
+        let text = call(ieee754, &[Value::from_float(-0.75)]);
+        assert_eq!(text.to_text(), Some("ieee754(-3,-2)"));
+        let float = call(ieee754, &[Value::from_integer(-3), Value::from_integer(-2)]);
+        assert_eq!(float.to_float(), Some(-0.75));
+        let blob = call(ieee754_to_blob, &[Value::from_float(1.0)]);
+        assert_eq!(blob.to_blob(), Some(vec![0x3f, 0xf0, 0, 0, 0, 0, 0, 0]));
+        assert_eq!(call(ieee754_from_blob, &[blob]).to_float(), Some(1.0));
+        let encoded = call(varint_encode, &[Value::from_integer(-1)]);
+        assert_eq!(encoded.to_blob(), Some(vec![0xff; 9]));
+        let padded = Value::from_blob(vec![0, 0x81, 0x00]);
+        let decoded = call(varint_decode, &[padded, Value::from_integer(1)]);
+        assert_eq!(decoded.to_integer(), Some(0x80));
+        assert_eq!(
+            call(ieee754_mantissa, &[Value::null()]).value_type(),
+            ValueType::Null
+        );
+        assert_eq!(
+            call(ieee754_exponent, &[Value::from_text("x".to_string())]).value_type(),
+            ValueType::Null
+        );
+    }
+}
@@ -0,0 +1,450 @@
+//! Read-only virtual table over Apache Parquet files.
+//!
+//! ## Example usage:
+//!
+//! ```sql
+//! CREATE VIRTUAL TABLE temp.events USING parquet(filename='events.parquet');
+//! SELECT user_id, count(*) FROM events GROUP BY user_id;
+//! ```
+//!
+//! ## Parameters:
+//! - `filename` — path to the Parquet file
+//!
+//! The table schema is derived from the top-level fields of the file schema: integer and boolean
+//! columns are declared as `INTEGER`, floating point columns as `REAL`, strings, decimals, dates,
+//! timestamps and nested fields as `TEXT` and other binary columns as `BLOB`.
+//!
+//! Columns are decoded lazily: only the columns which are actually read by the query are projected
+//! from the file, so narrow queries over wide files only pay for the column chunks they touch.
+use parquet::basic::{ConvertedType, Type as PhysicalType};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::record::reader::RowIter;
+use parquet::record::{Field, Row};
+use parquet::schema::types::{Type, TypePtr};
+use std::cell::RefCell;
+use std::fs::File;
+use std::sync::Arc;
+use turso_ext::{
+    register_extension, Connection, ResultCode, VTabCursor, VTabKind, VTabModule, VTabModuleDerive,
+    VTable, Value,
+};
+
+register_extension! {
+    vtabs: { ParquetVTabModule }
+}
+
+#[derive(Debug, VTabModuleDerive, Default)]
+struct ParquetVTabModule;
+
+impl ParquetVTabModule {
+    fn parse_arg(arg: &Value) -> Result<(&str, &str), ResultCode> {
+        let text = arg.to_text().ok_or(ResultCode::InvalidArgs)?;
+        let (name, value) = text.split_once('=').ok_or(ResultCode::InvalidArgs)?;
+        Ok((name.trim(), value.trim()))
+    }
+
+    fn parse_string(s: &str) -> Result<String, ResultCode> {
+        let Some(quote) = s.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            return Ok(s.to_owned());
+        };
+        if s.len() < 2 || !s.ends_with(quote) {
+            return Err(ResultCode::InvalidArgs);
+        }
+        let escaped = format!("{quote}{quote}");
+        Ok(s[1..s.len() - 1].replace(&escaped, &quote.to_string()))
+    }
+
+    fn escape_double_quote(identifier: &str) -> String {
+        identifier.replace('"', "\"\"")
+    }
+
+    fn column_type(field: &Type) -> &'static str {
+        if !field.is_primitive() {
+            return "TEXT";
+        }
+        match field.get_basic_info().converted_type() {
+            ConvertedType::UTF8
+            | ConvertedType::ENUM
+            | ConvertedType::JSON
+            | ConvertedType::DECIMAL
+            | ConvertedType::DATE
+            | ConvertedType::TIME_MILLIS
+            | ConvertedType::TIME_MICROS
+            | ConvertedType::TIMESTAMP_MILLIS
+            | ConvertedType::TIMESTAMP_MICROS
+            | ConvertedType::INTERVAL => return "TEXT",
+            _ => {}
+        }
+        match field.get_physical_type() {
+            PhysicalType::BOOLEAN | PhysicalType::INT32 | PhysicalType::INT64 => "INTEGER",
+            PhysicalType::FLOAT | PhysicalType::DOUBLE => "REAL",
+            // INT96 is the legacy timestamp encoding
+            PhysicalType::INT96 => "TEXT",
+            PhysicalType::BYTE_ARRAY | PhysicalType::FIXED_LEN_BYTE_ARRAY => "BLOB",
+        }
+    }
+}
+
+impl VTabModule for ParquetVTabModule {
+    type Table = ParquetTable;
+    const VTAB_KIND: VTabKind = VTabKind::VirtualTable;
+    const NAME: &'static str = "parquet";
+    const READONLY: bool = true;
+
+    fn create(args: &[Value]) -> Result<(String, Self::Table), ResultCode> {
+        let mut filename = None;
+        for arg in args {
+            let (name, value) = Self::parse_arg(arg)?;
+            match name {
+                "filename" => {
+                    if filename.is_some() {
+                        return Err(ResultCode::InvalidArgs);
+                    }
+                    filename = Some(Self::parse_string(value)?);
+                }
+                _ => return Err(ResultCode::InvalidArgs),
+            }
+        }
+        let filename = filename.ok_or(ResultCode::InvalidArgs)?;
+
+        let file = File::open(&filename).map_err(|_| ResultCode::Error)?;
+        let reader = SerializedFileReader::new(file).map_err(|_| ResultCode::Error)?;
+        let metadata = reader.metadata().file_metadata();
+        let root = metadata.schema_descr().root_schema();
+        let fields = root.get_fields().to_vec();
+        if fields.is_empty() {
+            return Err(ResultCode::Error);
+        }
+
+        let columns = fields
+            .iter()
+            .map(|field| {
+                format!(
+                    "\"{}\" {}",
+                    Self::escape_double_quote(field.name()),
+                    Self::column_type(field)
+                )
+            })
+            .collect::<Vec<_>>();
+        let schema = format!("CREATE TABLE x ({})", columns.join(", "));
+
+        let table = ParquetTable {
+            filename,
+            root_name: root.name().to_owned(),
+            fields,
+            row_count: metadata.num_rows().max(0) as usize,
+        };
+        Ok((schema, table))
+    }
+}
+
+#[derive(Clone)]
+struct ParquetTable {
+    filename: String,
+    /// Name of the root message of the file schema, projections must use the same name
+    root_name: String,
+    /// Top-level fields of the file schema, one per table column
+    fields: Vec<TypePtr>,
+    row_count: usize,
+}
+
+impl ParquetTable {
+    fn open_rows(&self, projection: &[usize]) -> Result<RowIter<'static>, ResultCode> {
+        let file = File::open(&self.filename).map_err(|_| ResultCode::Error)?;
+        let reader = SerializedFileReader::new(file).map_err(|_| ResultCode::Error)?;
+        let fields = projection
+            .iter()
+            .map(|&i| self.fields[i].clone())
+            .collect::<Vec<_>>();
+        let projection = Type::group_type_builder(&self.root_name)
+            .with_fields(fields)
+            .build()
+            .map_err(|_| ResultCode::Error)?;
+        RowIter::from_file_into(Box::new(reader))
+            .project(Some(projection))
+            .map_err(|_| ResultCode::Error)
+    }
+}
+
+impl VTable for ParquetTable {
+    type Cursor = ParquetCursor;
+    type Error = ResultCode;
+
+    fn open(&self, _conn: Option<Arc<Connection>>) -> Result<Self::Cursor, Self::Error> {
+        Ok(ParquetCursor {
+            table: self.clone(),
+            row: 0,
+            eof: true,
+            scan: RefCell::new(Scan::default()),
+        })
+    }
+}
+
+/// Row iterator over the columns requested so far
+///
+/// The projection only grows: when a column outside of it is requested, the iterator is reopened
+/// with the extended projection and fast-forwarded to the current row.
+#[derive(Default)]
+struct Scan {
+    /// Sorted table column indexes included into the projection
+    projection: Vec<usize>,
+    rows: Option<RowIter<'static>>,
+    /// Index of the row which will be returned by the next call to `rows.next()`
+    next_row: usize,
+    /// Row with index `next_row - 1`
+    current: Option<Row>,
+}
+
+struct ParquetCursor {
+    table: ParquetTable,
+    /// Zero-based index of the current row
+    row: usize,
+    eof: bool,
+    scan: RefCell<Scan>,
+}
+
+fn field_to_value(field: &Field) -> Value {
+    match field {
+        Field::Null => Value::null(),
+        Field::Bool(b) => Value::from_integer(*b as i64),
+        Field::Byte(i) => Value::from_integer(*i as i64),
+        Field::Short(i) => Value::from_integer(*i as i64),
+        Field::Int(i) => Value::from_integer(*i as i64),
+        Field::Long(i) => Value::from_integer(*i),
+        Field::UByte(i) => Value::from_integer(*i as i64),
+        Field::UShort(i) => Value::from_integer(*i as i64),
+        Field::UInt(i) => Value::from_integer(*i as i64),
+        Field::ULong(i) => match i64::try_from(*i) {
+            Ok(i) => Value::from_integer(i),
+            Err(_) => Value::from_float(*i as f64),
+        },
+        Field::Float(f) => Value::from_float(*f as f64),
+        Field::Double(f) => Value::from_float(*f),
+        Field::Str(s) => Value::from_text(s.clone()),
+        Field::Bytes(b) => Value::from_blob(b.data().to_vec()),
+        // decimals, dates, timestamps and nested values are rendered as text
+        other => Value::from_text(other.to_string()),
+    }
+}
+
+impl VTabCursor for ParquetCursor {
+    type Error = ResultCode;
+
+    fn filter(&mut self, _args: &[Value], _idx_info: Option<(&str, i32)>) -> ResultCode {
+        let scan = self.scan.get_mut();
+        if scan.next_row > 0 {
+            // rows iterator can't go backwards, so it is reopened on the next column access
+            scan.rows = None;
+            scan.next_row = 0;
+            scan.current = None;
+        }
+        self.row = 0;
+        self.eof = self.table.row_count == 0;
+        if self.eof {
+            ResultCode::EOF
+        } else {
+            ResultCode::OK
+        }
+    }
+
+    fn rowid(&self) -> i64 {
+        self.row as i64 + 1
+    }
+
+    fn column(&self, idx: u32) -> Result<Value, Self::Error> {
+        let idx = idx as usize;
+        if idx >= self.table.fields.len() {
+            return Ok(Value::null());
+        }
+        let mut scan = self.scan.borrow_mut();
+        let scan = &mut *scan;
+        if let Err(pos) = scan.projection.binary_search(&idx) {
+            scan.projection.insert(pos, idx);
+            scan.rows = None;
+        }
+        if scan.rows.is_none() {
+            scan.rows = Some(self.table.open_rows(&scan.projection)?);
+            scan.next_row = 0;
+            scan.current = None;
+        }
+        if scan.current.is_none() || scan.next_row != self.row + 1 {
+            let rows = scan.rows.as_mut().ok_or(ResultCode::Internal)?;
+            let skip = self
+                .row
+                .checked_sub(scan.next_row)
+                .ok_or(ResultCode::Internal)?;
+            let row = rows
+                .nth(skip)
+                .ok_or(ResultCode::Error)?
+                .map_err(|_| ResultCode::Error)?;
+            scan.current = Some(row);
+            scan.next_row = self.row + 1;
+        }
+        let pos = scan
+            .projection
+            .binary_search(&idx)
+            .map_err(|_| ResultCode::Internal)?;
+        let value = scan
+            .current
+            .as_ref()
+            .and_then(|row| row.get_column_iter().nth(pos))
+            .map_or(Value::null(), |(_, field)| field_to_value(field));
+        Ok(value)
+    }
+
+    fn eof(&self) -> bool {
+        self.eof
+    }
+
+    fn next(&mut self) -> ResultCode {
+        self.row += 1;
+        self.eof = self.row >= self.table.row_count;
+        if self.eof {
+            ResultCode::EOF
+        } else {
+            ResultCode::OK
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::data_type::{ByteArray, ByteArrayType, DoubleType, Int64Type};
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+    use tempfile::NamedTempFile;
+    use turso_ext::ValueType;
+
+    fn write_parquet() -> NamedTempFile {
+        let schema = Arc::new(
+            parse_message_type(
+                "message schema {
+                    REQUIRED INT64 id;
+                    REQUIRED BYTE_ARRAY name (UTF8);
+                    OPTIONAL DOUBLE score;
+                }",
+            )
+            .unwrap(),
+        );
+        let tmp = NamedTempFile::new().unwrap();
+        let mut writer =
+            SerializedFileWriter::new(tmp.reopen().unwrap(), schema, Default::default()).unwrap();
+        let mut row_group = writer.next_row_group().unwrap();
+
+        let mut column = row_group.next_column().unwrap().unwrap();
+        column
+            .typed::<Int64Type>()
+            .write_batch(&[1, 2, 3], None, None)
+            .unwrap();
+        column.close().unwrap();
+
+        let mut column = row_group.next_column().unwrap().unwrap();
+        let names = ["Alice", "Bob", "Carol"].map(ByteArray::from);
+        column
+            .typed::<ByteArrayType>()
+            .write_batch(&names, None, None)
+            .unwrap();
+        column.close().unwrap();
+
+        let mut column = row_group.next_column().unwrap().unwrap();
+        column
+            .typed::<DoubleType>()
+            .write_batch(&[1.5, 3.0], Some(&[1, 0, 1][..]), None)
+            .unwrap();
+        column.close().unwrap();
+
+        row_group.close().unwrap();
+        writer.close().unwrap();
+        tmp
+    }
+
+    fn new_table(file: &NamedTempFile) -> (String, ParquetTable) {
+        let arg = Value::from_text(format!("filename='{}'", file.path().to_string_lossy()));
+        ParquetVTabModule::create(&[arg]).unwrap()
+    }
+
+    #[test]
+    fn test_schema() {
+        let file = write_parquet();
+        let (schema, table) = new_table(&file);
+        assert_eq!(
+            schema,
+            "CREATE TABLE x (\"id\" INTEGER, \"name\" TEXT, \"score\" REAL)"
+        );
+        assert_eq!(table.row_count, 3);
+    }
+
+    #[test]
+    fn test_read_rows() {
+        let file = write_parquet();
+        let (_, table) = new_table(&file);
+        let mut cursor = table.open(None).unwrap();
+        assert_eq!(cursor.filter(&[], None), ResultCode::OK);
+        let mut rows = vec![];
+        while !cursor.eof() {
+            let id = cursor.column(0).unwrap().to_integer().unwrap();
+            let name = cursor.column(1).unwrap().to_text().unwrap().to_owned();
+            let score = cursor.column(2).unwrap();
+            let score = match score.value_type() {
+                ValueType::Null => None,
+                _ => score.to_float(),
+            };
+            rows.push((cursor.rowid(), id, name, score));
+            cursor.next();
+        }
+        assert_eq!(
+            rows,
+            vec![
+                (1, 1, "Alice".to_owned(), Some(1.5)),
+                (2, 2, "Bob".to_owned(), None),
+                (3, 3, "Carol".to_owned(), Some(3.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_projection_grows_mid_scan() {
+        let file = write_parquet();
+        let (_, table) = new_table(&file);
+        let mut cursor = table.open(None).unwrap();
+        cursor.filter(&[], None);
+        assert_eq!(cursor.column(0).unwrap().to_integer(), Some(1));
+        cursor.next();
+        assert_eq!(cursor.column(0).unwrap().to_integer(), Some(2));
+        // column outside of the projection reopens the scan at the current row
+        assert_eq!(cursor.column(1).unwrap().to_text(), Some("Bob"));
+        assert_eq!(cursor.scan.borrow().projection, vec![0, 1]);
+        cursor.next();
+        assert_eq!(cursor.column(1).unwrap().to_text(), Some("Carol"));
+
+        // rescan starts from the first row again
+        cursor.filter(&[], None);
+        assert_eq!(cursor.column(1).unwrap().to_text(), Some("Alice"));
+    }
+
+    #[test]
+    fn test_count_without_columns() {
+        let file = write_parquet();
+        let (_, table) = new_table(&file);
+        let mut cursor = table.open(None).unwrap();
+        let mut count = 0;
+        let mut rc = cursor.filter(&[], None);
+        while rc == ResultCode::OK {
+            count += 1;
+            rc = cursor.next();
+        }
+        assert_eq!(count, 3);
+        assert!(cursor.scan.borrow().rows.is_none());
+    }
+
+    #[test]
+    fn test_invalid_args() {
+        assert!(ParquetVTabModule::create(&[]).is_err());
+        assert!(ParquetVTabModule::create(&[Value::from_text("data=1".to_owned())]).is_err());
+        assert!(
+            ParquetVTabModule::create(&[Value::from_text("filename=/nonexistent".to_owned())])
+                .is_err()
+        );
+    }
+}
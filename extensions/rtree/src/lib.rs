@@ -0,0 +1,626 @@
+//! Port of SQLite's R*Tree module: <https://www.sqlite.org/rtree.html>
+//!
+//! An `rtree` table holds one bounding box per row and answers range queries on the box
+//! coordinates, e.g. finding all the objects that overlap a map tile, without scanning the
+//! whole table.
+//!
+//! ## Example usage:
+//!
+//! ```sql
+//! CREATE VIRTUAL TABLE places USING rtree(id, minX, maxX, minY, maxY, +name);
+//! INSERT INTO places VALUES (1, -80.77, -80.76, 35.37, 35.38, 'Charlotte');
+//! SELECT id, name FROM places WHERE maxX >= -81.0 AND minX <= -80.0 AND maxY >= 35.0 AND minY <= 36.0;
+//! ```
+//!
+//! ## Columns:
+//! - the first column is the integer id of the row; inserting `NULL` assigns the next free id
+//! - then 1 to 5 pairs of minimum and maximum coordinates, stored as 32-bit floats. Values
+//!   that aren't exactly representable are rounded so the stored box contains the given one.
+//! - then any number of auxiliary columns, whose names start with `+`, holding values that
+//!   are returned with the box but can't be searched on
+//!
+//! Unlike SQLite, which stores the tree in shadow tables, the tree is kept in memory and its
+//! contents are lost when the connection is closed.
+mod tree;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+use tree::{RTree, Rect};
+use turso_ext::{
+    register_extension, Connection, ConstraintInfo, ConstraintOp, ConstraintUsage, IndexInfo,
+    OrderByInfo, ResultCode, VTabCursor, VTabKind, VTabModule, VTabModuleDerive, VTable, Value,
+    ValueType,
+};
+
+register_extension! {
+    vtabs: { RTreeVTabModule }
+}
+
+const MAX_DIMENSIONS: usize = 5;
+
+/// `filter` looks up the row with the given id.
+const IDX_ID_LOOKUP: i32 = 1;
+/// `filter` searches for the boxes satisfying the constraints encoded in `idx_str`.
+const IDX_SEARCH: i32 = 2;
+
+#[derive(Debug, VTabModuleDerive, Default)]
+struct RTreeVTabModule;
+
+impl VTabModule for RTreeVTabModule {
+    type Table = RTreeTable;
+    const VTAB_KIND: VTabKind = VTabKind::VirtualTable;
+    const NAME: &'static str = "rtree";
+    const READONLY: bool = false;
+
+    fn create(args: &[Value]) -> Result<(String, Self::Table), ResultCode> {
+        let args = args
+            .iter()
+            .map(|arg| arg.to_text().map(|arg| arg.trim().to_string()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(ResultCode::InvalidArgs)?;
+        let coordinates = args
+            .iter()
+            .skip(1)
+            .take_while(|arg| !arg.starts_with('+'))
+            .count();
+        let aux = &args[(1 + coordinates).min(args.len())..];
+        if !(2..=MAX_DIMENSIONS * 2).contains(&coordinates)
+            || coordinates % 2 != 0
+            || aux.iter().any(|arg| !arg.starts_with('+'))
+        {
+            return Err(ResultCode::InvalidArgs);
+        }
+
+        let mut columns = vec![format!("{} INTEGER", quote(&args[0]))];
+        columns.extend(
+            args[1..=coordinates]
+                .iter()
+                .map(|arg| format!("{} REAL", quote(arg))),
+        );
+        // An auxiliary column is declared as `+name` or `+name type`.
+        columns.extend(aux.iter().map(|arg| {
+            let arg = arg[1..].trim_start();
+            match arg.split_once(char::is_whitespace) {
+                Some((name, ty)) => format!("{} {}", quote(name), ty.trim()),
+                None => quote(arg),
+            }
+        }));
+        let schema = format!("CREATE TABLE x({})", columns.join(", "));
+        let table = RTreeTable {
+            dims: coordinates / 2,
+            aux_columns: aux.len(),
+            contents: Rc::new(RefCell::new(Contents {
+                tree: RTree::new(coordinates / 2),
+                aux: HashMap::new(),
+            })),
+            savepoint: None,
+        };
+        Ok((schema, table))
+    }
+}
+
+/// Quotes a column name given to `CREATE VIRTUAL TABLE`, which may already be quoted.
+fn quote(name: &str) -> String {
+    let name = name.trim();
+    let unquoted = ['"', '`', '\'']
+        .iter()
+        .find_map(|quote| name.strip_prefix(*quote)?.strip_suffix(*quote))
+        .or_else(|| name.strip_prefix('[')?.strip_suffix(']'))
+        .unwrap_or(name);
+    format!("\"{}\"", unquoted.replace('"', "\"\""))
+}
+
+/// Value of an auxiliary column.
+#[derive(Debug, Clone, PartialEq)]
+enum AuxValue {
+    Null,
+    Integer(i64),
+    Float(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+impl AuxValue {
+    fn from_value(value: &Value) -> Self {
+        match value.value_type() {
+            ValueType::Integer => value.to_integer().map_or(Self::Null, Self::Integer),
+            ValueType::Float => value.to_float().map_or(Self::Null, Self::Float),
+            ValueType::Text => value
+                .to_text()
+                .map_or(Self::Null, |text| Self::Text(text.to_string())),
+            ValueType::Blob => value.to_blob().map_or(Self::Null, Self::Blob),
+            _ => Self::Null,
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        match self {
+            Self::Null => Value::null(),
+            Self::Integer(i) => Value::from_integer(*i),
+            Self::Float(f) => Value::from_float(*f),
+            Self::Text(text) => Value::from_text(text.clone()),
+            Self::Blob(blob) => Value::from_blob(blob.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Contents {
+    tree: RTree,
+    aux: HashMap<i64, Vec<AuxValue>>,
+}
+
+struct RTreeTable {
+    dims: usize,
+    aux_columns: usize,
+    contents: Rc<RefCell<Contents>>,
+    /// Contents at the start of the current transaction, restored on rollback.
+    savepoint: Option<Contents>,
+}
+
+impl RTreeTable {
+    /// Builds the box of an inserted or updated row from its coordinate columns.
+    fn rect_from_values(&self, values: &[Value]) -> Result<Rect, String> {
+        let rect = (0..self.dims * 2)
+            .map(|i| {
+                let value = values
+                    .get(1 + i)
+                    .filter(|v| v.value_type() != ValueType::Null);
+                let Some(coordinate) = value.and_then(|v| v.to_float()) else {
+                    return Err("rtree: coordinates must be numbers".to_string());
+                };
+                // Round outward, so the stored box contains the given one.
+                let rounded = coordinate as f32;
+                Ok(
+                    match (i % 2 == 0, (rounded as f64).partial_cmp(&coordinate)) {
+                        (true, Some(std::cmp::Ordering::Greater)) => rounded.next_down(),
+                        (false, Some(std::cmp::Ordering::Less)) => rounded.next_up(),
+                        _ => rounded,
+                    },
+                )
+            })
+            .collect::<Result<Rect, _>>()?;
+        if rect.chunks_exact(2).any(|d| d[0] > d[1]) {
+            return Err("rtree constraint failed: minimum is greater than maximum".to_string());
+        }
+        Ok(rect)
+    }
+
+    /// Adds a row, or replaces the row `replacing`, returning its id.
+    fn write_row(&mut self, values: &[Value], replacing: Option<i64>) -> Result<i64, String> {
+        let rect = self.rect_from_values(values)?;
+        let aux = (0..self.aux_columns)
+            .map(|i| {
+                values
+                    .get(1 + self.dims * 2 + i)
+                    .map_or(AuxValue::Null, AuxValue::from_value)
+            })
+            .collect::<Vec<_>>();
+        let mut contents = self.contents.borrow_mut();
+        let id = match values.first().filter(|v| v.value_type() != ValueType::Null) {
+            Some(id) => match id.value_type() {
+                ValueType::Integer => id.to_integer().unwrap_or_default(),
+                ValueType::Float if id.to_float().is_some_and(|f| f.fract() == 0.0) => {
+                    id.to_float().unwrap_or_default() as i64
+                }
+                _ => return Err("rtree: id must be an integer".to_string()),
+            },
+            None => match replacing {
+                Some(id) => id,
+                None => contents.tree.max_id().map_or(1, |id| id.saturating_add(1)),
+            },
+        };
+        if Some(id) != replacing && contents.tree.get(id).is_some() {
+            return Err(format!("rtree: UNIQUE constraint failed: id {id}"));
+        }
+        if let Some(old) = replacing {
+            contents.tree.remove(old);
+            contents.aux.remove(&old);
+        }
+        contents.tree.insert(id, rect);
+        if self.aux_columns > 0 {
+            contents.aux.insert(id, aux);
+        }
+        Ok(id)
+    }
+}
+
+impl VTable for RTreeTable {
+    type Cursor = RTreeCursor;
+    type Error = String;
+
+    fn open(&self, _conn: Option<Arc<Connection>>) -> Result<Self::Cursor, Self::Error> {
+        Ok(RTreeCursor {
+            dims: self.dims,
+            contents: self.contents.clone(),
+            rows: Vec::new(),
+            index: 0,
+        })
+    }
+
+    fn best_index(
+        constraints: &[ConstraintInfo],
+        _order_by: &[OrderByInfo],
+    ) -> Result<IndexInfo, ResultCode> {
+        let unused = ConstraintUsage {
+            argv_index: None,
+            omit: false,
+        };
+        let mut constraint_usages = vec![unused; constraints.len()];
+        if let Some(i) = constraints
+            .iter()
+            .position(|c| c.usable && c.op == ConstraintOp::Eq && c.column_index == 0)
+        {
+            constraint_usages[i].argv_index = Some(1);
+            return Ok(IndexInfo {
+                idx_num: IDX_ID_LOOKUP,
+                estimated_cost: 1.0,
+                estimated_rows: 1,
+                constraint_usages,
+                ..Default::default()
+            });
+        }
+
+        // Each usable constraint on a coordinate is encoded in idx_str as the letter of its
+        // operator followed by its column, and its value is passed to `filter` in order.
+        // The number of coordinates isn't known here, so `filter` skips constraints on
+        // auxiliary columns. Constraints aren't omitted, so those are still checked.
+        let mut idx_str = String::new();
+        let mut argc = 0;
+        for (constraint, usage) in constraints.iter().zip(constraint_usages.iter_mut()) {
+            let op = match constraint.op {
+                ConstraintOp::Eq => 'A',
+                ConstraintOp::Le => 'B',
+                ConstraintOp::Lt => 'C',
+                ConstraintOp::Ge => 'D',
+                ConstraintOp::Gt => 'E',
+                _ => continue,
+            };
+            if !constraint.usable
+                || constraint.column_index == 0
+                || constraint.column_index as usize > MAX_DIMENSIONS * 2
+            {
+                continue;
+            }
+            idx_str.push(op);
+            idx_str.push(char::from(b'0' + constraint.column_index as u8));
+            argc += 1;
+            usage.argv_index = Some(argc);
+        }
+        if argc == 0 {
+            return Ok(IndexInfo {
+                constraint_usages,
+                ..Default::default()
+            });
+        }
+        Ok(IndexInfo {
+            idx_num: IDX_SEARCH,
+            idx_str: Some(idx_str),
+            estimated_cost: 1_000_000.0 / (argc as f64 + 1.0),
+            estimated_rows: 1_000_000 / (argc + 1),
+            constraint_usages,
+            ..Default::default()
+        })
+    }
+
+    fn begin(&mut self) -> Result<(), Self::Error> {
+        self.savepoint = Some(self.contents.borrow().clone());
+        Ok(())
+    }
+
+    fn insert(&mut self, args: &[Value]) -> Result<i64, Self::Error> {
+        self.write_row(args, None)
+    }
+
+    fn update(&mut self, rowid: i64, args: &[Value]) -> Result<(), Self::Error> {
+        if self.contents.borrow().tree.get(rowid).is_none() {
+            return Err(format!("rtree: no row with id {rowid}"));
+        }
+        self.write_row(args, Some(rowid))?;
+        Ok(())
+    }
+
+    fn delete(&mut self, rowid: i64) -> Result<(), Self::Error> {
+        let mut contents = self.contents.borrow_mut();
+        contents.tree.remove(rowid);
+        contents.aux.remove(&rowid);
+        Ok(())
+    }
+
+    fn commit(&mut self) -> Result<(), Self::Error> {
+        self.savepoint = None;
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> Result<(), Self::Error> {
+        if let Some(savepoint) = self.savepoint.take() {
+            *self.contents.borrow_mut() = savepoint;
+        }
+        Ok(())
+    }
+}
+
+/// A constraint on a coordinate column, decoded from idx_str.
+struct Constraint {
+    /// Index of the coordinate in the box.
+    coordinate: usize,
+    op: ConstraintOp,
+    value: f64,
+}
+
+impl Constraint {
+    /// Decodes the constraints encoded in idx_str by `best_index`. Constraints on auxiliary
+    /// columns and constraints with a non-numeric value are left for the caller to check,
+    /// except that a `NULL` value matches nothing, which is signalled by returning `None`.
+    fn decode(idx_str: &str, args: &[Value], dims: usize) -> Option<Vec<Self>> {
+        let mut constraints = Vec::new();
+        for (pair, arg) in idx_str.as_bytes().chunks_exact(2).zip(args) {
+            if arg.value_type() == ValueType::Null {
+                return None;
+            }
+            let op = match pair[0] {
+                b'A' => ConstraintOp::Eq,
+                b'B' => ConstraintOp::Le,
+                b'C' => ConstraintOp::Lt,
+                b'D' => ConstraintOp::Ge,
+                b'E' => ConstraintOp::Gt,
+                _ => continue,
+            };
+            let column = pair[1].wrapping_sub(b'0') as usize;
+            if column == 0 || column > dims * 2 {
+                continue;
+            }
+            if let (ValueType::Integer | ValueType::Float, Some(value)) =
+                (arg.value_type(), arg.to_float())
+            {
+                constraints.push(Self {
+                    coordinate: column - 1,
+                    op,
+                    value,
+                });
+            }
+        }
+        Some(constraints)
+    }
+
+    fn test(&self, x: f64) -> bool {
+        match self.op {
+            ConstraintOp::Eq => x == self.value,
+            ConstraintOp::Le => x <= self.value,
+            ConstraintOp::Lt => x < self.value,
+            ConstraintOp::Ge => x >= self.value,
+            ConstraintOp::Gt => x > self.value,
+            _ => true,
+        }
+    }
+
+    /// Whether a box inside `node` can satisfy the constraint. Both the minimum and the maximum
+    /// of a dimension lie between the node's minimum and maximum of that dimension.
+    fn may_match_inside(&self, node: &[f32]) -> bool {
+        let dim = self.coordinate / 2;
+        let (lo, hi) = (node[dim * 2] as f64, node[dim * 2 + 1] as f64);
+        match self.op {
+            ConstraintOp::Eq => lo <= self.value && self.value <= hi,
+            ConstraintOp::Le => lo <= self.value,
+            ConstraintOp::Lt => lo < self.value,
+            ConstraintOp::Ge => hi >= self.value,
+            ConstraintOp::Gt => hi > self.value,
+            _ => true,
+        }
+    }
+}
+
+struct RTreeCursor {
+    dims: usize,
+    contents: Rc<RefCell<Contents>>,
+    /// Snapshot of the matching rows taken by `filter`.
+    rows: Vec<(i64, Rect)>,
+    index: usize,
+}
+
+impl VTabCursor for RTreeCursor {
+    type Error = String;
+
+    fn filter(&mut self, args: &[Value], idx_info: Option<(&str, i32)>) -> ResultCode {
+        self.index = 0;
+        let contents = self.contents.borrow();
+        self.rows = match idx_info {
+            Some((_, IDX_ID_LOOKUP)) => {
+                let id = args
+                    .first()
+                    .filter(|id| matches!(id.value_type(), ValueType::Integer | ValueType::Float))
+                    .and_then(|id| id.to_float())
+                    .filter(|id| id.fract() == 0.0);
+                id.and_then(|id| {
+                    let rect = contents.tree.get(id as i64)?;
+                    Some((id as i64, rect.clone()))
+                })
+                .into_iter()
+                .collect()
+            }
+            Some((idx_str, IDX_SEARCH)) => match Constraint::decode(idx_str, args, self.dims) {
+                Some(constraints) => contents.tree.search(
+                    |node| constraints.iter().all(|c| c.may_match_inside(node)),
+                    |rect| {
+                        constraints
+                            .iter()
+                            .all(|c| c.test(rect[c.coordinate] as f64))
+                    },
+                ),
+                None => Vec::new(),
+            },
+            _ => contents.tree.search(|_| true, |_| true),
+        };
+        if self.rows.is_empty() {
+            ResultCode::EOF
+        } else {
+            ResultCode::OK
+        }
+    }
+
+    fn rowid(&self) -> i64 {
+        self.rows.get(self.index).map_or(-1, |(id, _)| *id)
+    }
+
+    fn column(&self, idx: u32) -> Result<Value, Self::Error> {
+        let Some((id, rect)) = self.rows.get(self.index) else {
+            return Err("cursor out of range".to_string());
+        };
+        let idx = idx as usize;
+        let value = if idx == 0 {
+            Value::from_integer(*id)
+        } else if let Some(coordinate) = rect.get(idx - 1) {
+            Value::from_float(*coordinate as f64)
+        } else {
+            self.contents
+                .borrow()
+                .aux
+                .get(id)
+                .and_then(|aux| aux.get(idx - 1 - rect.len()))
+                .map_or_else(Value::null, AuxValue::to_value)
+        };
+        Ok(value)
+    }
+
+    fn eof(&self) -> bool {
+        self.index >= self.rows.len()
+    }
+
+    fn next(&mut self) -> ResultCode {
+        self.index += 1;
+        if self.eof() {
+            ResultCode::EOF
+        } else {
+            ResultCode::OK
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create(args: &[&str]) -> RTreeTable {
+        let args = args
+            .iter()
+            .map(|arg| Value::from_text(arg.to_string()))
+            .collect::<Vec<_>>();
+        RTreeVTabModule::create(&args).unwrap().1
+    }
+
+    fn row(id: Option<i64>, coordinates: &[f64]) -> Vec<Value> {
+        std::iter::once(id.map_or_else(Value::null, Value::from_integer))
+            .chain(coordinates.iter().map(|c| Value::from_float(*c)))
+            .collect()
+    }
+
+    fn ids(table: &RTreeTable, idx_str: &str, args: &[f64]) -> Vec<i64> {
+        let mut cursor = table.open(None).unwrap();
+        let args = args
+            .iter()
+            .map(|arg| Value::from_float(*arg))
+            .collect::<Vec<_>>();
+        let mut ids = Vec::new();
+        if cursor.filter(&args, Some((idx_str, IDX_SEARCH))) == ResultCode::OK {
+            while !cursor.eof() {
+                ids.push(cursor.rowid());
+                cursor.next();
+            }
+        }
+        ids
+    }
+
+    #[test]
+    fn test_schema() {
+        let args = [
+            "id",
+            "minX",
+            "maxX",
+            "\"minY\"",
+            "maxY",
+            "+name TEXT",
+            "+data",
+        ]
+        .map(|arg| Value::from_text(arg.to_string()));
+        let (schema, _) = RTreeVTabModule::create(&args).unwrap();
+        assert_eq!(
+            schema,
+            "CREATE TABLE x(\"id\" INTEGER, \"minX\" REAL, \"maxX\" REAL, \"minY\" REAL, \
+             \"maxY\" REAL, \"name\" TEXT, \"data\")"
+        );
+        for args in [&["id"][..], &["id", "a", "b", "c"], &["id", "+a", "b", "c"]] {
+            let args = args
+                .iter()
+                .map(|arg| Value::from_text(arg.to_string()))
+                .collect::<Vec<_>>();
+            assert!(RTreeVTabModule::create(&args).is_err());
+        }
+    }
+
+    #[test]
+    fn test_search() {
+        let mut table = create(&["id", "minX", "maxX", "minY", "maxY"]);
+        table
+            .insert(&row(Some(1), &[0.0, 10.0, 0.0, 10.0]))
+            .unwrap();
+        table.insert(&row(Some(2), &[5.0, 6.0, 5.0, 6.0])).unwrap();
+        table.insert(&row(None, &[20.0, 30.0, -5.0, 5.0])).unwrap();
+
+        // Boxes overlapping [4, 8] x [4, 8]
+        assert_eq!(ids(&table, "D2B1D4B3", &[4.0, 8.0, 4.0, 8.0]), vec![1, 2]);
+        assert_eq!(ids(&table, "E1", &[19.0]), vec![3]);
+        assert_eq!(ids(&table, "A2", &[10.0]), vec![1]);
+        assert_eq!(ids(&table, "C3", &[-5.0]), Vec::<i64>::new());
+        // Constraints on columns after the coordinates are left to the caller.
+        assert_eq!(ids(&table, "D2A5", &[8.0, 1.0]), vec![1, 3]);
+
+        table.update(3, &row(None, &[1.0, 2.0, 1.0, 2.0])).unwrap();
+        table.delete(1).unwrap();
+        assert_eq!(ids(&table, "D2B1D4B3", &[0.0, 8.0, 0.0, 8.0]), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_rejects_invalid_rows() {
+        let mut table = create(&["id", "minX", "maxX"]);
+        table.insert(&row(Some(1), &[0.0, 1.0])).unwrap();
+        assert!(table.insert(&row(Some(1), &[0.0, 1.0])).is_err());
+        assert!(table.insert(&row(Some(2), &[1.0, 0.0])).is_err());
+        assert!(table
+            .insert(&[Value::null(), Value::null(), Value::null()])
+            .is_err());
+        assert_eq!(ids(&table, "D2", &[f64::NEG_INFINITY]), vec![1]);
+    }
+
+    #[test]
+    fn test_coordinates_round_outward() {
+        let mut table = create(&["id", "minX", "maxX"]);
+        table.insert(&row(Some(1), &[0.1, 0.2])).unwrap();
+        let mut cursor = table.open(None).unwrap();
+        assert_eq!(cursor.filter(&[], None), ResultCode::OK);
+        let min = cursor.column(1).unwrap().to_float().unwrap();
+        let max = cursor.column(2).unwrap().to_float().unwrap();
+        assert!(min <= 0.1 && 0.2 <= max);
+    }
+
+    #[test]
+    fn test_rollback() {
+        let mut table = create(&["id", "minX", "maxX", "+name"]);
+        let mut values = row(Some(1), &[0.0, 1.0]);
+        values.push(Value::from_text("a".to_string()));
+        table.insert(&values).unwrap();
+        table.commit().unwrap();
+
+        table.begin().unwrap();
+        table.delete(1).unwrap();
+        table.insert(&row(Some(2), &[0.0, 1.0])).unwrap();
+        table.rollback().unwrap();
+
+        let mut cursor = table.open(None).unwrap();
+        assert_eq!(cursor.filter(&[], None), ResultCode::OK);
+        assert_eq!(cursor.rowid(), 1);
+        assert_eq!(cursor.column(3).unwrap().to_text(), Some("a"));
+        assert_eq!(cursor.next(), ResultCode::EOF);
+    }
+}
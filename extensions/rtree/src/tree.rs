@@ -0,0 +1,387 @@
+//! In-memory R-tree (Guttman, 1984) with quadratic node splits.
+//!
+//! Boxes are stored as `[min0, max0, min1, max1, ...]`. Entries are looked up by id through a
+//! side map, so deleting an entry only descends into the nodes whose box contains it.
+use std::collections::HashMap;
+
+/// Maximum number of entries of a node.
+const MAX_ENTRIES: usize = 16;
+/// Nodes with fewer entries are dissolved on delete, and splits put at least this many
+/// entries in each half.
+const MIN_ENTRIES: usize = MAX_ENTRIES / 3;
+
+pub type Rect = Box<[f32]>;
+
+#[derive(Debug, Clone)]
+enum Node {
+    Leaf(Vec<(Rect, i64)>),
+    Inner(Vec<(Rect, Node)>),
+}
+
+impl Node {
+    fn len(&self) -> usize {
+        match self {
+            Node::Leaf(entries) => entries.len(),
+            Node::Inner(children) => children.len(),
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Rect> {
+        match self {
+            Node::Leaf(entries) => union_all(entries.iter().map(|(rect, _)| rect)),
+            Node::Inner(children) => union_all(children.iter().map(|(rect, _)| rect)),
+        }
+    }
+
+    /// Moves all leaf entries below this node into `out`.
+    fn drain_leaves(self, out: &mut Vec<(Rect, i64)>) {
+        match self {
+            Node::Leaf(entries) => out.extend(entries),
+            Node::Inner(children) => {
+                for (_, child) in children {
+                    child.drain_leaves(out);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RTree {
+    dims: usize,
+    root: Node,
+    boxes: HashMap<i64, Rect>,
+}
+
+impl RTree {
+    pub fn new(dims: usize) -> Self {
+        Self {
+            dims,
+            root: Node::Leaf(Vec::new()),
+            boxes: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, id: i64) -> Option<&Rect> {
+        self.boxes.get(&id)
+    }
+
+    pub fn max_id(&self) -> Option<i64> {
+        self.boxes.keys().max().copied()
+    }
+
+    /// Inserts the entry `id`, replacing the box of an existing entry with that id.
+    pub fn insert(&mut self, id: i64, rect: Rect) {
+        debug_assert_eq!(rect.len(), self.dims * 2);
+        self.remove(id);
+        self.boxes.insert(id, rect.clone());
+        self.insert_entry(rect, id);
+    }
+
+    fn insert_entry(&mut self, rect: Rect, id: i64) {
+        if let Some(sibling) = insert_into(&mut self.root, rect, id) {
+            let old_root = std::mem::replace(&mut self.root, Node::Leaf(Vec::new()));
+            let old_box = old_root.bounding_box().expect("split nodes are not empty");
+            let sibling_box = sibling.bounding_box().expect("split nodes are not empty");
+            self.root = Node::Inner(vec![(old_box, old_root), (sibling_box, sibling)]);
+        }
+    }
+
+    /// Removes the entry `id`, returning its box.
+    pub fn remove(&mut self, id: i64) -> Option<Rect> {
+        let rect = self.boxes.remove(&id)?;
+        let mut orphans = Vec::new();
+        let removed = remove_from(&mut self.root, &rect, id, &mut orphans);
+        debug_assert!(removed, "entry {id} is in the id map but not in the tree");
+        // A root with a single child is replaced by the child, shrinking the tree.
+        while let Node::Inner(children) = &mut self.root {
+            match children.len() {
+                0 => self.root = Node::Leaf(Vec::new()),
+                1 => self.root = children.pop().unwrap().1,
+                _ => break,
+            }
+        }
+        for (rect, id) in orphans {
+            self.insert_entry(rect, id);
+        }
+        Some(rect)
+    }
+
+    /// Returns the entries whose box satisfies `matches`, sorted by id. `may_contain` is
+    /// called with the bounding box of a node and returns whether an entry below it can match.
+    pub fn search(
+        &self,
+        may_contain: impl Fn(&[f32]) -> bool,
+        matches: impl Fn(&[f32]) -> bool,
+    ) -> Vec<(i64, Rect)> {
+        let mut found = Vec::new();
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            match node {
+                Node::Leaf(entries) => found.extend(
+                    entries
+                        .iter()
+                        .filter(|(rect, _)| matches(rect))
+                        .map(|(rect, id)| (*id, rect.clone())),
+                ),
+                Node::Inner(children) => stack.extend(
+                    children
+                        .iter()
+                        .filter(|(rect, _)| may_contain(rect))
+                        .map(|(_, child)| child),
+                ),
+            }
+        }
+        found.sort_by_key(|(id, _)| *id);
+        found
+    }
+}
+
+/// Inserts the entry below `node`, returning the new sibling of `node` if it was split.
+fn insert_into(node: &mut Node, rect: Rect, id: i64) -> Option<Node> {
+    match node {
+        Node::Leaf(entries) => {
+            entries.push((rect, id));
+            if entries.len() <= MAX_ENTRIES {
+                return None;
+            }
+            let [left, right] = quadratic_split(std::mem::take(entries));
+            *entries = left;
+            Some(Node::Leaf(right))
+        }
+        Node::Inner(children) => {
+            let best = choose_subtree(children, &rect);
+            let (child_box, child) = &mut children[best];
+            *child_box = union(child_box, &rect);
+            let sibling = insert_into(child, rect, id)?;
+            *child_box = child.bounding_box().expect("split nodes are not empty");
+            let sibling_box = sibling.bounding_box().expect("split nodes are not empty");
+            children.push((sibling_box, sibling));
+            if children.len() <= MAX_ENTRIES {
+                return None;
+            }
+            let [left, right] = quadratic_split(std::mem::take(children));
+            *children = left;
+            Some(Node::Inner(right))
+        }
+    }
+}
+
+/// The child whose box needs the least enlargement to include `rect`, the smallest one on ties.
+fn choose_subtree(children: &[(Rect, Node)], rect: &[f32]) -> usize {
+    let mut best = 0;
+    let mut best_cost = (f64::INFINITY, f64::INFINITY);
+    for (i, (child_box, _)) in children.iter().enumerate() {
+        let area = area(child_box);
+        let cost = (area_of_union(child_box, rect) - area, area);
+        if cost < best_cost {
+            best = i;
+            best_cost = cost;
+        }
+    }
+    best
+}
+
+/// Removes the entry below `node`. Children left with too few entries are removed and their
+/// leaf entries are added to `orphans` to be inserted again.
+fn remove_from(node: &mut Node, rect: &[f32], id: i64, orphans: &mut Vec<(Rect, i64)>) -> bool {
+    match node {
+        Node::Leaf(entries) => match entries.iter().position(|(_, entry)| *entry == id) {
+            Some(i) => {
+                entries.swap_remove(i);
+                true
+            }
+            None => false,
+        },
+        Node::Inner(children) => {
+            for i in 0..children.len() {
+                if !contains(&children[i].0, rect) {
+                    continue;
+                }
+                if !remove_from(&mut children[i].1, rect, id, orphans) {
+                    continue;
+                }
+                if children[i].1.len() < MIN_ENTRIES {
+                    let (_, child) = children.swap_remove(i);
+                    child.drain_leaves(orphans);
+                } else if let Some(child_box) = children[i].1.bounding_box() {
+                    children[i].0 = child_box;
+                }
+                return true;
+            }
+            false
+        }
+    }
+}
+
+/// Splits an overflowing node into two, keeping entries whose boxes are close together.
+fn quadratic_split<T>(mut entries: Vec<(Rect, T)>) -> [Vec<(Rect, T)>; 2] {
+    // The pair that would waste the most area in one node seeds the two groups.
+    let (mut seed_a, mut seed_b, mut worst) = (0, 1, f64::NEG_INFINITY);
+    for i in 0..entries.len() {
+        for j in i + 1..entries.len() {
+            let waste = area_of_union(&entries[i].0, &entries[j].0)
+                - area(&entries[i].0)
+                - area(&entries[j].0);
+            if waste > worst {
+                (seed_a, seed_b, worst) = (i, j, waste);
+            }
+        }
+    }
+    // seed_b > seed_a, so removing it first leaves seed_a in place.
+    let b = entries.swap_remove(seed_b);
+    let a = entries.swap_remove(seed_a);
+    let (mut box_a, mut box_b) = (a.0.clone(), b.0.clone());
+    let (mut group_a, mut group_b) = (vec![a], vec![b]);
+
+    while !entries.is_empty() {
+        // Whatever is left goes to a group that needs it to reach the minimum size.
+        if group_a.len() + entries.len() == MIN_ENTRIES {
+            group_a.append(&mut entries);
+            break;
+        }
+        if group_b.len() + entries.len() == MIN_ENTRIES {
+            group_b.append(&mut entries);
+            break;
+        }
+        // Assign the entry with the strongest preference for one of the groups next.
+        let (next, _) = entries
+            .iter()
+            .map(|(rect, _)| {
+                let grow_a = area_of_union(&box_a, rect) - area(&box_a);
+                let grow_b = area_of_union(&box_b, rect) - area(&box_b);
+                (grow_a - grow_b).abs()
+            })
+            .enumerate()
+            .fold((0, f64::NEG_INFINITY), |best, (i, preference)| {
+                if preference > best.1 {
+                    (i, preference)
+                } else {
+                    best
+                }
+            });
+        let entry = entries.swap_remove(next);
+        let grow_a = area_of_union(&box_a, &entry.0) - area(&box_a);
+        let grow_b = area_of_union(&box_b, &entry.0) - area(&box_b);
+        let to_a = match grow_a.partial_cmp(&grow_b) {
+            Some(std::cmp::Ordering::Less) => true,
+            Some(std::cmp::Ordering::Greater) => false,
+            _ => (area(&box_a), group_a.len()) <= (area(&box_b), group_b.len()),
+        };
+        if to_a {
+            box_a = union(&box_a, &entry.0);
+            group_a.push(entry);
+        } else {
+            box_b = union(&box_b, &entry.0);
+            group_b.push(entry);
+        }
+    }
+    [group_a, group_b]
+}
+
+fn area(rect: &[f32]) -> f64 {
+    rect.chunks_exact(2)
+        .map(|d| (d[1] as f64 - d[0] as f64).max(0.0))
+        .product()
+}
+
+fn area_of_union(a: &[f32], b: &[f32]) -> f64 {
+    a.chunks_exact(2)
+        .zip(b.chunks_exact(2))
+        .map(|(a, b)| a[1].max(b[1]) as f64 - a[0].min(b[0]) as f64)
+        .product()
+}
+
+fn union(a: &[f32], b: &[f32]) -> Rect {
+    a.chunks_exact(2)
+        .zip(b.chunks_exact(2))
+        .flat_map(|(a, b)| [a[0].min(b[0]), a[1].max(b[1])])
+        .collect()
+}
+
+fn union_all<'a>(mut rects: impl Iterator<Item = &'a Rect>) -> Option<Rect> {
+    let first = rects.next()?.clone();
+    Some(rects.fold(first, |acc, rect| union(&acc, rect)))
+}
+
+/// Whether `outer` contains `inner`.
+fn contains(outer: &[f32], inner: &[f32]) -> bool {
+    outer
+        .chunks_exact(2)
+        .zip(inner.chunks_exact(2))
+        .all(|(o, i)| o[0] <= i[0] && i[1] <= o[1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic pseudo-random numbers for the tests.
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self, bound: u32) -> f32 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((self.0 >> 33) % bound as u64) as f32
+        }
+    }
+
+    fn random_box(rng: &mut Lcg) -> Rect {
+        let (x, y) = (rng.next(1000), rng.next(1000));
+        Box::new([x, x + rng.next(20), y, y + rng.next(20)])
+    }
+
+    fn overlapping(query: &[f32]) -> impl Fn(&[f32]) -> bool + '_ {
+        |rect| {
+            rect.chunks_exact(2)
+                .zip(query.chunks_exact(2))
+                .all(|(r, q)| r[0] <= q[1] && q[0] <= r[1])
+        }
+    }
+
+    #[test]
+    fn test_search_matches_scan() {
+        let mut rng = Lcg(42);
+        let mut tree = RTree::new(2);
+        let mut all = HashMap::new();
+        for id in 0..2000 {
+            let rect = random_box(&mut rng);
+            tree.insert(id, rect.clone());
+            all.insert(id, rect);
+        }
+        for id in (0..2000).step_by(3) {
+            assert_eq!(tree.remove(id), all.remove(&id));
+        }
+        assert_eq!(tree.boxes.len(), all.len());
+
+        for _ in 0..50 {
+            let query = random_box(&mut rng);
+            let found = tree.search(overlapping(&query), overlapping(&query));
+            let mut expected = all
+                .iter()
+                .filter(|(_, rect)| overlapping(&query)(rect))
+                .map(|(id, rect)| (*id, rect.clone()))
+                .collect::<Vec<_>>();
+            expected.sort_by_key(|(id, _)| *id);
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn test_remove_everything() {
+        let mut rng = Lcg(7);
+        let mut tree = RTree::new(2);
+        for id in 0..500 {
+            tree.insert(id, random_box(&mut rng));
+        }
+        for id in (0..500).rev() {
+            assert!(tree.remove(id).is_some());
+        }
+        assert_eq!(tree.boxes.len(), 0);
+        assert!(tree.search(|_| true, |_| true).is_empty());
+        assert!(matches!(tree.root, Node::Leaf(ref entries) if entries.is_empty()));
+    }
+}
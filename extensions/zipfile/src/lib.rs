@@ -0,0 +1,541 @@
+//! Port of SQLite's zipfile extension: <https://www.sqlite.org/zipfile.html>
+//!
+//! `zipfile` is a table-valued function that reads the entries of a ZIP archive, given either
+//! as a path or as the archive itself in a blob. Archives are written through tables created
+//! with `CREATE VIRTUAL TABLE`, which hold the entries of the archive at the given path (or no
+//! entries if it doesn't exist yet) and write the archive back when a transaction that changed
+//! them commits.
+//!
+//! ## Example usage:
+//!
+//! ```sql
+//! SELECT name, sz, data FROM zipfile('archive.zip');
+//!
+//! CREATE VIRTUAL TABLE temp.zz USING zipfile('new.zip');
+//! INSERT INTO temp.zz(name, mode, mtime, data) VALUES ('a.txt', 420, 1700000000, 'hello');
+//! ```
+//!
+//! ## Columns:
+//! - `name` — path of the entry in the archive; directories end with `/`
+//! - `mode` — POSIX mode, file type included; defaults to a regular file (or a directory if the
+//!   name ends with `/`) with `0644` (`0755`) permissions
+//! - `mtime` — modification time in seconds since the UNIX epoch; defaults to now
+//! - `sz` — uncompressed size
+//! - `rawdata` — data as stored in the archive
+//! - `data` — uncompressed data, `NULL` for directories
+//! - `method` — compression method, `0` (stored) or `8` (deflate). When inserting `data`, it
+//!   is deflated if that makes it smaller unless `method` is given. When inserting `rawdata`,
+//!   `method` and `sz` are required.
+mod zip;
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use turso_ext::{
+    register_extension, Connection, ConstraintInfo, ConstraintOp, ConstraintUsage, IndexInfo,
+    OrderByInfo, ResultCode, VTabCursor, VTabKind, VTabModule, VTabModuleDerive, VTable, Value,
+    ValueType,
+};
+use zip::{ZipEntry, METHOD_DEFLATE, METHOD_STORED, S_IFDIR, S_IFMT, S_IFREG};
+
+register_extension! {
+    vtabs: { ZipfileVTabModule }
+}
+
+const SCHEMA: &str = "CREATE TABLE x (
+    name PRIMARY KEY,
+    mode,
+    mtime,
+    sz,
+    rawdata,
+    data,
+    method,
+    z HIDDEN
+)";
+
+const COLUMN_NAME: usize = 0;
+const COLUMN_MODE: usize = 1;
+const COLUMN_MTIME: usize = 2;
+const COLUMN_SZ: usize = 3;
+const COLUMN_RAWDATA: usize = 4;
+const COLUMN_DATA: usize = 5;
+const COLUMN_METHOD: usize = 6;
+const COLUMN_Z: u32 = 7;
+
+/// Entries of a table by rowid, in archive order.
+type Entries = Rc<RefCell<BTreeMap<i64, ZipEntry>>>;
+
+#[derive(Debug, VTabModuleDerive, Default)]
+struct ZipfileVTabModule;
+
+impl VTabModule for ZipfileVTabModule {
+    type Table = ZipfileTable;
+    const VTAB_KIND: VTabKind = VTabKind::TableValuedFunction;
+    const NAME: &'static str = "zipfile";
+    const READONLY: bool = false;
+
+    fn create(args: &[Value]) -> Result<(String, Self::Table), ResultCode> {
+        let path = match args {
+            [] => None,
+            [path] => Some(unquote(path.to_text().ok_or(ResultCode::InvalidArgs)?)),
+            _ => return Err(ResultCode::InvalidArgs),
+        };
+        let mut table = ZipfileTable {
+            path,
+            entries: Rc::new(RefCell::new(BTreeMap::new())),
+            next_rowid: 1,
+            dirty: false,
+        };
+        table.load().map_err(|_| ResultCode::Error)?;
+        Ok((SCHEMA.to_string(), table))
+    }
+}
+
+/// Strips the quotes around an argument of `CREATE VIRTUAL TABLE`.
+fn unquote(arg: &str) -> String {
+    let arg = arg.trim();
+    for quote in ['\'', '"'] {
+        if let Some(inner) = arg
+            .strip_prefix(quote)
+            .and_then(|rest| rest.strip_suffix(quote))
+        {
+            return inner.replace(&format!("{quote}{quote}"), &quote.to_string());
+        }
+    }
+    arg.to_string()
+}
+
+struct ZipfileTable {
+    /// Archive of a table created with `CREATE VIRTUAL TABLE`, `None` for `zipfile()`.
+    path: Option<String>,
+    entries: Entries,
+    next_rowid: i64,
+    /// Whether the entries changed since the archive was last written.
+    dirty: bool,
+}
+
+impl ZipfileTable {
+    /// Replaces the entries with the ones in the archive.
+    fn load(&mut self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let entries = match std::fs::read(path) {
+            Ok(bytes) => zip::read_archive(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(format!("cannot read {path}: {e}")),
+        };
+        self.next_rowid = entries.len() as i64 + 1;
+        *self.entries.borrow_mut() = (1..).zip(entries).collect();
+        self.dirty = false;
+        Ok(())
+    }
+
+    fn writable_path(&self) -> Result<&str, String> {
+        self.path.as_deref().ok_or_else(|| {
+            "zipfile() is read-only, use CREATE VIRTUAL TABLE ... USING zipfile(path) to write \
+             an archive"
+                .to_string()
+        })
+    }
+
+    fn check_unique(&self, name: &str, rowid: Option<i64>) -> Result<(), String> {
+        let entries = self.entries.borrow();
+        if entries
+            .iter()
+            .any(|(id, entry)| Some(*id) != rowid && entry.name == name)
+        {
+            return Err(format!("duplicate name: \"{name}\""));
+        }
+        Ok(())
+    }
+}
+
+/// Builds an entry from the column values of an inserted row, or of an updated row whose entry
+/// was `previous`.
+fn entry_from_values(values: &[Value], previous: Option<&ZipEntry>) -> Result<ZipEntry, String> {
+    let value = |i: usize| values.get(i).filter(|v| v.value_type() != ValueType::Null);
+    let integer = |i: usize, column: &str| {
+        value(i)
+            .map(|v| match v.value_type() {
+                ValueType::Integer => v.to_integer().ok_or(()),
+                _ => Err(()),
+            })
+            .transpose()
+            .map_err(|_| format!("zipfile: {column} must be an integer"))
+    };
+
+    let mut name = value(COLUMN_NAME)
+        .and_then(|v| v.to_text())
+        .ok_or("zipfile: name must be a non-NULL string")?
+        .to_string();
+    let mode = match integer(COLUMN_MODE, "mode")? {
+        Some(mode) => mode as u32,
+        None if name.ends_with('/') => S_IFDIR | 0o755,
+        None => S_IFREG | 0o644,
+    };
+    let is_dir = mode & S_IFMT == S_IFDIR;
+    if is_dir && !name.ends_with('/') {
+        name.push('/');
+    } else if !is_dir && name.ends_with('/') {
+        return Err(format!("zipfile: mode of \"{name}\" is not a directory"));
+    }
+    let mtime = match integer(COLUMN_MTIME, "mtime")? {
+        Some(mtime) => mtime,
+        None => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64),
+    };
+    let method = integer(COLUMN_METHOD, "method")?
+        .map(|method| match u16::try_from(method) {
+            Ok(method @ (METHOD_STORED | METHOD_DEFLATE)) => Ok(method),
+            _ => Err(format!("zipfile: unsupported compression method: {method}")),
+        })
+        .transpose()?;
+    let mut sz = integer(COLUMN_SZ, "sz")?;
+    let mut rawdata = value(COLUMN_RAWDATA).and_then(|v| v.to_blob());
+    let mut data = value(COLUMN_DATA).and_then(|v| v.to_blob());
+
+    // An update passes the old value of every column that isn't assigned, so the content
+    // columns that still hold the old content are treated as not set.
+    if let Some(previous) = previous {
+        let old_data = if previous.is_dir() {
+            None
+        } else {
+            Some(previous.data()?)
+        };
+        let rawdata_unchanged = rawdata.as_ref() == Some(&previous.rawdata);
+        let data_unchanged = data == old_data;
+        if rawdata_unchanged && data_unchanged {
+            if method.is_none_or(|method| method == previous.method) {
+                return Ok(ZipEntry {
+                    name,
+                    mode,
+                    mtime,
+                    ..previous.clone()
+                });
+            }
+            rawdata = None;
+        } else if rawdata_unchanged {
+            rawdata = None;
+        } else if data_unchanged {
+            data = None;
+        }
+        if rawdata.is_none() && sz == Some(previous.sz as i64) {
+            sz = None;
+        }
+    }
+
+    match (rawdata, data) {
+        (Some(_), Some(_)) => Err("zipfile: rawdata and data cannot both be set".to_string()),
+        (Some(rawdata), None) => {
+            let (Some(sz), Some(method)) = (sz, method) else {
+                return Err("zipfile: sz and method are required with rawdata".to_string());
+            };
+            ZipEntry::from_raw(name, mode, mtime, sz.max(0) as u64, method, rawdata)
+        }
+        (None, data) => {
+            if sz.is_some() {
+                return Err("zipfile: sz must be NULL unless rawdata is set".to_string());
+            }
+            let data = data.unwrap_or_default();
+            if is_dir && !data.is_empty() {
+                return Err(format!("zipfile: directory \"{name}\" cannot have data"));
+            }
+            ZipEntry::new(name, mode, mtime, &data, method)
+        }
+    }
+}
+
+impl VTable for ZipfileTable {
+    type Cursor = ZipfileCursor;
+    type Error = String;
+
+    fn open(&self, _conn: Option<Arc<Connection>>) -> Result<Self::Cursor, Self::Error> {
+        Ok(ZipfileCursor {
+            table_entries: self.path.as_ref().map(|_| self.entries.clone()),
+            rows: Vec::new(),
+            index: 0,
+        })
+    }
+
+    fn best_index(
+        constraints: &[ConstraintInfo],
+        _order_by: &[OrderByInfo],
+    ) -> Result<IndexInfo, ResultCode> {
+        // `z`, the argument of `zipfile()`, is passed to `filter` as its only argument.
+        let z = constraints
+            .iter()
+            .position(|c| c.usable && c.op == ConstraintOp::Eq && c.column_index == COLUMN_Z);
+        let constraint_usages = (0..constraints.len())
+            .map(|i| ConstraintUsage {
+                argv_index: (Some(i) == z).then_some(1),
+                omit: Some(i) == z,
+            })
+            .collect();
+        Ok(IndexInfo {
+            idx_num: z.is_some() as i32,
+            idx_str: Some("z".to_string()),
+            estimated_cost: if z.is_some() { 10.0 } else { 1_000_000.0 },
+            constraint_usages,
+            ..Default::default()
+        })
+    }
+
+    fn insert(&mut self, args: &[Value]) -> Result<i64, Self::Error> {
+        self.writable_path()?;
+        let entry = entry_from_values(args, None)?;
+        self.check_unique(&entry.name, None)?;
+        let rowid = self.next_rowid;
+        self.next_rowid += 1;
+        self.entries.borrow_mut().insert(rowid, entry);
+        self.dirty = true;
+        Ok(rowid)
+    }
+
+    fn update(&mut self, rowid: i64, args: &[Value]) -> Result<(), Self::Error> {
+        self.writable_path()?;
+        let previous = self
+            .entries
+            .borrow()
+            .get(&rowid)
+            .cloned()
+            .ok_or_else(|| format!("zipfile: no row with rowid {rowid}"))?;
+        let entry = entry_from_values(args, Some(&previous))?;
+        self.check_unique(&entry.name, Some(rowid))?;
+        self.entries.borrow_mut().insert(rowid, entry);
+        self.dirty = true;
+        Ok(())
+    }
+
+    fn delete(&mut self, rowid: i64) -> Result<(), Self::Error> {
+        self.writable_path()?;
+        self.entries.borrow_mut().remove(&rowid);
+        self.dirty = true;
+        Ok(())
+    }
+
+    fn commit(&mut self) -> Result<(), Self::Error> {
+        if !self.dirty {
+            return Ok(());
+        }
+        let path = self.writable_path()?;
+        let entries = self.entries.borrow().values().cloned().collect::<Vec<_>>();
+        let archive = zip::write_archive(&entries)?;
+        std::fs::write(path, archive).map_err(|e| format!("cannot write {path}: {e}"))?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> Result<(), Self::Error> {
+        if self.dirty {
+            self.load()?;
+        }
+        Ok(())
+    }
+}
+
+struct ZipfileCursor {
+    /// Entries of a table created with `CREATE VIRTUAL TABLE`.
+    table_entries: Option<Entries>,
+    /// Snapshot of the rows taken by `filter`.
+    rows: Vec<(i64, ZipEntry)>,
+    index: usize,
+}
+
+impl ZipfileCursor {
+    fn current(&self) -> Option<&ZipEntry> {
+        self.rows.get(self.index).map(|(_, entry)| entry)
+    }
+}
+
+impl VTabCursor for ZipfileCursor {
+    type Error = String;
+
+    fn filter(&mut self, args: &[Value], _idx_info: Option<(&str, i32)>) -> ResultCode {
+        self.index = 0;
+        self.rows = match (args.first(), &self.table_entries) {
+            (Some(z), _) => {
+                let bytes = match z.value_type() {
+                    ValueType::Text => match std::fs::read(z.to_text().unwrap_or_default()) {
+                        Ok(bytes) => bytes,
+                        Err(_) => return ResultCode::Error,
+                    },
+                    ValueType::Blob => z.to_blob().unwrap_or_default(),
+                    _ => return ResultCode::InvalidArgs,
+                };
+                match zip::read_archive(&bytes) {
+                    Ok(entries) => (1..).zip(entries).collect(),
+                    Err(_) => return ResultCode::Error,
+                }
+            }
+            (None, Some(entries)) => entries
+                .borrow()
+                .iter()
+                .map(|(rowid, entry)| (*rowid, entry.clone()))
+                .collect(),
+            // zipfile() without an argument
+            (None, None) => return ResultCode::InvalidArgs,
+        };
+        if self.rows.is_empty() {
+            ResultCode::EOF
+        } else {
+            ResultCode::OK
+        }
+    }
+
+    fn rowid(&self) -> i64 {
+        self.rows.get(self.index).map_or(-1, |(rowid, _)| *rowid)
+    }
+
+    fn column(&self, idx: u32) -> Result<Value, Self::Error> {
+        let Some(entry) = self.current() else {
+            return Err("cursor out of range".to_string());
+        };
+        let value = match idx as usize {
+            COLUMN_NAME => Value::from_text(entry.name.clone()),
+            COLUMN_MODE => Value::from_integer(entry.mode as i64),
+            COLUMN_MTIME => Value::from_integer(entry.mtime),
+            COLUMN_SZ => Value::from_integer(entry.sz as i64),
+            COLUMN_RAWDATA => Value::from_blob(entry.rawdata.clone()),
+            COLUMN_DATA if entry.is_dir() => Value::null(),
+            COLUMN_DATA => Value::from_blob(entry.data()?),
+            COLUMN_METHOD => Value::from_integer(entry.method as i64),
+            _ => Value::null(),
+        };
+        Ok(value)
+    }
+
+    fn eof(&self) -> bool {
+        self.index >= self.rows.len()
+    }
+
+    fn next(&mut self) -> ResultCode {
+        self.index += 1;
+        if self.eof() {
+            ResultCode::EOF
+        } else {
+            ResultCode::OK
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Value {
+        Value::from_text(s.to_string())
+    }
+
+    fn row(name: &str, data: &str) -> Vec<Value> {
+        let mut values = (0..8).map(|_| Value::null()).collect::<Vec<_>>();
+        values[COLUMN_NAME] = text(name);
+        values[COLUMN_MTIME] = Value::from_integer(1_700_000_000);
+        values[COLUMN_DATA] = text(data);
+        values
+    }
+
+    fn names(table: &ZipfileTable, z: Option<Value>) -> Vec<String> {
+        let mut cursor = table.open(None).unwrap();
+        let args = z.into_iter().collect::<Vec<_>>();
+        let mut names = Vec::new();
+        if cursor.filter(&args, None) == ResultCode::OK {
+            while !cursor.eof() {
+                names.push(cursor.column(0).unwrap().to_text().unwrap().to_string());
+                cursor.next();
+            }
+        }
+        names
+    }
+
+    #[test]
+    fn test_write_and_read_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.zip");
+        let path = path.to_str().unwrap();
+        let (_, mut table) = ZipfileVTabModule::create(&[text(&format!("'{path}'"))]).unwrap();
+
+        table.begin().unwrap();
+        table.insert(&row("a.txt", &"hello ".repeat(100))).unwrap();
+        let b = table.insert(&row("b.txt", "b")).unwrap();
+        assert!(table.insert(&row("a.txt", "again")).is_err());
+        table.commit().unwrap();
+
+        table.begin().unwrap();
+        table.delete(b).unwrap();
+        table.rollback().unwrap();
+        assert_eq!(names(&table, None), vec!["a.txt", "b.txt"]);
+
+        // zipfile() reads the archive written on commit, from a path or a blob.
+        let (_, zipfile) = ZipfileVTabModule::create(&[]).unwrap();
+        assert_eq!(names(&zipfile, Some(text(path))), vec!["a.txt", "b.txt"]);
+        let blob = Value::from_blob(std::fs::read(path).unwrap());
+        let mut cursor = zipfile.open(None).unwrap();
+        assert_eq!(cursor.filter(&[blob], None), ResultCode::OK);
+        assert_eq!(
+            cursor.column(COLUMN_METHOD as u32).unwrap().to_integer(),
+            Some(8)
+        );
+        assert_eq!(
+            cursor
+                .column(COLUMN_DATA as u32)
+                .unwrap()
+                .to_blob()
+                .unwrap(),
+            "hello ".repeat(100).into_bytes()
+        );
+        assert!(zipfile.open(None).unwrap().filter(&[], None) == ResultCode::InvalidArgs);
+    }
+
+    #[test]
+    fn test_zipfile_function_is_read_only() {
+        let (_, mut zipfile) = ZipfileVTabModule::create(&[]).unwrap();
+        assert!(zipfile.insert(&row("a.txt", "a")).is_err());
+    }
+
+    #[test]
+    fn test_update_passes_unassigned_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("update.zip");
+        let (_, mut table) = ZipfileVTabModule::create(&[text(path.to_str().unwrap())]).unwrap();
+        let rowid = table.insert(&row("a.txt", &"a".repeat(100))).unwrap();
+
+        // The values an UPDATE passes: the current row with the assigned columns replaced.
+        let current_row = |table: &ZipfileTable| {
+            let mut cursor = table.open(None).unwrap();
+            cursor.filter(&[], None);
+            (0..8)
+                .map(|i| cursor.column(i).unwrap())
+                .collect::<Vec<_>>()
+        };
+        let mut values = current_row(&table);
+        values[COLUMN_NAME] = text("b.txt");
+        table.update(rowid, &values).unwrap();
+        let mut values = current_row(&table);
+        assert_eq!(
+            values[COLUMN_METHOD].to_integer(),
+            Some(METHOD_DEFLATE as i64)
+        );
+        values[COLUMN_DATA] = text("bye");
+        table.update(rowid, &values).unwrap();
+
+        let values = current_row(&table);
+        assert_eq!(values[COLUMN_NAME].to_text(), Some("b.txt"));
+        assert_eq!(values[COLUMN_SZ].to_integer(), Some(3));
+        assert_eq!(values[COLUMN_DATA].to_blob().unwrap(), b"bye");
+    }
+
+    #[test]
+    fn test_directory_entries() {
+        let mut values = row("dir", "");
+        values[COLUMN_MODE] = Value::from_integer((S_IFDIR | 0o755) as i64);
+        values[COLUMN_DATA] = Value::null();
+        let entry = entry_from_values(&values, None).unwrap();
+        assert_eq!(entry.name, "dir/");
+        assert!(entry.is_dir());
+        assert!(entry_from_values(&row("file/", "data"), None).is_err());
+    }
+}
@@ -0,0 +1,408 @@
+//! Reading and writing ZIP archives.
+//!
+//! Only what `zipfile` needs is supported: entries are either stored or compressed with
+//! deflate, and archives are limited to 4 GiB (no ZIP64). Modification times are read from the
+//! "extended timestamp" extra field when it is present and fall back to the DOS timestamp,
+//! which has a two second resolution and no time zone, otherwise.
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression, Crc};
+use std::io::{Read, Write};
+
+pub const METHOD_STORED: u16 = 0;
+pub const METHOD_DEFLATE: u16 = 8;
+
+pub const S_IFMT: u32 = 0o170000;
+pub const S_IFDIR: u32 = 0o040000;
+pub const S_IFREG: u32 = 0o100000;
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x04034b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x02014b50;
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x06054b50;
+const LOCAL_HEADER_LEN: usize = 30;
+const CENTRAL_HEADER_LEN: usize = 46;
+const END_OF_CENTRAL_DIRECTORY_LEN: usize = 22;
+/// Header id of the extended timestamp extra field.
+const EXTENDED_TIMESTAMP: u16 = 0x5455;
+/// "Version made by" of entries written by us: UNIX, ZIP 3.0.
+const VERSION_MADE_BY: u16 = (3 << 8) | 30;
+const VERSION_NEEDED: u16 = 20;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZipEntry {
+    pub name: String,
+    pub mode: u32,
+    /// Seconds since the UNIX epoch.
+    pub mtime: i64,
+    /// Uncompressed size.
+    pub sz: u64,
+    pub method: u16,
+    pub crc32: u32,
+    /// The entry data as stored in the archive.
+    pub rawdata: Vec<u8>,
+}
+
+impl ZipEntry {
+    /// Creates an entry for `data`, deflating it unless `method` says otherwise or deflating
+    /// doesn't make it smaller.
+    pub fn new(
+        name: String,
+        mode: u32,
+        mtime: i64,
+        data: &[u8],
+        method: Option<u16>,
+    ) -> Result<Self, String> {
+        let (method, rawdata) = match method {
+            Some(METHOD_STORED) => (METHOD_STORED, data.to_vec()),
+            Some(METHOD_DEFLATE) => (METHOD_DEFLATE, deflate(data)?),
+            None => {
+                let compressed = deflate(data)?;
+                if compressed.len() < data.len() {
+                    (METHOD_DEFLATE, compressed)
+                } else {
+                    (METHOD_STORED, data.to_vec())
+                }
+            }
+            Some(method) => return Err(format!("unsupported compression method: {method}")),
+        };
+        Ok(Self {
+            name,
+            mode,
+            mtime,
+            sz: data.len() as u64,
+            method,
+            crc32: crc32(data),
+            rawdata,
+        })
+    }
+
+    /// Creates an entry from data that is already compressed with `method`.
+    pub fn from_raw(
+        name: String,
+        mode: u32,
+        mtime: i64,
+        sz: u64,
+        method: u16,
+        rawdata: Vec<u8>,
+    ) -> Result<Self, String> {
+        let mut entry = Self {
+            name,
+            mode,
+            mtime,
+            sz,
+            method,
+            crc32: 0,
+            rawdata,
+        };
+        entry.crc32 = match method {
+            METHOD_STORED => crc32(&entry.rawdata),
+            METHOD_DEFLATE => {
+                let mut data = Vec::with_capacity(sz as usize);
+                DeflateDecoder::new(entry.rawdata.as_slice())
+                    .read_to_end(&mut data)
+                    .map_err(|e| format!("{}: {e}", entry.name))?;
+                crc32(&data)
+            }
+            method => return Err(format!("unsupported compression method: {method}")),
+        };
+        Ok(entry)
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.mode & S_IFMT == S_IFDIR
+    }
+
+    /// The uncompressed entry data.
+    pub fn data(&self) -> Result<Vec<u8>, String> {
+        let data = match self.method {
+            METHOD_STORED => self.rawdata.clone(),
+            METHOD_DEFLATE => {
+                let mut data = Vec::with_capacity(self.sz as usize);
+                DeflateDecoder::new(self.rawdata.as_slice())
+                    .read_to_end(&mut data)
+                    .map_err(|e| format!("{}: {e}", self.name))?;
+                data
+            }
+            method => {
+                return Err(format!(
+                    "{}: unsupported compression method: {method}",
+                    self.name
+                ))
+            }
+        };
+        if data.len() as u64 != self.sz || crc32(&data) != self.crc32 {
+            return Err(format!("{}: corrupt zip entry", self.name));
+        }
+        Ok(data)
+    }
+}
+
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = Crc::new();
+    crc.update(data);
+    crc.sum()
+}
+
+fn deflate(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).map_err(|e| e.to_string())?;
+    encoder.finish().map_err(|e| e.to_string())
+}
+
+/// Parses the entries of the archive in `bytes`, in central directory order.
+pub fn read_archive(bytes: &[u8]) -> Result<Vec<ZipEntry>, String> {
+    let corrupt = || "not a zip archive".to_string();
+    if bytes.is_empty() {
+        return Ok(Vec::new());
+    }
+    // The end of central directory record is followed by a comment of up to 64 KiB.
+    let eocd = (0..=bytes.len().saturating_sub(END_OF_CENTRAL_DIRECTORY_LEN))
+        .rev()
+        .take(u16::MAX as usize + 1)
+        .find(|&pos| u32_at(bytes, pos) == Some(END_OF_CENTRAL_DIRECTORY_SIGNATURE))
+        .ok_or_else(corrupt)?;
+    let count = u16_at(bytes, eocd + 10).ok_or_else(corrupt)? as usize;
+    let mut pos = u32_at(bytes, eocd + 16).ok_or_else(corrupt)? as usize;
+
+    let mut entries = Vec::with_capacity(count);
+    for _ in 0..count {
+        if u32_at(bytes, pos) != Some(CENTRAL_HEADER_SIGNATURE) {
+            return Err(corrupt());
+        }
+        let header = bytes
+            .get(pos..pos + CENTRAL_HEADER_LEN)
+            .ok_or_else(corrupt)?;
+        let version_made_by = u16_at(header, 4).unwrap();
+        let method = u16_at(header, 10).unwrap();
+        let dos_time = u16_at(header, 12).unwrap();
+        let dos_date = u16_at(header, 14).unwrap();
+        let crc32 = u32_at(header, 16).unwrap();
+        let compressed_size = u32_at(header, 20).unwrap() as usize;
+        let sz = u32_at(header, 24).unwrap() as u64;
+        let name_len = u16_at(header, 28).unwrap() as usize;
+        let extra_len = u16_at(header, 30).unwrap() as usize;
+        let comment_len = u16_at(header, 32).unwrap() as usize;
+        let external_attributes = u32_at(header, 38).unwrap();
+        let local_header = u32_at(header, 42).unwrap() as usize;
+
+        let name_start = pos + CENTRAL_HEADER_LEN;
+        let name = bytes
+            .get(name_start..name_start + name_len)
+            .ok_or_else(corrupt)?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        let extra = bytes
+            .get(name_start + name_len..name_start + name_len + extra_len)
+            .ok_or_else(corrupt)?;
+        pos = name_start + name_len + extra_len + comment_len;
+
+        let mode = if version_made_by >> 8 == 3 && external_attributes >> 16 != 0 {
+            external_attributes >> 16
+        } else if name.ends_with('/') {
+            S_IFDIR | 0o755
+        } else {
+            S_IFREG | 0o644
+        };
+        let mtime = extended_timestamp(extra).unwrap_or_else(|| dos_to_unix(dos_date, dos_time));
+
+        if u32_at(bytes, local_header) != Some(LOCAL_HEADER_SIGNATURE) {
+            return Err(corrupt());
+        }
+        let local_name_len = u16_at(bytes, local_header + 26).ok_or_else(corrupt)? as usize;
+        let local_extra_len = u16_at(bytes, local_header + 28).ok_or_else(corrupt)? as usize;
+        let data_start = local_header + LOCAL_HEADER_LEN + local_name_len + local_extra_len;
+        let rawdata = bytes
+            .get(data_start..data_start + compressed_size)
+            .ok_or_else(corrupt)?
+            .to_vec();
+
+        entries.push(ZipEntry {
+            name,
+            mode,
+            mtime,
+            sz,
+            method,
+            crc32,
+            rawdata,
+        });
+    }
+    Ok(entries)
+}
+
+/// Serializes `entries` into an archive.
+pub fn write_archive(entries: &[ZipEntry]) -> Result<Vec<u8>, String> {
+    if entries.len() > u16::MAX as usize {
+        return Err("too many entries for a zip archive".to_string());
+    }
+    let mut out = Vec::new();
+    let mut central_directory = Vec::new();
+    for entry in entries {
+        let offset = u32::try_from(out.len()).map_err(|_| "zip archive too large")?;
+        let compressed_size =
+            u32::try_from(entry.rawdata.len()).map_err(|_| "zip entry too large")?;
+        let sz = u32::try_from(entry.sz).map_err(|_| "zip entry too large")?;
+        let (dos_date, dos_time) = unix_to_dos(entry.mtime);
+        let mtime = entry.mtime.clamp(0, u32::MAX as i64) as u32;
+        let name = entry.name.as_bytes();
+
+        out.extend_from_slice(&LOCAL_HEADER_SIGNATURE.to_le_bytes());
+        out.extend_from_slice(&VERSION_NEEDED.to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes()); // flags
+        out.extend_from_slice(&entry.method.to_le_bytes());
+        out.extend_from_slice(&dos_time.to_le_bytes());
+        out.extend_from_slice(&dos_date.to_le_bytes());
+        out.extend_from_slice(&entry.crc32.to_le_bytes());
+        out.extend_from_slice(&compressed_size.to_le_bytes());
+        out.extend_from_slice(&sz.to_le_bytes());
+        out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        out.extend_from_slice(&9u16.to_le_bytes()); // extra field length
+        out.extend_from_slice(name);
+        write_extended_timestamp(&mut out, mtime);
+        out.extend_from_slice(&entry.rawdata);
+
+        let cd = &mut central_directory;
+        cd.extend_from_slice(&CENTRAL_HEADER_SIGNATURE.to_le_bytes());
+        cd.extend_from_slice(&VERSION_MADE_BY.to_le_bytes());
+        cd.extend_from_slice(&VERSION_NEEDED.to_le_bytes());
+        cd.extend_from_slice(&0u16.to_le_bytes()); // flags
+        cd.extend_from_slice(&entry.method.to_le_bytes());
+        cd.extend_from_slice(&dos_time.to_le_bytes());
+        cd.extend_from_slice(&dos_date.to_le_bytes());
+        cd.extend_from_slice(&entry.crc32.to_le_bytes());
+        cd.extend_from_slice(&compressed_size.to_le_bytes());
+        cd.extend_from_slice(&sz.to_le_bytes());
+        cd.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        cd.extend_from_slice(&9u16.to_le_bytes()); // extra field length
+        cd.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        cd.extend_from_slice(&0u16.to_le_bytes()); // disk number
+        cd.extend_from_slice(&0u16.to_le_bytes()); // internal attributes
+        cd.extend_from_slice(&(entry.mode << 16).to_le_bytes());
+        cd.extend_from_slice(&offset.to_le_bytes());
+        cd.extend_from_slice(name);
+        write_extended_timestamp(cd, mtime);
+    }
+
+    let central_directory_offset = u32::try_from(out.len()).map_err(|_| "zip archive too large")?;
+    out.extend_from_slice(&central_directory);
+    out.extend_from_slice(&END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes()); // disk number
+    out.extend_from_slice(&0u16.to_le_bytes()); // disk with the central directory
+    out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(central_directory.len() as u32).to_le_bytes());
+    out.extend_from_slice(&central_directory_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes()); // comment length
+    Ok(out)
+}
+
+fn write_extended_timestamp(out: &mut Vec<u8>, mtime: u32) {
+    out.extend_from_slice(&EXTENDED_TIMESTAMP.to_le_bytes());
+    out.extend_from_slice(&5u16.to_le_bytes());
+    out.push(1); // only the modification time is present
+    out.extend_from_slice(&mtime.to_le_bytes());
+}
+
+/// Modification time from the extended timestamp extra field, if there is one.
+fn extended_timestamp(mut extra: &[u8]) -> Option<i64> {
+    while extra.len() >= 4 {
+        let id = u16_at(extra, 0)?;
+        let len = u16_at(extra, 2)? as usize;
+        let data = extra.get(4..4 + len)?;
+        if id == EXTENDED_TIMESTAMP && data.len() >= 5 && data[0] & 1 != 0 {
+            return Some(u32_at(data, 1)? as i64);
+        }
+        extra = &extra[4 + len..];
+    }
+    None
+}
+
+fn dos_to_unix(date: u16, time: u16) -> i64 {
+    let year = 1980 + (date >> 9) as i64;
+    let month = ((date >> 5) & 0xf).clamp(1, 12) as i64;
+    let day = (date & 0x1f).max(1) as i64;
+    let seconds = (time >> 11) as i64 * 3600 + ((time >> 5) & 0x3f) as i64 * 60;
+    days_from_civil(year, month, day) * 86400 + seconds + (time & 0x1f) as i64 * 2
+}
+
+fn unix_to_dos(mtime: i64) -> (u16, u16) {
+    let (year, month, day) = civil_from_days(mtime.div_euclid(86400));
+    if year < 1980 {
+        return ((1 << 5) | 1, 0);
+    }
+    let secs = mtime.rem_euclid(86400);
+    let date = (((year - 1980).min(127) << 9) | (month << 5) | day) as u16;
+    let time = (((secs / 3600) << 11) | ((secs % 3600 / 60) << 5) | (secs % 60 / 2)) as u16;
+    (date, time)
+}
+
+/// Days since 1970-01-01 of a date in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Inverse of [days_from_civil].
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+fn u16_at(bytes: &[u8], pos: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        bytes.get(pos..pos + 2)?.try_into().ok()?,
+    ))
+}
+
+fn u32_at(bytes: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        bytes.get(pos..pos + 4)?.try_into().ok()?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_round_trip() {
+        let text = b"zip zip zip zip zip zip zip zip zip zip".repeat(20);
+        let entries = vec![
+            ZipEntry::new("dir/".into(), S_IFDIR | 0o755, 1_700_000_000, b"", None).unwrap(),
+            ZipEntry::new(
+                "dir/a.txt".into(),
+                S_IFREG | 0o644,
+                1_700_000_001,
+                &text,
+                None,
+            )
+            .unwrap(),
+            ZipEntry::new("b".into(), S_IFREG | 0o600, 0, b"b", None).unwrap(),
+        ];
+        assert_eq!(entries[1].method, METHOD_DEFLATE);
+        assert_eq!(entries[2].method, METHOD_STORED);
+
+        let archive = write_archive(&entries).unwrap();
+        let read = read_archive(&archive).unwrap();
+        assert_eq!(read, entries);
+        assert_eq!(read[1].data().unwrap(), text);
+        assert!(read_archive(b"not a zip file at all").is_err());
+    }
+
+    #[test]
+    fn test_dos_time() {
+        let mtime = 1_700_000_000; // 2023-11-14 22:13:20
+        let (date, time) = unix_to_dos(mtime);
+        assert_eq!(dos_to_unix(date, time), mtime);
+        assert_eq!(civil_from_days(days_from_civil(2024, 2, 29)), (2024, 2, 29));
+    }
+}
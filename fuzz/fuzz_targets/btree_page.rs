@@ -0,0 +1,59 @@
+#![no_main]
+use std::sync::Arc;
+
+use libfuzzer_sys::{fuzz_target, Corpus};
+use turso_core::storage::sqlite3_ondisk::BTreeCell;
+use turso_core::types::ImmutableRecord;
+use turso_core::{Buffer, Page};
+
+const PAGE_SIZE: usize = 512;
+const DATABASE_HEADER_SIZE: usize = 100;
+
+fuzz_target!(|data: &[u8]| -> Corpus {
+    let Some((&first, data)) = data.split_first() else {
+        return Corpus::Reject;
+    };
+    // Page 1 starts after the database header, exercise both layouts.
+    let page_id = if first & 1 == 0 { 1 } else { 2 };
+    let offset = if page_id == 1 {
+        DATABASE_HEADER_SIZE
+    } else {
+        0
+    };
+
+    let mut buf = vec![0u8; PAGE_SIZE];
+    let len = data.len().min(PAGE_SIZE - offset);
+    buf[offset..offset + len].copy_from_slice(&data[..len]);
+
+    let page = Page::new(page_id);
+    page.get().buffer = Some(Arc::new(Buffer::new(buf)));
+    let contents = page.get_contents();
+
+    if contents.maybe_page_type().is_none() {
+        return Corpus::Reject;
+    }
+    // Mirror the header checks done before a page is handed to the b-tree layer.
+    let cell_pointers_end = offset + contents.header_size() + contents.cell_pointer_array_size();
+    if cell_pointers_end > contents.cell_content_area() as usize
+        || contents.cell_content_area() as usize > PAGE_SIZE
+    {
+        return Corpus::Reject;
+    }
+
+    let _ = contents.rightmost_pointer();
+    for idx in 0..contents.cell_count() {
+        let Ok(cell) = contents.cell_get(idx, PAGE_SIZE) else {
+            continue;
+        };
+        let payload = match cell {
+            BTreeCell::TableLeafCell(cell) => cell.payload,
+            BTreeCell::IndexInteriorCell(cell) => cell.payload,
+            BTreeCell::IndexLeafCell(cell) => cell.payload,
+            BTreeCell::TableInteriorCell(_) => continue,
+        };
+        let record = ImmutableRecord::from_bin_record(payload.to_vec());
+        let _ = record.get_values();
+    }
+
+    Corpus::Keep
+});
@@ -0,0 +1,30 @@
+#![no_main]
+use std::sync::Arc;
+
+use libfuzzer_sys::{fuzz_target, Corpus};
+
+fn do_fuzz(data: &[u8]) -> Result<Corpus, Box<dyn std::error::Error>> {
+    // The parser works on raw bytes, so it must reject invalid UTF-8 gracefully too.
+    let mut parser = turso_parser::parser::Parser::new(data);
+    while let Ok(Some(_)) = parser.next_cmd() {}
+
+    let Ok(sql) = std::str::from_utf8(data) else {
+        return Ok(Corpus::Keep);
+    };
+
+    let io = Arc::new(turso_core::MemoryIO::new());
+    let db = turso_core::Database::open_file(io, ":memory:")?;
+    let conn = db.connect()?;
+    conn.execute(
+        "CREATE TABLE t(a INTEGER PRIMARY KEY, b TEXT, c REAL, d BLOB);
+         CREATE INDEX t_bc ON t(b, c);
+         CREATE VIEW v AS SELECT a, b FROM t;",
+    )?;
+
+    // Errors are expected for most inputs, only panics are bugs.
+    let _ = conn.prepare(sql);
+
+    Ok(Corpus::Keep)
+}
+
+fuzz_target!(|data: &[u8]| -> Corpus { do_fuzz(data).unwrap_or(Corpus::Keep) });
@@ -0,0 +1,19 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use turso_core::storage::sqlite3_ondisk::read_varint;
+use turso_core::types::ImmutableRecord;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok((_, n)) = read_varint(data) {
+        assert!((1..=9).contains(&n) && n <= data.len());
+    }
+
+    // Decoding arbitrary bytes must not panic. Whatever the decoder returns must then
+    // survive an encode/decode round trip unchanged.
+    let record = ImmutableRecord::from_bin_record(data.to_vec());
+    let values = record.get_values();
+    let encoded = ImmutableRecord::from_values(&values, values.len());
+    let decoded = encoded.get_values();
+    let reencoded = ImmutableRecord::from_values(&decoded, decoded.len());
+    assert_eq!(encoded.into_payload(), reencoded.into_payload());
+});
@@ -0,0 +1,76 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, ItemFn};
+
+use super::ScalarInfo;
+
+pub fn collation(attr: TokenStream, input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as ItemFn);
+    let fn_name = &ast.sig.ident;
+    let inputs = &ast.sig.inputs;
+    let output = &ast.sig.output;
+    let fn_body = &ast.block;
+    let collation_info = parse_macro_input!(attr as ScalarInfo);
+    let name = &collation_info.name;
+    let register_fn_name = format_ident!("register_{}", fn_name);
+    let alias_check = if let Some(alias) = &collation_info.alias {
+        quote! {
+            let Ok(alias_c_name) = ::std::ffi::CString::new(#alias) else {
+                return ::turso_ext::ResultCode::Error;
+            };
+            let result = (api.register_collation)(api.ctx, alias_c_name.as_ptr(), #fn_name);
+            if !result.is_ok() {
+                return result;
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    let expanded = quote! {
+        #[no_mangle]
+        pub unsafe extern "C" fn #register_fn_name(
+            api: *const ::turso_ext::ExtensionApi
+        ) -> ::turso_ext::ResultCode {
+            if api.is_null() {
+                return ::turso_ext::ResultCode::Error;
+            }
+            let api = unsafe { &*api };
+            let Ok(c_name) = ::std::ffi::CString::new(#name) else {
+                return ::turso_ext::ResultCode::Error;
+            };
+            let result = (api.register_collation)(api.ctx, c_name.as_ptr(), #fn_name);
+            if !result.is_ok() {
+                return result;
+            }
+            #alias_check
+            ::turso_ext::ResultCode::OK
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn #fn_name(
+            lhs: *const u8,
+            lhs_len: usize,
+            rhs: *const u8,
+            rhs_len: usize,
+        ) -> i32 {
+            // Named after the collation, so it can't shadow the items the body refers to.
+            fn #fn_name(#inputs) #output #fn_body
+
+            let as_str = |ptr: *const u8, len: usize| -> &str {
+                if ptr.is_null() || len == 0 {
+                    return "";
+                }
+                let bytes = unsafe { ::std::slice::from_raw_parts(ptr, len) };
+                ::std::str::from_utf8(bytes).unwrap_or_default()
+            };
+            match #fn_name(as_str(lhs, lhs_len), as_str(rhs, rhs_len)) {
+                ::std::cmp::Ordering::Less => -1,
+                ::std::cmp::Ordering::Equal => 0,
+                ::std::cmp::Ordering::Greater => 1,
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
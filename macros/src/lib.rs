@@ -211,6 +211,21 @@ pub fn scalar(attr: TokenStream, input: TokenStream) -> TokenStream {
     ext::scalar(attr, input)
 }
 
+/// Declare a collating sequence for your extension, used with `COLLATE name`. The function
+/// compares two strings, and like scalars it takes a name and an optional alias.
+/// ```ignore
+/// use std::cmp::Ordering;
+/// use turso_ext::collation;
+/// #[collation(name = "reverse")]
+/// fn reverse(lhs: &str, rhs: &str) -> Ordering {
+///     rhs.cmp(lhs)
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn collation(attr: TokenStream, input: TokenStream) -> TokenStream {
+    ext::collation(attr, input)
+}
+
 /// Define an aggregate function for your extension by deriving
 /// AggregateDerive on a struct that implements the AggFunc trait.
 /// ```ignore
@@ -123,6 +123,13 @@ pub enum Stmt {
         /// partial index
         where_clause: Option<Box<Expr>>,
     },
+    /// `CREATE NAMESPACE`
+    CreateNamespace {
+        /// `IF NOT EXISTS`
+        if_not_exists: bool,
+        /// namespace name
+        name: Name,
+    },
     /// `CREATE TABLE`
     CreateTable {
         /// `TEMPORARY`
@@ -211,6 +218,13 @@ pub enum Stmt {
         /// index name
         idx_name: QualifiedName,
     },
+    /// `DROP NAMESPACE`
+    DropNamespace {
+        /// `IF EXISTS`
+        if_exists: bool,
+        /// namespace name
+        name: Name,
+    },
     /// `DROP TABLE`
     DropTable {
         /// `IF EXISTS`
@@ -280,6 +294,11 @@ pub enum Stmt {
     Select(Select),
     /// `UPDATE`
     Update(Update),
+    /// `USE`: namespace name
+    Use {
+        // namespace name
+        name: Name,
+    },
     /// `VACUUM`: database name, into expr
     Vacuum {
         // database name
@@ -1452,6 +1471,8 @@ pub type PragmaValue = Box<Expr>; // TODO
 pub enum PragmaName {
     /// Returns the application ID of the database file.
     ApplicationId,
+    /// Enable or disable automatic transient indexes for joins
+    AutomaticIndex,
     /// set the autovacuum mode
     AutoVacuum,
     /// set the busy_timeout (see https://www.sqlite.org/pragma.html#pragma_busy_timeout)
@@ -1460,6 +1481,8 @@ pub enum PragmaName {
     CacheSize,
     /// set the cache spill behavior
     CacheSpill,
+    /// make LIKE case sensitive
+    CaseSensitiveLike,
     /// encryption cipher algorithm name for encrypted databases
     #[strum(serialize = "cipher")]
     #[cfg_attr(feature = "serde", serde(rename = "cipher"))]
@@ -1474,6 +1497,12 @@ pub enum PragmaName {
     FreelistCount,
     /// Enable or disable foreign key constraint enforcement
     ForeignKeys,
+    /// Window in milliseconds within which commits share a single WAL fsync (0 = disabled)
+    GroupCommitWindow,
+    /// returns information about the columns of an index
+    IndexInfo,
+    /// lists the indexes of a table
+    IndexList,
     /// Run integrity check on the database file
     IntegrityCheck,
     /// `journal_mode` pragma
@@ -1512,6 +1541,10 @@ pub enum PragmaName {
     UnstableCaptureDataChangesConn,
     /// Returns the user version of the database file.
     UserVersion,
+    /// Enable per-instruction profiling, or list the time spent in each opcode
+    VdbeProfile,
+    /// Number of WAL frames after which a commit triggers an automatic checkpoint (0 = disabled)
+    WalAutocheckpoint,
     /// trigger a checkpoint to run on database(s) if WAL is enabled
     WalCheckpoint,
     /// Sets or queries the threshold (in bytes) at which MVCC triggers an automatic checkpoint.
@@ -340,6 +340,19 @@ impl ToTokens for Stmt {
                 }
                 Ok(())
             }
+            Self::CreateNamespace {
+                if_not_exists,
+                name,
+            } => {
+                s.append(TK_CREATE, None)?;
+                s.append(TK_ID, Some("NAMESPACE"))?;
+                if *if_not_exists {
+                    s.append(TK_IF, None)?;
+                    s.append(TK_NOT, None)?;
+                    s.append(TK_EXISTS, None)?;
+                }
+                name.to_tokens(s, context)
+            }
             Self::CreateTable {
                 temporary,
                 if_not_exists,
@@ -527,6 +540,15 @@ impl ToTokens for Stmt {
                 }
                 idx_name.to_tokens(s, context)
             }
+            Self::DropNamespace { if_exists, name } => {
+                s.append(TK_DROP, None)?;
+                s.append(TK_ID, Some("NAMESPACE"))?;
+                if *if_exists {
+                    s.append(TK_IF, None)?;
+                    s.append(TK_EXISTS, None)?;
+                }
+                name.to_tokens(s, context)
+            }
             Self::DropTable {
                 if_exists,
                 tbl_name,
@@ -684,6 +706,10 @@ impl ToTokens for Stmt {
                 }
                 Ok(())
             }
+            Self::Use { name } => {
+                s.append(TK_ID, Some("USE"))?;
+                name.to_tokens(s, context)
+            }
             Self::Vacuum { name, into } => {
                 s.append(TK_VACUUM, None)?;
                 if let Some(ref name) = name {
@@ -108,3 +108,111 @@ pub enum Error {
     #[error("Parse error: {0}")]
     ParseError(String),
 }
+
+impl Error {
+    /// Byte offset of the offending token in the SQL text, if the error refers to one.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Error::UnrecognizedToken { offset, .. }
+            | Error::UnterminatedLiteral { offset, .. }
+            | Error::UnterminatedBracket { offset, .. }
+            | Error::UnterminatedBlockComment { offset, .. }
+            | Error::BadVariableName { offset, .. }
+            | Error::BadNumber { offset, .. }
+            | Error::BadFractionalPart { offset, .. }
+            | Error::BadExponentPart { offset, .. }
+            | Error::ExpectedEqualsSign { offset, .. }
+            | Error::MalformedHexInteger { offset, .. }
+            | Error::ParseUnexpectedToken { offset, .. } => Some(*offset),
+            Error::ParseUnexpectedEOF | Error::Custom(_) | Error::ParseError(_) => None,
+        }
+    }
+
+    /// Text of the offending token, if the error refers to one.
+    pub fn token_text(&self) -> Option<&str> {
+        match self {
+            Error::UnrecognizedToken { token_text, .. }
+            | Error::UnterminatedLiteral { token_text, .. }
+            | Error::UnterminatedBracket { token_text, .. }
+            | Error::UnterminatedBlockComment { token_text, .. }
+            | Error::BadVariableName { token_text, .. }
+            | Error::BadNumber { token_text, .. }
+            | Error::BadFractionalPart { token_text, .. }
+            | Error::BadExponentPart { token_text, .. }
+            | Error::ExpectedEqualsSign { token_text, .. }
+            | Error::MalformedHexInteger { token_text, .. }
+            | Error::ParseUnexpectedToken { token_text, .. } => Some(token_text),
+            Error::ParseUnexpectedEOF | Error::Custom(_) | Error::ParseError(_) => None,
+        }
+    }
+
+    /// Formats the error together with the line and column it was found at in `sql`, followed
+    /// by that line of `sql` with the offending token underlined:
+    ///
+    /// ```text
+    /// unexpected token '(' at offset 13 (line 1, column 14)
+    /// CREATE TABLE (x)
+    ///              ^
+    /// ```
+    ///
+    /// An unexpected end of file points just past the end of `sql`. Errors that don't refer to
+    /// a position are formatted as is.
+    pub fn display_with_source(&self, sql: &str) -> String {
+        let (offset, token_len) = match (self, self.offset()) {
+            (_, Some(offset)) => (
+                offset,
+                self.token_text().map_or(1, |text| text.chars().count()),
+            ),
+            (Error::ParseUnexpectedEOF, None) => (sql.trim_end().len(), 1),
+            _ => return self.to_string(),
+        };
+        if offset > sql.len() || !sql.is_char_boundary(offset) {
+            return self.to_string();
+        }
+        let line_start = sql[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = sql[offset..].find('\n').map_or(sql.len(), |i| offset + i);
+        let line = sql[line_start..line_end].trim_end_matches('\r');
+        let line_number = sql[..line_start].matches('\n').count() + 1;
+        let column = sql[line_start..offset].chars().count() + 1;
+        let underline_len = token_len
+            .min(line.chars().count().saturating_sub(column - 1))
+            .max(1);
+        format!(
+            "{self} (line {line_number}, column {column})\n{line}\n{}{}",
+            " ".repeat(column - 1),
+            "^".repeat(underline_len)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::Parser;
+
+    fn parse_error(sql: &str) -> String {
+        let mut parser = Parser::new(sql.as_bytes());
+        loop {
+            match parser.next_cmd() {
+                Ok(Some(_)) => continue,
+                Ok(None) => panic!("expected {sql:?} to fail to parse"),
+                Err(err) => return err.display_with_source(sql),
+            }
+        }
+    }
+
+    #[test]
+    fn test_display_with_source() {
+        assert_eq!(
+            parse_error("CREATE TABLE (x)"),
+            "unexpected token '(' at offset 13 (line 1, column 14)\nCREATE TABLE (x)\n             ^"
+        );
+        assert_eq!(
+            parse_error("SELECT 1;\nSELECT 'abc"),
+            "non-terminated literal ''abc' at offset 17 (line 2, column 8)\nSELECT 'abc\n       ^^^^"
+        );
+        assert_eq!(
+            parse_error("SELECT * FROM"),
+            "unexpected end of file (line 1, column 14)\nSELECT * FROM\n             ^"
+        );
+    }
+}
@@ -585,7 +585,19 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Checks whether the next token is the given non-reserved keyword, which the lexer
+    /// returns as an identifier.
+    fn peek_id_keyword(&mut self, keyword: &[u8]) -> Result<bool> {
+        Ok(match self.peek()? {
+            Some(tok) => tok.token_type == TK_ID && tok.value.eq_ignore_ascii_case(keyword),
+            None => false,
+        })
+    }
+
     fn parse_stmt(&mut self) -> Result<Stmt> {
+        if self.peek_id_keyword(b"USE")? {
+            return self.parse_use();
+        }
         let tok = peek_expect!(
             self,
             TK_BEGIN,
@@ -876,6 +888,9 @@ impl<'a> Parser<'a> {
 
     fn parse_create_stmt(&mut self) -> Result<Stmt> {
         eat_assert!(self, TK_CREATE);
+        if self.peek_id_keyword(b"NAMESPACE")? {
+            return self.parse_create_namespace();
+        }
         let mut first_tok = peek_expect!(
             self,
             TK_TEMP,
@@ -905,6 +920,22 @@ impl<'a> Parser<'a> {
         }
     }
 
+    fn parse_create_namespace(&mut self) -> Result<Stmt> {
+        eat_assert!(self, TK_ID);
+        let if_not_exists = self.parse_if_not_exists()?;
+        let name = self.parse_nm()?;
+        Ok(Stmt::CreateNamespace {
+            if_not_exists,
+            name,
+        })
+    }
+
+    fn parse_use(&mut self) -> Result<Stmt> {
+        eat_assert!(self, TK_ID);
+        let name = self.parse_nm()?;
+        Ok(Stmt::Use { name })
+    }
+
     fn parse_with_stmt(&mut self) -> Result<Stmt> {
         let with = self.parse_with()?;
         debug_assert!(with.is_some());
@@ -3988,6 +4019,12 @@ impl<'a> Parser<'a> {
 
     fn parse_drop_stmt(&mut self) -> Result<Stmt> {
         eat_assert!(self, TK_DROP);
+        if self.peek_id_keyword(b"NAMESPACE")? {
+            eat_assert!(self, TK_ID);
+            let if_exists = self.parse_if_exists()?;
+            let name = self.parse_nm()?;
+            return Ok(Stmt::DropNamespace { if_exists, name });
+        }
         let tok = peek_expect!(self, TK_TABLE, TK_INDEX, TK_TRIGGER, TK_VIEW);
 
         match tok.token_type {
@@ -9148,6 +9185,41 @@ mod tests {
                     name: Box::new(Expr::Id(Name::exact("bar".to_owned()))),
                 })],
             ),
+            // parse namespaces
+            (
+                b"CREATE NAMESPACE foo".as_slice(),
+                vec![Cmd::Stmt(Stmt::CreateNamespace {
+                    if_not_exists: false,
+                    name: Name::exact("foo".to_owned()),
+                })],
+            ),
+            (
+                b"create namespace if not exists foo".as_slice(),
+                vec![Cmd::Stmt(Stmt::CreateNamespace {
+                    if_not_exists: true,
+                    name: Name::exact("foo".to_owned()),
+                })],
+            ),
+            (
+                b"DROP NAMESPACE foo".as_slice(),
+                vec![Cmd::Stmt(Stmt::DropNamespace {
+                    if_exists: false,
+                    name: Name::exact("foo".to_owned()),
+                })],
+            ),
+            (
+                b"DROP NAMESPACE IF EXISTS foo".as_slice(),
+                vec![Cmd::Stmt(Stmt::DropNamespace {
+                    if_exists: true,
+                    name: Name::exact("foo".to_owned()),
+                })],
+            ),
+            (
+                b"USE foo".as_slice(),
+                vec![Cmd::Stmt(Stmt::Use {
+                    name: Name::exact("foo".to_owned()),
+                })],
+            ),
             // parse pragma
             (
                 b"PRAGMA foreign_keys = ON".as_slice(),
@@ -225,6 +225,10 @@ unsafe extern "C" {
     #[doc = " Get autocommit state of the connection"]
     pub fn turso_connection_get_autocommit(self_: *const turso_connection_t) -> bool;
 }
+unsafe extern "C" {
+    #[doc = " Interrupt statements currently executing on the connection; they will fail with TURSO_INTERRUPT\n This method is safe to call from another thread while the connection is in use"]
+    pub fn turso_connection_interrupt(self_: *const turso_connection_t);
+}
 unsafe extern "C" {
     #[doc = " Get last insert rowid for the connection or 0 if no inserts happened before"]
     pub fn turso_connection_last_insert_rowid(self_: *const turso_connection_t) -> i64;
@@ -119,6 +119,14 @@ pub extern "C" fn turso_connection_get_autocommit(
     }
 }
 
+#[no_mangle]
+#[signature(c)]
+pub extern "C" fn turso_connection_interrupt(connection: *const c::turso_connection_t) {
+    if let Ok(connection) = unsafe { TursoConnection::ref_from_capi(connection) } {
+        connection.interrupt();
+    }
+}
+
 #[no_mangle]
 #[signature(c)]
 pub extern "C" fn turso_connection_last_insert_rowid(
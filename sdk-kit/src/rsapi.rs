@@ -28,6 +28,7 @@ assert_send!(TursoDatabase, TursoConnection, TursoStatement);
 assert_sync!(TursoDatabase);
 
 pub use turso_core::types::FromValue;
+pub use turso_core::CArray;
 pub type EncryptionOpts = turso_core::EncryptionOpts;
 pub type Value = turso_core::Value;
 pub type ValueRef<'a> = turso_core::types::ValueRef<'a>;
@@ -377,7 +378,7 @@ pub fn c_string_to_str(ptr: *const std::ffi::c_char) -> std::ffi::CString {
 impl From<LimboError> for TursoError {
     fn from(value: LimboError) -> Self {
         match value {
-            LimboError::Constraint(e) => TursoError::Constraint(e),
+            LimboError::Constraint { message, .. } => TursoError::Constraint(message),
             LimboError::Corrupt(e) => TursoError::Corrupt(e),
             LimboError::NotADB => TursoError::NotAdb("file is not a database".to_string()),
             LimboError::DatabaseFull(e) => TursoError::DatabaseFull(e),
@@ -648,6 +649,10 @@ impl TursoConnection {
     pub fn get_auto_commit(&self) -> bool {
         self.connection.get_auto_commit()
     }
+    /// interrupt statements which are currently executing on the connection (can be called from any thread)
+    pub fn interrupt(&self) {
+        self.connection.interrupt();
+    }
     pub fn last_insert_rowid(&self) -> i64 {
         self.connection.last_insert_rowid()
     }
@@ -882,6 +887,20 @@ impl TursoStatement {
         }
         Ok(self.statement.get_column_name(index))
     }
+    /// returns declared type of the column or None if column is an expression or has no declared type
+    pub fn column_decltype(&self, index: usize) -> Result<Option<Cow<'_, str>>, TursoError> {
+        if index >= self.column_count() {
+            return Err(TursoError::Misuse("column index out of bounds".to_string()));
+        }
+        Ok(self.statement.get_column_decl_type(index))
+    }
+    /// returns name of the table the column originates from or None if column is an expression
+    pub fn column_table_name(&self, index: usize) -> Result<Option<Cow<'_, str>>, TursoError> {
+        if index >= self.column_count() {
+            return Err(TursoError::Misuse("column index out of bounds".to_string()));
+        }
+        Ok(self.statement.get_column_table_name(index))
+    }
     /// finalize statement execution
     /// this method must be called in the end of statement execution (either successfull or not)
     pub fn finalize(&mut self, waker: Option<&Waker>) -> Result<TursoStatusCode, TursoError> {
@@ -158,6 +158,11 @@ void turso_connection_set_busy_timeout_ms(const turso_connection_t *self, int64_
 /** Get autocommit state of the connection */
 bool turso_connection_get_autocommit(const turso_connection_t *self);
 
+/** Interrupt statements currently executing on the connection; they will fail with TURSO_INTERRUPT
+ * This method is safe to call from another thread while the connection is in use
+ */
+void turso_connection_interrupt(const turso_connection_t *self);
+
 /** Get last insert rowid for the connection or 0 if no inserts happened before */
 int64_t turso_connection_last_insert_rowid(const turso_connection_t *self);
 
@@ -24,6 +24,7 @@ use crate::{
         metrics::{InteractionStats, Remaining},
         property::Property,
     },
+    runner::env::SimulationType,
 };
 
 impl InteractionPlan {
@@ -329,8 +330,17 @@ fn random_fault<R: rand::Rng + ?Sized>(
 ) -> Interactions {
     let faults = if env.opts.disable_reopen_database {
         vec![Fault::Disconnect]
-    } else {
+    } else if matches!(env.type_, SimulationType::Differential) {
+        // SQLite reads the files directly, not through the simulated IO that tears the WAL.
         vec![Fault::Disconnect, Fault::ReopenDatabase]
+    } else {
+        vec![
+            Fault::Disconnect,
+            Fault::ReopenDatabase,
+            Fault::TornWalWrite {
+                percent: rng.random_range(1..=100),
+            },
+        ]
     };
     let fault = faults[rng.random_range(0..faults.len())];
     Interactions::new(conn_index, InteractionsType::Fault(fault))
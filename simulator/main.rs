@@ -38,6 +38,10 @@ fn main() -> anyhow::Result<()> {
     let mut cli_opts = SimulatorCLI::parse();
     cli_opts.validate()?;
 
+    if let Some(trace) = &cli_opts.inspect {
+        return runner::inspect::run(trace);
+    }
+
     let profile = Profile::parse_from_type(cli_opts.profile.clone())?;
     tracing::debug!(sim_profile = ?profile);
 
@@ -12,7 +12,10 @@ use indexmap::IndexSet;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use sql_generation::model::table::SimValue;
-use turso_core::{Connection, Result, StepResult};
+use turso_core::{
+    Connection, Result, StepResult,
+    storage::sqlite3_ondisk::{WAL_FRAME_HEADER_SIZE, WAL_HEADER_SIZE},
+};
 
 use crate::{
     generation::Shadow,
@@ -21,7 +24,9 @@ use crate::{
         metrics::InteractionStats,
         property::{Property, PropertyDiscriminants},
     },
-    runner::env::{ShadowTablesMut, SimConnection, SimulationType, SimulatorEnv},
+    runner::env::{
+        ShadowTablesMut, SimConnection, SimulationType, SimulatorEnv, open_sqlite_connection,
+    },
 };
 
 #[derive(Debug, Clone)]
@@ -428,6 +433,13 @@ impl Assertion {
 pub enum Fault {
     Disconnect,
     ReopenDatabase,
+    /// Simulates a crash in the middle of a write to the WAL: the first `percent` percent of the
+    /// last frame of the WAL are written again after it, and the database is reopened. Recovery
+    /// must discard the torn frame, even a complete one, as its checksum doesn't follow from the
+    /// frame before it.
+    TornWalWrite {
+        percent: u8,
+    },
 }
 
 impl Display for Fault {
@@ -435,6 +447,7 @@ impl Display for Fault {
         match self {
             Fault::Disconnect => write!(f, "DISCONNECT"),
             Fault::ReopenDatabase => write!(f, "REOPEN_DATABASE"),
+            Fault::TornWalWrite { percent } => write!(f, "TORN_WAL_WRITE {percent}"),
         }
     }
 }
@@ -698,6 +711,10 @@ impl InteractionType {
                     Fault::ReopenDatabase => {
                         reopen_database(env);
                     }
+                    Fault::TornWalWrite { percent } => {
+                        tear_wal(env, *percent)?;
+                        reopen_database(env);
+                    }
                 }
                 Ok(())
             }
@@ -827,6 +844,51 @@ impl InteractionType {
     }
 }
 
+/// Appends the first `percent` percent of the last frame of the WAL to the WAL, as a crash while
+/// writing the next frame would leave it.
+fn tear_wal(env: &SimulatorEnv, percent: u8) -> Result<()> {
+    let wal_path = env.get_db_path().with_extension("db-wal");
+    let file = env.io.open_file(
+        wal_path.to_str().expect("path should be 'to_str'"),
+        turso_core::OpenFlags::None,
+        false,
+    )?;
+    let read = |pos: usize, len: usize| -> Result<Vec<u8>> {
+        let buf = Arc::new(turso_core::Buffer::new_temporary(len));
+        let c = file.pread(
+            pos as u64,
+            turso_core::Completion::new_read(buf.clone(), |_| {}),
+        )?;
+        env.io.wait_for_completion(c)?;
+        Ok(buf.as_slice().to_vec())
+    };
+
+    let size = file.size()? as usize;
+    if size < WAL_HEADER_SIZE {
+        return Ok(());
+    }
+    let header = read(0, WAL_HEADER_SIZE)?;
+    let page_size = u32::from_be_bytes(header[8..12].try_into().unwrap()) as usize;
+    let frame_size = WAL_FRAME_HEADER_SIZE + page_size;
+    if page_size == 0 || size < WAL_HEADER_SIZE + frame_size {
+        // There is no frame to tear.
+        return Ok(());
+    }
+    let end = size - (size - WAL_HEADER_SIZE) % frame_size;
+    let mut frame = read(end - frame_size, frame_size)?;
+    frame.truncate((frame_size * percent as usize / 100).max(1));
+    tracing::info!(
+        "appending {} bytes of a torn frame to the WAL at offset {end}",
+        frame.len()
+    );
+    let c = file.pwrite(
+        end as u64,
+        Arc::new(turso_core::Buffer::new(frame)),
+        turso_core::Completion::new_write(|_| {}),
+    )?;
+    env.io.wait_for_completion(c)
+}
+
 fn reopen_database(env: &mut SimulatorEnv) {
     // 1. Close all connections without default checkpoint-on-close behavior
     // to expose bugs related to how we handle WAL
@@ -851,10 +913,11 @@ fn reopen_database(env: &mut SimulatorEnv) {
     match env.type_ {
         SimulationType::Differential => {
             for _ in 0..num_conns {
-                env.connections.push(SimConnection::SQLiteConnection(
-                    rusqlite::Connection::open(env.get_db_path())
-                        .expect("Failed to open SQLite connection"),
-                ));
+                env.connections
+                    .push(SimConnection::SQLiteConnection(open_sqlite_connection(
+                        &env.get_db_path(),
+                        env.opts.page_size,
+                    )));
             }
         }
         SimulationType::Default | SimulationType::Doublecheck => {
@@ -863,7 +926,9 @@ fn reopen_database(env: &mut SimulatorEnv) {
                 env.io.clone(),
                 env.get_db_path().to_str().expect("path should be 'to_str'"),
                 turso_core::OpenFlags::default(),
-                turso_core::DatabaseOpts::new().with_autovacuum(true),
+                turso_core::DatabaseOpts::new()
+                    .with_autovacuum(true)
+                    .with_page_size(env.opts.page_size as u32),
                 None,
             ) {
                 Ok(db) => db,
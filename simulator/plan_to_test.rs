@@ -43,6 +43,7 @@ enum PlanAction {
     Sql(SqlStatement),
     ReopenDatabase,
     Disconnect { connection: u32 },
+    TornWalWrite { percent: u8 },
 }
 
 fn parse_plan(content: &str) -> (Vec<PlanAction>, BTreeSet<u32>) {
@@ -77,6 +78,10 @@ fn parse_plan(content: &str) -> (Vec<PlanAction>, BTreeSet<u32>) {
                         connection: conn_num,
                     });
                 }
+                _ if fault_type.starts_with("TORN_WAL_WRITE ") => {
+                    let percent = fault_type["TORN_WAL_WRITE ".len()..].parse().unwrap();
+                    actions.push(PlanAction::TornWalWrite { percent });
+                }
                 _ => {
                     // Unknown fault type, skip
                 }
@@ -189,6 +194,45 @@ fn generate_test(content: &str, sql_path: &str) -> String {
                     "    drop(std::mem::replace(&mut connections[{connection}], tmp_db.connect_limbo()));\n\n"
                 ));
             }
+            PlanAction::TornWalWrite { percent } => {
+                code.push_str(&format!(
+                    "    // TORN_WAL_WRITE {percent} - write {percent}% of the last WAL frame again after it, then reopen database\n"
+                ));
+                code.push_str("    drop(connections);\n");
+                code.push_str("    {\n");
+                code.push_str(
+                    "        let wal_path = format!(\"{}-wal\", tmp_db.path.display());\n",
+                );
+                code.push_str(
+                    "        let mut wal = std::fs::read(&wal_path).unwrap_or_default();\n",
+                );
+                code.push_str("        if wal.len() >= 32 {\n");
+                code.push_str(
+                    "            let page_size = u32::from_be_bytes(wal[8..12].try_into().unwrap()) as usize;\n",
+                );
+                code.push_str("            let frame_size = 24 + page_size;\n");
+                code.push_str("            if page_size > 0 && wal.len() >= 32 + frame_size {\n");
+                code.push_str(
+                    "                let end = wal.len() - (wal.len() - 32) % frame_size;\n",
+                );
+                code.push_str(&format!(
+                    "                let torn_len = (frame_size * {percent} / 100).max(1);\n"
+                ));
+                code.push_str(
+                    "                let torn = wal[end - frame_size..end - frame_size + torn_len].to_vec();\n",
+                );
+                code.push_str("                wal.resize(wal.len().max(end + torn_len), 0);\n");
+                code.push_str("                wal[end..end + torn_len].copy_from_slice(&torn);\n");
+                code.push_str("                std::fs::write(&wal_path, wal).unwrap();\n");
+                code.push_str("            }\n");
+                code.push_str("        }\n");
+                code.push_str("    }\n");
+                code.push_str("    let tmp_db = TempDatabase::new_with_existent(&tmp_db.path);\n");
+                code.push_str("    let mut connections: Vec<Arc<Connection>> = Vec::new();\n");
+                code.push_str(&format!("    for _ in 0..{num_connections} {{\n"));
+                code.push_str("        connections.push(tmp_db.connect_limbo());\n");
+                code.push_str("    }\n\n");
+            }
         }
     }
 
@@ -5,6 +5,7 @@ use clap::{
     error::{ContextKind, ContextValue, ErrorKind},
 };
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 use crate::profiles::ProfileType;
 
@@ -153,6 +154,12 @@ pub struct SimulatorCLI {
     pub min_tick: Option<u64>,
     #[clap(long, help = "Maximum tick time in microseconds for simulated time")]
     pub max_tick: Option<u64>,
+    #[clap(
+        long,
+        help = "page size of the simulated database, picked at random when not set",
+        value_parser = parse_page_size
+    )]
+    pub page_size: Option<usize>,
     #[clap(long, help = "Enable experimental MVCC feature")]
     pub experimental_mvcc: Option<bool>,
     #[clap(
@@ -177,6 +184,17 @@ pub struct SimulatorCLI {
     #[clap(long, default_value_t = ProfileType::Default)]
     /// Profile selector for Simulation run
     pub profile: ProfileType,
+    #[clap(
+        long,
+        help = "record every interaction and the page writes it caused into a trace file",
+        default_value_t = false
+    )]
+    pub record_trace: bool,
+    #[clap(
+        long,
+        help = "step through a trace recorded with --record-trace instead of running a simulation"
+    )]
+    pub inspect: Option<PathBuf>,
 }
 
 #[derive(Parser, Debug, Clone, Serialize, Deserialize, PartialEq, PartialOrd, Eq, Ord)]
@@ -218,6 +236,9 @@ impl SimulatorCLI {
         if self.watch {
             anyhow::bail!("watch mode is disabled for now");
         }
+        if self.inspect.is_some() && self.record_trace {
+            anyhow::bail!("--inspect cannot be combined with --record-trace");
+        }
         if self.minimum_tests > self.maximum_tests {
             tracing::warn!(
                 "minimum size '{}' is greater than '{}' maximum size, setting both to '{}'",
@@ -231,6 +252,14 @@ impl SimulatorCLI {
     }
 }
 
+fn parse_page_size(value: &str) -> Result<usize, String> {
+    let page_size: usize = value.parse().map_err(|e| format!("{e}"))?;
+    if !(512..=65536).contains(&page_size) || !page_size.is_power_of_two() {
+        return Err("page size must be a power of two between 512 and 65536".to_string());
+    }
+    Ok(page_size)
+}
+
 #[derive(Clone)]
 pub struct ProfileTypeParser;
 
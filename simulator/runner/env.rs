@@ -24,6 +24,7 @@ use crate::runner::cli::IoBackend;
 use crate::runner::io::SimulatorIO;
 use crate::runner::memory::io::MemorySimIO;
 const DEFAULT_CACHE_SIZE: usize = 2000;
+const PAGE_SIZES: [usize; 8] = [512, 1024, 2048, 4096, 8192, 16384, 32768, 65536];
 use super::cli::SimulatorCLI;
 
 #[derive(Debug, Copy, Clone)]
@@ -744,7 +745,9 @@ impl SimulatorEnv {
             io.clone(),
             db_path.to_str().unwrap(),
             turso_core::OpenFlags::default(),
-            turso_core::DatabaseOpts::new().with_autovacuum(true),
+            turso_core::DatabaseOpts::new()
+                .with_autovacuum(true)
+                .with_page_size(self.opts.page_size as u32),
             None,
         ) {
             Ok(db) => db,
@@ -765,6 +768,10 @@ impl SimulatorEnv {
         self.paths.plan(&self.type_, &self.phase)
     }
 
+    pub(crate) fn get_trace_path(&self) -> PathBuf {
+        self.paths.trace(&self.type_, &self.phase)
+    }
+
     pub(crate) fn clone_as(&self, simulation_type: SimulationType) -> Self {
         let mut env = self.clone_without_connections();
         env.type_ = simulation_type;
@@ -816,11 +823,14 @@ impl SimulatorEnv {
                 .disable_union_all_preserves_cardinality,
             disable_fsync_no_wait: cli_opts.disable_fsync_no_wait,
             disable_faulty_query: cli_opts.disable_faulty_query,
-            page_size: 4096, // TODO: randomize this too
+            page_size: cli_opts
+                .page_size
+                .unwrap_or_else(|| PAGE_SIZES[rng.random_range(0..PAGE_SIZES.len())]),
             max_interactions: rng.random_range(cli_opts.minimum_tests..=cli_opts.maximum_tests),
             max_time_simulation: cli_opts.maximum_time,
             disable_reopen_database: cli_opts.disable_reopen_database,
             disable_integrity_check: cli_opts.disable_integrity_check,
+            record_trace: cli_opts.record_trace,
             cache_size: profile.cache_size_pages.unwrap_or(DEFAULT_CACHE_SIZE),
         };
 
@@ -890,7 +900,9 @@ impl SimulatorEnv {
             io.clone(),
             db_path.to_str().unwrap(),
             turso_core::OpenFlags::default(),
-            turso_core::DatabaseOpts::new().with_autovacuum(true),
+            turso_core::DatabaseOpts::new()
+                .with_autovacuum(true)
+                .with_page_size(opts.page_size as u32),
             None,
         ) {
             Ok(db) => db,
@@ -956,8 +968,7 @@ impl SimulatorEnv {
             }
             SimulationType::Differential => {
                 self.connections[connection_index] = SimConnection::SQLiteConnection(
-                    rusqlite::Connection::open(self.get_db_path())
-                        .expect("Failed to open SQLite connection"),
+                    open_sqlite_connection(&self.get_db_path(), self.opts.page_size),
                 );
             }
         };
@@ -1081,6 +1092,16 @@ impl Display for SimConnection {
     }
 }
 
+/// Opens a SQLite connection to the database at `path`. The page size only takes effect if the
+/// database is still empty, like the page size of the Limbo database in
+/// [turso_core::DatabaseOpts::with_page_size].
+pub(crate) fn open_sqlite_connection(path: &Path, page_size: usize) -> rusqlite::Connection {
+    let conn = rusqlite::Connection::open(path).expect("Failed to open SQLite connection");
+    conn.pragma_update(None, "page_size", page_size)
+        .expect("Failed to set SQLite page size");
+    conn
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct SimulatorOpts {
     pub(crate) seed: u64,
@@ -1098,6 +1119,7 @@ pub(crate) struct SimulatorOpts {
     pub(crate) disable_faulty_query: bool,
     pub(crate) disable_reopen_database: bool,
     pub(crate) disable_integrity_check: bool,
+    pub(crate) record_trace: bool,
 
     pub(crate) max_interactions: u32,
     pub(crate) page_size: usize,
@@ -1146,6 +1168,9 @@ impl Paths {
     pub(crate) fn plan(&self, type_: &SimulationType, phase: &SimulationPhase) -> PathBuf {
         self.path_(type_, phase).with_extension("sql")
     }
+    pub(crate) fn trace(&self, type_: &SimulationType, phase: &SimulationPhase) -> PathBuf {
+        self.path_(type_, phase).with_extension("trace")
+    }
 
     pub fn delete_all_files(&self) {
         if self.base.exists() {
@@ -30,6 +30,7 @@ use crate::{
 };
 
 use super::env::{SimConnection, SimulatorEnv};
+use super::trace::TraceWriter;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Execution {
@@ -85,6 +86,11 @@ pub(crate) fn execute_interactions(
 
     env.clear_tables();
 
+    let mut trace = env.opts.record_trace.then(|| {
+        env.io.write_log().enable();
+        TraceWriter::create(&env.get_trace_path()).expect("failed to create trace file")
+    });
+
     let mut interaction = plan
         .next(&mut env)
         .expect("we should always have at least 1 interaction to start");
@@ -102,7 +108,19 @@ pub(crate) fn execute_interactions(
         last_execution.connection_index = connection_index;
         last_execution.interaction_index = state.interaction_pointer;
         // Execute the interaction for the selected connection
-        match execute_plan(&mut env, &interaction, conn_state) {
+        let result = execute_plan(&mut env, &interaction, conn_state);
+        if let Some(trace) = trace.as_mut() {
+            trace
+                .record(
+                    connection_index,
+                    state.interaction_pointer,
+                    interaction.to_string(),
+                    env.io.write_log().take(),
+                    result.as_ref().err().map(|err| err.to_string()),
+                )
+                .expect("failed to write trace step");
+        }
+        match result {
             Ok(ExecutionContinuation::NextInteraction) => {
                 state.interaction_pointer += 1;
                 let Some(new_interaction) = plan.next(&mut env) else {
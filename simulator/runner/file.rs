@@ -9,7 +9,7 @@ use rand_chacha::ChaCha8Rng;
 use tracing::{Level, instrument};
 use turso_core::{File, Result};
 
-use crate::runner::{FAULT_ERROR_MSG, clock::SimulatorClock};
+use crate::runner::{FAULT_ERROR_MSG, clock::SimulatorClock, trace::WriteLog};
 pub(crate) struct SimulatorFile {
     pub path: String,
     pub(crate) inner: Arc<dyn File>,
@@ -42,6 +42,7 @@ pub(crate) struct SimulatorFile {
     pub sync_completion: RefCell<Option<turso_core::Completion>>,
     pub queued_io: RefCell<Vec<DelayedIo>>,
     pub clock: Arc<SimulatorClock>,
+    pub(crate) write_log: WriteLog,
 }
 
 type IoOperation = Box<dyn FnOnce(&SimulatorFile) -> Result<turso_core::Completion>>;
@@ -108,6 +109,14 @@ impl SimulatorFile {
             })
     }
 
+    fn record_write(&self, pos: u64, buffers: &[Arc<turso_core::Buffer>]) {
+        let mut pos = pos;
+        for buffer in buffers {
+            self.write_log.record(&self.path, pos, buffer.as_slice());
+            pos += buffer.len() as u64;
+        }
+    }
+
     #[instrument(skip_all, level = Level::DEBUG)]
     pub fn run_queued_io(&self, now: turso_core::Instant) -> Result<()> {
         let mut queued_io = self.queued_io.borrow_mut();
@@ -174,12 +183,16 @@ impl File for SimulatorFile {
         }
         if let Some(latency) = self.generate_latency_duration() {
             let cloned_c = c.clone();
-            let op = Box::new(move |file: &SimulatorFile| file.inner.pwrite(pos, buffer, cloned_c));
+            let op = Box::new(move |file: &SimulatorFile| {
+                file.record_write(pos, std::slice::from_ref(&buffer));
+                file.inner.pwrite(pos, buffer, cloned_c)
+            });
             self.queued_io
                 .borrow_mut()
                 .push(DelayedIo { time: latency, op });
             Ok(c)
         } else {
+            self.record_write(pos, std::slice::from_ref(&buffer));
             self.inner.pwrite(pos, buffer, c)
         }
     }
@@ -228,13 +241,16 @@ impl File for SimulatorFile {
         }
         if let Some(latency) = self.generate_latency_duration() {
             let cloned_c = c.clone();
-            let op =
-                Box::new(move |file: &SimulatorFile| file.inner.pwritev(pos, buffers, cloned_c));
+            let op = Box::new(move |file: &SimulatorFile| {
+                file.record_write(pos, &buffers);
+                file.inner.pwritev(pos, buffers, cloned_c)
+            });
             self.queued_io
                 .borrow_mut()
                 .push(DelayedIo { time: latency, op });
             Ok(c)
         } else {
+            self.record_write(pos, &buffers);
             let c = self.inner.pwritev(pos, buffers, c)?;
             Ok(c)
         }
@@ -0,0 +1,472 @@
+//! Interactive time-travel debugger over a trace recorded with `--record-trace`.
+//!
+//! The inspector rebuilds the content of every file touched by the simulation up to the
+//! selected step by replaying the recorded writes, overlays the committed WAL frames on top
+//! of the database file and prints a summary of the b-tree pages.
+
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, Write},
+    path::Path,
+};
+
+use indexmap::IndexMap;
+
+use crate::runner::trace::{TraceStep, load_trace};
+
+const WAL_HEADER_SIZE: usize = 32;
+const WAL_FRAME_HEADER_SIZE: usize = 24;
+const DATABASE_HEADER_SIZE: usize = 100;
+
+const HELP: &str = "\
+commands:
+  n, next [N]   step forward N interactions (default 1)
+  p, prev [N]   step backward N interactions (default 1)
+  g, goto N     jump to step N
+  e, error      jump to the first step that returned an error
+  w, writes     list the writes performed by the current step
+  b, btree      dump the b-tree pages as of the current step
+  h, help       print this message
+  q, quit       exit the inspector";
+
+pub(crate) fn run(trace_path: &Path) -> anyhow::Result<()> {
+    let steps = load_trace(trace_path)?;
+    if steps.is_empty() {
+        anyhow::bail!("trace '{}' has no steps", trace_path.display());
+    }
+    let mut inspector = Inspector::new(steps);
+    println!(
+        "loaded {} steps from {}",
+        inspector.steps.len(),
+        trace_path.display()
+    );
+    println!("{HELP}");
+    inspector.print_step();
+
+    let stdin = std::io::stdin();
+    let mut line = String::new();
+    loop {
+        print!("inspect[{}]> ", inspector.current);
+        std::io::stdout().flush()?;
+        line.clear();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        let mut parts = line.split_whitespace();
+        let Some(command) = parts.next() else {
+            continue;
+        };
+        let arg = parts.next().map(str::parse::<usize>).transpose();
+        let Ok(arg) = arg else {
+            println!("invalid argument, expected a number");
+            continue;
+        };
+        match command {
+            "n" | "next" => inspector.goto(inspector.current.saturating_add(arg.unwrap_or(1))),
+            "p" | "prev" => inspector.goto(inspector.current.saturating_sub(arg.unwrap_or(1))),
+            "g" | "goto" => match arg {
+                Some(step) => inspector.goto(step),
+                None => println!("goto requires a step number"),
+            },
+            "e" | "error" => match inspector.steps.iter().position(|s| s.error.is_some()) {
+                Some(step) => inspector.goto(step),
+                None => println!("no step in the trace returned an error"),
+            },
+            "w" | "writes" => inspector.print_writes(),
+            "b" | "btree" => inspector.print_btree()?,
+            "h" | "help" => println!("{HELP}"),
+            "q" | "quit" => return Ok(()),
+            _ => println!("unknown command '{command}', type 'h' for help"),
+        }
+    }
+}
+
+struct Inspector {
+    steps: Vec<TraceStep>,
+    current: usize,
+}
+
+impl Inspector {
+    fn new(steps: Vec<TraceStep>) -> Self {
+        Self { steps, current: 0 }
+    }
+
+    fn goto(&mut self, step: usize) {
+        self.current = step.min(self.steps.len() - 1);
+        self.print_step();
+    }
+
+    fn print_step(&self) {
+        let step = &self.steps[self.current];
+        println!(
+            "step {}/{} (connection {}, interaction {}): {}",
+            step.step,
+            self.steps.len() - 1,
+            step.connection_index,
+            step.interaction_index,
+            step.interaction.trim_end()
+        );
+        println!("  {} writes", step.writes.len());
+        if let Some(error) = &step.error {
+            println!("  error: {error}");
+        }
+    }
+
+    fn print_writes(&self) {
+        for write in &self.steps[self.current].writes {
+            println!(
+                "  {} offset={} len={}",
+                write.path,
+                write.offset,
+                write.data.len() / 2
+            );
+        }
+    }
+
+    /// Rebuilds the content of every file by replaying the writes up to and including the
+    /// current step.
+    fn files(&self) -> anyhow::Result<IndexMap<String, Vec<u8>>> {
+        let mut files: IndexMap<String, Vec<u8>> = IndexMap::new();
+        for step in &self.steps[..=self.current] {
+            for write in &step.writes {
+                let data = write.bytes()?;
+                let file = files.entry(write.path.clone()).or_default();
+                let offset = write.offset as usize;
+                if file.len() < offset + data.len() {
+                    file.resize(offset + data.len(), 0);
+                }
+                file[offset..offset + data.len()].copy_from_slice(&data);
+            }
+        }
+        Ok(files)
+    }
+
+    fn print_btree(&self) -> anyhow::Result<()> {
+        let files = self.files()?;
+        let Some((db_path, db, wal)) = database_files(&files) else {
+            println!("no database writes recorded up to this step");
+            return Ok(());
+        };
+        let Some(pages) = database_pages(db, wal) else {
+            println!("database header has not been written yet");
+            return Ok(());
+        };
+        println!("{db_path}: {} pages", pages.len());
+        for (page_no, page) in pages.iter() {
+            println!("  {}", describe_page(*page_no, page));
+        }
+        Ok(())
+    }
+}
+
+/// Finds the database file and its WAL among the written files. Before the first checkpoint
+/// only the WAL may have been written, in which case the database is empty.
+fn database_files(files: &IndexMap<String, Vec<u8>>) -> Option<(String, &[u8], Option<&[u8]>)> {
+    let db = files.iter().find(|(path, _)| path.ends_with(".db"));
+    let wal = files.iter().find(|(path, _)| path.ends_with(".db-wal"));
+    let db_path = match (db, wal) {
+        (Some((db_path, _)), _) => db_path.clone(),
+        (None, Some((wal_path, _))) => wal_path.trim_end_matches("-wal").to_string(),
+        (None, None) => return None,
+    };
+    let db = db.map_or(&[][..], |(_, db)| db.as_slice());
+    let wal = files.get(&format!("{db_path}-wal")).map(Vec::as_slice);
+    Some((db_path, db, wal))
+}
+
+/// Splits the database file into pages and applies the committed WAL frames on top of them.
+fn database_pages(db: &[u8], wal: Option<&[u8]>) -> Option<BTreeMap<u32, Vec<u8>>> {
+    let wal_page_size = wal
+        .filter(|wal| wal.len() >= WAL_HEADER_SIZE)
+        .map(|wal| read_u32(wal, 8) as usize);
+    let page_size = if db.len() >= DATABASE_HEADER_SIZE {
+        match read_u16(db, 16) {
+            1 => 65536,
+            size => size as usize,
+        }
+    } else {
+        wal_page_size?
+    };
+    if page_size == 0 {
+        return None;
+    }
+
+    let mut pages = BTreeMap::new();
+    for (i, page) in db.chunks(page_size).enumerate() {
+        pages.insert(i as u32 + 1, page.to_vec());
+    }
+
+    let Some(wal) = wal.filter(|_| wal_page_size == Some(page_size)) else {
+        return Some(pages);
+    };
+    let salt = &wal[16..24];
+    let frame_size = WAL_FRAME_HEADER_SIZE + page_size;
+    let mut pending = Vec::new();
+    for frame in wal[WAL_HEADER_SIZE..].chunks_exact(frame_size) {
+        // Frames left over from a previous WAL generation carry a stale salt.
+        if frame[8..16] != *salt {
+            break;
+        }
+        pending.push((read_u32(frame, 0), &frame[WAL_FRAME_HEADER_SIZE..]));
+        // A non-zero database size marks the commit frame of a transaction.
+        if read_u32(frame, 4) != 0 {
+            for (page_no, data) in pending.drain(..) {
+                pages.insert(page_no, data.to_vec());
+            }
+        }
+    }
+    Some(pages)
+}
+
+fn describe_page(page_no: u32, page: &[u8]) -> String {
+    let offset = if page_no == 1 {
+        DATABASE_HEADER_SIZE
+    } else {
+        0
+    };
+    if page.len() < offset + 8 {
+        return format!("page {page_no}: truncated ({} bytes)", page.len());
+    }
+    let (kind, interior) = match page[offset] {
+        0x02 => ("interior index", true),
+        0x05 => ("interior table", true),
+        0x0a => ("leaf index", false),
+        0x0d => ("leaf table", false),
+        _ => return format!("page {page_no}: not a b-tree page (overflow, freelist or ptrmap)"),
+    };
+    let cell_pointers = offset + if interior { 12 } else { 8 };
+    if page.len() < cell_pointers {
+        return format!("page {page_no}: truncated ({} bytes)", page.len());
+    }
+    let cell_count = read_u16(page, offset + 3) as usize;
+    let cells = (0..cell_count)
+        .filter_map(|i| {
+            let pos = cell_pointers + i * 2;
+            (pos + 2 <= page.len()).then(|| read_u16(page, pos) as usize)
+        })
+        .filter(|&cell| cell < page.len())
+        .collect::<Vec<_>>();
+
+    let mut description = format!("page {page_no}: {kind}, {cell_count} cells");
+    match page[offset] {
+        0x05 => {
+            let children = cells
+                .iter()
+                .filter(|&&cell| cell + 4 <= page.len())
+                .map(|&cell| {
+                    let key = read_varint(&page[cell + 4..]).map(|(key, _)| key as i64);
+                    format!("{}<={}", read_u32(page, cell), fmt_key(key))
+                })
+                .collect::<Vec<_>>();
+            description.push_str(&format!(
+                ", children [{}], right child {}",
+                children.join(" "),
+                read_u32(page, offset + 8)
+            ));
+        }
+        0x02 => {
+            let children = cells
+                .iter()
+                .filter(|&&cell| cell + 4 <= page.len())
+                .map(|&cell| read_u32(page, cell).to_string())
+                .collect::<Vec<_>>();
+            description.push_str(&format!(
+                ", children [{}], right child {}",
+                children.join(" "),
+                read_u32(page, offset + 8)
+            ));
+        }
+        0x0d => {
+            let rowid = |cell: usize| {
+                let (_, consumed) = read_varint(&page[cell..])?;
+                let (rowid, _) = read_varint(&page[cell + consumed..])?;
+                Some(rowid as i64)
+            };
+            if let (Some(first), Some(last)) = (cells.first(), cells.last()) {
+                description.push_str(&format!(
+                    ", rowids {}..={}",
+                    fmt_key(rowid(*first)),
+                    fmt_key(rowid(*last))
+                ));
+            }
+        }
+        _ => {}
+    }
+    description
+}
+
+fn fmt_key(key: Option<i64>) -> String {
+    key.map_or_else(|| "?".to_string(), |key| key.to_string())
+}
+
+fn read_u16(buf: &[u8], pos: usize) -> u16 {
+    u16::from_be_bytes([buf[pos], buf[pos + 1]])
+}
+
+fn read_u32(buf: &[u8], pos: usize) -> u32 {
+    u32::from_be_bytes([buf[pos], buf[pos + 1], buf[pos + 2], buf[pos + 3]])
+}
+
+/// Reads a SQLite varint, returning the value and the number of bytes consumed.
+fn read_varint(buf: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (i, &byte) in buf.iter().take(9).enumerate() {
+        if i == 8 {
+            return Some(((value << 8) | byte as u64, 9));
+        }
+        value = (value << 7) | (byte & 0x7f) as u64;
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE_SIZE: usize = 512;
+
+    fn database(pages: usize) -> Vec<u8> {
+        let mut db = vec![0u8; PAGE_SIZE * pages];
+        db[16..18].copy_from_slice(&(PAGE_SIZE as u16).to_be_bytes());
+        for (i, page) in db.chunks_mut(PAGE_SIZE).enumerate() {
+            page[PAGE_SIZE - 1] = i as u8 + 1;
+        }
+        db
+    }
+
+    fn wal(salt: [u8; 8], frames: &[(u32, u32, [u8; 8], u8)]) -> Vec<u8> {
+        let mut wal = vec![0u8; WAL_HEADER_SIZE];
+        wal[8..12].copy_from_slice(&(PAGE_SIZE as u32).to_be_bytes());
+        wal[16..24].copy_from_slice(&salt);
+        for &(page_no, db_size, frame_salt, fill) in frames {
+            let mut frame = vec![0u8; WAL_FRAME_HEADER_SIZE];
+            frame[0..4].copy_from_slice(&page_no.to_be_bytes());
+            frame[4..8].copy_from_slice(&db_size.to_be_bytes());
+            frame[8..16].copy_from_slice(&frame_salt);
+            frame.extend(std::iter::repeat_n(fill, PAGE_SIZE));
+            wal.extend(frame);
+        }
+        wal
+    }
+
+    #[test]
+    fn database_pages_splits_the_file() {
+        let pages = database_pages(&database(3), None).unwrap();
+        assert_eq!(pages.keys().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(pages[&2][PAGE_SIZE - 1], 2);
+        assert!(database_pages(&[], None).is_none());
+    }
+
+    #[test]
+    fn database_pages_applies_committed_wal_frames() {
+        let salt = [1, 2, 3, 4, 5, 6, 7, 8];
+        let wal = wal(
+            salt,
+            &[
+                // A committed transaction rewriting page 2 and appending page 4.
+                (2, 0, salt, 0xaa),
+                (4, 4, salt, 0xbb),
+                // A transaction without its commit frame.
+                (3, 0, salt, 0xcc),
+            ],
+        );
+        let pages = database_pages(&database(3), Some(&wal)).unwrap();
+        assert_eq!(pages.len(), 4);
+        assert!(pages[&2].iter().all(|&b| b == 0xaa));
+        assert!(pages[&4].iter().all(|&b| b == 0xbb));
+        assert_eq!(pages[&3][PAGE_SIZE - 1], 3);
+    }
+
+    #[test]
+    fn database_pages_stops_at_stale_frames() {
+        let salt = [1, 2, 3, 4, 5, 6, 7, 8];
+        let stale = [9; 8];
+        let wal = wal(salt, &[(2, 3, salt, 0xaa), (3, 3, stale, 0xcc)]);
+        let pages = database_pages(&database(3), Some(&wal)).unwrap();
+        assert!(pages[&2].iter().all(|&b| b == 0xaa));
+        assert_eq!(pages[&3][PAGE_SIZE - 1], 3);
+    }
+
+    #[test]
+    fn database_pages_reads_an_empty_database_from_the_wal() {
+        let salt = [1; 8];
+        let pages = database_pages(&[], Some(&wal(salt, &[(1, 1, salt, 0)]))).unwrap();
+        assert_eq!(pages.keys().copied().collect::<Vec<_>>(), vec![1]);
+    }
+
+    #[test]
+    fn database_files_finds_a_wal_without_database() {
+        let mut files = IndexMap::new();
+        files.insert("test.db-wal".to_string(), vec![1]);
+        let (db_path, db, wal) = database_files(&files).unwrap();
+        assert_eq!(db_path, "test.db");
+        assert!(db.is_empty());
+        assert_eq!(wal, Some(&[1][..]));
+
+        files.insert("test.db".to_string(), vec![2]);
+        let (db_path, db, wal) = database_files(&files).unwrap();
+        assert_eq!(db_path, "test.db");
+        assert_eq!(db, &[2]);
+        assert_eq!(wal, Some(&[1][..]));
+
+        assert!(database_files(&IndexMap::new()).is_none());
+    }
+
+    #[test]
+    fn describe_leaf_table_page() {
+        let mut page = vec![0u8; PAGE_SIZE];
+        page[0] = 0x0d;
+        page[3..5].copy_from_slice(&2u16.to_be_bytes());
+        // Cells: payload size 1, rowid 1 and payload size 1, rowid 300.
+        page[8..10].copy_from_slice(&400u16.to_be_bytes());
+        page[10..12].copy_from_slice(&410u16.to_be_bytes());
+        page[400..402].copy_from_slice(&[0x01, 0x01]);
+        page[410..413].copy_from_slice(&[0x01, 0x82, 0x2c]);
+        assert_eq!(
+            describe_page(2, &page),
+            "page 2: leaf table, 2 cells, rowids 1..=300"
+        );
+    }
+
+    #[test]
+    fn describe_interior_table_page() {
+        let mut page = vec![0u8; 100 + PAGE_SIZE];
+        page[100] = 0x05;
+        page[103..105].copy_from_slice(&1u16.to_be_bytes());
+        page[108..112].copy_from_slice(&7u32.to_be_bytes());
+        page[112..114].copy_from_slice(&300u16.to_be_bytes());
+        page[300..304].copy_from_slice(&3u32.to_be_bytes());
+        page[304] = 42;
+        assert_eq!(
+            describe_page(1, &page),
+            "page 1: interior table, 1 cells, children [3<=42], right child 7"
+        );
+    }
+
+    #[test]
+    fn describe_other_pages() {
+        assert_eq!(describe_page(3, &[0u8; 4]), "page 3: truncated (4 bytes)");
+        // The header of an interior page is 12 bytes long.
+        assert_eq!(
+            describe_page(3, &[0x05, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+            "page 3: truncated (10 bytes)"
+        );
+        assert_eq!(
+            describe_page(3, &[0u8; PAGE_SIZE]),
+            "page 3: not a b-tree page (overflow, freelist or ptrmap)"
+        );
+    }
+
+    #[test]
+    fn read_varints() {
+        assert_eq!(read_varint(&[0x7f]), Some((127, 1)));
+        assert_eq!(read_varint(&[0x81, 0x00]), Some((128, 2)));
+        assert_eq!(read_varint(&[0x82, 0x2c, 0xff]), Some((300, 2)));
+        assert_eq!(read_varint(&[0xff; 9]), Some((u64::MAX, 9)));
+        assert_eq!(read_varint(&[0x81]), None);
+        assert_eq!(read_varint(&[]), None);
+    }
+}
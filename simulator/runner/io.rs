@@ -7,7 +7,9 @@ use rand::{Rng, RngCore, SeedableRng};
 use rand_chacha::ChaCha8Rng;
 use turso_core::{Clock, IO, Instant, OpenFlags, PlatformIO, Result};
 
-use crate::runner::{SimIO, cli::IoBackend, clock::SimulatorClock, file::SimulatorFile};
+use crate::runner::{
+    SimIO, cli::IoBackend, clock::SimulatorClock, file::SimulatorFile, trace::WriteLog,
+};
 
 pub(crate) struct SimulatorIO {
     pub(crate) inner: Box<dyn IO>,
@@ -18,6 +20,7 @@ pub(crate) struct SimulatorIO {
     seed: u64,
     latency_probability: u8,
     clock: Arc<SimulatorClock>,
+    write_log: WriteLog,
 }
 
 unsafe impl Send for SimulatorIO {}
@@ -54,6 +57,7 @@ impl SimulatorIO {
             seed,
             latency_probability,
             clock: Arc::new(clock),
+            write_log: WriteLog::default(),
         })
     }
 }
@@ -96,6 +100,10 @@ impl SimIO for SimulatorIO {
         // Files are persisted automatically
         Ok(())
     }
+
+    fn write_log(&self) -> &WriteLog {
+        &self.write_log
+    }
 }
 
 impl Clock for SimulatorIO {
@@ -128,6 +136,7 @@ impl IO for SimulatorIO {
             sync_completion: RefCell::new(None),
             queued_io: RefCell::new(Vec::new()),
             clock: self.clock.clone(),
+            write_log: self.write_log.clone(),
         });
         self.files.borrow_mut().push(file.clone());
         Ok(file)
@@ -10,6 +10,7 @@ use turso_core::{Clock, Completion, IO, Instant, OpenFlags, Result};
 use crate::runner::SimIO;
 use crate::runner::clock::SimulatorClock;
 use crate::runner::memory::file::MemorySimFile;
+use crate::runner::trace::WriteLog;
 
 /// File descriptor
 pub type Fd = String;
@@ -60,7 +61,7 @@ pub struct Operation {
 }
 
 impl Operation {
-    fn do_operation(self, files: &IndexMap<Fd, Arc<MemorySimFile>>) {
+    fn do_operation(self, files: &IndexMap<Fd, Arc<MemorySimFile>>, write_log: &WriteLog) {
         let fd = self.fd;
         match self.op {
             OperationType::Read { completion, offset } => {
@@ -82,6 +83,7 @@ impl Operation {
                 offset,
             } => {
                 let file = files.get(fd.as_str()).unwrap();
+                write_log.record(fd.as_str(), offset as u64, buffer.as_slice());
                 let buf_size = file.write_buf(buffer.as_slice(), offset);
                 completion.complete(buf_size as i32);
             }
@@ -96,6 +98,7 @@ impl Operation {
                 let file = files.get(fd.as_str()).unwrap();
                 let mut pos = offset;
                 let written = buffers.into_iter().fold(0, |written, buffer| {
+                    write_log.record(fd.as_str(), pos as u64, buffer.as_slice());
                     let buf_size = file.write_buf(buffer.as_slice(), pos);
                     pos += buf_size;
                     written + buf_size
@@ -128,6 +131,7 @@ pub struct MemorySimIO {
     seed: u64,
     latency_probability: u8,
     clock: Arc<SimulatorClock>,
+    write_log: WriteLog,
 }
 
 unsafe impl Send for MemorySimIO {}
@@ -156,6 +160,7 @@ impl MemorySimIO {
                 min_tick,
                 max_tick,
             )),
+            write_log: WriteLog::default(),
         }
     }
 }
@@ -207,6 +212,10 @@ impl SimIO for MemorySimIO {
         }
         Ok(())
     }
+
+    fn write_log(&self) -> &WriteLog {
+        &self.write_log
+    }
 }
 
 impl Clock for MemorySimIO {
@@ -267,7 +276,7 @@ impl IO for MemorySimIO {
                     completion.abort();
                     continue;
                 }
-                callback.do_operation(&files);
+                callback.do_operation(&files, &self.write_log);
             } else {
                 timeouts.push(callback);
             }
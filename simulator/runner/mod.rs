@@ -7,8 +7,10 @@ pub mod env;
 pub mod execution;
 #[expect(dead_code)]
 pub mod file;
+pub mod inspect;
 pub mod io;
 pub mod memory;
+pub mod trace;
 
 pub const FAULT_ERROR_MSG: &str = "Injected Fault";
 
@@ -22,4 +24,7 @@ pub trait SimIO: turso_core::IO {
     fn close_files(&self);
 
     fn persist_files(&self) -> anyhow::Result<()>;
+
+    /// Log of the writes performed by this IO, used to record simulator traces.
+    fn write_log(&self) -> &trace::WriteLog;
 }
@@ -0,0 +1,192 @@
+//! Recording of simulator runs for the `--inspect` time-travel debugger.
+//!
+//! When `--record-trace` is passed, every executed interaction is appended to a JSON lines
+//! file together with the file writes the IO backend performed while executing it. The
+//! resulting trace can be replayed step by step with `limbo_sim --inspect <trace>`.
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    sync::Arc,
+};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+/// A single write issued by the simulated IO backend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct TracedWrite {
+    pub(crate) path: String,
+    pub(crate) offset: u64,
+    /// Hex encoded bytes that were written at `offset`.
+    pub(crate) data: String,
+}
+
+impl TracedWrite {
+    pub(crate) fn bytes(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(hex::decode(&self.data)?)
+    }
+}
+
+/// Shared sink for the writes issued by a simulated IO backend. Recording is disabled until
+/// [WriteLog::enable] is called, so runs without `--record-trace` do not pay for it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct WriteLog(Arc<Mutex<Option<Vec<TracedWrite>>>>);
+
+impl WriteLog {
+    pub(crate) fn enable(&self) {
+        let mut log = self.0.lock();
+        if log.is_none() {
+            *log = Some(Vec::new());
+        }
+    }
+
+    pub(crate) fn record(&self, path: &str, offset: u64, data: &[u8]) {
+        if let Some(log) = self.0.lock().as_mut() {
+            log.push(TracedWrite {
+                path: path.to_string(),
+                offset,
+                data: hex::encode(data),
+            });
+        }
+    }
+
+    /// Takes the writes recorded since the last call.
+    pub(crate) fn take(&self) -> Vec<TracedWrite> {
+        self.0
+            .lock()
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+}
+
+/// One executed interaction and the writes it caused.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct TraceStep {
+    pub(crate) step: usize,
+    pub(crate) connection_index: usize,
+    pub(crate) interaction_index: usize,
+    pub(crate) interaction: String,
+    pub(crate) writes: Vec<TracedWrite>,
+    pub(crate) error: Option<String>,
+}
+
+/// Appends [TraceStep]s to a trace file. Every step is flushed right away so that the trace
+/// is usable even if the simulation panics.
+pub(crate) struct TraceWriter {
+    out: BufWriter<File>,
+    step: usize,
+}
+
+impl TraceWriter {
+    pub(crate) fn create(path: &Path) -> anyhow::Result<Self> {
+        tracing::info!("recording trace to {}", path.display());
+        Ok(Self {
+            out: BufWriter::new(File::create(path)?),
+            step: 0,
+        })
+    }
+
+    pub(crate) fn record(
+        &mut self,
+        connection_index: usize,
+        interaction_index: usize,
+        interaction: String,
+        writes: Vec<TracedWrite>,
+        error: Option<String>,
+    ) -> anyhow::Result<()> {
+        let step = TraceStep {
+            step: self.step,
+            connection_index,
+            interaction_index,
+            interaction,
+            writes,
+            error,
+        };
+        self.step += 1;
+        serde_json::to_writer(&mut self.out, &step)?;
+        self.out.write_all(b"\n")?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+pub(crate) fn load_trace(path: &Path) -> anyhow::Result<Vec<TraceStep>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut steps = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        steps.push(serde_json::from_str(&line)?);
+    }
+    Ok(steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_log_records_only_when_enabled() {
+        let log = WriteLog::default();
+        log.record("test.db", 0, &[1, 2]);
+        assert!(log.take().is_empty());
+
+        log.enable();
+        log.record("test.db", 4096, &[0xab, 0xcd]);
+        let writes = log.take();
+        assert_eq!(writes.len(), 1);
+        assert_eq!(writes[0].path, "test.db");
+        assert_eq!(writes[0].offset, 4096);
+        assert_eq!(writes[0].bytes().unwrap(), vec![0xab, 0xcd]);
+        assert!(log.take().is_empty());
+    }
+
+    #[test]
+    fn load_trace_reads_recorded_steps() {
+        let path =
+            std::env::temp_dir().join(format!("limbo_sim_trace_{}.jsonl", std::process::id()));
+        let mut writer = TraceWriter::create(&path).unwrap();
+        let write = TracedWrite {
+            path: "test.db-wal".to_string(),
+            offset: 32,
+            data: "00ff".to_string(),
+        };
+        writer
+            .record(0, 0, "CREATE TABLE t (x)".to_string(), vec![write], None)
+            .unwrap();
+        writer
+            .record(
+                1,
+                3,
+                "SELECT * FROM u".to_string(),
+                vec![],
+                Some("no such table: u".to_string()),
+            )
+            .unwrap();
+        drop(writer);
+        // Blank lines are skipped.
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"\n")
+            .unwrap();
+
+        let steps = load_trace(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].step, 0);
+        assert_eq!(steps[0].interaction, "CREATE TABLE t (x)");
+        assert_eq!(steps[0].writes[0].bytes().unwrap(), vec![0x00, 0xff]);
+        assert_eq!(steps[1].step, 1);
+        assert_eq!(steps[1].connection_index, 1);
+        assert_eq!(steps[1].interaction_index, 3);
+        assert_eq!(steps[1].error.as_deref(), Some("no such table: u"));
+        assert!(load_trace(&path).is_err());
+    }
+}
@@ -91,7 +91,16 @@ int sqlite3_set_authorizer(sqlite3 *_db, int (*_callback)(void), void *_context)
 
 void *sqlite3_context_db_handle(void *_context);
 
-int sqlite3_prepare_v2(sqlite3 *db, const char *sql, int _len, sqlite3_stmt **out_stmt, const char **_tail);
+int sqlite3_prepare(sqlite3 *raw_db, const char *sql, int len, sqlite3_stmt **out_stmt, const char **tail);
+
+int sqlite3_prepare_v3(sqlite3 *raw_db,
+                       const char *sql,
+                       int len,
+                       unsigned int _prep_flags,
+                       sqlite3_stmt **out_stmt,
+                       const char **tail);
+
+int sqlite3_prepare_v2(sqlite3 *raw_db, const char *sql, int len, sqlite3_stmt **out_stmt, const char **tail);
 
 int sqlite3_finalize(sqlite3_stmt *stmt);
 
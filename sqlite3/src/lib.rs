@@ -376,35 +376,79 @@ pub unsafe extern "C" fn sqlite3_context_db_handle(_context: *mut ffi::c_void) -
     stub!();
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn sqlite3_prepare(
+    raw_db: *mut sqlite3,
+    sql: *const ffi::c_char,
+    len: ffi::c_int,
+    out_stmt: *mut *mut sqlite3_stmt,
+    tail: *mut *const ffi::c_char,
+) -> ffi::c_int {
+    sqlite3_prepare_v2(raw_db, sql, len, out_stmt, tail)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn sqlite3_prepare_v3(
+    raw_db: *mut sqlite3,
+    sql: *const ffi::c_char,
+    len: ffi::c_int,
+    _prep_flags: ffi::c_uint,
+    out_stmt: *mut *mut sqlite3_stmt,
+    tail: *mut *const ffi::c_char,
+) -> ffi::c_int {
+    sqlite3_prepare_v2(raw_db, sql, len, out_stmt, tail)
+}
+
+/// Compiles the first statement of `sql`. A negative `len` reads up to the nul terminator,
+/// otherwise at most `len` bytes are used. `tail` is set to the start of the next statement,
+/// and `out_stmt` to null when `sql` only holds whitespace or comments.
 #[no_mangle]
 pub unsafe extern "C" fn sqlite3_prepare_v2(
     raw_db: *mut sqlite3,
     sql: *const ffi::c_char,
-    _len: ffi::c_int,
+    len: ffi::c_int,
     out_stmt: *mut *mut sqlite3_stmt,
-    _tail: *mut *const ffi::c_char,
+    tail: *mut *const ffi::c_char,
 ) -> ffi::c_int {
     if raw_db.is_null() || sql.is_null() || out_stmt.is_null() {
         return SQLITE_MISUSE;
     }
+    *out_stmt = std::ptr::null_mut();
     let db: &mut sqlite3 = &mut *raw_db;
     let mut db = db.inner.lock().unwrap();
-    let sql = CStr::from_ptr(sql);
-    let sql = match sql.to_str() {
+    let bytes = if len < 0 {
+        CStr::from_ptr(sql).to_bytes()
+    } else {
+        let bytes = std::slice::from_raw_parts(sql as *const u8, len as usize);
+        // a nul terminator before len bytes still ends the input
+        bytes
+            .iter()
+            .position(|&b| b == 0)
+            .map_or(bytes, |nul| &bytes[..nul])
+    };
+    let sql_str = match std::str::from_utf8(bytes) {
         Ok(s) => s,
         Err(_) => {
             db.err_code = SQLITE_MISUSE;
             return SQLITE_MISUSE;
         }
     };
-    let stmt = match db.conn.prepare(sql) {
-        Ok(stmt) => stmt,
+    let (stmt, consumed) = match db.conn.consume_stmt(sql_str) {
+        Ok(Some(prepared)) => prepared,
+        Ok(None) => {
+            if !tail.is_null() {
+                *tail = sql.add(bytes.len());
+            }
+            return SQLITE_OK;
+        }
         Err(err) => {
-            let code = handle_limbo_err(err, std::ptr::null_mut());
-            db.err_code = code;
-            return code;
+            db.err_code = err.extended_result_code() as i32;
+            return handle_limbo_err(err, std::ptr::null_mut());
         }
     };
+    if !tail.is_null() {
+        *tail = sql.add(consumed);
+    }
     let new_stmt = Box::leak(Box::new(sqlite3_stmt::new(raw_db, stmt)));
 
     new_stmt.next = db.stmt_list;
@@ -800,8 +844,12 @@ pub unsafe extern "C" fn sqlite3_stmt_readonly(_stmt: *mut sqlite3_stmt) -> ffi:
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn sqlite3_stmt_busy(_stmt: *mut sqlite3_stmt) -> ffi::c_int {
-    stub!();
+pub unsafe extern "C" fn sqlite3_stmt_busy(stmt: *mut sqlite3_stmt) -> ffi::c_int {
+    if stmt.is_null() {
+        return 0;
+    }
+    let stmt = &*stmt;
+    stmt.stmt.is_busy() as ffi::c_int
 }
 
 /// Iterate over all prepared statements in the database.
@@ -937,7 +985,7 @@ pub unsafe extern "C" fn sqlite3_errcode(db: *mut sqlite3) -> ffi::c_int {
     if db.malloc_failed {
         return SQLITE_NOMEM;
     }
-    db.err_code & db.err_mask
+    db.err_code & 0xff & db.err_mask
 }
 
 #[no_mangle]
@@ -1256,8 +1304,8 @@ pub unsafe extern "C" fn sqlite3_column_decltype(
 ) -> *const ffi::c_char {
     let stmt = &mut *stmt;
 
-    if let Some(val) = stmt.stmt.get_column_type(idx as usize) {
-        let c_string = CString::new(val).expect("CString::new failed");
+    if let Some(val) = stmt.stmt.get_column_decl_type(idx as usize) {
+        let c_string = CString::new(val.as_ref()).expect("CString::new failed");
         c_string.into_raw()
     } else {
         std::ptr::null()
@@ -2223,14 +2271,5 @@ fn handle_limbo_err(err: LimboError, container: *mut *mut ffi::c_char) -> i32 {
         let err_msg = format!("{err}");
         unsafe { *container = CString::new(err_msg).unwrap().into_raw() };
     }
-    match err {
-        LimboError::Corrupt(..) => SQLITE_CORRUPT,
-        LimboError::NotADB => SQLITE_NOTADB,
-        LimboError::Constraint(_) => SQLITE_CONSTRAINT,
-        LimboError::DatabaseFull(_) => SQLITE_FULL,
-        LimboError::TableLocked => SQLITE_LOCKED,
-        LimboError::ReadOnly => SQLITE_READONLY,
-        LimboError::Busy => SQLITE_BUSY,
-        _ => SQLITE_ERROR,
-    }
+    err.result_code() as i32
 }
@@ -76,6 +76,7 @@ extern "C" {
     fn sqlite3_bind_parameter_name(stmt: *mut sqlite3_stmt, idx: i32) -> *const libc::c_char;
     fn sqlite3_bind_parameter_index(stmt: *mut sqlite3_stmt, name: *const libc::c_char) -> i32;
     fn sqlite3_clear_bindings(stmt: *mut sqlite3_stmt) -> i32;
+    fn sqlite3_stmt_busy(stmt: *mut sqlite3_stmt) -> i32;
     fn sqlite3_column_name(stmt: *mut sqlite3_stmt, idx: i32) -> *const libc::c_char;
     fn sqlite3_column_table_name(stmt: *mut sqlite3_stmt, idx: i32) -> *const libc::c_char;
     fn sqlite3_last_insert_rowid(db: *mut sqlite3) -> i32;
@@ -207,6 +208,48 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_prepare_tail_and_len() {
+        unsafe {
+            let mut db = ptr::null_mut();
+            assert_eq!(sqlite3_open(c":memory:".as_ptr(), &mut db), SQLITE_OK);
+
+            let sql = c"CREATE TABLE t(x); INSERT INTO t VALUES (1);  -- done";
+            let mut rest = sql.as_ptr();
+            let mut prepared = 0;
+            loop {
+                let mut stmt = ptr::null_mut();
+                let mut tail = ptr::null();
+                assert_eq!(
+                    sqlite3_prepare_v2(db, rest, -1, &mut stmt, &mut tail),
+                    SQLITE_OK
+                );
+                if stmt.is_null() {
+                    // only the trailing comment was left
+                    assert_eq!(*tail, 0);
+                    break;
+                }
+                prepared += 1;
+                assert_eq!(sqlite3_step(stmt), SQLITE_DONE);
+                assert_eq!(sqlite3_finalize(stmt), SQLITE_OK);
+                rest = tail;
+            }
+            assert_eq!(prepared, 2);
+
+            // only the first 8 bytes are compiled: "SELECT 4"
+            let mut stmt = ptr::null_mut();
+            assert_eq!(
+                sqlite3_prepare_v2(db, c"SELECT 42".as_ptr(), 8, &mut stmt, ptr::null_mut()),
+                SQLITE_OK
+            );
+            assert_eq!(sqlite3_step(stmt), SQLITE_ROW);
+            assert_eq!(sqlite3_column_int(stmt, 0), 4);
+            assert_eq!(sqlite3_finalize(stmt), SQLITE_OK);
+
+            assert_eq!(sqlite3_close(db), SQLITE_OK);
+        }
+    }
+
     #[test]
     fn test_sqlite3_bind_int() {
         unsafe {
@@ -742,7 +785,7 @@ mod tests {
             assert_eq!(
             sqlite3_prepare_v2(
                 db,
-                c"CREATE TABLE test_decltype (col_int INTEGER, col_float REAL, col_text TEXT, col_blob BLOB, col_null NULL)".as_ptr(),
+                c"CREATE TABLE test_decltype (col_int INTEGER, col_float REAL, col_text TEXT, col_blob BLOB, col_null NULL, col_varchar VARCHAR(32), col_decimal DECIMAL(10, 5))".as_ptr(),
                 -1,
                 &mut stmt,
                 std::ptr::null_mut(),
@@ -756,7 +799,7 @@ mod tests {
             assert_eq!(
                 sqlite3_prepare_v2(
                     db,
-                    c"SELECT col_int, col_float, col_text, col_blob, col_null FROM test_decltype"
+                    c"SELECT col_int, col_float, col_text, col_blob, col_null, col_varchar, col_decimal, col_int + 1 FROM test_decltype"
                         .as_ptr(),
                     -1,
                     &mut stmt,
@@ -770,6 +813,9 @@ mod tests {
                 Some("REAL"),
                 Some("TEXT"),
                 Some("BLOB"),
+                Some("NULL"),
+                Some("VARCHAR(32)"),
+                Some("DECIMAL(10, 5)"),
                 None,
             ];
 
@@ -1975,14 +2021,23 @@ mod tests {
             // Bind parameters - should find John (id=1, age=25 > 20)
             assert_eq!(sqlite3_bind_int(stmt, 1, 1), SQLITE_OK);
             assert_eq!(sqlite3_bind_int(stmt, 2, 20), SQLITE_OK);
+            assert_eq!(sqlite3_stmt_busy(stmt), 0);
             assert_eq!(sqlite3_step(stmt), SQLITE_ROW);
+            assert_eq!(sqlite3_stmt_busy(stmt), 1);
             assert_eq!(sqlite3_column_int(stmt, 0), 1);
             assert_eq!(sqlite3_column_int(stmt, 2), 25);
 
+            // Reset keeps the bindings, the same row is returned again
+            assert_eq!(sqlite3_reset(stmt), SQLITE_OK);
+            assert_eq!(sqlite3_stmt_busy(stmt), 0);
+            assert_eq!(sqlite3_step(stmt), SQLITE_ROW);
+            assert_eq!(sqlite3_column_int(stmt, 0), 1);
+
             // Reset and clear bindings, query should return no rows
             assert_eq!(sqlite3_reset(stmt), SQLITE_OK);
             assert_eq!(sqlite3_clear_bindings(stmt), SQLITE_OK);
             assert_eq!(sqlite3_step(stmt), SQLITE_DONE);
+            assert_eq!(sqlite3_stmt_busy(stmt), 0);
 
             assert_eq!(sqlite3_finalize(stmt), SQLITE_OK);
             assert_eq!(sqlite3_close(db), SQLITE_OK);
@@ -1,5 +1,7 @@
 #!/usr/bin/env python3
 import os
+import shutil
+import tempfile
 import time
 from pathlib import Path
 
@@ -380,6 +382,124 @@ def test_tables_with_attached_db():
     shell.quit()
 
 
+def test_read_script():
+    shell = TestTursoShell()
+    shell.run_test("open-memory", ".open :memory:", "")
+    shell.run_test("read-dump", ".read ./testing/test_files/read_dump.sql", "")
+    shell.run_test("verify-read-dump", "SELECT id, length(name) FROM t;", "1|3\n2|17")
+    shell.run_test_fn(
+        ".read ./testing/test_files/read_failing.sql",
+        lambda res: "UNIQUE constraint failed" in res,
+        "A failing statement in a script should be reported",
+    )
+    shell.run_test("read-rolled-back", "SELECT count(*) FROM t;", "2")
+    shell.quit()
+
+
+def test_import_json_and_export():
+    shell = TestTursoShell()
+    shell.run_test("open-memory", ".open :memory:", "")
+    shell.run_test(
+        "import-json-create-table",
+        ".import --json ./testing/test_files/test.ndjson json_table",
+        "",
+    )
+    shell.run_test(
+        "verify-json-import",
+        "select c1, c2, c3 from json_table;",
+        "1|2.5|one\n2||it's two",
+    )
+    shell.run_test(
+        "export-tsv",
+        ".export --tsv ./testing/test_files/export.tsv SELECT c1, c3 FROM json_table",
+        'Exported 2 rows to "./testing/test_files/export.tsv"',
+    )
+    shell.run_test("drop-json-table", "DROP TABLE json_table;", "")
+    shell.run_test(
+        "import-exported-tsv",
+        ".import --tsv ./testing/test_files/export.tsv tsv_table",
+        "",
+    )
+    shell.run_test("verify-tsv-import", "select * from tsv_table;", "1|one\n2|it's two")
+    shell.quit()
+    os.unlink("./testing/test_files/export.tsv")
+
+
+def test_archive():
+    src = tempfile.mkdtemp()
+    dst = tempfile.mkdtemp()
+    Path(src, "a.txt").write_text("hello " * 100)
+    Path(src, "sub").mkdir()
+    Path(src, "sub", "b.bin").write_bytes(bytes(range(256)))
+    shell = TestTursoShell()
+    shell.run_test("open-memory", ".open :memory:", "")
+    shell.run_test("archive-create", f".archive -c -C {src} a.txt sub", "")
+    shell.run_test("archive-list", ".archive -t", "a.txt\nsub\nsub/b.bin")
+    shell.run_test(
+        "archive-sizes",
+        "SELECT name, sz, length(data) < sz FROM sqlar WHERE sz > 0 ORDER BY name;",
+        "a.txt|600|1\nsub/b.bin|256|0",
+    )
+    shell.run_test("archive-extract", f".archive -x -C {dst} sub", "")
+    shell.run_test_fn(
+        ".archive -t missing",
+        lambda res: "not found in archive: missing" in res,
+        "Listing a file that is not in the archive should fail",
+    )
+    shell.quit()
+    assert Path(dst, "sub", "b.bin").read_bytes() == bytes(range(256))
+    assert not Path(dst, "a.txt").exists()
+    shutil.rmtree(src)
+    shutil.rmtree(dst)
+
+
+def test_output_modes():
+    shell = TestTursoShell()
+    query = "SELECT 1 AS a, 'two' AS bb, NULL AS c UNION ALL SELECT 10, 'it''s', 'x,y';"
+    shell.execute_dot(".mode table")
+    shell.run_test(
+        "mode-table",
+        query,
+        "+----+------+-----+\n| a  |  bb  |  c  |\n+----+------+-----+\n"
+        "| 1  | two  |     |\n| 10 | it's | x,y |\n+----+------+-----+",
+    )
+    shell.execute_dot(".mode markdown")
+    shell.run_test(
+        "mode-markdown",
+        query,
+        "| a  |  bb  |  c  |\n|----|------|-----|\n| 1  | two  |     |\n| 10 | it's | x,y |",
+    )
+    shell.execute_dot(".mode csv")
+    shell.run_test("mode-csv", query, '1,two,\n10,it\'s,"x,y"')
+    shell.execute_dot(".mode json")
+    shell.run_test(
+        "mode-json",
+        query,
+        '[{"a":1,"bb":"two","c":null},\n{"a":10,"bb":"it\'s","c":"x,y"}]',
+    )
+    shell.execute_dot(".mode ndjson")
+    shell.run_test(
+        "mode-ndjson",
+        query,
+        '{"a":1,"bb":"two","c":null}\n{"a":10,"bb":"it\'s","c":"x,y"}',
+    )
+    shell.execute_dot(".mode insert t1")
+    shell.run_test(
+        "mode-insert",
+        query,
+        "INSERT INTO \"t1\" VALUES(1,'two',NULL);\nINSERT INTO \"t1\" VALUES(10,'it''s','x,y');",
+    )
+    shell.quit()
+
+
+def test_mode_without_argument():
+    shell = TestTursoShell()
+    shell.run_test("mode-default", ".mode", "current output mode: list")
+    shell.execute_dot(".mode line")
+    shell.run_test("mode-after-switch", ".mode", "current output mode: line")
+    shell.quit()
+
+
 def main():
     console.info("Running all turso CLI tests...")
     test_basic_queries()
@@ -405,6 +525,11 @@ def main():
     test_copy_memory_db_to_file()
     test_parse_error()
     test_tables_with_attached_db()
+    test_mode_without_argument()
+    test_read_script()
+    test_import_json_and_export()
+    test_archive()
+    test_output_modes()
     console.info("All tests have passed")
 
 
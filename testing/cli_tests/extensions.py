@@ -73,9 +73,9 @@ def null(res):
 def test_regexp():
     turso = TestTursoShell(test_data)
     extension_path = "./target/debug/liblimbo_regexp"
-    # before extension loads, assert no function
+    # before extension loads, assert no function (regexp itself is built in)
     turso.run_test_fn(
-        "SELECT regexp('a.c', 'abc');",
+        "SELECT regexp_like('abc', 'a.c');",
         lambda res: "Parse error: no such function" in res,
     )
     turso.run_test_fn(f".load {extension_path}", null)
@@ -325,6 +325,16 @@ def test_crypto():
         == "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",  # noqa: E501
         "sha512 should encrypt correctly",
     )
+    turso.run_test_fn(
+        "SELECT crypto_encode(crypto_sha3('abc'), 'hex');",
+        lambda a: a == "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+        "sha3 should default to 256 bits",
+    )
+    turso.run_test_fn(
+        "SELECT crypto_encode(crypto_sha3('abc', 224), 'hex');",
+        lambda a: a == "e642824c3f8cf24ad09234ee7d3c766fc9a3a5168d0c94ad73b46fdf",
+        "sha3 should support other output sizes",
+    )
 
     # Encoding and Decoding
     turso.run_test_fn(
@@ -721,6 +731,37 @@ def test_fuzzy():
         validate_fuzzy_script,
         "fuzzy script function works",
     )
+    turso.run_test_fn(
+        "SELECT levenshtein('awesome', 'aewsme'), editdist('abc', 'ca');",
+        lambda res: res == "3|225",
+        "levenshtein and editdist aliases work",
+    )
+    turso.run_test_fn(
+        "CREATE VIRTUAL TABLE dict USING spellfix1;",
+        null,
+        "Create spellfix1 table",
+    )
+    turso.run_test_fn(
+        "INSERT INTO dict(word, rank) VALUES ('kennesaw', 10), ('kenosha', 5), ('boston', 1);",
+        null,
+        "Insert words into spellfix1 table",
+    )
+    turso.run_test_fn(
+        "SELECT word FROM dict WHERE search = 'kennasaw' AND top = 2;",
+        lambda res: res == "kennesaw\nkenosha",
+        "spellfix1 finds the closest words",
+    )
+    turso.run_test_fn(
+        "SELECT word, distance FROM dict WHERE search = 'Boston' AND top = 1;",
+        lambda res: res == "boston|0",
+        "spellfix1 search ignores case",
+    )
+    turso.run_test_fn(
+        "SELECT count(*) FROM dict;",
+        lambda res: res == "3",
+        "spellfix1 table without a search lists all words",
+    )
+    turso.quit()
 
 
 def test_vfs():
@@ -853,6 +894,106 @@ def test_csv():
     turso.quit()
 
 
+def test_zipfile():
+    zip_path = "testing/zipfile_test.zip"
+    turso = TestTursoShell(init_commands="")
+    test_module_list(turso, "target/debug/liblimbo_zipfile", "zipfile")
+
+    turso.run_test_fn(
+        f"CREATE VIRTUAL TABLE temp.zz USING zipfile('{zip_path}');",
+        null,
+        "Create zipfile table for a new archive",
+    )
+    turso.run_test_fn(
+        "INSERT INTO temp.zz(name, mode, mtime, data) VALUES "
+        "('a.txt', 33188, 1700000000, 'hello'), ('dir/', 16877, 1700000000, NULL);",
+        null,
+        "Insert entries into the archive",
+    )
+    turso.run_test_fn(
+        f"SELECT name, sz, data FROM zipfile('{zip_path}');",
+        lambda res: res == "a.txt|5|hello\ndir/|0|",
+        "Read the archive written on commit with zipfile()",
+    )
+    turso.run_test_fn(
+        "UPDATE temp.zz SET data = 'bye' WHERE name = 'a.txt';",
+        null,
+        "Update an entry of the archive",
+    )
+    turso.run_test_fn("DELETE FROM temp.zz WHERE name = 'dir/';", null, "Delete an entry")
+    turso.run_test_fn(
+        f"SELECT name, data FROM zipfile('{zip_path}');",
+        lambda res: res == "a.txt|bye",
+        "Updates and deletes are written to the archive",
+    )
+    turso.run_test_fn(
+        "INSERT INTO temp.zz(name, data) VALUES ('a.txt', 'again');",
+        lambda res: "error" in res.lower(),
+        "Names in an archive are unique",
+    )
+    turso.run_test_fn(
+        "INSERT INTO zipfile(name, data) VALUES ('b.txt', 'b');",
+        lambda res: "error" in res.lower(),
+        "zipfile() can't be written to",
+    )
+    turso.quit()
+    os.remove(zip_path)
+
+
+def test_rtree():
+    turso = TestTursoShell(init_commands="")
+    test_module_list(turso, "target/debug/liblimbo_rtree", "rtree")
+
+    turso.run_test_fn(
+        "CREATE VIRTUAL TABLE boxes USING rtree(id, minX, maxX, minY, maxY, +name);",
+        null,
+        "Create rtree table",
+    )
+    turso.run_test_fn(
+        "INSERT INTO boxes VALUES (1, 0, 10, 0, 10, 'big'), (2, 5, 6, 5, 6, 'small'), "
+        "(3, 20, 30, -5, 5, 'far');",
+        null,
+        "Insert boxes",
+    )
+    turso.run_test_fn(
+        "SELECT id, name FROM boxes WHERE maxX >= 4 AND minX <= 8 AND maxY >= 4 AND minY <= 8;",
+        lambda res: res == "1|big\n2|small",
+        "Find the boxes overlapping a region",
+    )
+    turso.run_test_fn(
+        "SELECT minX, maxX, name FROM boxes WHERE id = 3;",
+        lambda res: res == "20.0|30.0|far",
+        "Look up a box by id",
+    )
+    turso.run_test_fn(
+        "INSERT INTO boxes(minX, maxX, minY, maxY) VALUES (1, 2, 1, 2);",
+        null,
+        "Insert a box without an id",
+    )
+    turso.run_test_fn(
+        "SELECT id FROM boxes WHERE minX >= 1 AND maxX <= 2;",
+        lambda res: res == "4",
+        "A box inserted without an id gets the next id",
+    )
+    turso.run_test_fn("DELETE FROM boxes WHERE id = 1;", null, "Delete a box")
+    turso.run_test_fn(
+        "UPDATE boxes SET minX = 100, maxX = 101 WHERE id = 2;",
+        null,
+        "Move a box",
+    )
+    turso.run_test_fn(
+        "SELECT id FROM boxes WHERE maxX >= 4 AND minX <= 8;",
+        lambda res: res == "",
+        "Deleted and moved boxes are no longer found",
+    )
+    turso.run_test_fn(
+        "INSERT INTO boxes VALUES (5, 1, 0, 1, 0, NULL);",
+        lambda res: "error" in res.lower(),
+        "The minimum of a box can't be greater than its maximum",
+    )
+    turso.quit()
+
+
 def cleanup():
     if os.path.exists("testing/vfs.db"):
         os.remove("testing/vfs.db")
@@ -965,6 +1106,122 @@ def test_module_list(turso_shell, ext_path, module_name):
     )
 
 
+def test_icu():
+    turso = TestTursoShell()
+    turso.execute_dot(".load ./target/debug/liblimbo_icu")
+    turso.run_test_fn(
+        "SELECT icu_upper('straße'), icu_lower('ÉCOLE'), icu_upper('i', 'tr');",
+        lambda res: res == "STRASSE|école|İ",
+        "Unicode case mapping",
+    )
+    turso.run_test_fn(
+        "SELECT icu_like('élan%', 'ÉLAN VITAL'), icu_like('a%', 'b');",
+        lambda res: res == "1|0",
+        "Unicode LIKE ignores case",
+    )
+    turso.run_test_fn(
+        "CREATE TABLE words (w TEXT COLLATE unicode_nocase);",
+        null,
+        "Create a table with a collation from the extension",
+    )
+    turso.run_test_fn(
+        "INSERT INTO words VALUES ('zebra'), ('éclair'), ('Apple'), ('banana');",
+        null,
+    )
+    turso.run_test_fn(
+        "SELECT w FROM words ORDER BY w;",
+        lambda res: res == "Apple\nbanana\néclair\nzebra",
+        "Column collation sorts accented letters with their base letter",
+    )
+    turso.run_test_fn(
+        "SELECT w FROM words WHERE w = 'APPLE';",
+        lambda res: res == "Apple",
+        "Column collation compares without case",
+    )
+    turso.run_test_fn(
+        "SELECT w FROM words ORDER BY w COLLATE unicode_sv;",
+        lambda res: res == "Apple\nbanana\néclair\nzebra",
+        "Locale collation in ORDER BY",
+    )
+    turso.run_test_fn(
+        "SELECT 'ä' < 'z' COLLATE unicode_sv, 'ä' < 'z' COLLATE unicode_de;",
+        lambda res: res == "0|1",
+        "Locale collations follow the rules of the language",
+    )
+    turso.quit()
+
+
+def test_decimal():
+    turso = TestTursoShell()
+    turso.execute_dot(".load ./target/debug/liblimbo_decimal")
+    turso.run_test_fn(
+        "SELECT decimal_add('0.1', '0.2'), decimal_sub(1, '0.99'), decimal_mul('1.1', '1.1');",
+        lambda res: res == "0.3|0.01|1.21",
+        "Decimal arithmetic is exact",
+    )
+    turso.run_test_fn(
+        "SELECT decimal_cmp('2', '10'), decimal('007.50'), decimal_exp('125'), decimal_pow2(-3);",
+        lambda res: res == "-1|7.5|1.25e+02|0.125",
+        "Decimal comparison and formatting",
+    )
+    turso.run_test_fn(
+        "SELECT decimal(0.1);",
+        lambda res: res == "0.1000000000000000055511151231257827021181583404541015625",
+        "Floats are converted exactly",
+    )
+    turso.run_test_fn(
+        "SELECT decimal_add('abc', 1), decimal(NULL);",
+        lambda res: res == "|",
+        "Text that isn't a decimal gives NULL",
+    )
+    turso.run_test_fn(
+        "CREATE TABLE prices (amount TEXT COLLATE decimal);",
+        null,
+        "Create a table with the decimal collation",
+    )
+    turso.run_test_fn(
+        "INSERT INTO prices VALUES ('10.05'), ('9.99'), ('100'), ('0.01');",
+        null,
+    )
+    turso.run_test_fn(
+        "SELECT decimal_sum(amount) FROM prices;",
+        lambda res: res == "120.05",
+        "decimal_sum adds exactly",
+    )
+    turso.run_test_fn(
+        "SELECT amount FROM prices ORDER BY amount;",
+        lambda res: res == "0.01\n9.99\n10.05\n100",
+        "decimal collation sorts numerically",
+    )
+    turso.quit()
+
+
+def test_ieee754():
+    turso = TestTursoShell()
+    turso.execute_dot(".load ./target/debug/liblimbo_ieee754")
+    turso.run_test_fn(
+        "SELECT ieee754(0.75), ieee754_mantissa(0.1), ieee754_exponent(0.1), ieee754(3, -2);",
+        lambda res: res == "ieee754(3,-2)|3602879701896397|-55|0.75",
+        "Floats are split into mantissa and exponent",
+    )
+    turso.run_test_fn(
+        "SELECT hex(ieee754_to_blob(1.0)), ieee754_from_blob(x'3ff0000000000000');",
+        lambda res: res == "3FF0000000000000|1.0",
+        "Floats round trip through blobs",
+    )
+    turso.run_test_fn(
+        "SELECT hex(varint_encode(128)), varint_decode(x'8100'), varint_decode(x'00ff7f', 1);",
+        lambda res: res == "8100|128|16383",
+        "Varints are encoded and decoded",
+    )
+    turso.run_test_fn(
+        "SELECT varint_decode(x'81') IS NULL, ieee754(NULL) IS NULL;",
+        lambda res: res == "1|1",
+        "Incomplete varints and NULL give NULL",
+    )
+    turso.quit()
+
+
 def main():
     try:
         test_regexp()
@@ -978,6 +1235,11 @@ def main():
         test_sqlite_vfs_compat()
         test_kv()
         test_csv()
+        test_zipfile()
+        test_rtree()
+        test_icu()
+        test_decimal()
+        test_ieee754()
         test_tablestats()
         test_fuzzy()
     except Exception as e:
@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use turso_core::{Database, MemoryIO};
+
+/// A value returned by a query, independent of the engine that produced it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+/// A database engine the sqllogictest records can be run against.
+pub trait Backend {
+    /// Name matched against `skipif`/`onlyif` conditions.
+    fn engine(&self) -> &'static str;
+
+    fn execute(&mut self, sql: &str) -> Result<(), String>;
+
+    fn query(&mut self, sql: &str) -> Result<Vec<Vec<Value>>, String>;
+}
+
+pub struct TursoBackend {
+    conn: Arc<turso_core::Connection>,
+}
+
+impl TursoBackend {
+    pub fn new() -> anyhow::Result<Self> {
+        let io = Arc::new(MemoryIO::new());
+        let db = Database::open_file(io, ":memory:")?;
+        let conn = db.connect()?;
+        Ok(Self { conn })
+    }
+}
+
+impl Backend for TursoBackend {
+    fn engine(&self) -> &'static str {
+        // Records are written against SQLite, and limbo aims to be a drop-in replacement.
+        "sqlite"
+    }
+
+    fn execute(&mut self, sql: &str) -> Result<(), String> {
+        self.conn.execute(sql).map_err(|e| e.to_string())
+    }
+
+    fn query(&mut self, sql: &str) -> Result<Vec<Vec<Value>>, String> {
+        let mut stmt = self.conn.prepare(sql).map_err(|e| e.to_string())?;
+        let rows = stmt.run_collect_rows().map_err(|e| e.to_string())?;
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|value| match value {
+                        turso_core::Value::Null => Value::Null,
+                        turso_core::Value::Integer(i) => Value::Integer(i),
+                        turso_core::Value::Float(f) => Value::Real(f),
+                        turso_core::Value::Text(t) => Value::Text(t.as_str().to_string()),
+                        turso_core::Value::Blob(b) => Value::Blob(b),
+                    })
+                    .collect()
+            })
+            .collect())
+    }
+}
+
+pub struct SqliteBackend {
+    conn: rusqlite::Connection,
+}
+
+impl SqliteBackend {
+    pub fn new() -> anyhow::Result<Self> {
+        Ok(Self {
+            conn: rusqlite::Connection::open_in_memory()?,
+        })
+    }
+}
+
+impl Backend for SqliteBackend {
+    fn engine(&self) -> &'static str {
+        "sqlite"
+    }
+
+    fn execute(&mut self, sql: &str) -> Result<(), String> {
+        self.conn.execute_batch(sql).map_err(|e| e.to_string())
+    }
+
+    fn query(&mut self, sql: &str) -> Result<Vec<Vec<Value>>, String> {
+        let mut stmt = self.conn.prepare(sql).map_err(|e| e.to_string())?;
+        let columns = stmt.column_count();
+        let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+        let mut result = Vec::new();
+        while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+            let mut values = Vec::with_capacity(columns);
+            for i in 0..columns {
+                let value = match row.get_ref(i).map_err(|e| e.to_string())? {
+                    rusqlite::types::ValueRef::Null => Value::Null,
+                    rusqlite::types::ValueRef::Integer(i) => Value::Integer(i),
+                    rusqlite::types::ValueRef::Real(f) => Value::Real(f),
+                    rusqlite::types::ValueRef::Text(t) => {
+                        Value::Text(String::from_utf8_lossy(t).into_owned())
+                    }
+                    rusqlite::types::ValueRef::Blob(b) => Value::Blob(b.to_vec()),
+                };
+                values.push(value);
+            }
+            result.push(values);
+        }
+        Ok(result)
+    }
+}
@@ -0,0 +1,113 @@
+mod backend;
+mod parser;
+mod runner;
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use backend::{Backend, SqliteBackend, TursoBackend};
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+#[command(name = "turso_sqllogictest")]
+#[command(about = "Run sqllogictest files against Limbo", long_about = None)]
+struct Opts {
+    /// Test files or directories containing `.test`/`.slt` files
+    #[clap(required = true)]
+    paths: Vec<PathBuf>,
+    /// Also run every query on rusqlite and fail when the results differ
+    #[clap(long)]
+    compare_sqlite: bool,
+    /// Run the files against rusqlite only, useful to validate a corpus
+    #[clap(long, conflicts_with = "compare_sqlite")]
+    sqlite_only: bool,
+    /// Stop at the first failure in each file
+    #[clap(long)]
+    fail_fast: bool,
+    /// Maximum number of failures printed per file
+    #[clap(long, default_value_t = 10)]
+    max_reported_failures: usize,
+}
+
+fn main() -> anyhow::Result<()> {
+    let opts = Opts::parse();
+
+    let mut files = Vec::new();
+    for path in &opts.paths {
+        if path.is_file() {
+            files.push(path.clone());
+        } else {
+            collect_test_files(path, &mut files)?;
+        }
+    }
+    files.sort();
+
+    let (mut passed, mut skipped, mut failed, mut failed_files) = (0, 0, 0, 0);
+    for file in &files {
+        let input = std::fs::read_to_string(file)
+            .with_context(|| format!("failed to read {}", file.display()))?;
+        let records =
+            parser::parse(&input).with_context(|| format!("failed to parse {}", file.display()))?;
+
+        let mut backend: Box<dyn Backend> = if opts.sqlite_only {
+            Box::new(SqliteBackend::new()?)
+        } else {
+            Box::new(TursoBackend::new()?)
+        };
+        let mut reference = if opts.compare_sqlite {
+            Some(SqliteBackend::new()?)
+        } else {
+            None
+        };
+        let summary = runner::run_records(
+            &records,
+            backend.as_mut(),
+            reference.as_mut().map(|r| r as &mut dyn Backend),
+            opts.fail_fast,
+        );
+
+        passed += summary.passed;
+        skipped += summary.skipped;
+        failed += summary.failures.len();
+        if summary.failures.is_empty() {
+            println!("PASS {} ({} records)", file.display(), summary.passed);
+            continue;
+        }
+        failed_files += 1;
+        println!(
+            "FAIL {} ({} passed, {} failed)",
+            file.display(),
+            summary.passed,
+            summary.failures.len()
+        );
+        for failure in summary.failures.iter().take(opts.max_reported_failures) {
+            println!("  {}:{}: {}", file.display(), failure.line, failure.message);
+            for line in failure.sql.lines() {
+                println!("    {line}");
+            }
+        }
+    }
+
+    println!(
+        "\n{} files, {passed} records passed, {failed} failed, {skipped} skipped",
+        files.len()
+    );
+    if failed_files > 0 {
+        anyhow::bail!("{failed_files} files had failures");
+    }
+    Ok(())
+}
+
+fn collect_test_files(path: &Path, files: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    if path.is_dir() {
+        for entry in std::fs::read_dir(path)? {
+            collect_test_files(&entry?.path(), files)?;
+        }
+    } else if path
+        .extension()
+        .is_some_and(|ext| ext == "test" || ext == "slt")
+    {
+        files.push(path.to_path_buf());
+    }
+    Ok(())
+}
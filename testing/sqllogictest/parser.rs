@@ -0,0 +1,288 @@
+//! Parser for the sqllogictest file format.
+//!
+//! See <https://www.sqlite.org/sqllogictest/doc/trunk/about.wiki> for the description of the
+//! format. Records are separated by blank lines and each record is either a `statement`, a
+//! `query` or a control record such as `hash-threshold` or `halt`.
+
+use anyhow::{bail, Context};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Text,
+    Integer,
+    Real,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortMode {
+    NoSort,
+    RowSort,
+    ValueSort,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expected {
+    /// One value per line, in the order produced by the query after sorting.
+    Values(Vec<String>),
+    /// `N values hashing to <md5>`, used for large result sets.
+    Hash { count: usize, hash: String },
+}
+
+/// `skipif`/`onlyif` condition attached to a record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Condition {
+    SkipIf(String),
+    OnlyIf(String),
+}
+
+impl Condition {
+    /// Returns true if a record with this condition should run on an engine named `engine`.
+    pub fn applies_to(&self, engine: &str) -> bool {
+        match self {
+            Condition::SkipIf(name) => !name.eq_ignore_ascii_case(engine),
+            Condition::OnlyIf(name) => name.eq_ignore_ascii_case(engine),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordKind {
+    Statement {
+        sql: String,
+        expect_error: bool,
+    },
+    Query {
+        sql: String,
+        types: Vec<ColumnType>,
+        sort: SortMode,
+        label: Option<String>,
+        expected: Expected,
+    },
+    HashThreshold(usize),
+    Halt,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    /// 1-based line number of the record header.
+    pub line: usize,
+    pub conditions: Vec<Condition>,
+    pub kind: RecordKind,
+}
+
+pub fn parse(input: &str) -> anyhow::Result<Vec<Record>> {
+    let lines = input.lines().collect::<Vec<_>>();
+    let mut records = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim_end();
+        if line.trim().is_empty() || line.starts_with('#') {
+            i += 1;
+            continue;
+        }
+
+        let mut conditions = Vec::new();
+        while let Some(condition) = parse_condition(lines[i]) {
+            conditions.push(condition);
+            i += 1;
+            if i == lines.len() {
+                bail!("line {i}: condition without a record");
+            }
+        }
+
+        let header_line = i + 1;
+        let header = lines[i].split_whitespace().collect::<Vec<_>>();
+        i += 1;
+        let kind = match header.as_slice() {
+            ["statement", mode, ..] => {
+                let expect_error = match *mode {
+                    "ok" => false,
+                    "error" => true,
+                    other => bail!("line {header_line}: unknown statement mode '{other}'"),
+                };
+                let (sql, next) = read_sql(&lines, i);
+                i = next;
+                RecordKind::Statement { sql, expect_error }
+            }
+            ["query", types, rest @ ..] => {
+                let types = types
+                    .chars()
+                    .map(|c| match c {
+                        'T' => Ok(ColumnType::Text),
+                        'I' => Ok(ColumnType::Integer),
+                        'R' => Ok(ColumnType::Real),
+                        other => bail!("line {header_line}: unknown column type '{other}'"),
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                let sort = match rest.first() {
+                    None | Some(&"nosort") => SortMode::NoSort,
+                    Some(&"rowsort") => SortMode::RowSort,
+                    Some(&"valuesort") => SortMode::ValueSort,
+                    Some(other) => bail!("line {header_line}: unknown sort mode '{other}'"),
+                };
+                let label = rest.get(1).map(|label| label.to_string());
+                let (sql, next) = read_sql(&lines, i);
+                i = next;
+                let mut values = Vec::new();
+                if i < lines.len() && lines[i].trim_end() == "----" {
+                    i += 1;
+                    while i < lines.len() && !lines[i].trim().is_empty() {
+                        values.push(lines[i].trim_end().to_string());
+                        i += 1;
+                    }
+                }
+                let expected = parse_expected(values)
+                    .with_context(|| format!("line {header_line}: invalid query result"))?;
+                RecordKind::Query {
+                    sql,
+                    types,
+                    sort,
+                    label,
+                    expected,
+                }
+            }
+            ["hash-threshold", n] => RecordKind::HashThreshold(
+                n.parse()
+                    .with_context(|| format!("line {header_line}: invalid hash-threshold"))?,
+            ),
+            ["halt"] => RecordKind::Halt,
+            _ => bail!(
+                "line {header_line}: unrecognized record '{}'",
+                lines[header_line - 1]
+            ),
+        };
+        records.push(Record {
+            line: header_line,
+            conditions,
+            kind,
+        });
+    }
+    Ok(records)
+}
+
+fn parse_condition(line: &str) -> Option<Condition> {
+    let mut parts = line.split_whitespace();
+    let condition = match parts.next()? {
+        "skipif" => Condition::SkipIf,
+        "onlyif" => Condition::OnlyIf,
+        _ => return None,
+    };
+    Some(condition(parts.next()?.to_string()))
+}
+
+/// Reads SQL lines until a blank line or the `----` separator.
+fn read_sql(lines: &[&str], mut i: usize) -> (String, usize) {
+    let mut sql = Vec::new();
+    while i < lines.len() && !lines[i].trim().is_empty() && lines[i].trim_end() != "----" {
+        sql.push(lines[i]);
+        i += 1;
+    }
+    (sql.join("\n"), i)
+}
+
+fn parse_expected(values: Vec<String>) -> anyhow::Result<Expected> {
+    if let [line] = values.as_slice() {
+        let parts = line.split_whitespace().collect::<Vec<_>>();
+        if let [count, "values", "hashing", "to", hash] = parts.as_slice() {
+            return Ok(Expected::Hash {
+                count: count.parse()?,
+                hash: hash.to_string(),
+            });
+        }
+    }
+    Ok(Expected::Values(values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_statement_and_query() {
+        let records = parse(
+            "# comment\n\
+             statement ok\n\
+             CREATE TABLE t1(a INTEGER, b TEXT)\n\
+             \n\
+             statement error\n\
+             SELECT * FROM missing\n\
+             \n\
+             query IT rowsort label-1\n\
+             SELECT a, b\n\
+             FROM t1\n\
+             ----\n\
+             1\n\
+             one\n",
+        )
+        .unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(
+            records[0].kind,
+            RecordKind::Statement {
+                sql: "CREATE TABLE t1(a INTEGER, b TEXT)".to_string(),
+                expect_error: false,
+            }
+        );
+        assert!(matches!(
+            records[1].kind,
+            RecordKind::Statement {
+                expect_error: true,
+                ..
+            }
+        ));
+        assert_eq!(records[2].line, 8);
+        assert_eq!(
+            records[2].kind,
+            RecordKind::Query {
+                sql: "SELECT a, b\nFROM t1".to_string(),
+                types: vec![ColumnType::Integer, ColumnType::Text],
+                sort: SortMode::RowSort,
+                label: Some("label-1".to_string()),
+                expected: Expected::Values(vec!["1".to_string(), "one".to_string()]),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_hash_and_conditions() {
+        let records = parse(
+            "hash-threshold 8\n\
+             \n\
+             skipif mysql\n\
+             onlyif sqlite\n\
+             query I valuesort\n\
+             SELECT a FROM t1\n\
+             ----\n\
+             30 values hashing to 3c13dee48d9356ae19af2515e05e6b54\n\
+             \n\
+             halt\n",
+        )
+        .unwrap();
+        assert_eq!(records[0].kind, RecordKind::HashThreshold(8));
+        assert_eq!(
+            records[1].conditions,
+            vec![
+                Condition::SkipIf("mysql".to_string()),
+                Condition::OnlyIf("sqlite".to_string())
+            ]
+        );
+        assert!(records[1].conditions.iter().all(|c| c.applies_to("sqlite")));
+        let RecordKind::Query { expected, .. } = &records[1].kind else {
+            panic!("expected a query record");
+        };
+        assert_eq!(
+            *expected,
+            Expected::Hash {
+                count: 30,
+                hash: "3c13dee48d9356ae19af2515e05e6b54".to_string(),
+            }
+        );
+        assert_eq!(records[2].kind, RecordKind::Halt);
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_record() {
+        assert!(parse("frobnicate\n").is_err());
+        assert!(parse("query X\nSELECT 1\n").is_err());
+    }
+}
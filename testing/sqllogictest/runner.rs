@@ -0,0 +1,284 @@
+use crate::backend::{Backend, Value};
+use crate::parser::{ColumnType, Expected, Record, RecordKind, SortMode};
+
+#[derive(Debug)]
+pub struct Failure {
+    pub line: usize,
+    pub sql: String,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub passed: usize,
+    pub skipped: usize,
+    pub failures: Vec<Failure>,
+}
+
+/// Runs the records of a single file against `backend`. When `reference` is given, every
+/// query is also executed against it and the two result sets must match.
+pub fn run_records(
+    records: &[Record],
+    backend: &mut dyn Backend,
+    mut reference: Option<&mut dyn Backend>,
+    fail_fast: bool,
+) -> Summary {
+    let mut summary = Summary::default();
+    for record in records {
+        if !record
+            .conditions
+            .iter()
+            .all(|condition| condition.applies_to(backend.engine()))
+        {
+            summary.skipped += 1;
+            continue;
+        }
+        let result = match &record.kind {
+            // The threshold only matters when generating expected results, the expected
+            // format of every query is already recorded in the file.
+            RecordKind::HashThreshold(_) => continue,
+            RecordKind::Halt => break,
+            RecordKind::Statement { sql, expect_error } => {
+                if let Some(reference) = reference.as_deref_mut() {
+                    // Keep the reference database in sync, its result is not checked.
+                    let _ = reference.execute(sql);
+                }
+                run_statement(backend, sql, *expect_error).map_err(|message| (sql, message))
+            }
+            RecordKind::Query {
+                sql,
+                types,
+                sort,
+                expected,
+                ..
+            } => run_query(
+                backend,
+                reference.as_deref_mut(),
+                sql,
+                types,
+                *sort,
+                expected,
+            )
+            .map_err(|message| (sql, message)),
+        };
+        match result {
+            Ok(()) => summary.passed += 1,
+            Err((sql, message)) => {
+                summary.failures.push(Failure {
+                    line: record.line,
+                    sql: sql.clone(),
+                    message,
+                });
+                if fail_fast {
+                    break;
+                }
+            }
+        }
+    }
+    summary
+}
+
+fn run_statement(backend: &mut dyn Backend, sql: &str, expect_error: bool) -> Result<(), String> {
+    match (backend.execute(sql), expect_error) {
+        (Ok(()), false) | (Err(_), true) => Ok(()),
+        (Ok(()), true) => Err("statement succeeded but an error was expected".to_string()),
+        (Err(err), false) => Err(format!("statement failed: {err}")),
+    }
+}
+
+fn run_query(
+    backend: &mut dyn Backend,
+    reference: Option<&mut dyn Backend>,
+    sql: &str,
+    types: &[ColumnType],
+    sort: SortMode,
+    expected: &Expected,
+) -> Result<(), String> {
+    let rows = backend
+        .query(sql)
+        .map_err(|err| format!("query failed: {err}"))?;
+    let actual = format_results(&rows, types, sort);
+
+    if let Some(reference) = reference {
+        let reference_rows = reference
+            .query(sql)
+            .map_err(|err| format!("query failed on {}: {err}", reference.engine()))?;
+        let reference_values = format_results(&reference_rows, types, sort);
+        if actual != reference_values {
+            return Err(format!(
+                "result differs from {}:\n  actual:    {actual:?}\n  reference: {reference_values:?}",
+                reference.engine()
+            ));
+        }
+    }
+
+    match expected {
+        Expected::Values(values) => compare_values(&actual, values),
+        Expected::Hash { count, hash } => {
+            let actual_hash = hash_values(&actual);
+            if actual.len() != *count || actual_hash != *hash {
+                Err(format!(
+                    "expected {count} values hashing to {hash}, got {} values hashing to {actual_hash}",
+                    actual.len()
+                ))
+            } else {
+                Ok(())
+            }
+        }
+    }
+}
+
+fn compare_values(actual: &[String], expected: &[String]) -> Result<(), String> {
+    if actual == expected {
+        return Ok(());
+    }
+    if let Some(i) = actual
+        .iter()
+        .zip(expected.iter())
+        .position(|(actual, expected)| actual != expected)
+    {
+        return Err(format!(
+            "value {i} differs: expected '{}', got '{}'",
+            expected[i], actual[i]
+        ));
+    }
+    Err(format!(
+        "expected {} values, got {}",
+        expected.len(),
+        actual.len()
+    ))
+}
+
+/// Formats a result set the way sqllogictest prints it: one value per entry, sorted
+/// according to `sort`.
+pub fn format_results(rows: &[Vec<Value>], types: &[ColumnType], sort: SortMode) -> Vec<String> {
+    let mut rows = rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .enumerate()
+                .map(|(i, value)| format_value(value, types.get(i).copied()))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    match sort {
+        SortMode::NoSort => rows.into_iter().flatten().collect(),
+        SortMode::RowSort => {
+            rows.sort();
+            rows.into_iter().flatten().collect()
+        }
+        SortMode::ValueSort => {
+            let mut values = rows.into_iter().flatten().collect::<Vec<_>>();
+            values.sort();
+            values
+        }
+    }
+}
+
+fn format_value(value: &Value, column_type: Option<ColumnType>) -> String {
+    match (value, column_type) {
+        (Value::Null, _) => "NULL".to_string(),
+        (Value::Integer(i), Some(ColumnType::Real)) => format!("{:.3}", *i as f64),
+        (Value::Integer(i), _) => i.to_string(),
+        (Value::Real(f), Some(ColumnType::Integer)) => (*f as i64).to_string(),
+        (Value::Real(f), _) => format!("{f:.3}"),
+        (Value::Text(t), Some(ColumnType::Integer)) => leading_integer(t).to_string(),
+        (Value::Text(t), Some(ColumnType::Real)) => {
+            format!("{:.3}", t.trim().parse::<f64>().unwrap_or(0.0))
+        }
+        (Value::Text(t), _) if t.is_empty() => "(empty)".to_string(),
+        (Value::Text(t), _) => t
+            .chars()
+            .map(|c| if (' '..='~').contains(&c) { c } else { '@' })
+            .collect(),
+        (Value::Blob(b), _) if b.is_empty() => "(empty)".to_string(),
+        (Value::Blob(b), _) => b
+            .iter()
+            .map(|&c| {
+                if (b' '..=b'~').contains(&c) {
+                    c as char
+                } else {
+                    '@'
+                }
+            })
+            .collect(),
+    }
+}
+
+/// Mirrors `atoi()`, which sqllogictest uses to render text in integer columns.
+fn leading_integer(text: &str) -> i64 {
+    let text = text.trim_start();
+    let end = text
+        .char_indices()
+        .find(|&(i, c)| !(c.is_ascii_digit() || (i == 0 && (c == '-' || c == '+'))))
+        .map_or(text.len(), |(i, _)| i);
+    text[..end].parse().unwrap_or(0)
+}
+
+fn hash_values(values: &[String]) -> String {
+    let mut context = md5::Context::new();
+    for value in values {
+        context.consume(value.as_bytes());
+        context.consume(b"\n");
+    }
+    format!("{:x}", context.compute())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_value() {
+        assert_eq!(format_value(&Value::Null, Some(ColumnType::Text)), "NULL");
+        assert_eq!(
+            format_value(&Value::Integer(3), Some(ColumnType::Real)),
+            "3.000"
+        );
+        assert_eq!(
+            format_value(&Value::Real(2.5), Some(ColumnType::Integer)),
+            "2"
+        );
+        assert_eq!(
+            format_value(&Value::Text(String::new()), Some(ColumnType::Text)),
+            "(empty)"
+        );
+        assert_eq!(
+            format_value(&Value::Text("a\tb".to_string()), Some(ColumnType::Text)),
+            "a@b"
+        );
+        assert_eq!(
+            format_value(&Value::Text("12abc".to_string()), Some(ColumnType::Integer)),
+            "12"
+        );
+    }
+
+    #[test]
+    fn test_format_results_sorting() {
+        let rows = vec![
+            vec![Value::Integer(2), Value::Text("b".to_string())],
+            vec![Value::Integer(1), Value::Text("c".to_string())],
+        ];
+        let types = [ColumnType::Integer, ColumnType::Text];
+        assert_eq!(
+            format_results(&rows, &types, SortMode::NoSort),
+            vec!["2", "b", "1", "c"]
+        );
+        assert_eq!(
+            format_results(&rows, &types, SortMode::RowSort),
+            vec!["1", "c", "2", "b"]
+        );
+        assert_eq!(
+            format_results(&rows, &types, SortMode::ValueSort),
+            vec!["1", "2", "b", "c"]
+        );
+    }
+
+    #[test]
+    fn test_hash_values() {
+        assert_eq!(
+            hash_values(&["1".to_string(), "2".to_string()]),
+            format!("{:x}", md5::compute(b"1\n2\n"))
+        );
+    }
+}
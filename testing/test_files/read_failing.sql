@@ -0,0 +1,5 @@
+BEGIN TRANSACTION;
+INSERT INTO t VALUES(3,'three');
+INSERT INTO t VALUES(1,'duplicate');
+INSERT INTO t VALUES(4,'four');
+COMMIT;
@@ -1,5 +1,6 @@
 pub mod grammar_generator;
 pub mod journal_mode;
+pub mod no_panic;
 pub mod orderby_collation;
 pub mod rowid_alias;
 pub mod test_join_optimizer;
@@ -10,6 +11,10 @@ mod fuzz_tests {
     use rand::Rng;
     use rand_chacha::ChaCha8Rng;
     use rusqlite::{params, types::Value};
+    use sql_generation::{
+        generation::{pick, ArbitraryFrom},
+        model::table::{ColumnType, SimValue},
+    };
     use std::{collections::HashSet, io::Write};
     use tempfile::{NamedTempFile, TempDir};
 
@@ -22,6 +27,7 @@ mod fuzz_tests {
     use super::grammar_generator::{const_str, rand_int, rand_str, GrammarGenerator};
 
     use super::grammar_generator::SymbolHandle;
+    use super::rowid_alias::FuzzTestContext;
 
     /// [See this issue for more info](https://github.com/tursodatabase/turso/issues/1763)
     #[turso_macros::test(mvcc)]
@@ -4085,6 +4091,91 @@ mod fuzz_tests {
         }
     }
 
+    #[turso_macros::test(mvcc)]
+    pub fn bitwise_expression_fuzz(db: TempDatabase) {
+        let _ = env_logger::try_init();
+        let g = GrammarGenerator::new();
+        let (expr, expr_builder) = g.create_handle();
+        let (bin_op, bin_op_builder) = g.create_handle();
+        let (unary_op, unary_op_builder) = g.create_handle();
+        let (paren, paren_builder) = g.create_handle();
+
+        paren_builder
+            .concat("")
+            .push_str("(")
+            .push(expr)
+            .push_str(")")
+            .build();
+
+        unary_op_builder
+            .concat(" ")
+            .push_str("~")
+            .push(expr)
+            .build();
+
+        bin_op_builder
+            .concat(" ")
+            .push(expr)
+            .push(
+                g.create()
+                    .choice()
+                    .options_str(["&", "|", "<<", ">>"])
+                    .build(),
+            )
+            .push(expr)
+            .build();
+
+        // Operands that exercise the conversion to 64-bit integers: out of range numbers
+        // saturate, text is converted using its numeric prefix and shifts may be negative
+        // or larger than 64.
+        let operand = g
+            .create()
+            .choice()
+            .options_str([
+                "9223372036854775807",
+                "-9223372036854775808",
+                "64",
+                "-64",
+                "5.9",
+                "-5.9",
+                "1e19",
+                "-1e19",
+                "'12.7'",
+                "'1e3'",
+                "' 7 '",
+                "'abc'",
+                "x'31'",
+                "NULL",
+            ])
+            .build();
+
+        expr_builder
+            .choice()
+            .option_w(unary_op, 1.0)
+            .option_w(bin_op, 2.0)
+            .option_w(paren, 1.0)
+            .option_w(operand, 1.0)
+            .option_symbol_w(rand_int(-70..70), 1.0)
+            .build();
+
+        let sql = g.create().concat(" ").push_str("SELECT").push(expr).build();
+
+        let limbo_conn = db.connect_limbo();
+        let sqlite_conn = rusqlite::Connection::open_in_memory().unwrap();
+
+        let (mut rng, seed) = rng_from_time_or_env();
+        log::info!("seed: {seed}");
+        for _ in 0..1024 {
+            let query = g.generate(&mut rng, sql, 50);
+            let limbo = limbo_exec_rows(&limbo_conn, &query);
+            let sqlite = sqlite_exec_rows(&sqlite_conn, &query);
+            assert_eq!(
+                limbo, sqlite,
+                "query: {query}, limbo: {limbo:?}, sqlite: {sqlite:?} seed: {seed}"
+            );
+        }
+    }
+
     #[turso_macros::test(mvcc)]
     pub fn fuzz_ex(db: TempDatabase) {
         let _ = env_logger::try_init();
@@ -4295,6 +4386,23 @@ mod fuzz_tests {
                     .push_str(")")
                     .build(),
             )
+            .option(
+                g.create()
+                    .concat("")
+                    .push(g.create().choice().options_str(["instr"]).build())
+                    .push_str("(")
+                    .push(g.create().concat("").push(expr).repeat(2..3, ", ").build())
+                    .push_str(")")
+                    .build(),
+            )
+            .option(
+                g.create()
+                    .concat("")
+                    .push_str("unhex(hex(")
+                    .push(expr)
+                    .push_str("))")
+                    .build(),
+            )
             .option(
                 g.create()
                     .concat("")
@@ -5304,6 +5412,116 @@ mod fuzz_tests {
         }
     }
 
+    #[turso_macros::test(mvcc)]
+    pub fn fuzz_distinct_aggregates(db: TempDatabase) {
+        let limbo_conn = db.connect_limbo();
+        let sqlite_conn = rusqlite::Connection::open_in_memory().unwrap();
+
+        let (mut rng, seed) = rng_from_time_or_env();
+        tracing::info!("fuzz_distinct_aggregates seed: {}", seed);
+
+        let create_table = "CREATE TABLE t (g, x, y TEXT COLLATE NOCASE, z)";
+        limbo_exec_rows(&limbo_conn, create_table);
+        sqlite_exec_rows(&sqlite_conn, create_table);
+
+        // The values of z are picked from a small pool made by the simulator's value
+        // generators, so that they repeat.
+        let context = FuzzTestContext::new();
+        let column_types = [
+            ColumnType::Integer,
+            ColumnType::Float,
+            ColumnType::Text,
+            ColumnType::Blob,
+        ];
+        let z_pool = (0..8)
+            .map(|_| {
+                let column_type = *pick(&column_types, &mut rng);
+                SimValue::arbitrary_from(&mut rng, &context, column_type)
+            })
+            .collect::<Vec<_>>();
+        let z_pool = z_pool.iter().collect::<Vec<_>>();
+
+        // Narrow value ranges so that every group has plenty of duplicates, mixing integers,
+        // reals that compare equal to them, text and NULLs.
+        for _ in 0..500 {
+            let x = match rng.random_range(0..4) {
+                0 => rng.random_range(0..5).to_string(),
+                1 => format!("{}.0", rng.random_range(0..5)),
+                2 => format!("'{}'", rng.random_range(0..5)),
+                _ => "NULL".to_string(),
+            };
+            let y = match rng.random_range(0..3) {
+                0 => "'a'",
+                1 => "'A'",
+                _ => "'b'",
+            };
+            let z = SimValue::arbitrary_from(&mut rng, &context, &z_pool);
+            let query = format!(
+                "INSERT INTO t VALUES ({}, {x}, {y}, {z})",
+                rng.random_range(0..4)
+            );
+            limbo_exec_rows(&limbo_conn, &query);
+            sqlite_exec_rows(&sqlite_conn, &query);
+        }
+
+        let aggregates = [
+            "count(DISTINCT x)",
+            "sum(DISTINCT x)",
+            "total(DISTINCT x)",
+            "avg(DISTINCT x)",
+            "min(DISTINCT x)",
+            "max(DISTINCT x)",
+            "count(DISTINCT y)",
+            "count(DISTINCT y COLLATE BINARY)",
+            "count(DISTINCT x + 1)",
+            "count(DISTINCT z)",
+            "min(DISTINCT z)",
+            "max(DISTINCT z)",
+        ];
+        for _ in 0..200 {
+            let num_aggs = rng.random_range(1..=3);
+            let aggs = (0..num_aggs)
+                .map(|_| aggregates[rng.random_range(0..aggregates.len())])
+                .collect::<Vec<_>>()
+                .join(", ");
+            let query = if rng.random_bool(0.5) {
+                format!("SELECT g, {aggs} FROM t GROUP BY g ORDER BY g")
+            } else {
+                format!("SELECT {aggs} FROM t")
+            };
+
+            let limbo = limbo_exec_rows(&limbo_conn, &query);
+            let sqlite = sqlite_exec_rows(&sqlite_conn, &query);
+
+            assert_eq!(limbo, sqlite, "seed: {seed}, query: {query}");
+        }
+
+        // Values that compare equal but print differently, like 1 and 1.0 or 'a' and 'A', are
+        // left out of the result columns, as either of them may be returned for the group.
+        let distinct_columns = ["g", "CAST(x AS TEXT)", "lower(y)", "z"];
+        for _ in 0..200 {
+            let num_cols = rng.random_range(1..=distinct_columns.len());
+            let cols = distinct_columns
+                .choose_multiple(&mut rng, num_cols)
+                .copied()
+                .collect::<Vec<_>>()
+                .join(", ");
+            let query = match rng.random_range(0..3) {
+                0 => format!("SELECT DISTINCT {cols} FROM t ORDER BY {cols}"),
+                1 => format!("SELECT DISTINCT {cols} FROM t WHERE g > 1 ORDER BY {cols}"),
+                _ => {
+                    let agg = aggregates[rng.random_range(0..aggregates.len())];
+                    format!("SELECT DISTINCT {agg} FROM t GROUP BY g ORDER BY 1")
+                }
+            };
+
+            let limbo = limbo_exec_rows(&limbo_conn, &query);
+            let sqlite = sqlite_exec_rows(&sqlite_conn, &query);
+
+            assert_eq!(limbo, sqlite, "seed: {seed}, query: {query}");
+        }
+    }
+
     #[turso_macros::test(mvcc)]
     fn fuzz_long_create_table_drop_table_alter_table(db: TempDatabase) {
         let limbo_conn = db.connect_limbo();
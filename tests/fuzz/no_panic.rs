@@ -0,0 +1,99 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use core_tester::common::{limbo_exec_rows_fallible, rng_from_time_or_env, TempDatabase};
+use rand::seq::IndexedRandom;
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+
+const SCHEMA: &[&str] = &[
+    "CREATE TABLE t (id INTEGER PRIMARY KEY, a TEXT, b REAL, c BLOB)",
+    "CREATE INDEX t_a ON t(a)",
+    "CREATE TABLE u (x INTEGER, y TEXT UNIQUE, z NOT NULL DEFAULT 0)",
+    "INSERT INTO t VALUES (1, 'one', 1.5, x'01'), (2, 'two', NULL, x''), (3, NULL, -3, NULL)",
+    "INSERT INTO u VALUES (1, 'a', 1), (2, 'b', 2)",
+];
+
+/// Statements that are mutated into the SQL that is run.
+const CORPUS: &[&str] = &[
+    "SELECT * FROM t WHERE id = 1",
+    "SELECT a, count(*) FROM t GROUP BY a HAVING count(*) > 1 ORDER BY 2 DESC LIMIT 3",
+    "SELECT t.a, u.y FROM t JOIN u ON t.id = u.x WHERE u.z > 0",
+    "SELECT a || b, abs(b), length(c), typeof(c) FROM t",
+    "SELECT * FROM t WHERE a IN (SELECT y FROM u) OR b BETWEEN 1 AND 2",
+    "SELECT CASE WHEN b > 0 THEN 'pos' ELSE 'neg' END FROM t",
+    "SELECT datetime('now', '+1 day'), substr(a, 2, 1), coalesce(a, b, c) FROM t",
+    "SELECT max(id) OVER (ORDER BY a) FROM t",
+    "WITH c AS (SELECT x FROM u) SELECT * FROM c UNION SELECT id FROM t",
+    "INSERT INTO u VALUES (3, 'c', 3)",
+    "INSERT INTO t (a, b) SELECT y, z FROM u",
+    "UPDATE t SET b = b * 2 WHERE a LIKE 'o%'",
+    "DELETE FROM u WHERE x > 2",
+    "CREATE TABLE v AS SELECT * FROM t",
+    "CREATE VIEW w AS SELECT a FROM t",
+    "ALTER TABLE u ADD COLUMN w TEXT",
+    "CREATE INDEX u_z ON u(z)",
+    "DROP TABLE IF EXISTS v",
+    "PRAGMA table_info(t)",
+    "BEGIN",
+    "COMMIT",
+];
+
+/// Whitespace separated tokens that are spliced into the statements.
+const VOCABULARY: &str =
+    "SELECT FROM WHERE AND OR NOT NULL IS IN ( ) , * + - / || = <> < >= -> ->> t u a b c \
+    id x y z 1 0 -1 9223372036854775807 1e308 0.5 's' x'ff' ? ?1 :p ORDER BY GROUP \
+    LIMIT OFFSET JOIN ON USING AS EXISTS CAST COLLATE NOCASE DISTINCT UNION ALL VALUES \
+    SET DEFAULT RETURNING OVER FILTER WITH RECURSIVE PRIMARY KEY UNIQUE CHECK";
+
+fn tokenize(sql: &str) -> Vec<String> {
+    let mut padded = String::with_capacity(sql.len() * 2);
+    for c in sql.chars() {
+        if matches!(c, '(' | ')' | ',') {
+            padded.push(' ');
+            padded.push(c);
+            padded.push(' ');
+        } else {
+            padded.push(c);
+        }
+    }
+    padded.split_whitespace().map(str::to_string).collect()
+}
+
+fn mutate(rng: &mut ChaCha8Rng, sql: &str) -> String {
+    let vocabulary = VOCABULARY.split_whitespace().collect::<Vec<_>>();
+    let mut tokens = tokenize(sql);
+    for _ in 0..rng.random_range(1..=4) {
+        let len = tokens.len();
+        let i = rng.random_range(0..len.max(1));
+        match rng.random_range(0..4) {
+            0 if len > 1 => {
+                tokens.remove(i);
+            }
+            1 if len > 0 => tokens.insert(i, tokens[i].clone()),
+            2 if len > 1 => tokens.swap(i, (i + 1) % len),
+            _ => tokens.insert(i.min(len), vocabulary.choose(rng).unwrap().to_string()),
+        }
+    }
+    tokens.join(" ")
+}
+
+/// Errors are fine, panics are not: mutated statements are mostly invalid, and the ones that
+/// do parse exercise planner and VDBE paths that valid SQL tends not to reach.
+#[test]
+pub fn fuzz_mutated_sql_does_not_panic() {
+    let (mut rng, seed) = rng_from_time_or_env();
+    println!("fuzz_mutated_sql_does_not_panic seed: {seed}");
+    let db = TempDatabase::new_empty();
+    let conn = db.connect_limbo();
+    for sql in SCHEMA {
+        conn.execute(sql).unwrap();
+    }
+
+    for _ in 0..2000 {
+        let sql = mutate(&mut rng, CORPUS.choose(&mut rng).unwrap());
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            limbo_exec_rows_fallible(&db, &conn, &sql)
+        }));
+        assert!(result.is_ok(), "panic on `{sql}`, seed: {seed}");
+    }
+}
@@ -25,13 +25,13 @@ fn rng_from_time_or_env() -> (ChaCha8Rng, u64) {
 
 // Our test context that implements GenerationContext
 #[derive(Debug, Clone)]
-struct FuzzTestContext {
+pub(crate) struct FuzzTestContext {
     opts: Opts,
     tables: Vec<Table>,
 }
 
 impl FuzzTestContext {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
             opts: Opts::default(),
             tables: Vec::new(),
@@ -0,0 +1,35 @@
+use crate::common::TempDatabase;
+use turso_core::LimboError;
+
+#[turso_macros::test]
+fn test_load_extension_disabled_by_default(db: TempDatabase) {
+    let conn = db.connect_limbo();
+    let err = conn
+        .execute("SELECT load_extension('./no_such_extension')")
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("extension loading is disabled"),
+        "unexpected error: {err}"
+    );
+}
+
+#[turso_macros::test]
+fn test_load_extension_enabled_per_connection(db: TempDatabase) {
+    let conn = db.connect_limbo();
+    let other_conn = db.connect_limbo();
+    conn.enable_load_extension(true);
+    assert!(conn.load_extension_enabled());
+    assert!(!other_conn.load_extension_enabled());
+
+    // Once enabled, loading fails only because the library does not exist.
+    let err = conn
+        .execute("SELECT load_extension('./no_such_extension')")
+        .unwrap_err();
+    assert!(
+        matches!(err, LimboError::ExtensionError(_)),
+        "unexpected error: {err}"
+    );
+
+    conn.enable_load_extension(false);
+    assert!(!conn.load_extension_enabled());
+}
@@ -26,6 +26,44 @@ fn test_wal_frame_count(db: TempDatabase) {
     assert_eq!(conn.wal_state().unwrap().max_frame, 15);
 }
 
+// TODO: mvcc
+#[turso_macros::test()]
+fn test_wal_hook_and_autocheckpoint(db: TempDatabase) {
+    let conn = db.connect_limbo();
+    let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let hook_calls = calls.clone();
+    conn.set_wal_hook(Some(Box::new(move |db_name, frames| {
+        hook_calls
+            .lock()
+            .unwrap()
+            .push((db_name.to_string(), frames));
+    })));
+
+    let rows: Vec<(i64,)> = conn.exec_rows("PRAGMA wal_autocheckpoint");
+    assert_eq!(rows, vec![(1000,)]);
+    conn.execute("PRAGMA wal_autocheckpoint = 2").unwrap();
+    let rows: Vec<(i64,)> = conn.exec_rows("PRAGMA wal_autocheckpoint");
+    assert_eq!(rows, vec![(2,)]);
+
+    conn.execute("CREATE TABLE t(x INTEGER PRIMARY KEY, y)")
+        .unwrap();
+    conn.execute("INSERT INTO t VALUES (1, 1)").unwrap();
+    // Read-only statements don't commit anything.
+    let _: Vec<(i64,)> = conn.exec_rows("SELECT x FROM t");
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec![("main".to_string(), 2), ("main".to_string(), 3)]
+    );
+
+    conn.set_wal_hook(None);
+    conn.execute("INSERT INTO t VALUES (2, 2)").unwrap();
+    assert_eq!(calls.lock().unwrap().len(), 2);
+
+    conn.execute("PRAGMA wal_autocheckpoint = -1").unwrap();
+    let rows: Vec<(i64,)> = conn.exec_rows("PRAGMA wal_autocheckpoint");
+    assert_eq!(rows, vec![(0,)]);
+}
+
 // TODO: mvcc
 #[turso_macros::test()]
 fn test_wal_frame_transfer_no_schema_changes(db: TempDatabase) {
@@ -381,3 +381,103 @@ fn test_vector_sparse_ivf_fuzz(tmp_db: TempDatabase) {
         }
     }
 }
+
+// TODO: cannot use MVCC as we use indexes here
+#[turso_macros::test]
+fn test_vector_dense_lsh_fuzz(tmp_db: TempDatabase) {
+    let _ = env_logger::try_init();
+
+    let opts = tmp_db.db_opts;
+    let flags = tmp_db.db_flags;
+
+    const DIMS: usize = 16;
+    const BITS: u32 = 6;
+
+    let (mut rng, _) = rng_from_time_or_env();
+    let mut operation = 0;
+    for (function, probe_distance) in [
+        ("vector_distance_cos", BITS),
+        ("vector_distance_l2", BITS),
+        ("vector_distance_cos", 1),
+        ("vector_distance_l2", 0),
+    ] {
+        let seed = rng.next_u64();
+        tracing::info!("======== seed: {} ========", seed);
+
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let builder = TempDatabase::builder()
+            .with_opts(opts)
+            .with_flags(flags)
+            .with_init_sql("CREATE TABLE t(key TEXT PRIMARY KEY, embedding)");
+        let simple_db = builder.clone().build();
+        let index_db = builder.build();
+        let simple_conn = simple_db.connect_limbo();
+        let index_conn = index_db.connect_limbo();
+        simple_conn.wal_auto_checkpoint_disable();
+        index_conn.wal_auto_checkpoint_disable();
+        index_conn
+            .execute(format!("CREATE INDEX t_idx ON t USING toy_vector_dense_lsh (embedding) WITH (bits = {BITS}, probe_distance = {probe_distance})"))
+            .unwrap();
+
+        let vector = |rng: &mut ChaCha8Rng| {
+            let values = (0..DIMS)
+                .map(|_| (rng.next_u32() as f32 / (u32::MAX as f32) - 0.5).to_string())
+                .collect::<Vec<_>>();
+            format!("[{}]", values.join(", "))
+        };
+
+        let mut keys = Vec::new();
+        for _ in 0..200 {
+            let choice = rng.next_u32() % 4;
+            operation += 1;
+            if choice == 0 {
+                let key = rng.next_u64().to_string();
+                let v = vector(&mut rng);
+                let sql = format!("INSERT INTO t VALUES ('{key}', vector32('{v}'))");
+                tracing::info!("({}) {}", operation, sql);
+                simple_conn.execute(&sql).unwrap();
+                index_conn.execute(sql).unwrap();
+                keys.push(key);
+            } else if choice == 1 && !keys.is_empty() {
+                let idx = rng.next_u32() as usize % keys.len();
+                let key = &keys[idx];
+                let v = vector(&mut rng);
+                let sql = format!("UPDATE t SET embedding = vector32('{v}') WHERE key = '{key}'");
+                tracing::info!("({}) {}", operation, sql);
+                simple_conn.execute(&sql).unwrap();
+                index_conn.execute(&sql).unwrap();
+            } else if choice == 2 && !keys.is_empty() {
+                let idx = rng.next_u32() as usize % keys.len();
+                let key = &keys[idx];
+                let sql = format!("DELETE FROM t WHERE key = '{key}'");
+                tracing::info!("({}) {}", operation, sql);
+                simple_conn.execute(&sql).unwrap();
+                index_conn.execute(&sql).unwrap();
+                keys.remove(idx);
+            } else {
+                let v = vector(&mut rng);
+                let k = rng.next_u32() % 20 + 1;
+                let sql = format!("SELECT key, {function}(embedding, vector32('{v}')) as d FROM t ORDER BY d LIMIT {k}");
+                tracing::info!("({}) {}", operation, sql);
+                let simple_rows = limbo_exec_rows(&simple_conn, &sql);
+                let index_rows = limbo_exec_rows(&index_conn, &sql);
+                tracing::info!("simple: {:?}, index_rows: {:?}", simple_rows, index_rows);
+                if probe_distance == BITS {
+                    // all buckets are probed - so search must be exact
+                    assert_eq!(index_rows, simple_rows);
+                    continue;
+                }
+                assert!(index_rows.len() <= simple_rows.len());
+                // approximate search can miss neighbours but can't return rows closer than exact ones
+                for (a, b) in index_rows.iter().zip(simple_rows.iter()) {
+                    match (&a[1], &b[1]) {
+                        (rusqlite::types::Value::Real(a), rusqlite::types::Value::Real(b)) => {
+                            assert!(*a >= *b || (*a - *b).abs() < 1e-6, "a={a}, b={b}");
+                        }
+                        _ => panic!("unexpected column values"),
+                    }
+                }
+            }
+        }
+    }
+}
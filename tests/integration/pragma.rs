@@ -1,5 +1,10 @@
 use crate::common::TempDatabase;
-use turso_core::{StepResult, Value};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use turso_core::{
+    Buffer, Clock, Completion, CompletionError, Database, DatabaseOpts, File, Instant, LimboError,
+    OpenFlags, StepResult, Value, IO,
+};
 
 #[turso_macros::test(mvcc)]
 fn test_pragma_module_list_returns_list(db: TempDatabase) {
@@ -167,3 +172,566 @@ fn test_pragma_page_sizes_with_writes_persists(db: TempDatabase) {
         assert_eq!(page_size, test_page_size);
     }
 }
+
+#[test]
+fn test_pragma_freelist_count_on_sqlite_file() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let db_path = dir.path().join("freelist.db");
+
+    // Let SQLite produce a file with a freelist spanning several trunk pages.
+    let sqlite_freelist_count = {
+        let conn = rusqlite::Connection::open(&db_path).unwrap();
+        conn.pragma_update(None, "journal_mode", "wal").unwrap();
+        conn.execute_batch(
+            "CREATE TABLE keep (x INTEGER PRIMARY KEY, b BLOB);
+             INSERT INTO keep VALUES (1, randomblob(100));
+             CREATE TABLE dropped (x INTEGER PRIMARY KEY, b BLOB);
+             WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 3000)
+             INSERT INTO dropped SELECT i, randomblob(3000) FROM n;
+             DROP TABLE dropped;",
+        )
+        .unwrap();
+        conn.pragma_update(None, "wal_checkpoint", "TRUNCATE")
+            .unwrap();
+        conn.query_row("PRAGMA freelist_count", (), |row| row.get::<_, i64>(0))
+            .unwrap()
+    };
+    // A trunk page of a 4096 byte page lists at most 1022 leaves.
+    assert!(sqlite_freelist_count > 2 * 1022);
+
+    let freelist_count = |conn: &std::sync::Arc<turso_core::Connection>| {
+        let mut rows = conn.pragma_query("freelist_count").unwrap();
+        let Value::Integer(count) = rows.pop().unwrap().pop().unwrap() else {
+            panic!("expected integer value");
+        };
+        count
+    };
+    let page_count = |conn: &std::sync::Arc<turso_core::Connection>| {
+        let mut rows = conn.pragma_query("page_count").unwrap();
+        let Value::Integer(count) = rows.pop().unwrap().pop().unwrap() else {
+            panic!("expected integer value");
+        };
+        count
+    };
+
+    let db = TempDatabase::new_with_existent(&db_path);
+    let conn = db.connect_limbo();
+    assert_eq!(freelist_count(&conn), sqlite_freelist_count);
+
+    // New pages are taken from the freelist instead of growing the file.
+    let pages_before = page_count(&conn);
+    conn.execute("CREATE TABLE reused (x INTEGER PRIMARY KEY, b BLOB)")
+        .unwrap();
+    conn.execute("INSERT INTO reused SELECT value, randomblob(3000) FROM generate_series(1, 1000)")
+        .unwrap();
+    let limbo_freelist_count = freelist_count(&conn);
+    assert!(limbo_freelist_count < sqlite_freelist_count - 1000);
+    assert_eq!(page_count(&conn), pages_before);
+
+    // Dropping the table returns its pages to the freelist.
+    conn.execute("DROP TABLE reused").unwrap();
+    assert_eq!(freelist_count(&conn), sqlite_freelist_count);
+
+    conn.execute("PRAGMA wal_checkpoint(TRUNCATE)").unwrap();
+    drop(conn);
+    drop(db);
+    crate::common::rusqlite_integrity_check(&db_path).unwrap();
+    let conn = rusqlite::Connection::open(&db_path).unwrap();
+    let count = conn
+        .query_row("PRAGMA freelist_count", (), |row| row.get::<_, i64>(0))
+        .unwrap();
+    assert_eq!(count, sqlite_freelist_count);
+}
+
+#[test]
+fn test_pragma_group_commit_window() {
+    let db = TempDatabase::new("group_commit_window.db");
+    let conn = db.connect_limbo();
+    let other = db.connect_limbo();
+
+    let window = |conn: &std::sync::Arc<turso_core::Connection>| {
+        let mut rows = conn.pragma_query("group_commit_window").unwrap();
+        let Value::Integer(window) = rows.pop().unwrap().pop().unwrap() else {
+            panic!("expected integer value");
+        };
+        window
+    };
+    assert_eq!(window(&conn), 0);
+
+    // The window belongs to the database, so every connection sees it.
+    conn.execute("PRAGMA group_commit_window = 1000").unwrap();
+    assert_eq!(window(&conn), 1000);
+    assert_eq!(window(&other), 1000);
+    assert!(conn.execute("PRAGMA group_commit_window = -1").is_err());
+
+    // Every commit waits for the window to pass, keep it short.
+    conn.execute("PRAGMA group_commit_window = 5").unwrap();
+    conn.execute("CREATE TABLE t (x INTEGER)").unwrap();
+    for i in 0..10 {
+        conn.execute(format!("INSERT INTO t VALUES ({i})")).unwrap();
+    }
+    let rows = crate::common::limbo_exec_rows(&other, "SELECT count(*) FROM t");
+    assert_eq!(rows, vec![vec![rusqlite::types::Value::Integer(10)]]);
+
+    conn.execute("PRAGMA wal_checkpoint(TRUNCATE)").unwrap();
+    drop(conn);
+    drop(other);
+    let db_path = db.path.clone();
+    drop(db);
+    crate::common::rusqlite_integrity_check(&db_path).unwrap();
+}
+
+#[test]
+fn test_pragma_synchronous_normal() {
+    let db = TempDatabase::new("synchronous_normal.db");
+    let conn = db.connect_limbo();
+
+    let synchronous = |conn: &std::sync::Arc<turso_core::Connection>| {
+        let mut rows = conn.pragma_query("synchronous").unwrap();
+        let Value::Integer(mode) = rows.pop().unwrap().pop().unwrap() else {
+            panic!("expected integer value");
+        };
+        mode
+    };
+    assert_eq!(synchronous(&conn), 2);
+    conn.execute("PRAGMA synchronous = NORMAL").unwrap();
+    assert_eq!(synchronous(&conn), 1);
+    conn.execute("PRAGMA synchronous = OFF").unwrap();
+    assert_eq!(synchronous(&conn), 0);
+    conn.execute("PRAGMA synchronous = 1").unwrap();
+    assert_eq!(synchronous(&conn), 1);
+    // The same spellings as SQLite, where EXTRA behaves like FULL.
+    for (value, mode) in [
+        ("off", 0),
+        ("no", 0),
+        ("false", 0),
+        ("0", 0),
+        ("on", 1),
+        ("yes", 1),
+        ("true", 1),
+        ("normal", 1),
+        ("1", 1),
+        ("full", 2),
+        ("2", 2),
+        ("extra", 2),
+        ("3", 2),
+        ("'off'", 0),
+    ] {
+        conn.execute(format!("PRAGMA synchronous = {value}"))
+            .unwrap();
+        assert_eq!(synchronous(&conn), mode, "PRAGMA synchronous = {value}");
+    }
+
+    conn.execute("CREATE TABLE t (x INTEGER)").unwrap();
+    for i in 0..10 {
+        conn.execute(format!("INSERT INTO t VALUES ({i})")).unwrap();
+    }
+    conn.execute("PRAGMA wal_checkpoint(TRUNCATE)").unwrap();
+    let rows = crate::common::limbo_exec_rows(&conn, "SELECT count(*) FROM t");
+    assert_eq!(rows, vec![vec![rusqlite::types::Value::Integer(10)]]);
+
+    drop(conn);
+    crate::common::rusqlite_integrity_check(&db.path).unwrap();
+}
+
+#[test]
+fn test_pragma_vdbe_profile() {
+    let db = TempDatabase::new_empty();
+    let conn = db.connect_limbo();
+    conn.execute("CREATE TABLE t (x INTEGER)").unwrap();
+    conn.execute("INSERT INTO t SELECT value FROM generate_series(1, 100)")
+        .unwrap();
+
+    // Nothing is collected until profiling is enabled.
+    let mut stmt = conn.prepare("SELECT x FROM t").unwrap();
+    stmt.run_ignore_rows().unwrap();
+    assert!(stmt.vdbe_profile().is_empty());
+    assert!(conn.pragma_query("vdbe_profile").unwrap().is_empty());
+
+    conn.execute("PRAGMA vdbe_profile = ON").unwrap();
+    stmt.reset();
+    stmt.run_ignore_rows().unwrap();
+    let profile = stmt.vdbe_profile();
+    let (_, _, next) = profile
+        .iter()
+        .find(|(_, opcode, _)| *opcode == "Next")
+        .expect("Next should have been executed");
+    // Resumptions after I/O are counted as well.
+    assert!(next.executions >= 100);
+
+    let rows = conn.pragma_query("vdbe_profile").unwrap();
+    let next = rows
+        .iter()
+        .find(|row| row[0] == Value::build_text("Next"))
+        .expect("Next should be listed");
+    assert!(matches!(next[1], Value::Integer(n) if n >= 100));
+
+    conn.execute("PRAGMA vdbe_profile = OFF").unwrap();
+    assert!(conn.pragma_query("vdbe_profile").unwrap().is_empty());
+}
+
+/// Counts the syncs of WAL files, and fails them while `fail_wal_syncs` is set.
+struct WalSyncCountingIO {
+    inner: Arc<dyn IO>,
+    wal_syncs: Arc<AtomicUsize>,
+    fail_wal_syncs: Arc<AtomicBool>,
+}
+
+struct WalSyncCountingFile {
+    inner: Arc<dyn File>,
+    wal_syncs: Arc<AtomicUsize>,
+    fail_wal_syncs: Arc<AtomicBool>,
+}
+
+impl Clock for WalSyncCountingIO {
+    fn now(&self) -> Instant {
+        self.inner.now()
+    }
+}
+
+impl IO for WalSyncCountingIO {
+    fn open_file(
+        &self,
+        path: &str,
+        flags: OpenFlags,
+        direct: bool,
+    ) -> turso_core::Result<Arc<dyn File>> {
+        let file = self.inner.open_file(path, flags, direct)?;
+        if !path.ends_with("-wal") {
+            return Ok(file);
+        }
+        Ok(Arc::new(WalSyncCountingFile {
+            inner: file,
+            wal_syncs: self.wal_syncs.clone(),
+            fail_wal_syncs: self.fail_wal_syncs.clone(),
+        }))
+    }
+
+    fn remove_file(&self, path: &str) -> turso_core::Result<()> {
+        self.inner.remove_file(path)
+    }
+
+    fn step(&self) -> turso_core::Result<()> {
+        self.inner.step()
+    }
+}
+
+impl File for WalSyncCountingFile {
+    fn lock_file(&self, exclusive: bool) -> turso_core::Result<()> {
+        self.inner.lock_file(exclusive)
+    }
+
+    fn unlock_file(&self) -> turso_core::Result<()> {
+        self.inner.unlock_file()
+    }
+
+    fn pread(&self, pos: u64, c: Completion) -> turso_core::Result<Completion> {
+        self.inner.pread(pos, c)
+    }
+
+    fn pwrite(
+        &self,
+        pos: u64,
+        buffer: Arc<Buffer>,
+        c: Completion,
+    ) -> turso_core::Result<Completion> {
+        self.inner.pwrite(pos, buffer, c)
+    }
+
+    fn pwritev(
+        &self,
+        pos: u64,
+        buffers: Vec<Arc<Buffer>>,
+        c: Completion,
+    ) -> turso_core::Result<Completion> {
+        self.inner.pwritev(pos, buffers, c)
+    }
+
+    fn sync(&self, c: Completion) -> turso_core::Result<Completion> {
+        self.wal_syncs.fetch_add(1, Ordering::SeqCst);
+        if self.fail_wal_syncs.load(Ordering::SeqCst) {
+            c.error(CompletionError::IOError(std::io::ErrorKind::Other));
+            return Ok(c);
+        }
+        self.inner.sync(c)
+    }
+
+    fn size(&self) -> turso_core::Result<u64> {
+        self.inner.size()
+    }
+
+    fn truncate(&self, len: u64, c: Completion) -> turso_core::Result<Completion> {
+        self.inner.truncate(len, c)
+    }
+}
+
+#[test]
+fn test_group_commit_shares_wal_sync() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("group_commit.db");
+    let wal_syncs = Arc::new(AtomicUsize::new(0));
+    let io = Arc::new(WalSyncCountingIO {
+        inner: Arc::new(turso_core::PlatformIO::new().unwrap()),
+        wal_syncs: wal_syncs.clone(),
+        fail_wal_syncs: Arc::new(AtomicBool::new(false)),
+    });
+    let db = Database::open_file_with_flags(
+        io.clone(),
+        path.to_str().unwrap(),
+        OpenFlags::default(),
+        DatabaseOpts::new(),
+        None,
+    )
+    .unwrap();
+    let conns: Vec<_> = (0..4)
+        .map(|_| {
+            let conn = db.connect().unwrap();
+            // Commits that find the write lock taken retry instead of failing.
+            conn.set_busy_timeout(std::time::Duration::from_secs(5));
+            conn
+        })
+        .collect();
+    conns[0].execute("CREATE TABLE t (x INTEGER)").unwrap();
+
+    // Without a window every commit syncs the WAL.
+    let before = wal_syncs.load(Ordering::SeqCst);
+    for i in 0..3 {
+        conns[0]
+            .execute(format!("INSERT INTO t VALUES ({i})"))
+            .unwrap();
+    }
+    assert_eq!(wal_syncs.load(Ordering::SeqCst) - before, 3);
+
+    // Interleave the commits of all connections: each one writes its frames, releases the
+    // write lock and waits, so a single sync covers all of them.
+    conns[0].execute("PRAGMA group_commit_window = 20").unwrap();
+    let before = wal_syncs.load(Ordering::SeqCst);
+    let mut stmts: Vec<_> = conns
+        .iter()
+        .enumerate()
+        .map(|(i, conn)| {
+            conn.prepare(format!("INSERT INTO t VALUES ({})", 100 + i))
+                .unwrap()
+        })
+        .collect();
+    let mut done = vec![false; stmts.len()];
+    while done.contains(&false) {
+        for (stmt, done) in stmts.iter_mut().zip(done.iter_mut()) {
+            if *done {
+                continue;
+            }
+            match stmt.step().unwrap() {
+                StepResult::IO => io.step().unwrap(),
+                StepResult::Done => {
+                    // No commit completes before the sync that covers it.
+                    assert_eq!(wal_syncs.load(Ordering::SeqCst) - before, 1);
+                    *done = true;
+                }
+                other => panic!("unexpected step result {other:?}"),
+            }
+        }
+    }
+    assert_eq!(wal_syncs.load(Ordering::SeqCst) - before, 1);
+
+    let rows = crate::common::limbo_exec_rows(&conns[1], "SELECT count(*) FROM t");
+    assert_eq!(rows, vec![vec![rusqlite::types::Value::Integer(7)]]);
+}
+
+#[test]
+fn test_group_commit_sync_failure_keeps_other_writers() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("group_commit_sync_failure.db");
+    let fail_wal_syncs = Arc::new(AtomicBool::new(false));
+    let io = Arc::new(WalSyncCountingIO {
+        inner: Arc::new(turso_core::PlatformIO::new().unwrap()),
+        wal_syncs: Arc::new(AtomicUsize::new(0)),
+        fail_wal_syncs: fail_wal_syncs.clone(),
+    });
+    let db = Database::open_file_with_flags(
+        io.clone(),
+        path.to_str().unwrap(),
+        OpenFlags::default(),
+        DatabaseOpts::new(),
+        None,
+    )
+    .unwrap();
+    let conn = db.connect().unwrap();
+    let writer = db.connect().unwrap();
+    let other = db.connect().unwrap();
+    conn.set_data_sync_retry(true);
+    conn.execute("CREATE TABLE t (x INTEGER)").unwrap();
+    conn.execute("PRAGMA group_commit_window = 20").unwrap();
+
+    // The commit releases the write lock before its shared sync, which fails. Meanwhile another
+    // connection takes the write lock.
+    fail_wal_syncs.store(true, Ordering::SeqCst);
+    let mut stmt = conn.prepare("INSERT INTO t VALUES (1)").unwrap();
+    let mut writer_began = false;
+    let err = loop {
+        match stmt.step() {
+            Ok(StepResult::IO) => io.step().unwrap(),
+            Ok(other) => panic!("unexpected step result {other:?}"),
+            Err(err) => break err,
+        }
+        if !writer_began {
+            writer_began = writer.execute("BEGIN IMMEDIATE").is_ok();
+        }
+    };
+    assert!(
+        writer_began,
+        "the commit should release the write lock before its sync"
+    );
+    assert!(!matches!(err, LimboError::Busy), "unexpected error {err:?}");
+    drop(stmt);
+    fail_wal_syncs.store(false, Ordering::SeqCst);
+
+    // The failed commit neither released the write lock of the other connection nor rolled back
+    // its own frames.
+    assert!(matches!(
+        other.execute("INSERT INTO t VALUES (3)"),
+        Err(LimboError::Busy)
+    ));
+    writer.execute("INSERT INTO t VALUES (2)").unwrap();
+    writer.execute("COMMIT").unwrap();
+    other.execute("INSERT INTO t VALUES (3)").unwrap();
+    let rows = crate::common::limbo_exec_rows(&other, "SELECT x FROM t ORDER BY x");
+    assert_eq!(
+        rows,
+        vec![
+            vec![rusqlite::types::Value::Integer(1)],
+            vec![rusqlite::types::Value::Integer(2)],
+            vec![rusqlite::types::Value::Integer(3)],
+        ]
+    );
+}
+
+#[test]
+fn test_group_commit_wal_insert_session() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("group_commit_wal_insert.db");
+    let wal_syncs = Arc::new(AtomicUsize::new(0));
+    let io = Arc::new(WalSyncCountingIO {
+        inner: Arc::new(turso_core::PlatformIO::new().unwrap()),
+        wal_syncs: wal_syncs.clone(),
+        fail_wal_syncs: Arc::new(AtomicBool::new(false)),
+    });
+    let db = Database::open_file_with_flags(
+        io.clone(),
+        path.to_str().unwrap(),
+        OpenFlags::default(),
+        DatabaseOpts::new(),
+        None,
+    )
+    .unwrap();
+    let conn = db.connect().unwrap();
+    conn.execute("CREATE TABLE t (x INTEGER)").unwrap();
+    conn.execute("PRAGMA group_commit_window = 20").unwrap();
+
+    // Ending the session commits through the regular path, so it waits for a shared sync
+    // instead of leaving its frames unsynced.
+    let before = wal_syncs.load(Ordering::SeqCst);
+    conn.wal_insert_begin().unwrap();
+    conn.execute("INSERT INTO t VALUES (1)").unwrap();
+    conn.wal_insert_end(true).unwrap();
+    assert_eq!(wal_syncs.load(Ordering::SeqCst) - before, 1);
+
+    // No sync is left pending for the next commit of the connection.
+    let before = wal_syncs.load(Ordering::SeqCst);
+    conn.execute("INSERT INTO t VALUES (2)").unwrap();
+    assert_eq!(wal_syncs.load(Ordering::SeqCst) - before, 1);
+
+    let other = db.connect().unwrap();
+    let rows = crate::common::limbo_exec_rows(&other, "SELECT x FROM t ORDER BY x");
+    assert_eq!(
+        rows,
+        vec![
+            vec![rusqlite::types::Value::Integer(1)],
+            vec![rusqlite::types::Value::Integer(2)],
+        ]
+    );
+}
+
+#[test]
+fn test_checkpoint_wal_sync() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("checkpoint_wal_sync.db");
+    let wal_syncs = Arc::new(AtomicUsize::new(0));
+    let fail_wal_syncs = Arc::new(AtomicBool::new(false));
+    let io = Arc::new(WalSyncCountingIO {
+        inner: Arc::new(turso_core::PlatformIO::new().unwrap()),
+        wal_syncs: wal_syncs.clone(),
+        fail_wal_syncs: fail_wal_syncs.clone(),
+    });
+    let db = Database::open_file_with_flags(
+        io.clone(),
+        path.to_str().unwrap(),
+        OpenFlags::default(),
+        DatabaseOpts::new(),
+        None,
+    )
+    .unwrap();
+    let conn = db.connect().unwrap();
+    conn.set_data_sync_retry(true);
+    conn.execute("CREATE TABLE t (x INTEGER)").unwrap();
+
+    // In FULL mode every commit synced its frames, so the checkpoint doesn't sync the WAL again.
+    conn.execute("INSERT INTO t VALUES (1)").unwrap();
+    let before = wal_syncs.load(Ordering::SeqCst);
+    conn.execute("PRAGMA wal_checkpoint(PASSIVE)").unwrap();
+    assert_eq!(wal_syncs.load(Ordering::SeqCst), before);
+
+    // In NORMAL mode the checkpoint syncs the frames first, and fails if that sync fails.
+    conn.execute("PRAGMA synchronous = NORMAL").unwrap();
+    conn.execute("INSERT INTO t VALUES (2)").unwrap();
+    fail_wal_syncs.store(true, Ordering::SeqCst);
+    assert!(conn.execute("PRAGMA wal_checkpoint(PASSIVE)").is_err());
+    fail_wal_syncs.store(false, Ordering::SeqCst);
+    let before = wal_syncs.load(Ordering::SeqCst);
+    conn.execute("PRAGMA wal_checkpoint(PASSIVE)").unwrap();
+    assert_eq!(wal_syncs.load(Ordering::SeqCst) - before, 1);
+
+    let rows = crate::common::limbo_exec_rows(&conn, "SELECT count(*) FROM t");
+    assert_eq!(rows, vec![vec![rusqlite::types::Value::Integer(2)]]);
+}
+
+#[test]
+fn test_checkpoint_wal_sync_mixed_modes() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("checkpoint_wal_sync_mixed.db");
+    let wal_syncs = Arc::new(AtomicUsize::new(0));
+    let io = Arc::new(WalSyncCountingIO {
+        inner: Arc::new(turso_core::PlatformIO::new().unwrap()),
+        wal_syncs: wal_syncs.clone(),
+        fail_wal_syncs: Arc::new(AtomicBool::new(false)),
+    });
+    let db = Database::open_file_with_flags(
+        io.clone(),
+        path.to_str().unwrap(),
+        OpenFlags::default(),
+        DatabaseOpts::new(),
+        None,
+    )
+    .unwrap();
+    let full = db.connect().unwrap();
+    let normal = db.connect().unwrap();
+    full.execute("CREATE TABLE t (x INTEGER)").unwrap();
+    normal.execute("PRAGMA synchronous = NORMAL").unwrap();
+
+    // A checkpoint in FULL mode still syncs the frames another connection committed in NORMAL
+    // mode.
+    normal.execute("INSERT INTO t VALUES (1)").unwrap();
+    let before = wal_syncs.load(Ordering::SeqCst);
+    full.execute("PRAGMA wal_checkpoint(PASSIVE)").unwrap();
+    assert_eq!(wal_syncs.load(Ordering::SeqCst) - before, 1);
+
+    // A commit in FULL mode syncs the frames written before it, so a checkpoint in NORMAL mode
+    // doesn't sync the WAL again.
+    normal.execute("INSERT INTO t VALUES (2)").unwrap();
+    full.execute("INSERT INTO t VALUES (3)").unwrap();
+    let before = wal_syncs.load(Ordering::SeqCst);
+    normal.execute("PRAGMA wal_checkpoint(PASSIVE)").unwrap();
+    assert_eq!(wal_syncs.load(Ordering::SeqCst), before);
+
+    let rows = crate::common::limbo_exec_rows(&full, "SELECT count(*) FROM t");
+    assert_eq!(rows, vec![vec![rusqlite::types::Value::Integer(3)]]);
+}
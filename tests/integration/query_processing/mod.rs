@@ -6,6 +6,8 @@ mod test_write_path;
 mod encryption;
 mod test_expr_index;
 mod test_multi_thread;
+mod test_namespaces;
 mod test_page1;
+mod test_query_plan;
 mod test_schema_updated;
 mod test_transactions;
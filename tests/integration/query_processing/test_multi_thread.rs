@@ -201,7 +201,7 @@ fn test_schema_reprepare_write(tmp_db: TempDatabase) {
 }
 
 fn advance(stmt: &mut Statement) -> anyhow::Result<()> {
-    tracing::info!("Advancing statement: {:?}", stmt.get_sql());
+    tracing::info!("Advancing statement: {:?}", stmt.sql());
     while matches!(stmt.step()?, StepResult::IO) {
         stmt._io().step()?;
     }
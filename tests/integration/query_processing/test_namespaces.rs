@@ -0,0 +1,119 @@
+use rusqlite::types::Value;
+
+use crate::common::{limbo_exec_rows, TempDatabase};
+
+#[turso_macros::test]
+fn test_namespaces_are_isolated(tmp_db: TempDatabase) -> anyhow::Result<()> {
+    let conn = tmp_db.connect_limbo();
+
+    conn.execute("CREATE TABLE t (x)")?;
+    conn.execute("INSERT INTO t VALUES ('main')")?;
+    conn.execute("CREATE NAMESPACE tenant1")?;
+    conn.execute("CREATE NAMESPACE tenant2")?;
+
+    for tenant in ["tenant1", "tenant2"] {
+        conn.execute(format!("USE {tenant}"))?;
+        conn.execute("CREATE TABLE t (x)")?;
+        conn.execute("CREATE INDEX t_x ON t (x)")?;
+        conn.execute(format!("INSERT INTO t VALUES ('{tenant}')"))?;
+    }
+
+    conn.execute("USE tenant1")?;
+    assert_eq!(
+        limbo_exec_rows(&conn, "SELECT x FROM t"),
+        vec![vec![Value::Text("tenant1".into())]]
+    );
+    assert_eq!(
+        limbo_exec_rows(&conn, "SELECT name FROM sqlite_schema ORDER BY name"),
+        vec![
+            vec![Value::Text("t".into())],
+            vec![Value::Text("t_x".into())]
+        ]
+    );
+
+    conn.execute("USE main")?;
+    assert_eq!(
+        limbo_exec_rows(&conn, "SELECT x FROM t"),
+        vec![vec![Value::Text("main".into())]]
+    );
+    assert_eq!(
+        limbo_exec_rows(&conn, "PRAGMA integrity_check"),
+        vec![vec![Value::Text("ok".into())]]
+    );
+
+    // Another connection and a reopened database see the namespaces as well.
+    let conn2 = tmp_db.connect_limbo();
+    conn2.execute("USE tenant2")?;
+    assert_eq!(
+        limbo_exec_rows(&conn2, "SELECT x FROM t"),
+        vec![vec![Value::Text("tenant2".into())]]
+    );
+    let tmp_db = TempDatabase::new_with_existent(&tmp_db.path);
+    let conn = tmp_db.connect_limbo();
+    conn.execute("USE tenant1")?;
+    assert_eq!(
+        limbo_exec_rows(&conn, "SELECT x FROM t"),
+        vec![vec![Value::Text("tenant1".into())]]
+    );
+    Ok(())
+}
+
+#[turso_macros::test]
+fn test_namespace_schema_changes_are_seen_by_other_connections(
+    tmp_db: TempDatabase,
+) -> anyhow::Result<()> {
+    let conn1 = tmp_db.connect_limbo();
+    let conn2 = tmp_db.connect_limbo();
+
+    conn1.execute("CREATE NAMESPACE tenant1")?;
+    conn1.execute("USE tenant1")?;
+    conn2.execute("USE tenant1")?;
+    conn1.execute("CREATE TABLE t (x)")?;
+    conn2.execute("INSERT INTO t VALUES (1)")?;
+    assert_eq!(
+        limbo_exec_rows(&conn1, "SELECT x FROM t"),
+        vec![vec![Value::Integer(1)]]
+    );
+    Ok(())
+}
+
+#[turso_macros::test]
+fn test_drop_namespace(tmp_db: TempDatabase) -> anyhow::Result<()> {
+    let conn = tmp_db.connect_limbo();
+
+    conn.execute("CREATE NAMESPACE tenant1")?;
+    assert!(conn.execute("CREATE NAMESPACE tenant1").is_err());
+    conn.execute("CREATE NAMESPACE IF NOT EXISTS tenant1")?;
+    assert!(conn.execute("CREATE NAMESPACE main").is_err());
+
+    conn.execute("USE tenant1")?;
+    conn.execute("CREATE TABLE t (x)")?;
+    assert!(conn.execute("DROP NAMESPACE tenant1").is_err());
+    conn.execute("USE main")?;
+    let err = conn.execute("DROP NAMESPACE tenant1").unwrap_err();
+    assert!(err.to_string().contains("namespace tenant1 is not empty"));
+
+    conn.execute("USE tenant1")?;
+    conn.execute("DROP TABLE t")?;
+    conn.execute("USE main")?;
+    conn.execute("DROP NAMESPACE tenant1")?;
+    conn.execute("DROP NAMESPACE IF EXISTS tenant1")?;
+    assert!(conn.execute("USE tenant1").is_err());
+    assert_eq!(
+        limbo_exec_rows(&conn, "PRAGMA integrity_check"),
+        vec![vec![Value::Text("ok".into())]]
+    );
+    Ok(())
+}
+
+#[turso_macros::test]
+fn test_use_within_transaction_fails(tmp_db: TempDatabase) -> anyhow::Result<()> {
+    let conn = tmp_db.connect_limbo();
+
+    conn.execute("CREATE NAMESPACE tenant1")?;
+    conn.execute("BEGIN")?;
+    assert!(conn.execute("USE tenant1").is_err());
+    conn.execute("COMMIT")?;
+    conn.execute("USE tenant1")?;
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+use crate::common::TempDatabase;
+
+const SCHEMA: &[&str] = &[
+    "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT, name TEXT, age INTEGER)",
+    "CREATE INDEX users_email ON users(email)",
+    "CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER, total REAL)",
+    "CREATE INDEX orders_user_id ON orders(user_id)",
+];
+
+const SEPARATOR: &str = "----";
+
+fn fixture_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("integration/query_processing/query_plans.txt")
+}
+
+/// Splits the fixture into its header comment and (query, plan) cases.
+fn parse_fixture(contents: &str) -> (String, Vec<(String, String)>) {
+    let header = contents
+        .lines()
+        .take_while(|line| line.starts_with('#') || line.is_empty())
+        .map(|line| format!("{line}\n"))
+        .collect::<String>();
+    let cases = contents[header.len()..]
+        .split("\n\n")
+        .filter(|case| !case.trim().is_empty())
+        .map(|case| {
+            let (query, plan) = case
+                .split_once(&format!("\n{SEPARATOR}\n"))
+                .unwrap_or_else(|| panic!("case without `{SEPARATOR}` line: {case}"));
+            (query.trim().to_string(), plan.trim_end().to_string())
+        })
+        .collect();
+    (header, cases)
+}
+
+/// Renders the plan of `sql` as a tree, one node per line.
+fn query_plan(conn: &Arc<turso_core::Connection>, sql: &str) -> anyhow::Result<String> {
+    let mut stmt = conn.prepare(format!("EXPLAIN QUERY PLAN {sql}"))?;
+    let mut depths = HashMap::new();
+    let mut lines = Vec::new();
+    stmt.run_with_row_callback(|row| {
+        let id = row.get::<i64>(0)?;
+        let parent = row.get::<i64>(1)?;
+        let depth = depths.get(&parent).map_or(0, |depth| depth + 1);
+        depths.insert(id, depth);
+        lines.push(format!("{}{}", "  ".repeat(depth), row.get::<String>(3)?));
+        Ok(())
+    })?;
+    Ok(lines.join("\n"))
+}
+
+/// Checks the plans of the queries in `query_plans.txt` against the plans recorded there, so
+/// that optimizer changes that alter existing plans show up as a test failure. Run with
+/// `UPDATE_QUERY_PLANS=1` to record the current plans instead.
+#[test]
+fn query_plan_snapshots() -> anyhow::Result<()> {
+    let _ = env_logger::try_init();
+    let tmp_db = TempDatabase::new_empty();
+    let conn = tmp_db.connect_limbo();
+    for sql in SCHEMA {
+        conn.execute(sql)?;
+    }
+
+    let path = fixture_path();
+    let contents = std::fs::read_to_string(&path)?;
+    let (header, cases) = parse_fixture(&contents);
+    let mut mismatches = Vec::new();
+    let mut updated = Vec::with_capacity(cases.len());
+    for (query, expected) in cases {
+        let actual = query_plan(&conn, &query)?;
+        if actual != expected {
+            mismatches.push(format!(
+                "{query}\n  expected:\n{}\n  actual:\n{}",
+                indent(&expected),
+                indent(&actual)
+            ));
+        }
+        updated.push(format!("{query}\n{SEPARATOR}\n{actual}\n"));
+    }
+
+    if std::env::var_os("UPDATE_QUERY_PLANS").is_some() {
+        std::fs::write(&path, format!("{header}{}", updated.join("\n")))?;
+        return Ok(());
+    }
+    assert!(
+        mismatches.is_empty(),
+        "{} query plan(s) changed, rerun with UPDATE_QUERY_PLANS=1 if this is intended:\n\n{}",
+        mismatches.len(),
+        mismatches.join("\n\n")
+    );
+    Ok(())
+}
+
+fn indent(plan: &str) -> String {
+    plan.lines()
+        .map(|line| format!("    {line}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn test_parse_fixture() {
+    let (header, cases) = parse_fixture(
+        "# header\n\nSELECT 1\n----\nSCAN CONSTANT ROW\n\nSELECT * FROM t\n----\nSCAN t\n  SCAN s\n",
+    );
+    assert_eq!(header, "# header\n\n");
+    assert_eq!(
+        cases,
+        vec![
+            ("SELECT 1".to_string(), "SCAN CONSTANT ROW".to_string()),
+            (
+                "SELECT * FROM t".to_string(),
+                "SCAN t\n  SCAN s".to_string()
+            ),
+        ]
+    );
+}
@@ -33,6 +33,154 @@ fn test_statement_reset_bind(tmp_db: TempDatabase) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[turso_macros::test(mvcc, init_sql = "create table test (i integer);")]
+fn test_statement_reset_and_clear_bindings(tmp_db: TempDatabase) -> anyhow::Result<()> {
+    let conn = tmp_db.connect_limbo();
+    conn.execute("insert into test values (1), (2), (3)")?;
+
+    let mut stmt = conn.prepare("select i, ? from test")?;
+    assert_eq!(stmt.sql(), "select i, ? from test");
+    assert!(!stmt.is_busy());
+
+    stmt.bind_at(1.try_into()?, Value::Integer(7));
+    let row = stmt
+        .run_one_step_blocking(|| Ok(()), || Ok(()))?
+        .map(|row| row.get_values().cloned().collect::<Vec<_>>());
+    assert_eq!(row, Some(vec![Value::Integer(1), Value::Integer(7)]));
+    assert!(stmt.is_busy());
+
+    // Resetting a partially consumed statement rewinds it but keeps the bindings.
+    stmt.reset();
+    assert!(!stmt.is_busy());
+    assert!(stmt.row().is_none());
+    assert_eq!(
+        stmt.run_collect_rows()?,
+        vec![
+            vec![Value::Integer(1), Value::Integer(7)],
+            vec![Value::Integer(2), Value::Integer(7)],
+            vec![Value::Integer(3), Value::Integer(7)],
+        ]
+    );
+    assert!(!stmt.is_busy());
+
+    stmt.reset();
+    stmt.clear_bindings();
+    assert_eq!(
+        stmt.run_collect_rows()?,
+        vec![
+            vec![Value::Integer(1), Value::Null],
+            vec![Value::Integer(2), Value::Null],
+            vec![Value::Integer(3), Value::Null],
+        ]
+    );
+
+    Ok(())
+}
+
+#[turso_macros::test(mvcc, init_sql = "create table test (i integer);")]
+fn test_statement_bind_helpers(tmp_db: TempDatabase) -> anyhow::Result<()> {
+    let conn = tmp_db.connect_limbo();
+
+    let mut stmt = conn.prepare("select :a, $b, :a, ?5, @c")?;
+    // ?5 leaves a gap and :a is used twice, so the count is the largest index.
+    assert_eq!(stmt.parameters_count(), 6);
+    let names = stmt
+        .parameters()
+        .names()
+        .map(|(name, index)| (name.to_string(), index.get()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            (":a".to_string(), 1),
+            ("$b".to_string(), 2),
+            ("@c".to_string(), 6)
+        ]
+    );
+
+    stmt.bind_named([
+        (":a", Value::Integer(1)),
+        ("$b", Value::build_text("two")),
+        ("@c", Value::Float(3.5)),
+    ])?;
+    stmt.bind_at(5.try_into()?, Value::Integer(5));
+    assert_eq!(
+        stmt.run_collect_rows()?,
+        vec![vec![
+            Value::Integer(1),
+            Value::build_text("two"),
+            Value::Integer(1),
+            Value::Integer(5),
+            Value::Float(3.5),
+        ]]
+    );
+    assert!(matches!(
+        stmt.bind_by_name(":missing", Value::Null),
+        Err(LimboError::InvalidArgument(_))
+    ));
+    // The prefix is part of the name.
+    assert!(stmt.bind_by_name("a", Value::Null).is_err());
+
+    let mut stmt = conn.prepare("select ?, ?")?;
+    stmt.bind_values([Value::Integer(1), Value::Integer(2)])?;
+    assert_eq!(
+        stmt.run_collect_rows()?,
+        vec![vec![Value::Integer(1), Value::Integer(2)]]
+    );
+    stmt.reset();
+    assert!(matches!(
+        stmt.bind_values(vec![Value::Null; 3]),
+        Err(LimboError::InvalidArgument(_))
+    ));
+
+    Ok(())
+}
+
+#[turso_macros::test(
+    mvcc,
+    init_sql = "create table users (id integer primary key, name varchar(32), data, score real);"
+)]
+fn test_statement_column_metadata(tmp_db: TempDatabase) -> anyhow::Result<()> {
+    let conn = tmp_db.connect_limbo();
+
+    let stmt = conn.prepare("select u.id, name as n, data, score + 1, 'x' from users as u")?;
+    assert_eq!(stmt.num_columns(), 5);
+
+    assert_eq!(stmt.get_column_name(0), "id");
+    assert_eq!(stmt.get_column_name(1), "n");
+    assert_eq!(stmt.get_column_name(2), "data");
+
+    let decl_types = (0..stmt.num_columns())
+        .map(|i| stmt.get_column_decl_type(i).map(|t| t.into_owned()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        decl_types,
+        vec![
+            Some("integer".to_string()),
+            Some("varchar(32)".to_string()),
+            None,
+            None,
+            None
+        ]
+    );
+
+    let table_names = (0..stmt.num_columns())
+        .map(|i| stmt.get_column_table_name(i).map(|t| t.into_owned()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        table_names,
+        vec![
+            Some("users".to_string()),
+            Some("users".to_string()),
+            Some("users".to_string()),
+            None,
+            None
+        ]
+    );
+
+    Ok(())
+}
+
 #[turso_macros::test(mvcc, init_sql = "create table test (i integer);")]
 fn test_statement_bind(tmp_db: TempDatabase) -> anyhow::Result<()> {
     let conn = tmp_db.connect_limbo();
@@ -857,3 +1005,503 @@ fn test_eval_param_only_once(tmp_db: TempDatabase) {
     // the test will allocate 10^8 * 10^4 bytes in case if parameter will be evaluated for every row
     assert!(elapsed < std::time::Duration::from_millis(120));
 }
+
+#[turso_macros::test(init_sql = "create table test (i integer);")]
+fn test_statement_metrics_page_cache_and_io(tmp_db: TempDatabase) -> anyhow::Result<()> {
+    let conn = tmp_db.connect_limbo();
+    conn.execute("insert into test values (1), (2), (3)")?;
+    let insert = conn.metrics.read().last_statement.clone().unwrap();
+    assert_eq!(insert.rows_written, 3);
+    assert!(insert.pages_written > 0);
+
+    let mut stmt = conn.prepare("select * from test")?;
+    stmt.run_with_row_callback(|_| Ok(()))?;
+    let select = conn.metrics.read().last_statement.clone().unwrap();
+    assert_eq!(select.rows_read, 3);
+    assert!(select.cache_hits + select.cache_misses > 0);
+    assert_eq!(select.pages_written, 0);
+
+    Ok(())
+}
+
+#[turso_macros::test(init_sql = "create table test (i integer);")]
+fn test_statement_stats(tmp_db: TempDatabase) -> anyhow::Result<()> {
+    let conn = tmp_db.connect_limbo();
+    conn.execute("insert into test values (1), (2), (3)")?;
+
+    let mut stmt = conn.prepare("select i from test order by i desc")?;
+    for _ in 0..2 {
+        stmt.run_with_row_callback(|_| Ok(()))?;
+        stmt.reset();
+    }
+    let stats = stmt.stats(false);
+    assert_eq!(stats.rows_returned, 6);
+    assert_eq!(stats.sort_operations, 2);
+
+    // Counters of a partially run execution are included, and resetting them only keeps
+    // what happens afterwards.
+    loop {
+        match stmt.step()? {
+            StepResult::Row => break,
+            StepResult::IO => tmp_db.io.step()?,
+            other => panic!("unexpected step result: {other:?}"),
+        }
+    }
+    assert_eq!(stmt.stats(true).rows_returned, 7);
+    stmt.run_with_row_callback(|_| Ok(()))?;
+    let stats = stmt.stats(false);
+    assert_eq!(stats.rows_returned, 2);
+    assert_eq!(stats.sort_operations, 0);
+
+    Ok(())
+}
+
+#[turso_macros::test(
+    init_sql = "create table t (id integer primary key, a integer, b text); create index t_a on t(a); create index t_b on t(b);"
+)]
+fn test_or_terms_use_multi_index_scan(tmp_db: TempDatabase) -> anyhow::Result<()> {
+    let conn = tmp_db.connect_limbo();
+    conn.execute("insert into t values (1, 10, 'x'), (2, 20, 'y'), (3, 10, 'z'), (4, 30, 'x')")?;
+
+    let query = "select id from t where a = 10 or b = 'x' or id = 2";
+    let mut stmt = conn.prepare(format!("explain query plan {query}"))?;
+    let mut plans = Vec::new();
+    stmt.run_with_row_callback(|row| {
+        plans.push(row.get::<String>(3)?);
+        Ok(())
+    })?;
+    assert!(
+        plans
+            .iter()
+            .any(|p| p == "MULTI-INDEX OR t (INDEX t_a, INDEX t_b, INTEGER PRIMARY KEY)"),
+        "expected a multi-index OR plan, got {plans:?}"
+    );
+
+    // Row 1 matches two arms but is only returned once, and rows come back in rowid order.
+    let rows: Vec<(i64,)> = conn.exec_rows(query);
+    assert_eq!(rows, vec![(1,), (2,), (3,), (4,)]);
+
+    // An arm without a usable index falls back to a full scan.
+    let mut stmt =
+        conn.prepare("explain query plan select id from t where a = 10 or id + 1 = 3")?;
+    let mut plans = Vec::new();
+    stmt.run_with_row_callback(|row| {
+        plans.push(row.get::<String>(3)?);
+        Ok(())
+    })?;
+    assert_eq!(plans, vec!["SCAN t".to_string()]);
+
+    Ok(())
+}
+
+#[turso_macros::test(
+    init_sql = "create table t (id integer primary key, a integer, b text, c blob); create index t_ab on t(a, b); create index t_b on t(b);"
+)]
+fn test_row_enumeration_scans_smallest_covering_index(tmp_db: TempDatabase) -> anyhow::Result<()> {
+    let conn = tmp_db.connect_limbo();
+    conn.execute(
+        "insert into t values (1, 10, 'x', x'00'), (2, 20, 'y', null), (3, 30, null, null)",
+    )?;
+
+    let explain = |query: &str| -> anyhow::Result<Vec<String>> {
+        let mut stmt = conn.prepare(format!("explain query plan {query}"))?;
+        let mut plans = Vec::new();
+        stmt.run_with_row_callback(|row| {
+            plans.push(row.get::<String>(3)?);
+            Ok(())
+        })?;
+        Ok(plans)
+    };
+
+    // No table column is read, so the narrowest index is enough to count the rows.
+    assert_eq!(
+        explain("select count(*) from t")?,
+        vec!["SCAN t USING COVERING INDEX t_b".to_string()]
+    );
+    let rows: Vec<(i64,)> = conn.exec_rows("select count(*) from t");
+    assert_eq!(rows, vec![(3,)]);
+    let rows: Vec<(i64,)> = conn.exec_rows("select rowid from t where rowid > 1");
+    assert_eq!(rows.len(), 2);
+
+    // Queries that need rowid order or read a column keep scanning the table.
+    assert_eq!(
+        explain("select rowid from t order by rowid")?,
+        vec!["SCAN t".to_string()]
+    );
+    assert_eq!(explain("select c from t")?, vec!["SCAN t".to_string()]);
+
+    Ok(())
+}
+
+#[turso_macros::test(
+    init_sql = "create table t (a integer, b text, c integer); create index t_ab on t(a, b);"
+)]
+fn test_group_by_terms_follow_index_order(tmp_db: TempDatabase) -> anyhow::Result<()> {
+    let conn = tmp_db.connect_limbo();
+    conn.execute(
+        "insert into t values (2, 'x', 1), (1, 'y', 2), (2, 'x', 3), (1, null, 4), (1, 'y', 5)",
+    )?;
+
+    let explain = |query: &str| -> anyhow::Result<Vec<String>> {
+        let mut stmt = conn.prepare(format!("explain query plan {query}"))?;
+        let mut plans = Vec::new();
+        stmt.run_with_row_callback(|row| {
+            plans.push(row.get::<String>(3)?);
+            Ok(())
+        })?;
+        Ok(plans)
+    };
+
+    // The groups are the same in any order of the terms, so they are read in index order and
+    // aggregated without a sorter.
+    assert_eq!(
+        explain("select b, a, sum(c) from t group by b, a")?,
+        vec!["SCAN t USING INDEX t_ab".to_string()]
+    );
+    let rows: Vec<(Option<String>, i64, i64)> =
+        conn.exec_rows("select b, a, sum(c) from t group by b, a");
+    assert_eq!(
+        rows,
+        vec![
+            (None, 1, 4),
+            (Some("y".to_string()), 1, 7),
+            (Some("x".to_string()), 2, 4),
+        ]
+    );
+
+    // Terms that aren't a prefix of the index still need a sorter.
+    assert_eq!(
+        explain("select b, c, sum(a) from t group by b, c")?,
+        vec![
+            "SCAN t".to_string(),
+            "USE TEMP B-TREE FOR GROUP BY".to_string()
+        ]
+    );
+
+    Ok(())
+}
+
+#[turso_macros::test(
+    init_sql = "create table t1 (a integer, b text); create table t2 (x integer, y text);"
+)]
+fn test_pragma_automatic_index(tmp_db: TempDatabase) -> anyhow::Result<()> {
+    let conn = tmp_db.connect_limbo();
+    conn.execute("insert into t1 values (1, 'a'), (2, 'b'), (3, 'c')")?;
+    conn.execute("insert into t2 values (1, 'x'), (3, 'y'), (3, 'z'), (4, 'w')")?;
+
+    let explain = |query: &str| -> anyhow::Result<Vec<String>> {
+        let mut stmt = conn.prepare(format!("explain query plan {query}"))?;
+        let mut plans = Vec::new();
+        stmt.run_with_row_callback(|row| {
+            plans.push(row.get::<String>(3)?);
+            Ok(())
+        })?;
+        Ok(plans)
+    };
+
+    let query =
+        "select t1.b, coalesce(t2.y, '-') from t1 left join t2 on t2.x = t1.a order by t1.a, 2";
+    let rows: Vec<(i64,)> = conn.exec_rows("pragma automatic_index");
+    assert_eq!(rows, vec![(1,)]);
+    assert!(
+        explain(query)?
+            .iter()
+            .any(|p| p == "SEARCH t2 USING AUTOMATIC COVERING INDEX (x=?)"),
+        "expected an automatic index on t2"
+    );
+    let expected = vec![
+        ("a".to_string(), "x".to_string()),
+        ("b".to_string(), "-".to_string()),
+        ("c".to_string(), "y".to_string()),
+        ("c".to_string(), "z".to_string()),
+    ];
+    let rows: Vec<(String, String)> = conn.exec_rows(query);
+    assert_eq!(rows, expected);
+
+    conn.execute("pragma automatic_index = off")?;
+    let rows: Vec<(i64,)> = conn.exec_rows("pragma automatic_index");
+    assert_eq!(rows, vec![(0,)]);
+    assert!(
+        explain(query)?.iter().all(|p| !p.contains("AUTOMATIC")),
+        "automatic indexes should be disabled"
+    );
+    let rows: Vec<(String, String)> = conn.exec_rows(query);
+    assert_eq!(rows, expected);
+
+    Ok(())
+}
+
+#[turso_macros::test(
+    init_sql = "create table kv (k text primary key, v integer, note text) without rowid;"
+)]
+fn test_without_rowid_table(tmp_db: TempDatabase) -> anyhow::Result<()> {
+    let conn = tmp_db.connect_limbo();
+    conn.execute("insert into kv values ('c', 30, 'third'), ('a', 10, 'first')")?;
+    conn.execute("create index kv_v on kv (v)")?;
+    conn.execute("insert into kv (v, k) values (20, 'b')")?;
+
+    let explain = |query: &str| -> anyhow::Result<Vec<String>> {
+        let mut stmt = conn.prepare(format!("explain query plan {query}"))?;
+        let mut plans = Vec::new();
+        stmt.run_with_row_callback(|row| {
+            plans.push(row.get::<String>(3)?);
+            Ok(())
+        })?;
+        Ok(plans)
+    };
+
+    // The primary key index is the table, so it has no entry of its own in sqlite_schema.
+    let rows: Vec<(String,)> =
+        conn.exec_rows("select name from sqlite_schema where tbl_name = 'kv' order by name");
+    assert_eq!(rows, vec![("kv".to_string(),), ("kv_v".to_string(),)]);
+
+    // Rows are stored in primary key order.
+    assert_eq!(explain("select k, v from kv")?, vec!["SCAN kv".to_string()]);
+    let rows: Vec<(String, i64)> = conn.exec_rows("select k, v from kv");
+    assert_eq!(
+        rows,
+        vec![
+            ("a".to_string(), 10),
+            ("b".to_string(), 20),
+            ("c".to_string(), 30)
+        ]
+    );
+    assert_eq!(
+        explain("select note from kv where k = 'c'")?,
+        vec!["SEARCH kv USING PRIMARY KEY (k=?)".to_string()]
+    );
+    let rows: Vec<(String,)> = conn.exec_rows("select note from kv where k = 'c'");
+    assert_eq!(rows, vec![("third".to_string(),)]);
+
+    // Secondary index entries carry the primary key, which is enough to answer this query.
+    let rows: Vec<(String,)> = conn.exec_rows("select k from kv where v >= 20 order by v desc");
+    assert_eq!(rows, vec![("c".to_string(),), ("b".to_string(),)]);
+
+    let err = conn
+        .execute("insert into kv values ('a', 40, 'dup')")
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("UNIQUE constraint failed: kv.k"),
+        "{err}"
+    );
+    let err = conn
+        .execute("insert into kv values (null, 40, 'null')")
+        .unwrap_err();
+    assert!(err.to_string().contains("NOT NULL"), "{err}");
+    conn.execute("insert or ignore into kv values ('a', 40, 'dup')")?;
+    let rows: Vec<(i64,)> = conn.exec_rows("select count(*) from kv");
+    assert_eq!(rows, vec![(3,)]);
+
+    assert!(conn.execute("select rowid from kv").is_err());
+
+    Ok(())
+}
+
+#[turso_macros::test(
+    init_sql = "create table t (a integer, b integer, c text); create index t_a_b on t (a, b desc);"
+)]
+fn test_order_by_satisfied_by_desc_index(tmp_db: TempDatabase) -> anyhow::Result<()> {
+    let conn = tmp_db.connect_limbo();
+    conn.execute("insert into t values (1, 1, 'x'), (2, 1, 'y'), (1, 2, 'z'), (2, 3, 'w')")?;
+
+    let sorts = |query: &str| -> anyhow::Result<bool> {
+        let mut stmt = conn.prepare(format!("explain query plan {query}"))?;
+        let mut sorts = false;
+        stmt.run_with_row_callback(|row| {
+            sorts |= row.get::<String>(3)? == "USE TEMP B-TREE FOR ORDER BY";
+            Ok(())
+        })?;
+        Ok(sorts)
+    };
+
+    // Mixed directions are delivered by the index in either traversal direction.
+    let query = "select a, b from t order by a, b desc";
+    assert!(!sorts(query)?);
+    let rows: Vec<(i64, i64)> = conn.exec_rows(query);
+    assert_eq!(rows, vec![(1, 2), (1, 1), (2, 3), (2, 1)]);
+
+    let query = "select a, b from t order by a desc, b";
+    assert!(!sorts(query)?);
+    let rows: Vec<(i64, i64)> = conn.exec_rows(query);
+    assert_eq!(rows, vec![(2, 1), (2, 3), (1, 1), (1, 2)]);
+
+    // Columns fixed by an equality constraint do not take part in the ordering.
+    let query = "select b from t where a = 2 order by b";
+    assert!(!sorts(query)?);
+    let rows: Vec<(i64,)> = conn.exec_rows(query);
+    assert_eq!(rows, vec![(1,), (3,)]);
+
+    let query = "select a, b from t where a = 1 order by a desc, b desc";
+    assert!(!sorts(query)?);
+    let rows: Vec<(i64, i64)> = conn.exec_rows(query);
+    assert_eq!(rows, vec![(1, 2), (1, 1)]);
+
+    // Directions that disagree with the index still need a sort.
+    let query = "select a, b from t order by a, b";
+    assert!(sorts(query)?);
+    let rows: Vec<(i64, i64)> = conn.exec_rows(query);
+    assert_eq!(rows, vec![(1, 1), (1, 2), (2, 1), (2, 3)]);
+
+    Ok(())
+}
+
+#[turso_macros::test]
+fn test_parse_error_points_at_token(tmp_db: TempDatabase) -> anyhow::Result<()> {
+    let conn = tmp_db.connect_limbo();
+
+    let Err(LimboError::ParseError(msg)) = conn.execute("SELECT 1;\nCREATE TABLE (x)") else {
+        panic!("expected a parse error");
+    };
+    assert_eq!(
+        msg,
+        "unexpected token '(' at offset 23 (line 2, column 14)\nCREATE TABLE (x)\n             ^"
+    );
+    Ok(())
+}
+
+#[turso_macros::test(init_sql = "create table test (i integer);")]
+fn test_cte_materialization_hints(tmp_db: TempDatabase) -> anyhow::Result<()> {
+    let conn = tmp_db.connect_limbo();
+    conn.execute("insert into test select value from generate_series(1, 1000)")?;
+
+    // A self-joined CTE is run again for every row of the outer reference unless it's
+    // materialized, in which case the table is scanned once into an ephemeral table.
+    let rows_read = |hint: &str| -> anyhow::Result<u64> {
+        let query = format!(
+            "with c as {hint} (select i from test where i % 100 = 0)
+             select count(*) from c a join c b on a.i <= b.i"
+        );
+        let rows: Vec<(i64,)> = conn.exec_rows(&query);
+        assert_eq!(rows, vec![(55,)]);
+        Ok(conn
+            .metrics
+            .read()
+            .last_statement
+            .clone()
+            .unwrap()
+            .rows_read)
+    };
+    let not_materialized = rows_read("not materialized")?;
+    let materialized = rows_read("materialized")?;
+    // Without a hint, a CTE referenced more than once is materialized.
+    let default = rows_read("")?;
+
+    assert!(not_materialized > 10_000, "{not_materialized}");
+    assert!(materialized < 2_000, "{materialized}");
+    assert_eq!(default, materialized);
+
+    Ok(())
+}
+
+#[turso_macros::test(init_sql = "create table test (i integer, x integer);")]
+fn test_min_max_reads_index_endpoint(tmp_db: TempDatabase) -> anyhow::Result<()> {
+    let conn = tmp_db.connect_limbo();
+    conn.execute("create index test_x on test(x)")?;
+    conn.execute(
+        "insert into test select value, case when value % 10 = 0 then null else value end
+         from generate_series(1, 1000)",
+    )?;
+
+    let rows_read = |query: &str, expected: i64| -> anyhow::Result<u64> {
+        let rows: Vec<(i64,)> = conn.exec_rows(query);
+        assert_eq!(rows, vec![(expected,)]);
+        Ok(conn
+            .metrics
+            .read()
+            .last_statement
+            .clone()
+            .unwrap()
+            .rows_read)
+    };
+    // MAX reads the last index entry, and MIN steps over the 100 NULLs, which sort first.
+    assert_eq!(rows_read("select max(x) from test", 999)?, 1);
+    assert_eq!(rows_read("select min(x) from test", 1)?, 101);
+    // Without an index, the whole table is scanned.
+    assert_eq!(rows_read("select max(i) from test", 1000)?, 1000);
+
+    Ok(())
+}
+
+#[turso_macros::test(
+    init_sql = "create table events (id integer primary key, created integer, body text); create index events_created on events(created);"
+)]
+fn test_row_value_keyset_pagination(tmp_db: TempDatabase) -> anyhow::Result<()> {
+    let conn = tmp_db.connect_limbo();
+    for i in 1..=20 {
+        conn.execute(format!(
+            "insert into events values ({i}, {}, 'e{i}')",
+            (i * 7) % 5
+        ))?;
+    }
+
+    // The row value covers the indexed column and the rowid at the end of each index entry,
+    // so the seek starts right after the last row of the previous page.
+    let query = |created: i64, id: i64| {
+        format!(
+            "select id, created from events where (created, id) > ({created}, {id}) order by created, id limit 3"
+        )
+    };
+    let mut stmt = conn.prepare(format!("explain {}", query(0, 0)))?;
+    let mut seeks = Vec::new();
+    stmt.run_with_row_callback(|row| {
+        if row.get::<String>(1)? == "SeekGT" {
+            seeks.push(row.get::<String>(7)?);
+        }
+        Ok(())
+    })?;
+    let [seek] = seeks.as_slice() else {
+        panic!("expected a single SeekGT, got {seeks:?}");
+    };
+    let (first, last) = seek
+        .trim_start_matches("key=[")
+        .trim_end_matches(']')
+        .split_once("..")
+        .unwrap();
+    assert_eq!(last.parse::<usize>()? - first.parse::<usize>()?, 1);
+
+    let mut pages = Vec::new();
+    let mut after = (-1, 0);
+    loop {
+        let page: Vec<(i64, i64)> = conn.exec_rows(&query(after.0, after.1));
+        let Some(&(id, created)) = page.last() else {
+            break;
+        };
+        after = (created, id);
+        pages.extend(page);
+    }
+    let expected: Vec<(i64, i64)> =
+        conn.exec_rows("select id, created from events order by created, id");
+    assert_eq!(pages, expected);
+
+    Ok(())
+}
+
+#[turso_macros::test(init_sql = "create table test (a integer, b text);")]
+fn test_where_repeated_subexpression_evaluated_once(tmp_db: TempDatabase) -> anyhow::Result<()> {
+    let conn = tmp_db.connect_limbo();
+    conn.execute("insert into test values (1, 'x'), (2, 'yy'), (3, 'zzz')")?;
+
+    let count_functions = |query: &str| -> anyhow::Result<usize> {
+        let mut stmt = conn.prepare(format!("explain {query}"))?;
+        let mut functions = 0;
+        stmt.run_with_row_callback(|row| {
+            if row.get::<String>(1)? == "Function" {
+                functions += 1;
+            }
+            Ok(())
+        })?;
+        Ok(functions)
+    };
+    // Both terms run on every row, so length(b) is computed once and copied.
+    let query = "select a from test where length(b) > 1 and length(b) < 3";
+    assert_eq!(count_functions(query)?, 1);
+    let rows: Vec<(i64,)> = conn.exec_rows(query);
+    assert_eq!(rows, vec![(2,)]);
+    // The right operand of OR only runs for some rows, so the later term computes its own.
+    let query = "select a from test where (a > 2 or length(b) > 1) and length(b) < 3";
+    assert_eq!(count_functions(query)?, 2);
+    let rows: Vec<(i64,)> = conn.exec_rows(query);
+    assert_eq!(rows, vec![(2,)]);
+
+    Ok(())
+}
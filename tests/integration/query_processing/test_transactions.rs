@@ -178,7 +178,7 @@ fn test_constraint_error_aborts_only_stmt_not_entire_transaction(tmp_db: TempDat
 
     // Second insert fails due to UNIQUE constraint
     let result = conn.execute("INSERT INTO t VALUES (2),(3)");
-    assert!(matches!(result, Err(LimboError::Constraint(_))));
+    assert!(matches!(result, Err(LimboError::Constraint { .. })));
 
     // Third insert is valid again
     conn.execute("INSERT INTO t VALUES (4)").unwrap();
@@ -218,7 +218,7 @@ fn test_deferred_fk_violation_rollback_in_autocommit(tmp_db: TempDatabase) {
     // This insert should fail because parent(1) doesn't exist
     // and the deferred FK violation should be caught at statement end in autocommit mode
     let result = conn.execute("INSERT INTO child VALUES(1,1)");
-    assert!(matches!(result, Err(LimboError::Constraint(_))));
+    assert!(matches!(result, Err(LimboError::Constraint { .. })));
 
     // Do a truncating checkpoint
     conn.execute("PRAGMA wal_checkpoint(TRUNCATE)").unwrap();
@@ -1131,7 +1131,7 @@ pub fn test_conflict_autocommit(limbo: TempDatabase) {
     conn1.execute("INSERT INTO t VALUES (1, 10)").unwrap();
     assert!(matches!(
         conn1.execute("INSERT INTO t VALUES (1, 0)").unwrap_err(),
-        LimboError::Constraint(_)
+        LimboError::Constraint { .. }
     ));
     conn2.execute("INSERT INTO t VALUES (2, 20)").unwrap();
     assert_eq!(
@@ -1175,7 +1175,7 @@ pub fn test_conflict_multi_insert_autocommit(limbo: TempDatabase) {
         conn1
             .execute("INSERT INTO t VALUES (2, 20), (1, 0), (3, 30)")
             .unwrap_err(),
-        LimboError::Constraint(_)
+        LimboError::Constraint { .. }
     ));
     conn2.execute("INSERT INTO t VALUES (4, 40)").unwrap();
     assert_eq!(
@@ -1219,7 +1219,7 @@ pub fn test_conflict_inside_txn(limbo: TempDatabase) {
     conn1.execute("INSERT INTO t VALUES (2, 20)").unwrap();
     assert!(matches!(
         conn1.execute("INSERT INTO t VALUES (1, 0)").unwrap_err(),
-        LimboError::Constraint(_)
+        LimboError::Constraint { .. }
     ));
     conn1.execute("INSERT INTO t VALUES (3, 30)").unwrap();
     conn1.execute("COMMIT").unwrap();
@@ -1267,3 +1267,140 @@ pub fn test_conflict_inside_txn(limbo: TempDatabase) {
         limbo_exec_rows(&conn2, "SELECT * FROM t")
     );
 }
+
+#[turso_macros::test(init_sql = "CREATE TABLE test (x INTEGER PRIMARY KEY, b BLOB);")]
+/// Inserts blobs around the sizes where a 4096 byte page starts spilling the payload to
+/// overflow pages, or needs one more overflow page, then checks that the values read back
+/// intact and that the overflow chains are reused after deletion.
+fn test_overflow_payload_boundaries(tmp_db: TempDatabase) -> anyhow::Result<()> {
+    let _ = env_logger::try_init();
+    let conn = tmp_db.connect_limbo();
+
+    // The record of a row is a 4 byte header (header size, NULL rowid alias, 2 byte blob
+    // serial type) followed by the blob, up to 4061 bytes fit in a table leaf cell and
+    // each overflow page holds 4092 bytes.
+    let max_local = 4096 - 35 - 4;
+    let min_local = (4096 - 12) * 32 / 255 - 23 - 4;
+    let overflow_data = 4096 - 4;
+    let mut sizes = vec![0, 1, 56, 57, 1000];
+    for size in [
+        max_local,
+        min_local + overflow_data,
+        min_local + 2 * overflow_data,
+        3 * overflow_data,
+    ] {
+        sizes.extend([size - 1, size, size + 1]);
+    }
+    sizes.extend([100_000, 1_000_000]);
+    let blob = |id: usize, size: usize| (0..size).map(|i| (i * 31 + id) as u8).collect::<Vec<_>>();
+
+    let insert_all = || -> anyhow::Result<()> {
+        let mut stmt = conn.prepare("INSERT INTO test VALUES (?, ?)")?;
+        for (id, size) in sizes.iter().enumerate() {
+            stmt.reset();
+            stmt.bind_at(1.try_into()?, Value::Integer(id as i64));
+            stmt.bind_at(2.try_into()?, Value::Blob(blob(id, *size)));
+            stmt.run_ignore_rows()?;
+        }
+        Ok(())
+    };
+    insert_all()?;
+
+    let rows = limbo_exec_rows(&conn, "SELECT x, b FROM test ORDER BY x");
+    assert_eq!(rows.len(), sizes.len());
+    for (id, size) in sizes.iter().enumerate() {
+        assert_eq!(
+            rows[id],
+            vec![
+                rusqlite::types::Value::Integer(id as i64),
+                rusqlite::types::Value::Blob(blob(id, *size)),
+            ],
+            "blob of {size} bytes was not read back intact"
+        );
+    }
+
+    let page_count = |conn: &Arc<Connection>| -> i64 {
+        let rows: Vec<(i64,)> = conn.exec_rows("PRAGMA page_count");
+        rows[0].0
+    };
+    let pages_before = page_count(&conn);
+    conn.execute("DELETE FROM test")?;
+    let freelist: Vec<(i64,)> = conn.exec_rows("PRAGMA freelist_count");
+    assert!(
+        freelist[0].0 > 0,
+        "overflow pages should be freed on delete"
+    );
+    // The freed pages are reused, so the database doesn't grow.
+    insert_all()?;
+    assert_eq!(page_count(&conn), pages_before);
+
+    conn.execute("PRAGMA wal_checkpoint(TRUNCATE)")?;
+    rusqlite_integrity_check(tmp_db.path.as_path())?;
+    Ok(())
+}
+
+#[turso_macros::test(init_sql = "CREATE TABLE test (x INTEGER PRIMARY KEY, y TEXT);")]
+fn test_bulk_insert(tmp_db: TempDatabase) -> anyhow::Result<()> {
+    let conn = tmp_db.connect_limbo();
+
+    let rows = (1..=1000).map(|i| [Value::Integer(i), Value::build_text(format!("row {i}"))]);
+    assert_eq!(conn.bulk_insert("test", rows)?, 1000);
+    assert!(conn.get_auto_commit());
+    let count: Vec<(i64,)> = conn.exec_rows("SELECT count(*) FROM test");
+    assert_eq!(count[0].0, 1000);
+
+    // A failing row rolls back the whole batch.
+    let rows = [
+        vec![Value::Integer(1001), Value::Null],
+        vec![Value::Integer(1), Value::Null],
+    ];
+    assert!(conn.bulk_insert("test", rows).is_err());
+    assert!(conn.get_auto_commit());
+    let count: Vec<(i64,)> = conn.exec_rows("SELECT count(*) FROM test");
+    assert_eq!(count[0].0, 1000);
+
+    // Rows with a different number of values are rejected.
+    let rows = [
+        vec![Value::Integer(1003), Value::Null],
+        vec![Value::Integer(1004)],
+    ];
+    assert!(matches!(
+        conn.bulk_insert("test", rows),
+        Err(LimboError::InvalidArgument(_))
+    ));
+
+    conn.execute("PRAGMA wal_checkpoint(TRUNCATE)")?;
+    rusqlite_integrity_check(tmp_db.path.as_path())?;
+    Ok(())
+}
+
+#[turso_macros::test(
+    init_sql = "CREATE TABLE t (id INTEGER PRIMARY KEY, email TEXT UNIQUE, name TEXT NOT NULL);"
+)]
+fn test_constraint_error_result_codes(tmp_db: TempDatabase) -> anyhow::Result<()> {
+    let conn = tmp_db.connect_limbo();
+    conn.execute("INSERT INTO t VALUES (1, 'a@example.org', 'a')")?;
+
+    let extended_code = |sql: &str| {
+        let err = conn.execute(sql).unwrap_err();
+        assert!(matches!(err, LimboError::Constraint { .. }), "{err}");
+        assert_eq!(err.result_code(), 19); // SQLITE_CONSTRAINT
+        err.extended_result_code()
+    };
+    // SQLITE_CONSTRAINT_PRIMARYKEY
+    assert_eq!(
+        extended_code("INSERT INTO t VALUES (1, 'b@example.org', 'b')"),
+        1555
+    );
+    // SQLITE_CONSTRAINT_UNIQUE
+    assert_eq!(
+        extended_code("INSERT INTO t VALUES (2, 'a@example.org', 'b')"),
+        2067
+    );
+    // SQLITE_CONSTRAINT_NOTNULL
+    assert_eq!(
+        extended_code("INSERT INTO t VALUES (2, 'b@example.org', NULL)"),
+        1299
+    );
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
+use turso_core::{Database, DatabaseOpts, LimboError, OpenFlags};
+
+use crate::common::ExecRows;
+
+/// Create a database with pointer map pages using rusqlite
+fn create_autovacuum_db(db_path: &Path) {
+    let conn = rusqlite::Connection::open(db_path).unwrap();
+    conn.pragma_update(None, "auto_vacuum", "full").unwrap();
+    conn.pragma_update(None, "journal_mode", "wal").unwrap();
+    conn.execute_batch(
+        "CREATE TABLE t (id INTEGER PRIMARY KEY, val BLOB);
+         WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 2000)
+         INSERT INTO t SELECT i, zeroblob(1000) FROM n;",
+    )
+    .unwrap();
+    conn.pragma_update(None, "wal_checkpoint", "TRUNCATE")
+        .unwrap();
+}
+
+fn open_with_limbo(db_path: &Path, flags: OpenFlags) -> turso_core::Result<Arc<Database>> {
+    let io = Arc::new(turso_core::PlatformIO::new().unwrap());
+    Database::open_file_with_flags(
+        io,
+        db_path.to_str().unwrap(),
+        flags,
+        DatabaseOpts::new(),
+        None,
+    )
+}
+
+#[test]
+fn test_autovacuum_db_without_flag_is_readonly() {
+    let tmp_dir = TempDir::new().unwrap();
+    let db_path = tmp_dir.path().join("test.db");
+    create_autovacuum_db(&db_path);
+
+    // The database opens and reads, but writes would leave the pointer map stale.
+    let db = open_with_limbo(&db_path, OpenFlags::default()).unwrap();
+    let conn = db.connect().unwrap();
+    let rows: Vec<(i64, i64)> = conn.exec_rows("SELECT count(*), sum(length(val)) FROM t");
+    assert_eq!(rows, vec![(2000, 2000 * 1000)]);
+    match conn.execute("INSERT INTO t VALUES (2001, zeroblob(1000))") {
+        Err(LimboError::InvalidArgument(message)) => assert!(
+            message.contains("auto_vacuum"),
+            "unexpected error message: {message}"
+        ),
+        other => panic!("expected an error about auto_vacuum, got {other:?}"),
+    }
+    // A refused write leaves the connection usable.
+    let rows: Vec<(i64,)> = conn.exec_rows("SELECT count(*) FROM t");
+    assert_eq!(rows, vec![(2000,)]);
+    drop(conn);
+    drop(db);
+
+    let db = open_with_limbo(&db_path, OpenFlags::ReadOnly).unwrap();
+    assert!(db.is_readonly());
+    let conn = db.connect().unwrap();
+
+    // Pointer map pages are skipped when reading the table.
+    let rows: Vec<(i64, i64)> = conn.exec_rows("SELECT count(*), sum(length(val)) FROM t");
+    assert_eq!(rows, vec![(2000, 2000 * 1000)]);
+
+    let result = conn.execute("INSERT INTO t VALUES (2001, zeroblob(1000))");
+    assert!(
+        matches!(result, Err(LimboError::ReadOnly)),
+        "expected readonly error, got {result:?}"
+    );
+    drop(conn);
+    drop(db);
+
+    crate::common::rusqlite_integrity_check(&db_path).unwrap();
+}
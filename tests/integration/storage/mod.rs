@@ -1,3 +1,5 @@
+mod autovacuum;
 #[cfg(feature = "checksum")]
 mod checksum;
 mod header_version;
+mod reserved_space;
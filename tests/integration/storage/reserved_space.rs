@@ -0,0 +1,45 @@
+use crate::common::{rusqlite_integrity_check, ExecRows, TempDatabase};
+
+/// Read the reserved space byte (offset 20) from the database file header
+fn read_reserved_space(db_path: &std::path::Path) -> u8 {
+    let bytes = std::fs::read(db_path).expect("Failed to read database file");
+    assert!(bytes.len() >= 21, "Database file too small");
+    bytes[20]
+}
+
+#[test]
+fn test_create_database_with_reserved_space() {
+    let db = TempDatabase::new_empty();
+    let conn = db.connect_limbo();
+    conn.set_reserved_bytes(32).unwrap();
+    conn.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, val BLOB)")
+        .unwrap();
+    // Mix small rows with rows spilling to overflow pages, which also use the usable size.
+    conn.execute(
+        "INSERT INTO t SELECT value, randomblob(CASE WHEN value % 10 = 0 THEN 10000 ELSE 100 END) FROM generate_series(1, 1000)",
+    )
+    .unwrap();
+    conn.execute("PRAGMA wal_checkpoint(TRUNCATE)").unwrap();
+
+    assert_eq!(read_reserved_space(&db.path), 32);
+    rusqlite_integrity_check(&db.path).unwrap();
+
+    // The reserved space of an existing database can't be changed.
+    assert!(matches!(
+        conn.set_reserved_bytes(0),
+        Err(turso_core::LimboError::InvalidArgument(_))
+    ));
+    conn.set_reserved_bytes(32).unwrap();
+    conn.execute("INSERT INTO t VALUES (1001, randomblob(10000))")
+        .unwrap();
+    conn.execute("PRAGMA wal_checkpoint(TRUNCATE)").unwrap();
+    drop(conn);
+    assert_eq!(read_reserved_space(&db.path), 32);
+    rusqlite_integrity_check(&db.path).unwrap();
+
+    // The reserved space is read back from the header when reopening.
+    let db = TempDatabase::new_with_existent(&db.path);
+    let conn = db.connect_limbo();
+    let rows: Vec<(i64, i64)> = conn.exec_rows("SELECT count(*), sum(length(val)) FROM t");
+    assert_eq!(rows, vec![(1001, 100 * 10000 + 900 * 100 + 10000)]);
+}